@@ -0,0 +1,60 @@
+//! Inputs shared by the benchmark suites.
+//!
+//! Small and medium songs are the bundled samples; larger inputs are generated
+//! so the suites stay self-contained and their size is easy to dial.
+
+use lyrics_dsl::selftest::SAMPLES;
+use std::fmt::Write;
+
+/// Named benchmark inputs, from typical songs to pathological ones.
+pub fn inputs() -> Vec<(&'static str, String)> {
+    vec![
+        ("small", SAMPLES[0].source.to_string()),
+        ("medium", SAMPLES[1].source.to_string()),
+        ("huge", generated_song(200, 8, 40)),
+        ("many_sections", generated_song(5_000, 1, 20)),
+        ("long_lines", generated_song(4, 4, 20_000)),
+    ]
+}
+
+/// A syntactically valid song with `sections` alternating verses and
+/// choruses, each holding `lines` annotated lines of roughly `width` chars.
+pub fn generated_song(sections: usize, lines: usize, width: usize) -> String {
+    let mut out = String::from("title: \"Benchmark\"\nartist: \"Criterion\"\ntempo: 120\n\n");
+    let words = [
+        "never", "gonna", "syntax", "tree", "melody", "falling", "night", "light",
+    ];
+    let mut ms = 0u64;
+
+    for s in 0..sections {
+        if s % 2 == 0 {
+            let _ = writeln!(out, "VERSE[{}]", s / 2 + 1);
+        } else {
+            out.push_str("CHORUS\n");
+        }
+        for l in 0..lines {
+            let mut text = String::new();
+            let mut i = s + l;
+            while text.len() < width {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(words[i % words.len()]);
+                i += 3;
+            }
+            ms += 2_500;
+            let rhyme = (b'A' + (l % 4) as u8) as char;
+            let _ = writeln!(
+                out,
+                "{} {{rhyme: {}, chord: C, G, timing: {}:{:02}.{:02}}}",
+                text,
+                rhyme,
+                ms / 60_000,
+                (ms / 1000) % 60,
+                (ms % 1000) / 10
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
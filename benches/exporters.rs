@@ -0,0 +1,22 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lyrics_dsl::export::exporters;
+use lyrics_dsl::parser::parse_song;
+
+mod common;
+
+fn bench_exporters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("export");
+    for (name, input) in common::inputs() {
+        let song = parse_song(&input).expect("benchmark input parses");
+        group.throughput(Throughput::Elements(song.lines().count() as u64));
+        for exporter in exporters() {
+            group.bench_with_input(BenchmarkId::new(exporter.name(), name), &song, |b, song| {
+                b.iter(|| exporter.export(black_box(song)).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_exporters);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lyrics_dsl::parser::{parse_lyrics, parse_song};
+
+mod common;
+
+fn bench_parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, input) in common::inputs() {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("validate", name), &input, |b, input| {
+            b.iter(|| parse_lyrics(black_box(input)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("ast", name), &input, |b, input| {
+            b.iter(|| parse_song(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parser);
+criterion_main!(benches);
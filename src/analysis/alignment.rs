@@ -0,0 +1,182 @@
+//! Checks that a song's line timings fit its recording.
+//!
+//! Timings are typed by hand, and a slip of a minute (`1:05.00` for
+//! `0:05.00`) still parses. These checks catch such slips: a line timed past
+//! the end of the track, a line timed before the one above it, and gaps
+//! between lines that are too short to sing the earlier line or far longer
+//! than the song's usual spacing. The track length comes from the
+//! `duration` metadata, or from the recording itself.
+
+use super::language;
+use super::syllables::count_line_in;
+use crate::ast::{Line, Song, Timestamp};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+
+/// Fewest seconds a syllable takes to sing, even in fast passages.
+const SYLLABLE: f64 = 0.08;
+/// Gaps up to this many seconds are never suspicious: instrumental breaks
+/// are often this long.
+const LONG_GAP: f64 = 30.0;
+/// Longer gaps are suspicious once this many times the song's median gap.
+const GAP_FACTOR: f64 = 4.0;
+
+/// Seconds in a `duration` such as `3:45`, `1:02:30`, `225` or `225s`.
+pub fn parse_duration(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let seconds = |s: &str| s.parse::<f64>().ok().filter(|n| n.is_finite() && *n >= 0.0);
+    if let Some(plain) = text
+        .strip_suffix('s')
+        .or(Some(text))
+        .filter(|t| !t.contains(':'))
+    {
+        return seconds(plain);
+    }
+    let mut total = 0.0;
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        let value = match last {
+            true => seconds(part)?,
+            false => part.parse::<u32>().ok()? as f64,
+        };
+        if i > 0 && value >= 60.0 {
+            return None;
+        }
+        total = total * 60.0 + value;
+    }
+    Some(total)
+}
+
+/// Lines with a timing, in the order they are sung. `REPEAT` sections are
+/// left out, as they replay timings already checked.
+fn timed(song: &Song) -> Vec<(&Line, Timestamp)> {
+    song.sections
+        .iter()
+        .filter(|s| s.repeat.is_none())
+        .flat_map(|s| &s.lines)
+        .filter_map(|line| line.timing.map(|at| (line, at)))
+        .collect()
+}
+
+fn secs(seconds: f64) -> Timestamp {
+    Timestamp::from_millis((seconds.max(0.0) * 1000.0).round() as u64)
+}
+
+/// `at` a minute earlier or later, if that falls between `after` and
+/// `before`: the timing likely meant.
+fn minute_off(
+    at: Timestamp,
+    after: Option<Timestamp>,
+    before: Option<Timestamp>,
+) -> Option<Timestamp> {
+    [at.millis.checked_sub(60_000), at.millis.checked_add(60_000)]
+        .into_iter()
+        .flatten()
+        .map(Timestamp::from_millis)
+        .find(|t| after.is_none_or(|a| *t > a) && before.is_none_or(|b| *t < b))
+}
+
+/// Lines timed past `length` seconds, the end of the track; `track` says
+/// where the length came from, e.g. "the `duration`".
+pub fn check_length(song: &Song, length: f64, track: &str) -> Vec<Diagnostic> {
+    let end = secs(length);
+    timed(song)
+        .into_iter()
+        .filter(|(_, at)| *at > end)
+        .map(|(line, at)| {
+            Diagnostic::error(
+                format!(
+                    "timing {} is past the end of the track at {}, from {}",
+                    at, end, track
+                ),
+                line.span,
+            )
+            .with_code(&codes::TIMING_PAST_END)
+        })
+        .collect()
+}
+
+/// Check the timings of the unresolved `song` against each other and
+/// against its declared `duration`.
+pub fn check(song: &Song) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if let Some(entry) = song.metadata.iter().find(|e| e.key == "duration") {
+        let text = entry.value.to_string();
+        match parse_duration(&text) {
+            Some(length) => diagnostics.extend(check_length(song, length, "the `duration`")),
+            None => diagnostics.push(
+                Diagnostic::warning(
+                    format!("`duration` is `{}`, not a length such as `3:45`", text),
+                    entry.span,
+                )
+                .with_code(&codes::INVALID_DURATION),
+            ),
+        }
+    }
+
+    let language = language::of(song);
+    let lines = timed(song);
+    let mut gaps: Vec<f64> = lines
+        .windows(2)
+        .map(|pair| pair[1].1.as_secs_f64() - pair[0].1.as_secs_f64())
+        .filter(|gap| *gap > 0.0)
+        .collect();
+    gaps.sort_by(f64::total_cmp);
+    let median = gaps
+        .get(gaps.len().saturating_sub(1) / 2)
+        .copied()
+        .unwrap_or_default();
+    let long = LONG_GAP.max(median * GAP_FACTOR);
+
+    for (i, pair) in lines.windows(2).enumerate() {
+        let ((previous, from), (line, at)) = (pair[0], pair[1]);
+        let prior = i.checked_sub(1).map(|j| lines[j].1);
+        let next = lines.get(i + 2).map(|(_, t)| *t);
+        let gap = at.as_secs_f64() - from.as_secs_f64();
+        let syllables = count_line_in(previous, language);
+        let mut diagnostic = if gap < 0.0 {
+            Diagnostic::warning(
+                format!("timing {} is earlier than the line before, at {}", at, from),
+                line.span,
+            )
+            .with_code(&codes::TIMING_ORDER)
+        } else if gap < syllables as f64 * SYLLABLE {
+            Diagnostic::warning(
+                format!(
+                    "timing {} leaves {:.2}s to sing the {} syllable(s) of the line before",
+                    at, gap, syllables
+                ),
+                line.span,
+            )
+            .with_code(&codes::TIMING_GAP)
+        } else if gap > long {
+            Diagnostic::warning(
+                format!(
+                    "timing {} comes {:.0}s after the line before, where lines are usually {:.1}s apart",
+                    at, gap, median
+                ),
+                line.span,
+            )
+            .with_code(&codes::TIMING_GAP)
+        } else {
+            continue;
+        };
+        diagnostic = diagnostic.with_label(previous.span, format!("timed {}", from));
+        // Going backwards, the slip may be in either line.
+        let slipped_before = (gap < 0.0)
+            .then(|| minute_off(from, prior, Some(at)))
+            .flatten();
+        if let Some(meant) = slipped_before {
+            diagnostic =
+                diagnostic.with_help(format!("is the line before at {}, a minute off?", meant));
+        } else if let Some(meant) = minute_off(at, Some(from), next) {
+            diagnostic = diagnostic.with_help(format!("is it {}, a minute off?", meant));
+        }
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
@@ -0,0 +1,133 @@
+//! Recurring lines written in slightly different ways: a chorus typed out
+//! twice with `can't` in one and `cannot` in the other, or a dropped word
+//! in its third time round.
+//!
+//! Two lines are variants of one phrase when their words match once case,
+//! punctuation and common contractions are set aside, or, for lines of at
+//! least [`MIN_WORDS`] words, when what is left differs by at most one
+//! character in [`CHARS_PER_EDIT`]. Lines of `REPEAT`s and `USE`s are the
+//! lines they copy, so only lines written out are compared.
+
+use crate::ast::{words, Line, Song};
+use crate::suggest::levenshtein;
+
+/// Lines with fewer words only match when their words are the same.
+pub const MIN_WORDS: usize = 4;
+/// One edit is allowed for each this many characters of a normalized line.
+pub const CHARS_PER_EDIT: usize = 12;
+
+/// Spelled-out forms of contractions and casual spellings, so `can't` and
+/// `cannot` or `gonna` and `going to` compare equal.
+const EXPANSIONS: &[(&str, &str)] = &[
+    ("can't", "cannot"),
+    ("won't", "will not"),
+    ("ain't", "is not"),
+    ("gonna", "going to"),
+    ("wanna", "want to"),
+    ("gotta", "got to"),
+    // Leading apostrophes are not part of a word: `'cause` is `cause`.
+    ("cause", "because"),
+    ("cuz", "because"),
+    ("til", "until"),
+    ("till", "until"),
+    ("em", "them"),
+    ("ok", "okay"),
+];
+const SUFFIXES: &[(&str, &str)] = &[
+    ("n't", " not"),
+    ("'re", " are"),
+    ("'ll", " will"),
+    ("'ve", " have"),
+    ("'m", " am"),
+];
+
+/// One way a recurring line is written, with the lines written that way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant<'a> {
+    pub text: &'a str,
+    pub lines: Vec<&'a Line>,
+}
+
+/// A line written in more than one way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phrase<'a> {
+    /// The variants, the most written first and otherwise in the order they
+    /// first appear.
+    pub variants: Vec<Variant<'a>>,
+}
+
+/// Every line of the parsed `song` that recurs with differences.
+pub fn inconsistencies(song: &Song) -> Vec<Phrase<'_>> {
+    let lines = song
+        .sections
+        .iter()
+        .filter(|s| s.repeat.is_none())
+        .flat_map(|s| &s.lines)
+        .filter(|l| l.include.is_none() && !l.text.trim().is_empty());
+    // Each group is compared by its first line.
+    let mut groups: Vec<(String, Vec<&Line>)> = Vec::new();
+    for line in lines {
+        let normal = normalized(&line.text);
+        match groups.iter_mut().find(|(first, _)| alike(first, &normal)) {
+            Some((_, group)) => group.push(line),
+            None => groups.push((normal, vec![line])),
+        }
+    }
+
+    let mut phrases = Vec::new();
+    for (_, group) in groups {
+        let mut variants: Vec<Variant> = Vec::new();
+        for line in group {
+            match variants.iter_mut().find(|v| v.text == line.text) {
+                Some(variant) => variant.lines.push(line),
+                None => variants.push(Variant {
+                    text: &line.text,
+                    lines: vec![line],
+                }),
+            }
+        }
+        if variants.len() > 1 {
+            // A stable sort keeps ties in the order they first appear.
+            variants.sort_by_key(|v| std::cmp::Reverse(v.lines.len()));
+            phrases.push(Phrase { variants });
+        }
+    }
+    phrases
+}
+
+/// The words of `text` in lower case with contractions spelled out, joined
+/// by single spaces.
+pub fn normalized(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for word in words(text) {
+        let word = word.to_lowercase().replace('’', "'");
+        let expanded = EXPANSIONS
+            .iter()
+            .find(|(from, _)| *from == word)
+            .map(|(_, to)| to.to_string())
+            .or_else(|| {
+                SUFFIXES.iter().find_map(|(from, to)| {
+                    let stem = word.strip_suffix(from).filter(|s| !s.is_empty())?;
+                    Some(format!("{}{}", stem, to))
+                })
+            })
+            .unwrap_or(word);
+        out.extend(expanded.split(' ').map(str::to_string));
+    }
+    format!(" {} ", out.join(" "))
+        .replace(" can not ", " cannot ")
+        .trim()
+        .to_string()
+}
+
+fn alike(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    let count = |s: &str| s.split(' ').count();
+    if count(a) < MIN_WORDS || count(b) < MIN_WORDS {
+        return false;
+    }
+    let length = a.chars().count().max(b.chars().count());
+    levenshtein(a, b) <= length / CHARS_PER_EDIT
+}
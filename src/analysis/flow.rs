@@ -0,0 +1,125 @@
+//! Flow: how many syllables fall on each beat of a line divided by `·` beat
+//! and `|` bar markers, and how fast they come at the tempo the line is
+//! sung at, so a rap verse's density can be checked against the BPM.
+//!
+//! Each marker starts a beat that runs to the next one. Words before the
+//! first marker are a pickup, shown but left out of the averages, and a
+//! marker after the last word only closes the line. Only lines with at
+//! least one `·` are read this way; bar markers alone are left to
+//! [`super::meter`].
+
+use super::language;
+use super::syllables::word_syllables_in;
+use super::tempo::TempoMap;
+use crate::ast::{word_ranges, Line, Song, Timestamp};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Beat {
+    pub words: String,
+    pub syllables: usize,
+    /// Set when a `|` starts the beat, as the first of its bar.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub downbeat: bool,
+    /// Set for the words sung before the first marker.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pickup: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineFlow {
+    pub section: String,
+    pub text: String,
+    /// The tempo at the line's timing, or at the last timed line before it.
+    pub bpm: f64,
+    pub beats: Vec<Beat>,
+    /// Mean syllables on each marked beat.
+    pub syllables_per_beat: f64,
+    pub syllables_per_second: f64,
+}
+
+impl LineFlow {
+    /// The note value the syllables move in on average, e.g. `sixteenths`.
+    pub fn subdivision(&self) -> Option<&'static str> {
+        match self.syllables_per_beat.round() as u32 {
+            1 => Some("quarters"),
+            2 => Some("eighths"),
+            3 => Some("triplets"),
+            4 => Some("sixteenths"),
+            6 => Some("sextuplets"),
+            8 => Some("thirty-seconds"),
+            _ => None,
+        }
+    }
+}
+
+/// The flow of every line of the resolved `song` that has `·` markers.
+pub fn flow(song: &Song) -> Vec<LineFlow> {
+    let tempo = TempoMap::from_song(song);
+    let language = language::of(song);
+    let mut at = Timestamp::default();
+    let mut flows = Vec::new();
+    for (section, line) in song.lines() {
+        at = line.timing.unwrap_or(at);
+        if line.beats.is_empty() {
+            continue;
+        }
+        let beats = beats(line, &word_syllables_in(line, language));
+        let marked: Vec<&Beat> = beats.iter().filter(|b| !b.pickup).collect();
+        let syllables: usize = marked.iter().map(|b| b.syllables).sum();
+        let per_beat = syllables as f64 / marked.len().max(1) as f64;
+        let bpm = tempo.bpm_at(at);
+        flows.push(LineFlow {
+            section: section.label(),
+            text: line.text.clone(),
+            bpm,
+            syllables_per_beat: (per_beat * 100.0).round() / 100.0,
+            syllables_per_second: (per_beat * bpm / 60.0 * 100.0).round() / 100.0,
+            beats,
+        });
+    }
+    flows
+}
+
+/// `line` cut at its markers, with the syllables of each of its words.
+/// Markers with no words between them leave an empty beat, a rest.
+fn beats(line: &Line, syllables: &[usize]) -> Vec<Beat> {
+    let ranges = word_ranges(&line.text);
+    let count = ranges.len();
+    // (word, whether a bar starts there), a bar before a beat on one word.
+    let mut marks: Vec<(usize, bool)> = line
+        .bars
+        .iter()
+        .map(|&m| (m.min(count), true))
+        .chain(line.beats.iter().map(|&m| (m.min(count), false)))
+        .collect();
+    marks.sort_by_key(|&(word, bar)| (word, !bar));
+
+    let beat = |from: usize, to: usize| Beat {
+        words: match from < to {
+            true => line.text[ranges[from].start..ranges[to - 1].end].to_string(),
+            false => String::new(),
+        },
+        syllables: syllables.get(from..to).map_or(0, |s| s.iter().sum()),
+        downbeat: false,
+        pickup: false,
+    };
+    let mut beats = Vec::new();
+    if let Some(&(first, _)) = marks.first().filter(|(first, _)| *first > 0) {
+        beats.push(Beat {
+            pickup: true,
+            ..beat(0, first)
+        });
+    }
+    for (i, &(from, bar)) in marks.iter().enumerate() {
+        if from == count {
+            break;
+        }
+        let to = marks.get(i + 1).map_or(count, |&(to, _)| to);
+        beats.push(Beat {
+            downbeat: bar,
+            ..beat(from, to)
+        });
+    }
+    beats
+}
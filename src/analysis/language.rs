@@ -0,0 +1,384 @@
+//! Spelling rules of the languages songs are written in.
+//!
+//! A song names its language with `lang:` metadata, as a code such as `es`
+//! or `pt-BR` or a name such as `Spanish`. English, Spanish,
+//! Portuguese and German are built in, and songs without the entry, or in
+//! a language nobody registered, are counted as English. Other languages
+//! implement [`Language`] and are added with [`register`].
+
+use super::syllables::count_word;
+use crate::ast::Song;
+use std::sync::RwLock;
+
+/// Syllable and rhyme rules for the words of one language.
+pub trait Language: Send + Sync {
+    /// ISO 639-1 code, e.g. `es`.
+    fn code(&self) -> &str;
+
+    /// English name, e.g. `Spanish`.
+    fn name(&self) -> &str;
+
+    /// Estimate the syllables of a written word.
+    fn syllables(&self, word: &str) -> usize;
+
+    /// The part of a word that must match for another word to rhyme with
+    /// it: from its stressed vowel to the end, lowercased, e.g. `ight` for
+    /// "tonight".
+    fn rhyme(&self, word: &str) -> String;
+
+    /// Whether `a` and `b` rhyme.
+    fn rhymes(&self, a: &str, b: &str) -> bool {
+        let (a, b) = (self.rhyme(a), self.rhyme(b));
+        !a.is_empty() && a == b
+    }
+}
+
+static REGISTERED: RwLock<Vec<&'static dyn Language>> = RwLock::new(Vec::new());
+
+const BUILT_IN: &[&dyn Language] = &[&English, &Spanish, &Portuguese, &German];
+
+/// Make `language` available to songs that name it. It takes precedence
+/// over a built-in language with the same code.
+pub fn register(language: Box<dyn Language>) {
+    let mut registered = REGISTERED.write().expect("not poisoned");
+    registered.insert(0, Box::leak(language));
+}
+
+/// The language with the code or English name `name`, matching case
+/// insensitively and ignoring a region such as `-BR`.
+pub fn find(name: &str) -> Option<&'static dyn Language> {
+    let code = name
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let matches = |language: &&dyn Language| {
+        language.code() == code || language.name().eq_ignore_ascii_case(name.trim())
+    };
+    let registered = REGISTERED.read().expect("not poisoned");
+    registered
+        .iter()
+        .copied()
+        .find(matches)
+        .or_else(|| BUILT_IN.iter().copied().find(matches))
+}
+
+/// The language `song` is written in, by its `lang:` entry.
+pub fn of(song: &Song) -> &'static dyn Language {
+    song.meta_str("lang")
+        .and_then(|name| find(&name))
+        .unwrap_or(&English)
+}
+
+/// English, by spelling heuristics and the stress dictionary.
+pub struct English;
+
+/// Spanish, whose spelling marks stress and hiatus.
+pub struct Spanish;
+
+/// Portuguese, with its nasal vowels and diphthongs.
+pub struct Portuguese;
+
+/// German, with its diphthongs and unstressed final `e`.
+pub struct German;
+
+/// Lowercased letters of `word`, apostrophes and digits dropped.
+fn letters(word: &str) -> Vec<char> {
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Strip the accent from a vowel: `á` to `a`.
+fn base(c: char) -> char {
+    const ACCENTED: &str = "áàâãäéèêëíìîïóòôõöúùûü";
+    const BASES: &str = "aaaaaeeeeiiiiooooouuuu";
+    ACCENTED
+        .chars()
+        .position(|a| a == c)
+        .and_then(|i| BASES.chars().nth(i))
+        .unwrap_or(c)
+}
+
+/// How a letter takes part in a syllable nucleus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Vowel {
+    /// A consonant, or a vowel letter that is not pronounced.
+    None,
+    /// A vowel that forms a diphthong with a neighbouring vowel.
+    Weak,
+    /// A vowel that forms its own nucleus beside another strong vowel.
+    Strong,
+}
+
+/// Letter ranges of the syllable nuclei of a word, given each letter's role.
+/// Adjacent vowels share a nucleus unless both are strong.
+fn nuclei(roles: &[Vowel]) -> Vec<(usize, usize)> {
+    let mut nuclei: Vec<(usize, usize)> = Vec::new();
+    let mut previous = Vowel::None;
+    for (i, &role) in roles.iter().enumerate() {
+        if role == Vowel::None {
+            previous = role;
+            continue;
+        }
+        let joins =
+            previous != Vowel::None && !(previous == Vowel::Strong && role == Vowel::Strong);
+        match nuclei.last_mut() {
+            Some(nucleus) if joins => {
+                nucleus.1 = i + 1;
+            }
+            _ => nuclei.push((i, i + 1)),
+        }
+        previous = role;
+    }
+    nuclei
+}
+
+/// Roles of the letters of a Spanish or Portuguese word. `strong` lists the
+/// vowels that form their own nucleus; `u` after `q` or `g` before `e` or
+/// `i` is silent; `y` is a vowel unless another vowel follows.
+fn romance_roles(letters: &[char], strong: &str, weak: &str) -> Vec<Vowel> {
+    (0..letters.len())
+        .map(|i| {
+            let c = letters[i];
+            let next = letters.get(i + 1).map(|&n| base(n));
+            let after_qg = i > 0 && matches!(letters[i - 1], 'q' | 'g');
+            if c == 'u' && after_qg && matches!(next, Some('e' | 'i')) {
+                Vowel::None
+            } else if strong.contains(c) {
+                Vowel::Strong
+            } else if weak.contains(c) || (c == 'y' && !next.is_some_and(|n| "aeiou".contains(n))) {
+                Vowel::Weak
+            } else {
+                Vowel::None
+            }
+        })
+        .collect()
+}
+
+/// The stressed nucleus of a Spanish or Portuguese word: the one with a
+/// written accent, else the second to last when `penultimate` says so of
+/// the word's ending, else the last.
+fn romance_stress(
+    letters: &[char],
+    nuclei: &[(usize, usize)],
+    accents: &str,
+    penultimate: impl Fn(&str) -> bool,
+) -> Option<usize> {
+    if let Some(n) = nuclei
+        .iter()
+        .position(|&(from, to)| letters[from..to].iter().any(|c| accents.contains(*c)))
+    {
+        return Some(n);
+    }
+    let word: String = letters.iter().collect();
+    match nuclei.len() {
+        0 => None,
+        1 => Some(0),
+        n if penultimate(&word) => Some(n - 2),
+        n => Some(n - 1),
+    }
+}
+
+/// The letters from the main vowel of nucleus `n` to the end, unaccented.
+/// The main vowel is the accented or strong one, else the last.
+fn rhyme_from(letters: &[char], roles: &[Vowel], nucleus: (usize, usize)) -> String {
+    let (from, to) = nucleus;
+    let start = (from..to)
+        .find(|&i| base(letters[i]) != letters[i])
+        .or_else(|| (from..to).find(|&i| roles[i] == Vowel::Strong))
+        .unwrap_or(to - 1);
+    letters[start..].iter().map(|&c| base(c)).collect()
+}
+
+impl Language for English {
+    fn code(&self) -> &str {
+        "en"
+    }
+
+    fn name(&self) -> &str {
+        "English"
+    }
+
+    fn syllables(&self, word: &str) -> usize {
+        count_word(word)
+    }
+
+    fn rhyme(&self, word: &str) -> String {
+        let letters = letters(word);
+        let mut end = letters.len();
+        // A silent final e belongs to the rhyme but is not its vowel:
+        // "love", "dove".
+        if end > 2 && letters[end - 1] == 'e' && !"aeiou".contains(letters[end - 2]) {
+            end -= 1;
+        }
+        let vowel = |i: usize| "aeiou".contains(letters[i]) || (letters[i] == 'y' && i > 0);
+        let Some(last) = (0..end).rev().find(|&i| vowel(i)) else {
+            return letters.iter().collect();
+        };
+        let start = (0..=last)
+            .rev()
+            .take_while(|&i| vowel(i))
+            .last()
+            .unwrap_or(last);
+        letters[start..].iter().collect()
+    }
+}
+
+const SPANISH_STRONG: &str = "aeoáéóíú";
+const SPANISH_WEAK: &str = "iuü";
+
+impl Spanish {
+    fn analyse(word: &str) -> (Vec<char>, Vec<Vowel>, Vec<(usize, usize)>) {
+        let letters = letters(word);
+        let roles = romance_roles(&letters, SPANISH_STRONG, SPANISH_WEAK);
+        let nuclei = nuclei(&roles);
+        (letters, roles, nuclei)
+    }
+}
+
+impl Language for Spanish {
+    fn code(&self) -> &str {
+        "es"
+    }
+
+    fn name(&self) -> &str {
+        "Spanish"
+    }
+
+    fn syllables(&self, word: &str) -> usize {
+        let (letters, _, nuclei) = Spanish::analyse(word);
+        match letters.is_empty() {
+            true => 0,
+            false => nuclei.len().max(1),
+        }
+    }
+
+    fn rhyme(&self, word: &str) -> String {
+        let (letters, roles, nuclei) = Spanish::analyse(word);
+        // Words ending in a vowel, `n` or `s` stress the second to last
+        // syllable unless an accent says otherwise.
+        let penultimate = |w: &str| w.ends_with(['a', 'e', 'i', 'o', 'u', 'n', 's']);
+        match romance_stress(&letters, &nuclei, "áéíóú", penultimate) {
+            Some(n) => rhyme_from(&letters, &roles, nuclei[n]),
+            None => letters.iter().collect(),
+        }
+    }
+}
+
+const PORTUGUESE_STRONG: &str = "aeoáéóíúâêôãõ";
+const PORTUGUESE_WEAK: &str = "iuü";
+
+impl Portuguese {
+    fn analyse(word: &str) -> (Vec<char>, Vec<Vowel>, Vec<(usize, usize)>) {
+        let letters = letters(word);
+        let mut roles = romance_roles(&letters, PORTUGUESE_STRONG, PORTUGUESE_WEAK);
+        // The second vowel of the nasal diphthongs `ão`, `ãe` and `õe`.
+        for i in 1..letters.len() {
+            if matches!(letters[i - 1], 'ã' | 'õ') && matches!(letters[i], 'o' | 'e') {
+                roles[i] = Vowel::Weak;
+            }
+        }
+        let nuclei = nuclei(&roles);
+        (letters, roles, nuclei)
+    }
+}
+
+impl Language for Portuguese {
+    fn code(&self) -> &str {
+        "pt"
+    }
+
+    fn name(&self) -> &str {
+        "Portuguese"
+    }
+
+    fn syllables(&self, word: &str) -> usize {
+        let (letters, _, nuclei) = Portuguese::analyse(word);
+        match letters.is_empty() {
+            true => 0,
+            false => nuclei.len().max(1),
+        }
+    }
+
+    fn rhyme(&self, word: &str) -> String {
+        let (letters, roles, nuclei) = Portuguese::analyse(word);
+        // Words ending in `a`, `e`, `o`, `am`, `em` or `ens`, with or
+        // without a plural `s`, stress the second to last syllable.
+        let penultimate = |w: &str| {
+            let w = w.strip_suffix('s').unwrap_or(w);
+            w.ends_with(['a', 'e', 'o'])
+                || w.ends_with("am")
+                || w.ends_with("em")
+                || w.ends_with("en")
+        };
+        match romance_stress(&letters, &nuclei, "áéíóúâêôãõ", penultimate) {
+            Some(n) => rhyme_from(&letters, &roles, nuclei[n]),
+            None => letters.iter().collect(),
+        }
+    }
+}
+
+/// Vowel pairs that German spells for one sound.
+const GERMAN_DIGRAPHS: &[&str] = &[
+    "ai", "au", "ay", "ei", "ey", "eu", "äu", "ie", "aa", "ee", "oo",
+];
+
+impl German {
+    fn analyse(word: &str) -> (Vec<char>, Vec<(usize, usize)>) {
+        let letters = letters(word);
+        let vowel = |c: char| "aeiouyäöü".contains(c);
+        let mut nuclei: Vec<(usize, usize)> = Vec::new();
+        for (i, &c) in letters.iter().enumerate() {
+            if !vowel(c) {
+                continue;
+            }
+            match nuclei.last_mut() {
+                // Join a digraph, but only two letters: "Feuer" is Feu-er.
+                Some(nucleus) if nucleus.1 == i && nucleus.1 - nucleus.0 == 1 => {
+                    let pair: String = [letters[i - 1], c].iter().collect();
+                    match GERMAN_DIGRAPHS.contains(&pair.as_str()) {
+                        true => nucleus.1 = i + 1,
+                        false => nuclei.push((i, i + 1)),
+                    }
+                }
+                _ => nuclei.push((i, i + 1)),
+            }
+        }
+        (letters, nuclei)
+    }
+}
+
+impl Language for German {
+    fn code(&self) -> &str {
+        "de"
+    }
+
+    fn name(&self) -> &str {
+        "German"
+    }
+
+    fn syllables(&self, word: &str) -> usize {
+        let (letters, nuclei) = German::analyse(word);
+        match letters.is_empty() {
+            true => 0,
+            false => nuclei.len().max(1),
+        }
+    }
+
+    fn rhyme(&self, word: &str) -> String {
+        let (letters, nuclei) = German::analyse(word);
+        // Rhymes take in an unstressed final e: "Liebe", "Herzen", "Himmel".
+        let schwa = |&(from, to): &(usize, usize)| to - from == 1 && letters[from] == 'e';
+        let stressed = match nuclei.len() {
+            0 => None,
+            n if n > 1 && schwa(&nuclei[n - 1]) => Some(n - 2),
+            n => Some(n - 1),
+        };
+        match stressed {
+            Some(n) => letters[nuclei[n].0..].iter().collect(),
+            None => letters.iter().collect(),
+        }
+    }
+}
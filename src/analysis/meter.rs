@@ -0,0 +1,79 @@
+//! Checking `|` bar markers against the song's time signature.
+
+use super::language;
+use super::syllables::word_syllables_in;
+use crate::ast::{Line, Song, TimeSignature};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+use std::ops::Range;
+
+/// Most syllables a bar can carry: two per beat of the time signature.
+pub fn bar_capacity(time: TimeSignature) -> usize {
+    time.beats as usize * 2
+}
+
+/// Word ranges of the bars a line is divided into by its `|` markers. Words
+/// before the first marker form a pickup and words after the last one an
+/// open bar; both are included when not empty.
+pub fn line_bars(line: &Line, word_count: usize) -> Vec<Range<usize>> {
+    let mut bounds = Vec::with_capacity(line.bars.len() + 2);
+    bounds.push(0);
+    bounds.extend(line.bars.iter().map(|&b| b.min(word_count)));
+    bounds.push(word_count);
+    bounds
+        .windows(2)
+        .enumerate()
+        .filter(|(i, w)| w[0] < w[1] || (*i > 0 && *i < line.bars.len()))
+        .map(|(_, w)| w[0]..w[1])
+        .collect()
+}
+
+/// Warn about bars holding more syllables than the meter allows, except in
+/// lines with `·` beat markers, and about bar markers in a song that has no
+/// time signature.
+pub fn check(song: &Song) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let time = song.time_signature();
+    let meter = time.unwrap_or(TimeSignature::COMMON);
+    let capacity = bar_capacity(meter);
+    let language = language::of(song);
+
+    for (_, line) in song.lines() {
+        if line.bars.is_empty() {
+            continue;
+        }
+        if time.is_none() && diagnostics.is_empty() {
+            diagnostics.push(
+                Diagnostic::warning(
+                    "bar markers without a `time` signature; assuming 4/4",
+                    line.span,
+                )
+                .with_code(&codes::BARS_WITHOUT_TIME),
+            );
+        }
+        // A line with beat markers is a flow, measured by `super::flow`.
+        if !line.beats.is_empty() {
+            continue;
+        }
+        let syllables = word_syllables_in(line, language);
+        for (n, bar) in line_bars(line, syllables.len()).into_iter().enumerate() {
+            let count: usize = syllables[bar].iter().sum();
+            if count > capacity {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!(
+                            "bar {} of this line has {} syllables, more than the {} that fit in {}",
+                            n + 1,
+                            count,
+                            capacity,
+                            meter
+                        ),
+                        line.span,
+                    )
+                    .with_code(&codes::OVERFULL_BAR),
+                );
+            }
+        }
+    }
+    diagnostics
+}
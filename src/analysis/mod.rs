@@ -0,0 +1,117 @@
+//! Prosodic analysis of a resolved [`Song`]: syllable counts, a per-line
+//! singability score, where timed lines fall on the beat grid, whether
+//! barred lines fit the meter, how fast each section is sung and how densely
+//! a rap flow fills its beats.
+
+use crate::ast::{Song, TimeSignature};
+use serde::Serialize;
+
+pub mod alignment;
+pub mod consistency;
+pub mod flow;
+pub mod language;
+pub mod meter;
+pub mod pacing;
+pub mod scansion;
+pub mod similarity;
+pub mod singability;
+pub mod syllables;
+pub mod tempo;
+pub mod vocabulary;
+
+pub use language::Language;
+pub use pacing::Pace;
+pub use similarity::{compare, SimilarityOptions};
+pub use singability::{score_line, score_line_in, LineScore};
+pub use syllables::{count_ipa, count_line, count_line_in, count_word};
+pub use tempo::{TempoMap, BPM_RANGE};
+pub use vocabulary::Vocabulary;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineReport {
+    pub text: String,
+    /// Beats from the start of the recording, for timed lines.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beat: Option<f64>,
+    /// The 1-based bar a timed line starts in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar: Option<u32>,
+    #[serde(flatten)]
+    pub score: LineScore,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionReport {
+    pub label: String,
+    pub lines: Vec<LineReport>,
+    pub singability: u8,
+    /// How long the section lasts and how fast its words come, for timed
+    /// sections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace: Option<Pace>,
+    /// Set when the section is sung much faster or slower than the rest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace_warning: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub sections: Vec<SectionReport>,
+    pub singability: u8,
+}
+
+/// Score every line of `song`. Section and song scores are the mean of
+/// their lines.
+pub fn analyze(song: &Song) -> Report {
+    let tempo = TempoMap::from_song(song);
+    let language = language::of(song);
+    let bar_quarters = song
+        .time_signature()
+        .unwrap_or(TimeSignature::COMMON)
+        .bar_quarters();
+    let paces = pacing::paces(song);
+    let warnings = pacing::warnings(&paces);
+    let sections: Vec<SectionReport> = song
+        .sections
+        .iter()
+        .zip(paces.into_iter().zip(warnings))
+        .map(|(section, (pace, pace_warning))| {
+            let lines: Vec<LineReport> = section
+                .lines
+                .iter()
+                .map(|line| LineReport {
+                    text: line.text.clone(),
+                    beat: line.timing.map(|at| tempo.beat_at(at)),
+                    bar: line.timing.map(|at| {
+                        ((tempo.beat_at(at) / bar_quarters).floor() as u32).saturating_add(1)
+                    }),
+                    score: score_line_in(line, section.kind, language),
+                })
+                .collect();
+            let singability = mean(lines.iter().map(|l| l.score.score));
+            SectionReport {
+                label: section.label(),
+                lines,
+                singability,
+                pace,
+                pace_warning,
+            }
+        })
+        .collect();
+    let singability = mean(
+        sections
+            .iter()
+            .flat_map(|s| s.lines.iter().map(|l| l.score.score)),
+    );
+    Report {
+        sections,
+        singability,
+    }
+}
+
+fn mean(scores: impl Iterator<Item = u8>) -> u8 {
+    let (sum, count) = scores.fold((0u32, 0u32), |(sum, n), s| (sum + s as u32, n + 1));
+    (sum + count / 2)
+        .checked_div(count)
+        .map_or(100, |mean| mean as u8)
+}
@@ -0,0 +1,108 @@
+//! Pacing: how long each section lasts and how fast its words come, from the
+//! song's line timings, and which sections are sung much faster or slower
+//! than the rest, such as a rushed bridge.
+//!
+//! A section lasts from its first timed line until the next section starts,
+//! or until the song's `duration` for the last one. When the next section's
+//! start is unknown, the span between the section's own first and last
+//! timed lines is used instead. `REPEAT` sections replay the timings of the
+//! section they repeat and are left out.
+
+use super::alignment::parse_duration;
+use crate::ast::{words, Line, Section, Song};
+use serde::Serialize;
+
+/// A section is out of pace once its words a minute are this many times
+/// the median of the other sections, or that many times fewer.
+const PACE_FACTOR: f64 = 1.5;
+/// Fewest other sections with a pace needed to judge one against them.
+const MIN_OTHERS: usize = 2;
+/// Sections of fewer words, such as a held outro, are not judged.
+const MIN_WORDS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pace {
+    pub seconds: f64,
+    pub words: usize,
+    pub words_per_minute: f64,
+}
+
+/// The pace of each section of the resolved `song`, where its timings
+/// tell.
+pub fn paces(song: &Song) -> Vec<Option<Pace>> {
+    let start = |section: &Section| match section.repeat {
+        Some(_) => None,
+        None => section.lines.iter().find_map(|l| l.timing),
+    };
+    let duration = song.meta_str("duration").and_then(|d| parse_duration(&d));
+    let sections = &song.sections;
+    sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| {
+            let from = start(section)?.as_secs_f64();
+            let end = match sections.get(i + 1) {
+                Some(next) => start(next).map(|at| at.as_secs_f64()),
+                None => duration,
+            };
+            let (seconds, words) = match end.filter(|end| *end > from) {
+                Some(end) => (end - from, count(section.lines.iter())),
+                None => {
+                    // Only the lines sung before the last timed one.
+                    let last = section.lines.iter().rposition(|l| l.timing.is_some())?;
+                    let to = section.lines[last].timing?.as_secs_f64();
+                    (to - from, count(section.lines[..last].iter()))
+                }
+            };
+            if seconds <= 0.0 || words == 0 {
+                return None;
+            }
+            Some(Pace {
+                seconds: (seconds * 100.0).round() / 100.0,
+                words,
+                words_per_minute: (words as f64 * 600.0 / seconds).round() / 10.0,
+            })
+        })
+        .collect()
+}
+
+fn count<'a>(lines: impl Iterator<Item = &'a Line>) -> usize {
+    lines.map(|line| words(&line.text).count()).sum()
+}
+
+/// For each of `paces`, a warning when it is far from the median pace of
+/// the other sections.
+pub fn warnings(paces: &[Option<Pace>]) -> Vec<Option<String>> {
+    paces
+        .iter()
+        .enumerate()
+        .map(|(i, pace)| {
+            let pace = pace.as_ref().filter(|p| p.words >= MIN_WORDS)?;
+            let mut others: Vec<f64> = paces
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .filter_map(|(_, p)| p.as_ref().map(|p| p.words_per_minute))
+                .collect();
+            if others.len() < MIN_OTHERS {
+                return None;
+            }
+            others.sort_by(f64::total_cmp);
+            let mid = others.len() / 2;
+            let median = match others.len() % 2 {
+                0 => (others[mid - 1] + others[mid]) / 2.0,
+                _ => others[mid],
+            };
+            let ratio = pace.words_per_minute / median;
+            let how = match ratio {
+                r if r >= PACE_FACTOR => "rushed",
+                r if r <= 1.0 / PACE_FACTOR => "dragging",
+                _ => return None,
+            };
+            Some(format!(
+                "{}: {:.0} words a minute against {:.0} in the rest of the song",
+                how, pace.words_per_minute, median
+            ))
+        })
+        .collect()
+}
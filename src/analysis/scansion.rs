@@ -0,0 +1,360 @@
+//! Scansion: which syllables of a line are stressed, and whether that fights
+//! the meter the song or section declares.
+//!
+//! Patterns use the notation of the `stress:` line attribute, `/` for a
+//! stressed syllable and `x` for an unstressed one. Word stress comes from a
+//! `{/ipa/}` override when it marks one, then from a small built-in
+//! dictionary, then from suffix and prefix rules. Monosyllables may take
+//! either beat, so only the fixed stress of longer words can clash.
+
+use super::syllables::{count_word, ipa_nuclei};
+use crate::ast::{word_ranges, Line, Section, Song, Value};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+use crate::export::ttml::escape;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+static DICTIONARY: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    include_str!("stress.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(' '))
+        .collect()
+});
+
+/// The dictionary pattern of a lowercased word. [`count_word`] uses it too,
+/// so syllable counts and scansion agree on the words it lists.
+pub(crate) fn dictionary_stress(word: &str) -> Option<&'static str> {
+    DICTIONARY.get(word).copied()
+}
+
+/// Monosyllables that are normally unstressed.
+const FUNCTION_WORDS: &[&str] = &[
+    "a", "am", "an", "and", "are", "as", "at", "be", "been", "but", "by", "can", "could", "did",
+    "do", "does", "for", "from", "had", "has", "have", "he", "her", "him", "his", "i", "i'd",
+    "i'll", "i'm", "i've", "if", "in", "is", "it", "it's", "its", "me", "my", "nor", "of", "on",
+    "or", "our", "she", "should", "so", "than", "that", "the", "their", "them", "they", "this",
+    "to", "us", "was", "we", "were", "what", "when", "who", "will", "with", "would", "you",
+    "you're", "your",
+];
+
+/// Unstressed prefixes that move the stress of a word to its second syllable
+/// when a consonant follows them, as in "become" or "remind".
+const PREFIXES: &[&str] = &[
+    "be", "de", "dis", "em", "en", "ex", "mis", "pre", "re", "un",
+];
+
+/// A declared meter: a named foot repeated across the line, or an explicit
+/// pattern such as `x/x//`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meter {
+    pub name: String,
+    pub foot: String,
+}
+
+impl Meter {
+    /// Parse `iambic`, `trochaic`, `anapestic`, `dactylic` (or the foot's
+    /// name) or a pattern of `x` and `/`.
+    pub fn parse(text: &str) -> Option<Meter> {
+        let foot = match text.to_lowercase().as_str() {
+            "iambic" | "iamb" => "x/",
+            "trochaic" | "trochee" => "/x",
+            "anapestic" | "anapest" | "anapaestic" => "xx/",
+            "dactylic" | "dactyl" => "/xx",
+            _ if !text.is_empty() && text.chars().all(|c| c == 'x' || c == '/') => text,
+            _ => return None,
+        };
+        Some(Meter {
+            name: text.to_string(),
+            foot: foot.to_string(),
+        })
+    }
+
+    /// The foot repeated to `len` syllables.
+    pub fn expected(&self, len: usize) -> String {
+        self.foot.chars().cycle().take(len).collect()
+    }
+}
+
+impl fmt::Display for Meter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name == self.foot {
+            write!(f, "`{}`", self.foot)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// One word of a scanned line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedWord {
+    /// Byte range of the word in the line's text.
+    pub range: Range<usize>,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scansion {
+    pub words: Vec<ScannedWord>,
+    /// The line's `stress:` attribute, or the meter repeated to its length.
+    pub expected: Option<String>,
+    /// Indices into `words` of longer words stressed where the expected
+    /// pattern has an unstressed syllable.
+    pub clashes: Vec<usize>,
+}
+
+impl Scansion {
+    /// The stress pattern of the whole line.
+    pub fn pattern(&self) -> String {
+        self.words.iter().map(|w| w.pattern.as_str()).collect()
+    }
+}
+
+/// The stress pattern of one word, with its `{/ipa/}` override if any.
+pub fn word_stress(word: &str, ipa: Option<&str>) -> String {
+    if let Some(pattern) = ipa.and_then(ipa_stress) {
+        return pattern;
+    }
+    let lower = word.to_lowercase().replace('’', "'");
+    if let Some(pattern) = dictionary_stress(&lower) {
+        return pattern.to_string();
+    }
+    let syllables = count_word(&lower);
+    match syllables {
+        0 => String::new(),
+        1 if FUNCTION_WORDS.contains(&lower.as_str()) => "x".to_string(),
+        1 => "/".to_string(),
+        n => stressed_at(n, stress_position(&lower, n)),
+    }
+}
+
+/// Where the main stress of an unknown word of `n` syllables falls.
+fn stress_position(word: &str, n: usize) -> usize {
+    const PENULTIMATE: &[&str] = &["tion", "sion", "cian", "cious", "tious", "tial", "ic"];
+    const ANTEPENULTIMATE: &[&str] = &["ity", "ical", "ogy", "ography", "ify"];
+    if n >= 3 && ANTEPENULTIMATE.iter().any(|s| word.ends_with(s)) {
+        return n - 3;
+    }
+    if PENULTIMATE.iter().any(|s| word.ends_with(s)) {
+        return n - 2;
+    }
+    let prefixed = PREFIXES.iter().any(|p| {
+        word.strip_prefix(p)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| !"aeiouy".contains(c))
+    });
+    usize::from(prefixed)
+}
+
+fn stressed_at(n: usize, position: usize) -> String {
+    (0..n)
+        .map(|i| if i == position { '/' } else { 'x' })
+        .collect()
+}
+
+/// The pattern of an IPA transcription that marks stress with `ˈ` or `ˌ`.
+fn ipa_stress(ipa: &str) -> Option<String> {
+    if !ipa.contains(['ˈ', 'ˌ']) {
+        return None;
+    }
+    let n = ipa_nuclei(ipa).max(1);
+    let mut pattern = vec!['x'; n];
+    for (at, _) in ipa.match_indices(['ˈ', 'ˌ']) {
+        if let Some(slot) = pattern.get_mut(ipa_nuclei(&ipa[..at])) {
+            *slot = '/';
+        }
+    }
+    Some(pattern.into_iter().collect())
+}
+
+/// Scan `line` and compare it with its `stress:` attribute or `meter`.
+pub fn scan_line(line: &Line, meter: Option<&Meter>) -> Scansion {
+    let words: Vec<ScannedWord> = word_ranges(&line.text)
+        .into_iter()
+        .map(|range| {
+            let word = &line.text[range.clone()];
+            let pattern = word_stress(word, line.pronunciation(word));
+            ScannedWord { range, pattern }
+        })
+        .collect();
+    let len = words.iter().map(|w| w.pattern.len()).sum();
+    let expected = line
+        .stress
+        .clone()
+        .or_else(|| meter.map(|m| m.expected(len)));
+
+    let mut clashes = Vec::new();
+    if let Some(expected) = &expected {
+        let expected = expected.as_bytes();
+        let mut offset = 0;
+        for (i, word) in words.iter().enumerate() {
+            let clash = word.pattern.len() > 1
+                && word
+                    .pattern
+                    .bytes()
+                    .enumerate()
+                    .any(|(k, s)| s == b'/' && expected.get(offset + k) == Some(&b'x'));
+            if clash {
+                clashes.push(i);
+            }
+            offset += word.pattern.len();
+        }
+    }
+    Scansion {
+        words,
+        expected,
+        clashes,
+    }
+}
+
+/// The meter a section declares with a `meter` attribute, else the song's
+/// `meter` metadata.
+pub fn section_meter(song: &Song, section: &Section) -> Option<Meter> {
+    section
+        .attr("meter")
+        .map(Value::to_string)
+        .or_else(|| song.meta_str("meter"))
+        .and_then(|text| Meter::parse(&text))
+}
+
+/// Warn about unknown meters, `stress:` attributes whose length does not
+/// match the line, and lines whose word stress fights the declared meter.
+pub fn check(song: &Song) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let unknown = |text: &str| {
+        format!(
+            "unknown meter `{}`, expected iambic, trochaic, anapestic, dactylic or a pattern such as `x/`",
+            text
+        )
+    };
+    if let Some(entry) = song.metadata.iter().find(|e| e.key == "meter") {
+        if Meter::parse(&entry.value.to_string()).is_none() {
+            diagnostics.push(
+                Diagnostic::warning(unknown(&entry.value.to_string()), entry.span)
+                    .with_code(&codes::UNKNOWN_METER),
+            );
+        }
+    }
+
+    for section in &song.sections {
+        if let Some(value) = section.attr("meter") {
+            if Meter::parse(&value.to_string()).is_none() {
+                diagnostics.push(
+                    Diagnostic::warning(unknown(&value.to_string()), section.span)
+                        .with_code(&codes::UNKNOWN_METER),
+                );
+            }
+        }
+        let meter = section_meter(song, section);
+        for line in &section.lines {
+            let scansion = scan_line(line, meter.as_ref());
+            let pattern = scansion.pattern();
+            if let Some(declared) = &line.stress {
+                if declared.len() != pattern.len() {
+                    diagnostics.push(
+                        Diagnostic::warning(
+                            format!(
+                                "stress pattern `{}` has {} syllables but the line has {}",
+                                declared,
+                                declared.len(),
+                                pattern.len()
+                            ),
+                            line.span,
+                        )
+                        .with_code(&codes::STRESS_LENGTH),
+                    );
+                    continue;
+                }
+            }
+            if scansion.clashes.is_empty() {
+                continue;
+            }
+            let words: Vec<String> = scansion
+                .clashes
+                .iter()
+                .map(|&i| format!("`{}`", &line.text[scansion.words[i].range.clone()]))
+                .collect();
+            let against = match (&line.stress, &meter) {
+                (Some(_), _) | (None, None) => "the declared stress".to_string(),
+                (None, Some(meter)) => format!("the {} meter", meter),
+            };
+            diagnostics.push(
+                Diagnostic::warning(
+                    format!(
+                        "stress of {} fights {}: the line scans `{}` against `{}`",
+                        words.join(", "),
+                        against,
+                        pattern,
+                        scansion.expected.as_deref().unwrap_or_default()
+                    ),
+                    line.span,
+                )
+                .with_code(&codes::STRESS_CLASH),
+            );
+        }
+    }
+    diagnostics
+}
+
+/// The scansion mark for a pattern symbol: `ˈ` stressed, `˘` unstressed.
+pub fn mark(symbol: char) -> char {
+    if symbol == '/' {
+        'ˈ'
+    } else {
+        '˘'
+    }
+}
+
+/// A standalone HTML page with each word's stress marks above it and words
+/// that fight the meter highlighted.
+pub fn scansion_html(song: &Song) -> String {
+    let title = escape(&song.title().unwrap_or_else(|| "Untitled".to_string()));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} – scansion</title>\n<style>\n\
+         body {{ font-family: serif; margin: 2em; }}\n\
+         p {{ font-size: 1.3em; line-height: 2.6; margin: 0; }}\n\
+         rt {{ font-family: sans-serif; color: #555; letter-spacing: 0.1em; }}\n\
+         .clash {{ background: #fde2e2; }}\n\
+         .clash rt {{ color: #c00; }}\n\
+         .expected {{ font: 0.7em monospace; color: #888; margin-left: 1em; }}\n\
+         </style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, title
+    );
+    for section in &song.sections {
+        let meter = section_meter(song, section);
+        let _ = writeln!(html, "<h2>{}</h2>", escape(&section.label()));
+        for line in &section.lines {
+            let scansion = scan_line(line, meter.as_ref());
+            html.push_str("<p>");
+            let mut rest = 0;
+            for (i, word) in scansion.words.iter().enumerate() {
+                html.push_str(&escape(&line.text[rest..word.range.start]));
+                let marks: String = word.pattern.chars().map(mark).collect();
+                let class = if scansion.clashes.contains(&i) {
+                    " class=\"clash\""
+                } else {
+                    ""
+                };
+                let _ = write!(
+                    html,
+                    "<ruby{}>{}<rt>{}</rt></ruby>",
+                    class,
+                    escape(&line.text[word.range.clone()]),
+                    marks
+                );
+                rest = word.range.end;
+            }
+            html.push_str(&escape(&line.text[rest..]));
+            if let (false, Some(expected)) = (scansion.clashes.is_empty(), &scansion.expected) {
+                let _ = write!(html, "<span class=\"expected\">{}</span>", escape(expected));
+            }
+            html.push_str("</p>\n");
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,142 @@
+//! Similarity between songs: shared word n-grams for an overall figure, and
+//! runs of near-identical lines for the passages behind it.
+
+use crate::ast::{words, Line, Song, Span};
+use similar::{capture_diff_slices, get_diff_ratio, Algorithm};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarityOptions {
+    /// Word similarity, from 0 to 1, at which two lines count as matching.
+    pub threshold: f64,
+    /// Length of the word n-grams behind [`Similarity::ngram`].
+    pub ngram: usize,
+    /// Lines with fewer words are too generic ("oh yeah") to match.
+    pub min_words: usize,
+}
+
+impl Default for SimilarityOptions {
+    fn default() -> Self {
+        SimilarityOptions {
+            threshold: 0.8,
+            ngram: 4,
+            min_words: 3,
+        }
+    }
+}
+
+/// Two lines, one from each song, whose words are at least
+/// [`SimilarityOptions::threshold`] alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMatch {
+    pub a: Span,
+    pub b: Span,
+    pub a_text: String,
+    pub b_text: String,
+    pub score: f64,
+}
+
+/// Consecutive matching lines that follow each other in both songs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    pub lines: Vec<LineMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similarity {
+    /// Jaccard similarity of the two songs' sets of word n-grams.
+    pub ngram: f64,
+    /// Matching passages, longest first.
+    pub passages: Vec<Passage>,
+}
+
+/// Compare two resolved songs. `REPEAT` sections are skipped and each line
+/// is taken once, so a repeated chorus does not inflate the result.
+pub fn compare(a: &Song, b: &Song, options: &SimilarityOptions) -> Similarity {
+    let (a_lines, b_lines) = (distinct_lines(a), distinct_lines(b));
+    let ngram = jaccard(
+        &ngrams(&a_lines, options.ngram),
+        &ngrams(&b_lines, options.ngram),
+    );
+
+    // Score every pair once, then chain matches that continue on the next
+    // line of both songs into passages.
+    let mut matches: Vec<Vec<Option<LineMatch>>> = vec![vec![None; b_lines.len()]; a_lines.len()];
+    for (i, (a_line, a_words)) in a_lines.iter().enumerate() {
+        if a_words.len() < options.min_words {
+            continue;
+        }
+        for (j, (b_line, b_words)) in b_lines.iter().enumerate() {
+            if b_words.len() < options.min_words {
+                continue;
+            }
+            let score = line_similarity(a_words, b_words);
+            if score >= options.threshold {
+                matches[i][j] = Some(LineMatch {
+                    a: a_line.span,
+                    b: b_line.span,
+                    a_text: a_line.text.clone(),
+                    b_text: b_line.text.clone(),
+                    score,
+                });
+            }
+        }
+    }
+
+    let mut passages = Vec::new();
+    for i in 0..a_lines.len() {
+        for j in 0..b_lines.len() {
+            let continues = i > 0 && j > 0 && matches[i - 1][j - 1].is_some();
+            if matches[i][j].is_none() || continues {
+                continue;
+            }
+            let lines: Vec<LineMatch> = (0..)
+                .map_while(|k| matches.get(i + k)?.get(j + k)?.clone())
+                .collect();
+            passages.push(Passage { lines });
+        }
+    }
+    passages.sort_by(|x, y| {
+        y.lines
+            .len()
+            .cmp(&x.lines.len())
+            .then_with(|| x.lines[0].a.start.cmp(&y.lines[0].a.start))
+    });
+    Similarity { ngram, passages }
+}
+
+/// Word similarity of two lines: the share of words in matching runs.
+pub fn line_similarity(a: &[String], b: &[String]) -> f64 {
+    let ops = capture_diff_slices(Algorithm::Myers, a, b);
+    get_diff_ratio(&ops, a.len(), b.len()) as f64
+}
+
+fn distinct_lines(song: &Song) -> Vec<(&Line, Vec<String>)> {
+    let mut seen = HashSet::new();
+    song.sections
+        .iter()
+        .filter(|s| s.repeat.is_none())
+        .flat_map(|s| &s.lines)
+        .filter(|l| seen.insert((l.span, l.text.as_str())))
+        .map(|l| (l, normalized_words(&l.text)))
+        .collect()
+}
+
+fn normalized_words(text: &str) -> Vec<String> {
+    words(text)
+        .map(|w| w.to_lowercase().replace('’', "'"))
+        .collect()
+}
+
+fn ngrams(lines: &[(&Line, Vec<String>)], n: usize) -> HashSet<Vec<String>> {
+    let words: Vec<String> = lines.iter().flat_map(|(_, w)| w.iter().cloned()).collect();
+    words.windows(n.max(1)).map(<[String]>::to_vec).collect()
+}
+
+fn jaccard(a: &HashSet<Vec<String>>, b: &HashSet<Vec<String>>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
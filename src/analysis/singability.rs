@@ -0,0 +1,111 @@
+//! A rough singability score for lyric lines.
+//!
+//! Lines start at 100 and lose points for running past the syllable budget
+//! of their section, for consonant clusters that are hard to articulate at
+//! tempo, and for long words. Words with an IPA override are judged by their
+//! transcription rather than their spelling.
+
+use super::language::{English, Language};
+use super::syllables::{count_ipa, count_line_in, words};
+use crate::ast::{Line, SectionKind};
+use serde::Serialize;
+
+/// Maximum comfortable syllables per line for a section kind.
+pub fn syllable_limit(kind: SectionKind) -> usize {
+    match kind {
+        SectionKind::Chorus => 8,
+        _ => 12,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineScore {
+    pub syllables: usize,
+    /// 0 (unsingable) to 100.
+    pub score: u8,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+}
+
+const OVER_LIMIT_PENALTY: i32 = 8;
+const CLUSTER_PENALTY: i32 = 4;
+const LONG_WORD_PENALTY: i32 = 3;
+
+/// Score one English line sung in a section of the given kind.
+pub fn score_line(line: &Line, kind: SectionKind) -> LineScore {
+    score_line_in(line, kind, &English)
+}
+
+/// Score one line in `language` sung in a section of the given kind.
+pub fn score_line_in(line: &Line, kind: SectionKind, language: &dyn Language) -> LineScore {
+    let syllables = count_line_in(line, language);
+    let limit = syllable_limit(kind);
+    let mut score = 100;
+    let mut issues = Vec::new();
+
+    if syllables > limit {
+        score -= OVER_LIMIT_PENALTY * (syllables - limit) as i32;
+        issues.push(format!(
+            "{} syllables, over the {} limit of {}",
+            syllables,
+            kind.label().to_lowercase(),
+            limit
+        ));
+    }
+
+    for word in words(&line.text) {
+        let (clusters, length) = match line.pronunciation(word) {
+            Some(ipa) => (ipa_clusters(ipa), count_ipa(ipa)),
+            None => (spelling_clusters(word), language.syllables(word)),
+        };
+        if clusters > 0 {
+            score -= CLUSTER_PENALTY * clusters as i32;
+            issues.push(format!("consonant cluster in \"{}\"", word));
+        }
+        if length >= 4 {
+            score -= LONG_WORD_PENALTY;
+            issues.push(format!("long word \"{}\" ({} syllables)", word, length));
+        }
+    }
+
+    LineScore {
+        syllables,
+        score: score.clamp(0, 100) as u8,
+        issues,
+    }
+}
+
+/// Runs of three or more consonant sounds in a spelled word. Digraphs that
+/// spell a single sound (`th`, `ch`, `sh`, `ph`, `gh`, `ng`, `ck`) count once.
+fn spelling_clusters(word: &str) -> usize {
+    let lower = word.to_lowercase();
+    let mut simplified = lower.clone();
+    for digraph in ["th", "ch", "sh", "ph", "gh", "ng", "ck"] {
+        simplified = simplified.replace(digraph, "C");
+    }
+    count_runs(simplified.chars(), |c| {
+        c.is_alphabetic() && !"aeiouy".contains(c)
+    })
+}
+
+fn ipa_clusters(ipa: &str) -> usize {
+    count_runs(ipa.chars().filter(|c| !"ˈˌ.ː".contains(*c)), |c| {
+        c.is_alphabetic() && !"aeiouyæɑɒɔəɛɜɪʊʌøœɐɘɵɤɯɨʉɞɶʏɚɝ".contains(c)
+    })
+}
+
+fn count_runs(chars: impl Iterator<Item = char>, consonant: impl Fn(char) -> bool) -> usize {
+    let mut runs = 0;
+    let mut length = 0;
+    for c in chars {
+        if consonant(c) {
+            length += 1;
+            if length == 3 {
+                runs += 1;
+            }
+        } else {
+            length = 0;
+        }
+    }
+    runs
+}
@@ -0,0 +1,157 @@
+//! Syllable counting.
+//!
+//! Words are estimated with the spelling rules of the song's
+//! [`Language`] unless the line gives an IPA override (`word{/ipa/}`), in
+//! which case the vowel nuclei of the transcription are counted instead.
+//! English words in the stress dictionary of [`super::scansion`] take their
+//! count from it.
+
+use super::language::{English, Language};
+use super::scansion::dictionary_stress;
+pub use crate::ast::words;
+use crate::ast::Line;
+
+/// Syllables in a whole English line, honouring its pronunciation overrides.
+pub fn count_line(line: &Line) -> usize {
+    count_line_in(line, &English)
+}
+
+/// Syllables in a whole line in `language`.
+pub fn count_line_in(line: &Line, language: &dyn Language) -> usize {
+    word_syllables_in(line, language).iter().sum()
+}
+
+/// Syllables of each word of an English line, in order.
+pub fn word_syllables(line: &Line) -> Vec<usize> {
+    word_syllables_in(line, &English)
+}
+
+/// Syllables of each word of a line in `language`, in order.
+pub fn word_syllables_in(line: &Line, language: &dyn Language) -> Vec<usize> {
+    words(&line.text)
+        .map(|word| match line.pronunciation(word) {
+            Some(ipa) => count_ipa(ipa),
+            None => language.syllables(word),
+        })
+        .collect()
+}
+
+/// Estimate the syllables of a written English word.
+pub fn count_word(word: &str) -> usize {
+    if word.contains('-') {
+        return word
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .map(count_word)
+            .sum();
+    }
+    if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+        return word.chars().map(digit_syllables).sum();
+    }
+    if let Some(pattern) = dictionary_stress(&word.to_lowercase().replace('’', "'")) {
+        return pattern.len();
+    }
+
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if letters.is_empty() {
+        return 0;
+    }
+    if letters.len() <= 3 {
+        return 1;
+    }
+
+    let is_vowel = |i: usize| -> bool {
+        match letters[i] {
+            'a' | 'e' | 'i' | 'o' | 'u' => true,
+            // A leading y is a consonant: "you", "yellow".
+            'y' => i > 0,
+            c => !c.is_ascii() && "àáâäæãåèéêëìíîïòóôöõøùúûüý".contains(c),
+        }
+    };
+
+    let mut count = 0;
+    let mut previous = false;
+    for i in 0..letters.len() {
+        let vowel = is_vowel(i);
+        if vowel && !previous {
+            count += 1;
+        }
+        previous = vowel;
+    }
+
+    let n = letters.len();
+    let word: String = letters.iter().collect();
+    // Silent e before a suffix: "careful", "lovely", "movement".
+    for i in 2..n.saturating_sub(2) {
+        let rest: String = letters[i + 1..].iter().collect();
+        if letters[i] == 'e'
+            && !is_vowel(i - 1)
+            && is_vowel(i - 2)
+            && ["ful", "ly", "ment", "ness", "less"]
+                .iter()
+                .any(|suffix| rest.starts_with(suffix))
+        {
+            count -= 1;
+        }
+    }
+    let consonant_le = word.ends_with("le") && !is_vowel(n - 3);
+    if word.ends_with('e') && !word.ends_with("ee") && !consonant_le {
+        // Silent final e: "make", "love" but not "table" or "free".
+        count -= 1;
+    } else if word.ends_with("ed") && !matches!(letters[n - 3], 't' | 'd') {
+        // "walked" but not "wanted".
+        count -= 1;
+    } else if word.ends_with("es")
+        && !matches!(letters[n - 3], 's' | 'x' | 'z')
+        && !(letters[n - 3] == 'h' && matches!(letters[n - 4], 'c' | 's'))
+    {
+        // "makes" but not "glasses" or "watches".
+        count -= 1;
+    }
+    count.max(1)
+}
+
+fn digit_syllables(digit: char) -> usize {
+    match digit {
+        '0' | '7' => 2,
+        _ => 1,
+    }
+}
+
+/// IPA symbols that form a syllable nucleus.
+const IPA_VOWELS: &str = "aeiouyæɑɒɔəɛɜɪʊʌøœɐɘɵɤɯɨʉɞɶʏɚɝ";
+
+/// Count the syllables of an IPA transcription by its vowel nuclei.
+///
+/// Adjacent vowels form one nucleus (diphthongs such as `eɪ`), stress and
+/// syllable marks (`ˈ ˌ .`) separate nuclei, and syllabic consonants marked
+/// with U+0329 (`n̩`) count on their own.
+pub fn count_ipa(ipa: &str) -> usize {
+    ipa_nuclei(ipa).max(1)
+}
+
+/// The vowel nuclei of `ipa`, which may be none for a fragment.
+pub(crate) fn ipa_nuclei(ipa: &str) -> usize {
+    let mut count = 0;
+    let mut in_vowel = false;
+    for c in ipa.chars() {
+        if IPA_VOWELS.contains(c) {
+            if !in_vowel {
+                count += 1;
+            }
+            in_vowel = true;
+        } else if c == '\u{0329}' {
+            count += 1;
+            in_vowel = false;
+        } else if matches!(c, 'ː' | 'ˑ' | '\u{0303}' | '\u{032F}') {
+            // Length and nasalisation marks belong to the preceding vowel.
+        } else {
+            in_vowel = false;
+        }
+    }
+    count
+}
@@ -0,0 +1,107 @@
+//! Converting between recording time and musical beats.
+
+use crate::ast::{Song, Timestamp};
+use std::ops::RangeInclusive;
+
+/// Tempo assumed when a song gives none.
+pub const DEFAULT_BPM: f64 = 120.0;
+
+/// Tempos a song may give; resolution reports any outside it.
+pub const BPM_RANGE: RangeInclusive<f64> = 1.0..=1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    start: u64,
+    bpm: f64,
+    /// Beats elapsed before `start`.
+    beat: f64,
+}
+
+/// Piecewise-constant tempo over the length of a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    segments: Vec<Segment>,
+}
+
+impl TempoMap {
+    /// A map with a single tempo throughout.
+    pub fn constant(bpm: f64) -> Self {
+        TempoMap::new(bpm, &[])
+    }
+
+    /// Start at `initial` BPM and switch at each `(at, bpm)` change. Changes
+    /// that are out of order or outside [`BPM_RANGE`] are ignored;
+    /// resolution reports them.
+    pub fn new(initial: f64, changes: &[(Timestamp, f64)]) -> Self {
+        let mut segments = vec![Segment {
+            start: 0,
+            bpm: initial,
+            beat: 0.0,
+        }];
+        for &(at, bpm) in changes {
+            let last = *segments.last().expect("at least one segment");
+            if !BPM_RANGE.contains(&bpm) || (at.millis <= last.start && at.millis > 0) {
+                continue;
+            }
+            if at.millis == 0 {
+                segments[0].bpm = bpm;
+                continue;
+            }
+            segments.push(Segment {
+                start: at.millis,
+                bpm,
+                beat: last.beat + beats(at.millis - last.start, last.bpm),
+            });
+        }
+        TempoMap { segments }
+    }
+
+    /// The `tempo` metadata entry followed by the song's tempo changes. A
+    /// `tempo` that is missing or outside [`BPM_RANGE`] counts as
+    /// [`DEFAULT_BPM`]; resolution reports one outside the range.
+    pub fn from_song(song: &Song) -> Self {
+        let initial = song
+            .meta("tempo")
+            .and_then(|v| v.as_number())
+            .filter(|bpm| BPM_RANGE.contains(bpm))
+            .unwrap_or(DEFAULT_BPM);
+        let changes: Vec<(Timestamp, f64)> =
+            song.tempo_changes.iter().map(|c| (c.at, c.bpm)).collect();
+        TempoMap::new(initial, &changes)
+    }
+
+    /// Every tempo in effect, with the time it starts.
+    pub fn changes(&self) -> impl Iterator<Item = (Timestamp, f64)> + '_ {
+        self.segments
+            .iter()
+            .map(|s| (Timestamp::from_millis(s.start), s.bpm))
+    }
+
+    fn segment_at(&self, millis: u64) -> &Segment {
+        let i = self.segments.partition_point(|s| s.start <= millis);
+        &self.segments[i.saturating_sub(1)]
+    }
+
+    /// Tempo in effect at `at`.
+    pub fn bpm_at(&self, at: Timestamp) -> f64 {
+        self.segment_at(at.millis).bpm
+    }
+
+    /// Beats elapsed from the start of the recording to `at`.
+    pub fn beat_at(&self, at: Timestamp) -> f64 {
+        let segment = self.segment_at(at.millis);
+        segment.beat + beats(at.millis - segment.start, segment.bpm)
+    }
+
+    /// The point in the recording where `beat` falls.
+    pub fn time_at_beat(&self, beat: f64) -> Timestamp {
+        let i = self.segments.partition_point(|s| s.beat <= beat);
+        let segment = &self.segments[i.saturating_sub(1)];
+        let millis = (beat - segment.beat).max(0.0) * 60_000.0 / segment.bpm;
+        Timestamp::from_millis(segment.start + millis.round() as u64)
+    }
+}
+
+fn beats(millis: u64, bpm: f64) -> f64 {
+    millis as f64 * bpm / 60_000.0
+}
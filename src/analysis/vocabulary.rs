@@ -0,0 +1,146 @@
+//! Word frequencies of one song or a corpus, broken down by section kind and
+//! by song.
+
+use crate::ast::{words, SectionKind, Song};
+use crate::collate::Collator;
+use std::collections::HashMap;
+
+/// How often one word occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordCount {
+    /// The word, lowercased with typographic apostrophes straightened.
+    pub word: String,
+    pub total: usize,
+    /// Occurrences per section kind, in [`SectionKind::ALL`] order.
+    pub by_kind: [usize; SectionKind::ALL.len()],
+    /// Occurrences per song, in the order the songs were added.
+    pub by_song: Vec<usize>,
+}
+
+/// Word counts accumulated over the songs passed to [`Vocabulary::add`].
+/// Repeated sections count every time they are sung, so pass resolved songs.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    songs: Vec<String>,
+    counts: HashMap<String, WordCount>,
+    collator: Collator,
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A vocabulary whose words are put in order by `collator`.
+    pub fn with_collator(collator: Collator) -> Self {
+        Vocabulary {
+            collator,
+            ..Self::default()
+        }
+    }
+
+    /// Count the words of `song` under the column name `name`.
+    pub fn add(&mut self, name: &str, song: &Song) {
+        let index = self.songs.len();
+        self.songs.push(name.to_string());
+        for count in self.counts.values_mut() {
+            count.by_song.push(0);
+        }
+        for section in &song.sections {
+            let kind = SectionKind::ALL
+                .iter()
+                .position(|k| *k == section.kind)
+                .expect("every kind is listed");
+            for word in section.lines.iter().flat_map(|l| words(&l.text)) {
+                let word = normalize(word);
+                let songs = self.songs.len();
+                let count = self
+                    .counts
+                    .entry(word.clone())
+                    .or_insert_with(|| WordCount {
+                        word,
+                        total: 0,
+                        by_kind: [0; SectionKind::ALL.len()],
+                        by_song: vec![0; songs],
+                    });
+                count.total += 1;
+                count.by_kind[kind] += 1;
+                count.by_song[index] += 1;
+            }
+        }
+    }
+
+    /// The songs added so far, in order.
+    pub fn songs(&self) -> &[String] {
+        &self.songs
+    }
+
+    /// Every word, most frequent first and alphabetically among equals.
+    pub fn words(&self) -> Vec<&WordCount> {
+        let mut words: Vec<&WordCount> = self.counts.values().collect();
+        words.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| self.collator.compare(&a.word, &b.word))
+        });
+        words
+    }
+
+    /// Every word, alphabetically.
+    pub fn words_alphabetically(&self) -> Vec<&WordCount> {
+        let mut words: Vec<&WordCount> = self.counts.values().collect();
+        self.collator.sort_by_key(&mut words, |c| &c.word);
+        words
+    }
+
+    /// Number of words sung.
+    pub fn tokens(&self) -> usize {
+        self.counts.values().map(|c| c.total).sum()
+    }
+
+    /// Number of distinct words.
+    pub fn types(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// The counts as CSV: `word`, `total`, one column per section kind and,
+    /// for more than one song, one column per song.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["word".to_string(), "total".to_string()];
+        header.extend(SectionKind::ALL.iter().map(|k| k.label().to_lowercase()));
+        let per_song = self.songs.len() > 1;
+        if per_song {
+            header.extend(self.songs.iter().cloned());
+        }
+
+        let mut out = csv_row(&header);
+        for count in self.words() {
+            let mut row = vec![count.word.clone(), count.total.to_string()];
+            row.extend(count.by_kind.iter().map(usize::to_string));
+            if per_song {
+                row.extend(count.by_song.iter().map(usize::to_string));
+            }
+            out.push_str(&csv_row(&row));
+        }
+        out
+    }
+}
+
+pub(crate) fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('’', "'")
+}
+
+/// One RFC 4180 record, quoting fields that need it.
+pub(crate) fn csv_row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
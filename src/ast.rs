@@ -0,0 +1,973 @@
+//! Typed syntax tree produced by [`crate::parser::parse_song`].
+//!
+//! The tree mirrors the grammar in `lyrics.pest`: a song is a list of
+//! metadata entries followed by sections, and each section holds lyric lines
+//! with their optional annotations. Every node records the byte span it was
+//! parsed from so later stages can point diagnostics back at the source.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+
+/// Byte range of a node in the original source text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// One-based line and column of the start of the span within `source`.
+    pub fn line_col(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.start.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let col = before
+            .rfind('\n')
+            .map_or(before.len(), |nl| before.len() - nl - 1)
+            + 1;
+        (line, col)
+    }
+}
+
+/// A parsed song: metadata followed by its sections in source order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Song {
+    pub metadata: Vec<MetaEntry>,
+    /// Set when the metadata came in a `---` YAML block, which `fmt` keeps.
+    #[serde(skip)]
+    pub front_matter: Option<FrontMatter>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub variables: Vec<Variable>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub macros: Vec<Macro>,
+    /// `tempo: bpm @ m:ss` directives in source order; the plain `tempo`
+    /// metadata entry is the tempo before the first of them.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tempo_changes: Vec<TempoChange>,
+    /// Works the song interpolates or samples, from its `credits:` block.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub credits: Vec<Credit>,
+    /// The song's changelog, from its `history:` block, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub history: Vec<HistoryEntry>,
+    pub sections: Vec<Section>,
+}
+
+impl Song {
+    /// Value of the first metadata entry with the given key.
+    pub fn meta(&self, key: &str) -> Option<&Value> {
+        self.metadata
+            .iter()
+            .find(|m| m.key == key)
+            .map(|m| &m.value)
+    }
+
+    /// Metadata value rendered as plain text, if present.
+    pub fn meta_str(&self, key: &str) -> Option<String> {
+        self.meta(key).map(|v| v.to_string())
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.meta_str("title")
+    }
+
+    pub fn artist(&self) -> Option<String> {
+        self.meta_str("artist")
+    }
+
+    /// The items of the `key` entry: a front-matter list's, or the
+    /// comma-separated ones of its text.
+    fn meta_list(&self, key: &str) -> Vec<String> {
+        if let Some(Value::List(items)) = self.meta(key) {
+            return items.iter().map(|v| v.to_string()).collect();
+        }
+        self.meta_str(key)
+            .map(|t| {
+                t.split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The export formats named in `targets`, e.g. `targets: "ttml, slides"`.
+    pub fn targets(&self) -> Vec<String> {
+        self.meta_list("targets")
+    }
+
+    /// The languages every line is to be translated into, named in
+    /// `translations`, e.g. `translations: "es, pt-BR"`.
+    pub fn translation_languages(&self) -> Vec<String> {
+        self.meta_list("translations")
+    }
+
+    /// How many syllables a translation may differ from its line by, from
+    /// `syllable_delta`, if set to a whole number.
+    pub fn syllable_delta(&self) -> Option<usize> {
+        self.meta_str("syllable_delta")?.trim().parse().ok()
+    }
+
+    /// The `time` (or older `time_sig`) entry, if present and valid.
+    pub fn time_signature(&self) -> Option<TimeSignature> {
+        self.meta("time")
+            .or_else(|| self.meta("time_sig"))
+            .and_then(|v| TimeSignature::parse(&v.to_string()))
+    }
+
+    /// Iterate over every lyric line together with the section it belongs to.
+    pub fn lines(&self) -> impl Iterator<Item = (&Section, &Line)> {
+        self.sections
+            .iter()
+            .flat_map(|s| s.lines.iter().map(move |l| (s, l)))
+    }
+}
+
+/// An identifier together with the span it was written at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Name {
+    pub name: String,
+    pub span: Span,
+}
+
+impl Name {
+    pub fn new(name: impl Into<String>, span: Span) -> Self {
+        Name {
+            name: name.into(),
+            span,
+        }
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name {
+            name,
+            span: Span::default(),
+        }
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.name
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// A `$name = "text"` definition that lyric lines interpolate as `$name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variable {
+    pub name: Name,
+    pub value: String,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A `MACRO name` block of lines inserted wherever `USE name` appears.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: Name,
+    pub lines: Vec<Line>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// What a `REPEAT` or `USE` directive points at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefTarget {
+    /// A section header such as `CHORUS[1]`; without a number, the latest
+    /// section of that kind.
+    Section {
+        kind: SectionKind,
+        number: Option<u32>,
+    },
+    Macro(String),
+    Anchor(String),
+}
+
+impl fmt::Display for RefTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefTarget::Section {
+                kind,
+                number: Some(n),
+            } => write!(f, "{}[{}]", kind.keyword(), n),
+            RefTarget::Section { kind, number: None } => f.write_str(kind.keyword()),
+            RefTarget::Macro(name) => f.write_str(name),
+            RefTarget::Anchor(name) => write!(f, "*{}", name),
+        }
+    }
+}
+
+/// A reference to another part of the song together with its source span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    pub target: RefTarget,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A `key: value` metadata entry at the top of a song.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetaEntry {
+    pub key: String,
+    pub value: Value,
+    /// Whether a list was written as `- item` lines under its key rather
+    /// than as `[a, b]`.
+    #[serde(skip)]
+    pub block: bool,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A `---` YAML front-matter block. Its known keys become [`MetaEntry`]s;
+/// everything else, such as a site generator's `layout:`, is kept here.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    /// Lines that are not known metadata, verbatim and without their newline.
+    pub other: Vec<RawLine>,
+    pub span: Span,
+}
+
+/// A source line kept as written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawLine {
+    pub text: String,
+    pub span: Span,
+}
+
+/// Scalar value used by metadata entries and section attributes, or a list
+/// of them from front matter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::String(s) => s.parse().ok(),
+            Value::Boolean(_) | Value::List(_) => None,
+        }
+    }
+
+    /// The text of a string, or of each string in a list.
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            Value::String(s) => vec![s.as_str()],
+            Value::List(items) => items.iter().flat_map(Value::texts).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// [`Value::texts`], to be rewritten in place.
+    pub fn texts_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Value::String(s) => vec![s],
+            Value::List(items) => items.iter_mut().flat_map(Value::texts_mut).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => f.write_str(s),
+            Value::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The kinds of section the grammar recognises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    Verse,
+    Chorus,
+    Bridge,
+    PreChorus,
+    Outro,
+    Intro,
+    /// A `SECTION name` header, named by [`Section::name`].
+    Custom,
+}
+
+impl SectionKind {
+    /// The kinds with a keyword of their own.
+    pub const ALL: [SectionKind; 6] = [
+        SectionKind::Verse,
+        SectionKind::Chorus,
+        SectionKind::Bridge,
+        SectionKind::PreChorus,
+        SectionKind::Outro,
+        SectionKind::Intro,
+    ];
+
+    /// Parse a header keyword such as `PRE-CHORUS`.
+    pub fn from_keyword(keyword: &str) -> Option<SectionKind> {
+        SectionKind::ALL
+            .into_iter()
+            .find(|kind| kind.keyword() == keyword)
+    }
+
+    /// Header keyword as written in the DSL, e.g. `PRE-CHORUS`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            SectionKind::Verse => "VERSE",
+            SectionKind::Chorus => "CHORUS",
+            SectionKind::Bridge => "BRIDGE",
+            SectionKind::PreChorus => "PRE-CHORUS",
+            SectionKind::Outro => "OUTRO",
+            SectionKind::Intro => "INTRO",
+            SectionKind::Custom => "SECTION",
+        }
+    }
+
+    /// Human readable name, e.g. `Pre-Chorus`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SectionKind::Verse => "Verse",
+            SectionKind::Chorus => "Chorus",
+            SectionKind::Bridge => "Bridge",
+            SectionKind::PreChorus => "Pre-Chorus",
+            SectionKind::Outro => "Outro",
+            SectionKind::Intro => "Intro",
+            SectionKind::Custom => "Section",
+        }
+    }
+}
+
+/// A `name: value` attribute attached to a section header.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Value,
+}
+
+/// A section such as `VERSE[1]` together with its lines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub kind: SectionKind,
+    /// The name of a [`SectionKind::Custom`] section, e.g. `Post-Chorus`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub anchor: Option<Name>,
+    /// The `{id: ...}` that identifies the section across edits.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub attrs: Vec<Attribute>,
+    /// Set for `REPEAT` entries, whose lines are filled in by
+    /// [`crate::semantic::resolve`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub repeat: Option<Reference>,
+    pub lines: Vec<Line>,
+    /// `USE` directives expanded into `lines` by
+    /// [`crate::semantic::resolve`], nested ones included.
+    #[serde(skip)]
+    pub uses: Vec<Use>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A `USE` expanded while resolving a section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Use {
+    /// What was included, as written after `USE`: a macro name or `*anchor`.
+    pub target: String,
+    /// The macro or anchor whose lines held the `USE`; `None` when it was
+    /// written in the section itself.
+    pub within: Option<String>,
+}
+
+impl Section {
+    pub fn attr(&self, name: &str) -> Option<&Value> {
+        self.attrs.iter().find(|a| a.name == name).map(|a| &a.value)
+    }
+
+    /// Header as written in the DSL without attributes, e.g. `VERSE[2]`
+    /// or `SECTION Post-Chorus`.
+    pub fn header(&self) -> String {
+        let keyword = match &self.name {
+            Some(name) => format!("{} {}", self.kind.keyword(), name),
+            None => self.kind.keyword().to_string(),
+        };
+        match self.number {
+            Some(n) => format!("{}[{}]", keyword, n),
+            None => keyword,
+        }
+    }
+
+    /// Display label such as `Verse 2` or `Post-Chorus`.
+    pub fn label(&self) -> String {
+        let label = self.name.as_deref().unwrap_or(self.kind.label());
+        match self.number {
+            Some(n) => format!("{} {}", label, n),
+            None => label.to_string(),
+        }
+    }
+
+    /// Whether `name`, such as `chorus` or `verse 2`, names the section,
+    /// compared without regard to case. A name without a number names
+    /// every section of that name.
+    pub fn is_named(&self, name: &str) -> bool {
+        let label = self.label();
+        label.eq_ignore_ascii_case(name)
+            || label.rsplit_once(' ').is_some_and(|(kind, n)| {
+                n.parse::<u32>().is_ok() && kind.eq_ignore_ascii_case(name)
+            })
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.header())
+    }
+}
+
+/// A single lyric line and its inline annotations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    /// The words as written: `$name` references and `\{`-style escapes
+    /// are kept until the song is resolved.
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rhyme: Option<char>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stress: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub chords: Vec<String>,
+    /// `{pitch: E4, B4}` notes sung on the line, in scientific pitch
+    /// notation; see [`crate::voice`].
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub pitches: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub timing: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub anchor: Option<Name>,
+    /// The `{id: ...}` that identifies the line across edits.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub id: Option<String>,
+    /// `word{/ipa/}` overrides; `text` holds only the bare words.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub pronunciations: Vec<Pronunciation>,
+    /// `漢字{かんじ}` furigana; `text` holds only the kanji.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub rubies: Vec<Ruby>,
+    /// `____` and `<tbd: ...>` gaps still to be written, in order; the
+    /// `n`th stands for the `n`th run of underscores in `text`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub placeholders: Vec<Placeholder>,
+    /// `lo~~~ve` and `love{hold:1.2s}` marks, by word index into `text`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sustains: Vec<Sustain>,
+    /// `|` markers, as the index of the word each bar starts on. A marker
+    /// after the last word has an index equal to the word count.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub bars: Vec<usize>,
+    /// `·` beat markers within bars, indexed like `bars`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub beats: Vec<usize>,
+    /// Soft breaks, where the line continues on the next source line after
+    /// a trailing `\`, as the index of the word after the break.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub breaks: Vec<usize>,
+    /// `$name` references inside `text`, with absolute spans.
+    #[serde(skip)]
+    pub vars: Vec<Name>,
+    /// `/ alt: "..."` other takes of the line, kept beside the one sung
+    /// until one is chosen; see [`crate::takes`].
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub alternatives: Vec<Alternative>,
+    /// `!! todo: ...` notes written after the line.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub notes: Vec<Note>,
+    #[serde(skip_serializing_if = "Provenance::is_empty", default)]
+    pub provenance: Provenance,
+    /// `{translation.<tag>: "..."}` variants of the line, in source order.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub translations: Vec<Translation>,
+    /// Attributes outside the base grammar, such as `{mood: wistful}`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub annotations: Vec<Annotation>,
+    /// Set for `USE` lines, which carry no text of their own until resolved.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub include: Option<Reference>,
+    /// Set when a blank line separates this line from the one before it,
+    /// so the line starts a new stanza of its section.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub stanza_break: bool,
+    /// Indented `+` lines under this one: harmony or backing parts sung
+    /// with it, together making a line group led by this line.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub harmony: Vec<Line>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+impl Line {
+    /// IPA override for `word` on this line, compared case-insensitively.
+    pub fn pronunciation(&self, word: &str) -> Option<&str> {
+        self.pronunciations
+            .iter()
+            .find(|p| p.word.to_lowercase() == word.to_lowercase())
+            .map(|p| p.ipa.as_str())
+    }
+
+    /// `text[range]` cut into runs, each with the furigana written over it,
+    /// if any.
+    pub fn ruby_runs(&self, range: Range<usize>) -> Vec<(&str, Option<&str>)> {
+        let mut runs = Vec::new();
+        let mut cursor = range.start;
+        let mut rubies: Vec<&Ruby> = self
+            .rubies
+            .iter()
+            .filter(|r| r.at >= range.start && r.end() <= range.end)
+            .filter(|r| self.text.get(r.at..r.end()) == Some(r.base.as_str()))
+            .collect();
+        rubies.sort_by_key(|r| r.at);
+        for ruby in rubies {
+            if ruby.at < cursor {
+                continue;
+            }
+            if ruby.at > cursor {
+                runs.push((&self.text[cursor..ruby.at], None));
+            }
+            runs.push((ruby.base.as_str(), Some(ruby.reading.as_str())));
+            cursor = ruby.end();
+        }
+        if cursor < range.end {
+            runs.push((&self.text[cursor..range.end], None));
+        }
+        runs
+    }
+
+    /// The text cut at its soft breaks, as it was laid out in the source.
+    pub fn segments(&self) -> Vec<&str> {
+        let ranges = word_ranges(&self.text);
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for &word in &self.breaks {
+            if let Some(range) = ranges.get(word).filter(|r| r.start > start) {
+                segments.push(self.text[start..range.start].trim_end());
+                start = range.start;
+            }
+        }
+        segments.push(&self.text[start..]);
+        segments
+    }
+
+    /// The variant of this line tagged `lang`, compared case-insensitively.
+    pub fn translation(&self, lang: &str) -> Option<&str> {
+        self.translations
+            .iter()
+            .find(|t| t.lang.eq_ignore_ascii_case(lang))
+            .map(|t| t.text.as_str())
+    }
+
+    /// The romanized variant, the first tagged with the `Latn` script.
+    pub fn romanization(&self) -> Option<&Translation> {
+        self.translations.iter().find(|t| t.is_romanization())
+    }
+
+    /// Sustain mark on the word at `index`, if any.
+    pub fn sustain(&self, index: usize) -> Option<Hold> {
+        self.sustains
+            .iter()
+            .find(|s| s.word == index)
+            .map(|s| s.hold)
+    }
+}
+
+/// Where a line came from, for tracking who wrote what in co-writes: set
+/// with `{author: Sam, source: demo 2, confidence: 0.6, edited: 2024-05-01}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub author: Option<String>,
+    /// Where the words were taken from, such as a demo, a session or a book.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub source: Option<String>,
+    /// How settled the line is, from 0 (a placeholder) to 1 (final).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub confidence: Option<f64>,
+    /// The day the line last changed, as `YYYY-MM-DD`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub edited: Option<String>,
+}
+
+impl Provenance {
+    pub fn is_empty(&self) -> bool {
+        *self == Provenance::default()
+    }
+}
+
+/// The line in another language or script, such as its romanization
+/// under `ja-Latn`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Translation {
+    /// A BCP 47 language tag, e.g. `es` or `zh-Latn`.
+    pub lang: String,
+    pub text: String,
+}
+
+impl Translation {
+    /// Whether the tag has the `Latn` script, as a romanization's does.
+    pub fn is_romanization(&self) -> bool {
+        self.lang
+            .split('-')
+            .skip(1)
+            .any(|subtag| subtag.eq_ignore_ascii_case("latn"))
+    }
+}
+
+/// A line attribute the base grammar does not define, kept by name and
+/// raw value for a project's `extra.pest` rules to check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub name: String,
+    pub value: String,
+    /// The span of the value alone.
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// Another take of a line, e.g. `/ alt: "other version"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alternative {
+    pub text: String,
+    /// `$name` references inside `text`, with absolute spans.
+    #[serde(skip)]
+    pub vars: Vec<Name>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// A note left on a line for later, e.g. `!! todo: find a better rhyme`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub text: String,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    Todo,
+    Idea,
+    Done,
+}
+
+impl NoteKind {
+    pub fn from_keyword(keyword: &str) -> Option<NoteKind> {
+        match keyword {
+            "todo" => Some(NoteKind::Todo),
+            "idea" => Some(NoteKind::Idea),
+            "done" => Some(NoteKind::Done),
+            _ => None,
+        }
+    }
+
+    /// The kind as written after `!!`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            NoteKind::Todo => "todo",
+            NoteKind::Idea => "idea",
+            NoteKind::Done => "done",
+        }
+    }
+
+    /// Whether the note still asks for work; `done` notes are resolved.
+    pub fn is_open(&self) -> bool {
+        !matches!(self, NoteKind::Done)
+    }
+}
+
+/// A meter such as `6/8`: `beats` per bar, each one `unit`th of a whole note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSignature {
+    pub beats: u32,
+    pub unit: u32,
+}
+
+impl TimeSignature {
+    pub const COMMON: TimeSignature = TimeSignature { beats: 4, unit: 4 };
+
+    /// Parse `beats/unit`; the unit must be a power of two.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (beats, unit) = text.trim().split_once('/')?;
+        let beats: u32 = beats.parse().ok()?;
+        let unit: u32 = unit.parse().ok()?;
+        (beats > 0 && unit.is_power_of_two() && unit <= 64).then_some(TimeSignature { beats, unit })
+    }
+
+    /// Length of one bar in quarter notes, the unit of BPM.
+    pub fn bar_quarters(&self) -> f64 {
+        self.beats as f64 * 4.0 / self.unit as f64
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.beats, self.unit)
+    }
+}
+
+/// A tempo change at a point in the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TempoChange {
+    pub bpm: f64,
+    pub at: Timestamp,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// How a song borrows from another work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditKind {
+    /// Its melody or words re-recorded.
+    Interpolation,
+    /// A piece of its recording.
+    Sample,
+}
+
+impl CreditKind {
+    /// Keyword as written in a `credits:` entry.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            CreditKind::Interpolation => "interpolation",
+            CreditKind::Sample => "sample",
+        }
+    }
+}
+
+/// An entry of the `credits:` block: a work the song interpolates or
+/// samples, such as
+/// `interpolation "Stand By Me" {writers: "Ben E. King", lines: *hook}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credit {
+    pub kind: CreditKind,
+    /// The title of the original work.
+    pub work: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub writers: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub publishers: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub year: Option<String>,
+    /// The anchor of the first line that uses the work: a line, or a
+    /// section for all of its lines.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub from: Option<Reference>,
+    /// The anchor of the last line, for a `*from..*to` range.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub to: Option<Reference>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// An entry of the `history:` block: a change to the song and when it was
+/// made, such as `2026-10-15 09:30 UTC "new bridge"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the change was made, as `YYYY-MM-DD HH:MM UTC`.
+    pub when: String,
+    pub message: String,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// An IPA pronunciation given for one word of a line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pronunciation {
+    pub word: String,
+    pub ipa: String,
+    /// Index of the word in [`words`] of the line text.
+    #[serde(default)]
+    pub index: usize,
+}
+
+/// What a `<tbd>` placeholder leaves in the line text, and the shortest
+/// run of underscores that is a placeholder.
+pub const BLANK: &str = "____";
+
+/// Words still to be written, as `____` or `<tbd: rhyme with "river">`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placeholder {
+    /// What the words should do, from `<tbd: ...>`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub hint: Option<String>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+/// Byte ranges of the placeholder blanks in `text`: runs of at least four
+/// underscores.
+pub fn blank_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find(BLANK) {
+        let start = rest + start;
+        let end = text[start..]
+            .find(|c| c != '_')
+            .map_or(text.len(), |e| start + e);
+        ranges.push(start..end);
+        rest = end;
+    }
+    ranges
+}
+
+/// Furigana read over a run of kanji, written `漢字{かんじ}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruby {
+    pub base: String,
+    pub reading: String,
+    /// Byte offset of `base` in the line text.
+    #[serde(default)]
+    pub at: usize,
+}
+
+impl Ruby {
+    /// Byte offset just past `base` in the line text.
+    pub fn end(&self) -> usize {
+        self.at + self.base.len()
+    }
+}
+
+/// A word sung for longer than its even share of the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sustain {
+    /// Index of the word in [`words`] of the line text.
+    pub word: usize,
+    pub hold: Hold,
+    /// Where in the word, in bytes, a melisma written inside it falls, as
+    /// after `lo` in `lo~~~ve`; unset for a mark after the word.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub within: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hold {
+    /// Extra beats from a melisma: `lo~~~ve` holds for three more.
+    Beats(u32),
+    /// A fixed duration from `{hold:1.2s}`.
+    Fixed(Timestamp),
+}
+
+/// Characters a lyric line writes with a backslash, e.g. `\{`, because
+/// they would otherwise be read as markup.
+pub const ESCAPABLE: [char; 12] = ['\\', '{', '}', '[', ']', '|', '~', '$', '&', '!', ':', '·'];
+
+/// Characters that join the parts of a word without being words themselves.
+const JOINERS: [char; 3] = ['\'', '’', '-'];
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || JOINERS.contains(&c)
+}
+
+/// Byte ranges of the words in `text`. Inner apostrophes and hyphens belong
+/// to the word ("don't", "twenty-one"); leading and trailing ones do not.
+pub fn word_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find(is_word_char) {
+        let start = rest + start;
+        let end = text[start..]
+            .find(|c| !is_word_char(c))
+            .map_or(text.len(), |e| start + e);
+        let word = &text[start..end];
+        let leading = word.len() - word.trim_start_matches(JOINERS).len();
+        let trimmed = word.trim_matches(JOINERS);
+        if !trimmed.is_empty() {
+            ranges.push(start + leading..start + leading + trimmed.len());
+        }
+        rest = end;
+    }
+    ranges
+}
+
+/// The words of `text`, as split by [`word_ranges`].
+pub fn words(text: &str) -> impl Iterator<Item = &str> {
+    word_ranges(text).into_iter().map(move |r| &text[r])
+}
+
+/// A `minutes:seconds` position in the recording, stored in milliseconds.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Timestamp {
+    pub millis: u64,
+}
+
+impl Timestamp {
+    pub fn from_millis(millis: u64) -> Self {
+        Timestamp { millis }
+    }
+
+    /// The time `minutes` and `seconds` in, or `None` when it is too late to
+    /// count in milliseconds.
+    pub fn from_parts(minutes: u64, seconds: f64) -> Option<Self> {
+        let fraction = (seconds * 1000.0).round();
+        if !(0.0..u64::MAX as f64).contains(&fraction) {
+            return None;
+        }
+        let millis = minutes.checked_mul(60_000)?.checked_add(fraction as u64)?;
+        Some(Timestamp { millis })
+    }
+
+    pub fn as_secs_f64(&self) -> f64 {
+        self.millis as f64 / 1000.0
+    }
+}
+
+impl fmt::Display for Timestamp {
+    /// Formats as `m:ss.cc`, the notation accepted by the `timing` attribute.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let centis = self.millis.saturating_add(5) / 10;
+        write!(
+            f,
+            "{}:{:02}.{:02}",
+            centis / 6000,
+            (centis / 100) % 60,
+            centis % 100
+        )
+    }
+}
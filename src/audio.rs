@@ -0,0 +1,242 @@
+//! Reading recordings and finding the phrases sung in them.
+//!
+//! Only uncompressed WAV is read: 8, 16, 24 or 32-bit integer PCM and
+//! 32-bit float, any sample rate and channel count, mixed down to mono.
+//! Phrases are found from the loudness of short frames: a phrase starts at
+//! an onset, where the level rises well above the track's noise floor, and
+//! ends where it falls back for longer than a breath.
+
+use thiserror::Error;
+
+/// Length of the frames loudness is measured over, in seconds.
+const FRAME: f64 = 0.02;
+/// Quieter gaps shorter than this are breaths within a phrase.
+const MIN_GAP: f64 = 0.3;
+/// Louder stretches shorter than this are clicks rather than singing.
+const MIN_PHRASE: f64 = 0.15;
+/// How far between the noise floor and the loud passages a frame must be
+/// to count as sung.
+const THRESHOLD: f32 = 0.2;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AudioError {
+    #[error("not a WAV file")]
+    NotWav,
+    #[error("the WAV file is cut short")]
+    Truncated,
+    #[error("the WAV file has no `{0}` chunk")]
+    MissingChunk(&'static str),
+    #[error("unsupported WAV encoding: {0}")]
+    Unsupported(String),
+}
+
+/// A recording, mixed down to mono samples between -1 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+/// A stretch of the recording with singing in it, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Phrase {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Phrase {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16, AudioError> {
+    let b = bytes.get(at..at + 2).ok_or(AudioError::Truncated)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32, AudioError> {
+    let b = bytes.get(at..at + 4).ok_or(AudioError::Truncated)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Decode one sample of `bits` bits in `format` (1 for PCM, 3 for float).
+fn sample(bytes: &[u8], format: u16, bits: u16) -> f32 {
+    match (format, bits) {
+        (1, 8) => (bytes[0] as f32 - 128.0) / 128.0,
+        (1, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32_768.0,
+        (1, 24) => {
+            let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            value as f32 / 8_388_608.0
+        }
+        (1, 32) => {
+            i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2_147_483_648.0
+        }
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+/// The encoding of a WAV file's samples.
+struct Format {
+    /// 1 for integer PCM, 3 for float.
+    encoding: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+}
+
+impl Format {
+    /// Bytes per frame of one sample for every channel.
+    fn block(&self) -> usize {
+        self.bits as usize / 8 * self.channels as usize
+    }
+}
+
+/// The format and the sample data of a WAV file.
+fn chunks(bytes: &[u8]) -> Result<(Format, &[u8]), AudioError> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(AudioError::NotWav);
+    }
+    let mut format = None;
+    let mut data = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let size = u32_at(bytes, at + 4)? as usize;
+        let body = at + 8;
+        // Recorders that stream often leave the data size unset.
+        let end = body.saturating_add(size).min(bytes.len());
+        match &bytes[at..at + 4] {
+            b"fmt " => format = Some(&bytes[body..end]),
+            b"data" => data = Some(&bytes[body..end]),
+            _ => {}
+        }
+        at = body.saturating_add(size).saturating_add(size % 2);
+    }
+    let format = format.ok_or(AudioError::MissingChunk("fmt "))?;
+    let data = data.ok_or(AudioError::MissingChunk("data"))?;
+
+    let mut encoding = u16_at(format, 0)?;
+    let channels = u16_at(format, 2)?;
+    let sample_rate = u32_at(format, 4)?;
+    let bits = u16_at(format, 14)?;
+    if encoding == 0xFFFE {
+        // WAVE_FORMAT_EXTENSIBLE names the encoding in its sub-format.
+        encoding = u16_at(format, 24)?;
+    }
+    let supported = matches!((encoding, bits), (1, 8 | 16 | 24 | 32) | (3, 32));
+    if !supported || channels == 0 || sample_rate == 0 {
+        return Err(AudioError::Unsupported(format!(
+            "format {} with {} bit(s) and {} channel(s)",
+            encoding, bits, channels
+        )));
+    }
+    let format = Format {
+        encoding,
+        channels,
+        sample_rate,
+        bits,
+    };
+    Ok((format, data))
+}
+
+/// Length in seconds of a WAV file, from its header, without decoding it.
+pub fn wav_length(bytes: &[u8]) -> Result<f64, AudioError> {
+    let (format, data) = chunks(bytes)?;
+    Ok((data.len() / format.block()) as f64 / format.sample_rate as f64)
+}
+
+impl Audio {
+    /// Decode a WAV file.
+    pub fn read_wav(bytes: &[u8]) -> Result<Audio, AudioError> {
+        let (format, data) = chunks(bytes)?;
+        let Format {
+            encoding,
+            channels,
+            sample_rate,
+            bits,
+        } = format;
+        let width = bits as usize / 8;
+        let samples = data
+            .chunks_exact(format.block())
+            .map(|frame| {
+                let sum: f32 = frame
+                    .chunks_exact(width)
+                    .map(|bytes| sample(bytes, encoding, bits))
+                    .sum();
+                sum / channels as f32
+            })
+            .collect();
+        Ok(Audio {
+            sample_rate,
+            samples,
+        })
+    }
+
+    /// Length of the recording in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    /// Samples per [`FRAME`].
+    fn frame(&self) -> usize {
+        ((self.sample_rate as f64 * FRAME) as usize).max(1)
+    }
+
+    /// Root-mean-square level of each frame.
+    fn levels(&self) -> Vec<f32> {
+        self.samples
+            .chunks(self.frame())
+            .map(|chunk| (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt())
+            .collect()
+    }
+
+    /// The phrases sung, in order. A silent recording has none, and one
+    /// without quiet passages is a single phrase.
+    pub fn phrases(&self) -> Vec<Phrase> {
+        let levels = self.levels();
+        if levels.is_empty() {
+            return Vec::new();
+        }
+        let mut sorted = levels.clone();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+        let (floor, loud) = (percentile(10), percentile(95));
+        if loud < 1e-4 {
+            return Vec::new();
+        }
+        let duration = self.duration();
+        if loud <= floor * 1.5 {
+            return vec![Phrase {
+                start: 0.0,
+                end: duration,
+            }];
+        }
+        let threshold = floor + (loud - floor) * THRESHOLD;
+
+        let seconds_per_frame = self.frame() as f64 / self.sample_rate as f64;
+        let mut phrases: Vec<Phrase> = Vec::new();
+        let mut start = None;
+        for (i, level) in levels.iter().chain([&0.0]).enumerate() {
+            let at = i as f64 * seconds_per_frame;
+            match (start, *level > threshold) {
+                (None, true) => start = Some(at),
+                (Some(from), false) => {
+                    start = None;
+                    match phrases.last_mut() {
+                        Some(last) if from - last.end < MIN_GAP => last.end = at,
+                        _ => phrases.push(Phrase {
+                            start: from,
+                            end: at,
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        phrases.retain(|p| p.duration() >= MIN_PHRASE);
+        for phrase in &mut phrases {
+            phrase.end = phrase.end.min(duration);
+        }
+        phrases
+    }
+}
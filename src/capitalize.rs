@@ -0,0 +1,171 @@
+//! Capitalization styles for lyric lines.
+//!
+//! - `sentence`: the first word of the line, and of each sentence in it,
+//!   starts with a capital; every other letter is lower case.
+//! - `title`: every word starts with a capital except short articles,
+//!   conjunctions and prepositions inside the line.
+//! - `lower`: everything in lower case.
+//!
+//! Protected words, "I" and its contractions always among them, keep the
+//! spelling given for them in every style, so names survive as written.
+//! `$name` references are left alone.
+
+use crate::ast::{word_ranges, Line, Song};
+
+/// Words title case leaves in lower case unless they start or end the line.
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "so", "the",
+    "to", "up", "yet",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStyle {
+    Sentence,
+    Title,
+    Lower,
+}
+
+impl CaseStyle {
+    pub const ALL: [CaseStyle; 3] = [CaseStyle::Sentence, CaseStyle::Title, CaseStyle::Lower];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CaseStyle::Sentence => "sentence",
+            CaseStyle::Title => "title",
+            CaseStyle::Lower => "lower",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<CaseStyle> {
+        CaseStyle::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Applies a [`CaseStyle`], keeping protected words as spelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capitalizer {
+    style: CaseStyle,
+    protected: Vec<String>,
+}
+
+impl Capitalizer {
+    /// A capitalizer for `style` that keeps "I" and every word of
+    /// `protected` as spelled there.
+    pub fn new(style: CaseStyle, protected: &[String]) -> Capitalizer {
+        let mut words = vec!["I".to_string()];
+        words.extend(protected.iter().map(|w| w.trim().to_string()));
+        words.retain(|w| !w.is_empty());
+        Capitalizer {
+            style,
+            protected: words,
+        }
+    }
+
+    pub fn style(&self) -> CaseStyle {
+        self.style
+    }
+
+    /// The protected spelling of `word`, compared case-insensitively, with
+    /// any `'s`-style ending after it.
+    fn protect(&self, word: &str) -> Option<String> {
+        let stem_len = word.find(['\'', '’']).unwrap_or(word.len());
+        let (stem, ending) = word.split_at(stem_len);
+        let spelled = self
+            .protected
+            .iter()
+            .find(|p| p.to_lowercase() == stem.to_lowercase())?;
+        Some(format!("{}{}", spelled, ending.to_lowercase()))
+    }
+
+    /// `text` in the capitalizer's style.
+    pub fn apply(&self, text: &str) -> String {
+        self.rewrite(text).0
+    }
+
+    /// `text` in the style, with the end of each word in the old and the
+    /// new text.
+    fn rewrite(&self, text: &str) -> (String, Vec<(usize, usize)>) {
+        let ranges = word_ranges(text);
+        let mut out = String::with_capacity(text.len());
+        let mut ends = Vec::new();
+        let mut cursor = 0;
+        let mut sentence_start = true;
+        for (i, range) in ranges.iter().enumerate() {
+            let gap = &text[cursor..range.start];
+            out.push_str(gap);
+            if i > 0 && gap.contains(['.', '!', '?']) {
+                sentence_start = true;
+            }
+            let word = &text[range.clone()];
+            if gap.ends_with('$') || gap.ends_with("${") {
+                out.push_str(word);
+            } else {
+                let first = i == 0;
+                let last = i + 1 == ranges.len();
+                out.push_str(&self.word(word, first, last, sentence_start));
+            }
+            sentence_start = false;
+            cursor = range.end;
+            ends.push((range.end, out.len()));
+        }
+        out.push_str(&text[cursor..]);
+        (out, ends)
+    }
+
+    fn word(&self, word: &str, first: bool, last: bool, sentence_start: bool) -> String {
+        if let Some(spelled) = self.protect(word) {
+            return spelled;
+        }
+        let lower = word.to_lowercase();
+        let capital = match self.style {
+            CaseStyle::Sentence => sentence_start,
+            CaseStyle::Title => first || last || !MINOR_WORDS.contains(&lower.as_str()),
+            CaseStyle::Lower => false,
+        };
+        match capital {
+            true => capitalized(&lower),
+            false => lower,
+        }
+    }
+
+    /// A line in the style, with its furigana moved to match.
+    pub fn apply_line(&self, line: &Line) -> Line {
+        let mut line = line.clone();
+        let (text, ends) = self.rewrite(&line.text);
+        for ruby in &mut line.rubies {
+            let (old, new) = ends
+                .iter()
+                .rev()
+                .find(|(old, _)| *old <= ruby.at)
+                .copied()
+                .unwrap_or((0, 0));
+            ruby.at = ruby.at - old + new;
+        }
+        line.text = text;
+        line
+    }
+
+    /// `song` with its lyric lines, and the lines of its macros, in the
+    /// style.
+    pub fn apply_song(&self, song: &Song) -> Song {
+        let mut song = song.clone();
+        let lines = song
+            .sections
+            .iter_mut()
+            .flat_map(|s| s.lines.iter_mut())
+            .chain(song.macros.iter_mut().flat_map(|m| m.lines.iter_mut()));
+        for line in lines {
+            *line = self.apply_line(line);
+        }
+        song
+    }
+}
+
+/// `word` with its first letter upper case.
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
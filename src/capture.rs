@@ -0,0 +1,236 @@
+//! Quick capture of lyric ideas into a project inbox.
+//!
+//! Captured text is kept as song source whenever possible. Text that
+//! already parses as the DSL is stored unchanged; anything else is treated
+//! as plain lyrics and converted: stanzas separated by blank lines become
+//! sections, `[Chorus]`-style labels as found on lyric sites name them, and
+//! a stanza that recurs word for word becomes the chorus.
+
+use crate::ast::SectionKind;
+use crate::format::escape_text;
+use crate::parser::parse_song;
+use std::fmt::Write;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CaptureError {
+    #[error("nothing to capture: the text is empty")]
+    Empty,
+}
+
+/// What kind of text was captured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Detected {
+    /// Text that parsed as a song.
+    Song {
+        title: Option<String>,
+        sections: usize,
+        lines: usize,
+    },
+    /// Plain lyrics converted into a song.
+    Plain { stanzas: usize, lines: usize },
+    /// Plain text that could not be converted; kept verbatim.
+    Text { lines: usize },
+}
+
+impl Detected {
+    /// A one-line description for the user and the inbox entry header.
+    pub fn describe(&self) -> String {
+        match self {
+            Detected::Song {
+                title,
+                sections,
+                lines,
+            } => {
+                let title = title
+                    .as_ref()
+                    .map_or(String::new(), |t| format!(" \"{}\"", t));
+                format!(
+                    "DSL song{}, {} section(s), {} line(s)",
+                    title, sections, lines
+                )
+            }
+            Detected::Plain { stanzas, lines } => format!(
+                "plain lyrics, {} stanza(s), {} line(s), converted to DSL",
+                stanzas, lines
+            ),
+            Detected::Text { lines } => format!("plain text, {} line(s), kept as is", lines),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captured {
+    pub detected: Detected,
+    /// The text to store: song source, or the original text.
+    pub text: String,
+}
+
+/// Classify `text` and convert plain lyrics to song source.
+pub fn capture(text: &str) -> Result<Captured, CaptureError> {
+    let text = text.replace("\r\n", "\n");
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(CaptureError::Empty);
+    }
+    let source = format!("{}\n", trimmed);
+    if let Ok(song) = parse_song(&source) {
+        if !song.sections.is_empty() {
+            let detected = Detected::Song {
+                title: song.title(),
+                sections: song.sections.len(),
+                lines: song.lines().count(),
+            };
+            return Ok(Captured {
+                detected,
+                text: source,
+            });
+        }
+    }
+
+    let stanzas = stanzas(trimmed);
+    let lines = stanzas.iter().map(|s| s.lines.len()).sum();
+    let imported = import_plain(trimmed);
+    if parse_song(&imported).is_ok() {
+        return Ok(Captured {
+            detected: Detected::Plain {
+                stanzas: stanzas.len(),
+                lines,
+            },
+            text: imported,
+        });
+    }
+    Ok(Captured {
+        detected: Detected::Text {
+            lines: trimmed.lines().count(),
+        },
+        text: source,
+    })
+}
+
+struct Stanza {
+    label: Option<SectionKind>,
+    lines: Vec<String>,
+}
+
+/// Blank-line separated stanzas, with a leading `[Label]` line taken as
+/// the section kind.
+fn stanzas(text: &str) -> Vec<Stanza> {
+    let mut stanzas: Vec<Stanza> = Vec::new();
+    let mut current: Option<Stanza> = None;
+    for raw in text.lines().map(str::trim) {
+        if raw.is_empty() {
+            stanzas.extend(current.take());
+            continue;
+        }
+        if let Some(label) = bracket_label(raw) {
+            stanzas.extend(current.take());
+            current = Some(Stanza {
+                label: Some(label),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        current
+            .get_or_insert_with(|| Stanza {
+                label: None,
+                lines: Vec::new(),
+            })
+            .lines
+            .push(clean_line(raw));
+    }
+    stanzas.extend(current);
+    stanzas.retain(|s| !s.lines.is_empty());
+    stanzas
+}
+
+/// The section kind named by a `[Chorus]` or `[Verse 2]` line.
+fn bracket_label(line: &str) -> Option<SectionKind> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let word = inner
+        .split(|c: char| c.is_whitespace() || c == ':' || c.is_ascii_digit())
+        .next()?
+        .to_lowercase();
+    SectionKind::ALL
+        .into_iter()
+        .find(|kind| kind.label().to_lowercase() == word || kind.keyword().to_lowercase() == word)
+}
+
+/// A plain lyric line with its runs of whitespace collapsed.
+fn clean_line(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Convert plain lyrics to song source. The first line becomes the title,
+/// with its double quotes made single since a metadata string cannot hold
+/// them; the lyric lines have their markup characters escaped.
+pub fn import_plain(text: &str) -> String {
+    let stanzas = stanzas(text);
+    let title = stanzas
+        .first()
+        .and_then(|s| s.lines.first())
+        .map(|l| l.replace('"', "'"))
+        .unwrap_or_else(|| "Untitled".to_string());
+    let mut out = format!("title: \"{}\"\n", title);
+
+    let mut verses = 0;
+    for (i, stanza) in stanzas.iter().enumerate() {
+        let repeated = stanzas
+            .iter()
+            .enumerate()
+            .any(|(j, other)| j != i && other.lines == stanza.lines);
+        let kind = stanza.label.unwrap_or(match repeated {
+            true => SectionKind::Chorus,
+            false => SectionKind::Verse,
+        });
+        out.push('\n');
+        match kind {
+            SectionKind::Verse => {
+                verses += 1;
+                let _ = writeln!(out, "VERSE[{}]", verses);
+            }
+            kind => {
+                let _ = writeln!(out, "{}", kind.keyword());
+            }
+        }
+        for line in &stanza.lines {
+            let _ = writeln!(out, "{}", escape_text(line));
+        }
+    }
+    out
+}
+
+/// An inbox entry: a header line with the capture time and what was
+/// detected, then the captured text.
+pub fn inbox_entry(captured: &Captured, when: &str) -> String {
+    format!(
+        "## {} · {}\n{}\n",
+        when,
+        captured.detected.describe(),
+        captured.text
+    )
+}
+
+/// `YYYY-MM-DD HH:MM UTC` for seconds since the Unix epoch.
+pub fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let minutes = secs % 86_400 / 60;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
@@ -0,0 +1,65 @@
+//! The monospaced bitmap font cards are drawn with.
+//!
+//! `font.bin` holds DejaVu Sans Mono (Bitstream Vera license) rasterized to
+//! one-bit glyphs of [`CELL_WIDTH`] × [`CELL_HEIGHT`] pixels, three bytes per
+//! row, for the characters in [`RANGES`] in order. Text is drawn at other
+//! sizes by sampling the glyphs several times per output pixel, which gives
+//! anti-aliased edges.
+
+const FONT: &[u8] = include_bytes!("font.bin");
+
+pub const CELL_WIDTH: usize = 24;
+pub const CELL_HEIGHT: usize = 48;
+
+const ROW_BYTES: usize = CELL_WIDTH / 8;
+const GLYPH_BYTES: usize = ROW_BYTES * CELL_HEIGHT;
+
+/// Printable ASCII, Latin-1 and the typographic punctuation common in lyrics.
+const RANGES: &[(char, char)] = &[
+    (' ', '~'),
+    ('¡', 'ÿ'),
+    ('–', '—'),
+    ('‘', '’'),
+    ('“', '”'),
+    ('…', '…'),
+];
+
+/// Index of `c` in the font, with `?` standing in for missing characters.
+fn glyph_index(c: char) -> usize {
+    let mut index = 0;
+    for &(first, last) in RANGES {
+        if (first..=last).contains(&c) {
+            return index + (c as usize - first as usize);
+        }
+        index += last as usize - first as usize + 1;
+    }
+    glyph_index('?')
+}
+
+/// Whether the pixel at `x`, `y` of the glyph for `c` is set.
+fn ink(c: char, x: usize, y: usize) -> bool {
+    if x >= CELL_WIDTH || y >= CELL_HEIGHT {
+        return false;
+    }
+    let offset = glyph_index(c) * GLYPH_BYTES + y * ROW_BYTES + x / 8;
+    FONT[offset] & (0x80 >> (x % 8)) != 0
+}
+
+/// Samples per output pixel along each axis.
+const SAMPLES: usize = 4;
+
+/// Coverage from 0 to 1 of the output pixel at (`x`, `y`) of a glyph drawn
+/// in a cell `scale` times the font's own size.
+pub fn coverage(c: char, x: usize, y: usize, scale: f64) -> f64 {
+    let mut hits = 0;
+    for sy in 0..SAMPLES {
+        for sx in 0..SAMPLES {
+            let fx = (x as f64 + (sx as f64 + 0.5) / SAMPLES as f64) / scale;
+            let fy = (y as f64 + (sy as f64 + 0.5) / SAMPLES as f64) / scale;
+            if ink(c, fx as usize, fy as usize) {
+                hits += 1;
+            }
+        }
+    }
+    hits as f64 / (SAMPLES * SAMPLES) as f64
+}
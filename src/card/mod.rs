@@ -0,0 +1,205 @@
+//! Image cards: a short excerpt of a song set on a styled background with
+//! its title and artist, rendered to PNG for posting lyric quotes.
+//!
+//! Everything is drawn here without a graphics library: a gradient
+//! background, an accent bar beside the quote, and text from the bundled
+//! bitmap [`font`], sized as large as the excerpt allows.
+
+mod font;
+mod png;
+
+use crate::ast::Song;
+use crate::teleprompter::wrap;
+use font::{coverage, CELL_HEIGHT, CELL_WIDTH};
+use std::collections::HashSet;
+
+/// Color scheme of a card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardStyle {
+    Dark,
+    Light,
+    Sunset,
+}
+
+impl CardStyle {
+    pub const NAMES: [&'static str; 3] = ["dark", "light", "sunset"];
+
+    pub fn parse(name: &str) -> Option<CardStyle> {
+        match name {
+            "dark" => Some(CardStyle::Dark),
+            "light" => Some(CardStyle::Light),
+            "sunset" => Some(CardStyle::Sunset),
+            _ => None,
+        }
+    }
+
+    /// Background colors at the top and bottom, the text color and the
+    /// accent used for the bar and attribution.
+    fn palette(&self) -> ([u8; 3], [u8; 3], [u8; 3], [u8; 3]) {
+        match self {
+            CardStyle::Dark => ([20, 20, 30], [36, 36, 58], [245, 245, 245], [240, 180, 90]),
+            CardStyle::Light => (
+                [250, 247, 240],
+                [236, 230, 218],
+                [30, 30, 30],
+                [180, 80, 60],
+            ),
+            CardStyle::Sunset => (
+                [255, 126, 95],
+                [106, 48, 147],
+                [255, 255, 255],
+                [255, 228, 181],
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardOptions {
+    pub style: CardStyle,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for CardOptions {
+    fn default() -> Self {
+        CardOptions {
+            style: CardStyle::Dark,
+            width: 1080,
+            height: 1080,
+        }
+    }
+}
+
+/// Parse `12-15` or `12` into an inclusive range of line numbers.
+pub fn parse_line_range(spec: &str) -> Option<(usize, usize)> {
+    let (first, last) = spec.split_once('-').unwrap_or((spec, spec));
+    let first: usize = first.trim().parse().ok()?;
+    let last: usize = last.trim().parse().ok()?;
+    (first >= 1 && first <= last).then_some((first, last))
+}
+
+/// The lyric lines written on source lines `first..=last` of `source`, in
+/// the resolved `song`. A line replayed by `REPEAT` or `USE` appears once.
+pub fn excerpt(song: &Song, source: &str, first: usize, last: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    song.lines()
+        .map(|(_, line)| line)
+        .filter(|line| (first..=last).contains(&line.span.line_col(source).0))
+        .filter(|line| seen.insert(line.span.start))
+        .map(|line| line.text.clone())
+        .collect()
+}
+
+/// "Title — Artist", or whichever of the two the song has.
+pub fn attribution(song: &Song) -> String {
+    match (song.title(), song.artist()) {
+        (Some(title), Some(artist)) => format!("{} — {}", title, artist),
+        (Some(title), None) => title,
+        (None, Some(artist)) => artist,
+        (None, None) => String::new(),
+    }
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn gradient(width: usize, height: usize, top: [u8; 3], bottom: [u8; 3]) -> Canvas {
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let t = y as f64 / height.max(2).saturating_sub(1) as f64;
+            let color = mix(top, bottom, t);
+            pixels.extend(std::iter::repeat_n(color, width));
+        }
+        Canvas {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: [u8; 3], alpha: f64) {
+        if x < self.width && y < self.height && alpha > 0.0 {
+            let pixel = &mut self.pixels[y * self.width + x];
+            *pixel = mix(*pixel, color, alpha);
+        }
+    }
+
+    fn rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Draw `text` with the top of its line box at `top`, in cells `size`
+    /// pixels tall.
+    fn text(&mut self, x: usize, top: usize, size: usize, color: [u8; 3], text: &str) {
+        let scale = size as f64 / CELL_HEIGHT as f64;
+        let advance = (CELL_WIDTH as f64 * scale).round() as usize;
+        for (i, c) in text.chars().enumerate() {
+            if c == ' ' {
+                continue;
+            }
+            let left = x + i * advance;
+            for gy in 0..size {
+                for gx in 0..advance {
+                    let alpha = coverage(c, gx, gy, scale);
+                    self.blend(left + gx, top + gy, color, alpha);
+                }
+            }
+        }
+    }
+}
+
+fn mix(a: [u8; 3], b: [u8; 3], t: f64) -> [u8; 3] {
+    let channel = |i: usize| (a[i] as f64 + (b[i] as f64 - a[i] as f64) * t).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+const MAX_SIZE: usize = 72;
+const MIN_SIZE: usize = 16;
+
+/// Render `lines` with `attribution` beneath them as a PNG image. The text
+/// is set at the largest size, up to 72 pixel lines, at which the wrapped
+/// excerpt fits inside the margins.
+pub fn render(lines: &[String], attribution: &str, options: &CardOptions) -> Vec<u8> {
+    let (top, bottom, ink, accent) = options.style.palette();
+    let (width, height) = (options.width, options.height);
+    let mut canvas = Canvas::gradient(width, height, top, bottom);
+
+    let margin = width.min(height) / 11;
+    let text_left = margin + margin / 3;
+    let text_width = width.saturating_sub(text_left + margin);
+    let text_height = height.saturating_sub(2 * margin);
+
+    let layout = |size: usize| {
+        let columns = text_width * CELL_HEIGHT / (size * CELL_WIDTH);
+        let wrapped: Vec<String> = lines.iter().flat_map(|l| wrap(l, columns)).collect();
+        let small = (size / 2).max(MIN_SIZE);
+        let total = wrapped.len() * size * 4 / 3 + small * 2;
+        (wrapped, small, total)
+    };
+    let mut size = MAX_SIZE;
+    while size > MIN_SIZE && layout(size).2 > text_height {
+        size -= 2;
+    }
+    let (wrapped, small, total) = layout(size);
+
+    let mut y = margin + text_height.saturating_sub(total) / 2;
+    let quote_height = wrapped.len() * size * 4 / 3;
+    canvas.rect(margin, y, (margin / 10).max(2), quote_height, accent);
+    for line in &wrapped {
+        canvas.text(text_left, y, size, ink, line);
+        y += size * 4 / 3;
+    }
+    if !attribution.is_empty() {
+        canvas.text(text_left, y + small / 2, small, accent, attribution);
+    }
+    png::encode(canvas.width, canvas.height, &canvas.pixels)
+}
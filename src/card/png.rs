@@ -0,0 +1,181 @@
+//! PNG encoding for card images.
+//!
+//! Rows use the `Sub` filter, which turns flat and gently graded areas into
+//! runs of zeros, and the image data is compressed with a greedy LZ77 pass
+//! and the fixed Huffman codes of DEFLATE. That is far from optimal but
+//! keeps cards of solid backgrounds and text small without a zlib crate.
+
+use crate::export::zip::crc32;
+
+/// Encode `pixels`, `width` × `height` RGB triples, as a PNG file.
+pub fn encode(width: usize, height: usize, pixels: &[[u8; 3]]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    for row in pixels.chunks(width) {
+        raw.push(1); // Sub filter
+        let mut previous = [0u8; 3];
+        for pixel in row {
+            for channel in 0..3 {
+                raw.push(pixel[channel].wrapping_sub(previous[channel]));
+            }
+            previous = *pixel;
+        }
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib(&raw));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Bits written least significant first, as DEFLATE expects.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// A Huffman code, which DEFLATE stores most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        let reversed = code.reverse_bits() >> (32 - length);
+        self.bits(reversed, length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+/// Base lengths of the length codes 257..=285 and their extra bits.
+#[rustfmt::skip]
+const LENGTHS: [(u32, u32); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 1), (13, 1), (15, 1), (17, 1), (19, 2), (23, 2), (27, 2), (31, 2),
+    (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4),
+    (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+
+/// Base distances of the distance codes 0..=29 and their extra bits.
+#[rustfmt::skip]
+const DISTANCES: [(u32, u32); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2),
+    (17, 3), (25, 3), (33, 4), (49, 4), (65, 5), (97, 5), (129, 6), (193, 6),
+    (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9), (2049, 10), (3073, 10),
+    (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+
+/// Write a literal or length symbol with the fixed Huffman code.
+fn symbol(writer: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => writer.code(0x30 + symbol, 8),
+        144..=255 => writer.code(0x190 + symbol - 144, 9),
+        256..=279 => writer.code(symbol - 256, 7),
+        _ => writer.code(0xc0 + symbol - 280, 8),
+    }
+}
+
+/// The code index whose range contains `value`, and the extra bits.
+fn bucket(table: &[(u32, u32)], value: u32) -> (usize, u32, u32) {
+    let index = table
+        .iter()
+        .rposition(|&(base, _)| base <= value)
+        .expect("value in range");
+    let (base, extra) = table[index];
+    (index, value - base, extra)
+}
+
+const WINDOW: usize = 32 * 1024;
+const MAX_MATCH: usize = 258;
+const HASH_SIZE: usize = 1 << 15;
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.bits(1, 1); // final block
+    writer.bits(1, 2); // fixed Huffman codes
+
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) % HASH_SIZE
+    };
+    let mut heads = vec![usize::MAX; HASH_SIZE];
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + 3 <= data.len() {
+            let h = hash(i);
+            let candidate = heads[h];
+            heads[h] = i;
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let limit = MAX_MATCH.min(data.len() - i);
+                let length = (0..limit)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                if length >= 3 {
+                    best = (length, i - candidate);
+                }
+            }
+        }
+        match best {
+            (0, _) => {
+                symbol(&mut writer, data[i] as u32);
+                i += 1;
+            }
+            (length, distance) => {
+                let (code, extra_value, extra_bits) = bucket(&LENGTHS, length as u32);
+                symbol(&mut writer, 257 + code as u32);
+                writer.bits(extra_value, extra_bits);
+                let (code, extra_value, extra_bits) = bucket(&DISTANCES, distance as u32);
+                writer.code(code as u32, 5);
+                writer.bits(extra_value, extra_bits);
+                for k in 1..length {
+                    if i + k + 3 <= data.len() {
+                        heads[hash(i + k)] = i + k;
+                    }
+                }
+                i += length;
+            }
+        }
+    }
+    symbol(&mut writer, 256);
+    writer.finish()
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
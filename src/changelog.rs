@@ -0,0 +1,63 @@
+//! The changelog a song keeps of itself in its `history:` block.
+//!
+//! ```text
+//! history:
+//!   2026-09-30 21:04 UTC "first draft"
+//!   2026-10-15 09:30 UTC "new bridge"
+//! ```
+//!
+//! The tool maintains the block: `draft save` and commands run with
+//! `--log-change` append an entry with [`log_change`], which edits the
+//! source text in place so the rest of the file stays as written.
+
+use crate::ast::Song;
+use crate::capture::format_utc;
+use crate::parser::{parse_song, Rule};
+use std::time::SystemTime;
+
+/// The time now, as history entries write it.
+pub fn now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format_utc(secs)
+}
+
+/// `message` as it can be written between the quotes of an entry: on one
+/// line, with double quotes turned into single ones.
+pub fn entry_message(message: &str) -> String {
+    message
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('"', "'")
+}
+
+/// `source` with an entry for `message` made at `when` (`YYYY-MM-DD HH:MM
+/// UTC`) appended to its `history:` block, which is started at the end of
+/// the song's header when it has none yet.
+pub fn log_change(
+    source: &str,
+    when: &str,
+    message: &str,
+) -> Result<String, pest::error::Error<Rule>> {
+    let song = parse_song(source)?;
+    let entry = format!("  {} \"{}\"\n", when, entry_message(message));
+    let mut out = source.to_string();
+    match song.history.last() {
+        Some(last) => out.insert_str(last.span.end, &entry),
+        None => out.insert_str(header_end(&song), &format!("history:\n{}", entry)),
+    }
+    Ok(out)
+}
+
+/// Where the header of `song` ends: after its front matter and its last
+/// metadata entry, variable, tempo change or credit.
+fn header_end(song: &Song) -> usize {
+    let ends = song.metadata.iter().map(|m| m.span.end);
+    let ends = ends.chain(song.variables.iter().map(|v| v.span.end));
+    let ends = ends.chain(song.tempo_changes.iter().map(|t| t.span.end));
+    let ends = ends.chain(song.credits.iter().map(|c| c.span.end));
+    let ends = ends.chain(song.front_matter.iter().map(|f| f.span.end));
+    ends.max().unwrap_or(0)
+}
@@ -0,0 +1,126 @@
+//! Capo suggestions: which fret to clamp so that a song's chords can be
+//! played with familiar open shapes instead of barre chords.
+
+use super::diagram::Instrument;
+use super::Chord;
+use crate::ast::Song;
+
+/// Shapes most players can finger without a barre in standard tuning.
+const GUITAR_OPEN: &[&str] = &[
+    "A", "Am", "A7", "Am7", "Amaj7", "Asus2", "Asus4", "C", "C7", "Cmaj7", "Cadd9", "D", "Dm",
+    "D7", "Dm7", "Dmaj7", "Dsus2", "Dsus4", "E", "Em", "E7", "Em7", "Esus4", "G", "G7",
+];
+const UKULELE_OPEN: &[&str] = &[
+    "A", "Am", "A7", "Am7", "C", "C7", "Cmaj7", "D", "Dm", "D7", "Em", "F", "Fmaj7", "G", "G7",
+    "Gm",
+];
+
+/// A set of open chord shapes to aim for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeSet {
+    shapes: Vec<Chord>,
+}
+
+impl ShapeSet {
+    /// The usual open shapes for `instrument`.
+    pub fn open(instrument: Instrument) -> ShapeSet {
+        let names = match instrument {
+            Instrument::Guitar => GUITAR_OPEN,
+            Instrument::Ukulele => UKULELE_OPEN,
+        };
+        ShapeSet::parse(names).expect("built-in shapes parse")
+    }
+
+    /// A custom set such as `["C", "G", "D", "Em"]`; returns the first name
+    /// that is not a chord symbol as the error.
+    pub fn parse<S: AsRef<str>>(names: &[S]) -> Result<ShapeSet, String> {
+        let shapes = names
+            .iter()
+            .map(|n| Chord::parse(n.as_ref()).ok_or_else(|| n.as_ref().to_string()))
+            .collect::<Result<_, _>>()?;
+        Ok(ShapeSet { shapes })
+    }
+
+    /// Whether `chord` is one of the shapes. Slash chords count when their
+    /// upper chord does, since the bass note is usually one extra finger.
+    pub fn contains(&self, chord: &Chord) -> bool {
+        self.shapes
+            .iter()
+            .any(|s| s.root == chord.root && s.intervals() == chord.intervals())
+    }
+}
+
+/// The shape to finger for `chord` with a capo at `capo`, e.g. `G` for an
+/// `A` at the second fret. Symbols that do not parse are returned as is.
+pub fn shape(chord: &str, capo: u8) -> String {
+    match Chord::parse(chord) {
+        Some(parsed) => parsed.transpose(-(capo as i32)).to_string(),
+        None => chord.to_string(),
+    }
+}
+
+/// [`shape`] for each chord of a line.
+pub fn shapes(chords: &[String], capo: u8) -> Vec<String> {
+    chords.iter().map(|c| shape(c, capo)).collect()
+}
+
+/// One capo position and how the song's chords lie under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapoSuggestion {
+    pub capo: u8,
+    /// Each chord of the song with the shape played for it.
+    pub shapes: Vec<(String, String)>,
+    /// Chords whose shape is not in the set, so probably need a barre.
+    pub barre: Vec<String>,
+}
+
+/// Rank capo positions from 0 to `max_capo` for `chords`: fewest barre
+/// chords first, then the lowest fret. Chords that do not parse (such as
+/// Nashville numbers) are left out.
+pub fn suggest<S: AsRef<str>>(chords: &[S], set: &ShapeSet, max_capo: u8) -> Vec<CapoSuggestion> {
+    let parsed: Vec<(&str, Chord)> = chords
+        .iter()
+        .filter_map(|c| Some((c.as_ref(), Chord::parse(c.as_ref())?)))
+        .collect();
+    let mut suggestions: Vec<CapoSuggestion> = (0..=max_capo)
+        .map(|capo| {
+            let mut suggestion = CapoSuggestion {
+                capo,
+                shapes: Vec::new(),
+                barre: Vec::new(),
+            };
+            for (name, chord) in &parsed {
+                let shape = chord.transpose(-(capo as i32));
+                if !set.contains(&shape) {
+                    suggestion.barre.push(name.to_string());
+                }
+                suggestion
+                    .shapes
+                    .push((name.to_string(), shape.to_string()));
+            }
+            suggestion
+        })
+        .collect();
+    suggestions.sort_by_key(|s| (s.barre.len(), s.capo));
+    suggestions
+}
+
+/// The distinct chords of a song in order of first use.
+pub fn song_chords(song: &Song) -> Vec<String> {
+    let mut chords: Vec<String> = Vec::new();
+    let lines = song.sections.iter().flat_map(|s| &s.lines);
+    for line in lines.chain(song.macros.iter().flat_map(|m| &m.lines)) {
+        for chord in &line.chords {
+            if !chords.contains(chord) {
+                chords.push(chord.clone());
+            }
+        }
+    }
+    chords
+}
+
+/// The song's `capo` metadata, when it is a fret number above zero.
+pub fn song_capo(song: &Song) -> Option<u8> {
+    let fret = song.meta("capo")?.as_number()?;
+    ((1.0..=12.0).contains(&fret) && fret.fract() == 0.0).then_some(fret as u8)
+}
@@ -0,0 +1,442 @@
+//! Fretted-instrument chord diagrams.
+//!
+//! Shapes for the common open chords of standard guitar and ukulele tuning
+//! come from a built-in table, so players see the fingerings they know.
+//! Anything else, including every chord in an alternate tuning, is voiced by
+//! searching the first frets for a playable shape that covers the chord.
+
+use super::{Chord, Note};
+use crate::export::ttml::escape;
+use std::fmt::Write;
+
+/// An instrument and the tuning of its strings, lowest string first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fretboard {
+    pub instrument: Instrument,
+    pub tuning: Vec<Note>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instrument {
+    Guitar,
+    Ukulele,
+}
+
+impl Instrument {
+    pub fn parse(name: &str) -> Option<Instrument> {
+        match name {
+            "guitar" => Some(Instrument::Guitar),
+            "ukulele" | "uke" => Some(Instrument::Ukulele),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Instrument::Guitar => "guitar",
+            Instrument::Ukulele => "ukulele",
+        }
+    }
+
+    /// Standard tuning, lowest string first.
+    pub fn standard_tuning(self) -> &'static str {
+        match self {
+            Instrument::Guitar => "EADGBE",
+            Instrument::Ukulele => "GCEA",
+        }
+    }
+}
+
+impl Fretboard {
+    pub fn standard(instrument: Instrument) -> Fretboard {
+        Fretboard::new(instrument, instrument.standard_tuning()).expect("valid tuning")
+    }
+
+    /// `tuning` lists the open strings lowest first, e.g. `DADGAD` or
+    /// `D#G#C#F#A#D#`.
+    pub fn new(instrument: Instrument, tuning: &str) -> Option<Fretboard> {
+        let mut notes = Vec::new();
+        let mut rest = tuning;
+        while !rest.is_empty() {
+            let (note, len) = Note::parse_prefix(rest)?;
+            notes.push(note);
+            rest = &rest[len..];
+        }
+        (2..=8).contains(&notes.len()).then_some(Fretboard {
+            instrument,
+            tuning: notes,
+        })
+    }
+
+    /// The tuning as written on the command line, e.g. `EADGBE`.
+    pub fn tuning_name(&self) -> String {
+        self.tuning.iter().map(|n| n.name(false)).collect()
+    }
+
+    fn is_standard(&self) -> bool {
+        *self == Fretboard::standard(self.instrument)
+    }
+
+    /// A diagram for `chord`, or `None` when it cannot be parsed or no
+    /// playable shape was found.
+    pub fn diagram(&self, chord: &str) -> Option<ChordDiagram> {
+        let parsed = Chord::parse(chord)?;
+        let frets = self.known_shape(&parsed).or_else(|| self.search(&parsed))?;
+        Some(ChordDiagram::new(chord, frets))
+    }
+
+    fn known_shape(&self, chord: &Chord) -> Option<Vec<Option<u8>>> {
+        if !self.is_standard() || chord.bass.is_some() {
+            return None;
+        }
+        let table = match self.instrument {
+            Instrument::Guitar => GUITAR_SHAPES,
+            Instrument::Ukulele => UKULELE_SHAPES,
+        };
+        let quality = match chord.quality.as_str() {
+            "" | "maj" | "M" => "",
+            "m" | "min" | "-" => "m",
+            "7" => "7",
+            _ => return None,
+        };
+        let name = format!("{}{}", chord.root.name(false), quality);
+        table
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, shape)| parse_shape(shape))
+    }
+
+    /// Try every shape within a four-fret span, from the nut up to the
+    /// ninth fret, and keep the easiest one that sounds every chord tone
+    /// (the fifth may be left out of larger chords) with the right bass.
+    fn search(&self, chord: &Chord) -> Option<Vec<Option<u8>>> {
+        let tones = chord.notes();
+        let bass = chord.bass.unwrap_or(chord.root);
+        let strings = self.tuning.len();
+        let mut best: Option<(u32, Vec<Option<u8>>)> = None;
+
+        for position in 0..=9u8 {
+            let low = position.max(1);
+            let options: Vec<Vec<Option<u8>>> = self
+                .tuning
+                .iter()
+                .map(|open| {
+                    let mut frets = vec![None];
+                    frets.extend(
+                        std::iter::once(0)
+                            .chain(low..=low + 3)
+                            .filter(|&f| tones.contains(&open.transpose(f as i32)))
+                            .map(Some),
+                    );
+                    frets
+                })
+                .collect();
+
+            let mut choice = vec![0usize; strings];
+            loop {
+                let frets: Vec<Option<u8>> =
+                    choice.iter().zip(&options).map(|(&c, o)| o[c]).collect();
+                if let Some(cost) = self.cost(&frets, &tones, bass) {
+                    if best.as_ref().is_none_or(|(b, _)| cost < *b) {
+                        best = Some((cost, frets));
+                    }
+                }
+                // Advance the mixed-radix counter over string choices.
+                let mut i = 0;
+                while i < strings {
+                    choice[i] += 1;
+                    if choice[i] < options[i].len() {
+                        break;
+                    }
+                    choice[i] = 0;
+                    i += 1;
+                }
+                if i == strings {
+                    break;
+                }
+            }
+        }
+        best.map(|(_, frets)| frets)
+    }
+
+    /// How hard a shape is to play, or `None` when it is not a usable
+    /// voicing: wrong bass, a missing tone, muted inner strings or more than
+    /// four fingers.
+    fn cost(&self, frets: &[Option<u8>], tones: &[Note], bass: Note) -> Option<u32> {
+        let sounded: Vec<(usize, u8)> = frets
+            .iter()
+            .enumerate()
+            .filter_map(|(s, f)| f.map(|f| (s, f)))
+            .collect();
+        let &(first, first_fret) = sounded.first()?;
+        if self.tuning[first].transpose(first_fret as i32) != bass {
+            return None;
+        }
+        // Strings may only be muted below the bass note.
+        if sounded.len() != frets.len() - first {
+            return None;
+        }
+        let notes: Vec<Note> = sounded
+            .iter()
+            .map(|&(s, f)| self.tuning[s].transpose(f as i32))
+            .collect();
+        let optional_fifth = tones.len() > 3 || frets.len() <= 4 && tones.len() > 2;
+        let fifth = tones[0].transpose(7);
+        let covered = |t: Note| notes.contains(&t) || optional_fifth && t == fifth;
+        let missing = tones.iter().filter(|&&t| !covered(t)).count();
+        if missing > 0 {
+            return None;
+        }
+        let fingers = fingering(frets);
+        if fingers.iter().flatten().max().copied().unwrap_or(0) > 4 {
+            return None;
+        }
+        let fretted: Vec<u8> = sounded.iter().map(|&(_, f)| f).filter(|&f| f > 0).collect();
+        let highest = fretted.iter().max().copied().unwrap_or(0) as u32;
+        let lowest = fretted.iter().min().copied().unwrap_or(0) as u32;
+        let used = fingers
+            .iter()
+            .flatten()
+            .collect::<std::collections::HashSet<_>>()
+            .len() as u32;
+        let muted = first as u32;
+        Some(highest * 3 + (highest - lowest) * 2 + used * 2 + muted)
+    }
+}
+
+/// `x32010` style shapes, lowest string first; `x` is a muted string.
+const GUITAR_SHAPES: &[(&str, &str)] = &[
+    ("C", "x32010"),
+    ("Cm", "x35543"),
+    ("C7", "x32310"),
+    ("D", "xx0232"),
+    ("Dm", "xx0231"),
+    ("D7", "xx0212"),
+    ("E", "022100"),
+    ("Em", "022000"),
+    ("E7", "020100"),
+    ("F", "133211"),
+    ("Fm", "133111"),
+    ("F7", "131211"),
+    ("G", "320003"),
+    ("Gm", "355333"),
+    ("G7", "320001"),
+    ("A", "x02220"),
+    ("Am", "x02210"),
+    ("A7", "x02020"),
+    ("B", "x24442"),
+    ("Bm", "x24432"),
+    ("B7", "x21202"),
+];
+
+const UKULELE_SHAPES: &[(&str, &str)] = &[
+    ("C", "0003"),
+    ("Cm", "0333"),
+    ("C7", "0001"),
+    ("D", "2220"),
+    ("Dm", "2210"),
+    ("D7", "2223"),
+    ("E", "4442"),
+    ("Em", "0432"),
+    ("E7", "1202"),
+    ("F", "2010"),
+    ("Fm", "1013"),
+    ("F7", "2310"),
+    ("G", "0232"),
+    ("Gm", "0231"),
+    ("G7", "0212"),
+    ("A", "2100"),
+    ("Am", "2000"),
+    ("A7", "0100"),
+    ("B", "4322"),
+    ("Bm", "4222"),
+    ("B7", "2322"),
+];
+
+fn parse_shape(shape: &str) -> Vec<Option<u8>> {
+    shape
+        .chars()
+        .map(|c| c.to_digit(10).map(|d| d as u8))
+        .collect()
+}
+
+/// Fingers 1 to 4 for each fretted string: the lowest fret is barred by the
+/// first finger when it is needed on several strings, and the remaining
+/// notes take the next fingers from the lowest fret up.
+pub fn fingering(frets: &[Option<u8>]) -> Vec<Option<u8>> {
+    let mut fingers = vec![None; frets.len()];
+    let mut fretted: Vec<(u8, usize)> = frets
+        .iter()
+        .enumerate()
+        .filter_map(|(s, f)| f.filter(|&f| f > 0).map(|f| (f, s)))
+        .collect();
+    fretted.sort();
+    let Some(&(lowest, _)) = fretted.first() else {
+        return fingers;
+    };
+
+    let at_lowest = fretted.iter().filter(|(f, _)| *f == lowest).count();
+    let barre = at_lowest > 1 && fretted.len() > 4;
+    let mut next = 1;
+    if barre {
+        for &(f, s) in &fretted {
+            if f == lowest {
+                fingers[s] = Some(1);
+            }
+        }
+        next = 2;
+    }
+    for &(_, s) in &fretted {
+        if fingers[s].is_none() {
+            fingers[s] = Some(next);
+            next += 1;
+        }
+    }
+    fingers
+}
+
+/// One chord's frets and fingers, lowest string first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordDiagram {
+    pub name: String,
+    /// `None` for a muted string, `Some(0)` for an open one.
+    pub frets: Vec<Option<u8>>,
+    pub fingers: Vec<Option<u8>>,
+    /// Fret shown at the top of the grid; 1 means the nut is drawn.
+    pub base_fret: u8,
+}
+
+/// Frets shown in a diagram.
+pub const DIAGRAM_FRETS: u8 = 4;
+
+impl ChordDiagram {
+    pub fn new(name: &str, frets: Vec<Option<u8>>) -> ChordDiagram {
+        let highest = frets.iter().flatten().max().copied().unwrap_or(0);
+        let lowest = frets.iter().flatten().filter(|&&f| f > 0).min().copied();
+        let base_fret = match lowest {
+            Some(low) if highest > DIAGRAM_FRETS => low,
+            _ => 1,
+        };
+        ChordDiagram {
+            name: name.to_string(),
+            fingers: fingering(&frets),
+            frets,
+            base_fret,
+        }
+    }
+
+    /// The shape in `x32010` notation.
+    pub fn shape(&self) -> String {
+        self.frets
+            .iter()
+            .map(|f| match f {
+                None => "x".to_string(),
+                Some(f) if *f < 10 => f.to_string(),
+                Some(f) => format!("({})", f),
+            })
+            .collect()
+    }
+
+    /// An inline SVG of the diagram.
+    pub fn to_svg(&self) -> String {
+        let strings = self.frets.len();
+        let (spacing, fret_height, left, top) = (14.0, 16.0, 16.0, 34.0);
+        let width = left * 2.0 + spacing * (strings - 1) as f64;
+        let height = top + fret_height * DIAGRAM_FRETS as f64 + 22.0;
+        let right = left + spacing * (strings - 1) as f64;
+        let bottom = top + fret_height * DIAGRAM_FRETS as f64;
+
+        let mut svg = format!(
+            "<svg class=\"chord-diagram\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            width, height, width, height
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"12\" text-anchor=\"middle\" font-weight=\"bold\">{}</text>",
+            width / 2.0,
+            escape(&self.name)
+        );
+        for s in 0..strings {
+            let x = left + spacing * s as f64;
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{x}\" y1=\"{top}\" x2=\"{x}\" y2=\"{bottom}\" stroke=\"#333\"/>"
+            );
+        }
+        for f in 0..=DIAGRAM_FRETS {
+            let y = top + fret_height * f as f64;
+            let stroke = if f == 0 && self.base_fret == 1 { 4 } else { 1 };
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{left}\" y1=\"{y}\" x2=\"{right}\" y2=\"{y}\" stroke=\"#333\" stroke-width=\"{stroke}\"/>"
+            );
+        }
+        if self.base_fret > 1 {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{}fr</text>",
+                left - 4.0,
+                top + fret_height * 0.7,
+                self.base_fret
+            );
+        }
+        for (s, (fret, finger)) in self.frets.iter().zip(&self.fingers).enumerate() {
+            let x = left + spacing * s as f64;
+            match fret {
+                None => {
+                    let _ = writeln!(
+                        svg,
+                        "<text x=\"{x}\" y=\"{}\" font-size=\"11\" text-anchor=\"middle\">×</text>",
+                        top - 5.0
+                    );
+                }
+                Some(0) => {
+                    let _ = writeln!(
+                        svg,
+                        "<circle cx=\"{x}\" cy=\"{}\" r=\"4\" fill=\"none\" stroke=\"#333\"/>",
+                        top - 9.0
+                    );
+                }
+                Some(f) => {
+                    let row = (f + 1 - self.base_fret) as f64;
+                    let _ = writeln!(
+                        svg,
+                        "<circle cx=\"{x}\" cy=\"{}\" r=\"5.5\" fill=\"#333\"/>",
+                        top + fret_height * (row - 0.5)
+                    );
+                    if let Some(finger) = finger {
+                        let _ = writeln!(
+                            svg,
+                            "<text x=\"{x}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{finger}</text>",
+                            bottom + 14.0
+                        );
+                    }
+                }
+            }
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+/// Diagrams for every distinct chord of `chords` in first-use order, and the
+/// names that could not be voiced.
+pub fn diagrams<'a>(
+    fretboard: &Fretboard,
+    chords: impl IntoIterator<Item = &'a str>,
+) -> (Vec<ChordDiagram>, Vec<String>) {
+    let mut seen = Vec::new();
+    let mut found = Vec::new();
+    let mut unknown = Vec::new();
+    for chord in chords {
+        if seen.contains(&chord) {
+            continue;
+        }
+        seen.push(chord);
+        match fretboard.diagram(chord) {
+            Some(diagram) => found.push(diagram),
+            None => unknown.push(chord.to_string()),
+        }
+    }
+    (found, unknown)
+}
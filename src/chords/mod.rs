@@ -0,0 +1,181 @@
+//! Chord symbols: parsing the names written in `chord:` attributes into a
+//! root, an optional bass note and a quality, and the notes they contain.
+
+use crate::ast::Song;
+use std::fmt;
+
+pub mod capo;
+pub mod diagram;
+pub mod nashville;
+
+const SHARP_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+const FLAT_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+];
+
+/// A pitch class, 0 for C up to 11 for B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Note(pub u8);
+
+impl Note {
+    /// Parse a note name such as `C`, `F#` or `Bb` at the start of `text`,
+    /// returning the note and the number of bytes it used.
+    pub fn parse_prefix(text: &str) -> Option<(Note, usize)> {
+        let base = match text.chars().next()? {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+        let rest = &text[1..];
+        Some(match rest.chars().next() {
+            Some(c @ ('#' | '♯')) => (Note((base + 1) % 12), 1 + c.len_utf8()),
+            Some(c @ ('b' | '♭')) => (Note((base + 11) % 12), 1 + c.len_utf8()),
+            _ => (Note(base), 1),
+        })
+    }
+
+    /// Parse a whole note name.
+    pub fn parse(text: &str) -> Option<Note> {
+        match Note::parse_prefix(text)? {
+            (note, len) if len == text.len() => Some(note),
+            _ => None,
+        }
+    }
+
+    pub fn transpose(self, semitones: i32) -> Note {
+        Note((self.0 as i32 + semitones).rem_euclid(12) as u8)
+    }
+
+    /// Name of the note, spelled with flats or sharps.
+    pub fn name(self, flats: bool) -> &'static str {
+        if flats {
+            FLAT_NAMES[self.0 as usize]
+        } else {
+            SHARP_NAMES[self.0 as usize]
+        }
+    }
+}
+
+/// A parsed chord symbol such as `Am7` or `G/B`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub root: Note,
+    /// The quality as written, e.g. `m7`, `maj` or `` for a major triad.
+    pub quality: String,
+    /// Bass note of a slash chord.
+    pub bass: Option<Note>,
+    /// Whether the root was written with a flat, used to spell it back.
+    pub flats: bool,
+}
+
+impl Chord {
+    pub fn parse(text: &str) -> Option<Chord> {
+        let (main, bass) = match text.split_once('/') {
+            Some((main, bass)) => (main, Some(Note::parse(bass)?)),
+            None => (text, None),
+        };
+        let (root, len) = Note::parse_prefix(main)?;
+        let quality = &main[len..];
+        let chord = Chord {
+            root,
+            quality: quality.to_string(),
+            bass,
+            flats: main[..len].ends_with(['b', '♭']),
+        };
+        chord.intervals().map(|_| chord)
+    }
+
+    /// Semitones above the root of each chord tone, root first.
+    pub fn intervals(&self) -> Option<&'static [u8]> {
+        Some(match self.quality.as_str() {
+            "" | "maj" | "M" => &[0, 4, 7],
+            "m" | "min" | "-" => &[0, 3, 7],
+            "dim" | "°" => &[0, 3, 6],
+            "aug" | "+" => &[0, 4, 8],
+            "5" => &[0, 7],
+            "6" => &[0, 4, 7, 9],
+            "m6" | "min6" => &[0, 3, 7, 9],
+            "7" => &[0, 4, 7, 10],
+            "maj7" | "M7" => &[0, 4, 7, 11],
+            "m7" | "min7" | "-7" => &[0, 3, 7, 10],
+            "dim7" | "°7" => &[0, 3, 6, 9],
+            "m7b5" | "ø" => &[0, 3, 6, 10],
+            "9" => &[0, 4, 7, 10, 2],
+            "add9" => &[0, 4, 7, 2],
+            "sus2" => &[0, 2, 7],
+            "sus4" | "sus" => &[0, 5, 7],
+            "7sus4" => &[0, 5, 7, 10],
+            _ => return None,
+        })
+    }
+
+    /// The chord tones, root first.
+    pub fn notes(&self) -> Vec<Note> {
+        self.intervals()
+            .unwrap_or_default()
+            .iter()
+            .map(|&i| self.root.transpose(i as i32))
+            .collect()
+    }
+
+    /// The same chord moved by `semitones`, keeping its spelling preference.
+    pub fn transpose(&self, semitones: i32) -> Chord {
+        Chord {
+            root: self.root.transpose(semitones),
+            bass: self.bass.map(|b| b.transpose(semitones)),
+            ..self.clone()
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.root.name(self.flats), self.quality)?;
+        if let Some(bass) = self.bass {
+            write!(f, "/{}", bass.name(self.flats))?;
+        }
+        Ok(())
+    }
+}
+
+/// Move a chord symbol by `semitones`, spelling the result with flats or
+/// sharps. Unlike [`Chord::transpose`] this keeps any quality as written;
+/// symbols that do not start with a note name come back unchanged.
+pub fn transpose_symbol(symbol: &str, semitones: i32, flats: bool) -> String {
+    let (main, bass) = match symbol.split_once('/') {
+        Some((main, bass)) => (main, Some(bass)),
+        None => (symbol, None),
+    };
+    let Some((root, len)) = Note::parse_prefix(main) else {
+        return symbol.to_string();
+    };
+    let mut out = format!("{}{}", root.transpose(semitones).name(flats), &main[len..]);
+    if let Some(bass) = bass {
+        match Note::parse(bass) {
+            Some(bass) => {
+                out.push('/');
+                out.push_str(bass.transpose(semitones).name(flats));
+            }
+            None => return symbol.to_string(),
+        }
+    }
+    out
+}
+
+/// Transpose every chord of `song`, macros included.
+pub fn transpose_song(song: &mut Song, semitones: i32, flats: bool) {
+    let sections = song.sections.iter_mut().flat_map(|s| &mut s.lines);
+    let macros = song.macros.iter_mut().flat_map(|m| &mut m.lines);
+    for line in sections.chain(macros) {
+        for chord in &mut line.chords {
+            *chord = transpose_symbol(chord, semitones, flats);
+        }
+    }
+}
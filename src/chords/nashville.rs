@@ -0,0 +1,133 @@
+//! The Nashville number system: chords written as scale degrees of the key,
+//! so `C F G` in C and `G C D` in G both read `1 4 5`.
+//!
+//! Degrees count semitones from the tonic of the key along the major scale;
+//! chromatic roots take a flat (`b3`, `b7`) except the tritone, `#4`. In a
+//! minor key the tonic is still `1`, so `Am F G` in A minor is `1m b6 b7`.
+
+use super::Note;
+use std::fmt;
+
+const DEGREES: [&str; 12] = [
+    "1", "b2", "2", "b3", "3", "4", "#4", "5", "b6", "6", "b7", "7",
+];
+
+/// Keys whose notes are spelled with flats.
+const FLAT_KEYS: [&str; 12] = [
+    "F", "Bb", "Eb", "Ab", "Db", "Gb", "Dm", "Gm", "Cm", "Fm", "Bbm", "Ebm",
+];
+
+/// The key of a song, as given in its `key` metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub tonic: Note,
+    pub minor: bool,
+    /// Whether chords in this key are spelled with flats.
+    pub flats: bool,
+}
+
+impl Key {
+    /// Parse `C`, `F#m`, `Bb major`, `A minor` and the like.
+    pub fn parse(text: &str) -> Option<Key> {
+        let text = text.trim();
+        let (tonic, len) = Note::parse_prefix(text)?;
+        let mode = text[len..].trim().to_lowercase();
+        let minor = match mode.as_str() {
+            "" | "maj" | "major" => false,
+            "m" | "min" | "minor" => true,
+            _ => return None,
+        };
+        let name = format!("{}{}", &text[..len], if minor { "m" } else { "" });
+        Some(Key {
+            tonic,
+            minor,
+            flats: FLAT_KEYS.contains(&name.as_str()) || text[..len].ends_with('b'),
+        })
+    }
+
+    /// The Nashville degree of `note` in this key.
+    pub fn degree(&self, note: Note) -> &'static str {
+        DEGREES[((note.0 + 12 - self.tonic.0) % 12) as usize]
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minor = if self.minor { "m" } else { "" };
+        write!(f, "{}{}", self.tonic.name(self.flats), minor)
+    }
+}
+
+/// Whether `symbol` is written as a Nashville number rather than a letter.
+pub fn is_number(symbol: &str) -> bool {
+    symbol
+        .trim_start_matches(['b', '#'])
+        .starts_with(|c: char| ('1'..='7').contains(&c))
+}
+
+/// `chord` as a Nashville number in `key`, keeping its quality as written.
+/// Symbols that do not start with a note name come back unchanged.
+pub fn to_number(chord: &str, key: &Key) -> String {
+    let (main, bass) = match chord.split_once('/') {
+        Some((main, bass)) => (main, Some(bass)),
+        None => (chord, None),
+    };
+    let Some((root, len)) = Note::parse_prefix(main) else {
+        return chord.to_string();
+    };
+    let mut out = format!("{}{}", key.degree(root), &main[len..]);
+    if let Some(bass) = bass {
+        match Note::parse(bass) {
+            Some(bass) => {
+                out.push('/');
+                out.push_str(key.degree(bass));
+            }
+            None => return chord.to_string(),
+        }
+    }
+    out
+}
+
+/// Semitones above the tonic for a degree such as `b7` at the start of
+/// `text`, with the number of bytes it used.
+fn parse_degree(text: &str) -> Option<(i32, usize)> {
+    let (shift, rest) = match text.as_bytes().first()? {
+        b'b' => (-1, &text[1..]),
+        b'#' => (1, &text[1..]),
+        _ => (0, text),
+    };
+    let digit = rest.chars().next()?.to_digit(10)?;
+    const MAJOR: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+    let base = MAJOR.get((digit as usize).checked_sub(1)?)?;
+    Some((base + shift, text.len() - rest.len() + 1))
+}
+
+/// A Nashville number as a letter chord in `key`. Anything that is not a
+/// number comes back unchanged.
+pub fn to_letter(symbol: &str, key: &Key) -> String {
+    if !is_number(symbol) {
+        return symbol.to_string();
+    }
+    let (main, bass) = match symbol.split_once('/') {
+        Some((main, bass)) => (main, Some(bass)),
+        None => (symbol, None),
+    };
+    let Some((semitones, len)) = parse_degree(main) else {
+        return symbol.to_string();
+    };
+    let mut out = format!(
+        "{}{}",
+        key.tonic.transpose(semitones).name(key.flats),
+        &main[len..]
+    );
+    if let Some(bass) = bass {
+        match parse_degree(bass) {
+            Some((semitones, len)) if len == bass.len() => {
+                out.push('/');
+                out.push_str(key.tonic.transpose(semitones).name(key.flats));
+            }
+            _ => return symbol.to_string(),
+        }
+    }
+    out
+}
@@ -0,0 +1,592 @@
+//! Stable codes for every diagnostic, with the explanations `explain`
+//! prints.
+//!
+//! Each check tags what it reports with one of the [`Code`]s below, so this
+//! file is the single list of what can go wrong. Codes are never reused:
+//! `E` codes are errors and `W` codes warnings. The examples are checked by
+//! the test suite, which runs the same checks on them: `wrong` must be
+//! reported under the code and `right` must not.
+
+use crate::diagnostic::Severity;
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Code {
+    pub id: &'static str,
+    pub severity: Severity,
+    /// One line, lower case, as listed by `explain`.
+    pub title: &'static str,
+    pub explanation: &'static str,
+    /// A song that gets this diagnostic.
+    pub wrong: &'static str,
+    /// The same song corrected.
+    pub right: &'static str,
+    /// The `extra.pest` the examples are checked with, if they need one.
+    pub extra_pest: Option<&'static str>,
+}
+
+/// Diagnostics serialize as their code.
+impl Serialize for Code {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id)
+    }
+}
+
+pub static SYNTAX: Code = Code {
+    id: "E001",
+    severity: Severity::Error,
+    title: "syntax error",
+    explanation: "The song does not follow the grammar at the marked place. The message \
+        lists what the parser would have accepted there. `grammar --ebnf RULE` shows \
+        the rules in full.",
+    wrong: "title: \"T\"\n\nVERSE[1\nOne\n",
+    right: "title: \"T\"\n\nVERSE[1]\nOne\n",
+    extra_pest: None,
+};
+
+pub static UNKNOWN_SECTION_KIND: Code = Code {
+    id: "E002",
+    severity: Severity::Error,
+    title: "unknown section kind",
+    explanation: "A section header must start with VERSE, CHORUS, BRIDGE, PRE-CHORUS, \
+        OUTRO or INTRO, in capitals, or be a REPEAT or MACRO. A free-form name needs \
+        `SECTION name` and the permissive profile.",
+    wrong: "title: \"T\"\n\nVESE[1]\nOne\n",
+    right: "title: \"T\"\n\nVERSE[1]\nOne\n",
+    extra_pest: None,
+};
+
+pub static UNKNOWN_METADATA_KEY: Code = Code {
+    id: "E003",
+    severity: Severity::Error,
+    title: "unknown metadata key",
+    explanation: "Metadata lines use a fixed set of keys: title, artist, tempo, key, \
+        time, time_sig, genre, lang, writers, duration, meter, capo, targets, range, \
+        year, explicit, isrc, iswc, publishers, translations and syllable_delta.",
+    wrong: "titel: \"T\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static MISSING_METADATA: Code = Code {
+    id: "E004",
+    severity: Severity::Error,
+    title: "song starts without metadata",
+    explanation: "A song opens with at least one metadata line, or a `---` front-matter \
+        block, before its first section.",
+    wrong: "VERSE\nOne\n",
+    right: "title: \"T\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static DUPLICATE_DEFINITION: Code = Code {
+    id: "E005",
+    severity: Severity::Error,
+    title: "defined more than once",
+    explanation: "Variables, macros, anchors and numbered sections each need a unique \
+        name, so references to them are unambiguous, and each section and line `id` \
+        may only be used once. The note points at the first definition.",
+    wrong: "title: \"T\"\n$who = \"you\"\n$who = \"me\"\n\nVERSE\nHello ${who}\n",
+    right: "title: \"T\"\n$who = \"you\"\n$me = \"me\"\n\nVERSE\nHello ${who}\n",
+    extra_pest: None,
+};
+
+pub static UNDEFINED_REFERENCE: Code = Code {
+    id: "E006",
+    severity: Severity::Error,
+    title: "undefined reference",
+    explanation: "`${name}`, `USE name`, `USE *name` and `REPEAT KIND[n]` must name a \
+        variable, macro, anchor or section the song defines. A close match is \
+        suggested when there is one.",
+    wrong: "title: \"T\"\n$who = \"you\"\n\nVERSE\nHello ${whoo}\n",
+    right: "title: \"T\"\n$who = \"you\"\n\nVERSE\nHello ${who}\n",
+    extra_pest: None,
+};
+
+pub static TEMPO_RANGE: Code = Code {
+    id: "E007",
+    severity: Severity::Error,
+    title: "tempo out of range",
+    explanation: "The `tempo` and every tempo change need a BPM from 1 to 1000; \
+        beats are not counted from a tempo outside that.",
+    wrong: "title: \"T\"\ntempo: 0 @ 0:30.00\n\nVERSE\nOne\n",
+    right: "title: \"T\"\ntempo: 90 @ 0:30.00\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static TEMPO_ORDER: Code = Code {
+    id: "E008",
+    severity: Severity::Error,
+    title: "tempo changes out of order",
+    explanation: "Tempo changes are listed in the order they happen, each strictly \
+        later than the one before.",
+    wrong: "title: \"T\"\ntempo: 120 @ 1:00.00\ntempo: 90 @ 0:30.00\n\nVERSE\nOne\n",
+    right: "title: \"T\"\ntempo: 90 @ 0:30.00\ntempo: 120 @ 1:00.00\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static INVALID_TIME_SIGNATURE: Code = Code {
+    id: "E009",
+    severity: Severity::Error,
+    title: "invalid time signature",
+    explanation: "`time` takes beats over a note value that is a power of two, such \
+        as `4/4`, `3/4` or `6/8`.",
+    wrong: "title: \"T\"\ntime: 4/5\n\nVERSE\nOne\n",
+    right: "title: \"T\"\ntime: 4/4\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static FORWARD_REPEAT: Code = Code {
+    id: "E010",
+    severity: Severity::Error,
+    title: "REPEAT of a later section",
+    explanation: "A REPEAT replays a section that has already been sung, so it must \
+        come after the section it names.",
+    wrong: "title: \"T\"\n\nREPEAT CHORUS[1]\n\nCHORUS[1]\nLa\n",
+    right: "title: \"T\"\n\nCHORUS[1]\nLa\n\nREPEAT CHORUS[1]\n",
+    extra_pest: None,
+};
+
+pub static NOTHING_TO_REPEAT: Code = Code {
+    id: "E011",
+    severity: Severity::Error,
+    title: "REPEAT with nothing before it",
+    explanation: "`REPEAT KIND` without a number replays the latest section of that \
+        kind, so one must come before it.",
+    wrong: "title: \"T\"\n\nVERSE\nOne\n\nREPEAT CHORUS\n",
+    right: "title: \"T\"\n\nCHORUS\nLa\n\nREPEAT CHORUS\n",
+    extra_pest: None,
+};
+
+pub static INCLUDE_CYCLE: Code = Code {
+    id: "E012",
+    severity: Severity::Error,
+    title: "USE includes itself",
+    explanation: "A macro or anchored section cannot include itself, directly or \
+        through another `USE`, as the expansion would never end.",
+    wrong: "title: \"T\"\n\nMACRO hook\nOh\nUSE hook\n\nVERSE\nUSE hook\n",
+    right: "title: \"T\"\n\nMACRO hook\nOh\nOh oh\n\nVERSE\nUSE hook\n",
+    extra_pest: None,
+};
+
+pub static ANNOTATION_MISMATCH: Code = Code {
+    id: "E013",
+    severity: Severity::Error,
+    title: "annotation does not match its extra.pest rule",
+    explanation: "When the project's `extra.pest` defines a rule named like an \
+        annotation, the annotation's value must match that rule in full.",
+    wrong: "title: \"T\"\n\nVERSE\nOne {mood: happy}\n",
+    right: "title: \"T\"\n\nVERSE\nOne {mood: wistful}\n",
+    extra_pest: Some("mood = { \"wistful\" | \"angry\" }\n"),
+};
+
+pub static FREE_FORM_SECTION: Code = Code {
+    id: "E014",
+    severity: Severity::Error,
+    title: "free-form section outside the permissive profile",
+    explanation: "`SECTION name` headers are only accepted by the permissive profile. \
+        Use a built-in section kind, or select the profile with `--profile \
+        permissive` or in `.lyricsdsl/config.toml`.",
+    wrong: "title: \"T\"\n\nSECTION Tag\nOne\n",
+    right: "title: \"T\"\n\nOUTRO\nOne\n",
+    extra_pest: None,
+};
+
+pub static UNNUMBERED_SECTION: Code = Code {
+    id: "E015",
+    severity: Severity::Error,
+    title: "unnumbered section in the strict profile",
+    explanation: "The strict profile numbers every VERSE and CHORUS.",
+    wrong: "title: \"T\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\n\nVERSE[1]\nOne\n",
+    extra_pest: None,
+};
+
+pub static UNNUMBERED_REPEAT: Code = Code {
+    id: "E016",
+    severity: Severity::Error,
+    title: "REPEAT without a number in the strict profile",
+    explanation: "The strict profile names the section a REPEAT replays by number \
+        rather than taking the latest one.",
+    wrong: "title: \"T\"\n\nCHORUS[1]\nLa\n\nREPEAT CHORUS\n",
+    right: "title: \"T\"\n\nCHORUS[1]\nLa\n\nREPEAT CHORUS[1]\n",
+    extra_pest: None,
+};
+
+pub static SECTION_ORDER: Code = Code {
+    id: "E017",
+    severity: Severity::Error,
+    title: "section numbered out of order in the strict profile",
+    explanation: "The strict profile numbers the verses, and the choruses, 1, 2, 3 \
+        in the order they appear. `lint --fix` renumbers them.",
+    wrong: "title: \"T\"\n\nVERSE[1]\nOne\n\nVERSE[3]\nTwo\n",
+    right: "title: \"T\"\n\nVERSE[1]\nOne\n\nVERSE[2]\nTwo\n",
+    extra_pest: None,
+};
+
+pub static TIMING_PAST_END: Code = Code {
+    id: "E018",
+    severity: Severity::Error,
+    title: "timing past the end of the track",
+    explanation: "A line cannot start after the recording ends. The length comes from \
+        the `duration` metadata, or from the recording given to `validate --audio`. A \
+        timing a minute or an hour out is the usual cause.",
+    wrong:
+        "title: \"T\"\nduration: \"3:10\"\n\nVERSE\nOne {timing: 0:12.00}\nTwo {timing: 10:16.00}\n",
+    right:
+        "title: \"T\"\nduration: \"3:10\"\n\nVERSE\nOne {timing: 0:12.00}\nTwo {timing: 0:16.00}\n",
+    extra_pest: None,
+};
+
+pub static INVALID_ISRC: Code = Code {
+    id: "E019",
+    severity: Severity::Error,
+    title: "invalid ISRC",
+    explanation: "An `isrc` is twelve characters: a two-letter country code, a \
+        three-character registrant code, two digits of year and a five-digit \
+        designation, as in `US-S1Z-99-00001`. The hyphens are optional.",
+    wrong: "title: \"T\"\nisrc: \"US-S1Z-1999-0001\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\nisrc: \"US-S1Z-99-00001\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static INVALID_ISWC: Code = Code {
+    id: "E020",
+    severity: Severity::Error,
+    title: "invalid ISWC",
+    explanation: "An `iswc` is `T`, nine digits and a check digit, as in \
+        `T-034.524.680-1`. The check digit is one plus each of the nine digits times \
+        its position, taken from the next multiple of ten, so a mistyped digit is \
+        caught here rather than by a collecting society.",
+    wrong: "title: \"T\"\niswc: \"T-034.524.680-7\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\niswc: \"T-034.524.680-1\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static PUBLISHER_SHARES: Code = Code {
+    id: "E021",
+    severity: Severity::Error,
+    title: "publisher shares do not add up",
+    explanation: "`publishers` lists each publisher with its share of the work, \
+        comma separated, as in `Harbour Songs 50%, Kobalt 50%`. The shares must add \
+        up to 100%.",
+    wrong: "title: \"T\"\npublishers: \"Harbour Songs 50%, Kobalt 40%\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\npublishers: \"Harbour Songs 60%, Kobalt 40%\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static WRITER_SHARES: Code = Code {
+    id: "E022",
+    severity: Severity::Error,
+    title: "writer shares do not add up",
+    explanation: "`writers` may give each writer a role and a share, as in `Ann Lee \
+        (lyrics) 50%, Bo Chen (music) 50%`. Once one writer has a share, every writer \
+        needs one, and the shares must add up to 100%.",
+    wrong: "title: \"T\"\nwriters: \"Ann Lee (lyrics) 50%, Bo Chen (music)\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\nwriters: \"Ann Lee (lyrics) 50%, Bo Chen (music) 50%\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static CREDIT_LINES: Code = Code {
+    id: "E023",
+    severity: Severity::Error,
+    title: "credit points at the wrong lines",
+    explanation: "An entry of the `credits:` block names the lines that use the \
+        borrowed work with `lines: *anchor`, or `lines: *first..*last` for a range. \
+        The anchors must label lines or sections that are sung, not the lines of a \
+        `MACRO`, and a range must run forward through the song.",
+    wrong: "title: \"T\"\ncredits:\n  interpolation \"Old Song\" {lines: *end..*start}\n\n\
+        VERSE\nOne &start\nTwo &end\n",
+    right: "title: \"T\"\ncredits:\n  interpolation \"Old Song\" {lines: *start..*end}\n\n\
+        VERSE\nOne &start\nTwo &end\n",
+    extra_pest: None,
+};
+
+pub static CONFLICT_MARKER: Code = Code {
+    id: "E024",
+    severity: Severity::Error,
+    title: "merge conflict marker",
+    explanation: "A line starting with `<<<<<<<`, `|||||||`, `=======` or `>>>>>>>` \
+        is a marker git or `merge` left around changes made differently on both \
+        sides. It would otherwise be sung as a lyric. Keep the lines that should \
+        stay and delete the markers and the rest.",
+    wrong: "title: \"T\"\n\nVERSE\n<<<<<<< ours\nOne\n=======\nWon\n>>>>>>> theirs\n",
+    right: "title: \"T\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static STRICT_PLACEHOLDER: Code = Code {
+    id: "E025",
+    severity: Severity::Error,
+    title: "strict song still has a placeholder",
+    explanation: "In the strict profile a `____` or `<tbd: ...>` still standing for \
+        unwritten words is an error rather than the W017 warning, so a finished song \
+        cannot be released with a gap in it. Write the words, or check the draft \
+        under another profile.",
+    wrong: "title: \"T\"\n\nVERSE[1]\nThe night is <tbd: rhyme with \"river\">\n",
+    right: "title: \"T\"\n\nVERSE[1]\nThe night is cold as winter\n",
+    extra_pest: None,
+};
+
+pub static BARS_WITHOUT_TIME: Code = Code {
+    id: "W001",
+    severity: Severity::Warning,
+    title: "bar markers without a time signature",
+    explanation: "`|` bar markers are checked against the `time` signature, taken to \
+        be 4/4 when the song has none.",
+    wrong: "title: \"T\"\n\nVERSE\n| One more | time\n",
+    right: "title: \"T\"\ntime: 4/4\n\nVERSE\n| One more | time\n",
+    extra_pest: None,
+};
+
+pub static OVERFULL_BAR: Code = Code {
+    id: "W002",
+    severity: Severity::Warning,
+    title: "too many syllables in a bar",
+    explanation: "A bar fits about two syllables per beat; more than that is hard to \
+        sing at tempo. Move the bar marker or cut words.",
+    wrong: "title: \"T\"\ntime: 2/4\n\nVERSE\n| Everybody is singing | along\n",
+    right: "title: \"T\"\ntime: 2/4\n\nVERSE\n| Everybody | is singing | along\n",
+    extra_pest: None,
+};
+
+pub static UNKNOWN_METER: Code = Code {
+    id: "W003",
+    severity: Severity::Warning,
+    title: "unknown meter",
+    explanation: "`meter` is iambic, trochaic, anapestic or dactylic, or a pattern of \
+        `x` (unstressed) and `/` (stressed) syllables.",
+    wrong: "title: \"T\"\nmeter: iambics\n\nVERSE\nOne\n",
+    right: "title: \"T\"\nmeter: iambic\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static STRESS_LENGTH: Code = Code {
+    id: "W004",
+    severity: Severity::Warning,
+    title: "stress pattern does not fit the line",
+    explanation: "A `stress` attribute gives one mark per syllable of the line.",
+    wrong: "title: \"T\"\n\nVERSE\nHello there {stress: x/}\n",
+    right: "title: \"T\"\n\nVERSE\nHello there {stress: x//}\n",
+    extra_pest: None,
+};
+
+pub static STRESS_CLASH: Code = Code {
+    id: "W005",
+    severity: Severity::Warning,
+    title: "word stress fights the meter",
+    explanation: "The natural stress of the listed words falls on weak beats of the \
+        declared meter or stress pattern, so singing the line in time distorts them.",
+    wrong: "title: \"T\"\nmeter: iambic\n\nVERSE\nHappy people\n",
+    right: "title: \"T\"\nmeter: trochaic\n\nVERSE\nHappy people\n",
+    extra_pest: None,
+};
+
+pub static UNKNOWN_ANNOTATION: Code = Code {
+    id: "W006",
+    severity: Severity::Warning,
+    title: "unknown annotation",
+    explanation: "A line attribute the grammar does not know is kept as an annotation \
+        but not checked. Define a rule of the same name in `extra.pest` to check it, \
+        or correct the name if it is a misspelt built-in attribute.",
+    wrong: "title: \"T\"\n\nVERSE\nOne {rhym: A}\n",
+    right: "title: \"T\"\n\nVERSE\nOne {rhyme: A}\n",
+    extra_pest: None,
+};
+
+pub static MISSING_TITLE: Code = Code {
+    id: "W007",
+    severity: Severity::Warning,
+    title: "song has no title",
+    explanation: "Exports, setlists and cards show the title. `fix` offers one taken \
+        from the file name.",
+    wrong: "artist: \"A\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\nartist: \"A\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static SECTION_NUMBERING: Code = Code {
+    id: "W008",
+    severity: Severity::Warning,
+    title: "sections numbered out of order",
+    explanation: "Numbered verses, numbered choruses and the numbered sections of each \
+        SECTION name should run 1, 2, 3 in the order they appear. `lint --fix` and \
+        `renumber` renumber them together with the REPEATs that name them.",
+    wrong: "title: \"T\"\n\nVERSE[2]\nOne\n\nREPEAT VERSE[2]\n",
+    right: "title: \"T\"\n\nVERSE[1]\nOne\n\nREPEAT VERSE[1]\n",
+    extra_pest: None,
+};
+
+pub static HEADER_FORMAT: Code = Code {
+    id: "W009",
+    severity: Severity::Warning,
+    title: "header not in canonical form",
+    explanation: "Section headers are written the way `fmt` writes them: single \
+        spaces before an anchor and attributes, and no trailing space. `lint --fix` \
+        rewrites them.",
+    wrong: "title: \"T\"\n\nVERSE   &start\nOne\n",
+    right: "title: \"T\"\n\nVERSE &start\nOne\n",
+    extra_pest: None,
+};
+
+pub static TIMING_ORDER: Code = Code {
+    id: "W010",
+    severity: Severity::Warning,
+    title: "line timed before the line above it",
+    explanation: "Lines are sung in the order they are written, so their timings \
+        should only go forward. When moving the timing by a minute would put it in \
+        order, that is suggested.",
+    wrong: "title: \"T\"\n\nVERSE\nOne {timing: 1:05.00}\nTwo {timing: 0:09.00}\n",
+    right: "title: \"T\"\n\nVERSE\nOne {timing: 0:05.00}\nTwo {timing: 0:09.00}\n",
+    extra_pest: None,
+};
+
+pub static TIMING_GAP: Code = Code {
+    id: "W011",
+    severity: Severity::Warning,
+    title: "implausible gap between timed lines",
+    explanation: "The time from one line to the next should be enough to sing the \
+        first, at least 0.08s a syllable, and not far longer than the song's usual \
+        spacing: more than 30s and four times the median gap suggests a mistyped \
+        timing.",
+    wrong: "title: \"T\"\n\nVERSE\nOne {timing: 0:05.00}\nTwo {timing: 0:09.00}\nThree {timing: 0:13.00}\nFour {timing: 1:17.00}\n",
+    right: "title: \"T\"\n\nVERSE\nOne {timing: 0:05.00}\nTwo {timing: 0:09.00}\nThree {timing: 0:13.00}\nFour {timing: 0:17.00}\n",
+    extra_pest: None,
+};
+
+pub static INVALID_DURATION: Code = Code {
+    id: "W012",
+    severity: Severity::Warning,
+    title: "`duration` is not a length",
+    explanation: "`duration` gives the length of the recording as minutes and \
+        seconds, such as `\"3:45\"`, or as seconds, such as `225`. Timings are only \
+        checked against it when it can be read.",
+    wrong: "title: \"T\"\nduration: \"three minutes\"\n\nVERSE\nOne\n",
+    right: "title: \"T\"\nduration: \"3:00\"\n\nVERSE\nOne\n",
+    extra_pest: None,
+};
+
+pub static LINE_LENGTH: Code = Code {
+    id: "W013",
+    severity: Severity::Warning,
+    title: "line too long for an export target",
+    explanation: "The `targets` metadata names the formats a song is exported to. \
+        Some show only so much on one row, 42 characters for a `ttml` subtitle and 36 \
+        for `slides` and `pptx`, and wrap longer lines themselves. A line longer than \
+        the tightest of them is reported, so it can be broken with a trailing `\\` \
+        where the writer wants the new row to start.",
+    wrong: "title: \"T\"\ntargets: \"lrc, slides\"\n\nVERSE\nAnd the river keeps on running to the sea\n",
+    right: "title: \"T\"\ntargets: \"lrc, slides\"\n\nVERSE\nAnd the river keeps on running \\\n  to the sea\n",
+    extra_pest: None,
+};
+
+pub static RECURRING_LINE: Code = Code {
+    id: "W015",
+    severity: Severity::Warning,
+    title: "recurring line written differently",
+    explanation: "A line sung more than once, such as a chorus typed out again \
+        instead of repeated with `REPEAT`, is expected to read the same each time. \
+        Lines whose words match once case, punctuation and contractions like `can't` \
+        and `cannot` are set aside, or long lines a character or two apart, are \
+        reported, and `fix` offers to write each as the most common version.",
+    wrong: "title: \"T\"\n\nCHORUS[1]\nWe can't stop the rain\n\nCHORUS[2]\nWe cannot stop the rain\n\nCHORUS[3]\nWe can't stop the rain\n",
+    right: "title: \"T\"\n\nCHORUS[1]\nWe can't stop the rain\n\nCHORUS[2]\nWe can't stop the rain\n\nCHORUS[3]\nWe can't stop the rain\n",
+    extra_pest: None,
+};
+
+pub static SPELLING: Code = Code {
+    id: "W014",
+    severity: Severity::Warning,
+    title: "word not in the dictionary",
+    explanation: "With a `dictionary` such as `en_US` set in the project's \
+        `config.toml`, `lint` checks the words of every lyric line against that Hunspell \
+        dictionary and the project's `.lyricsdsl/words.txt`, where invented words and \
+        names go, one a line. A misspelling with a single likely correction is fixed by \
+        `lint --fix`; otherwise the corrections are suggested.",
+    wrong: "title: \"T\"\n\nVERSE\nWe danse until the morning\n",
+    right: "title: \"T\"\n\nVERSE\nWe dance until the morning\n",
+    extra_pest: None,
+};
+
+pub static TRANSLATION_ALIGNMENT: Code = Code {
+    id: "W016",
+    severity: Severity::Warning,
+    title: "translations out of line with the lyrics",
+    explanation: "Once lines carry `translation.<tag>` variants, every lyric line is \
+        expected to have each language listed in `translations` metadata, or without \
+        it each language any line has. A section with none of a language's \
+        translations is reported once, and a line missing one its section has \
+        elsewhere on its own. With `syllable_delta` set, a translation more than that \
+        many syllables longer or shorter than its line is reported too, so a sung \
+        translation still fits the melody. Romanizations are not counted against it.",
+    wrong: "title: \"T\"\ntranslations: \"es\"\n\nVERSE\nHold on {translation.es: \"Espera\"}\nLet go\n",
+    right: "title: \"T\"\ntranslations: \"es\"\n\nVERSE\nHold on {translation.es: \"Espera\"}\nLet go {translation.es: \"Suelta\"}\n",
+    extra_pest: None,
+};
+
+pub static PLACEHOLDER: Code = Code {
+    id: "W017",
+    severity: Severity::Warning,
+    title: "line still has a placeholder",
+    explanation: "`____` and `<tbd: rhyme with \"river\">` hold the place of words \
+        still to be written. Exports show them as blanks, or leave them out with \
+        `--placeholders hide`, and `todos` lists them with the open notes. Each is \
+        reported until it is filled in: as this warning, or as the error E025 in the \
+        strict profile.",
+    wrong: "title: \"T\"\n\nVERSE[1]\nThe night is <tbd: rhyme with \"river\">\n",
+    right: "title: \"T\"\n\nVERSE[1]\nThe night is cold as winter\n",
+    extra_pest: None,
+};
+
+/// Every code, in order.
+pub static ALL: &[&Code] = &[
+    &SYNTAX,
+    &UNKNOWN_SECTION_KIND,
+    &UNKNOWN_METADATA_KEY,
+    &MISSING_METADATA,
+    &DUPLICATE_DEFINITION,
+    &UNDEFINED_REFERENCE,
+    &TEMPO_RANGE,
+    &TEMPO_ORDER,
+    &INVALID_TIME_SIGNATURE,
+    &FORWARD_REPEAT,
+    &NOTHING_TO_REPEAT,
+    &INCLUDE_CYCLE,
+    &ANNOTATION_MISMATCH,
+    &FREE_FORM_SECTION,
+    &UNNUMBERED_SECTION,
+    &UNNUMBERED_REPEAT,
+    &SECTION_ORDER,
+    &TIMING_PAST_END,
+    &INVALID_ISRC,
+    &INVALID_ISWC,
+    &PUBLISHER_SHARES,
+    &WRITER_SHARES,
+    &CREDIT_LINES,
+    &CONFLICT_MARKER,
+    &STRICT_PLACEHOLDER,
+    &BARS_WITHOUT_TIME,
+    &OVERFULL_BAR,
+    &UNKNOWN_METER,
+    &STRESS_LENGTH,
+    &STRESS_CLASH,
+    &UNKNOWN_ANNOTATION,
+    &MISSING_TITLE,
+    &SECTION_NUMBERING,
+    &HEADER_FORMAT,
+    &TIMING_ORDER,
+    &TIMING_GAP,
+    &INVALID_DURATION,
+    &LINE_LENGTH,
+    &RECURRING_LINE,
+    &SPELLING,
+    &TRANSLATION_ALIGNMENT,
+    &PLACEHOLDER,
+];
+
+/// The code with `id`, ignoring case.
+pub fn find(id: &str) -> Option<&'static Code> {
+    ALL.iter()
+        .copied()
+        .find(|code| code.id.eq_ignore_ascii_case(id))
+}
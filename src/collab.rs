@@ -0,0 +1,294 @@
+//! Shared editing of one song by several writers over a local network.
+//!
+//! The host keeps the song as a list of source lines. To change a line a
+//! writer first locks it; while locked nobody else can edit or delete it.
+//! Locks belong to the line, not its number, so when a line is inserted or
+//! deleted above a locked one the lock moves with it. Edits carry only a
+//! number, though: a stale one is refused when it lands on a line the writer
+//! has not locked, but when the writer holds locks on neighbouring lines it
+//! may land on another of them.
+//!
+//! Every edit goes through the host, which applies it and then sends it to
+//! all writers in the same order. Each writer replays the edits on its own
+//! copy of the [`Document`], so all copies stay identical.
+//!
+//! The protocol is plain text, one message per line:
+//!
+//! ```text
+//! client → host   HELLO name | LOCK n | UNLOCK n | SET n text
+//!                 | INSERT n text | DELETE n | SAVE
+//! host → client   WELCOME name | JOINED name | LEFT name | EDIT name <edit>
+//!                 | SAVED name | OK | ERROR message
+//! ```
+//!
+//! Line numbers start at 1. `INSERT n` puts the new line before line `n`;
+//! one past the last line appends.
+
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CollabError {
+    #[error("there is no line {0}")]
+    NoSuchLine(usize),
+    #[error("line {line} is locked by {by}")]
+    Locked { line: usize, by: String },
+    #[error("lock line {0} before changing it")]
+    NotLocked(usize),
+    #[error("cannot understand `{0}`")]
+    Malformed(String),
+}
+
+/// A change to the shared song.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    Lock(usize),
+    Unlock(usize),
+    Set(usize, String),
+    Insert(usize, String),
+    Delete(usize),
+}
+
+/// A message from a writer to the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Hello(String),
+    Edit(Edit),
+    Save,
+}
+
+/// A message from the host to a writer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The name the writer was admitted under, made unique by the host.
+    Welcome(String),
+    Joined(String),
+    Left(String),
+    Edit {
+        by: String,
+        edit: Edit,
+    },
+    Saved(String),
+    Ok,
+    Error(String),
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Lock(n) => write!(f, "LOCK {}", n),
+            Edit::Unlock(n) => write!(f, "UNLOCK {}", n),
+            Edit::Set(n, text) => write!(f, "SET {} {}", n, text),
+            Edit::Insert(n, text) => write!(f, "INSERT {} {}", n, text),
+            Edit::Delete(n) => write!(f, "DELETE {}", n),
+        }
+    }
+}
+
+impl Edit {
+    pub fn parse(line: &str) -> Result<Edit, CollabError> {
+        let malformed = || CollabError::Malformed(line.to_string());
+        let (verb, rest) = line.split_once(' ').ok_or_else(malformed)?;
+        let (number, text) = rest.split_once(' ').unwrap_or((rest, ""));
+        let n: usize = number.parse().map_err(|_| malformed())?;
+        let bare = |edit: Edit| match text.is_empty() {
+            true => Ok(edit),
+            false => Err(malformed()),
+        };
+        match verb {
+            "LOCK" => bare(Edit::Lock(n)),
+            "UNLOCK" => bare(Edit::Unlock(n)),
+            "DELETE" => bare(Edit::Delete(n)),
+            "SET" => Ok(Edit::Set(n, text.to_string())),
+            "INSERT" => Ok(Edit::Insert(n, text.to_string())),
+            _ => Err(malformed()),
+        }
+    }
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::Hello(name) => write!(f, "HELLO {}", name),
+            Request::Edit(edit) => edit.fmt(f),
+            Request::Save => f.write_str("SAVE"),
+        }
+    }
+}
+
+impl Request {
+    pub fn parse(line: &str) -> Result<Request, CollabError> {
+        match line.split_once(' ') {
+            Some(("HELLO", name)) => Ok(Request::Hello(name.to_string())),
+            _ if line == "SAVE" => Ok(Request::Save),
+            _ => Edit::parse(line).map(Request::Edit),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Welcome(name) => write!(f, "WELCOME {}", name),
+            Message::Joined(name) => write!(f, "JOINED {}", name),
+            Message::Left(name) => write!(f, "LEFT {}", name),
+            Message::Edit { by, edit } => write!(f, "EDIT {} {}", by, edit),
+            Message::Saved(by) => write!(f, "SAVED {}", by),
+            Message::Ok => f.write_str("OK"),
+            Message::Error(message) => write!(f, "ERROR {}", message),
+        }
+    }
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Result<Message, CollabError> {
+        let (verb, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let name = rest.to_string();
+        match verb {
+            "OK" => Ok(Message::Ok),
+            "WELCOME" => Ok(Message::Welcome(name)),
+            "JOINED" => Ok(Message::Joined(name)),
+            "LEFT" => Ok(Message::Left(name)),
+            "SAVED" => Ok(Message::Saved(name)),
+            "ERROR" => Ok(Message::Error(name)),
+            "EDIT" => {
+                let (by, edit) = rest
+                    .split_once(' ')
+                    .ok_or_else(|| CollabError::Malformed(line.to_string()))?;
+                Ok(Message::Edit {
+                    by: by.to_string(),
+                    edit: Edit::parse(edit)?,
+                })
+            }
+            _ => Err(CollabError::Malformed(line.to_string())),
+        }
+    }
+}
+
+/// A writer's name as sent in messages: no spaces, never empty.
+pub fn clean_name(name: &str) -> String {
+    let name: String = name.split_whitespace().collect::<Vec<_>>().join("_");
+    match name.is_empty() {
+        true => "writer".to_string(),
+        false => name,
+    }
+}
+
+/// The shared song: its source lines and who holds each line's lock.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    lines: Vec<String>,
+    locks: Vec<Option<String>>,
+}
+
+impl Document {
+    pub fn from_source(source: &str) -> Document {
+        let lines: Vec<String> = source.lines().map(str::to_string).collect();
+        Document {
+            locks: vec![None; lines.len()],
+            lines,
+        }
+    }
+
+    pub fn source(&self) -> String {
+        self.lines.iter().map(|l| format!("{}\n", l)).collect()
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Who holds the lock on line `n`, if anyone.
+    pub fn lock(&self, n: usize) -> Option<&str> {
+        self.locks.get(n.checked_sub(1)?)?.as_deref()
+    }
+
+    /// Apply `edit` on behalf of writer `by`.
+    pub fn apply(&mut self, by: &str, edit: &Edit) -> Result<(), CollabError> {
+        match edit {
+            Edit::Insert(n, text) => {
+                if *n == 0 || *n > self.lines.len() + 1 {
+                    return Err(CollabError::NoSuchLine(*n));
+                }
+                self.lines.insert(n - 1, text.clone());
+                self.locks.insert(n - 1, None);
+            }
+            Edit::Lock(n) => {
+                let i = self.index(*n)?;
+                match &self.locks[i] {
+                    Some(owner) if owner != by => {
+                        return Err(CollabError::Locked {
+                            line: *n,
+                            by: owner.clone(),
+                        })
+                    }
+                    _ => self.locks[i] = Some(by.to_string()),
+                }
+            }
+            Edit::Unlock(n) => {
+                let i = self.owned(by, *n)?;
+                self.locks[i] = None;
+            }
+            Edit::Set(n, text) => {
+                let i = self.owned(by, *n)?;
+                self.lines[i] = text.clone();
+            }
+            Edit::Delete(n) => {
+                let i = self.owned(by, *n)?;
+                self.lines.remove(i);
+                self.locks.remove(i);
+            }
+        }
+        Ok(())
+    }
+
+    /// The unlock edits that release every lock `by` holds, for a writer
+    /// who leaves.
+    pub fn release(&self, by: &str) -> Vec<Edit> {
+        self.locks
+            .iter()
+            .enumerate()
+            .filter(|(_, owner)| owner.as_deref() == Some(by))
+            .map(|(i, _)| Edit::Unlock(i + 1))
+            .collect()
+    }
+
+    /// The messages that rebuild this document from an empty one, sent to
+    /// a writer who joins: the lines, inserted by `host`, then the locks.
+    pub fn replay(&self, host: &str) -> Vec<Message> {
+        let inserts = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| Message::Edit {
+                by: host.to_string(),
+                edit: Edit::Insert(i + 1, line.clone()),
+            });
+        let locks = self.locks.iter().enumerate().filter_map(|(i, owner)| {
+            Some(Message::Edit {
+                by: owner.clone()?,
+                edit: Edit::Lock(i + 1),
+            })
+        });
+        inserts.chain(locks).collect()
+    }
+
+    fn index(&self, n: usize) -> Result<usize, CollabError> {
+        match n {
+            1.. if n <= self.lines.len() => Ok(n - 1),
+            _ => Err(CollabError::NoSuchLine(n)),
+        }
+    }
+
+    fn owned(&self, by: &str, n: usize) -> Result<usize, CollabError> {
+        let i = self.index(n)?;
+        match &self.locks[i] {
+            Some(owner) if owner == by => Ok(i),
+            Some(owner) => Err(CollabError::Locked {
+                line: n,
+                by: owner.clone(),
+            }),
+            None => Err(CollabError::NotLocked(n)),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Alphabetical order for words and titles, following the conventions of a
+//! locale rather than the order of code points.
+//!
+//! This is a small subset of the Unicode Collation Algorithm. Strings are
+//! compared first by their letters, ignoring accents and case; then by
+//! accents; then by case. Punctuation sorts before digits, digits before
+//! Latin letters, and letters of other scripts after them by code point,
+//! with hiragana and katakana counted as the same letters. Accented Latin
+//! letters are decomposed by a table covering Latin-1 and Latin
+//! Extended-A. A few locales move letters of their alphabet:
+//!
+//! - `es`: `ñ` after `n`
+//! - `sv`, `fi`: `å`, `ä`, `ö` after `z`
+//! - `da`, `nb`, `nn`, `no`: `æ`, `ø`, `å` after `z`
+//!
+//! Any other locale gets the default order.
+
+use std::cmp::Ordering;
+
+/// Accented letters and the letters they are built on, one accent per
+/// entry, in the order accents sort among themselves.
+const ACCENTS: &[(&str, &str)] = &[
+    ("áéíóúýćĺńŕśźÁÉÍÓÚÝĆĹŃŔŚŹ", "aeiouyclnrszAEIOUYCLNRSZ"),
+    ("àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+    ("ăĕğĭŏŭĂĔĞĬŎŬ", "aegiouAEGIOU"),
+    ("âêîôûĉĝĥĵŝŵŷÂÊÎÔÛĈĜĤĴŜŴŶ", "aeioucghjswyAEIOUCGHJSWY"),
+    ("čďěňřšťžČĎĚŇŘŠŤŽ", "cdenrstzCDENRSTZ"),
+    ("åůÅŮ", "auAU"),
+    ("äëïöüÿÄËÏÖÜŸ", "aeiouyAEIOUY"),
+    ("őűŐŰ", "ouOU"),
+    ("ãĩñõũÃĨÑÕŨ", "ainouAINOU"),
+    ("ċėġżĊĖĠŻİ", "cegzCEGZI"),
+    ("çģķļņşţÇĢĶĻŅŞŢ", "cgklnstCGKLNST"),
+    ("ąęįųĄĘĮŲ", "aeiuAEIU"),
+    ("āēīōūĀĒĪŌŪ", "aeiouAEIOU"),
+    ("đħłøĐĦŁØ", "dhloDHLO"),
+];
+
+/// Letters written as two, e.g. `æ` as `ae`, marked as a variant of them.
+const EXPANSIONS: &[(char, &str)] = &[
+    ('ß', "ss"),
+    ('æ', "ae"),
+    ('Æ', "AE"),
+    ('œ', "oe"),
+    ('Œ', "OE"),
+    ('þ', "th"),
+    ('Þ', "TH"),
+];
+
+/// Letters a locale sorts after `z`, or after another letter, in order.
+fn tailoring(language: &str) -> &'static [(char, char)] {
+    match language {
+        "es" => &[('n', 'ñ')],
+        "sv" | "fi" => &[('z', 'å'), ('z', 'ä'), ('z', 'ö')],
+        "da" | "nb" | "nn" | "no" => &[('z', 'æ'), ('z', 'ø'), ('z', 'å')],
+        _ => &[],
+    }
+}
+
+/// Letters a locale counts as one of its own, e.g. Swedish `ø` as `ö`.
+fn equivalent(language: &str, letter: char) -> char {
+    match (language, letter) {
+        ("sv" | "fi", 'æ') => 'ä',
+        ("sv" | "fi", 'ø') => 'ö',
+        ("da" | "nb" | "nn" | "no", 'ä') => 'æ',
+        ("da" | "nb" | "nn" | "no", 'ö') => 'ø',
+        _ => letter,
+    }
+}
+
+/// One collation element: letter, accent and case weights.
+type Element = (u32, u8, u8);
+
+/// Compares strings in the order of a locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collator {
+    language: String,
+}
+
+impl Default for Collator {
+    fn default() -> Self {
+        Collator::new("root")
+    }
+}
+
+impl Collator {
+    /// A collator for `locale`, such as `es`, `sv-SE` or `nb_NO`.
+    pub fn new(locale: &str) -> Collator {
+        let language = locale.split(['-', '_', '.', '@']).next().unwrap_or("");
+        Collator {
+            language: language.to_ascii_lowercase(),
+        }
+    }
+
+    /// The language whose conventions are followed, e.g. `sv`.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    fn push(&self, c: char, elements: &mut Vec<Element>) {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let case = u8::from(c != lower);
+        let letter = equivalent(&self.language, lower);
+        let tailored = tailoring(&self.language);
+        if let Some(slot) = tailored.iter().position(|(_, t)| *t == letter) {
+            let (after, _) = tailored[slot];
+            let primary = latin(after) + 1 + slot as u32;
+            elements.push((primary, 0, case));
+            return;
+        }
+        if let Some((_, expansion)) = EXPANSIONS.iter().find(|(e, _)| *e == c) {
+            for part in expansion.chars() {
+                let part_lower = part.to_ascii_lowercase();
+                elements.push((latin(part_lower), 1, u8::from(part != part_lower)));
+            }
+            return;
+        }
+        for (accent, (accented, bases)) in ACCENTS.iter().enumerate() {
+            if let Some(i) = accented.chars().position(|a| a == c) {
+                let base = bases.chars().nth(i).expect("aligned tables");
+                let primary = latin(base.to_ascii_lowercase());
+                elements.push((primary, accent as u8 + 1, case));
+                return;
+            }
+        }
+        elements.push((primary(lower), 0, case));
+    }
+
+    /// The collation elements of `text`.
+    fn elements(&self, text: &str) -> Vec<Element> {
+        let mut elements = Vec::new();
+        for c in text.chars() {
+            self.push(c, &mut elements);
+        }
+        elements
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a_elements, b_elements) = (self.elements(a), self.elements(b));
+        let level = |weight: fn(&Element) -> u32| {
+            let a = a_elements.iter().map(weight);
+            let b = b_elements.iter().map(weight);
+            a.cmp(b)
+        };
+        level(|e| e.0)
+            .then_with(|| level(|e| e.1 as u32))
+            .then_with(|| level(|e| e.2 as u32))
+            .then_with(|| a.cmp(b))
+    }
+
+    /// Sort `items` by the text `key` gives for each.
+    pub fn sort_by_key<T>(&self, items: &mut [T], key: impl Fn(&T) -> &str) {
+        items.sort_by(|a, b| self.compare(key(a), key(b)));
+    }
+}
+
+/// Letters, each group above the one before: punctuation, digits, Latin,
+/// then other scripts.
+const DIGITS: u32 = 1 << 22;
+const LATIN: u32 = 2 << 22;
+const OTHER: u32 = 3 << 22;
+
+/// The weight of a lowercase Latin letter, with room after each for the
+/// letters locales insert.
+fn latin(letter: char) -> u32 {
+    LATIN + (letter as u32 - 'a' as u32) * 8
+}
+
+fn primary(c: char) -> u32 {
+    match c {
+        'a'..='z' => latin(c),
+        '0'..='9' => DIGITS + c as u32,
+        // Katakana as the matching hiragana.
+        '\u{30A1}'..='\u{30F6}' => OTHER + c as u32 - 0x60,
+        c if c.is_alphanumeric() => OTHER + c as u32,
+        c => c as u32 % DIGITS,
+    }
+}
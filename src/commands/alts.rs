@@ -0,0 +1,53 @@
+//! `alts`: the lines of a song that have `/ alt: "..."` takes, with the
+//! take that is sung and the alternatives numbered for `choose`.
+
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::takes;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("alts")
+        .about("List the lines with alternative takes, numbered for choose")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to list"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let source = read_song(path)?;
+    let song = parse(path, &source)?;
+    let stacks = takes::stacks(&song, &source);
+    if stacks.is_empty() {
+        println!(
+            "{}",
+            theme().paint(Role::Hint, &format!("{}: no lines with alternatives", path))
+        );
+        return Ok(());
+    }
+    for stack in &stacks {
+        println!(
+            "{}  {}",
+            theme().paint(Role::Hint, &format!("{}:{}", path, stack.line)),
+            theme().paint(Role::Heading, &stack.section)
+        );
+        println!(
+            "    {}  {}",
+            theme().paint(Role::Success, "*").bold(),
+            stack.text
+        );
+        for (n, alternative) in stack.alternatives.iter().enumerate() {
+            println!(
+                "    {}  {}",
+                theme().paint(Role::Warning, &(n + 1).to_string()),
+                alternative
+            );
+        }
+    }
+    Ok(())
+}
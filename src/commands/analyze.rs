@@ -0,0 +1,77 @@
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::analysis::analyze;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("analyze")
+        .about("Count syllables and score how singable each line is")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to analyze"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the report as JSON"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let report = analyze(&load_song(file)?);
+
+    if matches.get_flag("json") {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for section in &report.sections {
+        println!(
+            "{} {}",
+            format!("[{}]", section.label).bold(),
+            theme().paint(Role::Hint, &format!("singability {}", section.singability))
+        );
+        if let Some(pace) = &section.pace {
+            let seconds = pace.seconds.round() as u64;
+            let pace = format!(
+                "{}:{:02} · {} words a minute",
+                seconds / 60,
+                seconds % 60,
+                pace.words_per_minute.round()
+            );
+            println!("  {}", theme().paint(Role::Hint, &pace));
+        }
+        if let Some(warning) = &section.pace_warning {
+            println!("  {}", theme().paint(Role::Warning, warning));
+        }
+        for line in &section.lines {
+            let score = format!("{:>3}", line.score.score);
+            let score = match line.score.score {
+                80..=100 => theme().paint(Role::Success, &score),
+                50..=79 => theme().paint(Role::Warning, &score),
+                _ => theme().paint(Role::Error, &score),
+            };
+            let beat = match (line.bar, line.beat) {
+                (Some(bar), Some(beat)) => format!("{:>4}|{:<5.1}", bar, beat),
+                _ => " ".repeat(10),
+            };
+            println!(
+                "  {} {:>2} {}  {}",
+                theme().paint(Role::Hint, &beat),
+                line.score.syllables,
+                score,
+                line.text
+            );
+            for issue in &line.score.issues {
+                println!("                   {}", theme().paint(Role::Hint, issue));
+            }
+        }
+    }
+    println!("{} {}", "Singability:".bold(), report.singability);
+    Ok(())
+}
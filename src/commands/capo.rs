@@ -0,0 +1,137 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::ast::{MetaEntry, Song, Value};
+use lyrics_dsl::chords::capo::{song_chords, suggest, ShapeSet};
+use lyrics_dsl::chords::diagram::Instrument;
+use lyrics_dsl::format::format_song;
+
+pub fn command() -> Command {
+    Command::new("capo")
+        .about("Suggest capo positions that let a song be played with open shapes")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to read or edit"),
+        )
+        .arg(
+            Arg::new("instrument")
+                .long("instrument")
+                .value_name("INSTRUMENT")
+                .value_parser(["guitar", "ukulele"])
+                .default_value("guitar")
+                .help("Whose open shapes to aim for"),
+        )
+        .arg(
+            Arg::new("shapes")
+                .long("shapes")
+                .value_name("CHORDS")
+                .value_delimiter(',')
+                .help("Comma-separated shapes to aim for instead, e.g. C,G,D,Em,Am"),
+        )
+        .arg(
+            Arg::new("max-fret")
+                .long("max-fret")
+                .value_name("N")
+                .value_parser(value_parser!(u8).range(0..=12))
+                .default_value("7")
+                .help("Highest capo position to consider"),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .value_parser(value_parser!(usize))
+                .default_value("3")
+                .help("Number of suggestions to show"),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_name("N")
+                .value_parser(value_parser!(u8).range(0..=12))
+                .help("Write `capo: N` into the song instead (0 removes it)"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+
+    if let Some(&capo) = matches.get_one::<u8>("set") {
+        set_capo(&mut song, capo);
+        let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+        return summary(matches, changed as usize, 1);
+    }
+
+    let set = match matches.get_many::<String>("shapes") {
+        Some(names) => ShapeSet::parse(&names.collect::<Vec<_>>())
+            .map_err(|name| format!("unknown chord shape `{}`", name))?,
+        None => {
+            let instrument = matches.get_one::<String>("instrument").expect("defaulted");
+            ShapeSet::open(Instrument::parse(instrument).expect("validated by clap"))
+        }
+    };
+    let max_fret = *matches.get_one::<u8>("max-fret").expect("defaulted");
+    let top = *matches.get_one::<usize>("top").expect("defaulted");
+    let chords = song_chords(&song);
+    if chords.is_empty() {
+        return Err(format!("{}: no chords to place", path).into());
+    }
+
+    for suggestion in suggest(&chords, &set, max_fret).iter().take(top) {
+        let position = match suggestion.capo {
+            0 => "no capo".to_string(),
+            n => format!("capo {}", n),
+        };
+        let shapes: Vec<String> = suggestion
+            .shapes
+            .iter()
+            .map(|(chord, shape)| match chord == shape {
+                true => chord.clone(),
+                false => format!("{}→{}", chord, shape),
+            })
+            .collect();
+        println!("{:<8} {}", position, shapes.join(" "));
+        match suggestion.barre.len() {
+            0 => println!("         all open shapes"),
+            _ => println!("         barre: {}", suggestion.barre.join(" ")),
+        }
+    }
+    Ok(())
+}
+
+/// Replace the song's `capo` entry, adding it after the `key` when there
+/// was none, or drop it for `capo` 0.
+fn set_capo(song: &mut Song, capo: u8) {
+    let existing = song.metadata.iter().position(|m| m.key == "capo");
+    let value = Value::Number(capo as f64);
+    match (existing, capo) {
+        (Some(i), 0) => {
+            song.metadata.remove(i);
+        }
+        (Some(i), _) => song.metadata[i].value = value,
+        (None, 0) => {}
+        (None, _) => {
+            let at = song
+                .metadata
+                .iter()
+                .position(|m| m.key == "key")
+                .map_or(song.metadata.len(), |i| i + 1);
+            song.metadata.insert(
+                at,
+                MetaEntry {
+                    key: "capo".to_string(),
+                    value,
+                    block: false,
+                    span: Default::default(),
+                },
+            );
+        }
+    }
+}
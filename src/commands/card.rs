@@ -0,0 +1,86 @@
+use super::output::{backup_arg, write_file};
+use super::signing::{sign_arg, sign_output};
+use super::{load_valid, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::card::{attribution, excerpt, parse_line_range, render, CardOptions, CardStyle};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("card")
+        .about("Render an excerpt of a song as a PNG image for sharing")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to quote"),
+        )
+        .arg(
+            Arg::new("lines")
+                .short('l')
+                .long("lines")
+                .required(true)
+                .value_name("RANGE")
+                .help("Source lines to quote, e.g. 12-15"),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .value_name("STYLE")
+                .value_parser(CardStyle::NAMES)
+                .default_value("dark")
+                .help("Color scheme"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .value_name("WxH")
+                .default_value("1080x1080")
+                .help("Image size in pixels"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .required(true)
+                .value_name("FILE")
+                .help("PNG file to write"),
+        )
+        .arg(backup_arg())
+        .arg(sign_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let spec = matches.get_one::<String>("lines").expect("required");
+    let output = matches.get_one::<String>("output").expect("required");
+    let style = matches.get_one::<String>("style").expect("defaulted");
+    let size = matches.get_one::<String>("size").expect("defaulted");
+
+    let (first, last) =
+        parse_line_range(spec).ok_or_else(|| format!("invalid line range `{}`", spec))?;
+    let (width, height) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?)))
+        .filter(|&(w, h)| (200..=4096).contains(&w) && (200..=4096).contains(&h))
+        .ok_or_else(|| format!("invalid size `{}`: expected WxH from 200 to 4096", size))?;
+
+    let loaded = load_valid(path)?;
+    let song = &loaded.resolved.song;
+    let lines = excerpt(song, &loaded.source, first, last);
+    if lines.is_empty() {
+        return Err(format!("{}: no lyric lines on lines {}", path, spec).into());
+    }
+    let options = CardOptions {
+        style: CardStyle::parse(style).expect("validated by clap"),
+        width,
+        height,
+    };
+    let png = render(&lines, &attribution(song), &options);
+    write_file(matches, output, &png)?;
+    eprintln!(
+        "{}",
+        theme().paint(Role::Success, &tr("output-written", &[("path", output)]))
+    );
+    sign_output(matches, output)
+}
@@ -0,0 +1,258 @@
+//! `catalog dedupe`: near-identical songs across a catalog, clustered with
+//! a suggested canonical file each. See [`lyrics_dsl::dedupe`].
+//!
+//! `catalog manifest`: the metadata of every song as one CSV or JSON file.
+//! See [`lyrics_dsl::manifest`].
+
+use super::output::{backup_arg, write_file};
+use super::{project_root, relative_name, songs_in, theme, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::ast::Song;
+use lyrics_dsl::crypt::is_locked;
+use lyrics_dsl::dedupe::{clusters, Entry};
+use lyrics_dsl::manifest::{self, Record};
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::search;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+fn paths_arg(help: &'static str) -> Arg {
+    Arg::new("paths")
+        .num_args(0..)
+        .value_name("PATH")
+        .help(help)
+}
+
+pub fn command() -> Command {
+    Command::new("catalog")
+        .about("Work across every song in a catalog")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("dedupe")
+                .about("Group near-identical songs and suggest which copy to keep")
+                .arg(paths_arg(
+                    "Songs or directories to check; defaults to the project root",
+                ))
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .value_name("RATIO")
+                        .default_value("0.8")
+                        .value_parser(value_parser!(f64))
+                        .help("Share of word n-grams from 0 to 1 at which songs are copies"),
+                )
+                .arg(
+                    Arg::new("ngram")
+                        .long("ngram")
+                        .value_name("N")
+                        .default_value("4")
+                        .value_parser(value_parser!(usize))
+                        .help("Length of the word n-grams compared"),
+                ),
+        )
+        .subcommand(
+            Command::new("manifest")
+                .about("Write the metadata of every song as one CSV or JSON file")
+                .arg(paths_arg(
+                    "Songs or directories to list; defaults to the project root",
+                ))
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write to FILE instead of standard output"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["csv", "json"])
+                        .help("File format [default: by the output's extension, else csv]"),
+                )
+                .arg(backup_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand() {
+        Some(("dedupe", sub)) => dedupe(sub),
+        Some(("manifest", sub)) => manifest(sub),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+/// The songs named by the `paths` argument, or every song in the project,
+/// each with its name relative to the project root.
+fn catalog(matches: &ArgMatches) -> Result<Vec<(PathBuf, String)>, Box<dyn Error>> {
+    let root = project_root()?;
+    let paths: Vec<PathBuf> = match matches.get_many::<String>("paths") {
+        Some(paths) => paths.map(PathBuf::from).collect(),
+        None => vec![root.clone()],
+    };
+    let mut files = Vec::new();
+    for path in &paths {
+        if path.is_dir() {
+            songs_in(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let name = relative_name(&root, &file);
+            (file, name)
+        })
+        .collect())
+}
+
+/// The song at `file` and its source. Locked songs and songs that do not
+/// parse are left out, so a locked song's words are never written out.
+fn read(file: &Path) -> Result<(Song, String), String> {
+    let bytes = std::fs::read(file).map_err(|e| e.to_string())?;
+    if is_locked(&bytes) {
+        return Err("locked".to_string());
+    }
+    let source = String::from_utf8(bytes).map_err(|_| "not UTF-8".to_string())?;
+    let parsed = parse_song(&source).map_err(|e| error_diagnostic(&e, &source).message)?;
+    Ok((resolve(&parsed).song, source))
+}
+
+fn skip(name: &str, why: &str) {
+    eprintln!(
+        "{}",
+        theme().paint(Role::Warning, &format!("skipping {}: {}", name, why))
+    );
+}
+
+fn dedupe(matches: &ArgMatches) -> CommandResult {
+    let threshold = *matches.get_one::<f64>("threshold").expect("defaulted");
+    let ngram = *matches.get_one::<usize>("ngram").expect("defaulted");
+    let mut entries = Vec::new();
+    for (file, name) in catalog(matches)? {
+        match entry(&file, &name, ngram) {
+            Ok(entry) => entries.push(entry),
+            Err(why) => skip(&name, &why),
+        }
+    }
+
+    let found = clusters(&entries, threshold);
+    if found.is_empty() {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &format!("✅ No near-identical songs among {}", entries.len())
+            )
+        );
+    }
+    for (i, cluster) in found.iter().enumerate() {
+        println!(
+            "{}",
+            format!("Cluster {} · {} songs", i + 1, cluster.members.len()).bold()
+        );
+        let width = cluster
+            .members
+            .iter()
+            .map(|m| m.path.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (j, member) in cluster.members.iter().enumerate() {
+            let path = format!("{:<width$}", member.path, width = width);
+            match j {
+                0 => println!(
+                    "  ★ {}  {}",
+                    theme().paint(Role::Success, &path),
+                    theme().paint(Role::Success, "keep")
+                ),
+                _ => println!(
+                    "    {}  {}",
+                    path,
+                    theme().paint(
+                        Role::Hint,
+                        &format!("{:.0}% alike", member.similarity * 100.0)
+                    )
+                ),
+            }
+        }
+        println!();
+    }
+    let copies: usize = found.iter().map(|c| c.members.len() - 1).sum();
+    if copies > 0 {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Warning,
+                &format!(
+                    "{} cluster(s); {} file(s) look like copies of the one marked ★",
+                    found.len(),
+                    copies
+                )
+            )
+        );
+    }
+    Ok(())
+}
+
+/// The song at `file`, called `name`, reduced to what deduplication
+/// compares.
+fn entry(file: &Path, name: &str, ngram: usize) -> Result<Entry, String> {
+    let (song, source) = read(file)?;
+    let lines = search::lines(&song, &source);
+    let modified = std::fs::metadata(file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+    Ok(Entry::new(
+        name,
+        lines.iter().map(|l| l.text.as_str()),
+        ngram,
+        song.metadata.len(),
+        modified,
+    ))
+}
+
+fn manifest(matches: &ArgMatches) -> CommandResult {
+    let output = matches.get_one::<String>("output");
+    let json = match matches.get_one::<String>("format") {
+        Some(format) => format == "json",
+        None => output.is_some_and(|path| {
+            Path::new(path)
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        }),
+    };
+    let mut records = Vec::new();
+    for (file, name) in catalog(matches)? {
+        match read(&file) {
+            Ok((song, _)) => records.push(Record::new(&name, &song)),
+            Err(why) => skip(&name, &why),
+        }
+    }
+    records.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let text = match json {
+        true => manifest::to_json(&records),
+        false => manifest::to_csv(&records),
+    };
+    match output {
+        Some(path) => {
+            write_file(matches, path, text.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(
+                    Role::Success,
+                    &format!("📒 Listed {} song(s) in {}", records.len(), path)
+                )
+            );
+        }
+        None => std::io::stdout().write_all(text.as_bytes())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! `choose`: sing one of a line's `/ alt: "..."` takes instead, keeping the
+//! words it replaces as that alternative.
+
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::takes;
+
+pub fn command() -> Command {
+    Command::new("choose")
+        .about("Swap one of a line's alternatives in as the take that is sung")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to change"),
+        )
+        .arg(
+            Arg::new("line")
+                .long("line")
+                .required(true)
+                .value_name("N")
+                .value_parser(value_parser!(usize))
+                .help("Source line of the lyric line"),
+        )
+        .arg(
+            Arg::new("alt")
+                .long("alt")
+                .required(true)
+                .value_name("K")
+                .value_parser(value_parser!(usize))
+                .help("Alternative to sing, numbered from 1 as alts lists them"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let number = *matches.get_one::<usize>("line").expect("required");
+    let alt = *matches.get_one::<usize>("alt").expect("required");
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    let chosen =
+        takes::choose(&mut song, &source, number, alt).map_err(|e| format!("{}: {}", path, e))?;
+    eprintln!("line {} now sings: {}", number, chosen);
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
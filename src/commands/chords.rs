@@ -0,0 +1,91 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::ast::{Line, Song};
+use lyrics_dsl::chords::nashville::{to_letter, to_number, Key};
+use lyrics_dsl::format::format_song;
+use std::collections::BTreeMap;
+
+pub fn command() -> Command {
+    Command::new("chords")
+        .about("List the chords of a song, or rewrite them as Nashville numbers")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to read or edit"),
+        )
+        .arg(
+            Arg::new("nashville")
+                .long("nashville")
+                .action(ArgAction::SetTrue)
+                .help("Rewrite chord symbols as numbers relative to the key"),
+        )
+        .arg(
+            Arg::new("letters")
+                .long("letters")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("nashville")
+                .help("Rewrite Nashville numbers as chord symbols in the key"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .value_name("KEY")
+                .help("Key to convert in, e.g. G or F#m [default: the song's `key`]"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+
+    let nashville = matches.get_flag("nashville");
+    if !nashville && !matches.get_flag("letters") {
+        list(&song);
+        return Ok(());
+    }
+
+    let key = match matches.get_one::<String>("key") {
+        Some(key) => key.clone(),
+        None => song
+            .meta_str("key")
+            .ok_or_else(|| format!("{}: no `key` metadata; pass --key", path))?,
+    };
+    let key = Key::parse(&key).ok_or_else(|| format!("{}: invalid key `{}`", path, key))?;
+    let convert = |chord: &str| match nashville {
+        true => to_number(chord, &key),
+        false => to_letter(chord, &key),
+    };
+    for line in lines_mut(&mut song) {
+        line.chords = line.chords.iter().map(|c| convert(c)).collect();
+    }
+
+    let converted = format_song(&song);
+    let changed = apply(matches, path, &source, &converted)? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
+
+/// Every line of the song, including macro bodies.
+fn lines_mut(song: &mut Song) -> impl Iterator<Item = &mut Line> {
+    let sections = song.sections.iter_mut().flat_map(|s| &mut s.lines);
+    let macros = song.macros.iter_mut().flat_map(|m| &mut m.lines);
+    sections.chain(macros)
+}
+
+fn list(song: &Song) {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let lines = song.sections.iter().flat_map(|s| &s.lines);
+    for line in lines.chain(song.macros.iter().flat_map(|m| &m.lines)) {
+        for chord in &line.chords {
+            *counts.entry(chord).or_default() += 1;
+        }
+    }
+    for (chord, count) in counts {
+        println!("{:<8} {}", chord, count);
+    }
+}
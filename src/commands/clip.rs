@@ -0,0 +1,107 @@
+use super::{project_root, theme, CommandResult, PROJECT_DIR};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::capture::{capture, format_utc, inbox_entry};
+use lyrics_dsl::theme::Role;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::SystemTime;
+
+/// Programs that print the clipboard, tried in order.
+const READERS: &[(&str, &[&str])] = &[
+    ("pbpaste", &[]),
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+    (
+        "powershell.exe",
+        &["-NoProfile", "-Command", "Get-Clipboard"],
+    ),
+];
+
+pub fn command() -> Command {
+    Command::new("clip")
+        .about("Capture the clipboard into the project's inbox")
+        .arg(
+            Arg::new("inbox")
+                .long("inbox")
+                .value_name("FILE")
+                .help("Inbox to append to [default: .lyricsdsl/inbox.txt in the project]"),
+        )
+        .arg(
+            Arg::new("stdin")
+                .long("stdin")
+                .action(ArgAction::SetTrue)
+                .help("Read the text from standard input instead of the clipboard"),
+        )
+        .arg(
+            Arg::new("print")
+                .long("print")
+                .action(ArgAction::SetTrue)
+                .help("Print what would be captured without writing the inbox"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let text = match matches.get_flag("stdin") {
+        true => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        false => read_clipboard()?,
+    };
+    let captured = capture(&text)?;
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let entry = inbox_entry(&captured, &format_utc(secs));
+    if matches.get_flag("print") {
+        print!("{}", entry);
+        return Ok(());
+    }
+
+    let inbox = match matches.get_one::<String>("inbox") {
+        Some(path) => PathBuf::from(path),
+        None => project_root()?.join(PROJECT_DIR).join("inbox.txt"),
+    };
+    if let Some(dir) = inbox.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&inbox)
+        .map_err(|e| format!("cannot open '{}': {}", inbox.display(), e))?;
+    let separator = match file.metadata()?.len() {
+        0 => "",
+        _ => "\n",
+    };
+    write!(file, "{}{}", separator, entry)?;
+    eprintln!(
+        "{} {} → {}",
+        theme().paint(Role::Success, "✓ captured"),
+        captured.detected.describe(),
+        inbox.display()
+    );
+    Ok(())
+}
+
+fn read_clipboard() -> Result<String, String> {
+    for (program, args) in READERS {
+        let Ok(output) = process::Command::new(program).args(*args).output() else {
+            continue;
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    Err(format!(
+        "cannot read the clipboard: install one of {} or use --stdin",
+        READERS
+            .iter()
+            .map(|(program, _)| *program)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
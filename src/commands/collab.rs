@@ -0,0 +1,358 @@
+//! `collab host` and `collab join`: several writers editing one song over
+//! the local network, each in a small line-editing prompt.
+//!
+//! The host serves the song from a background thread and joins its own
+//! session like any other writer, so every prompt works the same way. See
+//! [`lyrics_dsl::collab`] for the protocol and the locking rules.
+
+use super::output::{backup_arg, write_file};
+use super::{read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::collab::{clean_name, Document, Edit, Message, Request};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+const HELP: &str = "commands:
+  show                 print the song with line numbers and locks
+  lock N / unlock N    take or release line N
+  set N TEXT           replace locked line N
+  insert N TEXT        add a line before line N
+  append TEXT          add a line at the end
+  delete N             remove locked line N
+  save                 write the song to the host's file
+  quit                 leave the session";
+
+fn name_arg() -> Arg {
+    Arg::new("name")
+        .long("name")
+        .value_name("NAME")
+        .help("Name shown to the other writers [default: $USER]")
+}
+
+pub fn command() -> Command {
+    Command::new("collab")
+        .about("Edit a song together over the local network (experimental)")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("host")
+                .about("Share a song and open a prompt to edit it")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .value_name("FILE")
+                        .help("Song to share; `save` writes it back here"),
+                )
+                .arg(
+                    Arg::new("bind")
+                        .long("bind")
+                        .value_name("ADDR")
+                        .default_value("0.0.0.0:7878")
+                        .help("Address to accept writers on"),
+                )
+                .arg(name_arg())
+                .arg(backup_arg()),
+        )
+        .subcommand(
+            Command::new("join")
+                .about("Join a song shared with `collab host`")
+                .arg(
+                    Arg::new("address")
+                        .required(true)
+                        .value_name("ADDR")
+                        .help("Host and port, e.g. 192.168.1.20:7878"),
+                )
+                .arg(name_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand() {
+        Some(("host", sub)) => host(sub),
+        Some(("join", sub)) => {
+            let address = sub.get_one::<String>("address").expect("required");
+            let stream = TcpStream::connect(address)
+                .map_err(|e| format!("cannot connect to {}: {}", address, e))?;
+            prompt(stream, &writer_name(sub))
+        }
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn writer_name(matches: &ArgMatches) -> String {
+    let name = matches
+        .get_one::<String>("name")
+        .cloned()
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default();
+    clean_name(&name)
+}
+
+/// The host's side of a session: the song and everyone connected to it.
+struct Hub {
+    /// The `host` arguments, for the file to save to and `--backup`.
+    matches: ArgMatches,
+    path: String,
+    host: String,
+    document: Document,
+    writers: Vec<(String, TcpStream)>,
+}
+
+impl Hub {
+    fn broadcast(&mut self, message: &Message) {
+        self.writers
+            .retain_mut(|(_, stream)| writeln!(stream, "{}", message).is_ok());
+    }
+
+    fn save(&mut self, by: &str) -> Result<(), Box<dyn Error>> {
+        let source = self.document.source();
+        if let Err(e) = parse_song(&source) {
+            let (line, _) = match e.line_col {
+                pest::error::LineColLocation::Pos(pos) => pos,
+                pest::error::LineColLocation::Span(start, _) => start,
+            };
+            return Err(format!("not saved: the song does not parse at line {}", line).into());
+        }
+        write_file(&self.matches, &self.path, source.as_bytes())?;
+        self.broadcast(&Message::Saved(by.to_string()));
+        Ok(())
+    }
+}
+
+fn host(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let bind = matches.get_one::<String>("bind").expect("defaulted");
+    let source = read_song(path)?;
+    let listener =
+        TcpListener::bind(bind).map_err(|e| format!("cannot listen on {}: {}", bind, e))?;
+    let address = listener.local_addr()?;
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Info,
+            &format!(
+                "🤝 Sharing {} on {}; others run `lyrics-dsl collab join`",
+                path, address
+            )
+        )
+    );
+
+    let name = writer_name(matches);
+    let hub = Arc::new(Mutex::new(Hub {
+        matches: matches.clone(),
+        path: path.clone(),
+        host: name.clone(),
+        document: Document::from_source(&source),
+        writers: Vec::new(),
+    }));
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let hub = Arc::clone(&hub);
+            std::thread::spawn(move || serve(stream, hub));
+        }
+    });
+
+    let local = SocketAddr::from(([127, 0, 0, 1], address.port()));
+    prompt(TcpStream::connect(local)?, &name)
+}
+
+/// Handle one writer's connection until it closes.
+fn serve(stream: TcpStream, hub: Arc<Mutex<Hub>>) -> std::io::Result<()> {
+    let mut reply = stream.try_clone()?;
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+    let name = match lines.next().transpose()?.map(|l| Request::parse(&l)) {
+        Some(Ok(Request::Hello(name))) => clean_name(&name),
+        _ => return writeln!(reply, "{}", Message::Error("say HELLO first".into())),
+    };
+
+    let name = {
+        let mut hub = hub.lock().expect("hub lock");
+        let taken = |n: &str| hub.writers.iter().any(|(w, _)| w == n);
+        let unique = (1..)
+            .map(|i| match i {
+                1 => name.clone(),
+                i => format!("{}-{}", name, i),
+            })
+            .find(|n| !taken(n))
+            .expect("some name is free");
+        hub.broadcast(&Message::Joined(unique.clone()));
+        writeln!(reply, "{}", Message::Welcome(unique.clone()))?;
+        for message in hub.document.replay(&hub.host) {
+            writeln!(reply, "{}", message)?;
+        }
+        writeln!(reply, "{}", Message::Ok)?;
+        hub.writers.push((unique.clone(), stream));
+        unique
+    };
+
+    for line in lines {
+        let line = line?;
+        let mut hub = hub.lock().expect("hub lock");
+        let outcome: Result<(), Box<dyn Error>> = match Request::parse(&line) {
+            Ok(Request::Edit(edit)) => match hub.document.apply(&name, &edit) {
+                Ok(()) => {
+                    hub.broadcast(&Message::Edit {
+                        by: name.clone(),
+                        edit,
+                    });
+                    Ok(())
+                }
+                Err(e) => Err(e.into()),
+            },
+            Ok(Request::Save) => hub.save(&name),
+            Ok(Request::Hello(_)) => Err("already joined".into()),
+            Err(e) => Err(e.into()),
+        };
+        let message = match outcome {
+            Ok(()) => Message::Ok,
+            Err(e) => Message::Error(e.to_string()),
+        };
+        writeln!(reply, "{}", message)?;
+    }
+
+    let mut hub = hub.lock().expect("hub lock");
+    hub.writers.retain(|(w, _)| *w != name);
+    for edit in hub.document.release(&name) {
+        if hub.document.apply(&name, &edit).is_ok() {
+            hub.broadcast(&Message::Edit {
+                by: name.clone(),
+                edit,
+            });
+        }
+    }
+    hub.broadcast(&Message::Left(name));
+    Ok(())
+}
+
+/// A writer's prompt: read commands from stdin, send them to the host and
+/// keep a copy of the song up to date from the host's messages.
+fn prompt(stream: TcpStream, name: &str) -> CommandResult {
+    let mut requests = stream.try_clone()?;
+    let document = Arc::new(Mutex::new(Document::default()));
+    let (replies, reply) = mpsc::channel::<Result<(), String>>();
+
+    let mirror = Arc::clone(&document);
+    std::thread::spawn(move || {
+        let mut me = String::new();
+        let mut synced = false;
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            match Message::parse(&line) {
+                Ok(Message::Welcome(name)) => me = name,
+                Ok(Message::Edit { by, edit }) => {
+                    let _ = mirror.lock().expect("mirror lock").apply(&by, &edit);
+                    if synced && by != me {
+                        eprintln!("{}", theme().paint(Role::Hint, &describe(&by, &edit)));
+                    }
+                }
+                Ok(Message::Joined(who)) => {
+                    eprintln!("{}", theme().paint(Role::Hint, &format!("{} joined", who)))
+                }
+                Ok(Message::Left(who)) => {
+                    eprintln!("{}", theme().paint(Role::Hint, &format!("{} left", who)))
+                }
+                Ok(Message::Saved(who)) => {
+                    eprintln!(
+                        "{}",
+                        theme().paint(Role::Success, &format!("💾 {} saved the song", who))
+                    )
+                }
+                Ok(Message::Ok) => {
+                    synced = true;
+                    let _ = replies.send(Ok(()));
+                }
+                Ok(Message::Error(e)) => {
+                    let _ = replies.send(Err(e));
+                }
+                Err(e) => eprintln!(
+                    "{}",
+                    theme().paint(Role::Warning, &format!("ignored message: {}", e))
+                ),
+            }
+        }
+        let _ = replies.send(Err("the session has ended".into()));
+    });
+
+    writeln!(requests, "{}", Request::Hello(name.to_string()))?;
+    reply.recv()??;
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Info,
+            &format!(
+                "Joined with {} line(s); type `help` for commands",
+                document.lock().expect("mirror lock").lines().len()
+            )
+        )
+    );
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let (verb, rest) = line
+            .trim_start()
+            .split_once(' ')
+            .unwrap_or((line.trim(), ""));
+        let request = match verb {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "show" => {
+                show(&document.lock().expect("mirror lock"));
+                continue;
+            }
+            "append" => {
+                let end = document.lock().expect("mirror lock").lines().len() + 1;
+                Ok(Request::Edit(Edit::Insert(end, rest.to_string())))
+            }
+            "save" => Ok(Request::Save),
+            verb => Request::parse(&format!("{} {}", verb.to_uppercase(), rest)),
+        };
+        let request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    theme().paint(Role::Error, &format!("{}; type `help` for commands", e))
+                );
+                continue;
+            }
+        };
+        writeln!(requests, "{}", request)?;
+        match reply.recv()? {
+            Ok(()) => {}
+            Err(e) if e == "the session has ended" => return Err(e.into()),
+            Err(e) => eprintln!("{}", theme().paint(Role::Error, &e)),
+        }
+    }
+    Ok(())
+}
+
+fn describe(by: &str, edit: &Edit) -> String {
+    match edit {
+        Edit::Lock(n) => format!("{} locked line {}", by, n),
+        Edit::Unlock(n) => format!("{} unlocked line {}", by, n),
+        Edit::Set(n, text) => format!("{} changed line {}: {}", by, n, text),
+        Edit::Insert(n, text) => format!("{} added line {}: {}", by, n, text),
+        Edit::Delete(n) => format!("{} deleted line {}", by, n),
+    }
+}
+
+fn show(document: &Document) {
+    for (i, line) in document.lines().iter().enumerate() {
+        let owner = document
+            .lock(i + 1)
+            .map_or(String::new(), |who| format!("  [{}]", who));
+        println!(
+            "{:>4}  {}{}",
+            i + 1,
+            line,
+            theme().paint(Role::Warning, &owner)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! `compare`: two takes of a section side by side, with how their lines
+//! differ in syllables and rhyme and how the takes score overall.
+
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::compare::{compare, Scores, Side};
+use lyrics_dsl::theme::Role;
+
+/// Widest a take's column gets before its lines are cut short.
+const MAX_WIDTH: usize = 40;
+
+pub fn command() -> Command {
+    Command::new("compare")
+        .about("Set two takes of a section side by side to pick between them")
+        .arg(
+            Arg::new("a")
+                .required(true)
+                .value_name("A")
+                .help("First take"),
+        )
+        .arg(
+            Arg::new("b")
+                .required(true)
+                .value_name("B")
+                .help("Second take"),
+        )
+        .arg(
+            Arg::new("section")
+                .long("section")
+                .value_name("NAME")
+                .help("Compare only the first section with this name, e.g. chorus or \"verse 2\""),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the comparison as JSON"),
+        )
+}
+
+/// `text` cut or padded to `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count > width {
+        let cut: String = text.chars().take(width - 1).collect();
+        format!("{}…", cut)
+    } else {
+        format!("{}{}", text, " ".repeat(width - count))
+    }
+}
+
+fn cell(side: Option<&Side>, width: usize) -> String {
+    match side {
+        Some(side) => {
+            let rhyme = side.rhyme.map_or(String::from(" "), String::from);
+            format!(
+                "{} {} {}",
+                fit(&side.text, width),
+                theme().paint(Role::Hint, &format!("{:>2}", side.syllables)),
+                theme().paint(Role::Hint, &rhyme)
+            )
+        }
+        None => " ".repeat(width + 5),
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let a_path = matches.get_one::<String>("a").expect("required");
+    let b_path = matches.get_one::<String>("b").expect("required");
+    let section = matches.get_one::<String>("section").map(String::as_str);
+    let comparison =
+        compare(&load_song(a_path)?, &load_song(b_path)?, section).map_err(|e| e.to_string())?;
+
+    if matches.get_flag("json") {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+        return Ok(());
+    }
+
+    let width = comparison
+        .rows
+        .iter()
+        .flat_map(|r| [&r.a, &r.b])
+        .flatten()
+        .map(|s| s.text.chars().count())
+        .chain([a_path.chars().count(), b_path.chars().count()])
+        .max()
+        .unwrap_or(0)
+        .min(MAX_WIDTH);
+    if let Some(label) = &comparison.section {
+        println!("{}", theme().paint(Role::Heading, &label.to_uppercase()));
+    }
+    println!(
+        "   {} │ {}",
+        fit(&format!("A: {}", a_path), width + 5).bold(),
+        format!("B: {}", b_path).bold()
+    );
+    for (n, row) in comparison.rows.iter().enumerate() {
+        let mut line = format!(
+            "{:>2} {} │ {}",
+            theme().paint(Role::Hint, &(n + 1).to_string()),
+            cell(row.a.as_ref(), width),
+            cell(row.b.as_ref(), width)
+        );
+        match row.syllable_delta() {
+            Some(0) => line += &format!("  {}", theme().paint(Role::Hint, "±0")),
+            Some(d) => line += &format!("  {}", theme().paint(Role::Warning, &format!("{:+}", d))),
+            None => {}
+        }
+        if let (false, Some(a), Some(b)) = (row.same_ending(), &row.a, &row.b) {
+            line += &format!(
+                " {}",
+                theme().paint(Role::Warning, &format!("-{} → -{}", a.ending, b.ending))
+            );
+        }
+        println!("{}", line.trim_end());
+    }
+
+    println!();
+    let (a, b) = (&comparison.a, &comparison.b);
+    println!("{:<14} {:>5} {:>5}", "", "A".bold(), "B".bold());
+    score_row("lines", a.lines, b.lines, false);
+    score_row("syllables", a.syllables, b.syllables, false);
+    let ranked = [
+        ("singability", Some(a.singability), Some(b.singability)),
+        (
+            "rhyme density",
+            Some(a.rhyme_density),
+            Some(b.rhyme_density),
+        ),
+        ("meter fit", a.meter_fit, b.meter_fit),
+        ("overall", Some(a.overall), Some(b.overall)),
+    ];
+    for (name, a_score, b_score) in ranked {
+        if let (Some(a_score), Some(b_score)) = (a_score, b_score) {
+            score_row(name, a_score.into(), b_score.into(), true);
+        }
+    }
+    println!("{}", verdict(a, b));
+    Ok(())
+}
+
+/// One figure for both takes; a `ranked` figure shows the higher in green.
+fn score_row(name: &str, a: usize, b: usize, ranked: bool) {
+    let paint = |mine: usize, theirs: usize| {
+        let text = format!("{:>5}", mine);
+        match ranked && mine > theirs {
+            true => theme().paint(Role::Success, &text).bold(),
+            false => text.normal(),
+        }
+    };
+    println!("{:<14} {} {}", name, paint(a, b), paint(b, a));
+}
+
+fn verdict(a: &Scores, b: &Scores) -> ColoredString {
+    match a.overall.cmp(&b.overall) {
+        std::cmp::Ordering::Greater => theme().paint(
+            Role::Success,
+            &format!("A scores higher by {}", a.overall - b.overall),
+        ),
+        std::cmp::Ordering::Less => theme().paint(
+            Role::Success,
+            &format!("B scores higher by {}", b.overall - a.overall),
+        ),
+        std::cmp::Ordering::Equal => "A and B score the same".normal(),
+    }
+}
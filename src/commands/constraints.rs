@@ -0,0 +1,89 @@
+//! `constraints`: what a line still to be written must satisfy, as JSON for
+//! tools that propose lines, and whether a proposal satisfies it.
+
+use super::{load_valid, project_root, CommandResult, PROJECT_DIR};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::constraints::{constraints, BANNED_FILE};
+use lyrics_dsl::i18n::tr;
+use std::error::Error;
+
+pub fn command() -> Command {
+    Command::new("constraints")
+        .about("Describe what a line must satisfy as JSON, or check a candidate for it")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song the line belongs to"),
+        )
+        .arg(
+            Arg::new("line")
+                .long("line")
+                .required(true)
+                .value_name("N")
+                .value_parser(value_parser!(usize))
+                .help("Source line of the lyric line"),
+        )
+        .arg(
+            Arg::new("check-candidate")
+                .long("check-candidate")
+                .value_name("TEXT")
+                .help("Check a proposed line instead, failing if it misses a constraint"),
+        )
+        .arg(
+            Arg::new("ban")
+                .long("ban")
+                .value_name("WORDS")
+                .value_delimiter(',')
+                .help("Words or phrases the line may not use, besides the project's banned.txt"),
+        )
+}
+
+/// The project's banned words, one a line; none if there is no list.
+pub fn project_banned() -> Result<Vec<String>, Box<dyn Error>> {
+    let path = project_root()?.join(PROJECT_DIR).join(BANNED_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(list) => Ok(list
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(tr("cannot-read", &[("path", &path.display()), ("error", &e)]).into()),
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let number = *matches.get_one::<usize>("line").expect("required");
+    let loaded = load_valid(path)?;
+    let mut banned = project_banned()?;
+    if let Some(terms) = matches.get_many::<String>("ban") {
+        banned.extend(
+            terms
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+        );
+    }
+    let constraints = constraints(&loaded.resolved.song, &loaded.source, number, &banned)
+        .map_err(|e| format!("{}: {}", path, e))?;
+
+    match matches.get_one::<String>("check-candidate") {
+        None => println!("{}", serde_json::to_string_pretty(&constraints)?),
+        Some(candidate) => {
+            let verdict = constraints.check(candidate);
+            println!("{}", serde_json::to_string_pretty(&verdict)?);
+            if !verdict.ok {
+                let missed: Vec<&str> = verdict
+                    .checks
+                    .iter()
+                    .filter(|c| !c.ok)
+                    .map(|c| c.constraint)
+                    .collect();
+                return Err(format!("candidate misses {}", missed.join(", ")).into());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! `cowrite export` and `cowrite import`: a song's structure, style and open
+//! lines packed into a prompt for a language model, and the lines it
+//! suggests merged back as `!! idea: suggested: ...` notes.
+
+use super::constraints::project_banned;
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, write_file, Outcome};
+use super::{load_valid, parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::cowrite;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+
+fn file_arg() -> Arg {
+    Arg::new("file")
+        .required(true)
+        .value_name("FILE")
+        .help("Song to co-write")
+}
+
+pub fn command() -> Command {
+    Command::new("cowrite")
+        .about("Pack a song into a prompt for a language model and merge its suggestions back")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Write the song's structure, style and open lines as a prompt pack")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_parser(["md", "json"])
+                        .default_value("md")
+                        .help("A Markdown prompt to paste, or JSON for a script"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write to FILE instead of standard output"),
+                )
+                .arg(backup_arg()),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Add a model's suggested lines to the song as idea notes")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("reply")
+                        .required(true)
+                        .value_name("REPLY")
+                        .help("The model's answer, JSON as the prompt pack asks for"),
+                )
+                .arg(dry_run_arg())
+                .arg(log_change_arg())
+                .arg(backup_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand().expect("subcommand is required") {
+        ("export", sub) => export(sub),
+        ("import", sub) => import(sub),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn export(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let loaded = load_valid(path)?;
+    let pack = cowrite::pack(&loaded.resolved.song, &loaded.source, &project_banned()?);
+    if pack.open.is_empty() {
+        eprintln!(
+            "{} {} has no placeholders; mark the lines to write with ____ or <tbd>",
+            theme().paint(Role::Warning, "warning:").bold(),
+            path
+        );
+    }
+    let out = match matches.get_one::<String>("format").map(String::as_str) {
+        Some("json") => serde_json::to_string_pretty(&pack)? + "\n",
+        _ => pack.to_markdown(),
+    };
+    match matches.get_one::<String>("output") {
+        Some(output) => {
+            write_file(matches, output, out.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", output)]))
+            );
+        }
+        None => std::io::stdout().write_all(out.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn import(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let reply_path = matches.get_one::<String>("reply").expect("required");
+    let reply = std::fs::read_to_string(reply_path)
+        .map_err(|e| tr("cannot-read", &[("path", reply_path), ("error", &e)]))?;
+    let suggestions = cowrite::suggestions(&reply).map_err(|e| format!("{}: {}", reply_path, e))?;
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    let merged = cowrite::merge(&mut song, &source, &suggestions, &project_banned()?);
+    if !merged.unknown.is_empty() {
+        eprintln!(
+            "{} the song has no {}; those suggestions were left out",
+            theme().paint(Role::Warning, "warning:").bold(),
+            merged.unknown.join(", ")
+        );
+    }
+    eprintln!(
+        "{} suggestions added as idea notes, {} of them missing a constraint",
+        merged.added, merged.missing
+    );
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
@@ -0,0 +1,182 @@
+//! `draft save`, `draft list` and `draft diff`: numbered snapshots of a
+//! song under `.lyricsdsl/drafts/`, one directory per song named after its
+//! path in the project. Saving a draft also logs it in the song's
+//! `history:` block.
+
+use super::keys::locked_key;
+use super::output::{backup_arg, write_file};
+use super::{project_root, read_song, theme, CommandResult, PROJECT_DIR};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::changelog::{self, log_change};
+use lyrics_dsl::draft::DraftStore;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::merge::{diff, ChangeKind, LineChange};
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::path::Path;
+
+fn file_arg() -> Arg {
+    Arg::new("file")
+        .required(true)
+        .value_name("FILE")
+        .help("Song whose drafts to use")
+}
+
+pub fn command() -> Command {
+    Command::new("draft")
+        .about("Save, list and compare snapshots of a song without git")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("save")
+                .about("Save the song as the next draft")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("message")
+                        .required(true)
+                        .value_name("MESSAGE")
+                        .help("What this draft tries, e.g. \"new bridge\""),
+                )
+                .arg(backup_arg()),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the saved drafts of a song")
+                .arg(file_arg()),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Show what changed, section by section, since a draft")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("draft")
+                        .required(true)
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .help("Draft to compare from"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .value_name("M")
+                        .value_parser(value_parser!(usize))
+                        .help("Draft to compare to [default: the file as it is now]"),
+                ),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let (name, sub) = matches.subcommand().expect("subcommand is required");
+    let path = sub.get_one::<String>("file").expect("required");
+    let store = store_for(Path::new(path))?;
+    match name {
+        "save" => save(sub, path, &store),
+        "list" => list(path, &store),
+        "diff" => show_diff(sub, path, &store),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+/// The drafts of `file`, kept under the project's drafts directory at the
+/// file's path relative to the project root. A locked song's drafts are
+/// locked with its key.
+pub fn store_for(file: &Path) -> Result<DraftStore, Box<dyn Error>> {
+    let root = project_root()?;
+    let canonical = file
+        .canonicalize()
+        .map_err(|e| tr("cannot-read", &[("path", &file.display()), ("error", &e)]))?;
+    let relative = root
+        .canonicalize()
+        .ok()
+        .and_then(|root| canonical.strip_prefix(root).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| canonical.file_name().expect("a file").into());
+    let store = DraftStore::new(root.join(PROJECT_DIR).join("drafts").join(relative));
+    match locked_key(file)? {
+        Some(key) => Ok(store.locked(key)),
+        None => Ok(store),
+    }
+}
+
+fn save(matches: &ArgMatches, path: &str, store: &DraftStore) -> CommandResult {
+    let message = matches.get_one::<String>("message").expect("required");
+    let when = changelog::now();
+    let mut source = read_song(path)?;
+    // A song that does not parse is still saved, just without the entry.
+    match log_change(&source, &when, message) {
+        Ok(logged) => {
+            write_file(matches, path, logged.as_bytes())?;
+            source = logged;
+        }
+        Err(_) => eprintln!(
+            "{}",
+            theme().paint(
+                Role::Warning,
+                &format!("{} does not parse, so its history was not updated", path)
+            )
+        ),
+    }
+    let draft = store
+        .save(&source, message, &when)
+        .map_err(|e| e.to_string())?;
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!(
+                "💾 Saved draft {} of {}: {}",
+                draft.number, path, draft.message
+            )
+        )
+    );
+    Ok(())
+}
+
+fn list(path: &str, store: &DraftStore) -> CommandResult {
+    let drafts = store.list().map_err(|e| e.to_string())?;
+    if drafts.is_empty() {
+        eprintln!("no drafts of {} yet; save one with `draft save`", path);
+    }
+    for draft in drafts {
+        println!(
+            "{:>4}  {}  {}",
+            draft.number,
+            theme().paint(Role::Hint, &draft.when),
+            draft.message
+        );
+    }
+    Ok(())
+}
+
+fn show_diff(matches: &ArgMatches, path: &str, store: &DraftStore) -> CommandResult {
+    let from = *matches.get_one::<usize>("draft").expect("required");
+    let load = |n| store.load(n).map_err(|e| format!("{}: {}", path, e));
+    let old = load(from)?;
+    let (new, label) = match matches.get_one::<usize>("to") {
+        Some(&to) => (load(to)?, format!("draft {}", to)),
+        None => (read_song(path)?, path.to_string()),
+    };
+    println!("{}", format!("draft {} → {}", from, label).bold());
+    let changes = diff(&old, &new);
+    if changes.is_empty() {
+        println!("no changes");
+    }
+    for change in changes {
+        let header = match change.kind {
+            ChangeKind::Added => theme().paint(Role::Success, &format!("+ {}", change.block)),
+            ChangeKind::Removed => theme().paint(Role::Error, &format!("- {}", change.block)),
+            ChangeKind::Modified => theme().paint(Role::Info, &format!("~ {}", change.block)),
+        };
+        println!("{}", header.bold());
+        for line in change.lines {
+            match line {
+                LineChange::Removed(text) => {
+                    println!("    {}", theme().paint(Role::Error, &format!("-{}", text)))
+                }
+                LineChange::Added(text) => println!(
+                    "    {}",
+                    theme().paint(Role::Success, &format!("+{}", text))
+                ),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! `edit`: structural edits for scripts, such as
+//! `edit song.lyr move-section 3 --before 1`, made in place without
+//! rewriting the rest of the file.
+
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{read_song, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::edit::{
+    delete_line, delete_section, insert_line, insert_section, move_section, Place,
+};
+
+/// `--before N` and `--after N`, counting what `unit` says.
+fn place_args(unit: &'static str, required: bool) -> [Arg; 2] {
+    let before = Arg::new("before")
+        .long("before")
+        .value_name("N")
+        .value_parser(value_parser!(usize))
+        .conflicts_with("after")
+        .help(format!("Put it before {}", unit));
+    let before = match required {
+        true => before.required_unless_present("after"),
+        false => before,
+    };
+    let after = Arg::new("after")
+        .long("after")
+        .value_name("N")
+        .value_parser(value_parser!(usize))
+        .help(format!("Put it after {}", unit));
+    [before, after]
+}
+
+fn place(matches: &ArgMatches) -> Option<Place> {
+    match (
+        matches.get_one::<usize>("before"),
+        matches.get_one::<usize>("after"),
+    ) {
+        (Some(&n), _) => Some(Place::Before(n)),
+        (None, Some(&n)) => Some(Place::After(n)),
+        (None, None) => None,
+    }
+}
+
+fn number_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .required(true)
+        .value_name("N")
+        .value_parser(value_parser!(usize))
+        .help(help)
+}
+
+pub fn command() -> Command {
+    Command::new("edit")
+        .about("Insert, move and delete sections and lines, leaving the rest of the file as it is")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to edit"),
+        )
+        .arg(dry_run_arg().global(true))
+        .arg(log_change_arg().global(true))
+        .arg(backup_arg().global(true))
+        .subcommand(
+            Command::new("insert-section")
+                .about("Add a section, after the last one unless placed")
+                .arg(
+                    Arg::new("header")
+                        .required(true)
+                        .value_name("HEADER")
+                        .help("Its header, e.g. BRIDGE, VERSE[3] or \"REPEAT CHORUS\""),
+                )
+                .arg(
+                    Arg::new("lines")
+                        .num_args(1..)
+                        .value_name("LINE")
+                        .help("Its lines; a REPEAT has none"),
+                )
+                .args(place_args("section N", false)),
+        )
+        .subcommand(
+            Command::new("move-section")
+                .about("Move a section before or after another")
+                .arg(number_arg("section", "Section to move, counted from 1"))
+                .args(place_args("section N", true)),
+        )
+        .subcommand(
+            Command::new("delete-section")
+                .about("Delete a section")
+                .arg(number_arg("section", "Section to delete, counted from 1")),
+        )
+        .subcommand(
+            Command::new("insert-line")
+                .about("Add a lyric line before or after another")
+                .arg(
+                    Arg::new("text")
+                        .required(true)
+                        .value_name("TEXT")
+                        .help("The line, with any attributes, e.g. \"Hold on {chord: G}\""),
+                )
+                .args(place_args("the line on source line N", true)),
+        )
+        .subcommand(
+            Command::new("delete-line")
+                .about("Delete a lyric line and its harmony parts")
+                .arg(number_arg("line", "Source line it is written on")),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let source = read_song(path)?;
+    let (name, sub) = matches.subcommand().expect("subcommand is required");
+    let number = |name| *sub.get_one::<usize>(name).expect("required");
+    let edited = match name {
+        "insert-section" => {
+            let header = sub.get_one::<String>("header").expect("required");
+            let lines: Vec<String> = sub
+                .get_many::<String>("lines")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+            insert_section(&source, header, &lines, place(sub))
+        }
+        "move-section" => move_section(&source, number("section"), place(sub).expect("required")),
+        "delete-section" => delete_section(&source, number("section")),
+        "insert-line" => {
+            let text = sub.get_one::<String>("text").expect("required");
+            insert_line(&source, text, place(sub).expect("required"))
+        }
+        "delete-line" => delete_line(&source, number("line")),
+        _ => unreachable!("subcommand is required"),
+    }
+    .map_err(|e| format!("{}: {}", path, e))?;
+    let changed = apply(sub, path, &source, &edited)? == Outcome::Changed;
+    summary(sub, changed as usize, 1)
+}
@@ -0,0 +1,67 @@
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::codes::{self, Code, ALL};
+use lyrics_dsl::extension;
+use lyrics_dsl::suggest::{self, did_you_mean};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("explain")
+        .about("Describe a diagnostic code, with examples")
+        .arg(
+            Arg::new("code")
+                .value_name("CODE")
+                .help("Code to explain, e.g. E006; lists every code when omitted"),
+        )
+}
+
+/// `text` with every non-blank line indented, for the examples.
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| match line {
+            "" => "\n".to_string(),
+            line => format!("    {}\n", line),
+        })
+        .collect()
+}
+
+fn explain(code: &Code) {
+    println!("{} {}: {}", code.id.bold(), code.severity, code.title);
+    println!();
+    println!("{}", code.explanation);
+    if let Some(extra) = code.extra_pest {
+        println!();
+        println!("With this {}:", extension::FILE_NAME);
+        print!("{}", indent(extra));
+    }
+    println!();
+    println!("{}", theme().paint(Role::Error, "Wrong:"));
+    print!("{}", indent(code.wrong));
+    println!();
+    println!("{}", theme().paint(Role::Success, "Right:"));
+    print!("{}", indent(code.right));
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let Some(id) = matches.get_one::<String>("code") else {
+        for code in ALL {
+            println!("{} {:<7} {}", code.id, code.severity, code.title);
+        }
+        return Ok(());
+    };
+    match codes::find(id) {
+        Some(code) => {
+            explain(code);
+            Ok(())
+        }
+        None => {
+            let mut message = format!("no diagnostic code `{}`", id);
+            let upper = id.to_uppercase();
+            if let Some(similar) = did_you_mean(&upper, ALL.iter().map(|code| code.id)) {
+                message = format!("{}; {}", message, suggest::help(similar));
+            }
+            Err(message.into())
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use super::output::{backup_arg, write_file};
+use super::signing::{sign_arg, sign_output};
+use super::{
+    capitalizer, case_arg, load_song, project_config, project_root, protect_arg, read_comments,
+    run_hooks, theme, CommandResult, PROJECT_DIR,
+};
+use clap::parser::ValueSource;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::chords::diagram::{Fretboard, Instrument};
+use lyrics_dsl::export::bundle::{bundle, Archive};
+use lyrics_dsl::export::{
+    exporter, exporters, with_markdown_options, with_sheet_options, with_slide_options,
+    without_alternatives, without_harmony, without_placeholders, ExportError, Exporter, Harmony,
+    MarkdownOptions, SheetOptions, SlideOptions,
+};
+use lyrics_dsl::feedback::Comments;
+use lyrics_dsl::freshness::{self, Stamps};
+use lyrics_dsl::hooks::Stage;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::preset;
+use lyrics_dsl::template::Layout;
+use lyrics_dsl::theme::Role;
+use serde_json::json;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Arguments that change what an exporter renders.
+const OPTIONS: &[&str] = &[
+    "format",
+    "formats",
+    "chord-diagrams",
+    "tuning",
+    "smart-typography",
+    "harmony",
+    "placeholders",
+    "template",
+    "comments",
+    "case",
+    "protect",
+    "lines-per-slide",
+    "no-title-slide",
+    "blank-between",
+    "heading-level",
+    "no-blockquotes",
+    "inline-chords",
+];
+
+pub fn command() -> Command {
+    let formats: Vec<&'static str> = exporters().iter().map(|e| e.name()).collect();
+    Command::new("export")
+        .about("Render a song into another format")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to export"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .default_value("json")
+                .value_parser(formats.clone())
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("preset")
+                .long("preset")
+                .value_name("NAME")
+                .help("Take options from a preset, e.g. web, print-songbook or karaoke-bar"),
+        )
+        .arg(
+            Arg::new("bundle")
+                .long("bundle")
+                .value_name("ARCHIVE")
+                .requires("formats")
+                .conflicts_with_all(["output", "in-place"])
+                .help("Package several formats and a manifest.json in a .zip or .tar"),
+        )
+        .arg(
+            Arg::new("formats")
+                .long("formats")
+                .value_name("FORMATS")
+                .value_delimiter(',')
+                .value_parser(formats)
+                .requires("bundle")
+                .help("Formats to bundle, e.g. lrc,html,pdf,json"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(
+            Arg::new("in-place")
+                .long("in-place")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("output")
+                .help("Overwrite the input file with the output"),
+        )
+        .arg(
+            Arg::new("chord-diagrams")
+                .long("chord-diagrams")
+                .value_name("INSTRUMENT")
+                .value_parser(["guitar", "ukulele"])
+                .help("Append chord diagrams (html and pdf)"),
+        )
+        .arg(
+            Arg::new("tuning")
+                .long("tuning")
+                .value_name("NOTES")
+                .requires("chord-diagrams")
+                .help("Open strings lowest first, e.g. DADGAD [default: standard]"),
+        )
+        .arg(
+            Arg::new("smart-typography")
+                .long("smart-typography")
+                .action(ArgAction::SetTrue)
+                .help("Set curly quotes, dashes and ellipses (html and pdf)"),
+        )
+        .arg(
+            Arg::new("harmony")
+                .long("harmony")
+                .value_name("MODE")
+                .value_parser(Harmony::NAMES)
+                .help("Include or exclude `+` harmony lines, or color each part (html and pdf)"),
+        )
+        .arg(
+            Arg::new("placeholders")
+                .long("placeholders")
+                .value_name("MODE")
+                .value_parser(["blank", "hide"])
+                .help("Show `____` and `<tbd>` placeholders as blanks or leave them out [default: blank]"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .value_name("FILE")
+                .help("Lay the sheet out with a template, partials beside it (html)"),
+        )
+        .arg(
+            Arg::new("comments")
+                .long("comments")
+                .value_name("FILE")
+                .help("Set review comments keyed by line ID beside their lines (html and pdf)"),
+        )
+        .arg(case_arg())
+        .arg(protect_arg())
+        .arg(
+            Arg::new("lines-per-slide")
+                .long("lines-per-slide")
+                .value_name("N")
+                .value_parser(value_parser!(u32).range(1..))
+                .help("Most lyric lines on one slide (pptx and slides) [default: 4]"),
+        )
+        .arg(
+            Arg::new("no-title-slide")
+                .long("no-title-slide")
+                .action(ArgAction::SetTrue)
+                .help("Leave out the title slide (pptx and slides)"),
+        )
+        .arg(
+            Arg::new("blank-between")
+                .long("blank-between")
+                .action(ArgAction::SetTrue)
+                .help("Put a blank slide between sections (pptx and slides)"),
+        )
+        .arg(
+            Arg::new("heading-level")
+                .long("heading-level")
+                .value_name("N")
+                .value_parser(value_parser!(u8).range(2..=6))
+                .help("Heading level of section names (md) [default: 2]"),
+        )
+        .arg(
+            Arg::new("no-blockquotes")
+                .long("no-blockquotes")
+                .action(ArgAction::SetTrue)
+                .help("Write stanzas as plain paragraphs (md)"),
+        )
+        .arg(
+            Arg::new("inline-chords")
+                .long("inline-chords")
+                .action(ArgAction::SetTrue)
+                .help("Show each line's chords before it (md)"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Export even when the output is up to date"),
+        )
+        .arg(backup_arg())
+        .arg(sign_arg().requires("output"))
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.get_one::<String>("preset") {
+        Some(name) => export(&with_preset(matches, name)?),
+        None => export(matches),
+    }
+}
+
+/// `matches` again with the options of the preset `name` filled in where
+/// the command line leaves them out.
+fn with_preset(matches: &ArgMatches, name: &str) -> Result<ArgMatches, Box<dyn Error>> {
+    let presets = &project_config()?.presets;
+    let preset = preset::find(presets, name).ok_or_else(|| {
+        format!(
+            "no preset `{}`; there are {}",
+            name,
+            preset::names(presets).join(", ")
+        )
+    })?;
+    if let Some((option, _)) = preset
+        .settings
+        .iter()
+        .find(|(o, _)| !OPTIONS.contains(&o.as_str()))
+    {
+        return Err(format!(
+            "preset `{}`: `{}` is not an export option; expected one of {}",
+            name,
+            option,
+            OPTIONS.join(", ")
+        )
+        .into());
+    }
+
+    let command = command();
+    let mut args = vec!["export".to_string()];
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let switch = matches!(arg.get_action(), ArgAction::SetTrue);
+        let given = matches.value_source(id) == Some(ValueSource::CommandLine);
+        let values: Vec<String> = match (given, preset.get(id)) {
+            (true, _) if switch => vec!["true".to_string()],
+            (true, _) => matches
+                .get_raw(id)
+                .into_iter()
+                .flatten()
+                .map(|v| v.to_string_lossy().into_owned())
+                .collect(),
+            (false, Some(value)) => vec![value.to_string()],
+            (false, None) => continue,
+        };
+        let Some(long) = arg.get_long() else {
+            args.extend(values);
+            continue;
+        };
+        match switch {
+            true => match values[0].as_str() {
+                "true" => args.push(format!("--{}", long)),
+                "false" => {}
+                value => {
+                    return Err(format!(
+                        "preset `{}`: `{}` is a switch, set to `true` or `false`, not `{}`",
+                        name, id, value
+                    )
+                    .into())
+                }
+            },
+            false => {
+                args.push(format!("--{}", long));
+                args.push(values.join(","));
+            }
+        }
+    }
+    command.try_get_matches_from(args).map_err(|e| {
+        let message = e.to_string();
+        let first = message.lines().next().unwrap_or_default();
+        format!("preset `{}`: {}", name, first.trim_start_matches("error: ")).into()
+    })
+}
+
+fn export(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let format = matches.get_one::<String>("format").expect("defaulted");
+
+    let archive = match matches.get_one::<String>("bundle") {
+        Some(path) => Some(
+            Archive::from_path(path)
+                .ok_or_else(|| format!("cannot bundle into '{}': use .zip or .tar", path))?,
+        ),
+        None => None,
+    };
+    let formats: Vec<&String> = match archive {
+        Some(_) => matches.get_many("formats").expect("required").collect(),
+        None => vec![format],
+    };
+
+    let mut song = without_alternatives(&load_song(file)?);
+    if let Some(capitalizer) = capitalizer(matches)? {
+        song = capitalizer.apply_song(&song);
+    }
+    if harmony(matches) == Harmony::Exclude {
+        song = without_harmony(&song);
+    }
+    if matches
+        .get_one::<String>("placeholders")
+        .is_some_and(|m| m == "hide")
+    {
+        song = without_placeholders(&song);
+    }
+    let review = comments(matches)?;
+    let orphans: Vec<&str> = review.orphans(&song).iter().map(|(id, _)| *id).collect();
+    if !orphans.is_empty() {
+        eprintln!(
+            "{} comments on lines no longer in the song: {}",
+            theme().paint(Role::Warning, "warning:").bold(),
+            orphans.join(", ")
+        );
+    }
+    let exporters = formats
+        .iter()
+        .map(|format| configured(format, matches, archive.is_some()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let output = match matches.get_flag("in-place") {
+        true => Some(file),
+        false => matches
+            .get_one::<String>("output")
+            .or(matches.get_one("bundle")),
+    };
+    let inputs = freshness::inputs(&[
+        format!("{:?}", song).as_bytes(),
+        format!("{:?}", options(matches)).as_bytes(),
+        format!("{:?}", template(matches)?).as_bytes(),
+        format!("{:?}", comments(matches)?).as_bytes(),
+    ]);
+    let mut project = match output {
+        Some(_) => Freshness::load()?,
+        None => None,
+    };
+    if let (Some(path), Some(project)) = (output, &project) {
+        let fresh = std::fs::read(path)
+            .is_ok_and(|contents| project.stamps.is_fresh(&key(path), &inputs, &contents));
+        if fresh && !matches.get_flag("force") {
+            eprintln!(
+                "{}",
+                theme().paint(Role::Hint, &tr("up-to-date", &[("path", &path)]))
+            );
+            return Ok(());
+        }
+    }
+
+    let mut details = match archive {
+        Some(_) => json!({ "formats": formats, "output": output }),
+        None => json!({ "format": format, "output": output }),
+    };
+    run_hooks(Stage::PreExport, file, details.clone())?;
+    let rendered = match archive {
+        Some(archive) => {
+            let stem = Path::new(file).file_stem().map(|s| s.to_string_lossy());
+            bundle(&song, &stem.unwrap_or("song".into()), &exporters, archive)?
+        }
+        None => exporters[0].export(&song)?,
+    };
+
+    match output {
+        Some(path) => {
+            write_file(matches, path, &rendered)?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+            );
+            sign_output(matches, path)?;
+            if let Some(project) = &mut project {
+                project.stamps.record(&key(path), inputs, &rendered);
+                project.save()?;
+            }
+        }
+        None => std::io::stdout().write_all(&rendered)?,
+    }
+    details["bytes"] = json!(rendered.len());
+    run_hooks(Stage::PostExport, file, details)
+}
+
+/// The exporter for `format` with the options given. In a bundle, options
+/// apply to the formats that take them and are ignored by the rest.
+fn configured(
+    format: &str,
+    matches: &ArgMatches,
+    bundled: bool,
+) -> Result<Box<dyn Exporter>, Box<dyn Error>> {
+    let configured = match sheet_options(matches)? {
+        Some(options) => with_sheet_options(format, options),
+        None if slide_options_given(matches) => {
+            let defaults = SlideOptions::default();
+            let options = SlideOptions {
+                lines_per_slide: matches
+                    .get_one::<u32>("lines-per-slide")
+                    .map_or(defaults.lines_per_slide, |n| *n as usize),
+                title_slide: !matches.get_flag("no-title-slide"),
+                blank_between: matches.get_flag("blank-between"),
+            };
+            with_slide_options(format, options)
+        }
+        None if markdown_options_given(matches) => {
+            let defaults = MarkdownOptions::default();
+            let options = MarkdownOptions {
+                heading_level: matches
+                    .get_one::<u8>("heading-level")
+                    .map_or(defaults.heading_level, |n| *n as usize),
+                blockquotes: !matches.get_flag("no-blockquotes"),
+                chords: matches.get_flag("inline-chords"),
+            };
+            with_markdown_options(format, options)
+        }
+        None => exporter(format),
+    };
+    match configured {
+        Err(ExportError::Unsupported { .. }) if bundled => Ok(exporter(format)?),
+        configured => Ok(configured?),
+    }
+}
+
+fn options(matches: &ArgMatches) -> Vec<(&str, Vec<String>)> {
+    OPTIONS
+        .iter()
+        .map(|id| {
+            let values = matches.get_raw(id).into_iter().flatten();
+            (
+                *id,
+                values.map(|v| v.to_string_lossy().into_owned()).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Stamps are kept by absolute path, so they hold from any directory.
+fn key(path: &str) -> String {
+    std::path::absolute(path).map_or(path.to_string(), |p| p.display().to_string())
+}
+
+/// The project's export stamps. Only projects with a `.lyricsdsl`
+/// directory keep them, so exporting elsewhere leaves no files behind.
+struct Freshness {
+    file: PathBuf,
+    stamps: Stamps,
+}
+
+impl Freshness {
+    fn load() -> Result<Option<Freshness>, Box<dyn Error>> {
+        let dir = project_root()?.join(PROJECT_DIR);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        let file = dir.join(freshness::FILE_NAME);
+        let stamps = match std::fs::read_to_string(&file) {
+            Ok(text) => Stamps::parse(&text).map_err(|e| format!("{}: {}", file.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Stamps::default(),
+            Err(e) => {
+                return Err(tr("cannot-read", &[("path", &file.display()), ("error", &e)]).into())
+            }
+        };
+        Ok(Some(Freshness { file, stamps }))
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        std::fs::write(&self.file, self.stamps.to_text())
+            .map_err(|e| format!("cannot write '{}': {}", self.file.display(), e).into())
+    }
+}
+
+/// The `html` and `pdf` options given, if any.
+fn sheet_options(matches: &ArgMatches) -> Result<Option<SheetOptions>, Box<dyn Error>> {
+    let chord_diagrams = match matches.get_one::<String>("chord-diagrams") {
+        Some(instrument) => {
+            let instrument = Instrument::parse(instrument).expect("validated by clap");
+            Some(match matches.get_one::<String>("tuning") {
+                Some(tuning) => Fretboard::new(instrument, tuning)
+                    .ok_or_else(|| format!("invalid tuning `{}`", tuning))?,
+                None => Fretboard::standard(instrument),
+            })
+        }
+        None => None,
+    };
+    let smart_typography = matches.get_flag("smart-typography");
+    let harmony = harmony(matches);
+    let template = template(matches)?;
+    let comments = comments(matches)?;
+    if chord_diagrams.is_none()
+        && !smart_typography
+        && harmony != Harmony::Separate
+        && template.is_none()
+        && comments.is_empty()
+    {
+        return Ok(None);
+    }
+    Ok(Some(SheetOptions {
+        chord_diagrams,
+        smart_typography,
+        harmony,
+        template,
+        comments,
+    }))
+}
+
+/// The `--comments` of a review, read afresh.
+fn comments(matches: &ArgMatches) -> Result<Comments, Box<dyn Error>> {
+    match matches.get_one::<String>("comments") {
+        Some(path) => read_comments(path),
+        None => Ok(Comments::default()),
+    }
+}
+
+/// The `--template` and the partials it includes, read afresh.
+fn template(matches: &ArgMatches) -> Result<Option<Layout>, Box<dyn Error>> {
+    match matches.get_one::<String>("template") {
+        Some(path) => Ok(Some(Layout::load(Path::new(path))?)),
+        None => Ok(None),
+    }
+}
+
+fn harmony(matches: &ArgMatches) -> Harmony {
+    matches
+        .get_one::<String>("harmony")
+        .and_then(|mode| Harmony::parse(mode))
+        .unwrap_or_default()
+}
+
+fn slide_options_given(matches: &ArgMatches) -> bool {
+    matches.contains_id("lines-per-slide")
+        || matches.get_flag("no-title-slide")
+        || matches.get_flag("blank-between")
+}
+
+fn markdown_options_given(matches: &ArgMatches) -> bool {
+    matches.contains_id("heading-level")
+        || matches.get_flag("no-blockquotes")
+        || matches.get_flag("inline-chords")
+}
@@ -0,0 +1,69 @@
+//! `extract`: a section or a range of source lines as a standalone song,
+//! with the song's metadata.
+
+use super::output::{backup_arg, write_file};
+use super::{load_valid, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::extract::{extract, Selection};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("extract")
+        .about("Take a section or a range of lines out as a standalone song")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to extract from"),
+        )
+        .arg(
+            Arg::new("section")
+                .long("section")
+                .value_name("NAME")
+                .required_unless_present("lines")
+                .conflicts_with("lines")
+                .help("The first section so named, e.g. bridge or \"verse 2\""),
+        )
+        .arg(
+            Arg::new("lines")
+                .long("lines")
+                .value_name("RANGE")
+                .help("Source lines to take, both ends included, e.g. 10..20"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let selection = match matches.get_one::<String>("section") {
+        Some(name) => Selection::Section(name.clone()),
+        None => {
+            let spec = matches.get_one::<String>("lines").expect("required");
+            Selection::lines(spec).map_err(|e| e.to_string())?
+        }
+    };
+    let loaded = load_valid(path)?;
+    let fragment = extract(&loaded.resolved.song, &loaded.source, &selection)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let text = format_song(&fragment);
+    match matches.get_one::<String>("output") {
+        Some(out) => {
+            write_file(matches, out, text.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", out)]))
+            );
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, Outcome};
+use super::{load_source, project_dictionary, read_song, report, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::diagnostic::Diagnostic;
+use lyrics_dsl::history::History;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::lint;
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::theme::Role;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("fix")
+        .about("Walk through a song's problems and apply suggested fixes")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to fix"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+/// Every problem in `source`: the syntax error if it does not parse, else
+/// the diagnostics every command reports followed by the style checks.
+fn findings(path: &str, source: &str) -> Result<Vec<Diagnostic>, Box<dyn std::error::Error>> {
+    let song = match parse_song(source) {
+        Ok(song) => song,
+        Err(e) => return Ok(vec![error_diagnostic(&e, source)]),
+    };
+    let mut found = load_source(path, source.to_string())?.resolved.diagnostics;
+    let name = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    found.extend(lint::check(&song, source, &name, project_dictionary()?));
+    found.sort_by_key(|d| d.span.start);
+    Ok(found)
+}
+
+/// What to do about a suggested fix.
+enum Answer {
+    Apply,
+    Skip,
+    /// Take back the last fix applied instead.
+    Undo,
+    Quit,
+}
+
+/// Ask whether to apply `description`.
+fn confirm(input: &mut impl Iterator<Item = io::Result<String>>, description: &str) -> Answer {
+    loop {
+        print!("{} ", tr("fix-prompt", &[("fix", &description.bold())]));
+        if io::stdout().flush().is_err() {
+            return Answer::Quit;
+        }
+        let Some(Ok(answer)) = input.next() else {
+            return Answer::Quit;
+        };
+        match answer.trim() {
+            "y" | "Y" | "yes" => return Answer::Apply,
+            "n" | "N" | "no" | "" => return Answer::Skip,
+            "u" | "U" | "undo" => return Answer::Undo,
+            "q" | "Q" | "quit" => return Answer::Quit,
+            _ => println!("{}", theme().paint(Role::Hint, &tr("fix-answer", &[]))),
+        }
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let original = read_song(path)?;
+    let mut source = original.clone();
+    let mut input = io::stdin().lock().lines();
+    // Findings already shown, by message and fix, so declined fixes are not
+    // offered again as the song changes around them.
+    let mut seen: HashSet<(String, Option<String>)> = HashSet::new();
+    let mut applied = 0;
+    let mut history: History<String> = History::default();
+
+    loop {
+        let found = findings(path, &source)?;
+        let next = found.into_iter().find(|d| {
+            let fix = d.fix.as_ref().map(|f| f.description.clone());
+            !seen.contains(&(d.message.clone(), fix))
+        });
+        let Some(diagnostic) = next else { break };
+        let fix = diagnostic.fix.as_ref().map(|f| f.description.clone());
+        seen.insert((diagnostic.message.clone(), fix));
+
+        report(path, &source, std::slice::from_ref(&diagnostic));
+        match &diagnostic.fix {
+            Some(fix) => match confirm(&mut input, &fix.description) {
+                Answer::Apply => {
+                    let fixed = fix.apply(&source);
+                    history.record(std::mem::replace(&mut source, fixed), None);
+                    applied += 1;
+                }
+                Answer::Skip => {}
+                // The finding shown is offered again once the last fix is
+                // taken back.
+                Answer::Undo => {
+                    seen.remove(&(diagnostic.message.clone(), Some(fix.description.clone())));
+                    match history.undo(source.clone()) {
+                        Some(before) => {
+                            source = before;
+                            applied -= 1;
+                            println!("{}", theme().paint(Role::Hint, &tr("fix-undone", &[])));
+                        }
+                        None => println!(
+                            "{}",
+                            theme().paint(Role::Hint, &tr("fix-nothing-to-undo", &[]))
+                        ),
+                    }
+                }
+                Answer::Quit => break,
+            },
+            // Nothing after a syntax error can be checked until it is fixed.
+            None if parse_song(&source).is_err() => break,
+            None => println!("{}", theme().paint(Role::Hint, &tr("fix-none", &[]))),
+        }
+        println!();
+    }
+
+    if apply(matches, path, &original, &source)? == Outcome::Changed {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &tr("fixes-applied", &[("count", &applied), ("path", &path)])
+            )
+        );
+    } else {
+        eprintln!("{}", theme().paint(Role::Hint, &tr("nothing-changed", &[])));
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::analysis::flow::flow;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("flow")
+        .about("Count the syllables on each beat of lines marked with `·`")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song with `·` beat markers"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the report as JSON"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let flows = flow(&load_song(file)?);
+
+    if matches.get_flag("json") {
+        println!("{}", serde_json::to_string_pretty(&flows)?);
+        return Ok(());
+    }
+    if flows.is_empty() {
+        return Err(format!("{}: no lines have `·` beat markers", file).into());
+    }
+
+    let mut section = None;
+    for line in &flows {
+        if section != Some(&line.section) {
+            println!("{}", format!("[{}]", line.section).bold());
+            section = Some(&line.section);
+        }
+        println!("  {}", line.text);
+        let mut counts = String::new();
+        for (i, beat) in line.beats.iter().enumerate() {
+            if i > 0 {
+                counts.push_str(if beat.downbeat { " | " } else { " · " });
+            }
+            match beat.pickup {
+                true => counts.push_str(&format!("({})", beat.syllables)),
+                false => counts.push_str(&beat.syllables.to_string()),
+            }
+        }
+        let mut rate = format!(
+            "{} a beat, {} a second at {} BPM",
+            line.syllables_per_beat, line.syllables_per_second, line.bpm
+        );
+        if let Some(subdivision) = line.subdivision() {
+            rate.push_str(&format!(" ({})", subdivision));
+        }
+        println!("    {}  {}", counts, theme().paint(Role::Hint, &rate));
+    }
+    Ok(())
+}
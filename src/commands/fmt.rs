@@ -0,0 +1,63 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{capitalizer, case_arg, parse, protect_arg, read_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("fmt")
+        .about("Rewrite songs in canonical formatting")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Songs to format"),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("dry-run")
+                .help("Only report files that are not formatted; fail if any"),
+        )
+        .arg(case_arg())
+        .arg(protect_arg())
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let files: Vec<&String> = matches
+        .get_many::<String>("files")
+        .expect("required")
+        .collect();
+    let capitalizer = capitalizer(matches)?;
+    let mut changed = 0;
+    for path in &files {
+        let source = read_song(path)?;
+        let mut song = parse(path, &source)?;
+        if let Some(capitalizer) = &capitalizer {
+            song = capitalizer.apply_song(&song);
+        }
+        let formatted = format_song(&song);
+        if matches.get_flag("check") {
+            if formatted != source {
+                changed += 1;
+                println!(
+                    "{} {}",
+                    theme().paint(Role::Warning, "not formatted:"),
+                    path
+                );
+            }
+        } else if apply(matches, path, &source, &formatted)? == Outcome::Changed {
+            changed += 1;
+        }
+    }
+    if matches.get_flag("check") && changed > 0 {
+        return Err(format!("{} file(s) need `lyrics-dsl fmt`", changed).into());
+    }
+    summary(matches, changed, files.len())
+}
@@ -0,0 +1,86 @@
+//! `:record` in interactive mode: capture a freestyle with rough timings.
+
+use super::sync::play;
+use super::{theme, CommandResult};
+use lyrics_dsl::ast::Timestamp;
+use lyrics_dsl::freestyle::Take;
+use lyrics_dsl::theme::Role;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Instant;
+
+const HELP: &str = "Type each line as you say it; a header such as CHORUS starts a section.
+:stop FILE saves the take as a song, :stop prints it, :cancel drops it.";
+
+/// Record a take from standard input until `:stop` or `:cancel`. With a
+/// `track` it is played, and lines are timed from its start.
+pub fn record(track: Option<&str>) -> CommandResult {
+    println!("{}", theme().paint(Role::Hint, HELP));
+    let mut player = match track {
+        Some(track) => Some(play(track, None)?),
+        None => None,
+    };
+    let start = Instant::now();
+    let mut take = Take::new();
+    let stdin = io::stdin();
+    let mut input = String::new();
+    let saved = loop {
+        let now = Timestamp::from_millis(start.elapsed().as_millis() as u64);
+        print!("{} ", theme().paint(Role::Error, &format!("● {}", now)));
+        io::stdout().flush()?;
+        input.clear();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            break None;
+        }
+        let at = Timestamp::from_millis(start.elapsed().as_millis() as u64);
+        let line = input.trim();
+        if line == ":cancel" {
+            println!("{}", theme().paint(Role::Hint, "Take dropped"));
+            break None;
+        }
+        let Some(file) = line.strip_prefix(":stop") else {
+            take.push(line, at);
+            continue;
+        };
+        let file = file.trim();
+        let title = match Path::new(file).file_stem() {
+            Some(stem) if !file.is_empty() => stem.to_string_lossy().into_owned(),
+            _ => "Freestyle".to_string(),
+        };
+        let source = take.to_source(&title);
+        if file.is_empty() {
+            print!("{}", source);
+            break None;
+        }
+        if Path::new(file).exists() {
+            println!(
+                "{}",
+                theme().paint(
+                    Role::Error,
+                    &format!("{} already exists; pick another name", file)
+                )
+            );
+            continue;
+        }
+        std::fs::write(file, &source)?;
+        break Some(file.to_string());
+    };
+    if let Some(player) = &mut player {
+        let _ = player.kill();
+        let _ = player.wait();
+    }
+    if let Some(file) = saved {
+        println!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &format!(
+                    "{} line(s) saved to {} with draft timings",
+                    take.len(),
+                    file
+                )
+            )
+        );
+    }
+    Ok(())
+}
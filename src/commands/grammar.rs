@@ -0,0 +1,72 @@
+use super::{project_extension, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::theme::Role;
+use lyrics_dsl::{extension, grammar};
+
+pub fn command() -> Command {
+    Command::new("grammar")
+        .about("Show the DSL grammar")
+        .arg(
+            Arg::new("rule")
+                .value_name("RULE")
+                .help("Only show the named rule"),
+        )
+        .arg(
+            Arg::new("dump")
+                .long("dump")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("ebnf")
+                .help("Print the effective pest rules (default)"),
+        )
+        .arg(
+            Arg::new("ebnf")
+                .long("ebnf")
+                .action(ArgAction::SetTrue)
+                .help("Print the rules as EBNF productions"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .value_name("FILE")
+                .conflicts_with("rule")
+                .help("Write an HTML page with railroad diagrams for every rule"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    if let Some(path) = matches.get_one::<String>("html") {
+        std::fs::write(path, grammar::railroad_html())?;
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &format!("💾 Grammar diagrams written to: {}", path)
+            )
+        );
+        return Ok(());
+    }
+
+    let ebnf = matches.get_flag("ebnf");
+    let extra = project_extension()?.map_or(&[][..], |e| e.rules());
+    match matches.get_one::<String>("rule") {
+        Some(name) => {
+            let rule = grammar::rule(name)
+                .or_else(|| extra.iter().find(|r| &r.name == name).cloned())
+                .ok_or_else(|| format!("no grammar rule named '{}'", name))?;
+            if let Some(comment) = &rule.comment {
+                println!("// {}", comment);
+            }
+            println!("{}", if ebnf { rule.to_ebnf() } else { rule.to_pest() });
+        }
+        None if ebnf => print!("{}", grammar::to_ebnf()),
+        None => print!("{}", grammar::dump()),
+    }
+    if matches.get_one::<String>("rule").is_none() && !extra.is_empty() {
+        println!("\n// {}", extension::FILE_NAME);
+        match ebnf {
+            true => print!("{}", grammar::ebnf_rules(extra)),
+            false => print!("{}", grammar::dump_rules(extra)),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! `history`: the changes logged in a song's `history:` block by
+//! `draft save` and `--log-change`, oldest first.
+
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("history")
+        .about("Show the changes logged in a song's history block")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song whose history to show"),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .value_name("DATE")
+                .help("Only changes made on or after DATE, e.g. 2026-10-01"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let song = parse(path, &read_song(path)?)?;
+    if song.history.is_empty() {
+        eprintln!(
+            "no history in {} yet; `draft save` and `--log-change` add to it",
+            path
+        );
+    }
+    let since = matches
+        .get_one::<String>("since")
+        .map_or("", String::as_str);
+    for entry in song.history.iter().filter(|e| e.when.as_str() >= since) {
+        println!(
+            "{}  {}",
+            theme().paint(Role::Hint, &entry.when),
+            entry.message
+        );
+    }
+    Ok(())
+}
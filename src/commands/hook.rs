@@ -0,0 +1,177 @@
+//! `hook install` and `hook run`: gating git commits on clean lyrics.
+//!
+//! The installed pre-commit hook calls `lyrics-dsl hook run`, which checks
+//! the staged version of every added or modified song, so what is checked is
+//! exactly what is about to be committed: it must validate, be formatted and
+//! pass the lint rules.
+
+use super::keys::decode;
+use super::{load_source, project_dictionary, report, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::diagnostic::has_errors;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::lint;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::theme::Role;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const HOOK: &str = "#!/bin/sh
+# Installed by `lyrics-dsl hook install`: check staged lyric files.
+exec lyrics-dsl hook run
+";
+
+pub fn command() -> Command {
+    Command::new("hook")
+        .about("Install or run the git pre-commit check for lyric files")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("install")
+                .about("Write .git/hooks/pre-commit")
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .action(ArgAction::SetTrue)
+                        .help("Replace an existing pre-commit hook"),
+                ),
+        )
+        .subcommand(
+            Command::new("run")
+                .about("Validate, format-check and lint the staged lyric files")
+                .arg(
+                    Arg::new("ext")
+                        .long("ext")
+                        .value_name("EXT")
+                        .num_args(1..)
+                        .action(ArgAction::Append)
+                        .default_values(["lyr"])
+                        .help("File extensions treated as songs"),
+                ),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand() {
+        Some(("install", sub)) => install(sub),
+        Some(("run", sub)) => check_staged(sub),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn git(args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = process::Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn install(matches: &ArgMatches) -> CommandResult {
+    let hooks = git(&["rev-parse", "--git-path", "hooks"])?;
+    let hooks = PathBuf::from(String::from_utf8_lossy(&hooks).trim());
+    let path = hooks.join("pre-commit");
+    if path.exists() && !matches.get_flag("force") {
+        return Err(format!(
+            "{} already exists; use --force to replace it",
+            path.display()
+        )
+        .into());
+    }
+
+    std::fs::create_dir_all(&hooks)?;
+    std::fs::write(&path, HOOK)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    println!(
+        "{} {}",
+        theme().paint(Role::Success, "✓ installed"),
+        path.display()
+    );
+    Ok(())
+}
+
+fn check_staged(matches: &ArgMatches) -> CommandResult {
+    let extensions: Vec<&String> = matches
+        .get_many::<String>("ext")
+        .expect("defaulted")
+        .collect();
+    let staged = git(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "--diff-filter=ACMR",
+        "-z",
+    ])?;
+    let files: Vec<String> = staged
+        .split(|b| *b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .filter(|name| {
+            extensions
+                .iter()
+                .any(|ext| name.ends_with(&format!(".{}", ext)))
+        })
+        .collect();
+
+    let dictionary = project_dictionary()?;
+    let mut failed = 0;
+    for path in &files {
+        let source = decode(path, git(&["show", &format!(":{}", path)])?)?;
+        let loaded = match load_source(path, source) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}", theme().paint(Role::Error, &e.to_string()));
+                continue;
+            }
+        };
+        let diagnostics = &loaded.resolved.diagnostics;
+        report(path, &loaded.source, diagnostics);
+        if has_errors(diagnostics) {
+            failed += 1;
+            continue;
+        }
+        let song = parse_song(&loaded.source)?;
+        if format_song(&song) != loaded.source {
+            failed += 1;
+            eprintln!(
+                "{} {} (run `lyrics-dsl fmt {}`)",
+                theme().paint(Role::Warning, "not formatted:"),
+                path,
+                path
+            );
+            continue;
+        }
+        let name = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let problems = lint::check(&song, &loaded.source, &name, dictionary);
+        if !problems.is_empty() {
+            failed += 1;
+            report(path, &loaded.source, &problems);
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} staged song(s) failed the pre-commit check", failed).into());
+    }
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!("✓ {} staged song(s) clean", files.len())
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::ids::assign;
+
+pub fn command() -> Command {
+    Command::new("ids")
+        .about("Give sections, and optionally lines, stable IDs for other systems")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to give IDs"),
+        )
+        .arg(
+            Arg::new("lines")
+                .long("lines")
+                .action(ArgAction::SetTrue)
+                .help("Give every line an ID as well"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    if assign(&mut song, matches.get_flag("lines")) == 0 {
+        return summary(matches, 0, 1);
+    }
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
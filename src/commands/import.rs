@@ -0,0 +1,128 @@
+use super::output::{backup_arg, write_file};
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::capture::import_plain;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::import::import_markdown;
+use lyrics_dsl::stt;
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("import")
+        .about("Convert a Markdown or plain-text lyric sheet into a song")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Document to import"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["md", "txt"])
+                .help("Input format [default: from the extension, else md]"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("audio")
+                .about("Transcribe a recording into a timed draft (needs the `stt` feature)")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .value_name("RECORDING")
+                        .help("Sung or spoken recording, in any format ffmpeg reads"),
+                )
+                .arg(
+                    Arg::new("model")
+                        .long("model")
+                        .value_name("FILE")
+                        .help("whisper.cpp model, e.g. ggml-base.en.bin [default: $WHISPER_MODEL]"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write to FILE instead of standard output"),
+                )
+                .arg(backup_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    if let Some(("audio", matches)) = matches.subcommand() {
+        return run_audio(matches);
+    }
+    let file = matches.get_one::<String>("file").expect("required");
+    let text = std::fs::read_to_string(file)
+        .map_err(|e| tr("cannot-read", &[("path", &file), ("error", &e)]))?;
+    let format = match matches.get_one::<String>("format") {
+        Some(format) => format.as_str(),
+        None if file.ends_with(".txt") => "txt",
+        None => "md",
+    };
+    let source = match format {
+        "txt" => import_plain(&text),
+        _ => import_markdown(&text).map_err(|e| format!("{}: {}", file, e))?,
+    };
+    write(matches, &source)
+}
+
+fn run_audio(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let model = match matches.get_one::<String>("model") {
+        Some(model) => model.clone(),
+        None => std::env::var("WHISPER_MODEL")
+            .map_err(|_| "give a whisper.cpp model with --model or WHISPER_MODEL")?,
+    };
+    let segments = transcribe(Path::new(file), Path::new(&model))?;
+    let title = Path::new(file)
+        .file_stem()
+        .map_or("Transcript".into(), |stem| stem.to_string_lossy());
+    let source = stt::draft(&title, &segments).map_err(|e| format!("{}: {}", file, e))?;
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Warning,
+            "Draft transcribed by speech-to-text; every line is marked low confidence"
+        )
+    );
+    write(matches, &source)
+}
+
+#[cfg(feature = "stt")]
+fn transcribe(audio: &Path, model: &Path) -> Result<Vec<stt::Segment>, String> {
+    stt::transcribe(audio, model).map_err(|e| format!("{}: {}", audio.display(), e))
+}
+
+#[cfg(not(feature = "stt"))]
+fn transcribe(_: &Path, _: &Path) -> Result<Vec<stt::Segment>, String> {
+    Err("speech-to-text is not built in: rebuild with `--features stt`".to_string())
+}
+
+fn write(matches: &ArgMatches, source: &str) -> CommandResult {
+    match matches.get_one::<String>("output") {
+        Some(path) => {
+            write_file(matches, path, source.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+            );
+        }
+        None => std::io::stdout().write_all(source.as_bytes())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! `index build` and `index search`: full-text search across a catalog too
+//! large to grep, through an index kept under `.lyricsdsl/index/`. See
+//! [`lyrics_dsl::search`] for the files and the ranking.
+
+use super::{project_root, relative_name, songs_in, theme, CommandResult, PROJECT_DIR};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::ast::word_ranges;
+use lyrics_dsl::crypt::is_locked;
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::search::{self, Builder, Hit, Index, Query};
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::path::PathBuf;
+
+fn index_arg() -> Arg {
+    Arg::new("index")
+        .long("index")
+        .value_name("DIR")
+        .help("Where the index is kept [default: .lyricsdsl/index in the project]")
+}
+
+pub fn command() -> Command {
+    Command::new("index")
+        .about("Build a full-text index of a catalog and search it")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("build")
+                .about("Index every song under the given directories")
+                .arg(
+                    Arg::new("paths")
+                        .num_args(0..)
+                        .value_name("PATH")
+                        .help("Songs or directories to index; defaults to the project root"),
+                )
+                .arg(index_arg()),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Find the lines that best match a query")
+                .arg(
+                    Arg::new("query")
+                        .required(true)
+                        .value_name("QUERY")
+                        .help("Words a line must hold; quote a phrase to keep its words together"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .short('n')
+                        .value_name("N")
+                        .default_value("20")
+                        .value_parser(value_parser!(usize))
+                        .help("How many matches to show"),
+                )
+                .arg(index_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand() {
+        Some(("build", sub)) => build(sub),
+        Some(("search", sub)) => find(sub),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn index_dir(matches: &ArgMatches) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(match matches.get_one::<String>("index") {
+        Some(dir) => PathBuf::from(dir),
+        None => project_root()?.join(PROJECT_DIR).join("index"),
+    })
+}
+
+fn build(matches: &ArgMatches) -> CommandResult {
+    let root = project_root()?;
+    let paths: Vec<PathBuf> = match matches.get_many::<String>("paths") {
+        Some(paths) => paths.map(PathBuf::from).collect(),
+        None => vec![root.clone()],
+    };
+    let mut files = Vec::new();
+    for path in &paths {
+        if path.is_dir() {
+            songs_in(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut builder = Builder::default();
+    for file in &files {
+        let name = relative_name(&root, file);
+        let skip = |why: &dyn std::fmt::Display| {
+            eprintln!(
+                "{}",
+                theme().paint(Role::Warning, &format!("skipping {}: {}", name, why))
+            );
+        };
+        let bytes = std::fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        // Locked songs stay out, so their words are never written to disk.
+        if is_locked(&bytes) {
+            skip(&"locked");
+            continue;
+        }
+        let Ok(source) = String::from_utf8(bytes) else {
+            skip(&"not UTF-8");
+            continue;
+        };
+        let song = match parse_song(&source) {
+            Ok(song) => resolve(&song).song,
+            Err(e) => {
+                skip(&error_diagnostic(&e, &source).message);
+                continue;
+            }
+        };
+        let title = song.title().unwrap_or_default();
+        builder.add(&name, &title, search::lines(&song, &source));
+    }
+
+    let dir = index_dir(matches)?;
+    builder.write(&dir).map_err(|e| e.to_string())?;
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!(
+                "🔎 Indexed {} song(s), {} line(s) in {}",
+                builder.songs(),
+                builder.lines(),
+                dir.display()
+            )
+        )
+    );
+    Ok(())
+}
+
+fn find(matches: &ArgMatches) -> CommandResult {
+    let query = Query::parse(matches.get_one::<String>("query").expect("required"));
+    if query.is_empty() {
+        return Err("the query has no words to search for".into());
+    }
+    let limit = *matches.get_one::<usize>("limit").expect("has a default");
+    let index = Index::open(&index_dir(matches)?).map_err(|e| e.to_string())?;
+    let hits = index.search(&query, limit).map_err(|e| e.to_string())?;
+    if hits.is_empty() {
+        eprintln!("{}", theme().paint(Role::Hint, "no matches"));
+    }
+    for hit in &hits {
+        print_hit(hit, &query);
+    }
+    Ok(())
+}
+
+fn print_hit(hit: &Hit, query: &Query) {
+    let mut context = hit.section.clone();
+    if !hit.title.is_empty() {
+        context = format!("{} · {}", hit.title, context);
+    }
+    println!(
+        "{}  {}",
+        format!("{}:{}", hit.path, hit.line).bold(),
+        theme().paint(Role::Heading, &context)
+    );
+    // The words asked for, bold in the line.
+    let wanted: Vec<&String> = query
+        .words
+        .iter()
+        .chain(query.phrases.iter().flatten())
+        .collect();
+    let mut line = String::new();
+    let mut at = 0;
+    for range in word_ranges(&hit.text) {
+        let word = &hit.text[range.clone()];
+        line.push_str(&hit.text[at..range.start]);
+        match search::terms(word)
+            .first()
+            .is_some_and(|t| wanted.contains(&t))
+        {
+            true => line.push_str(&word.bold().to_string()),
+            false => line.push_str(word),
+        }
+        at = range.end;
+    }
+    line.push_str(&hit.text[at..]);
+    println!("    {}", line);
+}
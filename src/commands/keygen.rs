@@ -0,0 +1,55 @@
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::delivery::SigningKey;
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+
+pub fn command() -> Command {
+    Command::new("keygen")
+        .about("Create a key pair for signing exports with --sign")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Private key to create; the public key goes to FILE.pub"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let public_path = format!("{}.pub", path);
+    for existing in [path, &public_path] {
+        if std::path::Path::new(existing).exists() {
+            return Err(format!("'{}' already exists; not replacing a key", existing).into());
+        }
+    }
+
+    let key = SigningKey::generate()?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let error = |e: std::io::Error| format!("cannot write '{}': {}", path, e);
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(key.to_text().as_bytes()))
+        .map_err(error)?;
+    std::fs::write(&public_path, key.public().to_text())
+        .map_err(|e| format!("cannot write '{}': {}", public_path, e))?;
+
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!("🔑 Signing key written to: {} (keep it private)", path)
+        )
+    );
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!("💾 Public key written to: {}", public_path)
+        )
+    );
+    Ok(())
+}
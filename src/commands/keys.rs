@@ -0,0 +1,103 @@
+//! Keys for locked songs, shared by every command that reads or rewrites
+//! one.
+//!
+//! A key file comes from `--keyfile` where a command offers it, else from
+//! `LYRICS_DSL_KEYFILE`. A passphrase comes from `LYRICS_DSL_PASSPHRASE`,
+//! else it is asked for on the terminal. The key is remembered for the rest
+//! of the run, so a command that reads and then rewrites a locked song, or
+//! reads several locked with the same passphrase, asks only once.
+
+use super::terminal::read_secret;
+use lyrics_dsl::crypt::{self, Key, KeyKind};
+use lyrics_dsl::i18n::tr;
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const KEYFILE_VAR: &str = "LYRICS_DSL_KEYFILE";
+pub const PASSPHRASE_VAR: &str = "LYRICS_DSL_PASSPHRASE";
+
+static REMEMBERED: Mutex<Option<Key>> = Mutex::new(None);
+
+/// The key file named by `--keyfile` or the environment, if any.
+pub fn keyfile_path(explicit: Option<&String>) -> Option<String> {
+    explicit
+        .cloned()
+        .or_else(|| std::env::var(KEYFILE_VAR).ok().filter(|v| !v.is_empty()))
+}
+
+/// A key of `kind`. With `confirm`, a typed passphrase is asked twice, as
+/// when locking a file.
+pub fn key(kind: KeyKind, keyfile: Option<&String>, confirm: bool) -> Result<Key, Box<dyn Error>> {
+    let mut remembered = REMEMBERED.lock().expect("key lock");
+    if let Some(key) = remembered.as_ref().filter(|k| k.kind() == kind) {
+        return Ok(key.clone());
+    }
+    let key = match kind {
+        KeyKind::Keyfile => {
+            let path = keyfile_path(keyfile).ok_or_else(|| {
+                format!(
+                    "a key file is needed: pass --keyfile or set {}",
+                    KEYFILE_VAR
+                )
+            })?;
+            let contents = std::fs::read(&path)
+                .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+            Key::Keyfile(contents)
+        }
+        KeyKind::Passphrase => Key::Passphrase(passphrase(confirm)?),
+    };
+    *remembered = Some(key.clone());
+    Ok(key)
+}
+
+fn passphrase(confirm: bool) -> Result<String, Box<dyn Error>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "a passphrase is needed: set {} or run in a terminal",
+            PASSPHRASE_VAR
+        )
+        .into());
+    }
+    let passphrase = read_secret("Passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err("the passphrase is empty".into());
+    }
+    if confirm && read_secret("Repeat passphrase: ")? != passphrase {
+        return Err("the passphrases do not match".into());
+    }
+    Ok(passphrase)
+}
+
+/// The text of a song file read as `bytes`, decrypted when it is locked.
+pub fn decode(path: &str, bytes: Vec<u8>) -> Result<String, Box<dyn Error>> {
+    let bytes = match crypt::locked_with(&bytes) {
+        Some(kind) => crypt::unlock(&bytes, &key(kind, None, false)?)
+            .map_err(|e| format!("cannot unlock '{}': {}", path, e))?,
+        None => bytes,
+    };
+    String::from_utf8(bytes)
+        .map_err(|_| format!("cannot read '{}': stream did not contain valid UTF-8", path).into())
+}
+
+/// The key the song at `path` is locked with, or `None` when it is not
+/// locked.
+pub fn locked_key(path: &Path) -> Result<Option<Key>, Box<dyn Error>> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| tr("cannot-read", &[("path", &path.display()), ("error", &e)]))?;
+    match crypt::locked_with(&bytes) {
+        Some(kind) => Ok(Some(key(kind, None, false)?)),
+        None => Ok(None),
+    }
+}
+
+/// `contents` locked the way the file it replaces, `old`, was locked.
+pub fn relock(path: &str, old: &[u8], contents: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let kind = crypt::locked_with(old).ok_or_else(|| format!("'{}' is damaged", path))?;
+    let key = key(kind, None, false)?;
+    Ok(crypt::lock(contents, &key).map_err(|e| format!("cannot lock '{}': {}", path, e))?)
+}
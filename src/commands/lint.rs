@@ -0,0 +1,130 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, Outcome};
+use super::{parse, project_dictionary, read_song, report, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::diagnostic::Applicability;
+use lyrics_dsl::lint::{self, RULES};
+use lyrics_dsl::theme::Role;
+use std::path::Path;
+
+/// Fixes can uncover or unblock others, e.g. a renumbered header that is
+/// then rewritten; passes stop once nothing changes, or after this many.
+const MAX_PASSES: usize = 10;
+
+pub fn command() -> Command {
+    Command::new("lint")
+        .about("Check songs for style problems and fix the safe ones")
+        .arg(
+            Arg::new("files")
+                .required_unless_present("rules")
+                .num_args(1..)
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Songs to check"),
+        )
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .action(ArgAction::SetTrue)
+                .help("Apply every machine-applicable fix in place"),
+        )
+        .arg(
+            Arg::new("rules")
+                .long("rules")
+                .action(ArgAction::SetTrue)
+                .help("List the rules and whether they can fix what they find"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+fn list_rules() {
+    for rule in RULES {
+        let fixer = match rule.fixer {
+            Some(Applicability::MachineApplicable) => "fix",
+            Some(Applicability::MaybeIncorrect) => "suggests fix",
+            None => "",
+        };
+        println!(
+            "{} {:<18} {:<13} {}",
+            rule.code.id, rule.name, fixer, rule.summary
+        );
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    if matches.get_flag("rules") {
+        list_rules();
+        return Ok(());
+    }
+    let fix = matches.get_flag("fix");
+    let dictionary = project_dictionary()?;
+    let (mut found, mut fixable, mut failed) = (0, 0, 0);
+    for path in matches.get_many::<String>("files").expect("required") {
+        let name = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let source = read_song(path)?;
+        let mut text = source.clone();
+        let mut song = match parse(path, &text) {
+            Ok(song) => song,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}", theme().paint(Role::Error, &e.to_string()));
+                continue;
+            }
+        };
+        let mut diagnostics = lint::check(&song, &text, &name, dictionary);
+
+        if fix {
+            let mut made = Vec::new();
+            for _ in 0..MAX_PASSES {
+                let (fixed, descriptions) = lint::apply_safe_fixes(&text, &diagnostics);
+                if descriptions.is_empty() {
+                    break;
+                }
+                // A fix that broke the song would be a bug in its rule; keep
+                // the text from before it rather than write a broken song.
+                let Ok(reparsed) = parse(path, &fixed) else {
+                    break;
+                };
+                made.extend(descriptions);
+                text = fixed;
+                song = reparsed;
+                diagnostics = lint::check(&song, &text, &name, dictionary);
+            }
+            if apply(matches, path, &source, &text)? == Outcome::Changed {
+                println!("{}: {} fix(es)", path.bold(), made.len());
+                for description in &made {
+                    println!("  {} {}", theme().paint(Role::Success, "✓"), description);
+                }
+            }
+        }
+
+        report(path, &text, &diagnostics);
+        found += diagnostics.len();
+        fixable += diagnostics
+            .iter()
+            .filter(|d| d.fix.as_ref().is_some_and(|f| f.is_machine_applicable()))
+            .count();
+    }
+
+    if fixable > 0 && !fix {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Hint,
+                &format!("{} can be fixed with `lint --fix`", fixable)
+            )
+        );
+    }
+    if failed > 0 {
+        return Err(format!("{} file(s) do not parse", failed).into());
+    }
+    if found > 0 {
+        return Err(format!("{} problem(s) found", found).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use super::keys::{key, keyfile_path};
+use super::output::replace_file;
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::crypt::{self, KeyKind};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("lock")
+        .about("Encrypt songs at rest with a passphrase or key file")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .help("Songs to lock in place"),
+        )
+        .arg(
+            Arg::new("keyfile")
+                .long("keyfile")
+                .value_name("KEYFILE")
+                .help("Lock with the contents of KEYFILE instead of a passphrase"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let keyfile = matches.get_one::<String>("keyfile");
+    let kind = match keyfile_path(keyfile) {
+        Some(_) => KeyKind::Keyfile,
+        None => KeyKind::Passphrase,
+    };
+    for path in matches.get_many::<String>("files").expect("required") {
+        let bytes = std::fs::read(path)
+            .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+        if crypt::is_locked(&bytes) {
+            eprintln!(
+                "{}",
+                theme().paint(Role::Warning, &format!("{} is already locked", path))
+            );
+            continue;
+        }
+        let key = key(kind, keyfile, true)?;
+        let locked = crypt::lock(&bytes, &key).map_err(|e| format!("{}: {}", path, e))?;
+        replace_file(path, &locked, false)?;
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &format!("🔒 Locked {}", path))
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use super::{load_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::songmap::{Measure, SongMap};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("map")
+        .about("Draw the song's sections in order as colored blocks")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to map"),
+        )
+        .arg(
+            Arg::new("by")
+                .long("by")
+                .value_name("MEASURE")
+                .value_parser(Measure::NAMES)
+                .help("Size blocks by duration or line count [default: duration when timed]"),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .value_name("COLUMNS")
+                .value_parser(value_parser!(u16).range(10..))
+                .default_value("60")
+                .help("Width of the map in the terminal"),
+        )
+        .arg(
+            Arg::new("svg")
+                .long("svg")
+                .value_name("FILE")
+                .help("Also write the map as an SVG image"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let measure = matches
+        .get_one::<String>("by")
+        .map(|by| Measure::parse(by).expect("validated by clap"));
+    let columns = *matches.get_one::<u16>("width").expect("defaulted") as usize;
+
+    let song = load_song(file)?;
+    let map = SongMap::new(&song, measure).map_err(|e| e.to_string())?;
+
+    let (mut blocks, mut labels) = (String::new(), String::new());
+    for (block, width) in map.blocks.iter().zip(map.widths(columns)) {
+        let role = Role::of_section(block.kind);
+        blocks.push_str(&theme().paint(role, &"█".repeat(width)).to_string());
+        let label: String = block.short_label().chars().take(width).collect();
+        labels.push_str(&format!("{:<width$}", label, width = width));
+    }
+    println!("{}", blocks);
+    println!("{}", labels.trim_end());
+    println!("{}", theme().paint(Role::Hint, &map.total()));
+
+    if let Some(path) = matches.get_one::<String>("svg") {
+        std::fs::write(path, map.to_svg())?;
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use super::output::{backup_arg, write_file};
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::export::{exporter, exporters, without_alternatives};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::medley::Medley;
+use lyrics_dsl::theme::Role;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+pub fn command() -> Command {
+    let mut formats: Vec<&'static str> = vec!["lyr"];
+    formats.extend(exporters().iter().map(|e| e.name()));
+    Command::new("medley")
+        .about("Combine sections of several songs into one medley")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("MEDLEY")
+                .help("Composition file of `use \"song.lyr\"::CHORUS` lines"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(formats)
+                .default_value("lyr")
+                .help("Output format; `lyr` writes the medley as one song"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let format = matches.get_one::<String>("format").expect("defaulted");
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+    let medley = Medley::parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+
+    // Each song is loaded, and its problems reported, once.
+    let mut loaded = HashMap::new();
+    let mut songs = Vec::new();
+    for part in &medley.parts {
+        if !loaded.contains_key(&part.file) {
+            let file = base.join(&part.file);
+            loaded.insert(part.file.clone(), load_song(&file.to_string_lossy())?);
+        }
+        songs.push(loaded[&part.file].clone());
+    }
+    let song = medley
+        .compose(&songs)
+        .map_err(|e| format!("{}: {}", path, e))?;
+
+    let rendered = match format.as_str() {
+        "lyr" => format_song(&song).into_bytes(),
+        format => exporter(format)?.export(&without_alternatives(&song))?,
+    };
+    match matches.get_one::<String>("output") {
+        Some(out) => {
+            write_file(matches, out, &rendered)?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", out)]))
+            );
+        }
+        None => std::io::stdout().write_all(&rendered)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::output::{backup_arg, write_file};
+use super::{read_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::merge::merge;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("merge")
+        .about("Three-way merge of song files, usable as a git merge driver")
+        .arg(
+            Arg::new("base")
+                .required(true)
+                .value_name("BASE")
+                .help("Common ancestor (%O)"),
+        )
+        .arg(
+            Arg::new("ours")
+                .required(true)
+                .value_name("OURS")
+                .help("Our version (%A); receives the result unless -o or --stdout is given"),
+        )
+        .arg(
+            Arg::new("theirs")
+                .required(true)
+                .value_name("THEIRS")
+                .help("Their version (%B)"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write the result to FILE"),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("output")
+                .help("Print the result instead of writing it"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let read = |name: &str| read_song(matches.get_one::<String>(name).expect("required"));
+    let merged = merge(&read("base")?, &read("ours")?, &read("theirs")?);
+
+    if matches.get_flag("stdout") {
+        print!("{}", merged.text);
+    } else {
+        let path = matches
+            .get_one::<String>("output")
+            .or_else(|| matches.get_one::<String>("ours"))
+            .expect("required");
+        write_file(matches, path, merged.text.as_bytes())?;
+    }
+
+    if merged.conflicts > 0 {
+        return Err(format!("{} conflict(s) left to resolve", merged.conflicts).into());
+    }
+    eprintln!("{}", theme().paint(Role::Success, "✓ merged cleanly"));
+    Ok(())
+}
@@ -0,0 +1,583 @@
+//! Subcommands of the `lyrics-dsl` binary.
+//!
+//! Each module exposes `command()` describing its arguments and `run()`
+//! executing it; [`all`] and [`run`] wire them into the top-level CLI.
+
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::analysis::{alignment, meter, scansion};
+use lyrics_dsl::ast::Song;
+use lyrics_dsl::capitalize::{Capitalizer, CaseStyle};
+use lyrics_dsl::collate::Collator;
+use lyrics_dsl::config::{self, Config};
+use lyrics_dsl::diagnostic::{has_errors, Diagnostic, Severity};
+use lyrics_dsl::extension::{self, Extension, ExtensionError};
+use lyrics_dsl::feedback::Comments;
+use lyrics_dsl::hooks::{self, Stage};
+use lyrics_dsl::i18n::{self, tr, Catalog};
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::profile::{self, Profile};
+use lyrics_dsl::semantic::{resolve, Resolved};
+use lyrics_dsl::spelling::{self, Dictionary};
+use lyrics_dsl::theme::Theme;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+mod alts;
+mod analyze;
+mod capo;
+mod card;
+mod catalog;
+mod choose;
+mod chords;
+mod clip;
+mod collab;
+mod compare;
+mod constraints;
+mod cowrite;
+mod draft;
+mod edit;
+mod explain;
+mod export;
+mod extract;
+mod fix;
+mod flow;
+mod fmt;
+pub(crate) mod freestyle;
+mod grammar;
+mod history;
+mod hook;
+mod ids;
+mod import;
+mod index;
+mod keygen;
+mod keys;
+mod lint;
+mod lock;
+mod map;
+mod medley;
+mod merge;
+mod output;
+mod overlay;
+mod prompt;
+mod query;
+mod range;
+mod redact;
+mod rename;
+mod renumber;
+mod retime;
+mod review;
+mod romanize;
+mod run;
+mod scan;
+mod schema;
+mod score;
+mod self_test;
+mod setlist;
+mod show;
+mod signing;
+mod similar;
+mod speak;
+mod split_sheet;
+mod stats;
+mod sync;
+mod sync_stub;
+mod terminal;
+mod todos;
+mod tui;
+mod unlock;
+mod validate;
+mod verify;
+mod xliff;
+
+pub type CommandResult = Result<(), Box<dyn Error>>;
+
+/// Every subcommand definition, in the order shown by `--help`.
+pub fn all() -> Vec<Command> {
+    vec![
+        alts::command(),
+        analyze::command(),
+        capo::command(),
+        card::command(),
+        catalog::command(),
+        choose::command(),
+        chords::command(),
+        clip::command(),
+        collab::command(),
+        compare::command(),
+        constraints::command(),
+        cowrite::command(),
+        draft::command(),
+        edit::command(),
+        explain::command(),
+        export::command(),
+        extract::command(),
+        fix::command(),
+        flow::command(),
+        fmt::command(),
+        grammar::command(),
+        history::command(),
+        hook::command(),
+        ids::command(),
+        import::command(),
+        index::command(),
+        keygen::command(),
+        lint::command(),
+        lock::command(),
+        map::command(),
+        medley::command(),
+        merge::command(),
+        overlay::command(),
+        prompt::command(),
+        query::command(),
+        range::command(),
+        redact::command(),
+        rename::command(),
+        renumber::command(),
+        retime::command(),
+        review::command(),
+        romanize::command(),
+        run::command(),
+        scan::command(),
+        schema::command(),
+        score::command(),
+        self_test::command(),
+        setlist::command(),
+        show::command(),
+        similar::command(),
+        speak::command(),
+        split_sheet::command(),
+        stats::command(),
+        sync::command(),
+        sync_stub::command(),
+        todos::command(),
+        tui::command(),
+        unlock::command(),
+        validate::command(),
+        verify::command(),
+        xliff::command(),
+    ]
+}
+
+/// Dispatch a parsed subcommand by name.
+pub fn run(name: &str, matches: &ArgMatches) -> CommandResult {
+    if let Some(name) = matches.try_get_one::<String>("profile").ok().flatten() {
+        let _ = CLI_PROFILE.set(Profile::parse(name).expect("clap checks the profile"));
+    }
+    let lang = matches.try_get_one::<String>("lang").ok().flatten();
+    set_language(lang.map(String::as_str))?;
+    match name {
+        "alts" => alts::run(matches),
+        "analyze" => analyze::run(matches),
+        "capo" => capo::run(matches),
+        "card" => card::run(matches),
+        "catalog" => catalog::run(matches),
+        "choose" => choose::run(matches),
+        "chords" => chords::run(matches),
+        "clip" => clip::run(matches),
+        "collab" => collab::run(matches),
+        "compare" => compare::run(matches),
+        "constraints" => constraints::run(matches),
+        "cowrite" => cowrite::run(matches),
+        "draft" => draft::run(matches),
+        "edit" => edit::run(matches),
+        "explain" => explain::run(matches),
+        "export" => export::run(matches),
+        "extract" => extract::run(matches),
+        "fix" => fix::run(matches),
+        "flow" => flow::run(matches),
+        "fmt" => fmt::run(matches),
+        "grammar" => grammar::run(matches),
+        "history" => history::run(matches),
+        "hook" => hook::run(matches),
+        "ids" => ids::run(matches),
+        "import" => import::run(matches),
+        "index" => index::run(matches),
+        "keygen" => keygen::run(matches),
+        "lint" => lint::run(matches),
+        "lock" => lock::run(matches),
+        "map" => map::run(matches),
+        "medley" => medley::run(matches),
+        "merge" => merge::run(matches),
+        "overlay" => overlay::run(matches),
+        "prompt" => prompt::run(matches),
+        "query" => query::run(matches),
+        "range" => range::run(matches),
+        "redact" => redact::run(matches),
+        "rename" => rename::run(matches),
+        "renumber" => renumber::run(matches),
+        "retime" => retime::run(matches),
+        "review" => review::run(matches),
+        "romanize" => romanize::run(matches),
+        "run" => run::run(matches),
+        "scan" => scan::run(matches),
+        "schema" => schema::run(matches),
+        "score" => score::run(matches),
+        "self-test" => self_test::run(matches),
+        "setlist" => setlist::run(matches),
+        "show" => show::run(matches),
+        "similar" => similar::run(matches),
+        "speak" => speak::run(matches),
+        "split-sheet" => split_sheet::run(matches),
+        "stats" => stats::run(matches),
+        "sync" => sync::run(matches),
+        "sync-stub" => sync_stub::run(matches),
+        "todos" => todos::run(matches),
+        "tui" => tui::run(matches),
+        "unlock" => unlock::run(matches),
+        "validate" => validate::run(matches),
+        "verify" => verify::run(matches),
+        "xliff" => xliff::run(matches),
+        _ => Err(format!("unknown command '{}'", name).into()),
+    }
+}
+
+/// Translate messages into the language given with `--lang` as `flag`,
+/// else the language of the environment. A project's `.lyricsdsl/locales/<lang>.ftl` adds to
+/// or overrides the built-in translation. Only an unknown `--lang` is an
+/// error; an unknown language from the environment keeps English.
+fn set_language(flag: Option<&str>) -> CommandResult {
+    let Some(lang) = flag
+        .map(str::to_string)
+        .or_else(|| i18n::language_from_env(|name| std::env::var(name).ok()))
+    else {
+        return Ok(());
+    };
+    let path = project_root()?
+        .join(PROJECT_DIR)
+        .join("locales")
+        .join(format!("{}.ftl", lang));
+    let project = match std::fs::read_to_string(&path) {
+        Ok(text) => Some(Catalog::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            let path = path.display();
+            return Err(tr("cannot-read", &[("path", &path), ("error", &e)]).into());
+        }
+    };
+    let catalog = match (Catalog::built_in(&lang), project) {
+        (Some(mut catalog), Some(project)) => {
+            catalog.extend(project);
+            catalog
+        }
+        (Some(catalog), None) | (None, Some(catalog)) => catalog,
+        (None, None) if flag.is_some() => {
+            let built_in: Vec<&str> = i18n::BUILT_IN.iter().map(|(code, _)| *code).collect();
+            return Err(format!(
+                "no messages in `{}`: built in are {}, others go in {}",
+                lang,
+                built_in.join(", "),
+                path.display()
+            )
+            .into());
+        }
+        (None, None) => return Ok(()),
+    };
+    i18n::set(catalog);
+    Ok(())
+}
+
+/// A song file read from disk together with its resolution results.
+pub struct Loaded {
+    pub source: String,
+    pub resolved: Resolved,
+}
+
+/// Read, parse and resolve a song file, then check it against its meter.
+/// Parse errors are returned with the file name; diagnostics are left for
+/// the caller to report. The project's parse hooks run around it.
+pub fn load(path: &str) -> Result<Loaded, Box<dyn Error>> {
+    run_hooks(Stage::PreParse, path, json!({}))?;
+    load_source(path, read_song(path)?)
+}
+
+/// Read a song file, decrypting it first when it is locked.
+pub fn read_song(path: &str) -> Result<String, Box<dyn Error>> {
+    let bytes =
+        std::fs::read(path).map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+    keys::decode(path, bytes)
+}
+
+/// Review comments keyed by line ID, from the JSON file a review tool wrote.
+pub fn read_comments(path: &str) -> Result<Comments, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+    Comments::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+}
+
+/// [`load`] for text that did not come from the file system, such as the
+/// staged version of a file; `path` is only used in messages.
+pub fn load_source(path: &str, source: String) -> Result<Loaded, Box<dyn Error>> {
+    let song = parse(path, &source)?;
+    let resolved = check(&song)?;
+    let count = |severity| {
+        let diagnostics = resolved.diagnostics.iter();
+        diagnostics.filter(|d| d.severity == severity).count()
+    };
+    let counts = json!({
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+    });
+    run_hooks(Stage::PostParse, path, counts)?;
+    Ok(Loaded { source, resolved })
+}
+
+/// Resolve the parsed `song` and check it against its meter, alignment,
+/// the project's grammar extension and profile, with the diagnostics in
+/// source order.
+pub fn check(song: &Song) -> Result<Resolved, Box<dyn Error>> {
+    let mut resolved = resolve(song);
+    resolved.diagnostics.extend(meter::check(&resolved.song));
+    resolved.diagnostics.extend(scansion::check(&resolved.song));
+    resolved.diagnostics.extend(alignment::check(song));
+    resolved
+        .diagnostics
+        .extend(extension::check(song, project_extension()?));
+    resolved
+        .diagnostics
+        .extend(profile::check(song, profile()?));
+    resolved.diagnostics.sort_by_key(|d| d.span.start);
+    resolved.diagnostics.dedup();
+    Ok(resolved)
+}
+
+/// Run the project's hooks for `stage` on the song at `path`.
+pub fn run_hooks(stage: Stage, path: &str, details: Value) -> Result<(), Box<dyn Error>> {
+    hooks::run(&project_config()?.hooks, stage, path, details)?;
+    Ok(())
+}
+
+/// Parse `source`, turning a syntax error into a rendered report naming
+/// `path`.
+pub fn parse(path: &str, source: &str) -> Result<Song, Box<dyn Error>> {
+    parse_song(source).map_err(|e| error_diagnostic(&e, source).render(path, source).into())
+}
+
+/// Print diagnostics to stderr, colored by severity.
+pub fn report(path: &str, source: &str, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        let text = diagnostic.render(path, source);
+        eprintln!("{}", theme().paint(diagnostic.severity, &text));
+    }
+}
+
+/// Load a song for rendering: diagnostics are reported and errors abort, so
+/// callers get the resolved song with all references expanded.
+pub fn load_song(path: &str) -> Result<Song, Box<dyn Error>> {
+    Ok(load_valid(path)?.resolved.song)
+}
+
+/// [`load_song`] for callers that also need the source text, e.g. to turn
+/// spans into line numbers.
+pub fn load_valid(path: &str) -> Result<Loaded, Box<dyn Error>> {
+    let loaded = load(path)?;
+    let diagnostics = &loaded.resolved.diagnostics;
+    report(path, &loaded.source, diagnostics);
+    if has_errors(diagnostics) {
+        return Err(tr("has-errors", &[("path", &path)]).into());
+    }
+    Ok(loaded)
+}
+
+/// The project's `extra.pest` grammar extension, if it has one. It is read
+/// once per run, at the project root.
+pub fn project_extension() -> Result<Option<&'static Extension>, Box<dyn Error>> {
+    static EXTENSION: OnceLock<Result<Option<Extension>, String>> = OnceLock::new();
+    let loaded = EXTENSION.get_or_init(|| {
+        let root = project_root().map_err(|e| e.to_string())?;
+        let path = root.join(extension::FILE_NAME);
+        let shown = path.display();
+        match std::fs::read_to_string(&path) {
+            Ok(source) => Extension::parse(&source).map(Some).map_err(|e| match e {
+                ExtensionError::Syntax(_) => format!("{}\n{}", shown, e),
+                _ => format!("{}: {}", shown, e),
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(tr("cannot-read", &[("path", &shown), ("error", &e)])),
+        }
+    });
+    match loaded {
+        Ok(extension) => Ok(extension.as_ref()),
+        Err(e) => Err(e.clone().into()),
+    }
+}
+
+/// The profile given with `--profile`, set once by [`run`].
+static CLI_PROFILE: OnceLock<Profile> = OnceLock::new();
+
+/// The parser profile songs are checked against: `--profile`, else the
+/// project's `config.toml`, else the standard profile.
+pub fn profile() -> Result<Profile, Box<dyn Error>> {
+    if let Some(profile) = CLI_PROFILE.get() {
+        return Ok(*profile);
+    }
+    Ok(project_config()?.profile.unwrap_or_default())
+}
+
+/// The `--locale` option of commands that sort words or titles.
+pub fn locale_arg() -> Arg {
+    Arg::new("locale")
+        .long("locale")
+        .value_name("LOCALE")
+        .help("Sort words for this locale, e.g. es or sv [default: from config.toml]")
+}
+
+/// The collator for `--locale`, else the project's configured locale, else
+/// the default order.
+pub fn collator(matches: &ArgMatches) -> Result<Collator, Box<dyn Error>> {
+    let locale = match matches.get_one::<String>("locale") {
+        Some(locale) => Some(locale.as_str()),
+        None => project_config()?.locale.as_deref(),
+    };
+    Ok(locale.map(Collator::new).unwrap_or_default())
+}
+
+pub fn case_arg() -> Arg {
+    Arg::new("case")
+        .long("case")
+        .value_name("STYLE")
+        .value_parser(CaseStyle::ALL.map(|s| s.name()))
+        .help("Recapitalize lyric lines")
+}
+
+pub fn protect_arg() -> Arg {
+    Arg::new("protect")
+        .long("protect")
+        .value_name("WORDS")
+        .value_delimiter(',')
+        .requires("case")
+        .help("Words --case keeps as spelled, e.g. Maria,NASA [default: from config.toml]")
+}
+
+/// The capitalizer for `--case`, if given, keeping the `--protect` words,
+/// else the project's configured ones.
+pub fn capitalizer(matches: &ArgMatches) -> Result<Option<Capitalizer>, Box<dyn Error>> {
+    let Some(style) = matches.get_one::<String>("case") else {
+        return Ok(None);
+    };
+    let style = CaseStyle::parse(style).expect("validated by clap");
+    let protected: Vec<String> = match matches.get_many::<String>("protect") {
+        Some(words) => words.cloned().collect(),
+        None => project_config()?.protect.clone(),
+    };
+    Ok(Some(Capitalizer::new(style, &protected)))
+}
+
+/// The dictionary named by `dictionary` in the project's `config.toml`,
+/// with the words of its `words.txt` added, or `None` when no dictionary
+/// is set and spelling goes unchecked. It is read once per run.
+pub fn project_dictionary() -> Result<Option<&'static Dictionary>, Box<dyn Error>> {
+    static DICTIONARY: OnceLock<Result<Option<Dictionary>, String>> = OnceLock::new();
+    let loaded = DICTIONARY.get_or_init(|| {
+        let config = project_config().map_err(|e| e.to_string())?;
+        let Some(name) = &config.dictionary else {
+            return Ok(None);
+        };
+        let dir = project_root().map_err(|e| e.to_string())?.join(PROJECT_DIR);
+        let path = spelling::locate(name, &spelling::search_path(&dir));
+        let mut dictionary = path
+            .and_then(|path| Dictionary::load(&path))
+            .map_err(|e| e.to_string())?;
+        let words = dir.join(spelling::WORDS_FILE);
+        match std::fs::read_to_string(&words) {
+            Ok(list) => dictionary.add_words(&list),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(tr(
+                    "cannot-read",
+                    &[("path", &words.display()), ("error", &e)],
+                ))
+            }
+        }
+        Ok(Some(dictionary))
+    });
+    match loaded {
+        Ok(dictionary) => Ok(dictionary.as_ref()),
+        Err(e) => Err(e.clone().into()),
+    }
+}
+
+/// The project's `.lyricsdsl/config.toml`, or the defaults when it has none.
+/// It is read once per run.
+pub fn project_config() -> Result<&'static Config, Box<dyn Error>> {
+    static CONFIG: OnceLock<Result<Config, String>> = OnceLock::new();
+    let loaded = CONFIG.get_or_init(|| {
+        let root = project_root().map_err(|e| e.to_string())?;
+        let path = root.join(PROJECT_DIR).join(config::FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(tr(
+                "cannot-read",
+                &[("path", &path.display()), ("error", &e)],
+            )),
+        }
+    });
+    match loaded {
+        Ok(config) => Ok(config),
+        Err(e) => Err(e.clone().into()),
+    }
+}
+
+/// The project's terminal colors, or the default theme outside a project
+/// or when its config does not load, which is reported where it matters.
+pub fn theme() -> &'static Theme {
+    static DEFAULT: OnceLock<Theme> = OnceLock::new();
+    match project_config() {
+        Ok(config) => &config.theme,
+        Err(_) => DEFAULT.get_or_init(Theme::default),
+    }
+}
+
+/// Directory of per-project state such as the capture inbox.
+pub const PROJECT_DIR: &str = ".lyricsdsl";
+
+/// Root of the project the current directory belongs to: the nearest
+/// ancestor holding a `.lyricsdsl` directory, else the nearest git work
+/// tree, else the current directory itself.
+pub fn project_root() -> Result<PathBuf, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    for marker in [PROJECT_DIR, ".git"] {
+        if let Some(root) = cwd.ancestors().find(|dir| dir.join(marker).exists()) {
+            return Ok(root.to_path_buf());
+        }
+    }
+    Ok(cwd)
+}
+
+/// `.lyr` files under `dir`, skipping hidden directories and build output.
+pub fn songs_in(dir: &Path, files: &mut Vec<PathBuf>) -> CommandResult {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| tr("cannot-read", &[("path", &dir.display()), ("error", &e)]))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                songs_in(&path, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "lyr") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `file` relative to the project `root` when inside it, so paths read
+/// the same from anywhere in the project.
+pub fn relative_name(root: &Path, file: &Path) -> String {
+    file.canonicalize()
+        .ok()
+        .and_then(|file| {
+            let root = root.canonicalize().ok()?;
+            file.strip_prefix(root).ok().map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| file.to_path_buf())
+        .display()
+        .to_string()
+}
@@ -0,0 +1,179 @@
+//! The shared "apply or preview" step of commands that rewrite song files.
+//!
+//! A mutating command computes the new text and hands it to [`apply`]. With
+//! `--dry-run` the change is shown as a colored unified diff and nothing is
+//! written; otherwise the file is replaced when its content changed. With
+//! `--log-change MESSAGE` the change is also recorded in the song's
+//! `history:` block.
+//!
+//! Every write goes through [`write_file`], which writes a temporary file
+//! next to the target and renames it into place, so a crash mid-write leaves
+//! the old file intact. With `--backup` the previous version is kept as
+//! `FILE.bak`.
+
+use super::keys::relock;
+use super::{theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches};
+use colored::*;
+use lyrics_dsl::changelog::{self, log_change};
+use lyrics_dsl::crypt;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use similar::{ChangeTag, TextDiff};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+/// The `--dry-run` flag every mutating command accepts.
+pub fn dry_run_arg() -> Arg {
+    Arg::new("dry-run")
+        .long("dry-run")
+        .action(ArgAction::SetTrue)
+        .help("Print a diff of the changes instead of writing files")
+}
+
+/// The `--log-change` option every mutating command accepts.
+pub fn log_change_arg() -> Arg {
+    Arg::new("log-change")
+        .long("log-change")
+        .value_name("MESSAGE")
+        .help("Record the change in the song's history block, e.g. \"tighter chorus\"")
+}
+
+/// The `--backup` flag of commands that may overwrite an existing file.
+pub fn backup_arg() -> Arg {
+    Arg::new("backup")
+        .long("backup")
+        .action(ArgAction::SetTrue)
+        .help("Keep the previous version of an overwritten file as FILE.bak")
+}
+
+/// Atomically replace `path` with `contents`, keeping a `.bak` copy of the
+/// old file first when `--backup` was given. A locked file stays locked:
+/// the new contents are encrypted with the same kind of key.
+pub fn write_file(matches: &ArgMatches, path: &str, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+    let relocked = match std::fs::read(path) {
+        Ok(old) if crypt::is_locked(&old) => Some(relock(path, &old, contents)?),
+        _ => None,
+    };
+    let contents = relocked.as_deref().unwrap_or(contents);
+    replace_file(path, contents, matches.get_flag("backup"))
+}
+
+/// [`write_file`] without re-locking, for `lock` and `unlock` themselves.
+pub fn replace_file(path: &str, contents: &[u8], backup: bool) -> Result<(), Box<dyn Error>> {
+    let target = Path::new(path);
+    let existing = std::fs::metadata(target).ok();
+    if backup && existing.is_some() {
+        let bak = format!("{}.bak", path);
+        std::fs::copy(target, &bak).map_err(|e| format!("cannot write '{}': {}", bak, e))?;
+    }
+
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let error = |e: std::io::Error| format!("cannot write '{}': {}", path, e);
+    let mut temp = tempfile::NamedTempFile::new_in(dir).map_err(error)?;
+    temp.write_all(contents).map_err(error)?;
+    temp.as_file().sync_all().map_err(error)?;
+    if let Some(metadata) = existing {
+        temp.as_file()
+            .set_permissions(metadata.permissions())
+            .map_err(error)?;
+    }
+    temp.persist(target).map_err(|e| error(e.error))?;
+    Ok(())
+}
+
+/// What happened to one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Unchanged,
+    Changed,
+}
+
+/// Write `after` to `path`, or preview it as a diff against `before` when
+/// the command was run with `--dry-run`. A `--log-change` message becomes
+/// a new entry of the song's history.
+pub fn apply(
+    matches: &ArgMatches,
+    path: &str,
+    before: &str,
+    after: &str,
+) -> Result<Outcome, Box<dyn Error>> {
+    if before == after {
+        return Ok(Outcome::Unchanged);
+    }
+    let logged;
+    let after = match matches.get_one::<String>("log-change") {
+        Some(message) => {
+            logged = log_change(after, &changelog::now(), message)
+                .map_err(|e| format!("cannot log the change to '{}': {}", path, e))?;
+            logged.as_str()
+        }
+        None => after,
+    };
+    if matches.get_flag("dry-run") {
+        print_diff(path, before, after);
+    } else {
+        write_file(matches, path, after.as_bytes())?;
+    }
+    Ok(Outcome::Changed)
+}
+
+/// Print a unified diff with `path` in the headers.
+pub fn print_diff(path: &str, before: &str, after: &str) {
+    let diff = TextDiff::from_lines(before, after);
+    println!(
+        "{}",
+        theme().paint(Role::Error, &format!("--- {}", path)).bold()
+    );
+    println!(
+        "{}",
+        theme()
+            .paint(Role::Success, &format!("+++ {}", path))
+            .bold()
+    );
+    for group in diff.grouped_ops(3) {
+        let (first, last) = (&group[0], &group[group.len() - 1]);
+        println!(
+            "{}",
+            theme().paint(
+                Role::Info,
+                &format!(
+                    "@@ -{},{} +{},{} @@",
+                    first.old_range().start + 1,
+                    last.old_range().end - first.old_range().start,
+                    first.new_range().start + 1,
+                    last.new_range().end - first.new_range().start
+                )
+            )
+        );
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let text = change.value().trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => {
+                        println!("{}", theme().paint(Role::Error, &format!("-{}", text)))
+                    }
+                    ChangeTag::Insert => {
+                        println!("{}", theme().paint(Role::Success, &format!("+{}", text)))
+                    }
+                    ChangeTag::Equal => println!(" {}", text),
+                }
+            }
+        }
+    }
+}
+
+/// Summarise a run over several files.
+pub fn summary(matches: &ArgMatches, changed: usize, total: usize) -> CommandResult {
+    let id = match matches.get_flag("dry-run") {
+        true => "files-would-change",
+        false => "files-changed",
+    };
+    let args: [(&str, &dyn std::fmt::Display); 2] = [("changed", &changed), ("total", &total)];
+    eprintln!("{}", theme().paint(Role::Hint, &tr(id, &args)));
+    Ok(())
+}
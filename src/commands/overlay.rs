@@ -0,0 +1,172 @@
+use super::{load_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use lyrics_dsl::overlay::{handshake_key, handshake_response, text_frame, Snapshot};
+use lyrics_dsl::playback::{cues, is_timed};
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long the last line stays up before the overlay clears and exits.
+const LINGER_SECS: f64 = 5.0;
+
+pub fn command() -> Command {
+    Command::new("overlay")
+        .about("Publish the line being sung for streaming overlays while a timed song plays")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Timed song to follow"),
+        )
+        .arg(
+            Arg::new("text")
+                .long("text")
+                .value_name("PATH")
+                .help("Keep PATH holding the current line, for a text source that reads a file"),
+        )
+        .arg(
+            Arg::new("next")
+                .long("next")
+                .value_name("PATH")
+                .help("Keep PATH holding the line after the current one"),
+        )
+        .arg(
+            Arg::new("websocket")
+                .long("websocket")
+                .value_name("ADDR")
+                .help("Push JSON updates to WebSocket clients on ADDR, e.g. 127.0.0.1:4455"),
+        )
+        .arg(
+            Arg::new("start")
+                .long("start")
+                .value_name("SECONDS")
+                .value_parser(value_parser!(f64))
+                .default_value("0")
+                .help("Start this many seconds into the song"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let text = matches.get_one::<String>("text");
+    let next = matches.get_one::<String>("next");
+    let address = matches.get_one::<String>("websocket");
+    let start = *matches.get_one::<f64>("start").expect("defaulted");
+    if text.is_none() && next.is_none() && address.is_none() {
+        return Err("overlay needs at least one of --text, --next or --websocket".into());
+    }
+
+    let song = load_song(path)?;
+    let cues = cues(&song);
+    if !is_timed(&cues) {
+        return Err(format!("{}: overlay needs `timing` on at least one line", path).into());
+    }
+    let end = cues
+        .iter()
+        .filter_map(|c| c.start)
+        .max()
+        .map_or(0.0, |t| t.as_secs_f64())
+        + LINGER_SECS;
+
+    let listener = match address {
+        Some(address) => {
+            let listener = TcpListener::bind(address)
+                .map_err(|e| format!("cannot listen on {}: {}", address, e))?;
+            listener.set_nonblocking(true)?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Info, &format!("🔌 WebSocket on ws://{}", address))
+            );
+            Some(listener)
+        }
+        None => None,
+    };
+    let mut clients: Vec<TcpStream> = Vec::new();
+
+    let began = Instant::now();
+    let mut shown: Option<Snapshot> = None;
+    loop {
+        let elapsed = start + began.elapsed().as_secs_f64();
+        let snapshot = match elapsed < end {
+            true => Snapshot::at(&cues, elapsed),
+            false => Snapshot::default(),
+        };
+        if let Some(listener) = &listener {
+            while let Ok((stream, _)) = listener.accept() {
+                match accept(stream) {
+                    Ok(mut client) => {
+                        if send(&mut client, &snapshot).is_ok() {
+                            clients.push(client);
+                        }
+                    }
+                    Err(e) => eprintln!(
+                        "{}",
+                        theme().paint(Role::Warning, &format!("rejected client: {}", e))
+                    ),
+                }
+            }
+        }
+        if shown.as_ref() != Some(&snapshot) {
+            if let Some(text) = text {
+                write_atomic(text, &snapshot.current)?;
+            }
+            if let Some(next) = next {
+                write_atomic(next, &snapshot.next)?;
+            }
+            clients.retain_mut(|client| send(client, &snapshot).is_ok());
+            if !snapshot.current.is_empty() {
+                eprintln!(
+                    "{} {}",
+                    theme().paint(Role::Hint, &format!("[{:>6.1}s]", elapsed)),
+                    snapshot.current
+                );
+            }
+            shown = Some(snapshot);
+        }
+        if elapsed >= end {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+/// Complete the opening handshake of a new WebSocket client.
+fn accept(mut stream: TcpStream) -> Result<TcpStream, Box<dyn Error>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        if n == 0 || request.len() > 16 * 1024 {
+            return Err("incomplete handshake".into());
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let key = handshake_key(&request).ok_or("not a WebSocket request")?;
+    stream.write_all(handshake_response(key).as_bytes())?;
+    Ok(stream)
+}
+
+fn send(client: &mut TcpStream, snapshot: &Snapshot) -> std::io::Result<()> {
+    client.write_all(&text_frame(&snapshot.to_json()))
+}
+
+/// Replace `path` by renaming a finished temporary file over it, so a text
+/// source polling the file never reads it half written.
+fn write_atomic(path: &str, text: &str) -> Result<(), Box<dyn Error>> {
+    let dir = match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let error = |e: std::io::Error| format!("cannot write '{}': {}", path, e);
+    let mut temp = tempfile::NamedTempFile::new_in(dir).map_err(error)?;
+    temp.write_all(text.as_bytes()).map_err(error)?;
+    temp.persist(path).map_err(|e| error(e.error))?;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use super::terminal::{Key, Screen};
+use super::{load_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::playback::{cues, is_timed};
+use lyrics_dsl::teleprompter::{RowStyle, Teleprompter};
+use lyrics_dsl::theme::Role;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const HELP: &str = "space pause · ↑↓ scroll · +/- speed · r restart · q quit";
+
+pub fn command() -> Command {
+    Command::new("prompt")
+        .about("Scroll a song full-screen in the terminal, teleprompter style")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to show"),
+        )
+        .arg(
+            Arg::new("speed")
+                .long("speed")
+                .value_name("FACTOR")
+                .value_parser(value_parser!(f64))
+                .default_value("1.0")
+                .help("Scroll speed, from 0.25 to 4"),
+        )
+        .arg(
+            Arg::new("timed")
+                .long("timed")
+                .action(ArgAction::SetTrue)
+                .help("Follow the song's timings instead of scrolling steadily"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let speed = *matches.get_one::<f64>("speed").expect("defaulted");
+    let song = load_song(path)?;
+    let timed = matches.get_flag("timed");
+    if timed && !is_timed(&cues(&song)) {
+        return Err(format!("{}: --timed needs `timing` on at least one line", path).into());
+    }
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return Err("prompt needs an interactive terminal".into());
+    }
+
+    let mut prompter = Teleprompter::new(&song, speed, timed);
+    let title = song.title().unwrap_or_else(|| path.clone());
+    let screen = Screen::enter()?;
+    let mut last = Instant::now();
+    loop {
+        let (width, height) = screen.size();
+        draw(&prompter, &title, width, height)?;
+
+        if let Some(key) = screen.read_key(Duration::from_millis(50))? {
+            match key {
+                Key::Char('q') | Key::Escape | Key::Interrupt => break,
+                Key::Char(' ') => prompter.toggle_pause(),
+                Key::Char('+') | Key::Char('=') => prompter.change_speed(1.25),
+                Key::Char('-') | Key::Char('_') => prompter.change_speed(0.8),
+                Key::Char('r') => prompter.restart(),
+                Key::Up => prompter.scroll(-1),
+                Key::Down => prompter.scroll(1),
+                Key::PageUp => prompter.scroll(-(height as i32 / 2)),
+                Key::PageDown => prompter.scroll(height as i32 / 2),
+                _ => {}
+            }
+        }
+        let now = Instant::now();
+        prompter.tick((now - last).as_secs_f64());
+        last = now;
+    }
+    Ok(())
+}
+
+fn draw(prompter: &Teleprompter, title: &str, width: usize, height: usize) -> CommandResult {
+    let mut out = String::from("\x1b[H");
+    for (text, style) in prompter.frame(width, height.saturating_sub(1)) {
+        let text = match style {
+            RowStyle::Heading => theme().paint(Role::Hint, &text).to_string(),
+            RowStyle::Current => text.bold().to_string(),
+            RowStyle::Normal => text,
+        };
+        out.push_str(&text);
+        out.push_str("\x1b[K\r\n");
+    }
+    let state = match prompter.paused {
+        true => "paused",
+        false if prompter.finished() => "end",
+        false => "playing",
+    };
+    let elapsed = prompter.elapsed() as u64;
+    let status = format!(
+        "{} · {} · {:.2}× · {}:{:02} · {}",
+        title,
+        state,
+        prompter.speed,
+        elapsed / 60,
+        elapsed % 60,
+        HELP
+    );
+    let status: String = status.chars().take(width).collect();
+    out.push_str(&status.reversed().to_string());
+    out.push_str("\x1b[K");
+    let mut stdout = std::io::stdout();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
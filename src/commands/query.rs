@@ -0,0 +1,53 @@
+use super::{load_song, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::query::Query;
+use serde_json::Value;
+
+pub fn command() -> Command {
+    Command::new("query")
+        .about("Pull data out of songs with a path such as `sections[kind=chorus].lines | count`")
+        .arg(
+            Arg::new("query")
+                .required(true)
+                .value_name("QUERY")
+                .help("Path into the song as exported to JSON, then `| function`s"),
+        )
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .help("Songs to query; with several, each result is prefixed by its file"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the results as a JSON array"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let query = Query::parse(matches.get_one::<String>("query").expect("required"))
+        .map_err(|e| e.to_string())?;
+    let files: Vec<&String> = matches.get_many("files").expect("required").collect();
+    for file in &files {
+        let song = serde_json::to_value(load_song(file)?)?;
+        let results = query.run(&song).map_err(|e| format!("{}: {}", file, e))?;
+        let prefix = match files.len() {
+            1 => String::new(),
+            _ => format!("{}: ", file),
+        };
+        if matches.get_flag("json") {
+            println!("{}{}", prefix, serde_json::to_string_pretty(&results)?);
+            continue;
+        }
+        for result in results {
+            match result {
+                Value::String(text) => println!("{}{}", prefix, text),
+                other => println!("{}{}", prefix, other),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use super::{load_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::chords::nashville::Key;
+use lyrics_dsl::theme::Role;
+use lyrics_dsl::voice::{Reach, SongRange, VocalRange};
+
+pub fn command() -> Command {
+    Command::new("range")
+        .about("Report a song's lowest and highest notes and check them against a singer")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song with `pitch` annotations or a `range`"),
+        )
+        .arg(
+            Arg::new("singer")
+                .long("singer")
+                .value_name("RANGE")
+                .help("The singer's range, e.g. A2-E4"),
+        )
+        .arg(
+            Arg::new("transpose")
+                .long("transpose")
+                .allow_hyphen_values(true)
+                .value_name("SEMITONES")
+                .value_parser(value_parser!(i32).range(-24..=24))
+                .help("Check the song moved by this many semitones"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .value_name("KEY")
+                .conflicts_with("transpose")
+                .help("Check the song moved into KEY, by the nearest way"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let song = load_song(file)?;
+    let range = SongRange::of(&song).map_err(|e| format!("{}: {}", file, e))?;
+    let mut flats = range.flats;
+
+    let shift = match matches.get_one::<String>("key") {
+        Some(target) => {
+            let to = Key::parse(target).ok_or_else(|| format!("`{}` is not a key", target))?;
+            let from = song
+                .meta_str("key")
+                .ok_or("cannot move to a key: the song has no `key`")?;
+            let from = Key::parse(&from).ok_or_else(|| format!("`{}` is not a key", from))?;
+            flats = to.flats;
+            // Up to a tritone either way, preferring down.
+            (to.tonic.0 as i32 - from.tonic.0 as i32 + 6).rem_euclid(12) - 6
+        }
+        None => matches.get_one::<i32>("transpose").copied().unwrap_or(0),
+    };
+    let reach = |label: &str, reach: &Reach| {
+        let at = match &reach.section {
+            Some(section) => format!("({})", section),
+            None => "(range)".to_string(),
+        };
+        println!(
+            "{:<8} {:<4} {}",
+            label,
+            reach.pitch.transpose(shift).name(flats),
+            theme().paint(Role::Hint, &at)
+        );
+    };
+    if shift != 0 {
+        println!("{}", format!("Transposed {:+}", shift).bold());
+    }
+    reach("Lowest", &range.lowest);
+    reach("Highest", &range.highest);
+    println!("{:<8} {} semitones", "Span", range.range().semitones());
+
+    let Some(singer) = matches.get_one::<String>("singer") else {
+        return Ok(());
+    };
+    let singer = VocalRange::parse(singer)?;
+    let song_range = range.range();
+    let sung = song_range.transpose(shift);
+    let fits = singer.contains(&sung);
+    match fits {
+        true => println!(
+            "{}",
+            theme().paint(Role::Success, &format!("Fits {}", singer.name(flats)))
+        ),
+        false if sung.high > singer.high => println!(
+            "{}",
+            theme().paint(
+                Role::Error,
+                &format!(
+                    "{} is {} semitone(s) above the singer's {}",
+                    sung.high.name(flats),
+                    sung.high.0 - singer.high.0,
+                    singer.high.name(flats)
+                )
+            )
+        ),
+        false => println!(
+            "{}",
+            theme().paint(
+                Role::Error,
+                &format!(
+                    "{} is {} semitone(s) below the singer's {}",
+                    sung.low.name(flats),
+                    singer.low.0 - sung.low.0,
+                    singer.low.name(flats)
+                )
+            )
+        ),
+    }
+    match song_range.shifts_within(&singer) {
+        Some((down, up)) if !fits && down == up => println!("Fits transposed by {:+}", down),
+        Some((down, up)) if !fits => println!("Fits transposed by {:+} to {:+}", down, up),
+        Some(_) => {}
+        None => println!(
+            "No transposition fits: the song spans {} semitones, the singer {}",
+            song_range.semitones(),
+            singer.semitones()
+        ),
+    }
+    match fits {
+        true => Ok(()),
+        false => Err(format!("{} does not fit the singer's range", file).into()),
+    }
+}
@@ -0,0 +1,111 @@
+use super::output::{backup_arg, write_file};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::redact::{redact, RedactOptions};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("redact")
+        .about("Replace names and sensitive terms with rhythm-preserving placeholders")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to redact"),
+        )
+        .arg(
+            Arg::new("term")
+                .short('t')
+                .long("term")
+                .value_name("TERM")
+                .action(ArgAction::Append)
+                .help("Word or phrase to redact; may be repeated"),
+        )
+        .arg(
+            Arg::new("terms-file")
+                .long("terms-file")
+                .value_name("FILE")
+                .help("File with one term per line; `#` starts a comment"),
+        )
+        .arg(
+            Arg::new("keep-names")
+                .long("keep-names")
+                .action(ArgAction::SetTrue)
+                .help("Only redact the given terms, not detected proper nouns"),
+        )
+        .arg(
+            Arg::new("list")
+                .long("list")
+                .action(ArgAction::SetTrue)
+                .help("Print which term each placeholder stands for to stderr"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let mut terms: Vec<String> = matches
+        .get_many::<String>("term")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    if let Some(file) = matches.get_one::<String>("terms-file") {
+        let text = std::fs::read_to_string(file)
+            .map_err(|e| tr("cannot-read", &[("path", &file), ("error", &e)]))?;
+        terms.extend(
+            text.lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    let source = read_song(path)?;
+    let song = parse(path, &source)?;
+    let options = RedactOptions {
+        proper_nouns: !matches.get_flag("keep-names"),
+        terms,
+    };
+    let redacted = redact(&song, &options);
+
+    if matches.get_flag("list") {
+        for r in &redacted.replacements {
+            eprintln!("  {:<6} {} ({}×)", r.syllable, r.term, r.count);
+        }
+    }
+    let words: usize = redacted.replacements.iter().map(|r| r.count).sum();
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Hint,
+            &format!(
+                "redacted {} word(s) across {} term(s)",
+                words,
+                redacted.replacements.len()
+            )
+        )
+    );
+
+    let formatted = format_song(&redacted.song);
+    match matches.get_one::<String>("output") {
+        Some(out) => {
+            write_file(matches, out, formatted.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", out)]))
+            );
+        }
+        None => print!("{}", formatted),
+    }
+    Ok(())
+}
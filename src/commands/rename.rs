@@ -0,0 +1,58 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{read_song, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::refactor::rename;
+use lyrics_dsl::semantic::SymbolKind;
+
+pub fn command() -> Command {
+    Command::new("rename")
+        .about("Rename a variable, macro or anchor and every reference to it")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to edit"),
+        )
+        .arg(
+            Arg::new("symbol")
+                .long("symbol")
+                .required(true)
+                .value_name("NAME")
+                .help("Current name, without `$`, `&` or `*`"),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .required(true)
+                .value_name("NEW")
+                .help("New name"),
+        )
+        .arg(
+            Arg::new("kind")
+                .long("kind")
+                .value_name("KIND")
+                .value_parser(["variable", "macro", "anchor"])
+                .help("Which kind of symbol, when the name is used by several"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let from = matches.get_one::<String>("symbol").expect("required");
+    let to = matches.get_one::<String>("to").expect("required");
+    let kind = matches
+        .get_one::<String>("kind")
+        .map(|kind| match kind.as_str() {
+            "variable" => SymbolKind::Variable,
+            "macro" => SymbolKind::Macro,
+            _ => SymbolKind::Anchor,
+        });
+
+    let source = read_song(path)?;
+    let renamed = rename(&source, kind, from, to).map_err(|e| format!("{}: {}", path, e))?;
+    let changed = apply(matches, path, &source, &renamed)? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
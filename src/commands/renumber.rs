@@ -0,0 +1,49 @@
+//! `renumber`: numbered sections renumbered 1, 2, 3... in the order they
+//! are written, with the `REPEAT`s that name them; the `section-numbering`
+//! lint fix on its own.
+
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::lint;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("renumber")
+        .about("Renumber numbered sections in order, with the REPEATs that name them")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Songs to renumber"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let files: Vec<&String> = matches
+        .get_many::<String>("files")
+        .expect("required")
+        .collect();
+    let mut changed = 0;
+    for path in &files {
+        let source = read_song(path)?;
+        let song = parse(path, &source)?;
+        let (renumbered, made) = lint::renumber(&song, &source);
+        // The edits only touch `[n]`s; a song they break would be a bug.
+        parse(path, &renumbered)?;
+        if apply(matches, path, &source, &renumbered)? == Outcome::Changed {
+            changed += 1;
+            println!("{}", path.bold());
+            for description in &made {
+                println!("  {} {}", theme().paint(Role::Success, "✓"), description);
+            }
+        }
+    }
+    summary(matches, changed, files.len())
+}
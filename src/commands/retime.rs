@@ -0,0 +1,65 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::ast::{Song, Timestamp};
+use lyrics_dsl::format::format_song;
+
+pub fn command() -> Command {
+    Command::new("retime")
+        .about("Shift every timing and tempo change by a fixed offset")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to retime"),
+        )
+        .arg(
+            Arg::new("shift")
+                .long("shift")
+                .required(true)
+                .allow_hyphen_values(true)
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(f64))
+                .help("Seconds to add, negative to move earlier"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+/// Move every timestamp in `song` by `millis`, failing if one would land
+/// before the start of the recording.
+fn shift(song: &mut Song, millis: i64) -> Result<(), String> {
+    let moved = |ts: Timestamp| -> Result<Timestamp, String> {
+        let target = ts.millis as i64 + millis;
+        if target < 0 {
+            return Err(format!("shifting would move {} before 0:00", ts));
+        }
+        Ok(Timestamp::from_millis(target as u64))
+    };
+    for change in &mut song.tempo_changes {
+        change.at = moved(change.at)?;
+    }
+    let lines = song
+        .sections
+        .iter_mut()
+        .flat_map(|s| s.lines.iter_mut())
+        .chain(song.macros.iter_mut().flat_map(|m| m.lines.iter_mut()));
+    for line in lines {
+        if let Some(ts) = line.timing {
+            line.timing = Some(moved(ts)?);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let seconds = *matches.get_one::<f64>("shift").expect("required");
+
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    shift(&mut song, (seconds * 1000.0).round() as i64)?;
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
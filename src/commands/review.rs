@@ -0,0 +1,97 @@
+//! `review`: a song in the terminal with a review's comments under the
+//! lines they are about, numbered through the song like the review copies,
+//! and the comments left on lines an edit has since removed.
+
+use super::{load_song, read_comments, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::ast::Line;
+use lyrics_dsl::feedback::Comment;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("review")
+        .about("Show a song with review comments keyed by line ID under its lines")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song the comments are about"),
+        )
+        .arg(
+            Arg::new("comments")
+                .required(true)
+                .value_name("COMMENTS")
+                .help("JSON file of comments by line ID, from a review tool"),
+        )
+        .arg(
+            Arg::new("commented")
+                .long("commented")
+                .action(ArgAction::SetTrue)
+                .help("Only show the lines that have comments"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let song = load_song(path)?;
+    let comments = read_comments(matches.get_one::<String>("comments").expect("required"))?;
+    let only_commented = matches.get_flag("commented");
+    let show = |number: &str, line: &Line| {
+        let notes = comments.on(line);
+        if only_commented && notes.is_empty() {
+            return;
+        }
+        println!("{:>5}  {}", theme().paint(Role::Hint, number), line.text);
+        for comment in notes {
+            print_comment(comment);
+        }
+    };
+
+    println!("{}", song.title().unwrap_or_else(|| path.clone()).bold());
+    let mut number = 0;
+    for section in &song.sections {
+        let commented = section.lines.iter().any(|line| {
+            !comments.on(line).is_empty() || line.harmony.iter().any(|p| !comments.on(p).is_empty())
+        });
+        if !only_commented || commented {
+            println!(
+                "{}",
+                theme().paint(Role::Heading, &section.label().to_uppercase())
+            );
+        }
+        for line in &section.lines {
+            number += 1;
+            show(&number.to_string(), line);
+            for (part, letter) in line.harmony.iter().zip('a'..='z') {
+                show(&format!("{}{}", number, letter), part);
+            }
+        }
+    }
+
+    let orphans = comments.orphans(&song);
+    if !orphans.is_empty() {
+        println!();
+        println!(
+            "{}",
+            theme()
+                .paint(Role::Warning, "Comments on lines no longer in the song")
+                .bold()
+        );
+        for (id, notes) in orphans {
+            println!("{:>5}  {}", "", theme().paint(Role::Hint, id));
+            notes.iter().for_each(print_comment);
+        }
+    }
+    Ok(())
+}
+
+fn print_comment(comment: &Comment) {
+    let date = comment.date.as_deref().map(|d| format!(" ({})", d));
+    println!(
+        "{:>5}  {}{}",
+        "",
+        theme().paint(Role::Warning, &format!("💬 {}", comment.note())),
+        theme().paint(Role::Hint, &date.unwrap_or_default())
+    );
+}
@@ -0,0 +1,66 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, Outcome};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::ast::Translation;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::romanize::romanize_line;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("romanize")
+        .about("Add romanized versions of CJK and Cyrillic lines")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to romanize"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Replace romanizations the lines already have"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let force = matches.get_flag("force");
+
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    let lang = song.meta_str("lang");
+    let lines = song
+        .sections
+        .iter_mut()
+        .flat_map(|s| s.lines.iter_mut())
+        .chain(song.macros.iter_mut().flat_map(|m| m.lines.iter_mut()));
+    for line in lines {
+        if line.include.is_some() || (line.romanization().is_some() && !force) {
+            continue;
+        }
+        match romanize_line(line, lang.as_deref()) {
+            Ok(Some(romanized)) => {
+                line.translations.retain(|t| t.lang != romanized.lang);
+                line.translations.push(Translation {
+                    lang: romanized.lang,
+                    text: romanized.text,
+                });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let number = source[..line.span.start].matches('\n').count() + 1;
+                let message = format!(
+                    "{}:{}: {}, so its romanization has to be written by hand",
+                    path, number, e
+                );
+                eprintln!("{}", theme().paint(Role::Warning, &message));
+            }
+        }
+    }
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
@@ -0,0 +1,100 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, Outcome};
+use super::{load_valid, parse, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::ast::Song;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+use std::path::Path;
+use std::process::{self, Stdio};
+
+/// Interpreters for scripts by extension; other scripts are run directly,
+/// e.g. through their `#!` line.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("lua", "lua"),
+    ("py", "python3"),
+    ("js", "node"),
+    ("rb", "ruby"),
+    ("sh", "sh"),
+];
+
+pub fn command() -> Command {
+    Command::new("run")
+        .about("Run a script on a song, to transform it or report on it")
+        .arg(
+            Arg::new("script")
+                .required(true)
+                .value_name("SCRIPT")
+                .help("Script reading the song as JSON on standard input"),
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to run the script on"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let script = matches.get_one::<String>("script").expect("required");
+    let path = matches.get_one::<String>("file").expect("required");
+
+    let loaded = load_valid(path)?;
+    let song = parse(path, &loaded.source)?;
+    let json = serde_json::to_vec_pretty(&song)?;
+
+    let extension = Path::new(script).extension().and_then(|e| e.to_str());
+    let mut command = match INTERPRETERS.iter().find(|(ext, _)| Some(*ext) == extension) {
+        Some((_, interpreter)) => {
+            let mut command = process::Command::new(interpreter);
+            command.arg(script);
+            command
+        }
+        None => process::Command::new(script),
+    };
+    let mut child = command
+        .env("LYRICS_DSL_FILE", path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run '{}': {}", script, e))?;
+    // A script that never reads its input closes the pipe early; that is
+    // fine, as its output is all that matters.
+    let _ = child.stdin.take().expect("piped").write_all(&json);
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!("'{}' failed ({})", script, output.status).into());
+    }
+
+    // A script printing a song transforms it; anything else is a report.
+    let printed = String::from_utf8_lossy(&output.stdout);
+    let transformed = printed
+        .trim_start()
+        .starts_with('{')
+        .then(|| serde_json::from_slice::<Song>(&output.stdout).ok())
+        .flatten();
+    let Some(transformed) = transformed else {
+        print!("{}", printed);
+        return Ok(());
+    };
+    if serde_json::to_value(&transformed)? == serde_json::to_value(&song)? {
+        eprintln!(
+            "{}",
+            theme().paint(Role::Hint, &format!("{} is unchanged", path))
+        );
+        return Ok(());
+    }
+    let after = format_song(&transformed);
+    if apply(matches, path, &loaded.source, &after)? == Outcome::Changed
+        && !matches.get_flag("dry-run")
+    {
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &format!("{} rewritten by {}", path, script))
+        );
+    }
+    Ok(())
+}
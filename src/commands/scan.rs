@@ -0,0 +1,85 @@
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::analysis::scansion::{mark, scan_line, scansion_html, section_meter, Scansion};
+use lyrics_dsl::ast::Line;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("scan")
+        .about("Show stressed and unstressed syllables against the declared meter")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to scan"),
+        )
+        .arg(
+            Arg::new("html")
+                .long("html")
+                .value_name("FILE")
+                .help("Also write the scansion as an HTML page"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let song = load_song(file)?;
+
+    for section in &song.sections {
+        let meter = section_meter(&song, section);
+        match &meter {
+            Some(meter) => println!(
+                "{} {}",
+                format!("[{}]", section.label()).bold(),
+                theme().paint(Role::Hint, &meter.to_string())
+            ),
+            None => println!("{}", format!("[{}]", section.label()).bold()),
+        }
+        for line in &section.lines {
+            let scansion = scan_line(line, meter.as_ref());
+            println!("  {}", marks_row(line, &scansion));
+            match (&scansion.expected, scansion.clashes.is_empty()) {
+                (Some(expected), false) => {
+                    println!(
+                        "  {}  {}",
+                        line.text,
+                        theme().paint(Role::Error, &format!("≠ {}", expected))
+                    )
+                }
+                _ => println!("  {}", line.text),
+            }
+        }
+        println!();
+    }
+
+    if let Some(path) = matches.get_one::<String>("html") {
+        std::fs::write(path, scansion_html(&song))?;
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+        );
+    }
+    Ok(())
+}
+
+/// Each word's marks starting at the word's column, red for words that
+/// fight the meter.
+fn marks_row(line: &Line, scansion: &Scansion) -> String {
+    let mut row = String::new();
+    let mut width = 0;
+    for (i, word) in scansion.words.iter().enumerate() {
+        let column = line.text[..word.range.start].chars().count();
+        let pad = column.saturating_sub(width).max(usize::from(width > 0));
+        row.push_str(&" ".repeat(pad));
+        let marks: String = word.pattern.chars().map(mark).collect();
+        width += pad + word.pattern.len();
+        if scansion.clashes.contains(&i) {
+            row.push_str(&theme().paint(Role::Error, &marks).bold().to_string());
+        } else {
+            row.push_str(&marks);
+        }
+    }
+    row
+}
@@ -0,0 +1,45 @@
+use super::output::{backup_arg, write_file};
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::schema::song_schema;
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+
+pub fn command() -> Command {
+    Command::new("schema")
+        .about("Print the schema of the JSON export")
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .default_value("json-schema")
+                .value_parser(["json-schema"])
+                .help("Schema language"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let mut schema = serde_json::to_vec_pretty(&song_schema())?;
+    schema.push(b'\n');
+    match matches.get_one::<String>("output") {
+        Some(path) => {
+            write_file(matches, path, &schema)?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+            );
+        }
+        None => std::io::stdout().write_all(&schema)?,
+    }
+    Ok(())
+}
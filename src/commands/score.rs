@@ -0,0 +1,101 @@
+//! `score`: one weighted score for each song, or for each saved draft of
+//! one, from the project's rubric, with the metrics it is made of.
+
+use super::draft::store_for;
+use super::{load_song, parse, project_config, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::rubric::{score, Rubric, Score};
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::theme::Role;
+use serde_json::json;
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("score")
+        .about("Score songs or drafts against the project's weighted rubric")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .help("Songs to score"),
+        )
+        .arg(
+            Arg::new("drafts")
+                .long("drafts")
+                .action(ArgAction::SetTrue)
+                .help("Also score every saved draft of each song, oldest first"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the scores as JSON"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let rubric = project_config()?.rubric.clone().unwrap_or_default();
+    let mut scored: Vec<(String, Score)> = Vec::new();
+    for file in matches.get_many::<String>("files").expect("required") {
+        if matches.get_flag("drafts") {
+            let store = store_for(Path::new(file))?;
+            for draft in store.list().map_err(|e| e.to_string())? {
+                let source = store.load(draft.number).map_err(|e| e.to_string())?;
+                let label = format!("{} draft {}", file, draft.number);
+                match parse(&label, &source) {
+                    Ok(song) => scored.push((label, score(&resolve(&song).song, &rubric))),
+                    Err(e) => eprintln!(
+                        "{}",
+                        theme().paint(Role::Warning, &format!("skipping {}", e))
+                    ),
+                }
+            }
+        }
+        scored.push((file.clone(), score(&load_song(file)?, &rubric)));
+    }
+
+    if matches.get_flag("json") {
+        let scores: Vec<_> = scored
+            .iter()
+            .map(|(name, score)| json!({ "name": name, "score": score }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&scores)?);
+        return Ok(());
+    }
+    print_table(&rubric, &scored);
+    Ok(())
+}
+
+fn print_table(rubric: &Rubric, scored: &[(String, Score)]) {
+    let names: Vec<String> = rubric
+        .weights
+        .iter()
+        .map(|(metric, weight)| format!("{} ×{}", metric.name(), weight))
+        .collect();
+    let width = scored
+        .iter()
+        .map(|(n, _)| n.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut header = format!("{:<width$}", "", width = width);
+    for name in &names {
+        header += &format!("  {}", name);
+    }
+    println!("{}  {}", theme().paint(Role::Hint, &header), "score".bold());
+    for (name, score) in scored {
+        let mut row = format!("{:<width$}", name, width = width);
+        for (part, column) in score.parts.iter().zip(&names) {
+            let value = part.value.map_or("-".to_string(), |v| v.to_string());
+            row += &format!("  {:>w$}", value, w = column.chars().count());
+        }
+        let total = format!("{:>5}", score.total);
+        let total = match score.total {
+            80..=100 => theme().paint(Role::Success, &total),
+            50..=79 => theme().paint(Role::Warning, &total),
+            _ => theme().paint(Role::Error, &total),
+        };
+        println!("{}  {}", row, total.bold());
+    }
+}
@@ -0,0 +1,66 @@
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::selftest::{self, CaseResult};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("self-test")
+        .about("Render the bundled sample songs through every exporter and compare with snapshots")
+        .arg(
+            Arg::new("diff")
+                .long("diff")
+                .action(clap::ArgAction::SetTrue)
+                .help("Print expected and actual output for mismatches"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let show_diff = matches.get_flag("diff");
+    let cases = selftest::run();
+    let mut failed = 0;
+
+    for case in &cases {
+        let label = format!("{} → {}", case.sample, case.format);
+        match &case.result {
+            CaseResult::Pass => println!("  {} {}", theme().paint(Role::Success, "✓"), label),
+            CaseResult::Missing => {
+                failed += 1;
+                println!(
+                    "  {} {} (no snapshot)",
+                    theme().paint(Role::Warning, "?"),
+                    label
+                );
+            }
+            CaseResult::Failed(err) => {
+                failed += 1;
+                println!("  {} {}: {}", theme().paint(Role::Error, "✗"), label, err);
+            }
+            CaseResult::Mismatch { expected, actual } => {
+                failed += 1;
+                println!(
+                    "  {} {} (output differs from snapshot)",
+                    theme().paint(Role::Error, "✗"),
+                    label
+                );
+                if show_diff {
+                    println!("{}", theme().paint(Role::Hint, "--- expected"));
+                    println!("{}", expected);
+                    println!("{}", theme().paint(Role::Hint, "+++ actual"));
+                    println!("{}", actual);
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} snapshot checks failed", failed, cases.len()).into());
+    }
+    println!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!("✅ {} snapshot checks passed", cases.len())
+        )
+    );
+    Ok(())
+}
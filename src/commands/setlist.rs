@@ -0,0 +1,103 @@
+use super::output::{backup_arg, write_file};
+use super::signing::{sign_arg, sign_output};
+use super::{load_song, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::export::{list_pdf, Exporter, PdfExporter, TextExporter};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::setlist::{apply_key, cheat_sheet, Setlist};
+use lyrics_dsl::theme::Role;
+use std::io::Write;
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("setlist")
+        .about("Combine the songs of a setlist into one performance document")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("SET")
+                .help("Setlist TOML file"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(["pdf", "txt"])
+                .default_value("pdf")
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("cheat-sheet")
+                .long("cheat-sheet")
+                .action(ArgAction::SetTrue)
+                .help("Only list the songs, one line each, with key and tempo"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write to FILE instead of standard output"),
+        )
+        .arg(backup_arg())
+        .arg(sign_arg().requires("output"))
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let format = matches.get_one::<String>("format").expect("defaulted");
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+    let setlist = Setlist::parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+
+    let mut songs = Vec::new();
+    for entry in &setlist.songs {
+        let file = base.join(&entry.file);
+        let file = file.to_string_lossy();
+        let mut song = load_song(&file)?;
+        apply_key(&mut song, entry).map_err(|e| format!("{}: {}", file, e))?;
+        songs.push(song);
+    }
+    let title = setlist
+        .title
+        .clone()
+        .unwrap_or_else(|| "Setlist".to_string());
+
+    let rendered = if matches.get_flag("cheat-sheet") {
+        let pairs: Vec<_> = setlist.songs.iter().zip(&songs).collect();
+        let lines = cheat_sheet(&pairs);
+        match format.as_str() {
+            "pdf" => list_pdf(&title, &lines),
+            _ => format!("{}\n\n{}\n", title, lines.join("\n")).into_bytes(),
+        }
+    } else {
+        match format.as_str() {
+            "pdf" => PdfExporter::default().export_all(&title, &songs),
+            _ => {
+                let mut out = Vec::new();
+                for (i, song) in songs.iter().enumerate() {
+                    if i > 0 {
+                        out.extend_from_slice(b"\n\x0c\n");
+                    }
+                    out.extend(TextExporter.export(song)?);
+                }
+                out
+            }
+        }
+    };
+
+    match matches.get_one::<String>("output") {
+        Some(out) => {
+            write_file(matches, out, &rendered)?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", out)]))
+            );
+            sign_output(matches, out)?;
+        }
+        None => std::io::stdout().write_all(&rendered)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! `show`: a song as it reads, with colored section headings and chords set
+//! above the words, paged through `$PAGER` when it is taller than the
+//! terminal.
+
+use super::terminal::window_size;
+use super::{load_valid, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::theme::Role;
+use lyrics_dsl::view::{render, rows, RowKind};
+use std::io::{IsTerminal, Write};
+use std::process::Stdio;
+
+/// The pager used when `$PAGER` is not set.
+const PAGER: &str = "less -R";
+
+pub fn command() -> Command {
+    Command::new("show")
+        .about("Show a song with section headings and chords above the words")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to show"),
+        )
+        .arg(
+            Arg::new("line-numbers")
+                .short('n')
+                .long("line-numbers")
+                .action(ArgAction::SetTrue)
+                .help("Number the lines as they are in the source"),
+        )
+        .arg(
+            Arg::new("no-pager")
+                .long("no-pager")
+                .action(ArgAction::SetTrue)
+                .help("Print the song even when it is taller than the terminal"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let loaded = load_valid(path)?;
+    let rows = rows(&loaded.resolved.song, &loaded.source);
+    let theme = theme();
+    let text = render(&rows, matches.get_flag("line-numbers"), |kind, text| {
+        let painted = match kind {
+            RowKind::Title => theme.paint(Role::Heading, text),
+            RowKind::Heading(kind) => theme.paint(Role::of_section(kind), text).bold(),
+            RowKind::Chords => theme.paint(Role::Hint, text).bold(),
+            RowKind::Lyric => theme.paint(Role::Lyric, text),
+            RowKind::Harmony => theme.paint(Role::Lyric, text).italic(),
+            RowKind::Blank => return String::new(),
+        };
+        painted.to_string()
+    });
+
+    let paged = !matches.get_flag("no-pager")
+        && std::io::stdout().is_terminal()
+        && window_size().is_some_and(|(_, height)| rows.len() >= height);
+    if !paged || !page(&text) {
+        print!("{}", text);
+    }
+    Ok(())
+}
+
+/// Send `text` through `$PAGER`, or `less -R`. Returns whether a pager
+/// took it; an empty `$PAGER` or one that cannot be started leaves the
+/// text to be printed.
+fn page(text: &str) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        return false;
+    };
+    let child = std::process::Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The reader quitting early closes the pipe; that is not an error.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
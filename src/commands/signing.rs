@@ -0,0 +1,74 @@
+//! `--sign`: a manifest and detached signature written next to an export,
+//! for commands that deliver files to someone else.
+//!
+//! Signing `song.pdf` writes `song.pdf.sha256`, listing the digest of the
+//! file as written to disk, and `song.pdf.sha256.sig`, the signature of
+//! that manifest. `lyrics-dsl verify` checks both.
+
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches};
+use lyrics_dsl::delivery::{Manifest, SigningKey};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use std::error::Error;
+use std::path::Path;
+
+/// The `--sign` option of export commands.
+pub fn sign_arg() -> Arg {
+    Arg::new("sign")
+        .long("sign")
+        .value_name("KEYFILE")
+        .help("Write a signed SHA-256 manifest next to the output, see `keygen`")
+}
+
+/// The signing key in `path`.
+pub fn signing_key(path: &str) -> Result<SigningKey, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+    Ok(SigningKey::parse(&text).map_err(|e| format!("{}: {}", path, e))?)
+}
+
+/// The manifest written for the output `path`.
+pub fn manifest_path(path: &str) -> String {
+    format!("{}.sha256", path)
+}
+
+/// The signature written for the manifest `path`.
+pub fn signature_path(manifest: &str) -> String {
+    format!("{}.sig", manifest)
+}
+
+/// With `--sign`, write the signed manifest of `output`, which must already
+/// be written.
+pub fn sign_output(matches: &ArgMatches, output: &str) -> CommandResult {
+    let Some(keyfile) = matches.get_one::<String>("sign") else {
+        return Ok(());
+    };
+    let key = signing_key(keyfile)?;
+    let contents = std::fs::read(output)
+        .map_err(|e| tr("cannot-read", &[("path", &output), ("error", &e)]))?;
+    let name = Path::new(output)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| output.to_string());
+    let mut manifest = Manifest::new();
+    manifest.add(&name, &contents);
+    let text = manifest.to_text();
+
+    let manifest_path = manifest_path(output);
+    let signature_path = signature_path(&manifest_path);
+    for (path, contents) in [
+        (&manifest_path, text.clone()),
+        (&signature_path, key.sign(text.as_bytes())),
+    ] {
+        std::fs::write(path, contents).map_err(|e| format!("cannot write '{}': {}", path, e))?;
+    }
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!("🔏 Signed manifest written to: {}", manifest_path)
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use super::{load_valid, theme, CommandResult, Loaded};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::analysis::similarity::{compare, Similarity, SimilarityOptions};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("similar")
+        .about("Find passages two or more songs share")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(2..)
+                .value_name("FILE")
+                .help("Songs to compare; more than two compares every pair"),
+        )
+        .arg(
+            Arg::new("threshold")
+                .long("threshold")
+                .value_name("RATIO")
+                .default_value("0.8")
+                .value_parser(value_parser!(f64))
+                .help("Word similarity from 0 to 1 at which lines match"),
+        )
+        .arg(
+            Arg::new("ngram")
+                .long("ngram")
+                .value_name("N")
+                .default_value("4")
+                .value_parser(value_parser!(usize))
+                .help("Length of the word n-grams behind the overall score"),
+        )
+        .arg(
+            Arg::new("min-words")
+                .long("min-words")
+                .value_name("N")
+                .default_value("3")
+                .value_parser(value_parser!(usize))
+                .help("Ignore lines with fewer words"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let options = SimilarityOptions {
+        threshold: *matches.get_one("threshold").expect("defaulted"),
+        ngram: *matches.get_one("ngram").expect("defaulted"),
+        min_words: *matches.get_one("min-words").expect("defaulted"),
+    };
+    let files: Vec<&String> = matches.get_many("files").expect("required").collect();
+    let songs = files
+        .iter()
+        .map(|f| load_valid(f))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut pairs = Vec::new();
+    for i in 0..songs.len() {
+        for j in i + 1..songs.len() {
+            let similarity = compare(&songs[i].resolved.song, &songs[j].resolved.song, &options);
+            pairs.push((i, j, similarity));
+        }
+    }
+    pairs.sort_by(|x, y| y.2.ngram.total_cmp(&x.2.ngram));
+
+    for (i, j, similarity) in &pairs {
+        print_pair(
+            (files[*i], &songs[*i]),
+            (files[*j], &songs[*j]),
+            similarity,
+            &options,
+        );
+    }
+    Ok(())
+}
+
+fn print_pair(
+    (a_path, a): (&str, &Loaded),
+    (b_path, b): (&str, &Loaded),
+    similarity: &Similarity,
+    options: &SimilarityOptions,
+) {
+    let percent = format!("{:.0}%", similarity.ngram * 100.0);
+    println!(
+        "{} {} {}: {} shared {}-grams, {} matching passage(s)",
+        a_path.bold(),
+        theme().paint(Role::Hint, "↔"),
+        b_path.bold(),
+        if similarity.ngram >= options.threshold {
+            theme().paint(Role::Error, &percent)
+        } else {
+            percent.normal()
+        },
+        options.ngram,
+        similarity.passages.len()
+    );
+    for passage in &similarity.passages {
+        let first = &passage.lines[0];
+        let (a_line, _) = first.a.line_col(&a.source);
+        let (b_line, _) = first.b.line_col(&b.source);
+        println!(
+            "  {}:{} {} {}:{} ({} line(s))",
+            a_path,
+            a_line,
+            theme().paint(Role::Hint, "↔"),
+            b_path,
+            b_line,
+            passage.lines.len()
+        );
+        for line in &passage.lines {
+            println!(
+                "    {} {}",
+                theme().paint(Role::Hint, &format!("{:>3.0}%", line.score * 100.0)),
+                line.a_text
+            );
+            if line.b_text != line.a_text {
+                println!("         {}", theme().paint(Role::Hint, &line.b_text));
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::{load_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::speech::{self, Utterance};
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("speak")
+        .about("Read a song aloud through text-to-speech (needs the `tts` feature)")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to read"),
+        )
+        .arg(
+            Arg::new("section")
+                .long("section")
+                .value_name("NAME")
+                .help("Only read sections with this name, e.g. chorus or \"verse 2\""),
+        )
+        .arg(
+            Arg::new("voice")
+                .long("voice")
+                .value_name("VOICE")
+                .help("Voice of the speech program"),
+        )
+        .arg(
+            Arg::new("rate")
+                .long("rate")
+                .value_name("WPM")
+                .value_parser(value_parser!(u32).range(40..=600))
+                .help("Words a minute"),
+        )
+        .arg(
+            Arg::new("print")
+                .long("print")
+                .action(ArgAction::SetTrue)
+                .help("Print the lines and pauses instead of speaking them"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let file = matches.get_one::<String>("file").expect("required");
+    let song = load_song(file)?;
+    let section = matches.get_one::<String>("section").map(String::as_str);
+    let script = speech::script(&song, section).map_err(|e| format!("{}: {}", file, e))?;
+    if script.is_empty() {
+        return Err(format!("{}: nothing to speak", file).into());
+    }
+    if matches.get_flag("print") {
+        for utterance in &script {
+            println!(
+                "{} {}",
+                utterance.text,
+                theme().paint(Role::Hint, &format!("[{} ms]", utterance.pause.as_millis()))
+            );
+        }
+        return Ok(());
+    }
+    let voice = matches.get_one::<String>("voice").map(String::as_str);
+    let rate = matches.get_one::<u32>("rate").copied();
+    speak(&script, voice, rate)?;
+    Ok(())
+}
+
+#[cfg(feature = "tts")]
+fn speak(script: &[Utterance], voice: Option<&str>, rate: Option<u32>) -> Result<(), String> {
+    speech::speak(script, voice, rate).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "tts"))]
+fn speak(_: &[Utterance], _: Option<&str>, _: Option<u32>) -> Result<(), String> {
+    Err(
+        "text-to-speech is not built in: rebuild with `--features tts`, or use `--print`"
+            .to_string(),
+    )
+}
@@ -0,0 +1,76 @@
+use super::output::{backup_arg, write_file};
+use super::{load_valid, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::capture::format_utc;
+use lyrics_dsl::export::{split_sheet_docx, split_sheet_pdf};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::rights::SplitSheet;
+use lyrics_dsl::theme::Role;
+use std::path::Path;
+use std::time::SystemTime;
+
+pub fn command() -> Command {
+    Command::new("split-sheet")
+        .about("Write a split sheet of a song's writers, shares and publishers to sign")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song whose writers are listed"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .required(true)
+                .value_name("FILE")
+                .help("PDF or DOCX file to write"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["pdf", "docx"])
+                .help("File format [default: by the output's extension, else pdf]"),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .value_name("DATE")
+                .help("Date the split was agreed [default: today]"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let output = matches.get_one::<String>("output").expect("required");
+    let docx = match matches.get_one::<String>("format") {
+        Some(format) => format == "docx",
+        None => Path::new(output)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("docx")),
+    };
+    let date = match matches.get_one::<String>("date") {
+        Some(date) => date.clone(),
+        None => {
+            let secs = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            format_utc(secs)[..10].to_string()
+        }
+    };
+
+    let loaded = load_valid(path)?;
+    let sheet =
+        SplitSheet::from_song(&loaded.resolved.song).map_err(|e| format!("{}: {}", path, e))?;
+    let bytes = match docx {
+        true => split_sheet_docx(&sheet, &date),
+        false => split_sheet_pdf(&sheet, &date),
+    };
+    write_file(matches, output, &bytes)?;
+    eprintln!(
+        "{}",
+        theme().paint(Role::Success, &tr("output-written", &[("path", output)]))
+    );
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use super::output::{backup_arg, write_file};
+use super::{collator, load_song, locale_arg, theme, CommandResult};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::analysis::Vocabulary;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("stats")
+        .about("Word counts and vocabulary of one song or a corpus")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .help("Songs to count; several files give per-song columns"),
+        )
+        .arg(
+            Arg::new("export-csv")
+                .long("export-csv")
+                .value_name("CSV")
+                .help("Write per-word counts with section and song breakdown to CSV"),
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .default_value("10")
+                .value_parser(value_parser!(usize))
+                .help("How many of the most frequent words to list"),
+        )
+        .arg(
+            Arg::new("alphabetical")
+                .long("alphabetical")
+                .action(ArgAction::SetTrue)
+                .help("List every word alphabetically instead of the most frequent"),
+        )
+        .arg(locale_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let files: Vec<&String> = matches.get_many("files").expect("required").collect();
+    let mut vocabulary = Vocabulary::with_collator(collator(matches)?);
+    for file in &files {
+        let song = load_song(file)?;
+        vocabulary.add(&column_name(file, &files), &song);
+    }
+
+    if let Some(path) = matches.get_one::<String>("export-csv") {
+        write_file(matches, path, vocabulary.to_csv().as_bytes())?;
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &tr("output-written", &[("path", path)]))
+        );
+    }
+
+    let (tokens, types) = (vocabulary.tokens(), vocabulary.types());
+    println!("{} {}", "songs:".bold(), vocabulary.songs().len());
+    println!("{} {}", "words:".bold(), tokens);
+    println!("{} {}", "distinct:".bold(), types);
+    if tokens > 0 {
+        println!(
+            "{} {:.2}",
+            "type/token ratio:".bold(),
+            types as f64 / tokens as f64
+        );
+    }
+    let listed = match matches.get_flag("alphabetical") {
+        true => vocabulary.words_alphabetically(),
+        false => {
+            let top = *matches.get_one::<usize>("top").expect("defaulted");
+            vocabulary.words().into_iter().take(top).collect()
+        }
+    };
+    for count in listed {
+        println!("  {:>5}  {}", count.total, count.word);
+    }
+    Ok(())
+}
+
+/// The file stem, or the whole path when another file has the same stem.
+fn column_name(file: &str, files: &[&String]) -> String {
+    let stem = |f: &str| {
+        Path::new(f)
+            .file_stem()
+            .map_or_else(|| f.to_string(), |s| s.to_string_lossy().into_owned())
+    };
+    let name = stem(file);
+    if files.iter().filter(|f| stem(f) == name).count() > 1 {
+        file.to_string()
+    } else {
+        name
+    }
+}
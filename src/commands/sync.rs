@@ -0,0 +1,202 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, Outcome};
+use super::terminal::{Key, Screen};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::ast::{Song, Timestamp};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::sync::{timed_lines, Taps};
+use lyrics_dsl::theme::Role;
+use std::io::{IsTerminal, Write};
+use std::process::{self, Child, Stdio};
+use std::time::{Duration, Instant};
+
+const HELP: &str = "space/enter tap · u undo · q save and quit";
+
+/// Players tried in turn for `--audio`, with the arguments that make them
+/// play without a window or chatter.
+const PLAYERS: &[(&str, &[&str])] = &[
+    ("ffplay", &["-nodisp", "-autoexit", "-loglevel", "quiet"]),
+    ("mpv", &["--no-video", "--really-quiet"]),
+    ("afplay", &[]),
+    ("paplay", &[]),
+    ("aplay", &["-q"]),
+];
+
+pub fn command() -> Command {
+    Command::new("sync")
+        .about("Time a song by tapping along as its recording plays")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to time"),
+        )
+        .arg(
+            Arg::new("audio")
+                .long("audio")
+                .value_name("TRACK")
+                .help("Recording to play while tapping; without it, play it yourself"),
+        )
+        .arg(
+            Arg::new("player")
+                .long("player")
+                .value_name("COMMAND")
+                .requires("audio")
+                .help("Program to play the recording with; the track is appended"),
+        )
+        .arg(
+            Arg::new("offset")
+                .long("offset")
+                .allow_hyphen_values(true)
+                .value_name("SECONDS")
+                .value_parser(value_parser!(f64))
+                .default_value("0")
+                .help("Seconds to add to every tap, negative to allow for reaction time"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+/// Start playing `track`, with `player` or the first of [`PLAYERS`] found.
+pub fn play(track: &str, player: Option<&String>) -> Result<Child, String> {
+    let spawn = |program: &str, args: &[&str]| {
+        process::Command::new(program)
+            .args(args)
+            .arg(track)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    };
+    if let Some(player) = player {
+        let mut words = player.split_whitespace();
+        let program = words.next().ok_or("--player is empty")?;
+        let args: Vec<&str> = words.collect();
+        return spawn(program, &args).map_err(|e| format!("cannot run {}: {}", program, e));
+    }
+    for (program, args) in PLAYERS {
+        if let Ok(child) = spawn(program, args) {
+            return Ok(child);
+        }
+    }
+    Err(format!(
+        "cannot play {}: install one of {} or pass --player",
+        track,
+        PLAYERS
+            .iter()
+            .map(|(program, _)| *program)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let offset = *matches.get_one::<f64>("offset").expect("defaulted");
+    let source = read_song(path)?;
+    let song = parse(path, &source)?;
+    if timed_lines(&song).is_empty() {
+        return Err(format!("{}: no lines to time", path).into());
+    }
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return Err("sync needs an interactive terminal".into());
+    }
+
+    let title = song.title().unwrap_or_else(|| path.clone());
+    let mut taps = Taps::new(&song);
+    let screen = Screen::enter()?;
+    let mut player = match matches.get_one::<String>("audio") {
+        Some(track) => Some(play(track, matches.get_one::<String>("player"))?),
+        None => None,
+    };
+    let start = Instant::now();
+    while !taps.is_finished() {
+        let elapsed = start.elapsed().as_secs_f64();
+        let (width, height) = screen.size();
+        draw(&song, &taps, &title, elapsed, width, height)?;
+        match screen.read_key(Duration::from_millis(30))? {
+            Some(Key::Char(' ' | '\r' | '\n')) => {
+                let at = (start.elapsed().as_secs_f64() + offset).max(0.0);
+                taps.tap(Timestamp::from_millis((at * 1000.0).round() as u64));
+            }
+            Some(Key::Char('u' | '\x7f' | '\x08')) => taps.undo(),
+            Some(Key::Char('q') | Key::Escape | Key::Interrupt) => break,
+            _ => {}
+        }
+    }
+    if let Some(player) = &mut player {
+        let _ = player.kill();
+        let _ = player.wait();
+    }
+    drop(screen);
+
+    let (tapped, total) = taps.progress();
+    let timed = format_song(&taps.apply(&song));
+    if apply(matches, path, &source, &timed)? == Outcome::Changed {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &format!("{} of {} line(s) timed in {}", tapped, total, path)
+            )
+        );
+    } else {
+        eprintln!("{}", theme().paint(Role::Hint, &tr("nothing-changed", &[])));
+    }
+    Ok(())
+}
+
+/// The lines around the one to tap next, with a status line at the bottom.
+fn draw(
+    song: &Song,
+    taps: &Taps,
+    title: &str,
+    elapsed: f64,
+    width: usize,
+    height: usize,
+) -> CommandResult {
+    let lines = timed_lines(song);
+    let (tapped, total) = taps.progress();
+    let rows = height.saturating_sub(1).max(1);
+    // Keep the line to tap a third of the way down.
+    let first = tapped.saturating_sub(rows / 3);
+    let timed = taps.apply(song);
+
+    let mut out = String::from("\x1b[H");
+    for (i, &(s, l)) in lines.iter().enumerate().skip(first).take(rows) {
+        let section = &timed.sections[s];
+        let line = &section.lines[l];
+        let stamp = match (i < tapped, line.timing) {
+            (true, Some(at)) => format!("{:>8}", at.to_string()),
+            _ => " ".repeat(8),
+        };
+        let label = if l == 0 {
+            section.header()
+        } else {
+            String::new()
+        };
+        let row = format!("{} {:<12} {}", stamp, label, line.text);
+        let row: String = row.chars().take(width).collect();
+        let row = match i.cmp(&tapped) {
+            std::cmp::Ordering::Less => theme().paint(Role::Hint, &row).to_string(),
+            std::cmp::Ordering::Equal => row.bold().to_string(),
+            std::cmp::Ordering::Greater => row,
+        };
+        out.push_str(&row);
+        out.push_str("\x1b[K\r\n");
+    }
+    out.push_str("\x1b[J");
+    let at = Timestamp::from_millis((elapsed * 1000.0) as u64);
+    let status = format!("{} · {} · {}/{} · {}", title, at, tapped, total, HELP);
+    let status: String = status.chars().take(width).collect();
+    out.push_str(&format!("\x1b[{};1H", height));
+    out.push_str(&status.reversed().to_string());
+    out.push_str("\x1b[K");
+    let mut stdout = std::io::stdout();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, Outcome};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use lyrics_dsl::audio::Audio;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::sync;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("sync-stub")
+        .about("Draft line timings from a recording's sung phrases (experimental)")
+        .arg(
+            Arg::new("audio")
+                .required(true)
+                .value_name("WAV")
+                .help("Recording of the song, as uncompressed WAV"),
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Song to write the timings into"),
+        )
+        .arg(dry_run_arg())
+        .arg(log_change_arg())
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let audio_path = matches.get_one::<String>("audio").expect("required");
+    let path = matches.get_one::<String>("file").expect("required");
+
+    let bytes = std::fs::read(audio_path)
+        .map_err(|e| tr("cannot-read", &[("path", &audio_path), ("error", &e)]))?;
+    let audio = Audio::read_wav(&bytes).map_err(|e| format!("{}: {}", audio_path, e))?;
+    let phrases = audio.phrases();
+    if phrases.is_empty() {
+        return Err(format!("{}: no sung phrases found", audio_path).into());
+    }
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Hint,
+            &format!(
+                "{} phrase(s) found in {:.1}s of audio",
+                phrases.len(),
+                audio.duration()
+            )
+        )
+    );
+
+    let source = read_song(path)?;
+    let song = parse(path, &source)?;
+    let drafted = sync::stub(&song, &phrases);
+    if apply(matches, path, &source, &format_song(&drafted))? == Outcome::Changed {
+        eprintln!(
+            "{}",
+            theme().paint(
+                Role::Success,
+                &format!("Draft timings written to {}; check them by ear", path)
+            )
+        );
+    }
+    Ok(())
+}
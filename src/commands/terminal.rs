@@ -0,0 +1,214 @@
+//! Just enough terminal control for full-screen views and passphrase
+//! prompts: raw input, the alternate screen, the window size and reading a
+//! line without echo, using termios directly.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+/// A key press, as far as full-screen views care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Delete,
+    Escape,
+    /// Ctrl-C, which arrives as a byte in raw mode.
+    Interrupt,
+    Other,
+}
+
+/// The terminal in raw mode on the alternate screen, restored on drop.
+pub struct Screen {
+    #[cfg(unix)]
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl Screen {
+    pub fn enter() -> io::Result<Screen> {
+        // SAFETY: termios is plain data, filled in by tcgetattr before use.
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        stdout.flush()?;
+        Ok(Screen { original })
+    }
+
+    /// Columns and rows, or 80 × 24 when the size is unknown.
+    pub fn size(&self) -> (usize, usize) {
+        window_size().unwrap_or((80, 24))
+    }
+
+    /// Wait up to `timeout` for a key press.
+    pub fn read_key(&self, timeout: Duration) -> io::Result<Option<Key>> {
+        Ok(self.read_keys(timeout)?.into_iter().next())
+    }
+
+    /// Wait up to `timeout` for key presses, and take every one waiting,
+    /// such as the characters of pasted text.
+    pub fn read_keys(&self, timeout: Duration) -> io::Result<Vec<Key>> {
+        let mut fd = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as libc::c_int) };
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::Interrupted => Ok(Vec::new()),
+                _ => Err(err),
+            };
+        }
+        if ready == 0 {
+            return Ok(Vec::new());
+        }
+        let mut buf = [0u8; 1024];
+        let n = io::stdin().read(&mut buf)?;
+        let mut keys = Vec::new();
+        let mut rest = &buf[..n];
+        while !rest.is_empty() {
+            let len = sequence_len(rest);
+            keys.push(decode(&rest[..len]));
+            rest = &rest[len..];
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+#[cfg(not(unix))]
+impl Screen {
+    pub fn enter() -> io::Result<Screen> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "full-screen views need a Unix terminal",
+        ))
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        window_size().unwrap_or((80, 24))
+    }
+
+    pub fn read_key(&self, _timeout: Duration) -> io::Result<Option<Key>> {
+        Ok(None)
+    }
+
+    pub fn read_keys(&self, _timeout: Duration) -> io::Result<Vec<Key>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Columns and rows of the terminal standard output is written to, if it
+/// is one.
+#[cfg(unix)]
+pub fn window_size() -> Option<(usize, usize)> {
+    // SAFETY: winsize is plain data written by the ioctl.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    (ok && size.ws_col > 0 && size.ws_row > 0)
+        .then_some((size.ws_col as usize, size.ws_row as usize))
+}
+
+#[cfg(not(unix))]
+pub fn window_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// Ask for a line on the terminal without showing what is typed.
+pub fn read_secret(prompt: &str) -> io::Result<String> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let line = without_echo(|| {
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).map(|_| line)
+    });
+    eprintln!();
+    Ok(line?.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(unix)]
+fn without_echo<T>(read: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    // SAFETY: termios is plain data, filled in by tcgetattr before use.
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+        return read();
+    }
+    let mut quiet = original;
+    quiet.c_lflag &= !libc::ECHO;
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &quiet) };
+    let result = read();
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &original) };
+    result
+}
+
+#[cfg(not(unix))]
+fn without_echo<T>(read: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    read()
+}
+
+/// The length of the key press `bytes` start with: an escape sequence,
+/// else one UTF-8 character.
+fn sequence_len(bytes: &[u8]) -> usize {
+    match bytes {
+        // `ESC [` runs to a final byte from `@` to `~`.
+        [0x1b, b'[', rest @ ..] => rest
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        [0x1b, b'O', _, ..] => 3,
+        [first, ..] => match first.leading_ones() {
+            2..=4 => (first.leading_ones() as usize).min(bytes.len()),
+            _ => 1,
+        },
+        [] => 0,
+    }
+}
+
+fn decode(bytes: &[u8]) -> Key {
+    match bytes {
+        [0x1b] => Key::Escape,
+        [0x1b, b'[', b'A', ..] | [0x1b, b'O', b'A', ..] => Key::Up,
+        [0x1b, b'[', b'B', ..] | [0x1b, b'O', b'B', ..] => Key::Down,
+        [0x1b, b'[', b'C', ..] | [0x1b, b'O', b'C', ..] => Key::Right,
+        [0x1b, b'[', b'D', ..] | [0x1b, b'O', b'D', ..] => Key::Left,
+        [0x1b, b'[', b'H', ..] | [0x1b, b'O', b'H', ..] | [0x1b, b'[', b'1', b'~', ..] => Key::Home,
+        [0x1b, b'[', b'F', ..] | [0x1b, b'O', b'F', ..] | [0x1b, b'[', b'4', b'~', ..] => Key::End,
+        [0x1b, b'[', b'3', b'~', ..] => Key::Delete,
+        [0x1b, b'[', b'5', b'~', ..] => Key::PageUp,
+        [0x1b, b'[', b'6', b'~', ..] => Key::PageDown,
+        [0x1b, ..] => Key::Other,
+        [0x03, ..] => Key::Interrupt,
+        _ => match std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+        {
+            Some(c) => Key::Char(c),
+            None => Key::Other,
+        },
+    }
+}
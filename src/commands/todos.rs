@@ -0,0 +1,90 @@
+use super::{parse, project_root, read_song, songs_in, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::notes::{self, Located};
+use lyrics_dsl::theme::Role;
+use std::path::{Path, PathBuf};
+
+pub fn command() -> Command {
+    Command::new("todos")
+        .about("List open !! todo and !! idea notes and placeholders across a project")
+        .arg(
+            Arg::new("paths")
+                .num_args(0..)
+                .value_name("PATH")
+                .help("Songs or directories to search; defaults to the project root"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("Include notes marked done"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let paths: Vec<PathBuf> = match matches.get_many::<String>("paths") {
+        Some(paths) => paths.map(PathBuf::from).collect(),
+        None => vec![project_root()?],
+    };
+    let mut files = Vec::new();
+    for path in &paths {
+        if path.is_dir() {
+            songs_in(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut total = 0;
+    for file in &files {
+        let path = file.to_string_lossy();
+        let source = read_song(&path)?;
+        let song = match parse(&path, &source) {
+            Ok(song) => song,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    theme().paint(Role::Warning, &format!("skipping {}", e))
+                );
+                continue;
+            }
+        };
+        let mut found = notes::collect(&song, &source);
+        if !matches.get_flag("all") {
+            found = notes::open(found);
+        }
+        found.extend(notes::placeholders(&song, &source));
+        found.sort_by_key(|n| n.line);
+        if found.is_empty() {
+            continue;
+        }
+        total += found.len();
+        print_song(file, song.title(), &found);
+    }
+    if total == 0 {
+        eprintln!("{}", theme().paint(Role::Success, "✅ No open notes"));
+    }
+    Ok(())
+}
+
+fn print_song(file: &Path, title: Option<String>, found: &[Located]) {
+    match title {
+        Some(title) => println!("{} ({})", title.bold(), file.display()),
+        None => println!("{}", file.display().to_string().bold()),
+    }
+    let mut section = None;
+    for note in found {
+        if section != Some(&note.section) {
+            println!("  {}", theme().paint(Role::Heading, &note.section));
+            section = Some(&note.section);
+        }
+        println!(
+            "    {:>4}  {}: {}  {}",
+            note.line,
+            note.kind.keyword(),
+            note.note,
+            theme().paint(Role::Hint, &format!("— {}", note.text))
+        );
+    }
+}
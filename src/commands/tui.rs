@@ -0,0 +1,317 @@
+use super::keys::locked_key;
+use super::output::{backup_arg, write_file};
+use super::terminal::{Key, Screen};
+use super::{check, project_dictionary, project_root, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::history::{Recovery, Run};
+use lyrics_dsl::lint;
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::theme::Role;
+use lyrics_dsl::tui::{Buffer, Focus, Frame, Kind, Paint, Workbench};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long typing must pause before the song is checked again.
+const CHECK_DELAY: Duration = Duration::from_millis(250);
+
+// Control keys arrive as their ASCII control characters in raw mode.
+const CTRL_Q: char = '\x11';
+const CTRL_S: char = '\x13';
+const CTRL_Y: char = '\x19';
+const CTRL_Z: char = '\x1a';
+
+pub fn command() -> Command {
+    Command::new("tui")
+        .about("Edit, check and preview the project's songs full-screen in the terminal")
+        .arg(
+            Arg::new("file")
+                .value_name("FILE")
+                .help("Song to open first"),
+        )
+        .arg(backup_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return Err("tui needs an interactive terminal".into());
+    }
+    let mut bench = Workbench::new(&project_root()?)?;
+    if let Some(file) = matches.get_one::<String>("file") {
+        open(&mut bench, Path::new(file), &read_song(file)?);
+    }
+
+    let screen = Screen::enter()?;
+    let mut last_edit = Instant::now();
+    let mut quitting = false;
+    loop {
+        if bench.stale() && last_edit.elapsed() >= CHECK_DELAY {
+            recheck(&mut bench);
+        }
+        autosave(&mut bench);
+        let (width, height) = screen.size();
+        draw(&bench.frame(width, height))?;
+
+        for key in screen.read_keys(Duration::from_millis(50))? {
+            bench.message = None;
+            match key {
+                Key::Char(CTRL_Q) | Key::Interrupt => {
+                    if !bench.buffer.modified || quitting {
+                        if let Some(recovery) = &mut bench.recovery {
+                            recovery.discard()?;
+                        }
+                        return Ok(());
+                    }
+                    bench.message = Some("unsaved changes; ^Q again to quit anyway".to_string());
+                    quitting = true;
+                    continue;
+                }
+                Key::Char(CTRL_S) => save(matches, &mut bench),
+                Key::Char(CTRL_Z) => {
+                    if !bench.undo() {
+                        bench.message = Some("nothing to undo".to_string());
+                    }
+                    last_edit = Instant::now();
+                }
+                Key::Char(CTRL_Y) => {
+                    if !bench.redo() {
+                        bench.message = Some("nothing to redo".to_string());
+                    }
+                    last_edit = Instant::now();
+                }
+                Key::Char('\t') => bench.next_focus(),
+                key => match bench.focus {
+                    Focus::Files => files_key(&mut bench, key),
+                    Focus::Editor => {
+                        if editor_key(&mut bench, key, height) {
+                            last_edit = Instant::now();
+                        }
+                    }
+                    Focus::Diagnostics => match key {
+                        Key::Char('\r') => bench.jump_to_diagnostic(),
+                        key => scroll_key(&mut bench, key, height),
+                    },
+                    Focus::Preview => match key {
+                        Key::Left => bench.switch_tab(-1),
+                        Key::Right => bench.switch_tab(1),
+                        key => scroll_key(&mut bench, key, height),
+                    },
+                },
+            }
+            quitting = false;
+        }
+    }
+}
+
+fn files_key(bench: &mut Workbench, key: Key) {
+    match key {
+        Key::Char('\r') | Key::Right => {
+            let Some(entry) = bench.selected_entry().filter(|e| !e.dir) else {
+                return;
+            };
+            let path = entry.path.clone();
+            if bench.buffer.modified && bench.path.as_ref() != Some(&path) {
+                bench.message = Some("unsaved changes; ^S to save them first".to_string());
+                return;
+            }
+            match read_song(&path.display().to_string()) {
+                Ok(text) => open(bench, &path, &text),
+                Err(e) => bench.message = Some(e.to_string()),
+            }
+        }
+        key => scroll_key(bench, key, 0),
+    }
+}
+
+/// Whether the key changed the text.
+fn editor_key(bench: &mut Workbench, key: Key, height: usize) -> bool {
+    if bench.path.is_none() {
+        return false;
+    }
+    match key {
+        Key::Char('\r' | '\n') => bench.edit(None, Buffer::newline),
+        Key::Char('\x7f' | '\x08') => bench.edit(Some(Run::Deleting), Buffer::backspace),
+        Key::Delete => bench.edit(Some(Run::Deleting), Buffer::delete),
+        // A word and the space after it undo together.
+        Key::Char(c) if !c.is_control() => {
+            bench.edit(Some(Run::Typing), |buffer| buffer.insert(c));
+            if c.is_whitespace() {
+                bench.history.seal();
+            }
+        }
+        key => {
+            bench.history.seal();
+            move_cursor(bench, key, height);
+        }
+    }
+    matches!(key, Key::Char(_) | Key::Delete)
+}
+
+fn move_cursor(bench: &mut Workbench, key: Key, height: usize) {
+    let buffer = &mut bench.buffer;
+    match key {
+        Key::Left => buffer.left(),
+        Key::Right => buffer.right(),
+        Key::Up => buffer.vertical(-1),
+        Key::Down => buffer.vertical(1),
+        Key::PageUp => buffer.vertical(-(height as isize / 2)),
+        Key::PageDown => buffer.vertical(height as isize / 2),
+        Key::Home => buffer.home(),
+        Key::End => buffer.end(),
+        Key::Escape => bench.focus = Focus::Files,
+        _ => {}
+    }
+}
+
+fn scroll_key(bench: &mut Workbench, key: Key, height: usize) {
+    let page = (height as isize / 2).max(1);
+    match key {
+        Key::Up => bench.move_by(-1),
+        Key::Down => bench.move_by(1),
+        Key::PageUp => bench.move_by(-page),
+        Key::PageDown => bench.move_by(page),
+        Key::Escape => bench.focus = Focus::Files,
+        _ => {}
+    }
+}
+
+fn save(matches: &ArgMatches, bench: &mut Workbench) {
+    let Some(path) = bench.path.clone() else {
+        return;
+    };
+    let path = path.display().to_string();
+    bench.message = Some(
+        match write_file(matches, &path, bench.buffer.text().as_bytes()) {
+            Ok(()) => {
+                bench.saved();
+                match bench.recovery.as_mut().map(Recovery::discard) {
+                    Some(Err(e)) => e.to_string(),
+                    _ => format!("saved {}", path),
+                }
+            }
+            Err(e) => e.to_string(),
+        },
+    );
+}
+
+/// Start editing `path`, taking back any edits to it a crashed session
+/// left in its recovery file.
+fn open(bench: &mut Workbench, path: &Path, text: &str) {
+    bench.open(path, text);
+    // A locked song's unsaved edits are kept locked too.
+    match locked_key(path) {
+        Ok(Some(key)) => bench.recovery = bench.recovery.take().map(|r| r.locked(key)),
+        Ok(None) => {}
+        Err(e) => {
+            bench.recovery = None;
+            bench.message = Some(e.to_string());
+        }
+    }
+    let Some(recovery) = &mut bench.recovery else {
+        return;
+    };
+    match recovery.load() {
+        Ok(Some(recovered)) if recovered != text => {
+            bench.replace(&recovered);
+            bench.message =
+                Some("recovered unsaved edits; ^S keeps them, ^Z drops them".to_string());
+        }
+        Ok(Some(_)) => {
+            if let Err(e) = recovery.discard() {
+                bench.message = Some(e.to_string());
+            }
+        }
+        Ok(None) => {}
+        Err(e) => bench.message = Some(e.to_string()),
+    }
+}
+
+fn autosave(bench: &mut Workbench) {
+    let Some(recovery) = &mut bench.recovery else {
+        return;
+    };
+    let text = bench.buffer.text();
+    if let Err(e) = recovery.autosave(&text, bench.buffer.modified, Instant::now()) {
+        bench.message = Some(format!("{}: {}", recovery.path().display(), e));
+    }
+}
+
+/// Check the text being edited the way `lint` would, without running the
+/// project's parse hooks on every pause in typing.
+fn recheck(bench: &mut Workbench) {
+    let text = bench.buffer.text();
+    let song = match parse_song(&text) {
+        Ok(song) => song,
+        Err(e) => {
+            let diagnostic = error_diagnostic(&e, &text);
+            bench.checked(text, vec![diagnostic], None);
+            return;
+        }
+    };
+    let resolved = match check(&song) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            bench.message = Some(e.to_string());
+            bench.checked(text, Vec::new(), None);
+            return;
+        }
+    };
+    let name = bench
+        .path
+        .as_ref()
+        .and_then(|p| p.file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dictionary = project_dictionary().unwrap_or_else(|e| {
+        bench.message = Some(e.to_string());
+        None
+    });
+    let mut diagnostics = resolved.diagnostics;
+    diagnostics.extend(lint::check(&song, &text, &name, dictionary));
+    diagnostics.sort_by_key(|d| d.span.start);
+    bench.checked(text, diagnostics, Some(resolved.song));
+}
+
+fn draw(frame: &Frame) -> CommandResult {
+    let theme = theme();
+    let mut out = String::from("\x1b[H");
+    for (i, row) in frame.iter().enumerate() {
+        if i > 0 {
+            out.push_str("\x1b[K\r\n");
+        }
+        for (text, paint) in row {
+            let text = text.as_str();
+            let painted = match paint {
+                Paint::Plain | Paint::Code(Kind::Text) => text.normal(),
+                Paint::Border | Paint::Gutter | Paint::Dim => theme.paint(Role::Hint, text),
+                Paint::Title { focused: true } => text.bold().reversed(),
+                Paint::Title { focused: false } => text.bold(),
+                Paint::Selected | Paint::Cursor | Paint::Status => text.reversed(),
+                Paint::Severity(severity) => theme.paint(*severity, text),
+                Paint::Code(kind) => theme.paint(role(*kind), text),
+            };
+            out.push_str(&painted.to_string());
+        }
+    }
+    out.push_str("\x1b[K");
+    let mut stdout = std::io::stdout();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// The theme role source of each kind is colored as.
+fn role(kind: Kind) -> Role {
+    match kind {
+        Kind::Keyword => Role::Heading,
+        Kind::Meta => Role::Section,
+        Kind::Value => Role::Success,
+        Kind::Variable => Role::Verse,
+        Kind::Attribute => Role::Bridge,
+        Kind::Anchor => Role::Prompt,
+        Kind::Note => Role::Warning,
+        Kind::Harmony => Role::Chorus,
+        Kind::Markup | Kind::Text => Role::Hint,
+    }
+}
@@ -0,0 +1,58 @@
+use super::keys::key;
+use super::output::replace_file;
+use super::{theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::crypt;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("unlock")
+        .about("Decrypt songs locked with `lock`")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .help("Songs to unlock in place"),
+        )
+        .arg(
+            Arg::new("keyfile")
+                .long("keyfile")
+                .value_name("KEYFILE")
+                .help("Key file the songs were locked with"),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .action(ArgAction::SetTrue)
+                .help("Print the songs instead of unlocking the files"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let keyfile = matches.get_one::<String>("keyfile");
+    for path in matches.get_many::<String>("files").expect("required") {
+        let bytes = std::fs::read(path)
+            .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+        let Some(kind) = crypt::locked_with(&bytes) else {
+            eprintln!(
+                "{}",
+                theme().paint(Role::Warning, &format!("{} is not locked", path))
+            );
+            continue;
+        };
+        let song = crypt::unlock(&bytes, &key(kind, keyfile, false)?)
+            .map_err(|e| format!("cannot unlock '{}': {}", path, e))?;
+        if matches.get_flag("stdout") {
+            print!("{}", String::from_utf8_lossy(&song));
+            continue;
+        }
+        replace_file(path, &song, false)?;
+        eprintln!(
+            "{}",
+            theme().paint(Role::Success, &format!("🔓 Unlocked {}", path))
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use super::{load, report, theme, CommandResult};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use lyrics_dsl::analysis::alignment;
+use lyrics_dsl::audio::wav_length;
+use lyrics_dsl::diagnostic::has_errors;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+
+pub fn command() -> Command {
+    Command::new("validate")
+        .about("Parse songs and check their references")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .num_args(1..)
+                .value_name("FILE")
+                .action(ArgAction::Append)
+                .help("Songs to validate"),
+        )
+        .arg(
+            Arg::new("audio")
+                .long("audio")
+                .value_name("WAV")
+                .help("Recording to check the songs' timings against"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let track = match matches.get_one::<String>("audio") {
+        Some(path) => {
+            let bytes = std::fs::read(path)
+                .map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))?;
+            let length = wav_length(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+            Some((length, path.clone()))
+        }
+        None => None,
+    };
+    let mut failed = 0;
+    for path in matches.get_many::<String>("files").expect("required") {
+        let loaded = match load(path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}", theme().paint(Role::Error, &e.to_string()));
+                continue;
+            }
+        };
+        let mut diagnostics = loaded.resolved.diagnostics;
+        if let Some((length, audio)) = &track {
+            let song = &loaded.resolved.song;
+            diagnostics.extend(alignment::check_length(song, *length, audio));
+            diagnostics.sort_by_key(|d| d.span.start);
+        }
+        let diagnostics = &diagnostics;
+        report(path, &loaded.source, diagnostics);
+        if has_errors(diagnostics) {
+            failed += 1;
+        } else {
+            println!("{} {}", theme().paint(Role::Success, "✓"), path);
+        }
+    }
+
+    if failed > 0 {
+        return Err(tr("validation-failed", &[("count", &failed)]).into());
+    }
+    Ok(())
+}
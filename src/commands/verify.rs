@@ -0,0 +1,90 @@
+use super::signing::signature_path;
+use super::{theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::delivery::{matches as digest_matches, Manifest, PublicKey};
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use std::path::Path;
+
+pub fn command() -> Command {
+    Command::new("verify")
+        .about("Check a signed manifest from `--sign` and the files it lists")
+        .arg(
+            Arg::new("manifest")
+                .required(true)
+                .value_name("MANIFEST")
+                .help("Manifest to check, e.g. song.pdf.sha256"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .required(true)
+                .value_name("PUBKEY")
+                .help("Public key of the sender, from `keygen`"),
+        )
+        .arg(
+            Arg::new("signature")
+                .long("signature")
+                .value_name("FILE")
+                .help("Detached signature [default: MANIFEST.sig]"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    let manifest_path = matches.get_one::<String>("manifest").expect("required");
+    let key_path = matches.get_one::<String>("key").expect("required");
+    let signature_path = matches
+        .get_one::<String>("signature")
+        .cloned()
+        .unwrap_or_else(|| signature_path(manifest_path));
+    let read = |path: &str| {
+        std::fs::read(path).map_err(|e| tr("cannot-read", &[("path", &path), ("error", &e)]))
+    };
+
+    let key = PublicKey::parse(&String::from_utf8_lossy(&read(key_path)?))
+        .map_err(|e| format!("{}: {}", key_path, e))?;
+    let text = read(manifest_path)?;
+    let signature = read(&signature_path)?;
+    key.verify(&text, &String::from_utf8_lossy(&signature))
+        .map_err(|e| format!("{}: {}", signature_path, e))?;
+    let manifest = Manifest::parse(&String::from_utf8_lossy(&text))
+        .map_err(|e| format!("{}: {}", manifest_path, e))?;
+
+    let base = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+    let mut failed = 0;
+    for entry in &manifest.entries {
+        let ok = std::fs::read(base.join(&entry.name))
+            .map(|contents| digest_matches(entry, &contents))
+            .unwrap_or(false);
+        if ok {
+            println!("{}: {}", entry.name, theme().paint(Role::Success, "OK"));
+        } else {
+            println!(
+                "{}: {}",
+                entry.name,
+                theme().paint(Role::Error, "FAILED").bold()
+            );
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} file(s) do not match the signed manifest",
+            failed,
+            manifest.entries.len()
+        )
+        .into());
+    }
+    eprintln!(
+        "{}",
+        theme().paint(
+            Role::Success,
+            &format!(
+                "✅ Signature good, {} file(s) verified",
+                manifest.entries.len()
+            )
+        )
+    );
+    Ok(())
+}
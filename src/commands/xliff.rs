@@ -0,0 +1,138 @@
+//! `xliff export` and `xliff import`: a song's lines out to a localization
+//! vendor as XLIFF translation units, and the finished translations back
+//! into the song as `{translation.<tag>: "..."}` variants.
+
+use super::output::{apply, backup_arg, dry_run_arg, log_change_arg, summary, write_file, Outcome};
+use super::{parse, read_song, theme, CommandResult};
+use clap::{Arg, ArgMatches, Command};
+use colored::*;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::i18n::tr;
+use lyrics_dsl::theme::Role;
+use lyrics_dsl::xliff;
+use std::io::Write;
+use std::path::Path;
+
+fn file_arg() -> Arg {
+    Arg::new("file")
+        .required(true)
+        .value_name("FILE")
+        .help("Song to translate")
+}
+
+pub fn command() -> Command {
+    Command::new("xliff")
+        .about("Send a song's lines for translation as XLIFF and take the translations back")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Write the lines as XLIFF translation units, one per line ID")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("target")
+                        .long("target")
+                        .required(true)
+                        .value_name("LANG")
+                        .help("Language to translate into, e.g. fr or pt-BR"),
+                )
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .value_name("LANG")
+                        .help("Language of the lyrics [default: the song's lang, else en]"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write to FILE instead of standard output"),
+                )
+                .arg(backup_arg()),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Add the translations of an XLIFF file to the lines they belong to")
+                .arg(file_arg())
+                .arg(
+                    Arg::new("xliff")
+                        .required(true)
+                        .value_name("XLIFF")
+                        .help("Translated XLIFF 1.2 or 2.0 file"),
+                )
+                .arg(Arg::new("lang").long("lang").value_name("LANG").help(
+                    "Tag to file the translations under [default: the file's target language]",
+                ))
+                .arg(dry_run_arg())
+                .arg(log_change_arg())
+                .arg(backup_arg()),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> CommandResult {
+    match matches.subcommand().expect("subcommand is required") {
+        ("export", sub) => export(sub),
+        ("import", sub) => import(sub),
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn export(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let song = parse(path, &read_song(path)?)?;
+    let source = match matches.get_one::<String>("source") {
+        Some(lang) => lang.clone(),
+        None => song.meta_str("lang").unwrap_or_else(|| "en".to_string()),
+    };
+    let target = matches.get_one::<String>("target").expect("required");
+    let original = Path::new(path)
+        .file_name()
+        .map_or(path.clone(), |name| name.to_string_lossy().into_owned());
+    let out = xliff::export(&song, &original, &source, target)?;
+
+    let unnamed = xliff::unnamed(&song);
+    if unnamed > 0 {
+        eprintln!(
+            "{} {} lines have no ID and are numbered by position; `ids {} --lines` keeps them matched through edits",
+            theme().paint(Role::Warning, "warning:").bold(),
+            unnamed,
+            path
+        );
+    }
+    match matches.get_one::<String>("output") {
+        Some(output) => {
+            write_file(matches, output, out.as_bytes())?;
+            eprintln!(
+                "{}",
+                theme().paint(Role::Success, &tr("output-written", &[("path", output)]))
+            );
+        }
+        None => std::io::stdout().write_all(out.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn import(matches: &ArgMatches) -> CommandResult {
+    let path = matches.get_one::<String>("file").expect("required");
+    let xliff_path = matches.get_one::<String>("xliff").expect("required");
+    let file = std::fs::read_to_string(xliff_path)
+        .map_err(|e| tr("cannot-read", &[("path", xliff_path), ("error", &e)]))?;
+    let source = read_song(path)?;
+    let mut song = parse(path, &source)?;
+    let lang = matches.get_one::<String>("lang").map(String::as_str);
+    let imported =
+        xliff::import(&mut song, &file, lang).map_err(|e| format!("{}: {}", xliff_path, e))?;
+    if !imported.unknown.is_empty() {
+        eprintln!(
+            "{} no line has the IDs {}; their translations were left out",
+            theme().paint(Role::Warning, "warning:").bold(),
+            imported.unknown.join(", ")
+        );
+    }
+    eprintln!(
+        "{} lines translated into {}",
+        imported.translated, imported.lang
+    );
+    let changed = apply(matches, path, &source, &format_song(&song))? == Outcome::Changed;
+    summary(matches, changed as usize, 1)
+}
@@ -0,0 +1,206 @@
+//! Two takes of a section, or of a whole song, set line by line against each
+//! other with the figures that help pick between them: syllables and rhyme
+//! per line, and singability, rhyme density and meter fit overall.
+
+use crate::analysis::language;
+use crate::analysis::scansion::{scan_line, section_meter};
+use crate::analysis::score_line_in;
+use crate::ast::{words, Line, Section, Song};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CompareError {
+    #[error("take {take} has no section `{name}`; it has {labels}")]
+    NoSection {
+        take: char,
+        name: String,
+        labels: String,
+    },
+}
+
+/// One line of one take.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Side {
+    pub text: String,
+    pub syllables: usize,
+    /// The `rhyme` letter written on the line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhyme: Option<char>,
+    /// The sound its last word ends in.
+    pub ending: String,
+    pub singability: u8,
+}
+
+/// The lines in the same place in both takes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Row {
+    pub a: Option<Side>,
+    pub b: Option<Side>,
+}
+
+impl Row {
+    /// How many more syllables take B's line has than take A's.
+    pub fn syllable_delta(&self) -> Option<i64> {
+        Some(self.b.as_ref()?.syllables as i64 - self.a.as_ref()?.syllables as i64)
+    }
+
+    /// Whether the two lines end on the same sound.
+    pub fn same_ending(&self) -> bool {
+        match (&self.a, &self.b) {
+            (Some(a), Some(b)) => a.ending == b.ending,
+            _ => false,
+        }
+    }
+}
+
+/// The figures of a whole take.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Scores {
+    pub lines: usize,
+    pub syllables: usize,
+    /// Mean singability of its lines, 0 to 100.
+    pub singability: u8,
+    /// Share of lines whose last word rhymes with another line's, 0 to 100.
+    pub rhyme_density: u8,
+    /// Share of lines whose stress fits the meter, 0 to 100, when a meter
+    /// is declared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meter_fit: Option<u8>,
+    /// The mean of the three scores above.
+    pub overall: u8,
+}
+
+/// Two takes side by side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    /// The section compared, or none for the whole songs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub rows: Vec<Row>,
+    pub a: Scores,
+    pub b: Scores,
+}
+
+fn percent(part: usize, whole: usize) -> u8 {
+    match whole {
+        0 => 100,
+        _ => ((part * 100 + whole / 2) / whole) as u8,
+    }
+}
+
+/// The sections of `song` to compare: the first named `name`, or every
+/// written-out section.
+fn pick<'a>(
+    song: &'a Song,
+    name: Option<&str>,
+    take: char,
+) -> Result<Vec<&'a Section>, CompareError> {
+    let written = song.sections.iter().filter(|s| s.repeat.is_none());
+    let Some(name) = name else {
+        return Ok(written.collect());
+    };
+    match written.clone().find(|s| s.is_named(name)) {
+        Some(section) => Ok(vec![section]),
+        None => {
+            let mut labels: Vec<String> = Vec::new();
+            for label in written.map(|s| s.label()) {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+            Err(CompareError::NoSection {
+                take,
+                name: name.to_string(),
+                labels: labels.join(", "),
+            })
+        }
+    }
+}
+
+/// The sides of the lines of `sections` and the scores of the take.
+fn measure(song: &Song, sections: &[&Section]) -> (Vec<Side>, Scores) {
+    let language = language::of(song);
+    let mut sides = Vec::new();
+    let (mut rhymed, mut metered, mut fitting) = (0, 0, 0);
+    for section in sections {
+        let lines: Vec<&Line> = section
+            .lines
+            .iter()
+            .filter(|l| !l.text.trim().is_empty())
+            .collect();
+        let last: Vec<String> = lines
+            .iter()
+            .map(|l| words(&l.text).last().unwrap_or_default().to_lowercase())
+            .collect();
+        let meter = section_meter(song, section);
+        for (i, line) in lines.iter().enumerate() {
+            let score = score_line_in(line, section.kind, language);
+            let rhymes = last.iter().enumerate().any(|(j, other)| {
+                i != j && !last[i].is_empty() && language.rhymes(&last[i], other)
+            });
+            rhymed += rhymes as usize;
+            if meter.is_some() || line.stress.is_some() {
+                metered += 1;
+                fitting += scan_line(line, meter.as_ref()).clashes.is_empty() as usize;
+            }
+            sides.push(Side {
+                text: line.text.clone(),
+                syllables: score.syllables,
+                rhyme: line.rhyme,
+                ending: language.rhyme(&last[i]),
+                singability: score.score,
+            });
+        }
+    }
+
+    let singability = percent(
+        sides.iter().map(|s| s.singability as usize).sum(),
+        sides.len() * 100,
+    );
+    let rhyme_density = percent(rhymed, sides.len());
+    let meter_fit = (metered > 0).then(|| percent(fitting, metered));
+    let parts = [Some(singability), Some(rhyme_density), meter_fit];
+    let parts: Vec<usize> = parts.iter().flatten().map(|&p| p as usize).collect();
+    let scores = Scores {
+        lines: sides.len(),
+        syllables: sides.iter().map(|s| s.syllables).sum(),
+        singability,
+        rhyme_density,
+        meter_fit,
+        overall: percent(parts.iter().sum(), parts.len() * 100),
+    };
+    (sides, scores)
+}
+
+/// The scores of every written-out section of the resolved `song`.
+pub fn scores(song: &Song) -> Scores {
+    let sections: Vec<&Section> = song
+        .sections
+        .iter()
+        .filter(|s| s.repeat.is_none())
+        .collect();
+    measure(song, &sections).1
+}
+
+/// Compare the resolved songs `a` and `b`, or only their first sections
+/// named `section`, such as `chorus` or `verse 2`.
+pub fn compare(a: &Song, b: &Song, section: Option<&str>) -> Result<Comparison, CompareError> {
+    let (a_sides, a_scores) = measure(a, &pick(a, section, 'A')?);
+    let (b_sides, b_scores) = measure(b, &pick(b, section, 'B')?);
+    let len = a_sides.len().max(b_sides.len());
+    let mut a_sides = a_sides.into_iter();
+    let mut b_sides = b_sides.into_iter();
+    let rows = (0..len)
+        .map(|_| Row {
+            a: a_sides.next(),
+            b: b_sides.next(),
+        })
+        .collect();
+    Ok(Comparison {
+        section: section.map(str::to_string),
+        rows,
+        a: a_scores,
+        b: b_scores,
+    })
+}
@@ -0,0 +1,175 @@
+//! Project settings, read from `.lyricsdsl/config.toml`.
+//!
+//! ```toml
+//! profile = "strict"   # see `crate::profile`
+//! hooks.post-export = "scripts/upload.sh"   # see `crate::hooks`
+//! locale = "es"   # see `crate::collate`
+//! protect = "Maria, NASA"   # see `crate::capitalize`
+//! dictionary = "en_US"   # see `crate::spelling`
+//! preset.web.format = "html"   # see `crate::preset`
+//! theme.name = "high-contrast"   # see `crate::theme`
+//! rubric.singability = 2   # see `crate::rubric`
+//! ```
+//!
+//! Like setlists, the file uses only the parts of TOML it needs: comments
+//! and `key = value` lines with string and number values.
+
+use crate::hooks::{Hook, Stage};
+use crate::preset::Preset;
+use crate::profile::Profile;
+use crate::rubric::{Metric, Rubric};
+use crate::setlist::{parse_value, strip_comment};
+use crate::theme::{Role, Style, Theme};
+use thiserror::Error;
+
+/// Name of the settings file inside the project directory.
+pub const FILE_NAME: &str = "config.toml";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("line {line}: unknown setting `{key}`")]
+    UnknownKey { line: usize, key: String },
+    #[error("line {line}: unknown profile `{name}`, expected strict, standard or permissive")]
+    UnknownProfile { line: usize, name: String },
+    #[error(
+        "line {line}: unknown hook `{name}`, expected pre-parse, post-parse, pre-export or post-export"
+    )]
+    UnknownHook { line: usize, name: String },
+    #[error("line {line}: unknown theme `{name}`, expected default, high-contrast or colorblind")]
+    UnknownTheme { line: usize, name: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// The parser profile songs in the project are checked against.
+    pub profile: Option<Profile>,
+    /// Commands to run around pipeline stages, in the order given.
+    pub hooks: Vec<Hook>,
+    /// The locale words and titles are sorted for, e.g. `sv`.
+    pub locale: Option<String>,
+    /// Words capitalization styles keep as spelled, such as names.
+    pub protect: Vec<String>,
+    /// The Hunspell dictionary `lint` checks spelling against, e.g. `en_GB`.
+    pub dictionary: Option<String>,
+    /// Named export presets, in the order they are first set.
+    pub presets: Vec<Preset>,
+    /// Colors of terminal output, with any `theme.<role>` styles applied.
+    pub theme: Theme,
+    /// The metrics `score` weighs, when the project sets its own.
+    pub rubric: Option<Rubric>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        // Styles apply over the theme wherever `theme.name` is set in the file.
+        let mut styles: Vec<(Role, Style)> = Vec::new();
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let syntax = |message: &str| ConfigError::Syntax {
+                line,
+                message: message.to_string(),
+            };
+            let content = strip_comment(raw).trim();
+            if content.is_empty() {
+                continue;
+            }
+            let (key, value) = content
+                .split_once('=')
+                .ok_or_else(|| syntax("expected `key = value`"))?;
+            let value = parse_value(value.trim()).ok_or_else(|| syntax("invalid value"))?;
+            match key.trim() {
+                "profile" => {
+                    let profile = Profile::parse(&value)
+                        .ok_or(ConfigError::UnknownProfile { line, name: value })?;
+                    config.profile = Some(profile);
+                }
+                "locale" => config.locale = Some(value),
+                "dictionary" => config.dictionary = Some(value),
+                "protect" => {
+                    let words = value.split(',').map(|w| w.trim().to_string());
+                    config.protect.extend(words.filter(|w| !w.is_empty()));
+                }
+                key if key.starts_with("preset.") => {
+                    let (name, option) = key["preset.".len()..]
+                        .split_once('.')
+                        .filter(|(name, option)| !name.is_empty() && !option.is_empty())
+                        .ok_or_else(|| syntax("expected `preset.<name>.<option> = value`"))?;
+                    match config.presets.iter_mut().find(|p| p.name == name) {
+                        Some(preset) => preset.set(option, &value),
+                        None => config.presets.push(Preset {
+                            name: name.to_string(),
+                            settings: vec![(option.to_string(), value)],
+                        }),
+                    }
+                }
+                // `theme` is the table of `theme.*` keys, so the theme itself
+                // is named by one of them.
+                "theme" => return Err(syntax("pick the theme with `theme.name = \"...\"`")),
+                "theme.name" => {
+                    config.theme = Theme::named(&value)
+                        .ok_or(ConfigError::UnknownTheme { line, name: value })?;
+                }
+                key if key.starts_with("theme.") => {
+                    let name = &key["theme.".len()..];
+                    let role = Role::parse(name).ok_or_else(|| {
+                        let roles: Vec<&str> = Role::ALL.iter().map(|r| r.name()).collect();
+                        syntax(&format!(
+                            "unknown theme role `{}`, expected one of {}",
+                            name,
+                            roles.join(", ")
+                        ))
+                    })?;
+                    let style = Style::parse(&value)
+                        .ok_or_else(|| syntax(&format!("invalid style `{}`", value)))?;
+                    styles.push((role, style));
+                }
+                key if key.starts_with("rubric.") => {
+                    let name = &key["rubric.".len()..];
+                    let metric = Metric::parse(name).ok_or_else(|| {
+                        let names: Vec<&str> = Metric::ALL.iter().map(|m| m.name()).collect();
+                        syntax(&format!(
+                            "unknown metric `{}`, expected one of {}",
+                            name,
+                            names.join(", ")
+                        ))
+                    })?;
+                    let weight = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|w| w.is_finite())
+                        .ok_or_else(|| syntax(&format!("invalid weight `{}`", value)))?;
+                    config
+                        .rubric
+                        .get_or_insert_with(|| Rubric {
+                            weights: Vec::new(),
+                        })
+                        .set(metric, weight);
+                }
+                key if key.starts_with("hooks.") => {
+                    let name = &key["hooks.".len()..];
+                    let stage = Stage::parse(name).ok_or_else(|| ConfigError::UnknownHook {
+                        line,
+                        name: name.to_string(),
+                    })?;
+                    config.hooks.push(Hook {
+                        stage,
+                        command: value,
+                    });
+                }
+                key => {
+                    return Err(ConfigError::UnknownKey {
+                        line,
+                        key: key.to_string(),
+                    })
+                }
+            }
+        }
+        for (role, style) in styles {
+            config.theme.set(role, style);
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,319 @@
+//! What a line still to be written must satisfy, worked out from the rest
+//! of the song, so a tool proposing lines can be steered by the same
+//! analysis `lint` and `scan` use and its proposals checked against it.
+//!
+//! [`constraints`] describes the line: how many syllables it should have,
+//! which lines it rhymes with, the stress pattern of the section's meter and
+//! the words it may not use. [`Constraints::check`] measures a candidate
+//! against each of them.
+
+use crate::analysis::language::{self, English, Language};
+use crate::analysis::scansion::{scan_line, section_meter, Meter};
+use crate::analysis::syllables::count_line_in;
+use crate::ast::{blank_ranges, words, Line, Section, Song};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Name of the list of words generated lines may not use, one a line,
+/// inside the project directory.
+pub const BANNED_FILE: &str = "banned.txt";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConstraintsError {
+    #[error("line {0} is not a lyric line of a section")]
+    NotALine(usize),
+}
+
+/// How many syllables the line should have, and what says so.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Syllables {
+    pub target: usize,
+    pub min: usize,
+    pub max: usize,
+    pub basis: String,
+}
+
+/// The lines the line rhymes with, by its rhyme letter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rhyme {
+    pub scheme: char,
+    /// The last words of the partner lines.
+    pub with: Vec<String>,
+    /// The sound the last word should end in, e.g. `iver`.
+    pub ending: String,
+}
+
+/// The stress pattern the section's meter asks of the line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Stress {
+    pub meter: String,
+    pub pattern: String,
+}
+
+/// Everything a line must satisfy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Constraints {
+    /// 1-based source line.
+    pub line: usize,
+    pub section: String,
+    /// The line as it stands, with blanks where words are missing.
+    pub current: String,
+    /// What its `<tbd: ...>` placeholders ask for.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+    /// The song's language code, whose rules syllables and rhymes follow.
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syllables: Option<Syllables>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhyme: Option<Rhyme>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stress: Option<Stress>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub banned: Vec<String>,
+}
+
+/// How a candidate fares against one constraint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub constraint: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// How a candidate fares against every constraint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Verdict {
+    pub candidate: String,
+    pub ok: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Whether `line` is written out, with no blanks left in it.
+fn complete(line: &Line) -> bool {
+    line.include.is_none() && !line.text.trim().is_empty() && line.placeholders.is_empty()
+}
+
+fn last_word(text: &str) -> Option<String> {
+    words(text).last().map(str::to_lowercase)
+}
+
+fn median(mut counts: Vec<usize>) -> Option<usize> {
+    counts.sort_unstable();
+    counts.get(counts.len() / 2).copied()
+}
+
+/// The sections that are sung to the same music as `section`: the other
+/// written-out sections of its kind and name.
+fn parallels<'a>(song: &'a Song, section: &'a Section) -> impl Iterator<Item = &'a Section> {
+    song.sections.iter().filter(move |s| {
+        !std::ptr::eq(*s, section)
+            && s.repeat.is_none()
+            && s.kind == section.kind
+            && s.name == section.name
+    })
+}
+
+/// The constraints on the lyric line written at source line `number` of
+/// the resolved `song`, parsed from `source`. Words in `banned` may not be
+/// used.
+pub fn constraints(
+    song: &Song,
+    source: &str,
+    number: usize,
+    banned: &[String],
+) -> Result<Constraints, ConstraintsError> {
+    let (section, index) = song
+        .sections
+        .iter()
+        .filter(|s| s.repeat.is_none())
+        .find_map(|s| {
+            s.lines
+                .iter()
+                .position(|l| l.include.is_none() && l.span.line_col(source).0 == number)
+                .map(|i| (s, i))
+        })
+        .ok_or(ConstraintsError::NotALine(number))?;
+    let line = &section.lines[index];
+    let language = language::of(song);
+    let count = |l: &Line| count_line_in(l, language);
+    let others: Vec<&Line> = section
+        .lines
+        .iter()
+        .enumerate()
+        .filter(|(i, l)| *i != index && complete(l))
+        .map(|(_, l)| l)
+        .collect();
+    let parallel: Vec<(&Section, &Line)> = parallels(song, section)
+        .filter_map(|s| s.lines.get(index).filter(|l| complete(l)).map(|l| (s, l)))
+        .collect();
+
+    let syllables = if let Some(stress) = &line.stress {
+        Some(Syllables {
+            target: stress.len(),
+            min: stress.len(),
+            max: stress.len(),
+            basis: "the line's stress pattern".to_string(),
+        })
+    } else if let Some(target) = median(parallel.iter().map(|(_, l)| count(l)).collect()) {
+        let labels: Vec<String> = parallel
+            .iter()
+            .map(|(s, _)| format!("line {} of {}", index + 1, s.label()))
+            .collect();
+        Some(Syllables {
+            target,
+            min: target.saturating_sub(1),
+            max: target + 1,
+            basis: labels.join(", "),
+        })
+    } else {
+        let counts: Vec<usize> = others.iter().map(|l| count(l)).collect();
+        median(counts.clone()).map(|target| Syllables {
+            target,
+            min: counts.iter().copied().min().unwrap_or(target),
+            max: counts.iter().copied().max().unwrap_or(target),
+            basis: format!("the other lines of {}", section.label()),
+        })
+    };
+
+    let scheme = line
+        .rhyme
+        .or_else(|| parallel.iter().find_map(|(_, l)| l.rhyme));
+    let rhyme = scheme.and_then(|scheme| {
+        let with: Vec<String> = others
+            .iter()
+            .filter(|l| l.rhyme == Some(scheme))
+            .filter_map(|l| last_word(&l.text))
+            .collect();
+        let ending = language.rhyme(with.first()?);
+        Some(Rhyme {
+            scheme,
+            with,
+            ending,
+        })
+    });
+
+    let stress = match (&line.stress, section_meter(song, section)) {
+        (Some(pattern), _) => Some(Stress {
+            meter: "the line's stress pattern".to_string(),
+            pattern: pattern.clone(),
+        }),
+        (None, Some(meter)) => syllables.as_ref().map(|s| Stress {
+            meter: meter.name.clone(),
+            pattern: meter.expected(s.target),
+        }),
+        (None, None) => None,
+    };
+
+    Ok(Constraints {
+        line: number,
+        section: section.label(),
+        current: line.text.clone(),
+        hints: line
+            .placeholders
+            .iter()
+            .filter_map(|p| p.hint.clone())
+            .collect(),
+        language: language.code().to_string(),
+        syllables,
+        rhyme,
+        stress,
+        banned: banned.to_vec(),
+    })
+}
+
+impl Constraints {
+    /// Measure `candidate` against every constraint.
+    pub fn check(&self, candidate: &str) -> Verdict {
+        let language = language::find(&self.language).unwrap_or(&English as &dyn Language);
+        let line = Line {
+            text: candidate.trim().to_string(),
+            ..Line::default()
+        };
+        let mut checks = Vec::new();
+
+        checks.push(Check {
+            constraint: "complete",
+            ok: blank_ranges(&line.text).is_empty() && !line.text.contains("<tbd"),
+            detail: "no placeholders left".to_string(),
+        });
+        if let Some(syllables) = &self.syllables {
+            let count = count_line_in(&line, language);
+            checks.push(Check {
+                constraint: "syllables",
+                ok: (syllables.min..=syllables.max).contains(&count),
+                detail: format!(
+                    "{} syllables, {} to {} wanted",
+                    count, syllables.min, syllables.max
+                ),
+            });
+        }
+        if let Some(rhyme) = &self.rhyme {
+            let last = last_word(&line.text).unwrap_or_default();
+            let partner = rhyme
+                .with
+                .iter()
+                .find(|w| **w != last && language.rhymes(w, &last));
+            checks.push(Check {
+                constraint: "rhyme",
+                ok: partner.is_some(),
+                detail: match partner {
+                    Some(partner) => format!("`{}` rhymes with `{}`", last, partner),
+                    None => format!("`{}` does not rhyme with {}", last, rhyme.with.join(", ")),
+                },
+            });
+        }
+        if let Some(stress) = &self.stress {
+            let meter = Meter::parse(&stress.meter).unwrap_or(Meter {
+                name: stress.meter.clone(),
+                foot: stress.pattern.clone(),
+            });
+            let scansion = scan_line(&line, Some(&meter));
+            let clashes: Vec<&str> = scansion
+                .clashes
+                .iter()
+                .map(|&i| &line.text[scansion.words[i].range.clone()])
+                .collect();
+            checks.push(Check {
+                constraint: "stress",
+                ok: clashes.is_empty(),
+                detail: match clashes.is_empty() {
+                    true => format!("fits {}", stress.pattern),
+                    false => format!("{} fight {}", clashes.join(", "), stress.pattern),
+                },
+            });
+        }
+        if !self.banned.is_empty() {
+            let used: Vec<String> = words(&line.text).map(str::to_lowercase).collect();
+            let found: Vec<&String> = self
+                .banned
+                .iter()
+                .filter(|term| {
+                    let term: Vec<String> = words(term).map(str::to_lowercase).collect();
+                    !term.is_empty() && used.windows(term.len()).any(|w| w == term.as_slice())
+                })
+                .collect();
+            checks.push(Check {
+                constraint: "banned",
+                ok: found.is_empty(),
+                detail: match found.is_empty() {
+                    true => "no banned words".to_string(),
+                    false => format!(
+                        "uses {}",
+                        found
+                            .iter()
+                            .map(|s| s.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                },
+            });
+        }
+
+        Verdict {
+            candidate: line.text,
+            ok: checks.iter().all(|c| c.ok),
+            checks,
+        }
+    }
+}
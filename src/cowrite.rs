@@ -0,0 +1,373 @@
+//! Co-writing with a language model: a prompt pack describing the song for
+//! the model to write its missing lines, and the lines it suggests merged
+//! back as `!! idea: suggested: ...` notes for the writer to take or leave.
+//!
+//! The pack holds the song's structure and lines, a few statistics of its
+//! style and the [`constraints`] on each line with a placeholder, as JSON or
+//! as a Markdown document to paste into a chat. Its answer format names lines
+//! by source line or line ID, so a reply can be merged into the song it came
+//! from.
+
+use crate::analysis::language;
+use crate::analysis::syllables::count_line_in;
+use crate::analysis::Vocabulary;
+use crate::ast::{Line, Note, NoteKind, Song};
+use crate::constraints::{constraints, Constraints};
+use crate::semantic::resolve;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// What a suggested line's note starts with, to tell it from the writer's
+/// own ideas.
+pub const SUGGESTED: &str = "suggested";
+
+/// How the model is asked to answer.
+pub const ANSWER: &str = "Answer with JSON only: \
+    {\"suggestions\": [{\"line\": <line number>, \"text\": \"<the whole line>\"}]}. \
+    Give the whole line, with every blank filled in. \
+    Suggest as many alternatives per line as you like.";
+
+#[derive(Debug, Error)]
+pub enum CowriteError {
+    #[error("not a JSON reply: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("the reply has no suggestions")]
+    NoSuggestions,
+}
+
+/// One line of the song as the model sees it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackLine {
+    /// 1-based source line.
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhyme: Option<char>,
+    pub syllables: usize,
+    /// Whether the line still has blanks to fill in.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub open: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackSection {
+    pub label: String,
+    /// For a `REPEAT`, the section it repeats; it has no lines of its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeats: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<PackLine>,
+}
+
+/// A few figures of how the song is written so far.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Style {
+    pub lines: usize,
+    pub syllables_per_line: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meter: Option<String>,
+    /// Rhyme letters of each section's lines, `-` for a line without one.
+    pub rhyme_schemes: Vec<(String, String)>,
+    /// The longer words the song comes back to, most frequent first.
+    pub recurring_words: Vec<String>,
+}
+
+/// Everything a model needs to write a song's missing lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pack {
+    pub title: String,
+    pub language: String,
+    pub structure: Vec<PackSection>,
+    pub style: Style,
+    /// The constraints on each line still to be written.
+    pub open: Vec<Constraints>,
+    pub answer: &'static str,
+}
+
+/// The pack for the resolved `song`, parsed from `source`. Words in
+/// `banned` may not be used in the lines written for it.
+pub fn pack(song: &Song, source: &str, banned: &[String]) -> Pack {
+    let language = language::of(song);
+    let mut structure = Vec::new();
+    let mut open = Vec::new();
+    for section in &song.sections {
+        if let Some(reference) = &section.repeat {
+            structure.push(PackSection {
+                label: section.label(),
+                repeats: Some(reference.target.to_string()),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let lines: Vec<PackLine> = section
+            .lines
+            .iter()
+            .filter(|l| l.include.is_none())
+            .map(|l| PackLine {
+                line: l.span.line_col(source).0,
+                id: l.id.clone(),
+                text: l.text.clone(),
+                rhyme: l.rhyme,
+                syllables: count_line_in(l, language),
+                open: !l.placeholders.is_empty(),
+            })
+            .collect();
+        for line in lines.iter().filter(|l| l.open) {
+            if let Ok(found) = constraints(song, source, line.line, banned) {
+                open.push(found);
+            }
+        }
+        structure.push(PackSection {
+            label: section.label(),
+            repeats: None,
+            lines,
+        });
+    }
+
+    let written: Vec<&PackLine> = structure
+        .iter()
+        .flat_map(|s| &s.lines)
+        .filter(|l| !l.open)
+        .collect();
+    let syllables = written.iter().map(|l| l.syllables).sum::<usize>() as f64;
+    let mut vocabulary = Vocabulary::new();
+    vocabulary.add("song", song);
+    let style = Style {
+        lines: structure.iter().map(|s| s.lines.len()).sum(),
+        syllables_per_line: match written.len() {
+            0 => 0.0,
+            n => (syllables / n as f64 * 10.0).round() / 10.0,
+        },
+        meter: song.meta_str("meter"),
+        rhyme_schemes: structure
+            .iter()
+            .filter(|s| s.lines.iter().any(|l| l.rhyme.is_some()))
+            .map(|s| {
+                let scheme = s.lines.iter().map(|l| l.rhyme.unwrap_or('-')).collect();
+                (s.label.clone(), scheme)
+            })
+            .collect(),
+        recurring_words: vocabulary
+            .words()
+            .into_iter()
+            .filter(|w| w.total > 1 && w.word.chars().count() > 3)
+            .take(12)
+            .map(|w| w.word.clone())
+            .collect(),
+    };
+
+    Pack {
+        title: song.title().unwrap_or_default(),
+        language: language.code().to_string(),
+        structure,
+        style,
+        open,
+        answer: ANSWER,
+    }
+}
+
+impl Pack {
+    /// The pack as a Markdown document to paste into a chat.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Co-writing \"{}\"\n", self.title);
+        let _ = writeln!(
+            out,
+            "Help finish this song, written in `{}`. Each line is numbered; \
+             `____` marks words still to be written.\n",
+            self.language
+        );
+        out.push_str("## The song so far\n\n```text\n");
+        for (i, section) in self.structure.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            match &section.repeats {
+                Some(target) => {
+                    let _ = writeln!(out, "[{}: repeats {}]", section.label, target);
+                }
+                None => {
+                    let _ = writeln!(out, "[{}]", section.label);
+                    for line in &section.lines {
+                        let rhyme = line.rhyme.map(|r| format!("  ({})", r));
+                        let _ = writeln!(
+                            out,
+                            "{:>4}  {}{}",
+                            line.line,
+                            line.text,
+                            rhyme.unwrap_or_default()
+                        );
+                    }
+                }
+            }
+        }
+        out.push_str("```\n\n## Style\n\n");
+        let style = &self.style;
+        let _ = writeln!(
+            out,
+            "- {} lines, {} syllables a line on average",
+            style.lines, style.syllables_per_line
+        );
+        if let Some(meter) = &style.meter {
+            let _ = writeln!(out, "- meter: {}", meter);
+        }
+        for (label, scheme) in &style.rhyme_schemes {
+            let _ = writeln!(out, "- rhyme scheme of {}: {}", label, scheme);
+        }
+        if !style.recurring_words.is_empty() {
+            let _ = writeln!(
+                out,
+                "- recurring words: {}",
+                style.recurring_words.join(", ")
+            );
+        }
+        out.push_str("\n## Lines to write\n");
+        for line in &self.open {
+            let _ = writeln!(out, "\n### Line {} ({})\n", line.line, line.section);
+            let _ = writeln!(out, "- now: `{}`", line.current);
+            for hint in &line.hints {
+                let _ = writeln!(out, "- the writer's note: {}", hint);
+            }
+            if let Some(syllables) = &line.syllables {
+                let _ = writeln!(
+                    out,
+                    "- {} syllables ({} to {})",
+                    syllables.target, syllables.min, syllables.max
+                );
+            }
+            if let Some(rhyme) = &line.rhyme {
+                let _ = writeln!(
+                    out,
+                    "- rhymes with {} (ending in -{})",
+                    rhyme.with.join(", "),
+                    rhyme.ending
+                );
+            }
+            if let Some(stress) = &line.stress {
+                let _ = writeln!(
+                    out,
+                    "- stress pattern {} (x unstressed, / stressed)",
+                    stress.pattern
+                );
+            }
+            if !line.banned.is_empty() {
+                let _ = writeln!(out, "- do not use: {}", line.banned.join(", "));
+            }
+        }
+        let _ = write!(out, "\n## How to answer\n\n{}\n", self.answer);
+        out
+    }
+}
+
+/// One line a model suggests, by source line or line ID.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Suggestion {
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub id: Option<String>,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    suggestions: Vec<Suggestion>,
+}
+
+/// The suggestions in a model's reply, which may wrap its JSON in prose or
+/// a code fence.
+pub fn suggestions(reply: &str) -> Result<Vec<Suggestion>, CowriteError> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply,
+    };
+    let reply: Reply = serde_json::from_str(json)?;
+    match reply.suggestions.is_empty() {
+        true => Err(CowriteError::NoSuggestions),
+        false => Ok(reply.suggestions),
+    }
+}
+
+/// What merging a reply did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Merged {
+    /// Suggestions added as notes.
+    pub added: usize,
+    /// Of those, the ones that miss a constraint.
+    pub missing: usize,
+    /// Lines and IDs named in the reply that are not lines of the song.
+    pub unknown: Vec<String>,
+}
+
+/// Add each suggestion to the line it is for in `song`, parsed from
+/// `source`, as an `!! idea: suggested: ...` note; one that misses a
+/// constraint says which. Suggestions already on their line are skipped.
+pub fn merge(
+    song: &mut Song,
+    source: &str,
+    suggestions: &[Suggestion],
+    banned: &[String],
+) -> Merged {
+    let resolved = resolve(song).song;
+    let mut merged = Merged::default();
+    for suggestion in suggestions {
+        let text = suggestion
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace("!!", "!");
+        let Some(line) = find(song, source, suggestion) else {
+            let name = match (&suggestion.id, suggestion.line) {
+                (Some(id), _) => id.clone(),
+                (None, Some(n)) => format!("line {}", n),
+                (None, None) => "a suggestion with no line".to_string(),
+            };
+            merged.unknown.push(name);
+            continue;
+        };
+        let number = line.span.line_col(source).0;
+        let missed: Vec<&str> = match constraints(&resolved, source, number, banned) {
+            Ok(found) => found
+                .check(&text)
+                .checks
+                .into_iter()
+                .filter(|c| !c.ok)
+                .map(|c| c.constraint)
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let note = match missed.is_empty() {
+            true => format!("{}: {}", SUGGESTED, text),
+            false => format!("{} (misses {}): {}", SUGGESTED, missed.join(", "), text),
+        };
+        if line.notes.iter().any(|n| n.text == note) {
+            continue;
+        }
+        line.notes.push(Note {
+            kind: NoteKind::Idea,
+            text: note,
+            span: Default::default(),
+        });
+        merged.added += 1;
+        merged.missing += !missed.is_empty() as usize;
+    }
+    merged
+}
+
+/// The lead line `suggestion` is for, in a section or a macro.
+fn find<'a>(song: &'a mut Song, source: &str, suggestion: &Suggestion) -> Option<&'a mut Line> {
+    let macros = song.macros.iter_mut().flat_map(|m| m.lines.iter_mut());
+    let sections = song.sections.iter_mut().flat_map(|s| s.lines.iter_mut());
+    macros.chain(sections).find(|line| {
+        line.include.is_none()
+            && match (&suggestion.id, suggestion.line) {
+                (Some(id), _) => line.id.as_ref() == Some(id),
+                (None, Some(n)) => line.span.line_col(source).0 == n,
+                (None, None) => false,
+            }
+    })
+}
@@ -0,0 +1,114 @@
+//! Interpolation and sample credits: the works a song borrows from, as
+//! declared in its `credits:` block.
+//!
+//! ```text
+//! credits:
+//!   interpolation "Stand By Me" {writers: "Ben E. King, Jerry Leiber, Mike Stoller", lines: *hook}
+//!   sample "Amen, Brother" {artist: "The Winstons", lines: *drop..*outro}
+//! ```
+//!
+//! Each credit points at the lines that use the work through anchors, so
+//! the credit follows the lines wherever they are sung: [`credited_lines`]
+//! numbers them through the resolved song, repeats included, the way the
+//! review copy numbers them.
+
+use crate::ast::{Credit, CreditKind, RefTarget, Reference, Song, Span};
+use std::collections::HashSet;
+
+/// The liner-note line for `credit`, such as `Contains an interpolation of
+/// "Stand By Me", written by Ben E. King (1961)`.
+pub fn citation(credit: &Credit) -> String {
+    let mut out = match credit.kind {
+        CreditKind::Interpolation => format!("Contains an interpolation of \"{}\"", credit.work),
+        CreditKind::Sample => format!("Contains a sample of \"{}\"", credit.work),
+    };
+    if let Some(artist) = &credit.artist {
+        out.push_str(&format!(" as performed by {}", artist));
+    }
+    if let Some(writers) = &credit.writers {
+        out.push_str(&format!(", written by {}", writers));
+    }
+    if let Some(publishers) = &credit.publishers {
+        out.push_str(&format!(", published by {}", publishers));
+    }
+    if let Some(year) = &credit.year {
+        out.push_str(&format!(" ({})", year));
+    }
+    out
+}
+
+/// The numbers of the lines of the resolved `song` that use `credit`'s
+/// work, counted from 1 through every section. Lines that come back in a
+/// `REPEAT` or through `USE` are numbered wherever they are sung. Empty
+/// when the credit names no lines.
+pub fn credited_lines(song: &Song, credit: &Credit) -> Vec<usize> {
+    let lines: Vec<_> = song.lines().collect();
+    let anchor = |reference: &Option<Reference>| match reference {
+        Some(Reference {
+            target: RefTarget::Anchor(name),
+            ..
+        }) => Some(name.clone()),
+        _ => None,
+    };
+    let Some(from) = anchor(&credit.from) else {
+        return Vec::new();
+    };
+    let to = anchor(&credit.to).unwrap_or_else(|| from.clone());
+    let on_line = |i: usize, name: &str| lines[i].1.anchor.as_ref().is_some_and(|a| a.name == name);
+    let in_section =
+        |i: usize, name: &str| lines[i].0.anchor.as_ref().is_some_and(|a| a.name == name);
+    let labelled = |i: usize, name: &str| on_line(i, name) || in_section(i, name);
+    let Some(start) = (0..lines.len()).find(|&i| labelled(i, &from)) else {
+        return Vec::new();
+    };
+    let mut end = (start..lines.len())
+        .find(|&i| labelled(i, &to))
+        .unwrap_or(start);
+    // A section anchor at the end of the range takes in the whole section.
+    if !on_line(end, &to) {
+        while end + 1 < lines.len() && in_section(end + 1, &to) {
+            end += 1;
+        }
+    }
+    let spans: HashSet<Span> = lines[start..=end].iter().map(|(_, l)| l.span).collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, line))| spans.contains(&line.span))
+        .map(|(i, _)| i + 1)
+        .collect()
+}
+
+/// `numbers` in ascending order as ranges, such as `5–8, 21–24`.
+pub fn ranges(numbers: &[usize]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < numbers.len() {
+        let mut j = i;
+        while j + 1 < numbers.len() && numbers[j + 1] == numbers[j] + 1 {
+            j += 1;
+        }
+        out.push(match i == j {
+            true => numbers[i].to_string(),
+            false => format!("{}–{}", numbers[i], numbers[j]),
+        });
+        i = j + 1;
+    }
+    out.join(", ")
+}
+
+/// The citation of every credit of `song` with the lines it covers, such as
+/// `Contains a sample of "Amen, Brother" (lines 1–4)`.
+pub fn citations(song: &Song) -> Vec<String> {
+    song.credits
+        .iter()
+        .map(|credit| {
+            let lines = credited_lines(song, credit);
+            match lines.len() {
+                0 => citation(credit),
+                1 => format!("{} (line {})", citation(credit), lines[0]),
+                _ => format!("{} (lines {})", citation(credit), ranges(&lines)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,179 @@
+//! The ChaCha20-Poly1305 AEAD construction of RFC 8439.
+
+/// Encrypt `plaintext` and append the 16-byte tag, which also covers `aad`.
+pub fn seal(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    chacha20(key, 1, nonce, &mut out);
+    let tag = tag(key, nonce, aad, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// Check the tag of `sealed` and decrypt it, or `None` when the key is
+/// wrong or the data or `aad` were altered.
+pub fn open(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let split = sealed.len().checked_sub(16)?;
+    let (ciphertext, expected) = sealed.split_at(split);
+    let actual = tag(key, nonce, aad, ciphertext);
+    // Compare without an early exit, so timing says nothing about the tag.
+    let difference = actual
+        .iter()
+        .zip(expected)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return None;
+    }
+    let mut out = ciphertext.to_vec();
+    chacha20(key, 1, nonce, &mut out);
+    Some(out)
+}
+
+fn tag(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let block = block(key, 0, nonce);
+    let mut one_time = [0u8; 32];
+    one_time.copy_from_slice(&block[..32]);
+
+    let mut data = aad.to_vec();
+    data.resize(aad.len().div_ceil(16) * 16, 0);
+    data.extend_from_slice(ciphertext);
+    data.resize(data.len().div_ceil(16) * 16, 0);
+    data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(&one_time, &data)
+}
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 {
+        state[4 + i] = le32(&key[4 * i..]);
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = le32(&nonce[4 * i..]);
+    }
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+    let mut out = [0u8; 64];
+    for (i, chunk) in out.chunks_mut(4).enumerate() {
+        chunk.copy_from_slice(&working[i].wrapping_add(state[i]).to_le_bytes());
+    }
+    out
+}
+
+fn chacha20(key: &[u8; 32], counter: u32, nonce: &[u8; 12], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let stream = block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, k) in chunk.iter_mut().zip(stream) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// Poly1305 over 26-bit limbs, after poly1305-donna.
+fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; 16] {
+    const MASK: u32 = 0x3ff_ffff;
+    let r = [
+        le32(&key[0..]) & 0x3ff_ffff,
+        (le32(&key[3..]) >> 2) & 0x3ff_ff03,
+        (le32(&key[6..]) >> 4) & 0x3ff_c0ff,
+        (le32(&key[9..]) >> 6) & 0x3f0_3fff,
+        (le32(&key[12..]) >> 8) & 0x00f_ffff,
+    ];
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u32; 5];
+
+    for chunk in message.chunks(16) {
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        h[0] += le32(&block[0..]) & MASK;
+        h[1] += (le32(&block[3..]) >> 2) & MASK;
+        h[2] += (le32(&block[6..]) >> 4) & MASK;
+        h[3] += (le32(&block[9..]) >> 6) & MASK;
+        h[4] += (le32(&block[12..]) >> 8) | ((block[16] as u32) << 24);
+
+        let m = |a: u32, b: u32| a as u64 * b as u64;
+        let d = [
+            m(h[0], r[0]) + m(h[1], s[3]) + m(h[2], s[2]) + m(h[3], s[1]) + m(h[4], s[0]),
+            m(h[0], r[1]) + m(h[1], r[0]) + m(h[2], s[3]) + m(h[3], s[2]) + m(h[4], s[1]),
+            m(h[0], r[2]) + m(h[1], r[1]) + m(h[2], r[0]) + m(h[3], s[3]) + m(h[4], s[2]),
+            m(h[0], r[3]) + m(h[1], r[2]) + m(h[2], r[1]) + m(h[3], r[0]) + m(h[4], s[3]),
+            m(h[0], r[4]) + m(h[1], r[3]) + m(h[2], r[2]) + m(h[3], r[1]) + m(h[4], r[0]),
+        ];
+        let mut carry = 0u64;
+        for i in 0..5 {
+            let v = d[i] + carry;
+            h[i] = v as u32 & MASK;
+            carry = v >> 26;
+        }
+        let v = h[0] as u64 + carry * 5;
+        h[0] = v as u32 & MASK;
+        h[1] += (v >> 26) as u32;
+    }
+
+    // Fully carry h, then reduce it modulo 2^130 - 5.
+    let mut carry = 0;
+    for limb in h.iter_mut().skip(1) {
+        *limb += carry;
+        carry = *limb >> 26;
+        *limb &= MASK;
+    }
+    h[0] += carry * 5;
+    carry = h[0] >> 26;
+    h[0] &= MASK;
+    h[1] += carry;
+
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for i in 0..5 {
+        g[i] = h[i].wrapping_add(carry);
+        carry = g[i] >> 26;
+        g[i] &= MASK;
+    }
+    // h + 5 - 2^130 is non-negative exactly when h >= 2^130 - 5.
+    let select_g = carry.wrapping_neg();
+    for i in 0..5 {
+        h[i] = (h[i] & !select_g) | (g[i] & select_g);
+    }
+
+    let words = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut out = [0u8; 16];
+    let mut carry = 0u64;
+    for i in 0..4 {
+        let v = words[i] as u64 + le32(&key[16 + 4 * i..]) as u64 + carry;
+        out[4 * i..4 * i + 4].copy_from_slice(&(v as u32).to_le_bytes());
+        carry = v >> 32;
+    }
+    out
+}
@@ -0,0 +1,429 @@
+//! Ed25519 signatures (RFC 8032), for signing delivered exports.
+//!
+//! Field elements are five 51-bit limbs; points are extended twisted
+//! Edwards coordinates. Scalar multiplication walks every bit and selects
+//! the sum without branching, and scalars are reduced modulo the group
+//! order without branching either, so signing takes the same time for any
+//! key.
+
+use super::sha512::sha512;
+
+#[derive(Clone, Copy)]
+struct Fe([u64; 5]);
+
+const MASK51: u64 = (1 << 51) - 1;
+
+impl Fe {
+    const ZERO: Fe = Fe([0; 5]);
+    const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+
+    fn from_u64(v: u64) -> Fe {
+        Fe([v & MASK51, v >> 51, 0, 0, 0])
+    }
+
+    /// The element encoded in `bytes`, ignoring the top bit.
+    fn from_bytes(bytes: &[u8; 32]) -> Fe {
+        let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().expect("8 bytes"));
+        Fe([
+            load(0) & MASK51,
+            (load(6) >> 3) & MASK51,
+            (load(12) >> 6) & MASK51,
+            (load(19) >> 1) & MASK51,
+            (load(24) >> 12) & MASK51,
+        ])
+    }
+
+    /// The canonical encoding, fully reduced modulo 2^255 - 19.
+    fn to_bytes(self) -> [u8; 32] {
+        let mut h = self.0;
+        for _ in 0..2 {
+            for i in 0..4 {
+                h[i + 1] += h[i] >> 51;
+                h[i] &= MASK51;
+            }
+            h[0] += (h[4] >> 51) * 19;
+            h[4] &= MASK51;
+        }
+        // q is 1 exactly when h >= p; adding 19q and dropping bit 255
+        // subtracts p.
+        let mut q = (h[0] + 19) >> 51;
+        for limb in &h[1..] {
+            q = (limb + q) >> 51;
+        }
+        h[0] += 19 * q;
+        for i in 0..4 {
+            h[i + 1] += h[i] >> 51;
+            h[i] &= MASK51;
+        }
+        h[4] &= MASK51;
+
+        let mut out = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut at = 0;
+        for limb in h {
+            acc |= (limb as u128) << bits;
+            bits += 51;
+            while bits >= 8 && at < 32 {
+                out[at] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                at += 1;
+            }
+        }
+        if at < 32 {
+            out[at] = acc as u8;
+        }
+        out
+    }
+
+    fn add(self, other: Fe) -> Fe {
+        let mut h = [0u128; 5];
+        for (i, limb) in h.iter_mut().enumerate() {
+            *limb = (self.0[i] + other.0[i]) as u128;
+        }
+        carry(h)
+    }
+
+    fn sub(self, other: Fe) -> Fe {
+        // Add 2p first so no limb goes negative.
+        const TWO_P: [u64; 5] = [
+            0xf_ffff_ffff_ffda,
+            0xf_ffff_ffff_fffe,
+            0xf_ffff_ffff_fffe,
+            0xf_ffff_ffff_fffe,
+            0xf_ffff_ffff_fffe,
+        ];
+        let mut h = [0u128; 5];
+        for (i, limb) in h.iter_mut().enumerate() {
+            *limb = (self.0[i] + TWO_P[i] - other.0[i]) as u128;
+        }
+        carry(h)
+    }
+
+    fn neg(self) -> Fe {
+        Fe::ZERO.sub(self)
+    }
+
+    fn mul(self, other: Fe) -> Fe {
+        let a = self.0.map(|l| l as u128);
+        let b = other.0.map(|l| l as u128);
+        let b19 = b.map(|l| l * 19);
+        carry([
+            a[0] * b[0] + a[1] * b19[4] + a[2] * b19[3] + a[3] * b19[2] + a[4] * b19[1],
+            a[0] * b[1] + a[1] * b[0] + a[2] * b19[4] + a[3] * b19[3] + a[4] * b19[2],
+            a[0] * b[2] + a[1] * b[1] + a[2] * b[0] + a[3] * b19[4] + a[4] * b19[3],
+            a[0] * b[3] + a[1] * b[2] + a[2] * b[1] + a[3] * b[0] + a[4] * b19[4],
+            a[0] * b[4] + a[1] * b[3] + a[2] * b[2] + a[3] * b[1] + a[4] * b[0],
+        ])
+    }
+
+    fn square(self) -> Fe {
+        self.mul(self)
+    }
+
+    /// `self` raised to the little-endian exponent `e`.
+    fn pow(self, e: &[u8; 32]) -> Fe {
+        let mut out = Fe::ONE;
+        for i in (0..256).rev() {
+            out = out.square();
+            if (e[i / 8] >> (i % 8)) & 1 == 1 {
+                out = out.mul(self);
+            }
+        }
+        out
+    }
+
+    fn invert(self) -> Fe {
+        // p - 2
+        self.pow(&exponent(0xeb, 0x7f))
+    }
+
+    fn is_negative(self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    fn equals(self, other: Fe) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+
+    /// `a` where `choice` is 0, `b` where it is 1, without branching.
+    fn select(a: Fe, b: Fe, choice: u64) -> Fe {
+        let mask = choice.wrapping_neg();
+        let mut out = a.0;
+        for (o, b) in out.iter_mut().zip(b.0) {
+            *o ^= (*o ^ b) & mask;
+        }
+        Fe(out)
+    }
+}
+
+/// A 256-bit little-endian exponent of the form `low, 0xff.., high`.
+fn exponent(low: u8, high: u8) -> [u8; 32] {
+    let mut e = [0xff; 32];
+    e[0] = low;
+    e[31] = high;
+    e
+}
+
+fn carry(mut h: [u128; 5]) -> Fe {
+    for i in 0..4 {
+        h[i + 1] += h[i] >> 51;
+        h[i] &= MASK51 as u128;
+    }
+    h[0] += (h[4] >> 51) * 19;
+    h[4] &= MASK51 as u128;
+    h[1] += h[0] >> 51;
+    h[0] &= MASK51 as u128;
+    Fe(h.map(|l| l as u64))
+}
+
+/// -121665 / 121666, the curve constant.
+fn d() -> Fe {
+    Fe::from_u64(121665)
+        .neg()
+        .mul(Fe::from_u64(121666).invert())
+}
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl Point {
+    const IDENTITY: Point = Point {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+        t: Fe::ZERO,
+    };
+
+    fn base() -> Point {
+        let mut encoded = [0x66; 32];
+        encoded[0] = 0x58;
+        Point::decode(&encoded).expect("the base point decodes")
+    }
+
+    /// The unified addition of RFC 8032 section 5.1.4, which also doubles.
+    fn add(self, other: Point) -> Point {
+        let d2 = d().add(d());
+        let a = self.y.sub(self.x).mul(other.y.sub(other.x));
+        let b = self.y.add(self.x).mul(other.y.add(other.x));
+        let c = self.t.mul(d2).mul(other.t);
+        let d = self.z.add(self.z).mul(other.z);
+        let (e, f, g, h) = (b.sub(a), d.sub(c), d.add(c), b.add(a));
+        Point {
+            x: e.mul(f),
+            y: g.mul(h),
+            z: f.mul(g),
+            t: e.mul(h),
+        }
+    }
+
+    /// `scalar` (little-endian) times `self`.
+    fn times(self, scalar: &[u8; 32]) -> Point {
+        let mut out = Point::IDENTITY;
+        for i in (0..256).rev() {
+            out = out.add(out);
+            let sum = out.add(self);
+            let bit = ((scalar[i / 8] >> (i % 8)) & 1) as u64;
+            out = Point {
+                x: Fe::select(out.x, sum.x, bit),
+                y: Fe::select(out.y, sum.y, bit),
+                z: Fe::select(out.z, sum.z, bit),
+                t: Fe::select(out.t, sum.t, bit),
+            };
+        }
+        out
+    }
+
+    fn encode(self) -> [u8; 32] {
+        let inverse = self.z.invert();
+        let x = self.x.mul(inverse);
+        let mut out = self.y.mul(inverse).to_bytes();
+        out[31] |= (x.is_negative() as u8) << 7;
+        out
+    }
+
+    /// The point encoded in `bytes`, if it is on the curve.
+    fn decode(bytes: &[u8; 32]) -> Option<Point> {
+        let y = Fe::from_bytes(bytes);
+        let mut canonical = *bytes;
+        canonical[31] &= 0x7f;
+        if y.to_bytes() != canonical {
+            return None;
+        }
+        let sign = bytes[31] >> 7 == 1;
+
+        // x = u v^3 (u v^7)^((p-5)/8) with u = y^2 - 1, v = d y^2 + 1.
+        let yy = y.square();
+        let u = yy.sub(Fe::ONE);
+        let v = d().mul(yy).add(Fe::ONE);
+        let v3 = v.square().mul(v);
+        let v7 = v3.square().mul(v);
+        let mut x = u.mul(v3).mul(u.mul(v7).pow(&exponent(0xfd, 0x0f)));
+        let check = v.mul(x.square());
+        if check.equals(u.neg()) {
+            // sqrt(-1) = 2^((p-1)/4)
+            x = x.mul(Fe::from_u64(2).pow(&exponent(0xfb, 0x1f)));
+        } else if !check.equals(u) {
+            return None;
+        }
+        if x.equals(Fe::ZERO) && sign {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = x.neg();
+        }
+        Some(Point {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x.mul(y),
+        })
+    }
+}
+
+/// The group order L = 2^252 + 27742317777372353535851937790883648493,
+/// as little-endian 64-bit words.
+const L: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0x0000000000000000,
+    0x1000000000000000,
+];
+
+/// A little-endian number of any length reduced modulo L, by long
+/// division one bit at a time. Only used a few times per signature.
+///
+/// The secret scalar and nonce pass through here, so L is subtracted every
+/// step and the difference kept by mask when it did not borrow, rather
+/// than after a comparison.
+fn reduce(words: &[u64]) -> [u8; 32] {
+    let mut r = [0u64; 4];
+    for i in (0..words.len() * 64).rev() {
+        let bit = (words[i / 64] >> (i % 64)) & 1;
+        // r < L < 2^253, so shifting left cannot overflow four words.
+        for j in (1..4).rev() {
+            r[j] = (r[j] << 1) | (r[j - 1] >> 63);
+        }
+        r[0] = (r[0] << 1) | bit;
+        let mut difference = [0u64; 4];
+        let mut borrow = 0u64;
+        for j in 0..4 {
+            let (v, b1) = r[j].overflowing_sub(L[j]);
+            let (v, b2) = v.overflowing_sub(borrow);
+            difference[j] = v;
+            borrow = (b1 | b2) as u64;
+        }
+        // All ones when r >= L, so the difference is kept.
+        let keep = borrow.wrapping_sub(1);
+        for j in 0..4 {
+            r[j] = (difference[j] & keep) | (r[j] & !keep);
+        }
+    }
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_mut(8).zip(r) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+fn words(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|c| u64::from_le_bytes(c.try_into().expect("8 bytes")))
+        .collect()
+}
+
+/// (a * b + c) mod L for 32-byte little-endian scalars.
+fn mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let (a, b, c) = (words(a), words(b), words(c));
+    let mut product = [0u64; 9];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let v = product[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+            product[i + j] = v as u64;
+            carry = v >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    let mut carry = 0u128;
+    for (i, limb) in product.iter_mut().enumerate() {
+        let v = *limb as u128 + c.get(i).copied().unwrap_or(0) as u128 + carry;
+        *limb = v as u64;
+        carry = v >> 64;
+    }
+    reduce(&product)
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> [u8; 32] {
+    reduce(&words(&sha512(&parts.concat())))
+}
+
+/// The expanded secret: the clamped scalar and the nonce prefix.
+fn expand(secret: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let h = sha512(secret);
+    let mut scalar: [u8; 32] = h[..32].try_into().expect("32 bytes");
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    (scalar, h[32..].try_into().expect("32 bytes"))
+}
+
+/// The public key for the 32-byte `secret` seed.
+pub fn public_key(secret: &[u8; 32]) -> [u8; 32] {
+    let (scalar, _) = expand(secret);
+    Point::base().times(&scalar).encode()
+}
+
+/// The signature of `message` under `secret`.
+pub fn sign(secret: &[u8; 32], message: &[u8]) -> [u8; 64] {
+    let (scalar, prefix) = expand(secret);
+    let public = Point::base().times(&scalar).encode();
+    let r = hash_to_scalar(&[&prefix, message]);
+    let big_r = Point::base().times(&r).encode();
+    let k = hash_to_scalar(&[&big_r, &public, message]);
+    let s = mul_add(&k, &scalar, &r);
+    let mut out = [0u8; 64];
+    out[..32].copy_from_slice(&big_r);
+    out[32..].copy_from_slice(&s);
+    out
+}
+
+/// Whether `signature` is `public`'s signature of `message`.
+pub fn verify(public: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let big_r: [u8; 32] = signature[..32].try_into().expect("32 bytes");
+    let s: [u8; 32] = signature[32..].try_into().expect("32 bytes");
+    let s_words: [u64; 4] = words(&s).try_into().expect("4 words");
+    if !less_than(&s_words, &L) {
+        return false;
+    }
+    let (Some(a), Some(_)) = (Point::decode(public), Point::decode(&big_r)) else {
+        return false;
+    };
+    let k = hash_to_scalar(&[&big_r, public, message]);
+    // [S]B = R + [k]A, compared by encoding R' = [S]B - [k]A.
+    let minus_ka = {
+        let ka = a.times(&k);
+        Point {
+            x: ka.x.neg(),
+            t: ka.t.neg(),
+            ..ka
+        }
+    };
+    Point::base().times(&s).add(minus_ka).encode() == big_r
+}
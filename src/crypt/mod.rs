@@ -0,0 +1,175 @@
+//! Encryption at rest for unreleased songs.
+//!
+//! A locked file starts with the line `lyrics-dsl locked v1`, followed by
+//! a binary header and the song encrypted with ChaCha20-Poly1305. The key
+//! comes either from a passphrase, stretched with PBKDF2-HMAC-SHA256 over
+//! a random salt, or from the contents of a key file. The header is
+//! authenticated along with the song, so tampering with either is detected
+//! as a wrong key.
+//!
+//! ```text
+//! "lyrics-dsl locked v1\n"  kind (1)  iterations (4, BE)  salt (16)
+//! nonce (12)  ciphertext  tag (16)
+//! ```
+
+mod chacha20poly1305;
+pub mod ed25519;
+pub mod sha256;
+mod sha512;
+
+pub use chacha20poly1305::{open, seal};
+use thiserror::Error;
+
+const MAGIC: &[u8] = b"lyrics-dsl locked v1\n";
+const HEADER: usize = MAGIC.len() + 1 + 4 + 16 + 12;
+
+/// PBKDF2 rounds for passphrases: slow enough to make guessing costly,
+/// quick enough to unlock a song without noticing.
+pub const ITERATIONS: u32 = 600_000;
+
+/// The most rounds [`unlock`] will run. The count is read from the header
+/// before anything is authenticated, so a tampered file could otherwise make
+/// unlocking take hours.
+pub const MAX_ITERATIONS: u32 = 10 * ITERATIONS;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CryptError {
+    #[error("the file is not locked")]
+    NotLocked,
+    #[error("the locked file is damaged or truncated")]
+    Damaged,
+    #[error("the file was locked with a {0}")]
+    WrongKind(KeyKind),
+    #[error("wrong passphrase or key file, or the file was altered")]
+    WrongKey,
+    #[error("no random numbers available: {0}")]
+    Random(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Passphrase,
+    Keyfile,
+}
+
+impl std::fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            KeyKind::Passphrase => "passphrase",
+            KeyKind::Keyfile => "key file",
+        })
+    }
+}
+
+/// What a file is locked with.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Key {
+    Passphrase(String),
+    /// The contents of a key file.
+    Keyfile(Vec<u8>),
+}
+
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Key({})", self.kind())
+    }
+}
+
+impl Key {
+    pub fn kind(&self) -> KeyKind {
+        match self {
+            Key::Passphrase(_) => KeyKind::Passphrase,
+            Key::Keyfile(_) => KeyKind::Keyfile,
+        }
+    }
+
+    fn derive(&self, salt: &[u8], iterations: u32) -> [u8; 32] {
+        match self {
+            Key::Passphrase(passphrase) => {
+                let mut key = [0u8; 32];
+                sha256::pbkdf2(passphrase.as_bytes(), salt, iterations, &mut key);
+                key
+            }
+            Key::Keyfile(contents) => sha256::hmac(contents, salt),
+        }
+    }
+}
+
+/// Whether `data` is a locked file.
+pub fn is_locked(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// What the locked file `data` needs to be unlocked.
+pub fn locked_with(data: &[u8]) -> Option<KeyKind> {
+    match data.get(MAGIC.len()).filter(|_| is_locked(data))? {
+        0 => Some(KeyKind::Passphrase),
+        1 => Some(KeyKind::Keyfile),
+        _ => None,
+    }
+}
+
+/// Encrypt `plaintext` under `key`, with [`ITERATIONS`] rounds for a
+/// passphrase.
+pub fn lock(plaintext: &[u8], key: &Key) -> Result<Vec<u8>, CryptError> {
+    lock_with_iterations(plaintext, key, ITERATIONS)
+}
+
+/// [`lock`] with a chosen number of PBKDF2 rounds; [`unlock`] refuses files
+/// with none, or with more than [`MAX_ITERATIONS`].
+pub fn lock_with_iterations(
+    plaintext: &[u8],
+    key: &Key,
+    iterations: u32,
+) -> Result<Vec<u8>, CryptError> {
+    let mut random = [0u8; 28];
+    getrandom::fill(&mut random).map_err(|e| CryptError::Random(e.to_string()))?;
+    let (salt, nonce) = random.split_at(16);
+
+    let mut out = MAGIC.to_vec();
+    out.push(match key.kind() {
+        KeyKind::Passphrase => 0,
+        KeyKind::Keyfile => 1,
+    });
+    out.extend_from_slice(&iterations.to_be_bytes());
+    out.extend_from_slice(salt);
+    out.extend_from_slice(nonce);
+    let derived = key.derive(salt, iterations);
+    let sealed = seal(
+        &derived,
+        nonce.try_into().expect("12 bytes"),
+        &out,
+        plaintext,
+    );
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Decrypt a file made by [`lock`].
+pub fn unlock(data: &[u8], key: &Key) -> Result<Vec<u8>, CryptError> {
+    if !is_locked(data) {
+        return Err(CryptError::NotLocked);
+    }
+    let kind = locked_with(data).ok_or(CryptError::Damaged)?;
+    if kind != key.kind() {
+        return Err(CryptError::WrongKind(kind));
+    }
+    if data.len() < HEADER + 16 {
+        return Err(CryptError::Damaged);
+    }
+    let (header, sealed) = data.split_at(HEADER);
+    let fields = &header[MAGIC.len() + 1..];
+    let iterations = u32::from_be_bytes(fields[..4].try_into().expect("4 bytes"));
+    if !(1..=MAX_ITERATIONS).contains(&iterations) {
+        return Err(CryptError::Damaged);
+    }
+    let (salt, nonce) = fields[4..].split_at(16);
+    let derived = key.derive(salt, iterations);
+    open(
+        &derived,
+        nonce.try_into().expect("12 bytes"),
+        header,
+        sealed,
+    )
+    .ok_or(CryptError::WrongKey)
+}
@@ -0,0 +1,251 @@
+//! Near-identical songs in a catalog: alternate drafts, re-uploads and
+//! copies, grouped into clusters with a suggested canonical file each.
+//!
+//! Each song is reduced to the set of its word n-grams (shingles), the
+//! same measure `similar` reports as its overall figure. Comparing every
+//! pair is too slow for a large catalog, so a MinHash signature of each set
+//! is cut into bands, and only songs sharing a band are compared exactly.
+//! Songs with identical shingles are grouped first, so an archive full of
+//! copies costs no more than one copy.
+
+use crate::search::terms;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// MinHash values per signature, as `BANDS` bands of `ROWS`. Songs at 80%
+/// Jaccard similarity share a band with near certainty; songs at 30%
+/// rarely do.
+const BANDS: usize = 16;
+const ROWS: usize = 4;
+
+/// Words in a name that mark a file as a copy of another.
+const COPY_MARKS: &[&str] = &["copy", "backup", "old", "draft", "duplicate", "bak"];
+
+/// A song as far as deduplication cares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub shingles: HashSet<u64>,
+    /// Words in the song, the measure of how complete a draft is.
+    pub words: usize,
+    /// Metadata entries, such as `title` and `artist`.
+    pub metadata: usize,
+    /// When the file was last modified, in seconds since the epoch.
+    pub modified: u64,
+}
+
+impl Entry {
+    /// An entry for the song at `path` with lyric lines `lines`.
+    pub fn new<'a>(
+        path: &str,
+        lines: impl IntoIterator<Item = &'a str>,
+        ngram: usize,
+        metadata: usize,
+        modified: u64,
+    ) -> Entry {
+        let words: Vec<String> = lines.into_iter().flat_map(terms).collect();
+        Entry {
+            path: path.to_string(),
+            shingles: shingles(&words, ngram),
+            words: words.len(),
+            metadata,
+            modified,
+        }
+    }
+}
+
+/// Hashes of the `n`-word runs of `words`; a song shorter than `n` words
+/// is one shingle.
+pub fn shingles(words: &[String], n: usize) -> HashSet<u64> {
+    let n = n.max(1).min(words.len().max(1));
+    words
+        .windows(n)
+        .map(|run| {
+            let mut hasher = DefaultHasher::new();
+            run.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// The share of shingles two sets have in common, from 0 to 1.
+pub fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let shared = a.intersection(b).count();
+    let union = a.len() + b.len() - shared;
+    match union {
+        0 => 0.0,
+        _ => shared as f64 / union as f64,
+    }
+}
+
+/// A member of a [`Cluster`] and how alike it is to the canonical file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub path: String,
+    pub similarity: f64,
+}
+
+/// Songs alike enough to be copies of one another. The first member is
+/// the suggested canonical file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub members: Vec<Member>,
+}
+
+impl Cluster {
+    pub fn canonical(&self) -> &Member {
+        &self.members[0]
+    }
+}
+
+/// Clusters of the `entries` at least `threshold` alike, largest first.
+/// Songs with no words are left out.
+pub fn clusters(entries: &[Entry], threshold: f64) -> Vec<Cluster> {
+    let mut sets = DisjointSets::new(entries.len());
+
+    // Identical songs first, then one representative of each in the bands.
+    let mut identical: HashMap<Vec<u64>, usize> = HashMap::new();
+    let mut representatives = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.shingles.is_empty() {
+            continue;
+        }
+        let mut key: Vec<u64> = entry.shingles.iter().copied().collect();
+        key.sort_unstable();
+        match identical.get(&key) {
+            Some(&first) => sets.join(first, i),
+            None => {
+                identical.insert(key, i);
+                representatives.push(i);
+            }
+        }
+    }
+
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for &i in &representatives {
+        let signature = signature(&entries[i].shingles);
+        for (band, rows) in signature.chunks(ROWS).enumerate() {
+            let mut hasher = DefaultHasher::new();
+            rows.hash(&mut hasher);
+            buckets.entry((band, hasher.finish())).or_default().push(i);
+        }
+    }
+    let mut compared = HashSet::new();
+    for bucket in buckets.values() {
+        for (k, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[k + 1..] {
+                if sets.find(a) == sets.find(b) || !compared.insert((a, b)) {
+                    continue;
+                }
+                if jaccard(&entries[a].shingles, &entries[b].shingles) >= threshold {
+                    sets.join(a, b);
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if !entry.shingles.is_empty() {
+            groups.entry(sets.find(i)).or_default().push(i);
+        }
+    }
+    let mut clusters: Vec<Cluster> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort_by(|&a, &b| rank(&entries[a], &entries[b]));
+            let canonical = &entries[group[0]];
+            let members = group
+                .iter()
+                .map(|&i| Member {
+                    path: entries[i].path.clone(),
+                    similarity: jaccard(&canonical.shingles, &entries[i].shingles),
+                })
+                .collect();
+            Cluster { members }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then_with(|| a.canonical().path.cmp(&b.canonical().path))
+    });
+    clusters
+}
+
+/// Which of two alike songs makes the better canonical file: one not
+/// named like a copy, then the more complete (more words, then more
+/// metadata), then the more recently modified, then the shorter path.
+fn rank(a: &Entry, b: &Entry) -> std::cmp::Ordering {
+    looks_like_copy(&a.path)
+        .cmp(&looks_like_copy(&b.path))
+        .then(b.words.cmp(&a.words))
+        .then(b.metadata.cmp(&a.metadata))
+        .then(b.modified.cmp(&a.modified))
+        .then(a.path.len().cmp(&b.path.len()))
+        .then(a.path.cmp(&b.path))
+}
+
+/// Whether the file name of `path` reads like a copy's: `song copy.lyr`,
+/// `song (2).lyr`, `song-old.lyr`, `song.bak.lyr`.
+pub fn looks_like_copy(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let stem = name.strip_suffix(".lyr").unwrap_or(name).to_lowercase();
+    let numbered = stem
+        .strip_suffix(')')
+        .and_then(|s| s.rsplit_once('('))
+        .is_some_and(|(_, n)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    numbered
+        || stem
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| COPY_MARKS.contains(&word))
+}
+
+/// `BANDS * ROWS` MinHash values of `shingles`.
+fn signature(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..(BANDS * ROWS) as u64)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|&s| mix(s ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// SplitMix64's finalizer, scattering similar inputs far apart.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Union-find over entry indices.
+struct DisjointSets {
+    parent: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> DisjointSets {
+        DisjointSets {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[a.max(b)] = a.min(b);
+    }
+}
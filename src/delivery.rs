@@ -0,0 +1,214 @@
+//! Manifests and signatures for exports sent to labels and publishers.
+//!
+//! A manifest lists the SHA-256 of each delivered file in the format of
+//! `sha256sum`, so it can also be checked with `sha256sum -c`. The manifest
+//! is signed with Ed25519, and the signature is kept in a separate file
+//! next to it. A recipient holding our public key can then confirm both
+//! that the manifest came from us and that every file matches it.
+//!
+//! Keys and signatures are short text files:
+//!
+//! ```text
+//! lyrics-dsl signing key       lyrics-dsl public key       lyrics-dsl signature
+//! <seed, 64 hex digits>        <key, 64 hex digits>        <public key, 64 hex digits>
+//!                                                          <signature, 128 hex digits>
+//! ```
+
+use crate::crypt::ed25519;
+use crate::crypt::sha256::sha256;
+use thiserror::Error;
+
+const SECRET_HEADER: &str = "lyrics-dsl signing key";
+const PUBLIC_HEADER: &str = "lyrics-dsl public key";
+const SIGNATURE_HEADER: &str = "lyrics-dsl signature";
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DeliveryError {
+    #[error("not a {0}")]
+    Malformed(&'static str),
+    #[error("manifest line {0} is not `<sha256>  <file>`")]
+    BadLine(usize),
+    #[error("the manifest was signed with a different key")]
+    OtherKey,
+    #[error("the signature does not match the manifest")]
+    BadSignature,
+    #[error("no random numbers available: {0}")]
+    Random(String),
+}
+
+/// One delivered file and its digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub digest: [u8; 32],
+    pub name: String,
+}
+
+/// The files of one delivery, in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<Entry>,
+}
+
+impl Manifest {
+    pub fn new() -> Manifest {
+        Manifest::default()
+    }
+
+    /// Record `name` with the digest of `contents`.
+    pub fn add(&mut self, name: &str, contents: &[u8]) {
+        self.entries.push(Entry {
+            digest: sha256(contents),
+            name: name.to_string(),
+        });
+    }
+
+    /// Read a manifest written by [`Manifest::to_text`] or `sha256sum`.
+    pub fn parse(text: &str) -> Result<Manifest, DeliveryError> {
+        let mut entries = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let bad = || DeliveryError::BadLine(i + 1);
+            let (hex, name) = line.split_once(' ').ok_or_else(bad)?;
+            // sha256sum marks binary-mode entries with `*` instead of a space.
+            let name = name.strip_prefix([' ', '*']).ok_or_else(bad)?;
+            let digest = from_hex(hex).ok_or_else(bad)?;
+            if name.is_empty() {
+                return Err(bad());
+            }
+            entries.push(Entry {
+                digest,
+                name: name.to_string(),
+            });
+        }
+        Ok(Manifest { entries })
+    }
+
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{}  {}\n", to_hex(&e.digest), e.name))
+            .collect()
+    }
+}
+
+/// Whether `contents` is what `entry` recorded.
+pub fn matches(entry: &Entry, contents: &[u8]) -> bool {
+    sha256(contents) == entry.digest
+}
+
+/// The key that signs deliveries. Keep it private.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SigningKey([u8; 32]);
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SigningKey({})", self.public().to_hex())
+    }
+}
+
+impl SigningKey {
+    pub fn generate() -> Result<SigningKey, DeliveryError> {
+        let mut seed = [0u8; 32];
+        getrandom::fill(&mut seed).map_err(|e| DeliveryError::Random(e.to_string()))?;
+        Ok(SigningKey(seed))
+    }
+
+    pub fn from_seed(seed: [u8; 32]) -> SigningKey {
+        SigningKey(seed)
+    }
+
+    pub fn parse(text: &str) -> Result<SigningKey, DeliveryError> {
+        let malformed = DeliveryError::Malformed("signing key");
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some(SECRET_HEADER) {
+            return Err(malformed);
+        }
+        lines
+            .next()
+            .and_then(from_hex)
+            .map(SigningKey)
+            .ok_or(malformed)
+    }
+
+    pub fn to_text(&self) -> String {
+        format!("{}\n{}\n", SECRET_HEADER, to_hex(&self.0))
+    }
+
+    /// The key to hand to recipients.
+    pub fn public(&self) -> PublicKey {
+        PublicKey(ed25519::public_key(&self.0))
+    }
+
+    /// A detached signature of `manifest`, as the text of a signature file.
+    pub fn sign(&self, manifest: &[u8]) -> String {
+        let signature = ed25519::sign(&self.0, manifest);
+        format!(
+            "{}\n{}\n{}\n",
+            SIGNATURE_HEADER,
+            self.public().to_hex(),
+            to_hex(&signature)
+        )
+    }
+}
+
+/// The key recipients verify deliveries with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey([u8; 32]);
+
+impl PublicKey {
+    pub fn parse(text: &str) -> Result<PublicKey, DeliveryError> {
+        let malformed = DeliveryError::Malformed("public key");
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some(PUBLIC_HEADER) {
+            return Err(malformed);
+        }
+        lines
+            .next()
+            .and_then(from_hex)
+            .map(PublicKey)
+            .ok_or(malformed)
+    }
+
+    pub fn to_text(&self) -> String {
+        format!("{}\n{}\n", PUBLIC_HEADER, self.to_hex())
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// Check the signature file `signature` against `manifest`.
+    pub fn verify(&self, manifest: &[u8], signature: &str) -> Result<(), DeliveryError> {
+        let malformed = DeliveryError::Malformed("signature");
+        let mut lines = signature.lines().map(str::trim);
+        if lines.next() != Some(SIGNATURE_HEADER) {
+            return Err(malformed);
+        }
+        let signer: [u8; 32] = lines.next().and_then(from_hex).ok_or(malformed.clone())?;
+        let signature: [u8; 64] = lines.next().and_then(from_hex).ok_or(malformed)?;
+        if signer != self.0 {
+            return Err(DeliveryError::OtherKey);
+        }
+        if !ed25519::verify(&self.0, manifest, &signature) {
+            return Err(DeliveryError::BadSignature);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn from_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != 2 * N || !hex.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(out)
+}
@@ -0,0 +1,262 @@
+//! Problems reported about a song after it has parsed successfully.
+//!
+//! A [`Diagnostic`] points at the span it is about and may carry extra
+//! labelled spans, e.g. the earlier definition a duplicate clashes with,
+//! as well as notes, a suggested fix in words and, when the fix is
+//! mechanical, the [`Fix`] itself as edits to the source. Syntax errors are
+//! turned into
+//! diagnostics too, by [`crate::parser::error_diagnostic`], so every problem
+//! is rendered the same way.
+
+use crate::ast::Span;
+use crate::codes::Code;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        })
+    }
+}
+
+/// A secondary span with an explanation, such as "first defined here".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Replace `span` of the source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// How far a [`Fix`] can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Applicability {
+    /// Certainly what the author meant, so `lint --fix` applies it unasked.
+    MachineApplicable,
+    /// A likely repair for a person to confirm, such as a suggested spelling.
+    MaybeIncorrect,
+}
+
+/// A change to the source that resolves a diagnostic. Like renames, it only
+/// touches the spans it edits, so the rest of the file is kept byte for byte.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    /// What the fix does, e.g. "replace `VESE` with `VERSE`".
+    pub description: String,
+    pub edits: Vec<Edit>,
+    pub applicability: Applicability,
+}
+
+impl Fix {
+    pub fn new(description: impl Into<String>, edits: Vec<Edit>) -> Self {
+        Fix {
+            description: description.into(),
+            edits,
+            applicability: Applicability::MaybeIncorrect,
+        }
+    }
+
+    /// A fix replacing `span` with `replacement`.
+    pub fn replace(description: impl Into<String>, span: Span, replacement: &str) -> Self {
+        Fix::new(
+            description,
+            vec![Edit {
+                span,
+                replacement: replacement.to_string(),
+            }],
+        )
+    }
+
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+
+    /// Whether any edit of `self` touches text `other` edits.
+    pub fn overlaps(&self, other: &Fix) -> bool {
+        self.edits.iter().any(|a| {
+            other.edits.iter().any(|b| {
+                a.span.start < b.span.end.max(b.span.start + 1)
+                    && b.span.start < a.span.end.max(a.span.start + 1)
+            })
+        })
+    }
+
+    /// `source` with the edits made. Edits must not overlap.
+    pub fn apply(&self, source: &str) -> String {
+        let mut edits: Vec<&Edit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| edit.span.start);
+        let mut out = source.to_string();
+        for edit in edits.iter().rev() {
+            out.replace_range(edit.span.start..edit.span.end, &edit.replacement);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// The stable code `explain` describes, such as `E006`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static Code>,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    /// Background shown below the snippet, such as what the grammar allows.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// A suggested fix, such as "did you mean `CHORUS`?".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            code: None,
+            severity,
+            message: message.into(),
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+            fix: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic::new(Severity::Warning, message, span)
+    }
+
+    pub fn with_code(mut self, code: &'static Code) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Attach a secondary span.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render as `path:line:col: severity[code]: message` followed by the source
+    /// line with the span underlined by carets, each label's line underlined
+    /// by dashes, then the notes and the help:
+    ///
+    /// ```text
+    /// song.lyr:3:1: error[E002]: unknown section kind `chorus`
+    ///   |
+    /// 3 | chorus
+    ///   | ^^^^^^
+    ///   = help: did you mean `CHORUS`?
+    /// ```
+    pub fn render(&self, path: &str, source: &str) -> String {
+        let (line, col) = self.span.line_col(source);
+        let code = self
+            .code
+            .map_or(String::new(), |code| format!("[{}]", code.id));
+        let mut out = format!(
+            "{}:{}:{}: {}{}: {}",
+            path, line, col, self.severity, code, self.message
+        );
+        let last_line = self
+            .labels
+            .iter()
+            .map(|label| label.span.line_col(source).0)
+            .fold(line, usize::max);
+        let gutter = last_line.to_string().len();
+        out.push_str(&format!("\n{:gutter$} |", ""));
+        out.push_str(&snippet(source, self.span, '^', "", gutter));
+        for label in &self.labels {
+            out.push_str(&snippet(source, label.span, '-', &label.message, gutter));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("\n{:gutter$} = note: {}", "", note));
+        }
+        if let Some(help) = &self.help {
+            out.push_str(&format!("\n{:gutter$} = help: {}", "", help));
+        }
+        out
+    }
+}
+
+/// The source line `span` starts on, numbered in a gutter `gutter` wide,
+/// and a line underlining the span with `marker` followed by `message`.
+/// A span running past the end of its line is underlined to the line end.
+fn snippet(source: &str, span: Span, marker: char, message: &str, gutter: usize) -> String {
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |nl| nl + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |nl| start + nl);
+    let text = source[line_start..line_end].trim_end_matches('\r');
+    let end = span.end.clamp(start, line_start + text.len());
+    // Keep tabs so the underline lines up however the terminal shows them.
+    let indent: String = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = source[start..end].chars().count().max(1);
+    let mut underline = format!("{}{}", indent, marker.to_string().repeat(width));
+    if !message.is_empty() {
+        underline.push(' ');
+        underline.push_str(message);
+    }
+    let (line, _) = span.line_col(source);
+    format!(
+        "\n{:>gutter$} | {}\n{:gutter$} | {}",
+        line, text, "", underline
+    )
+}
+
+/// True when any diagnostic in `diagnostics` is an error.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(Diagnostic::is_error)
+}
@@ -0,0 +1,141 @@
+//! Numbered snapshots of a song for writers who don't use git.
+//!
+//! Each song gets its own directory of drafts, holding `1.lyr`, `2.lyr`, …
+//! and an `index.tsv` with one `number, time, message` row per draft.
+//! The drafts of a locked song are locked with the song's key.
+
+use crate::crypt::{self, CryptError, Key};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DraftError {
+    #[error("no draft {0}")]
+    NotFound(usize),
+    #[error("draft {number}: {source}")]
+    Crypt {
+        number: usize,
+        #[source]
+        source: CryptError,
+    },
+    #[error("drafts in '{path}': {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// One saved draft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    pub number: usize,
+    pub when: String,
+    pub message: String,
+}
+
+/// The drafts of one song, kept in `dir`.
+#[derive(Debug, Clone)]
+pub struct DraftStore {
+    dir: PathBuf,
+    key: Option<Key>,
+}
+
+impl DraftStore {
+    pub fn new(dir: impl Into<PathBuf>) -> DraftStore {
+        DraftStore {
+            dir: dir.into(),
+            key: None,
+        }
+    }
+
+    /// The store with drafts locked under `key`, for a locked song.
+    pub fn locked(self, key: Key) -> DraftStore {
+        DraftStore {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Every draft, oldest first. A song without drafts has none.
+    pub fn list(&self) -> Result<Vec<Draft>, DraftError> {
+        let index = match fs::read_to_string(self.index()) {
+            Ok(index) => index,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(self.io(e)),
+        };
+        Ok(index
+            .lines()
+            .filter_map(|row| {
+                let mut fields = row.splitn(3, '\t');
+                Some(Draft {
+                    number: fields.next()?.parse().ok()?,
+                    when: fields.next()?.to_string(),
+                    message: fields.next().unwrap_or("").to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Store `source` as the next draft, locked when the store has a key.
+    pub fn save(&self, source: &str, message: &str, when: &str) -> Result<Draft, DraftError> {
+        let number = self.list()?.last().map_or(1, |d| d.number + 1);
+        let draft = Draft {
+            number,
+            when: when.to_string(),
+            message: message.split_whitespace().collect::<Vec<_>>().join(" "),
+        };
+        let contents = match &self.key {
+            Some(key) => crypt::lock(source.as_bytes(), key)
+                .map_err(|source| DraftError::Crypt { number, source })?,
+            None => source.as_bytes().to_vec(),
+        };
+        fs::create_dir_all(&self.dir).map_err(|e| self.io(e))?;
+        fs::write(self.path(number), contents).map_err(|e| self.io(e))?;
+        let mut index = fs::read_to_string(self.index()).unwrap_or_default();
+        index.push_str(&format!(
+            "{}\t{}\t{}\n",
+            draft.number, draft.when, draft.message
+        ));
+        fs::write(self.index(), index).map_err(|e| self.io(e))?;
+        Ok(draft)
+    }
+
+    /// The source saved as draft `number`, unlocked with the store's key
+    /// when it was saved locked.
+    pub fn load(&self, number: usize) -> Result<String, DraftError> {
+        let bytes = fs::read(self.path(number)).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => DraftError::NotFound(number),
+            _ => self.io(e),
+        })?;
+        let crypt_error = |source| DraftError::Crypt { number, source };
+        let bytes = match (crypt::is_locked(&bytes), &self.key) {
+            (false, _) => bytes,
+            (true, Some(key)) => crypt::unlock(&bytes, key).map_err(crypt_error)?,
+            (true, None) => return Err(crypt_error(CryptError::WrongKey)),
+        };
+        String::from_utf8(bytes)
+            .map_err(|e| self.io(io::Error::new(io::ErrorKind::InvalidData, e.utf8_error())))
+    }
+
+    fn path(&self, number: usize) -> PathBuf {
+        self.dir.join(format!("{}.lyr", number))
+    }
+
+    fn index(&self) -> PathBuf {
+        self.dir.join("index.tsv")
+    }
+
+    fn io(&self, source: io::Error) -> DraftError {
+        DraftError::Io {
+            path: self.dir.display().to_string(),
+            source,
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Structural edits for scripts: sections inserted, moved and deleted, and
+//! lines inserted and deleted, without rewriting the rest of the song.
+//!
+//! Like [`crate::refactor`], every edit is made to the original text at the
+//! spans the parser recorded, so everything else is left byte-for-byte
+//! unchanged. Sections are numbered from 1 in the order they are written,
+//! macros aside; lines by the source line they start on. An edit that would
+//! leave the song with an error, such as a `REPEAT` moved above the section
+//! it repeats, is refused.
+
+use crate::ast::{Line, SectionKind, Song, Span};
+use crate::diagnostic::{has_errors, Severity};
+use crate::parser::parse_song;
+use crate::semantic::resolve;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum EditError {
+    #[error("the song does not parse:\n{0}")]
+    Parse(String),
+    #[error("the song has errors; fix them before editing")]
+    Invalid,
+    #[error("there is no section {index}; the song has {count}")]
+    NoSection { index: usize, count: usize },
+    #[error("line {0} is not a lyric line")]
+    NotALine(usize),
+    #[error("line {0} is the only line of its section; delete the section instead")]
+    LastLine(usize),
+    #[error("`{0}` is not a section header")]
+    NotAHeader(String),
+    #[error("`{0}` is not a single lyric line")]
+    NotOneLine(String),
+    #[error("the edit would break the song: {0}")]
+    Breaks(String),
+}
+
+/// Where to put a section or a line, by the section number or source line
+/// it goes next to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Place {
+    Before(usize),
+    After(usize),
+}
+
+/// The parsed `source`, which must be free of errors.
+fn checked(source: &str) -> Result<Song, EditError> {
+    let song = parse_song(source).map_err(|e| EditError::Parse(e.to_string()))?;
+    if has_errors(&resolve(&song).diagnostics) {
+        return Err(EditError::Invalid);
+    }
+    Ok(song)
+}
+
+/// The edited song, provided it still parses and resolves without errors.
+/// `unparsable` is the error when it no longer parses, if the edit has a
+/// better one than the parser's message.
+fn verified(edited: String, unparsable: Option<EditError>) -> Result<(String, Song), EditError> {
+    let song = parse_song(&edited).map_err(|e| {
+        unparsable.unwrap_or_else(|| EditError::Breaks(e.variant.message().into_owned()))
+    })?;
+    let resolved = resolve(&song);
+    if let Some(error) = resolved
+        .diagnostics
+        .iter()
+        .find(|d| d.severity == Severity::Error)
+    {
+        return Err(EditError::Breaks(error.message.clone()));
+    }
+    Ok((edited, song))
+}
+
+/// `text` with a trailing newline.
+fn terminated(text: &str) -> String {
+    match text.ends_with('\n') {
+        true => text.to_string(),
+        false => format!("{}\n", text),
+    }
+}
+
+/// The source range to remove with the section at `span`: it and the blank
+/// lines after it, or, for the last section, the blank lines before it.
+fn section_range(source: &str, span: Span) -> std::ops::Range<usize> {
+    let blank: usize = source[span.end..]
+        .split_inclusive('\n')
+        .take_while(|l| l.trim().is_empty())
+        .map(str::len)
+        .sum();
+    let end = span.end + blank;
+    let start = match end == source.len() {
+        true => match source[..span.start].trim_end().len() {
+            0 => span.start,
+            content => (content + 1).min(span.start),
+        },
+        false => span.start,
+    };
+    start..end
+}
+
+fn section_span(song: &Song, index: usize) -> Result<Span, EditError> {
+    match index.checked_sub(1).and_then(|i| song.sections.get(i)) {
+        Some(section) => Ok(section.span),
+        None => Err(EditError::NoSection {
+            index,
+            count: song.sections.len(),
+        }),
+    }
+}
+
+/// Where the text of a section goes at `place`, or after the last section,
+/// and what it is written with.
+fn section_insertion(
+    source: &str,
+    song: &Song,
+    place: Option<Place>,
+    text: &str,
+) -> Result<(usize, String), EditError> {
+    let place = place.unwrap_or(Place::After(song.sections.len()));
+    match place {
+        Place::Before(index) => Ok((section_span(song, index)?.start, format!("{}\n", text))),
+        Place::After(index) => {
+            let end = section_span(song, index)?.end;
+            match source[..end].ends_with('\n') {
+                true => Ok((end, format!("\n{}", text))),
+                false => Ok((end, format!("\n\n{}", text.trim_end_matches('\n')))),
+            }
+        }
+    }
+}
+
+/// Insert a section with `header`, such as `BRIDGE`, `VERSE[3]` or
+/// `REPEAT CHORUS`, and `lines` at `place`, or after the last section.
+pub fn insert_section(
+    source: &str,
+    header: &str,
+    lines: &[String],
+    place: Option<Place>,
+) -> Result<String, EditError> {
+    let song = checked(source)?;
+    let keyword = header.trim_start().split(['[', ' ', '\t', '{', '&']).next();
+    let known = keyword
+        .is_some_and(|k| SectionKind::from_keyword(k).is_some() || k == "SECTION" || k == "REPEAT");
+    if !known || header.contains('\n') {
+        return Err(EditError::NotAHeader(header.to_string()));
+    }
+    if let Some(line) = lines
+        .iter()
+        .find(|l| l.contains('\n') || l.trim().is_empty())
+    {
+        return Err(EditError::NotOneLine(line.clone()));
+    }
+    let mut text = format!("{}\n", header.trim());
+    for line in lines {
+        text.push_str(&terminated(line.trim()));
+    }
+    let (at, text) = section_insertion(source, &song, place, &text)?;
+    let mut edited = source.to_string();
+    edited.insert_str(at, &text);
+    let (edited, after) = verified(edited, None)?;
+    if after.sections.len() != song.sections.len() + 1 {
+        return Err(EditError::NotAHeader(header.to_string()));
+    }
+    Ok(edited)
+}
+
+/// Move section `index` to `place`.
+pub fn move_section(source: &str, index: usize, place: Place) -> Result<String, EditError> {
+    let song = checked(source)?;
+    let span = section_span(&song, index)?;
+    let target = match place {
+        Place::Before(n) | Place::After(n) => n,
+    };
+    section_span(&song, target)?;
+    let unmoved = match place {
+        Place::Before(n) => n == index || n == index + 1,
+        Place::After(n) => n == index || n + 1 == index,
+    };
+    if unmoved {
+        return Ok(source.to_string());
+    }
+    let text = terminated(&source[span.start..span.end]);
+    let (at, text) = section_insertion(source, &song, Some(place), &text)?;
+    let removed = section_range(source, span);
+    let mut edited = source.to_string();
+    if at >= removed.end {
+        edited.insert_str(at, &text);
+        edited.replace_range(removed, "");
+    } else {
+        edited.replace_range(removed, "");
+        edited.insert_str(at, &text);
+    }
+    Ok(verified(edited, None)?.0)
+}
+
+/// Delete section `index`.
+pub fn delete_section(source: &str, index: usize) -> Result<String, EditError> {
+    let song = checked(source)?;
+    let span = section_span(&song, index)?;
+    let mut edited = source.to_string();
+    edited.replace_range(section_range(source, span), "");
+    Ok(verified(edited, None)?.0)
+}
+
+/// Every line of the sections and macros of `song`, with the number of
+/// lines in its section.
+fn lines(song: &Song) -> impl Iterator<Item = (&Line, usize)> {
+    let macros = song.macros.iter().map(|m| &m.lines);
+    let sections = song.sections.iter().map(|s| &s.lines);
+    macros
+        .chain(sections)
+        .flat_map(|lines| lines.iter().map(move |l| (l, lines.len())))
+}
+
+fn find_line<'a>(
+    song: &'a Song,
+    source: &str,
+    number: usize,
+) -> Result<(&'a Line, usize), EditError> {
+    lines(song)
+        .find(|(l, _)| l.span.line_col(source).0 == number)
+        .ok_or(EditError::NotALine(number))
+}
+
+/// Insert the lyric line `text`, attributes and all, before or after the
+/// line written at source line `place`.
+pub fn insert_line(source: &str, text: &str, place: Place) -> Result<String, EditError> {
+    let song = checked(source)?;
+    if text.contains('\n') || text.trim().is_empty() {
+        return Err(EditError::NotOneLine(text.to_string()));
+    }
+    let text = terminated(text.trim());
+    let (at, text) = match place {
+        Place::Before(number) => (find_line(&song, source, number)?.0.span.start, text),
+        Place::After(number) => {
+            let end = find_line(&song, source, number)?.0.span.end;
+            match source[..end].ends_with('\n') {
+                true => (end, text),
+                false => (end, format!("\n{}", text.trim_end_matches('\n'))),
+            }
+        }
+    };
+    let mut edited = source.to_string();
+    edited.insert_str(at, &text);
+    let not_one_line = EditError::NotOneLine(text.trim_end().to_string());
+    let (edited, after) = verified(edited, Some(not_one_line.clone()))?;
+    if lines(&after).count() != lines(&song).count() + 1 {
+        return Err(not_one_line);
+    }
+    Ok(edited)
+}
+
+/// Delete the line written at source line `number`, with its harmony
+/// parts.
+pub fn delete_line(source: &str, number: usize) -> Result<String, EditError> {
+    let song = checked(source)?;
+    let (line, count) = find_line(&song, source, number)?;
+    if count == 1 {
+        return Err(EditError::LastLine(number));
+    }
+    let mut edited = source.to_string();
+    edited.replace_range(line.span.start..line.span.end, "");
+    Ok(verified(edited, None)?.0)
+}
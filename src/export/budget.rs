@@ -0,0 +1,132 @@
+//! Line-length budgets: how many characters a target shows on one row, and
+//! how a longer lyric line is wrapped to fit.
+//!
+//! Soft breaks written in the song always start a new row; the budget only
+//! adds breaks inside the parts between them. A word longer than the width
+//! gets a row of its own.
+
+use crate::ast::{word_ranges, Line};
+
+/// Where the extra breaks of a long line go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    /// Fill each row before starting the next.
+    Greedy,
+    /// Use as few rows as greedy wrapping but keep them close in length,
+    /// as subtitle guidelines ask.
+    Balanced,
+}
+
+/// The most characters a target shows on one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub width: usize,
+    pub wrap: Wrap,
+}
+
+impl Budget {
+    /// Word indices of `line` that start a new row: its soft breaks and
+    /// the breaks that keep every row within the width.
+    pub fn breaks(&self, line: &Line) -> Vec<usize> {
+        let text = &line.text;
+        let ranges = word_ranges(text);
+        // Word `i` with the spacing and punctuation up to the next word.
+        let start_of = |i: usize| if i == 0 { 0 } else { ranges[i].start };
+        let end_of = |i: usize| ranges.get(i + 1).map_or(text.len(), |r| r.start);
+        let row_width =
+            |from: usize, to: usize| text[start_of(from)..end_of(to - 1)].trim().chars().count();
+
+        let mut bounds = vec![0];
+        bounds.extend(line.breaks.iter().filter(|&&b| b < ranges.len()));
+        bounds.push(ranges.len());
+        let mut breaks = Vec::new();
+        for part in bounds.windows(2) {
+            let (from, to) = (part[0], part[1]);
+            if from > 0 {
+                breaks.push(from);
+            }
+            let rows = match self.wrap {
+                Wrap::Greedy => self.greedy(from, to, &row_width),
+                Wrap::Balanced => self.balanced(from, to, &row_width),
+            };
+            breaks.extend(rows);
+        }
+        breaks
+    }
+
+    /// The rows `line` is shown on.
+    pub fn rows(&self, line: &Line) -> Vec<String> {
+        let ranges = word_ranges(&line.text);
+        let mut rows = Vec::new();
+        let mut start = 0;
+        for word in self.breaks(line) {
+            let at = ranges[word].start;
+            rows.push(line.text[start..at].trim_end().to_string());
+            start = at;
+        }
+        rows.push(line.text[start..].to_string());
+        rows
+    }
+
+    /// Whether `line` is shown on more rows than its soft breaks make.
+    pub fn wraps(&self, line: &Line) -> bool {
+        self.breaks(line).len() > line.breaks.len()
+    }
+
+    fn greedy(&self, from: usize, to: usize, width: &dyn Fn(usize, usize) -> usize) -> Vec<usize> {
+        let mut breaks = Vec::new();
+        let mut start = from;
+        for end in from + 1..to {
+            if width(start, end + 1) > self.width {
+                breaks.push(end);
+                start = end;
+            }
+        }
+        breaks
+    }
+
+    /// Breaks giving as many rows as [`Budget::greedy`], chosen so the
+    /// longest row is as short as it can be.
+    fn balanced(
+        &self,
+        from: usize,
+        to: usize,
+        width: &dyn Fn(usize, usize) -> usize,
+    ) -> Vec<usize> {
+        let rows = self.greedy(from, to, width).len() + 1;
+        if rows == 1 {
+            return Vec::new();
+        }
+        // longest[k][i]: the longest row when the words from `from` to
+        // `from + i` are set on `k` rows, with where the last row starts.
+        let words = to - from;
+        let mut longest = vec![vec![None::<(usize, usize)>; words + 1]; rows + 1];
+        longest[0][0] = Some((0, 0));
+        for k in 1..=rows {
+            for i in 1..=words {
+                for j in (k - 1)..i {
+                    let Some((before, _)) = longest[k - 1][j] else {
+                        continue;
+                    };
+                    let row = width(from + j, from + i);
+                    if row > self.width && i - j > 1 {
+                        continue;
+                    }
+                    let candidate = before.max(row);
+                    if longest[k][i].is_none_or(|(best, _)| candidate < best) {
+                        longest[k][i] = Some((candidate, j));
+                    }
+                }
+            }
+        }
+        let mut breaks = Vec::new();
+        let mut i = words;
+        for k in (2..=rows).rev() {
+            let (_, j) = longest[k][i].expect("greedy wrapping fits this many rows");
+            breaks.push(from + j);
+            i = j;
+        }
+        breaks.reverse();
+        breaks
+    }
+}
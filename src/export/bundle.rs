@@ -0,0 +1,111 @@
+//! Several exports of one song packaged in a single archive, the way
+//! distributors take deliveries.
+//!
+//! Each format is rendered to `<stem>.<extension>`, or to
+//! `<stem>.<format>.<extension>` when two formats share an extension, and
+//! a `manifest.json` lists every file with its format, size and SHA-256.
+
+use super::tar::TarWriter;
+use super::zip::ZipWriter;
+use super::{ExportError, Exporter};
+use crate::ast::Song;
+use crate::crypt::sha256::sha256;
+use crate::delivery::to_hex;
+use serde::Serialize;
+
+/// Name of the manifest inside the archive.
+pub const MANIFEST: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Archive {
+    Zip,
+    Tar,
+}
+
+impl Archive {
+    /// The archive an output file calls for by its extension.
+    pub fn from_path(path: &str) -> Option<Archive> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "zip" => Some(Archive::Zip),
+            "tar" => Some(Archive::Tar),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    generator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artist: Option<String>,
+    files: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    name: String,
+    format: &'static str,
+    bytes: usize,
+    sha256: String,
+}
+
+/// Render `song` with each of `exporters` and package the results, named
+/// after `stem`, in one archive.
+pub fn bundle(
+    song: &Song,
+    stem: &str,
+    exporters: &[Box<dyn Exporter>],
+    archive: Archive,
+) -> Result<Vec<u8>, ExportError> {
+    let mut files = Vec::new();
+    let mut entries = Vec::new();
+    for exporter in exporters {
+        let extension = exporter.extension();
+        let shared = exporters
+            .iter()
+            .filter(|e| e.extension() == extension)
+            .count()
+            > 1;
+        let name = match shared {
+            true => format!("{}.{}.{}", stem, exporter.name(), extension),
+            false => format!("{}.{}", stem, extension),
+        };
+        let data = exporter.export(song)?;
+        entries.push(Entry {
+            name: name.clone(),
+            format: exporter.name(),
+            bytes: data.len(),
+            sha256: to_hex(&sha256(&data)),
+        });
+        files.push((name, data));
+    }
+    let manifest = Manifest {
+        generator: format!("lyrics-dsl {}", env!("CARGO_PKG_VERSION")),
+        title: song.title(),
+        artist: song.artist(),
+        files: entries,
+    };
+    let mut json = serde_json::to_vec_pretty(&manifest)?;
+    json.push(b'\n');
+    files.push((MANIFEST.to_string(), json));
+
+    Ok(match archive {
+        Archive::Zip => {
+            let mut zip = ZipWriter::default();
+            for (name, data) in &files {
+                zip.add(name, data);
+            }
+            zip.finish()
+        }
+        Archive::Tar => {
+            let mut tar = TarWriter::default();
+            for (name, data) in &files {
+                tar.add(name, data);
+            }
+            tar.finish()
+        }
+    })
+}
@@ -0,0 +1,83 @@
+//! The smallest WordprocessingML package Word opens: one document part on
+//! A4 pages, its paragraphs and tables written by the caller.
+
+use super::ttml::escape;
+use super::zip::ZipWriter;
+use std::fmt::Write;
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+const WORD: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// A paragraph of one run; `style` is run properties such as `<w:b/>`.
+pub(crate) fn paragraph(out: &mut String, text: &str, style: &str) {
+    out.push_str("<w:p>");
+    run(out, text, style);
+    out.push_str("</w:p>");
+}
+
+/// A run of `text` in `style`.
+pub(crate) fn run(out: &mut String, text: &str, style: &str) {
+    out.push_str("<w:r>");
+    if !style.is_empty() {
+        let _ = write!(out, "<w:rPr>{}</w:rPr>", style);
+    }
+    let _ = write!(
+        out,
+        "<w:t xml:space=\"preserve\">{}</w:t></w:r>",
+        escape(text)
+    );
+}
+
+/// The start of a full-width table with columns `widths` wide, in twips,
+/// ruled between rows.
+pub(crate) fn table_start(out: &mut String, widths: &[u32]) {
+    out.push_str(
+        "<w:tbl><w:tblPr><w:tblW w:w=\"5000\" w:type=\"pct\"/><w:tblBorders>\
+         <w:top w:val=\"single\" w:sz=\"4\"/><w:bottom w:val=\"single\" w:sz=\"4\"/>\
+         <w:insideH w:val=\"single\" w:sz=\"4\"/></w:tblBorders></w:tblPr><w:tblGrid>",
+    );
+    for width in widths {
+        let _ = write!(out, "<w:gridCol w:w=\"{}\"/>", width);
+    }
+    out.push_str("</w:tblGrid>");
+}
+
+/// One table cell holding `text`, across `span` columns.
+pub(crate) fn cell(out: &mut String, text: &str, style: &str, span: usize) {
+    out.push_str("<w:tc>");
+    if span > 1 {
+        let _ = write!(out, "<w:tcPr><w:gridSpan w:val=\"{}\"/></w:tcPr>", span);
+    }
+    paragraph(out, text, style);
+    out.push_str("</w:tc>");
+}
+
+/// A `.docx` file of `body`.
+pub(crate) fn package(body: &str) -> Vec<u8> {
+    let document = format!(
+        "{}<w:document xmlns:w=\"{}\"><w:body>{}<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>\
+         <w:pgMar w:top=\"1134\" w:right=\"1134\" w:bottom=\"1134\" w:left=\"1134\"/></w:sectPr>\
+         </w:body></w:document>",
+        XML_HEADER, WORD, body
+    );
+    let content_types = format!(
+        "{}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Override PartName=\"/word/document.xml\" \
+         ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/></Types>",
+        XML_HEADER
+    );
+    let rels = format!(
+        "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" \
+         Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" \
+         Target=\"word/document.xml\"/></Relationships>",
+        XML_HEADER
+    );
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", content_types.as_bytes());
+    zip.add("_rels/.rels", rels.as_bytes());
+    zip.add("word/document.xml", document.as_bytes());
+    zip.finish()
+}
@@ -0,0 +1,58 @@
+use super::karaoke::{line_ends, word_timings};
+use super::lrc::{lrc_time, write_gap, write_romanization, write_tags};
+use super::{ExportError, Exporter};
+use crate::ast::Song;
+use std::fmt::Write;
+
+/// Enhanced LRC: the LRC line tags plus a `<mm:ss.xx>` tag before each word
+/// and one closing the line, so players can highlight word by word.
+/// Romanizations and stanza breaks are written as in plain LRC.
+pub struct EnhancedLrcExporter;
+
+impl Exporter for EnhancedLrcExporter {
+    fn name(&self) -> &'static str {
+        "elrc"
+    }
+
+    fn extension(&self) -> &'static str {
+        "lrc"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let mut out = String::new();
+        write_tags(&mut out, song);
+
+        let lines: Vec<_> = song.lines().map(|(_, line)| line).collect();
+        for (i, end) in line_ends(song).into_iter().enumerate() {
+            let line = lines[i];
+            let Some(begin) = line.timing else {
+                let _ = writeln!(out, "{}", line.text);
+                write_romanization(&mut out, line);
+                continue;
+            };
+            let timings = word_timings(line, begin, end);
+            let _ = write!(out, "[{}]", lrc_time(begin));
+            let mut cursor = 0;
+            for word in &timings {
+                out.push_str(&line.text[cursor..word.range.start]);
+                let _ = write!(
+                    out,
+                    "<{}>{}",
+                    lrc_time(word.begin),
+                    &line.text[word.range.clone()]
+                );
+                cursor = word.range.end;
+            }
+            out.push_str(&line.text[cursor..]);
+            match timings.last() {
+                Some(last) => {
+                    let _ = writeln!(out, "<{}>", lrc_time(last.end));
+                }
+                None => out.push('\n'),
+            }
+            write_romanization(&mut out, line);
+            write_gap(&mut out, line, lines.get(i + 1).copied(), end);
+        }
+        Ok(out.into_bytes())
+    }
+}
@@ -0,0 +1,256 @@
+//! Structure graphs for Graphviz (`dot`) and D2 (`d2`).
+//!
+//! The sections are drawn in the order they are sung, colored as on the
+//! song map. A `REPEAT` points back at the section it replays, and a
+//! section points at each macro or anchor it `USE`s, as does a macro that
+//! uses another. Songs must be resolved first: the `USE`s are read from
+//! the record [`crate::semantic::resolve`] keeps on each section.
+
+use super::{ExportError, Exporter};
+use crate::ast::{RefTarget, Song};
+use crate::songmap::color;
+use std::fmt::Write;
+
+/// Graphviz DOT source of the song's structure.
+pub struct DotExporter;
+
+/// D2 source of the song's structure.
+pub struct D2Exporter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Section,
+    Repeat,
+    Macro,
+    Anchor,
+}
+
+struct Node {
+    id: String,
+    label: String,
+    shape: Shape,
+    fill: (u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Next,
+    Repeat,
+    Use,
+}
+
+struct Graph {
+    title: Option<String>,
+    nodes: Vec<Node>,
+    edges: Vec<(String, String, Edge)>,
+}
+
+impl Graph {
+    fn new(song: &Song) -> Graph {
+        let sections = &song.sections;
+        let mut nodes: Vec<Node> = sections
+            .iter()
+            .enumerate()
+            .map(|(i, section)| Node {
+                id: format!("s{}", i + 1),
+                label: section.label(),
+                shape: match section.repeat {
+                    Some(_) => Shape::Repeat,
+                    None => Shape::Section,
+                },
+                fill: color(section.kind),
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for i in 1..sections.len() {
+            edges.push((nodes[i - 1].id.clone(), nodes[i].id.clone(), Edge::Next));
+        }
+
+        for (i, section) in sections.iter().enumerate() {
+            let Some(reference) = &section.repeat else {
+                continue;
+            };
+            let RefTarget::Section { kind, number } = &reference.target else {
+                continue;
+            };
+            // The section a `REPEAT` replays, found as the resolver does.
+            let target = match number {
+                Some(_) => {
+                    let header = reference.target.to_string();
+                    sections
+                        .iter()
+                        .position(|s| s.repeat.is_none() && s.header() == header)
+                }
+                None => sections[..i].iter().rposition(|s| s.kind == *kind),
+            };
+            if let Some(target) = target {
+                edges.push((nodes[i].id.clone(), nodes[target].id.clone(), Edge::Repeat));
+            }
+        }
+
+        // Included macros and line anchors get a node of their own; an
+        // anchored section is its own node.
+        let mut included: Vec<(String, String)> = Vec::new();
+        let mut id_of = |name: &str, nodes: &mut Vec<Node>| -> String {
+            if let Some(anchor) = name.strip_prefix('*') {
+                let anchored = sections
+                    .iter()
+                    .position(|s| s.anchor.as_ref().is_some_and(|a| a.name == anchor));
+                if let Some(s) = anchored {
+                    return format!("s{}", s + 1);
+                }
+            }
+            if let Some((_, id)) = included.iter().find(|(n, _)| n == name) {
+                return id.clone();
+            }
+            let id = format!("u{}", included.len() + 1);
+            nodes.push(Node {
+                id: id.clone(),
+                label: name.to_string(),
+                shape: match name.starts_with('*') {
+                    true => Shape::Anchor,
+                    false => Shape::Macro,
+                },
+                fill: (255, 255, 255),
+            });
+            included.push((name.to_string(), id.clone()));
+            id
+        };
+        for (i, section) in sections.iter().enumerate() {
+            for used in &section.uses {
+                let from = match &used.within {
+                    Some(within) => id_of(within, &mut nodes),
+                    None => format!("s{}", i + 1),
+                };
+                let edge = (from, id_of(&used.target, &mut nodes), Edge::Use);
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+
+        Graph {
+            title: song.title(),
+            nodes,
+            edges,
+        }
+    }
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// `text` as a double-quoted DOT or D2 string.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Exporter for DotExporter {
+    fn name(&self) -> &'static str {
+        "dot"
+    }
+
+    fn extension(&self) -> &'static str {
+        "dot"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let graph = Graph::new(song);
+        let mut out = String::from("digraph song {\n");
+        if let Some(title) = &graph.title {
+            let _ = writeln!(out, "  label={};\n  labelloc=t;", quoted(title));
+        }
+        out.push_str("  rankdir=LR;\n");
+        out.push_str(
+            "  node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\", \
+             fontcolor=white];\n",
+        );
+        out.push_str("  edge [fontname=\"Helvetica\", fontsize=10];\n");
+        for node in &graph.nodes {
+            let style = match node.shape {
+                Shape::Section => "",
+                Shape::Repeat => ", style=\"rounded,filled,dashed\"",
+                Shape::Macro => ", shape=note, fontcolor=black",
+                Shape::Anchor => ", shape=cds, fontcolor=black",
+            };
+            let _ = writeln!(
+                out,
+                "  {} [label={}, fillcolor=\"{}\"{}];",
+                node.id,
+                quoted(&node.label),
+                hex(node.fill),
+                style
+            );
+        }
+        for (from, to, edge) in &graph.edges {
+            let style = match edge {
+                Edge::Next => "",
+                Edge::Repeat => " [label=\"REPEAT\", style=dashed, constraint=false]",
+                Edge::Use => " [label=\"USE\", style=dotted]",
+            };
+            let _ = writeln!(out, "  {} -> {}{};", from, to, style);
+        }
+        out.push_str("}\n");
+        Ok(out.into_bytes())
+    }
+}
+
+impl Exporter for D2Exporter {
+    fn name(&self) -> &'static str {
+        "d2"
+    }
+
+    fn extension(&self) -> &'static str {
+        "d2"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let graph = Graph::new(song);
+        let mut out = String::from("direction: right\n");
+        if let Some(title) = &graph.title {
+            let _ = writeln!(
+                out,
+                "title: {} {{\n  shape: text\n  near: top-center\n}}",
+                quoted(title)
+            );
+        }
+        for node in &graph.nodes {
+            let _ = writeln!(out, "{}: {} {{", node.id, quoted(&node.label));
+            match node.shape {
+                Shape::Section | Shape::Repeat => {
+                    let _ = writeln!(out, "  style.fill: \"{}\"", hex(node.fill));
+                    out.push_str("  style.font-color: white\n");
+                }
+                Shape::Macro => out.push_str("  shape: page\n"),
+                Shape::Anchor => out.push_str("  shape: hexagon\n"),
+            }
+            if node.shape == Shape::Repeat {
+                out.push_str("  style.stroke-dash: 3\n");
+            }
+            out.push_str("}\n");
+        }
+        for (from, to, edge) in &graph.edges {
+            match edge {
+                Edge::Next => {
+                    let _ = writeln!(out, "{} -> {}", from, to);
+                }
+                Edge::Repeat => {
+                    let _ = writeln!(
+                        out,
+                        "{} -> {}: REPEAT {{\n  style.stroke-dash: 3\n}}",
+                        from, to
+                    );
+                }
+                Edge::Use => {
+                    let _ = writeln!(
+                        out,
+                        "{} -> {}: USE {{\n  style.stroke-dash: 1\n}}",
+                        from, to
+                    );
+                }
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
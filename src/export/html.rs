@@ -0,0 +1,370 @@
+use super::ttml::escape;
+use super::{ExportError, Exporter, Harmony};
+use crate::analysis::{count_line_in, language};
+use crate::ast::{word_ranges, words, Line, Section, Song};
+use crate::chords::capo::{shapes, song_capo};
+use crate::chords::diagram::{diagrams, Fretboard};
+use crate::credits::citations;
+use crate::feedback::Comments;
+use crate::template::Layout;
+use crate::typography::smarten_song;
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// A standalone HTML lyric sheet: one `section` per song section, the
+/// line's chords before its text and pronunciations and furigana as ruby. With
+/// `chord_diagrams` set, diagrams of every chord used are appended, and with
+/// `smart_typography` the lyrics get curly quotes, dashes and ellipses. A
+/// song with `capo` metadata shows the capo and the shapes played above it.
+/// Harmony lines follow their lead line in italics, or each part in its own
+/// color with [`Harmony::Separate`]. Interpolation and sample credits go in
+/// a footer, and review `comments` on lines float in the right margin.
+///
+/// With a `template` the sheet is the template filled in instead, from
+/// these values:
+///
+/// - `title`, `artist`, `lang` and `capo`, `meta` with every metadata
+///   entry by key, and `style`, the stylesheet the built-in sheet uses.
+/// - `sections`, each with its `kind`, `label` and `lines`; a line has its
+///   `text`, its `html` with ruby, its `chords` as played above the capo,
+///   `stanza` when a blank line comes before it, its `syllables`, its
+///   `rhyme` letter and its `timing`, its review `comments`, each with its
+///   `text` and any `author` and `date`, and `harmony`, the lines sung over
+///   it, unless harmony is excluded.
+/// - `syllables`, the song's total, and `chords`, every chord it uses once.
+/// - `credits`, a line for each work the song interpolates or samples.
+/// - `diagrams`, the chord diagrams as SVG, and `unknown_chords`, those
+///   without one, when `chord_diagrams` is set.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlExporter {
+    pub chord_diagrams: Option<Fretboard>,
+    pub smart_typography: bool,
+    pub harmony: Harmony,
+    pub template: Option<Layout>,
+    pub comments: Comments,
+}
+
+const STYLE: &str = "body { font-family: Georgia, serif; max-width: 40em; margin: 2em auto; }
+h1 { margin-bottom: 0; }
+.artist { color: #666; margin-top: 0.2em; }
+.capo { font: bold 0.9em sans-serif; }
+h2 { font-size: 1em; text-transform: uppercase; letter-spacing: 0.1em; color: #555; }
+p.line { margin: 0.2em 0; }
+p.stanza { margin-top: 1.2em; }
+p.harmony { margin-left: 1.5em; font-style: italic; color: #666; }
+.chords { font: bold 0.8em sans-serif; color: #a33; margin-right: 0.8em; }
+.chord-diagrams { display: flex; flex-wrap: wrap; gap: 1em; }
+.chord-diagrams h2 { width: 100%; }
+";
+
+/// Room on the right for review comments, and the notes set in it.
+const COMMENT_STYLE: &str = "body { padding-right: 16em; }
+span.comment { float: right; clear: right; width: 14em; margin-right: -16em; font: 0.75em sans-serif; color: #9c5b00; }
+";
+
+/// Colors the harmony parts under a line take in turn when set apart.
+const PART_STYLE: &str = "p.part-1 { color: #2a6fb0; }
+p.part-2 { color: #2e8540; }
+p.part-3 { color: #9c5b00; }
+";
+
+fn line_body(line: &Line) -> String {
+    let mut out = String::new();
+    let mut cursor = 0;
+    for (i, range) in word_ranges(&line.text).into_iter().enumerate() {
+        let gap = &line.text[cursor..range.start];
+        match line.breaks.contains(&i) {
+            true => {
+                out.push_str(&escape(gap.trim_end()));
+                out.push_str("<br>");
+            }
+            false => out.push_str(&escape(gap)),
+        }
+        let word = &line.text[range.clone()];
+        match line.pronunciation(word) {
+            Some(ipa) => {
+                let _ = write!(out, "<ruby>{}<rt>{}</rt></ruby>", escape(word), escape(ipa));
+            }
+            None => {
+                for (run, reading) in line.ruby_runs(range.clone()) {
+                    match reading {
+                        Some(reading) => {
+                            let _ = write!(
+                                out,
+                                "<ruby>{}<rt>{}</rt></ruby>",
+                                escape(run),
+                                escape(reading)
+                            );
+                        }
+                        None => out.push_str(&escape(run)),
+                    }
+                }
+            }
+        }
+        cursor = range.end;
+    }
+    out.push_str(&escape(&line.text[cursor..]));
+    out
+}
+
+/// The rhyme letter of each line of `section`: the one written on it, else
+/// the letter its last word shares with the lines it rhymes with, in the
+/// order the rhymes first appear. Lines that rhyme with nothing get none.
+fn rhyme_letters(section: &Section, language: &dyn language::Language) -> Vec<Option<char>> {
+    let ends: Vec<String> = section
+        .lines
+        .iter()
+        .map(|l| {
+            words(&l.text)
+                .last()
+                .map(|w| language.rhyme(w))
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut letters: Vec<(String, char)> = Vec::new();
+    let mut next = 'A';
+    section
+        .lines
+        .iter()
+        .zip(&ends)
+        .map(|(line, end)| {
+            if line.rhyme.is_some() {
+                return line.rhyme;
+            }
+            if end.is_empty() || ends.iter().filter(|e| *e == end).count() < 2 {
+                return None;
+            }
+            if let Some((_, letter)) = letters.iter().find(|(e, _)| e == end) {
+                return Some(*letter);
+            }
+            let letter = next;
+            next = char::from_u32(next as u32 + 1).unwrap_or('Z');
+            letters.push((end.clone(), letter));
+            Some(letter)
+        })
+        .collect()
+}
+
+impl HtmlExporter {
+    /// The stylesheet, with what harmony parts and comments need.
+    fn style(&self) -> String {
+        let mut style = STYLE.to_string();
+        if self.harmony == Harmony::Separate {
+            style.push_str(PART_STYLE);
+        }
+        if !self.comments.is_empty() {
+            style.push_str(COMMENT_STYLE);
+        }
+        style
+    }
+
+    /// The review comments on `line`, as margin notes.
+    fn notes(&self, line: &Line) -> String {
+        let mut out = String::new();
+        for comment in self.comments.on(line) {
+            let _ = write!(
+                out,
+                "<span class=\"comment\">{}</span>",
+                escape(&comment.note())
+            );
+        }
+        out
+    }
+
+    /// The values a template is filled in from.
+    fn context(&self, song: &Song) -> Value {
+        let language = language::of(song);
+        let capo = song_capo(song).unwrap_or(0);
+        let mut chords: Vec<String> = Vec::new();
+        let mut total = 0;
+        let line_value = |line: &Line, rhyme: Option<char>, total: &mut usize| {
+            let syllables = count_line_in(line, language);
+            *total += syllables;
+            json!({
+                "text": line.text,
+                "html": line_body(line),
+                "chords": shapes(&line.chords, capo),
+                "stanza": line.stanza_break,
+                "syllables": syllables,
+                "rhyme": rhyme.map(String::from),
+                "timing": line.timing.map(|t| t.to_string()),
+                "comments": self.comments.on(line),
+            })
+        };
+        let sections: Vec<Value> = song
+            .sections
+            .iter()
+            .map(|section| {
+                let rhymes = rhyme_letters(section, language);
+                let lines: Vec<Value> = section
+                    .lines
+                    .iter()
+                    .zip(rhymes)
+                    .map(|(line, rhyme)| {
+                        for chord in shapes(&line.chords, capo) {
+                            if !chords.contains(&chord) {
+                                chords.push(chord);
+                            }
+                        }
+                        let mut value = line_value(line, rhyme, &mut total);
+                        let harmony: Vec<Value> = match self.harmony {
+                            Harmony::Exclude => Vec::new(),
+                            _ => line
+                                .harmony
+                                .iter()
+                                .map(|part| line_value(part, None, &mut 0))
+                                .collect(),
+                        };
+                        value["harmony"] = Value::from(harmony);
+                        value
+                    })
+                    .collect();
+                json!({
+                    "kind": section.kind.keyword().to_lowercase(),
+                    "label": section.label(),
+                    "lines": lines,
+                })
+            })
+            .collect();
+        let meta: serde_json::Map<String, Value> = song
+            .metadata
+            .iter()
+            .map(|entry| (entry.key.clone(), Value::from(entry.value.to_string())))
+            .collect();
+        let mut context = json!({
+            "title": song.title().unwrap_or_else(|| "Untitled".to_string()),
+            "artist": song.artist(),
+            "lang": song.meta_str("lang").unwrap_or_else(|| "en".to_string()),
+            "capo": capo,
+            "meta": meta,
+            "style": self.style(),
+            "sections": sections,
+            "syllables": total,
+            "chords": chords,
+            "credits": citations(song),
+        });
+        if let Some(fretboard) = &self.chord_diagrams {
+            let (found, unknown) = diagrams(fretboard, chords.iter().map(String::as_str));
+            context["diagrams"] = found.iter().map(|d| d.to_svg()).collect();
+            context["unknown_chords"] = Value::from(unknown);
+        }
+        context
+    }
+}
+
+impl Exporter for HtmlExporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let song = &match self.smart_typography {
+            true => smarten_song(song),
+            false => song.clone(),
+        };
+        if let Some(layout) = &self.template {
+            return Ok(layout.render(&self.context(song))?.into_bytes());
+        }
+        let title = song.title().unwrap_or_else(|| "Untitled".to_string());
+        let lang = song.meta_str("lang").unwrap_or_else(|| "en".to_string());
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>",
+            escape(&lang),
+            escape(&title),
+            self.style()
+        );
+        let _ = writeln!(out, "<header>\n<h1>{}</h1>", escape(&title));
+        if let Some(artist) = song.artist() {
+            let _ = writeln!(out, "<p class=\"artist\">{}</p>", escape(&artist));
+        }
+        let capo = song_capo(song).unwrap_or(0);
+        if capo > 0 {
+            let _ = writeln!(out, "<p class=\"capo\">Capo {}</p>", capo);
+        }
+        out.push_str("</header>\n");
+
+        for section in &song.sections {
+            let _ = writeln!(
+                out,
+                "<section class=\"{}\">\n<h2>{}</h2>",
+                section.kind.keyword().to_lowercase(),
+                escape(&section.label())
+            );
+            for line in &section.lines {
+                out.push_str(match line.stanza_break {
+                    true => "<p class=\"line stanza\">",
+                    false => "<p class=\"line\">",
+                });
+                out.push_str(&self.notes(line));
+                if !line.chords.is_empty() {
+                    let _ = write!(
+                        out,
+                        "<span class=\"chords\">{}</span>",
+                        escape(&shapes(&line.chords, capo).join(" "))
+                    );
+                }
+                let _ = writeln!(out, "{}</p>", line_body(line));
+                if self.harmony == Harmony::Exclude {
+                    continue;
+                }
+                for (i, part) in line.harmony.iter().enumerate() {
+                    let class = match self.harmony {
+                        Harmony::Separate => format!("line harmony part-{}", i % 3 + 1),
+                        _ => "line harmony".to_string(),
+                    };
+                    let _ = writeln!(
+                        out,
+                        "<p class=\"{}\">{}{}</p>",
+                        class,
+                        self.notes(part),
+                        line_body(part)
+                    );
+                }
+            }
+            out.push_str("</section>\n");
+        }
+
+        if let Some(fretboard) = &self.chord_diagrams {
+            let chords: Vec<String> = song
+                .lines()
+                .flat_map(|(_, l)| shapes(&l.chords, capo))
+                .collect();
+            let (found, unknown) = diagrams(fretboard, chords.iter().map(String::as_str));
+            if !found.is_empty() || !unknown.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "<section class=\"chord-diagrams\">\n<h2>Chords ({}, {})</h2>",
+                    fretboard.instrument.name(),
+                    fretboard.tuning_name()
+                );
+                for diagram in &found {
+                    let _ = writeln!(out, "{}", diagram.to_svg());
+                }
+                if !unknown.is_empty() {
+                    let _ = writeln!(
+                        out,
+                        "<p class=\"unknown\">No diagram for {}</p>",
+                        escape(&unknown.join(", "))
+                    );
+                }
+                out.push_str("</section>\n");
+            }
+        }
+        let citations = citations(song);
+        if !citations.is_empty() {
+            out.push_str("<footer class=\"credits\">\n");
+            for citation in citations {
+                let _ = writeln!(out, "<p>{}</p>", escape(&citation));
+            }
+            out.push_str("</footer>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        Ok(out.into_bytes())
+    }
+}
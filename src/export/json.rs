@@ -0,0 +1,21 @@
+use super::{ExportError, Exporter};
+use crate::ast::Song;
+
+/// Serializes the song tree as pretty-printed JSON.
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let mut out = serde_json::to_vec_pretty(song)?;
+        out.push(b'\n');
+        Ok(out)
+    }
+}
@@ -0,0 +1,115 @@
+//! Word-level timing shared by the karaoke exporters.
+//!
+//! A timed line runs until the next timed line starts. Its words split that
+//! time evenly, one beat each, except that a melisma (`lo~~~ve`) adds a beat
+//! per `~` and a fixed hold (`love{hold:1.2s}`) takes its duration off the
+//! top before the beats are shared out. When the next timed line starts no
+//! later, as the lines a `REPEAT` copies do, each beat is
+//! [`FALLBACK_BEAT_MS`] long instead.
+//!
+//! A line followed by a stanza break stops short of the next line, leaving a
+//! cue gap of [`STANZA_GAP_MS`], or half the time between the two lines if
+//! that is shorter, with nothing on screen.
+
+use crate::ast::{word_ranges, Hold, Line, Song, Timestamp};
+use std::ops::Range;
+
+/// Beat length used for the last timed line, whose end is unknown.
+const FALLBACK_BEAT_MS: u64 = 400;
+
+/// Silence left before a line that starts a new stanza.
+pub const STANZA_GAP_MS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordTiming {
+    /// Byte range of the word in the line text.
+    pub range: Range<usize>,
+    pub begin: Timestamp,
+    pub end: Timestamp,
+}
+
+/// For each line of `song`, in [`Song::lines`] order, the start of the next
+/// timed line, less the cue gap when a stanza break comes first. A timed
+/// line has no end when the next one does not start after it.
+pub fn line_ends(song: &Song) -> Vec<Option<Timestamp>> {
+    let lines: Vec<&Line> = song.lines().map(|(_, line)| line).collect();
+    let mut ends = vec![None; lines.len()];
+    let mut next: Option<Timestamp> = None;
+    let mut gap = false;
+    for (i, line) in lines.iter().enumerate().rev() {
+        ends[i] = match (next, line.timing) {
+            (Some(next), Some(begin)) if next <= begin => None,
+            (Some(next), Some(begin)) if gap => {
+                let room = next.millis.saturating_sub(begin.millis);
+                Some(Timestamp::from_millis(
+                    next.millis - STANZA_GAP_MS.min(room / 2),
+                ))
+            }
+            _ => next,
+        };
+        if line.timing.is_some() {
+            next = line.timing;
+            gap = false;
+        }
+        gap |= line.stanza_break;
+    }
+    ends
+}
+
+/// Spread the words of `line` between `begin` and `end`.
+pub fn word_timings(line: &Line, begin: Timestamp, end: Option<Timestamp>) -> Vec<WordTiming> {
+    let ranges = word_ranges(&line.text);
+    let holds: Vec<Option<Hold>> = (0..ranges.len()).map(|i| line.sustain(i)).collect();
+
+    let fixed: u64 = holds
+        .iter()
+        .map(|hold| match hold {
+            Some(Hold::Fixed(ts)) => ts.millis,
+            _ => 0,
+        })
+        .fold(0, u64::saturating_add);
+    let beats: u64 = holds
+        .iter()
+        .map(|hold| match hold {
+            Some(Hold::Fixed(_)) => 0,
+            Some(Hold::Beats(n)) => 1 + *n as u64,
+            None => 1,
+        })
+        .fold(0, u64::saturating_add);
+    let shared = match end {
+        Some(end) => end
+            .millis
+            .saturating_sub(begin.millis)
+            .saturating_sub(fixed),
+        None => beats.saturating_mul(FALLBACK_BEAT_MS),
+    };
+    let limit = end.map_or(u64::MAX, |end| end.millis.max(begin.millis));
+
+    let mut timings = Vec::with_capacity(ranges.len());
+    // Holds can add up past the end of time; the words stop there.
+    let at = |fixed: u64, beats_so_far: u64| {
+        let share = (shared as u128 * beats_so_far as u128)
+            .checked_div(beats as u128)
+            .unwrap_or(0) as u64;
+        begin.millis.saturating_add(fixed).saturating_add(share)
+    };
+    let (mut fixed_so_far, mut beats_so_far) = (0u64, 0u64);
+    for (range, hold) in ranges.into_iter().zip(holds) {
+        let start = at(fixed_so_far, beats_so_far);
+        match hold {
+            Some(Hold::Fixed(ts)) => fixed_so_far = fixed_so_far.saturating_add(ts.millis),
+            Some(Hold::Beats(n)) => beats_so_far = beats_so_far.saturating_add(1 + n as u64),
+            None => beats_so_far += 1,
+        }
+        let stop = at(fixed_so_far, beats_so_far);
+        timings.push(WordTiming {
+            range,
+            begin: Timestamp::from_millis(start.min(limit)),
+            end: Timestamp::from_millis(stop.min(limit)),
+        });
+    }
+    if let (Some(last), Some(end)) = (timings.last_mut(), end) {
+        last.end = end.max(last.begin);
+    }
+    timings
+}
@@ -0,0 +1,98 @@
+use super::karaoke::line_ends;
+use super::{ExportError, Exporter};
+use crate::ast::{Line, Song, Timestamp};
+use std::fmt::Write;
+
+/// Renders synced lyrics in the LRC format.
+///
+/// Lines carrying a `timing` attribute get a `[mm:ss.xx]` tag; untimed lines
+/// are written without one, which players treat as unsynced text. A line's
+/// romanization follows it with the same tag. Before a stanza break an
+/// empty tag clears the screen for the cue gap.
+pub struct LrcExporter;
+
+/// Format a timestamp as an LRC time tag body, e.g. `01:05.20`.
+pub(crate) fn lrc_time(ts: Timestamp) -> String {
+    let centis = ts.millis.saturating_add(5) / 10;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        (centis / 100) % 60,
+        centis % 100
+    )
+}
+
+/// Write the `[ti:]`, `[ar:]`, `[au:]` and `[length:]` header tags.
+pub(crate) fn write_tags(out: &mut String, song: &Song) {
+    let tags = [
+        ("ti", "title"),
+        ("ar", "artist"),
+        ("au", "writers"),
+        ("length", "duration"),
+    ];
+    for (tag, key) in tags {
+        if let Some(value) = song.meta_str(key) {
+            let _ = writeln!(out, "[{}:{}]", tag, value);
+        }
+    }
+}
+
+/// Repeat the time tag of `line` before its romanization, if it has one,
+/// which players that support translations show under the line.
+pub(crate) fn write_romanization(out: &mut String, line: &Line) {
+    let Some(romanized) = line.romanization() else {
+        return;
+    };
+    match line.timing {
+        Some(ts) => {
+            let _ = writeln!(out, "[{}]{}", lrc_time(ts), romanized.text);
+        }
+        None => {
+            let _ = writeln!(out, "{}", romanized.text);
+        }
+    }
+}
+
+/// Write an empty time tag at `end` when `next` starts a new stanza after
+/// the timed `line`.
+pub(crate) fn write_gap(
+    out: &mut String,
+    line: &Line,
+    next: Option<&Line>,
+    end: Option<Timestamp>,
+) {
+    if let (Some(_), Some(end), Some(true)) = (line.timing, end, next.map(|n| n.stanza_break)) {
+        let _ = writeln!(out, "[{}]", lrc_time(end));
+    }
+}
+
+impl Exporter for LrcExporter {
+    fn name(&self) -> &'static str {
+        "lrc"
+    }
+
+    fn extension(&self) -> &'static str {
+        "lrc"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let mut out = String::new();
+        write_tags(&mut out, song);
+
+        let lines: Vec<_> = song.lines().map(|(_, line)| line).collect();
+        for (i, end) in line_ends(song).into_iter().enumerate() {
+            let line = lines[i];
+            match line.timing {
+                Some(ts) => {
+                    let _ = writeln!(out, "[{}]{}", lrc_time(ts), line.text);
+                }
+                None => {
+                    let _ = writeln!(out, "{}", line.text);
+                }
+            }
+            write_romanization(&mut out, line);
+            write_gap(&mut out, line, lines.get(i + 1).copied(), end);
+        }
+        Ok(out.into_bytes())
+    }
+}
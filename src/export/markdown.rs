@@ -0,0 +1,102 @@
+use super::{ExportError, Exporter};
+use crate::ast::{Line, Song};
+use std::fmt::Write;
+
+/// How a song is laid out as Markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Heading level of section names, 2 to 6; the title is always `#`.
+    pub heading_level: usize,
+    /// Write each stanza as a blockquote rather than a plain paragraph.
+    pub blockquotes: bool,
+    /// Put a line's chords in a code span before its text.
+    pub chords: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            heading_level: 2,
+            blockquotes: true,
+            chords: false,
+        }
+    }
+}
+
+/// A Markdown lyric sheet in the convention [`crate::import::import_markdown`]
+/// reads back: the title as a `#` heading, the artist in italics, a heading
+/// per section and one blockquote per stanza, whose lines are joined by `\`
+/// hard breaks so renderers keep them apart.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownExporter {
+    pub options: MarkdownOptions,
+}
+
+/// Backslash-escape `text` so Markdown shows it as written.
+pub(crate) fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let list_marker = text
+        .find(|c: char| !c.is_ascii_digit())
+        .filter(|&i| i > 0 && text[i..].starts_with(['.', ')']));
+    for (i, c) in text.char_indices() {
+        let special =
+            "\\`*_[]<>|".contains(c) || (i == 0 && "#-+=".contains(c)) || list_marker == Some(i);
+        if special {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn line_markdown(line: &Line, options: &MarkdownOptions) -> String {
+    let mut out = String::new();
+    if options.chords && !line.chords.is_empty() {
+        let _ = write!(out, "`{}` ", line.chords.join(" "));
+    }
+    out.push_str(&escape_markdown(&line.text));
+    out
+}
+
+impl Exporter for MarkdownExporter {
+    fn name(&self) -> &'static str {
+        "md"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let options = &self.options;
+        let title = song.title().unwrap_or_else(|| "Untitled".to_string());
+        let mut out = format!("# {}\n", escape_markdown(&title));
+        if let Some(artist) = song.artist() {
+            let _ = writeln!(out, "\n*{}*", escape_markdown(&artist));
+        }
+
+        let quote = match options.blockquotes {
+            true => "> ",
+            false => "",
+        };
+        let heading = "#".repeat(options.heading_level.clamp(2, 6));
+        for section in &song.sections {
+            let _ = writeln!(out, "\n{} {}\n", heading, section.label());
+            for (i, line) in section.lines.iter().enumerate() {
+                let last = section
+                    .lines
+                    .get(i + 1)
+                    .is_none_or(|next| next.stanza_break);
+                if line.stanza_break && i > 0 {
+                    out.push('\n');
+                }
+                let _ = write!(out, "{}{}", quote, line_markdown(line, options));
+                out.push_str(match last {
+                    true => "\n",
+                    false => "\\\n",
+                });
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
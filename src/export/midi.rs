@@ -0,0 +1,142 @@
+use super::karaoke::{line_ends, word_timings};
+use super::{ExportError, Exporter};
+use crate::analysis::TempoMap;
+use crate::ast::{Song, TimeSignature, Timestamp};
+
+/// Standard MIDI File (format 1) carrying the tempo map and karaoke lyrics.
+///
+/// Track 0 holds the song title, its time signature (which is where MIDI
+/// puts the barlines) and a tempo event for every change in the tempo map.
+/// Track 1 holds one lyric meta event per word of each timed line, placed by
+/// converting its time to ticks through the tempo map. Following RP-026, a
+/// line ends with `\r` and a section with `\n`.
+pub struct MidiExporter;
+
+/// Ticks per quarter note.
+const PPQ: u16 = 480;
+
+const META: u8 = 0xFF;
+const TRACK_NAME: u8 = 0x03;
+const LYRIC: u8 = 0x05;
+const END_OF_TRACK: u8 = 0x2F;
+const SET_TEMPO: u8 = 0x51;
+const TIME_SIGNATURE: u8 = 0x58;
+
+struct Event {
+    tick: u64,
+    kind: u8,
+    data: Vec<u8>,
+}
+
+fn meta(tick: u64, kind: u8, data: impl Into<Vec<u8>>) -> Event {
+    Event {
+        tick,
+        kind,
+        data: data.into(),
+    }
+}
+
+fn ticks(tempo: &TempoMap, at: Timestamp) -> u64 {
+    (tempo.beat_at(at) * PPQ as f64).round() as u64
+}
+
+/// Append `value` as a MIDI variable-length quantity.
+fn write_vlq(out: &mut Vec<u8>, value: u64) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    let mut rest = value >> 7;
+    while rest > 0 {
+        bytes.push((rest & 0x7F) as u8 | 0x80);
+        rest >>= 7;
+    }
+    out.extend(bytes.iter().rev());
+}
+
+fn write_track(out: &mut Vec<u8>, mut events: Vec<Event>) {
+    events.sort_by_key(|e| e.tick);
+    let end = events.last().map_or(0, |e| e.tick);
+    events.push(meta(end, END_OF_TRACK, []));
+
+    let mut body = Vec::new();
+    let mut last = 0;
+    for event in events {
+        write_vlq(&mut body, event.tick - last);
+        last = event.tick;
+        body.extend([META, event.kind]);
+        write_vlq(&mut body, event.data.len() as u64);
+        body.extend(event.data);
+    }
+    out.extend(b"MTrk");
+    out.extend((body.len() as u32).to_be_bytes());
+    out.extend(body);
+}
+
+impl Exporter for MidiExporter {
+    fn name(&self) -> &'static str {
+        "midi"
+    }
+
+    fn extension(&self) -> &'static str {
+        "mid"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let tempo = TempoMap::from_song(song);
+
+        let time = song.time_signature().unwrap_or(TimeSignature::COMMON);
+        let click = (96 / time.unit).max(1) as u8;
+        let mut conductor = vec![meta(
+            0,
+            TIME_SIGNATURE,
+            [time.beats as u8, time.unit.trailing_zeros() as u8, click, 8],
+        )];
+        if let Some(title) = song.title() {
+            conductor.push(meta(0, TRACK_NAME, title.into_bytes()));
+        }
+        for (at, bpm) in tempo.changes() {
+            let micros = (60_000_000.0 / bpm).round() as u32;
+            conductor.push(meta(
+                ticks(&tempo, at),
+                SET_TEMPO,
+                &micros.to_be_bytes()[1..],
+            ));
+        }
+
+        let mut lyrics = vec![meta(0, TRACK_NAME, "Lyrics")];
+        let ends = line_ends(song);
+        let mut index = 0;
+        for section in &song.sections {
+            for (i, line) in section.lines.iter().enumerate() {
+                let end = ends[index];
+                index += 1;
+                let Some(begin) = line.timing else {
+                    continue;
+                };
+                let timings = word_timings(line, begin, end);
+                for (w, word) in timings.iter().enumerate() {
+                    let mut text = match timings.get(w + 1) {
+                        Some(next) => line.text[word.range.start..next.range.start].to_string(),
+                        None => line.text[word.range.start..].to_string(),
+                    };
+                    if w + 1 == timings.len() {
+                        text.push(if i + 1 == section.lines.len() {
+                            '\n'
+                        } else {
+                            '\r'
+                        });
+                    }
+                    lyrics.push(meta(ticks(&tempo, word.begin), LYRIC, text.into_bytes()));
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        out.extend(b"MThd");
+        out.extend(6u32.to_be_bytes());
+        out.extend(1u16.to_be_bytes());
+        out.extend(2u16.to_be_bytes());
+        out.extend(PPQ.to_be_bytes());
+        write_track(&mut out, conductor);
+        write_track(&mut out, lyrics);
+        Ok(out)
+    }
+}
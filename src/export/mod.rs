@@ -0,0 +1,326 @@
+//! Exporters turning a parsed [`Song`] into other file formats.
+//!
+//! Each format implements [`Exporter`]; [`exporters`] is the registry used by
+//! the `export` and `self-test` commands, so a new format only has to be added
+//! there to become available everywhere.
+
+use crate::ast::{blank_ranges, Line, Song};
+use crate::chords::diagram::Fretboard;
+use crate::feedback::Comments;
+use crate::template::Layout;
+use thiserror::Error;
+
+pub mod budget;
+pub mod bundle;
+mod docx;
+mod elrc;
+mod graph;
+mod html;
+mod json;
+pub mod karaoke;
+mod lrc;
+mod markdown;
+mod midi;
+mod musicxml;
+mod pdf;
+mod pptx;
+mod review;
+pub mod slides;
+mod split_sheet;
+mod tar;
+mod text;
+pub(crate) mod ttml;
+pub(crate) mod zip;
+
+pub use budget::{Budget, Wrap};
+pub use elrc::EnhancedLrcExporter;
+pub use graph::{D2Exporter, DotExporter};
+pub use html::HtmlExporter;
+pub use json::JsonExporter;
+pub use lrc::LrcExporter;
+pub use markdown::{MarkdownExporter, MarkdownOptions};
+pub use midi::MidiExporter;
+pub use musicxml::MusicXmlExporter;
+pub use pdf::{list_pdf, PdfExporter};
+pub use pptx::PptxExporter;
+pub use review::{ReviewDocxExporter, ReviewPdfExporter};
+pub use slides::{SlideOptions, SlidesExporter};
+pub use split_sheet::{split_sheet_docx, split_sheet_pdf};
+pub use text::TextExporter;
+pub use ttml::TtmlExporter;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("unknown export format `{0}`")]
+    UnknownFormat(String),
+    #[error("`{format}` export does not support {option}")]
+    Unsupported {
+        format: String,
+        option: &'static str,
+    },
+    #[error("failed to serialize song: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("template: {0}")]
+    Template(#[from] crate::template::TemplateError),
+}
+
+/// A target format for rendered lyrics.
+pub trait Exporter {
+    /// Format name used on the command line, e.g. `lrc`.
+    fn name(&self) -> &'static str;
+
+    /// File extension for the rendered output, without the leading dot.
+    fn extension(&self) -> &'static str;
+
+    /// Render `song`. Output must be deterministic for a given input.
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError>;
+
+    /// How long a row the format shows, for formats that wrap long lines.
+    fn budget(&self) -> Option<Budget> {
+        None
+    }
+}
+
+/// The tightest budget among the exporters named in `formats`, with the
+/// name of the format it comes from. Unknown names are skipped.
+pub fn tightest_budget<'a>(
+    formats: impl IntoIterator<Item = &'a str>,
+) -> Option<(&'static str, Budget)> {
+    formats
+        .into_iter()
+        .filter_map(|name| exporter(name.trim()).ok())
+        .filter_map(|e| Some((e.name(), e.budget()?)))
+        .min_by_key(|(_, budget)| budget.width)
+}
+
+/// All built-in exporters in a stable order.
+pub fn exporters() -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(JsonExporter),
+        Box::new(LrcExporter),
+        Box::new(EnhancedLrcExporter),
+        Box::new(HtmlExporter::default()),
+        Box::new(MarkdownExporter::default()),
+        Box::new(MidiExporter),
+        Box::new(MusicXmlExporter),
+        Box::new(PdfExporter::default()),
+        Box::new(PptxExporter::default()),
+        Box::new(SlidesExporter::default()),
+        Box::new(TextExporter),
+        Box::new(TtmlExporter),
+        Box::new(DotExporter),
+        Box::new(D2Exporter),
+        Box::new(ReviewPdfExporter),
+        Box::new(ReviewDocxExporter),
+    ]
+}
+
+/// Look up an exporter by its format name.
+pub fn exporter(name: &str) -> Result<Box<dyn Exporter>, ExportError> {
+    exporters()
+        .into_iter()
+        .find(|e| e.name() == name)
+        .ok_or_else(|| ExportError::UnknownFormat(name.to_string()))
+}
+
+/// What lyric sheets do with the `+` harmony lines under a lead line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Harmony {
+    /// Set in italics under the lead line.
+    #[default]
+    Include,
+    /// Left out; see [`without_harmony`].
+    Exclude,
+    /// Set under the lead line with each part in its own color, so every
+    /// singer can follow theirs.
+    Separate,
+}
+
+impl Harmony {
+    pub const NAMES: [&'static str; 3] = ["include", "exclude", "separate"];
+
+    pub fn parse(name: &str) -> Option<Harmony> {
+        match name {
+            "include" => Some(Harmony::Include),
+            "exclude" => Some(Harmony::Exclude),
+            "separate" => Some(Harmony::Separate),
+            _ => None,
+        }
+    }
+}
+
+/// `song` with only its lead lines, for formats that would otherwise show
+/// the harmony parts too.
+pub fn without_harmony(song: &Song) -> Song {
+    let mut song = song.clone();
+    for section in &mut song.sections {
+        for line in &mut section.lines {
+            line.harmony.clear();
+        }
+    }
+    song
+}
+
+/// `song` without its `____` and `<tbd>` placeholders, and without the
+/// lines that were nothing else, for a copy to share before the gaps are
+/// filled in.
+pub fn without_placeholders(song: &Song) -> Song {
+    fn hide(lines: &mut Vec<Line>) {
+        for line in lines.iter_mut() {
+            for range in blank_ranges(&line.text).into_iter().rev() {
+                // The blank goes with the space before it, or after it at
+                // the start of the line.
+                let text = &mut line.text;
+                let start = match text[..range.start].ends_with(' ') {
+                    true => range.start - 1,
+                    false => range.start,
+                };
+                let end = match start == range.start && text[range.end..].starts_with(' ') {
+                    true => range.end + 1,
+                    false => range.end,
+                };
+                text.replace_range(start..end, "");
+                for ruby in line.rubies.iter_mut().filter(|r| r.at >= end) {
+                    ruby.at -= end - start;
+                }
+            }
+            line.placeholders.clear();
+            hide(&mut line.harmony);
+        }
+        // A stanza that started on a line left out starts on the next.
+        let mut stanza_break = false;
+        lines.retain_mut(|line| {
+            if line.include.is_none() && line.text.trim().is_empty() {
+                stanza_break |= line.stanza_break;
+                return false;
+            }
+            line.stanza_break |= std::mem::take(&mut stanza_break);
+            true
+        });
+    }
+    let mut song = song.clone();
+    for section in &mut song.sections {
+        hide(&mut section.lines);
+    }
+    song
+}
+
+/// `song` with only the take of each line that is sung, leaving out its
+/// `/ alt: "..."` alternatives.
+pub fn without_alternatives(song: &Song) -> Song {
+    let mut song = song.clone();
+    for section in &mut song.sections {
+        for line in &mut section.lines {
+            line.alternatives.clear();
+        }
+    }
+    song
+}
+
+/// How the `html` and `pdf` lyric sheets are set.
+#[derive(Debug, Clone, Default)]
+pub struct SheetOptions {
+    /// Append diagrams of the song's chords on this fretboard.
+    pub chord_diagrams: Option<Fretboard>,
+    /// Curly quotes, dashes and ellipses; see [`crate::typography`].
+    pub smart_typography: bool,
+    pub harmony: Harmony,
+    /// Fill in this template instead; only the `html` sheet has one.
+    pub template: Option<Layout>,
+    /// Review comments to set beside the lines they are about.
+    pub comments: Comments,
+}
+
+/// The `html` or `pdf` exporter set to append diagrams of the song's chords.
+pub fn with_chord_diagrams(
+    name: &str,
+    fretboard: Fretboard,
+) -> Result<Box<dyn Exporter>, ExportError> {
+    let options = SheetOptions {
+        chord_diagrams: Some(fretboard),
+        ..SheetOptions::default()
+    };
+    with_sheet_options(name, options)
+}
+
+/// The `html` or `pdf` exporter with its sheet options changed.
+pub fn with_sheet_options(
+    name: &str,
+    options: SheetOptions,
+) -> Result<Box<dyn Exporter>, ExportError> {
+    let SheetOptions {
+        chord_diagrams,
+        smart_typography,
+        harmony,
+        template,
+        comments,
+    } = options;
+    match name {
+        "html" => Ok(Box::new(HtmlExporter {
+            chord_diagrams,
+            smart_typography,
+            harmony,
+            template,
+            comments,
+        })),
+        // The PDF sheet is drawn rather than written out, so there is no
+        // markup for a template to give.
+        "pdf" if template.is_some() => Err(ExportError::Unsupported {
+            format: name.to_string(),
+            option: "templates",
+        }),
+        "pdf" => Ok(Box::new(PdfExporter {
+            chord_diagrams,
+            smart_typography,
+            harmony,
+            comments,
+        })),
+        _ => {
+            exporter(name)?;
+            Err(ExportError::Unsupported {
+                format: name.to_string(),
+                option: match (chord_diagrams, smart_typography, template) {
+                    (Some(_), _, _) => "chord diagrams",
+                    (None, true, _) => "smart typography",
+                    (None, false, Some(_)) => "templates",
+                    (None, false, None) => "harmony colors",
+                },
+            })
+        }
+    }
+}
+
+/// The `pptx` or `slides` exporter with its slide layout changed.
+pub fn with_slide_options(
+    name: &str,
+    options: SlideOptions,
+) -> Result<Box<dyn Exporter>, ExportError> {
+    match name {
+        "pptx" => Ok(Box::new(PptxExporter { options })),
+        "slides" => Ok(Box::new(SlidesExporter { options })),
+        _ => {
+            exporter(name)?;
+            Err(ExportError::Unsupported {
+                format: name.to_string(),
+                option: "slide options",
+            })
+        }
+    }
+}
+
+/// The `md` exporter with its layout changed.
+pub fn with_markdown_options(
+    name: &str,
+    options: MarkdownOptions,
+) -> Result<Box<dyn Exporter>, ExportError> {
+    match name {
+        "md" => Ok(Box::new(MarkdownExporter { options })),
+        _ => {
+            exporter(name)?;
+            Err(ExportError::Unsupported {
+                format: name.to_string(),
+                option: "Markdown options",
+            })
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::ttml::escape;
+use super::{ExportError, Exporter};
+use crate::analysis::meter::line_bars;
+use crate::analysis::TempoMap;
+use crate::ast::{word_ranges, Hold, Line, Song, TimeSignature, Timestamp};
+use crate::rights::{splits, Isrc, Iswc};
+use std::fmt::Write;
+
+/// MusicXML lead sheet with the lyrics on a one-line rhythm staff.
+///
+/// Every bar of a line, as divided by its `|` markers, fills one measure of
+/// the song's time signature; a line without markers is a single measure.
+/// Words split their measure evenly, with melisma beats weighting a word the
+/// same way they do for karaoke timing, and empty bars become whole-measure
+/// rests. Sections open with a rehearsal mark and close with a double bar.
+///
+/// The `iswc` is the work number, each of the `publishers` a `rights` line
+/// with its share, and the `isrc` a miscellaneous field, so a publisher's
+/// system reads them with the score.
+pub struct MusicXmlExporter;
+
+/// Divisions per quarter note.
+const DIVISIONS: u64 = 480;
+
+fn measure(out: &mut String, number: usize, body: &str, barline: Option<&str>) {
+    let _ = writeln!(out, "    <measure number=\"{}\">", number);
+    out.push_str(body);
+    if let Some(style) = barline {
+        let _ = writeln!(
+            out,
+            "      <barline location=\"right\"><bar-style>{}</bar-style></barline>",
+            style
+        );
+    }
+    out.push_str("    </measure>\n");
+}
+
+/// Notes for the words `first..last` of `line`, filling `length` divisions.
+fn notes(line: &Line, words: &[&str], first: usize, length: u64) -> String {
+    let mut out = String::new();
+    if words.is_empty() {
+        let _ = writeln!(
+            out,
+            "      <note><rest measure=\"yes\"/><duration>{}</duration></note>",
+            length
+        );
+        return out;
+    }
+    let weight = |i: usize| match line.sustain(first + i) {
+        Some(Hold::Beats(n)) => 1 + n as u64,
+        _ => 1,
+    };
+    let total: u64 = (0..words.len()).map(weight).sum();
+    let mut so_far = 0;
+    for (i, word) in words.iter().enumerate() {
+        let start = length * so_far / total;
+        so_far += weight(i);
+        let duration = length * so_far / total - start;
+        let _ = writeln!(
+            out,
+            "      <note><unpitched><display-step>B</display-step><display-octave>4</display-octave></unpitched><duration>{}</duration><lyric number=\"1\"><syllabic>single</syllabic><text>{}</text></lyric></note>",
+            duration,
+            escape(word)
+        );
+    }
+    out
+}
+
+impl Exporter for MusicXmlExporter {
+    fn name(&self) -> &'static str {
+        "musicxml"
+    }
+
+    fn extension(&self) -> &'static str {
+        "musicxml"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let time = song.time_signature().unwrap_or(TimeSignature::COMMON);
+        let length = (time.bar_quarters() * DIVISIONS as f64).round() as u64;
+        let bpm = TempoMap::from_song(song).bpm_at(Timestamp::default());
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+        out.push_str("<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \"http://www.musicxml.org/dtds/partwise.dtd\">\n");
+        out.push_str("<score-partwise version=\"4.0\">\n");
+        let iswc = song.meta_str("iswc").and_then(|c| Iswc::parse(&c).ok());
+        let mut work = String::new();
+        if let Some(iswc) = &iswc {
+            let _ = write!(work, "<work-number>{}</work-number>", iswc);
+        }
+        if let Some(title) = song.title() {
+            let _ = write!(work, "<work-title>{}</work-title>", escape(&title));
+        }
+        if !work.is_empty() {
+            let _ = writeln!(out, "  <work>{}</work>", work);
+        }
+        let mut identification = String::new();
+        if let Some(writers) = song.meta_str("writers") {
+            let _ = write!(
+                identification,
+                "<creator type=\"lyricist\">{}</creator>",
+                escape(&writers)
+            );
+        }
+        let publishers = song.meta_str("publishers");
+        for share in publishers
+            .as_deref()
+            .and_then(|p| splits(p).ok())
+            .unwrap_or_default()
+        {
+            let _ = write!(
+                identification,
+                "<rights type=\"publisher\">{} ({}%)</rights>",
+                escape(&share.publisher),
+                share.percent
+            );
+        }
+        if let Some(isrc) = song.meta_str("isrc").and_then(|c| Isrc::parse(&c).ok()) {
+            let _ = write!(
+                identification,
+                "<miscellaneous><miscellaneous-field name=\"isrc\">{}</miscellaneous-field></miscellaneous>",
+                isrc
+            );
+        }
+        if !identification.is_empty() {
+            let _ = writeln!(out, "  <identification>{}</identification>", identification);
+        }
+        out.push_str("  <part-list>\n    <score-part id=\"P1\"><part-name>Voice</part-name></score-part>\n  </part-list>\n");
+        out.push_str("  <part id=\"P1\">\n");
+
+        let mut number = 0;
+        for (s, section) in song.sections.iter().enumerate() {
+            let bars: Vec<(&Line, Vec<&str>, usize)> = section
+                .lines
+                .iter()
+                .flat_map(|line| {
+                    let words: Vec<&str> = word_ranges(&line.text)
+                        .into_iter()
+                        .map(|r| &line.text[r])
+                        .collect();
+                    line_bars(line, words.len())
+                        .into_iter()
+                        .map(move |bar| (line, words[bar.clone()].to_vec(), bar.start))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            for (b, (line, words, first)) in bars.iter().enumerate() {
+                number += 1;
+                let mut body = String::new();
+                if number == 1 {
+                    let _ = writeln!(
+                        body,
+                        "      <attributes><divisions>{}</divisions><time><beats>{}</beats><beat-type>{}</beat-type></time><clef><sign>percussion</sign></clef><staff-details><staff-lines>1</staff-lines></staff-details></attributes>",
+                        DIVISIONS, time.beats, time.unit
+                    );
+                    let _ = writeln!(
+                        body,
+                        "      <direction placement=\"above\"><direction-type><metronome><beat-unit>quarter</beat-unit><per-minute>{}</per-minute></metronome></direction-type><sound tempo=\"{}\"/></direction>",
+                        bpm, bpm
+                    );
+                }
+                if b == 0 {
+                    let _ = writeln!(
+                        body,
+                        "      <direction placement=\"above\"><direction-type><rehearsal>{}</rehearsal></direction-type></direction>",
+                        escape(&section.label())
+                    );
+                }
+                body.push_str(&notes(line, words, *first, length));
+                let barline = if b + 1 < bars.len() {
+                    None
+                } else if s + 1 == song.sections.len() {
+                    Some("light-heavy")
+                } else {
+                    Some("light-light")
+                };
+                measure(&mut out, number, &body, barline);
+            }
+        }
+        out.push_str("  </part>\n</score-partwise>\n");
+        Ok(out.into_bytes())
+    }
+}
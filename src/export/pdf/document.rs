@@ -0,0 +1,278 @@
+//! A minimal PDF writer: A4 pages of text in the standard Helvetica faces,
+//! straight lines and circles. Text is encoded as WinAnsi, so characters
+//! outside Latin-1 and common punctuation are replaced with `?`.
+
+use std::fmt::Write;
+
+pub const PAGE_WIDTH: f64 = 595.0;
+pub const PAGE_HEIGHT: f64 = 842.0;
+pub const MARGIN: f64 = 56.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+    Oblique,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Oblique => "F3",
+        }
+    }
+}
+
+/// Helvetica advance widths of the printable ASCII characters, in
+/// thousandths of the font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Approximate width of `text` set in `font` at `size` points. Bold is
+/// estimated from the regular metrics.
+pub fn text_width(text: &str, font: Font, size: f64) -> f64 {
+    let units: f64 = text
+        .chars()
+        .map(|c| match c as u32 {
+            32..=126 => HELVETICA_WIDTHS[c as usize - 32] as f64,
+            _ => 556.0,
+        })
+        .sum();
+    let scale = if font == Font::Bold { 1.07 } else { 1.0 };
+    units * scale * size / 1000.0
+}
+
+/// `text` broken at spaces into lines no wider than `width`.
+pub fn wrap(text: &str, font: Font, size: f64, width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = match line.is_empty() {
+            true => word.to_string(),
+            false => format!("{} {}", line, word),
+        };
+        if !line.is_empty() && text_width(&candidate, font, size) > width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// The WinAnsi byte for `c`, if it has one.
+fn win_ansi(c: char) -> Option<u8> {
+    Some(match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        _ => return None,
+    })
+}
+
+/// `text` as a PDF string literal.
+fn literal(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        match win_ansi(c).unwrap_or(b'?') {
+            b @ (b'(' | b')' | b'\\') => out.extend([b'\\', b]),
+            b => out.push(b),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+/// Pages under construction. Coordinates passed in are measured from the
+/// top-left corner, as in a layout, and flipped when written.
+pub struct Document {
+    pages: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    /// The layout cursor, in points from the top of the page.
+    pub y: f64,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Document {
+            pages: Vec::new(),
+            current: Vec::new(),
+            y: MARGIN,
+        }
+    }
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_page(&mut self) {
+        let page = std::mem::take(&mut self.current);
+        self.pages.push(page);
+        self.y = MARGIN;
+    }
+
+    /// Start a new page unless `height` more points fit on this one.
+    pub fn ensure(&mut self, height: f64) {
+        if self.y + height > PAGE_HEIGHT - MARGIN && self.y > MARGIN {
+            self.new_page();
+        }
+    }
+
+    /// Text with its baseline at `y`.
+    pub fn text(&mut self, x: f64, y: f64, font: Font, size: f64, text: &str) {
+        let _ = write!(
+            Ops(&mut self.current),
+            "BT /{} {} Tf {:.2} {:.2} Td ",
+            font.resource(),
+            size,
+            x,
+            PAGE_HEIGHT - y
+        );
+        self.current.extend(literal(text));
+        self.current.extend(b" Tj ET\n");
+    }
+
+    /// Text centred on `x`.
+    pub fn text_centered(&mut self, x: f64, y: f64, font: Font, size: f64, text: &str) {
+        let width = text_width(text, font, size);
+        self.text(x - width / 2.0, y, font, size, text);
+    }
+
+    /// Set the fill colour for following text and shapes.
+    pub fn color(&mut self, r: f64, g: f64, b: f64) {
+        let _ = writeln!(Ops(&mut self.current), "{:.2} {:.2} {:.2} rg", r, g, b);
+    }
+
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, width: f64) {
+        let _ = writeln!(
+            Ops(&mut self.current),
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width,
+            x1,
+            PAGE_HEIGHT - y1,
+            x2,
+            PAGE_HEIGHT - y2
+        );
+    }
+
+    /// A circle drawn from four Bézier arcs, filled or stroked.
+    pub fn circle(&mut self, cx: f64, cy: f64, r: f64, filled: bool) {
+        const K: f64 = 0.5523;
+        let cy = PAGE_HEIGHT - cy;
+        let k = r * K;
+        let _ = writeln!(
+            Ops(&mut self.current),
+            "1 w {:.2} {:.2} m {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c \
+             {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c {}",
+            cx + r, cy,
+            cx + r, cy + k, cx + k, cy + r, cx, cy + r,
+            cx - k, cy + r, cx - r, cy + k, cx - r, cy,
+            cx - r, cy - k, cx - k, cy - r, cx, cy - r,
+            cx + k, cy - r, cx + r, cy - k, cx + r, cy,
+            if filled { "f" } else { "S" }
+        );
+    }
+
+    /// Serialize the document with `title` in its metadata.
+    pub fn finish(mut self, title: &str) -> Vec<u8> {
+        if !self.current.is_empty() || self.pages.is_empty() {
+            self.new_page();
+        }
+        let fonts = ["Helvetica", "Helvetica-Bold", "Helvetica-Oblique"];
+        let first_page = 3 + fonts.len() + 1;
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", first_page + 2 * i))
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+        for font in fonts {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font
+                )
+                .into_bytes(),
+            );
+        }
+        let mut info = b"<< /Title ".to_vec();
+        info.extend(literal(title));
+        info.extend(b" /Producer (lyrics-dsl) >>");
+        objects.push(info);
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    first_page + 2 * i + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend(content);
+            stream.extend(b"endstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).bytes());
+            out.extend(object);
+            out.extend(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                3 + fonts.len(),
+                xref
+            )
+            .bytes(),
+        );
+        out
+    }
+}
+
+/// `fmt::Write` over a content stream, which is always ASCII here.
+struct Ops<'a>(&'a mut Vec<u8>);
+
+impl Write for Ops<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.extend(s.as_bytes());
+        Ok(())
+    }
+}
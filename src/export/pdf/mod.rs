@@ -0,0 +1,383 @@
+use super::{ExportError, Exporter, Harmony};
+use crate::ast::{Line, Ruby, Song};
+use crate::chords::capo::{shapes, song_capo};
+use crate::chords::diagram::{diagrams, ChordDiagram, Fretboard, DIAGRAM_FRETS};
+use crate::credits::citations;
+use crate::feedback::Comments;
+use crate::typography::smarten_song;
+
+pub(crate) mod document;
+
+use document::{text_width, wrap, Document, Font, MARGIN, PAGE_WIDTH};
+
+/// A printable A4 lyric sheet. Like the HTML sheet it shows each line's
+/// chords before its text, with `chord_diagrams` set it ends with a
+/// diagram of every chord used, played as shapes above any `capo`, and with
+/// `smart_typography` it sets curly quotes, dashes and ellipses. Harmony
+/// lines are set in oblique type under their lead line, and interpolation
+/// and sample credits follow the lyrics. Review `comments` are set in a
+/// column on the right, level with the lines they are about.
+#[derive(Debug, Clone, Default)]
+pub struct PdfExporter {
+    pub chord_diagrams: Option<Fretboard>,
+    pub smart_typography: bool,
+    pub harmony: Harmony,
+    pub comments: Comments,
+}
+
+const LINE_SIZE: f64 = 11.0;
+const LEADING: f64 = 15.0;
+const RUBY_SIZE: f64 = 5.5;
+/// Where the column of review comments starts, and their type.
+const COMMENT_X: f64 = PAGE_WIDTH - MARGIN - 150.0;
+const COMMENT_SIZE: f64 = 8.0;
+const COMMENT_LEADING: f64 = 10.0;
+/// Colors the harmony parts under a line take in turn when set apart, as
+/// in the HTML sheet.
+const PART_COLORS: [(f64, f64, f64); 3] =
+    [(0.16, 0.44, 0.69), (0.18, 0.52, 0.25), (0.61, 0.36, 0.0)];
+
+/// Write `text` from `x`, wrapping at word boundaries before `limit`;
+/// continuation lines are indented to `indent`. `rubies` are set in small
+/// type above their kanji, with room made for them on each line.
+fn wrapped(
+    doc: &mut Document,
+    x: f64,
+    indent: f64,
+    limit: f64,
+    font: Font,
+    text: &str,
+    rubies: &[Ruby],
+) {
+    let leading = match rubies.is_empty() {
+        true => LEADING,
+        false => LEADING + RUBY_SIZE,
+    };
+    let mut line = String::new();
+    let mut line_start = 0;
+    let mut start = x;
+    let mut offset = 0;
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if !line.is_empty() && start + text_width(&candidate, font, LINE_SIZE) > limit {
+            set_line(doc, start, font, &line, line_start, rubies);
+            doc.y += leading;
+            doc.ensure(leading);
+            start = indent;
+            line = word.to_string();
+            line_start = offset;
+        } else {
+            line = candidate;
+        }
+        offset += word.len() + 1;
+    }
+    set_line(doc, start, font, &line, line_start, rubies);
+}
+
+/// One wrapped line, `text` starting at byte `from` of the lyric line,
+/// with the furigana of the kanji it holds.
+fn set_line(doc: &mut Document, x: f64, font: Font, text: &str, from: usize, rubies: &[Ruby]) {
+    doc.text(x, doc.y, font, LINE_SIZE, text);
+    for ruby in rubies {
+        let Some(before) = ruby.at.checked_sub(from).and_then(|at| text.get(..at)) else {
+            continue;
+        };
+        if !text[before.len()..].starts_with(&ruby.base) {
+            continue;
+        }
+        let centre =
+            x + text_width(before, font, LINE_SIZE) + text_width(&ruby.base, font, LINE_SIZE) / 2.0;
+        let y = doc.y - LINE_SIZE;
+        doc.text_centered(centre, y, Font::Regular, RUBY_SIZE, &ruby.reading);
+    }
+}
+
+/// Draw `diagram` with its top-left corner at (`x`, `y`).
+fn draw_diagram(doc: &mut Document, diagram: &ChordDiagram, x: f64, y: f64) {
+    let strings = diagram.frets.len();
+    let (spacing, fret_height) = (12.0, 14.0);
+    let left = x + 14.0;
+    let right = left + spacing * (strings - 1) as f64;
+    let top = y + 30.0;
+    let bottom = top + fret_height * DIAGRAM_FRETS as f64;
+
+    doc.text_centered(
+        (left + right) / 2.0,
+        y + 10.0,
+        Font::Bold,
+        10.0,
+        &diagram.name,
+    );
+    for s in 0..strings {
+        let sx = left + spacing * s as f64;
+        doc.line(sx, top, sx, bottom, 0.6);
+    }
+    for f in 0..=DIAGRAM_FRETS {
+        let fy = top + fret_height * f as f64;
+        let width = if f == 0 && diagram.base_fret == 1 {
+            3.0
+        } else {
+            0.6
+        };
+        doc.line(left, fy, right, fy, width);
+    }
+    if diagram.base_fret > 1 {
+        let label = format!("{}fr", diagram.base_fret);
+        let width = text_width(&label, Font::Regular, 7.0);
+        doc.text(
+            left - width - 3.0,
+            top + fret_height * 0.7,
+            Font::Regular,
+            7.0,
+            &label,
+        );
+    }
+    for (s, (fret, finger)) in diagram.frets.iter().zip(&diagram.fingers).enumerate() {
+        let sx = left + spacing * s as f64;
+        match fret {
+            None => doc.text_centered(sx, top - 4.0, Font::Regular, 8.0, "x"),
+            Some(0) => doc.circle(sx, top - 7.0, 3.0, false),
+            Some(f) => {
+                let row = (f + 1 - diagram.base_fret) as f64;
+                doc.circle(sx, top + fret_height * (row - 0.5), 4.5, true);
+                if let Some(finger) = finger {
+                    let label = finger.to_string();
+                    doc.text_centered(sx, bottom + 11.0, Font::Regular, 8.0, &label);
+                }
+            }
+        }
+    }
+}
+
+/// A one-page list: `title` in bold, then one line per item, such as the
+/// cheat sheet of a setlist.
+pub fn list_pdf(title: &str, items: &[String]) -> Vec<u8> {
+    let mut doc = Document::new();
+    doc.y += 20.0;
+    doc.text(MARGIN, doc.y, Font::Bold, 20.0, title);
+    doc.y += 28.0;
+    for item in items {
+        doc.ensure(LEADING);
+        let limit = PAGE_WIDTH - MARGIN;
+        wrapped(
+            &mut doc,
+            MARGIN,
+            MARGIN + 12.0,
+            limit,
+            Font::Regular,
+            item,
+            &[],
+        );
+        doc.y += LEADING + 4.0;
+    }
+    doc.finish(title)
+}
+
+impl PdfExporter {
+    /// Several songs in one document, each starting on a new page, e.g. the
+    /// songs of a setlist in performance order.
+    pub fn export_all(&self, title: &str, songs: &[Song]) -> Vec<u8> {
+        let mut doc = Document::new();
+        for (i, song) in songs.iter().enumerate() {
+            if i > 0 {
+                doc.new_page();
+            }
+            self.render(&mut doc, &self.typeset(song));
+        }
+        doc.finish(title)
+    }
+
+    /// `song` as it is printed.
+    fn typeset(&self, song: &Song) -> Song {
+        match self.smart_typography {
+            true => smarten_song(song),
+            false => song.clone(),
+        }
+    }
+
+    /// The review comments on `line`, broken to fit the comment column.
+    fn notes(&self, line: &Line) -> Vec<String> {
+        let width = PAGE_WIDTH - MARGIN - COMMENT_X;
+        let comments = self.comments.on(line).iter();
+        comments
+            .flat_map(|c| wrap(&c.note(), Font::Oblique, COMMENT_SIZE, width))
+            .collect()
+    }
+
+    /// Set `notes` in the comment column from the baseline `top` down.
+    fn margin_notes(doc: &mut Document, notes: &[String], top: f64) {
+        if notes.is_empty() {
+            return;
+        }
+        doc.color(0.61, 0.36, 0.0);
+        for (i, note) in notes.iter().enumerate() {
+            let y = top + COMMENT_LEADING * i as f64;
+            doc.text(COMMENT_X, y, Font::Oblique, COMMENT_SIZE, note);
+            doc.y = doc.y.max(y);
+        }
+        doc.color(0.0, 0.0, 0.0);
+    }
+
+    /// Room for a line with `notes` beside it.
+    fn room(notes: &[String]) -> f64 {
+        LEADING.max(COMMENT_LEADING * notes.len() as f64)
+    }
+
+    fn render(&self, doc: &mut Document, song: &Song) {
+        // The lyrics leave room on the right for any review comments.
+        let limit = match self.comments.is_empty() {
+            true => PAGE_WIDTH - MARGIN,
+            false => COMMENT_X - 12.0,
+        };
+        let title = song.title().unwrap_or_else(|| "Untitled".to_string());
+        doc.y += 20.0;
+        doc.text(MARGIN, doc.y, Font::Bold, 20.0, &title);
+        doc.y += 18.0;
+        if let Some(artist) = song.artist() {
+            doc.color(0.4, 0.4, 0.4);
+            doc.text(MARGIN, doc.y, Font::Regular, 12.0, &artist);
+            doc.color(0.0, 0.0, 0.0);
+            doc.y += 18.0;
+        }
+        let capo = song_capo(song).unwrap_or(0);
+        if capo > 0 {
+            doc.text(MARGIN, doc.y, Font::Bold, 11.0, &format!("Capo {}", capo));
+            doc.y += 18.0;
+        }
+
+        for section in &song.sections {
+            doc.y += 12.0;
+            doc.ensure(LEADING * 2.0);
+            doc.color(0.33, 0.33, 0.33);
+            doc.text(
+                MARGIN,
+                doc.y,
+                Font::Bold,
+                10.0,
+                &section.label().to_uppercase(),
+            );
+            doc.color(0.0, 0.0, 0.0);
+            doc.y += LEADING;
+            for line in &section.lines {
+                if line.stanza_break {
+                    doc.y += LEADING / 2.0;
+                }
+                if !line.rubies.is_empty() {
+                    doc.y += RUBY_SIZE;
+                }
+                let notes = self.notes(line);
+                doc.ensure(Self::room(&notes));
+                let top = doc.y;
+                let mut x = MARGIN;
+                if !line.chords.is_empty() {
+                    let chords = shapes(&line.chords, capo).join(" ");
+                    doc.color(0.67, 0.2, 0.2);
+                    doc.text(x, doc.y, Font::Bold, 9.0, &chords);
+                    doc.color(0.0, 0.0, 0.0);
+                    x += text_width(&chords, Font::Bold, 9.0) + 10.0;
+                }
+                let (indent, font) = (MARGIN + 12.0, Font::Regular);
+                wrapped(doc, x, indent, limit, font, &line.text, &line.rubies);
+                Self::margin_notes(doc, &notes, top);
+                doc.y += LEADING;
+                if self.harmony == Harmony::Exclude {
+                    continue;
+                }
+                for (i, part) in line.harmony.iter().enumerate() {
+                    let (r, g, b) = match self.harmony {
+                        Harmony::Separate => PART_COLORS[i % PART_COLORS.len()],
+                        _ => (0.4, 0.4, 0.4),
+                    };
+                    let notes = self.notes(part);
+                    doc.ensure(Self::room(&notes));
+                    let top = doc.y;
+                    doc.color(r, g, b);
+                    let indent = MARGIN + 18.0;
+                    let (x, font) = (indent, Font::Oblique);
+                    wrapped(doc, x, x + 12.0, limit, font, &part.text, &part.rubies);
+                    doc.color(0.0, 0.0, 0.0);
+                    Self::margin_notes(doc, &notes, top);
+                    doc.y += LEADING;
+                }
+            }
+        }
+
+        let citations = citations(song);
+        if !citations.is_empty() {
+            doc.y += 12.0;
+            doc.color(0.4, 0.4, 0.4);
+            for citation in &citations {
+                doc.ensure(LEADING);
+                let width = PAGE_WIDTH - MARGIN;
+                wrapped(
+                    doc,
+                    MARGIN,
+                    MARGIN + 12.0,
+                    width,
+                    Font::Oblique,
+                    citation,
+                    &[],
+                );
+                doc.y += LEADING;
+            }
+            doc.color(0.0, 0.0, 0.0);
+        }
+
+        if let Some(fretboard) = &self.chord_diagrams {
+            let chords: Vec<String> = song
+                .lines()
+                .flat_map(|(_, l)| shapes(&l.chords, capo))
+                .collect();
+            let (found, unknown) = diagrams(fretboard, chords.iter().map(String::as_str));
+            let cell_width = 14.0 * 2.0 + 12.0 * (fretboard.tuning.len() - 1) as f64 + 12.0;
+            let cell_height = 30.0 + 14.0 * DIAGRAM_FRETS as f64 + 24.0;
+            if !found.is_empty() || !unknown.is_empty() {
+                doc.y += 18.0;
+                doc.ensure(LEADING + cell_height);
+                let heading = format!(
+                    "CHORDS ({}, {})",
+                    fretboard.instrument.name(),
+                    fretboard.tuning_name()
+                );
+                doc.text(MARGIN, doc.y, Font::Bold, 10.0, &heading);
+                doc.y += 8.0;
+            }
+            let per_row = (((PAGE_WIDTH - 2.0 * MARGIN) / cell_width).floor() as usize).max(1);
+            for row in found.chunks(per_row) {
+                doc.ensure(cell_height);
+                let y = doc.y;
+                for (i, diagram) in row.iter().enumerate() {
+                    draw_diagram(doc, diagram, MARGIN + cell_width * i as f64, y);
+                }
+                doc.y += cell_height;
+            }
+            if !unknown.is_empty() {
+                doc.ensure(LEADING);
+                let note = format!("No diagram for {}", unknown.join(", "));
+                doc.text(MARGIN, doc.y, Font::Oblique, 9.0, &note);
+            }
+        }
+    }
+}
+
+impl Exporter for PdfExporter {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn extension(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let song = &self.typeset(song);
+        let title = song.title().unwrap_or_else(|| "Untitled".to_string());
+        let mut doc = Document::new();
+        self.render(&mut doc, song);
+        Ok(doc.finish(&title))
+    }
+}
@@ -0,0 +1,287 @@
+use super::slides::{slides, Slide, SlideOptions, BUDGET};
+use super::ttml::escape;
+use super::zip::ZipWriter;
+use super::{Budget, ExportError, Exporter};
+use crate::ast::Song;
+use std::fmt::Write;
+
+/// PowerPoint deck for projecting lyrics: white centered text on black
+/// 16:9 slides, laid out by [`slides`]. ProPresenter and Keynote import it
+/// as well.
+#[derive(Debug, Clone, Default)]
+pub struct PptxExporter {
+    pub options: SlideOptions,
+}
+
+const NAMESPACES: &str = concat!(
+    r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+    r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#
+);
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument";
+
+/// Slide size in EMU (16:9 at 13.33 × 7.5 inches) and the text box margin.
+const WIDTH: u64 = 12_192_000;
+const HEIGHT: u64 = 6_858_000;
+const INSET: u64 = 457_200;
+
+const EMPTY_TREE: &str =
+    "<p:nvGrpSpPr><p:cNvPr id=\"1\" name=\"\"/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr/>";
+
+fn relationships(targets: &[(&str, String)]) -> String {
+    let mut out = format!(
+        "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        XML_HEADER
+    );
+    for (i, (kind, target)) in targets.iter().enumerate() {
+        let _ = write!(
+            out,
+            "<Relationship Id=\"rId{}\" Type=\"{}/{}\" Target=\"{}\"/>",
+            i + 1,
+            REL,
+            kind,
+            target
+        );
+    }
+    out.push_str("</Relationships>");
+    out
+}
+
+fn content_types(slides: usize) -> String {
+    let mut out = format!(
+        "{}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+        XML_HEADER
+    );
+    let mut part = |name: &str, kind: &str| {
+        let _ = write!(
+            out,
+            "<Override PartName=\"{}\" ContentType=\"{}.{}+xml\"/>",
+            name, CONTENT_TYPE, kind
+        );
+    };
+    part("/ppt/presentation.xml", "presentationml.presentation.main");
+    part(
+        "/ppt/slideMasters/slideMaster1.xml",
+        "presentationml.slideMaster",
+    );
+    part(
+        "/ppt/slideLayouts/slideLayout1.xml",
+        "presentationml.slideLayout",
+    );
+    part("/ppt/theme/theme1.xml", "theme");
+    for n in 1..=slides {
+        part(
+            &format!("/ppt/slides/slide{}.xml", n),
+            "presentationml.slide",
+        );
+    }
+    out.push_str("</Types>");
+    out
+}
+
+fn presentation(slides: usize) -> String {
+    let mut out = format!(
+        "{}<p:presentation {}><p:sldMasterIdLst><p:sldMasterId id=\"2147483648\" r:id=\"rId1\"/></p:sldMasterIdLst><p:sldIdLst>",
+        XML_HEADER, NAMESPACES
+    );
+    for n in 0..slides {
+        let _ = write!(out, "<p:sldId id=\"{}\" r:id=\"rId{}\"/>", 256 + n, n + 3);
+    }
+    let _ = write!(
+        out,
+        "</p:sldIdLst><p:sldSz cx=\"{}\" cy=\"{}\"/><p:notesSz cx=\"{}\" cy=\"{}\"/></p:presentation>",
+        WIDTH, HEIGHT, HEIGHT, WIDTH
+    );
+    out
+}
+
+fn master() -> String {
+    format!(
+        "{}<p:sldMaster {}><p:cSld><p:bg><p:bgPr><a:solidFill><a:srgbClr val=\"000000\"/></a:solidFill><a:effectLst/></p:bgPr></p:bg>\
+         <p:spTree>{}</p:spTree></p:cSld>\
+         <p:clrMap bg1=\"dk1\" tx1=\"lt1\" bg2=\"dk2\" tx2=\"lt2\" accent1=\"accent1\" accent2=\"accent2\" accent3=\"accent3\" \
+         accent4=\"accent4\" accent5=\"accent5\" accent6=\"accent6\" hlink=\"hlink\" folHlink=\"folHlink\"/>\
+         <p:sldLayoutIdLst><p:sldLayoutId id=\"2147483649\" r:id=\"rId1\"/></p:sldLayoutIdLst></p:sldMaster>",
+        XML_HEADER, NAMESPACES, EMPTY_TREE
+    )
+}
+
+fn layout() -> String {
+    format!(
+        "{}<p:sldLayout {} type=\"blank\" preserve=\"1\"><p:cSld name=\"Blank\"><p:spTree>{}</p:spTree></p:cSld>\
+         <p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>",
+        XML_HEADER, NAMESPACES, EMPTY_TREE
+    )
+}
+
+fn theme() -> String {
+    let mut colors = String::new();
+    for (name, rgb) in [
+        ("dk1", "000000"),
+        ("lt1", "FFFFFF"),
+        ("dk2", "1F1F1F"),
+        ("lt2", "E7E6E6"),
+        ("accent1", "4472C4"),
+        ("accent2", "ED7D31"),
+        ("accent3", "A5A5A5"),
+        ("accent4", "FFC000"),
+        ("accent5", "5B9BD5"),
+        ("accent6", "70AD47"),
+        ("hlink", "0563C1"),
+        ("folHlink", "954F72"),
+    ] {
+        let _ = write!(colors, "<a:{0}><a:srgbClr val=\"{1}\"/></a:{0}>", name, rgb);
+    }
+    let fill = "<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>";
+    let line = "<a:ln w=\"9525\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill></a:ln>";
+    let effect = "<a:effectStyle><a:effectLst/></a:effectStyle>";
+    format!(
+        "{header}<a:theme xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" name=\"Lyrics\"><a:themeElements>\
+         <a:clrScheme name=\"Lyrics\">{colors}</a:clrScheme>\
+         <a:fontScheme name=\"Lyrics\">\
+         <a:majorFont><a:latin typeface=\"Arial\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:majorFont>\
+         <a:minorFont><a:latin typeface=\"Arial\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:minorFont></a:fontScheme>\
+         <a:fmtScheme name=\"Lyrics\"><a:fillStyleLst>{fill}{fill}{fill}</a:fillStyleLst>\
+         <a:lnStyleLst>{line}{line}{line}</a:lnStyleLst>\
+         <a:effectStyleLst>{effect}{effect}{effect}</a:effectStyleLst>\
+         <a:bgFillStyleLst>{fill}{fill}{fill}</a:bgFillStyleLst></a:fmtScheme>\
+         </a:themeElements></a:theme>",
+        header = XML_HEADER,
+        colors = colors,
+        fill = fill,
+        line = line,
+        effect = effect
+    )
+}
+
+/// A centered paragraph of white text at `size` points.
+fn paragraph(out: &mut String, text: &str, size: u32, bold: bool, lang: &str) {
+    let _ = write!(
+        out,
+        "<a:p><a:pPr algn=\"ctr\"/><a:r><a:rPr lang=\"{}\" sz=\"{}\"{}><a:solidFill><a:srgbClr val=\"FFFFFF\"/></a:solidFill></a:rPr><a:t>{}</a:t></a:r></a:p>",
+        escape(lang),
+        size * 100,
+        if bold { " b=\"1\"" } else { "" },
+        escape(text)
+    );
+}
+
+fn slide(slide: &Slide, lang: &str) -> String {
+    let mut body = String::new();
+    match slide {
+        Slide::Title { title, artist } => {
+            paragraph(&mut body, title, 54, true, lang);
+            if let Some(artist) = artist {
+                paragraph(&mut body, artist, 32, false, lang);
+            }
+        }
+        Slide::Lyrics(lines) => {
+            for line in lines {
+                paragraph(&mut body, line, 40, false, lang);
+            }
+        }
+        Slide::Blank => {}
+    }
+    let mut out = format!(
+        "{}<p:sld {}><p:cSld><p:spTree>{}",
+        XML_HEADER, NAMESPACES, EMPTY_TREE
+    );
+    if !body.is_empty() {
+        let _ = write!(
+            out,
+            "<p:sp><p:nvSpPr><p:cNvPr id=\"2\" name=\"Lyrics\"/><p:cNvSpPr txBox=\"1\"/><p:nvPr/></p:nvSpPr>\
+             <p:spPr><a:xfrm><a:off x=\"{inset}\" y=\"{inset}\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm>\
+             <a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></p:spPr>\
+             <p:txBody><a:bodyPr wrap=\"square\" anchor=\"ctr\"><a:normAutofit/></a:bodyPr><a:lstStyle/>{body}</p:txBody></p:sp>",
+            inset = INSET,
+            cx = WIDTH - 2 * INSET,
+            cy = HEIGHT - 2 * INSET,
+            body = body
+        );
+    }
+    out.push_str("</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>");
+    out
+}
+
+impl Exporter for PptxExporter {
+    fn name(&self) -> &'static str {
+        "pptx"
+    }
+
+    fn extension(&self) -> &'static str {
+        "pptx"
+    }
+
+    fn budget(&self) -> Option<Budget> {
+        Some(BUDGET)
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let slides = slides(song, &self.options);
+        let lang = song.meta_str("lang").unwrap_or_else(|| "en-US".to_string());
+
+        let mut zip = ZipWriter::default();
+        zip.add(
+            "[Content_Types].xml",
+            content_types(slides.len()).as_bytes(),
+        );
+        zip.add(
+            "_rels/.rels",
+            relationships(&[("officeDocument", "ppt/presentation.xml".to_string())]).as_bytes(),
+        );
+        zip.add(
+            "ppt/presentation.xml",
+            presentation(slides.len()).as_bytes(),
+        );
+        let mut targets = vec![
+            ("slideMaster", "slideMasters/slideMaster1.xml".to_string()),
+            ("theme", "theme/theme1.xml".to_string()),
+        ];
+        targets.extend((1..=slides.len()).map(|n| ("slide", format!("slides/slide{}.xml", n))));
+        zip.add(
+            "ppt/_rels/presentation.xml.rels",
+            relationships(&targets).as_bytes(),
+        );
+        zip.add("ppt/slideMasters/slideMaster1.xml", master().as_bytes());
+        zip.add(
+            "ppt/slideMasters/_rels/slideMaster1.xml.rels",
+            relationships(&[
+                (
+                    "slideLayout",
+                    "../slideLayouts/slideLayout1.xml".to_string(),
+                ),
+                ("theme", "../theme/theme1.xml".to_string()),
+            ])
+            .as_bytes(),
+        );
+        zip.add("ppt/slideLayouts/slideLayout1.xml", layout().as_bytes());
+        zip.add(
+            "ppt/slideLayouts/_rels/slideLayout1.xml.rels",
+            relationships(&[(
+                "slideMaster",
+                "../slideMasters/slideMaster1.xml".to_string(),
+            )])
+            .as_bytes(),
+        );
+        zip.add("ppt/theme/theme1.xml", theme().as_bytes());
+        for (i, s) in slides.iter().enumerate() {
+            zip.add(
+                &format!("ppt/slides/slide{}.xml", i + 1),
+                slide(s, &lang).as_bytes(),
+            );
+            zip.add(
+                &format!("ppt/slides/_rels/slide{}.xml.rels", i + 1),
+                relationships(&[(
+                    "slideLayout",
+                    "../slideLayouts/slideLayout1.xml".to_string(),
+                )])
+                .as_bytes(),
+            );
+        }
+        Ok(zip.finish())
+    }
+}
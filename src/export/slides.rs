@@ -0,0 +1,111 @@
+use super::{Budget, ExportError, Exporter, Wrap};
+use crate::ast::Song;
+
+/// How a song is divided into slides for projection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlideOptions {
+    /// Most lyric lines on one slide; longer sections continue on the next.
+    pub lines_per_slide: usize,
+    /// Open with a slide showing the title and artist.
+    pub title_slide: bool,
+    /// Put an empty slide between sections, so the screen goes dark during
+    /// instrumental breaks.
+    pub blank_between: bool,
+}
+
+impl Default for SlideOptions {
+    fn default() -> Self {
+        SlideOptions {
+            lines_per_slide: 4,
+            title_slide: true,
+            blank_between: false,
+        }
+    }
+}
+
+/// Projected rows of at most 36 characters, balanced in length.
+pub const BUDGET: Budget = Budget {
+    width: 36,
+    wrap: Wrap::Balanced,
+};
+
+/// One projected slide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Slide {
+    Title {
+        title: String,
+        artist: Option<String>,
+    },
+    Lyrics(Vec<String>),
+    Blank,
+}
+
+/// Split `song` into slides: every section starts a new slide, and is cut
+/// into slides of at most `lines_per_slide` lines, balanced so a section of
+/// five lines at four per slide becomes three and two rather than four and
+/// one. Lines longer than the [`BUDGET`] are wrapped onto several rows of
+/// their slide. Sections without lines are left out.
+pub fn slides(song: &Song, options: &SlideOptions) -> Vec<Slide> {
+    let per_slide = options.lines_per_slide.max(1);
+    let mut slides = Vec::new();
+    if options.title_slide {
+        if let Some(title) = song.title() {
+            slides.push(Slide::Title {
+                title,
+                artist: song.artist(),
+            });
+        }
+    }
+    let mut first = true;
+    for section in song.sections.iter().filter(|s| !s.lines.is_empty()) {
+        if options.blank_between && !first {
+            slides.push(Slide::Blank);
+        }
+        first = false;
+        let lines: Vec<Vec<String>> = section.lines.iter().map(|l| BUDGET.rows(l)).collect();
+        let count = lines.len().div_ceil(per_slide);
+        let size = lines.len().div_ceil(count);
+        slides.extend(
+            lines
+                .chunks(size)
+                .map(|chunk| Slide::Lyrics(chunk.concat())),
+        );
+    }
+    slides
+}
+
+/// Plain text with one block per slide, separated by `---` lines, for
+/// presentation software that imports slides from text.
+#[derive(Debug, Clone, Default)]
+pub struct SlidesExporter {
+    pub options: SlideOptions,
+}
+
+impl Exporter for SlidesExporter {
+    fn name(&self) -> &'static str {
+        "slides"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn budget(&self) -> Option<Budget> {
+        Some(BUDGET)
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let blocks: Vec<String> = slides(song, &self.options)
+            .into_iter()
+            .map(|slide| match slide {
+                Slide::Title { title, artist } => match artist {
+                    Some(artist) => format!("{}\n{}\n", title, artist),
+                    None => format!("{}\n", title),
+                },
+                Slide::Lyrics(lines) => lines.iter().map(|l| format!("{}\n", l)).collect(),
+                Slide::Blank => String::new(),
+            })
+            .collect();
+        Ok(blocks.join("---\n").into_bytes())
+    }
+}
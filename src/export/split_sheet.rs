@@ -0,0 +1,185 @@
+use super::docx;
+use super::pdf::document::{text_width, Document, Font, MARGIN, PAGE_WIDTH};
+use super::ttml::escape;
+use crate::rights::SplitSheet;
+use std::fmt::Write;
+
+/// A share as printed, or a blank to fill in by hand.
+fn percent(share: Option<f64>) -> String {
+    match share {
+        Some(p) => format!("{}%", p),
+        None => "____ %".to_string(),
+    }
+}
+
+/// The lines above the tables: what the work is and when it was split.
+fn particulars(sheet: &SplitSheet, date: &str) -> Vec<(&'static str, String)> {
+    let mut out = vec![("Song", sheet.title.clone())];
+    if let Some(artist) = &sheet.artist {
+        out.push(("Artist", artist.clone()));
+    }
+    if let Some(iswc) = &sheet.iswc {
+        out.push(("ISWC", iswc.clone()));
+    }
+    if let Some(isrc) = &sheet.isrc {
+        out.push(("ISRC", isrc.clone()));
+    }
+    out.push(("Date", date.to_string()));
+    out
+}
+
+/// The writers' table: name, role and share, one row each.
+fn writer_rows(sheet: &SplitSheet) -> Vec<[String; 3]> {
+    sheet
+        .writers
+        .iter()
+        .map(|w| {
+            [
+                w.name.clone(),
+                w.role.clone().unwrap_or_default(),
+                percent(w.percent),
+            ]
+        })
+        .collect()
+}
+
+const AGREEMENT: &str = "The writers below agree that the shares above are their shares \
+    of the song, and sign to confirm it.";
+
+/// An A4 split sheet for `sheet` agreed on `date`: the song's particulars,
+/// the writers with their roles and shares, the publishers with theirs, and
+/// a signature line for each writer.
+pub fn split_sheet_pdf(sheet: &SplitSheet, date: &str) -> Vec<u8> {
+    const LEADING: f64 = 16.0;
+    let columns = [MARGIN, MARGIN + 230.0, PAGE_WIDTH - MARGIN - 60.0];
+    let rule = |doc: &mut Document| {
+        doc.line(MARGIN, doc.y - 11.0, PAGE_WIDTH - MARGIN, doc.y - 11.0, 0.5);
+    };
+    let mut doc = Document::new();
+    doc.y += 20.0;
+    doc.text(MARGIN, doc.y, Font::Bold, 20.0, "Split Sheet");
+    doc.y += 30.0;
+    for (label, value) in particulars(sheet, date) {
+        doc.text(MARGIN, doc.y, Font::Bold, 11.0, label);
+        doc.text(MARGIN + 70.0, doc.y, Font::Regular, 11.0, &value);
+        doc.y += LEADING;
+    }
+
+    let table = |doc: &mut Document, heading: [&str; 3], rows: Vec<[String; 3]>| {
+        doc.y += LEADING;
+        doc.ensure(LEADING * (rows.len() + 2) as f64);
+        for (x, text) in columns.iter().zip(heading) {
+            doc.text(*x, doc.y, Font::Bold, 11.0, text);
+        }
+        doc.y += 6.0;
+        rule(doc);
+        doc.y += LEADING;
+        for row in rows {
+            for (x, text) in columns.iter().zip(&row) {
+                doc.text(*x, doc.y, Font::Regular, 11.0, text);
+            }
+            doc.y += LEADING;
+        }
+    };
+    table(&mut doc, ["Writer", "Role", "Share"], writer_rows(sheet));
+    if !sheet.publishers.is_empty() {
+        let rows = sheet
+            .publishers
+            .iter()
+            .map(|p| [p.publisher.clone(), String::new(), percent(Some(p.percent))])
+            .collect();
+        table(&mut doc, ["Publisher", "", "Share"], rows);
+    }
+
+    doc.y += LEADING;
+    doc.ensure(LEADING * 2.0);
+    let mut line = String::new();
+    for word in AGREEMENT.split(' ') {
+        let candidate = format!("{} {}", line, word);
+        if text_width(candidate.trim(), Font::Oblique, 10.0) > PAGE_WIDTH - 2.0 * MARGIN {
+            doc.text(MARGIN, doc.y, Font::Oblique, 10.0, &line);
+            doc.y += 13.0;
+            line = word.to_string();
+        } else {
+            line = candidate.trim().to_string();
+        }
+    }
+    doc.text(MARGIN, doc.y, Font::Oblique, 10.0, &line);
+    doc.y += LEADING;
+
+    for writer in &sheet.writers {
+        doc.y += 30.0;
+        doc.ensure(LEADING * 2.0);
+        let date_x = PAGE_WIDTH - MARGIN - 130.0;
+        doc.line(MARGIN, doc.y, date_x - 20.0, doc.y, 0.5);
+        doc.line(date_x, doc.y, PAGE_WIDTH - MARGIN, doc.y, 0.5);
+        doc.y += 12.0;
+        doc.text(MARGIN, doc.y, Font::Regular, 9.0, &writer.name);
+        doc.text(date_x, doc.y, Font::Regular, 9.0, "Date");
+    }
+    doc.finish(&format!("Split Sheet: {}", sheet.title))
+}
+
+/// A bordered table with a bold heading row.
+fn docx_table(out: &mut String, heading: [&str; 3], rows: &[[String; 3]]) {
+    docx::table_start(out, &[4500, 3000, 1500]);
+    let heading = heading.map(String::from);
+    let rows = rows.iter().map(|row| (row, ""));
+    for (row, style) in std::iter::once((&heading, "<w:b/>")).chain(rows) {
+        out.push_str("<w:tr>");
+        for text in row {
+            docx::cell(out, text, style, 1);
+        }
+        out.push_str("</w:tr>");
+    }
+    out.push_str("</w:tbl>");
+}
+
+/// The split sheet of [`split_sheet_pdf`] as a Word document, for when the
+/// paperwork still needs editing before it is signed.
+pub fn split_sheet_docx(sheet: &SplitSheet, date: &str) -> Vec<u8> {
+    let mut body = String::new();
+    body.push_str(
+        "<w:p><w:r><w:rPr><w:b/><w:sz w:val=\"40\"/></w:rPr><w:t>Split Sheet</w:t></w:r></w:p>",
+    );
+    for (label, value) in particulars(sheet, date) {
+        let _ = write!(
+            body,
+            "<w:p><w:r><w:rPr><w:b/></w:rPr><w:t xml:space=\"preserve\">{}: </w:t></w:r>\
+             <w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+            label,
+            escape(&value)
+        );
+    }
+    docx::paragraph(&mut body, "", "");
+    docx_table(&mut body, ["Writer", "Role", "Share"], &writer_rows(sheet));
+    if !sheet.publishers.is_empty() {
+        let rows: Vec<[String; 3]> = sheet
+            .publishers
+            .iter()
+            .map(|p| [p.publisher.clone(), String::new(), percent(Some(p.percent))])
+            .collect();
+        docx::paragraph(&mut body, "", "");
+        docx_table(&mut body, ["Publisher", "", "Share"], &rows);
+    }
+    docx::paragraph(&mut body, "", "");
+    docx::paragraph(&mut body, AGREEMENT, "<w:i/>");
+    // Signature and date lines, the date on a tab stop two thirds across.
+    let signature = |left: &str, right: &str, size: u32| {
+        format!(
+            "<w:p><w:pPr><w:tabs><w:tab w:val=\"left\" w:pos=\"6500\"/></w:tabs></w:pPr>\
+             <w:r><w:rPr><w:sz w:val=\"{}\"/></w:rPr><w:t xml:space=\"preserve\">{}</w:t><w:tab/>\
+             <w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+            size,
+            escape(left),
+            right
+        )
+    };
+    for writer in &sheet.writers {
+        docx::paragraph(&mut body, "", "");
+        body.push_str(&signature(&"_".repeat(40), &"_".repeat(20), 22));
+        body.push_str(&signature(&writer.name, "Date", 18));
+    }
+
+    docx::package(&body)
+}
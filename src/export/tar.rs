@@ -0,0 +1,50 @@
+//! A minimal tar archive writer, in the POSIX ustar format.
+//!
+//! Like [`super::zip`], entries get fixed owners and modification times, so
+//! the same files always produce the same archive bytes.
+
+const BLOCK: usize = 512;
+
+#[derive(Default)]
+pub(crate) struct TarWriter {
+    out: Vec<u8>,
+}
+
+/// Write `value` as a NUL-terminated octal field filling `field`.
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+impl TarWriter {
+    /// Add a regular file. Names longer than 100 bytes are cut short, which
+    /// the names of exported files never are.
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        let mut header = [0u8; BLOCK];
+        let name = &name.as_bytes()[..name.len().min(100)];
+        header[..name.len()].copy_from_slice(name);
+        octal(&mut header[100..108], 0o644); // mode
+        octal(&mut header[108..116], 0); // uid
+        octal(&mut header[116..124], 0); // gid
+        octal(&mut header[124..136], data.len() as u64);
+        octal(&mut header[136..148], 0); // modification time
+        header[156] = b'0'; // regular file
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        // The checksum is summed with its own field as spaces.
+        header[148..156].fill(b' ');
+        let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+        octal(&mut header[148..155], checksum as u64);
+
+        self.out.extend_from_slice(&header);
+        self.out.extend_from_slice(data);
+        let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+        self.out.resize(self.out.len() + padding, 0);
+    }
+
+    /// The archive, ended by two empty blocks.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.out.resize(self.out.len() + 2 * BLOCK, 0);
+        self.out
+    }
+}
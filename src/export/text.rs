@@ -0,0 +1,56 @@
+use super::{ExportError, Exporter};
+use crate::ast::Song;
+use crate::credits::citations;
+use std::fmt::Write;
+
+/// Plain lyric sheet with bracketed section labels and no annotations.
+/// Harmony lines are indented under their lead line, and the song's
+/// interpolation and sample credits close it.
+pub struct TextExporter;
+
+impl Exporter for TextExporter {
+    fn name(&self) -> &'static str {
+        "txt"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let mut out = String::new();
+        match (song.title(), song.artist()) {
+            (Some(title), Some(artist)) => {
+                let _ = writeln!(out, "{} - {}\n", title, artist);
+            }
+            (Some(title), None) => {
+                let _ = writeln!(out, "{}\n", title);
+            }
+            _ => {}
+        }
+
+        for (i, section) in song.sections.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "[{}]", section.label());
+            for line in &section.lines {
+                if line.stanza_break {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "{}", line.segments().join("\n"));
+                for part in &line.harmony {
+                    let _ = writeln!(out, "  {}", part.text);
+                }
+            }
+        }
+        let citations = citations(song);
+        if !citations.is_empty() {
+            out.push('\n');
+            for citation in citations {
+                let _ = writeln!(out, "{}", citation);
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
@@ -0,0 +1,187 @@
+use super::karaoke::{line_ends, word_timings, WordTiming};
+use super::{Budget, ExportError, Exporter, Wrap};
+use crate::ast::{word_ranges, Line, Song, Timestamp};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// Timed Text Markup Language (TTML2), as used by streaming karaoke views.
+///
+/// Each section becomes a `div` and each line a `p`. A timed line ends where
+/// the next timed line begins, and its words carry their own timing (see
+/// [`super::karaoke`]). Words with an IPA override are wrapped in TTML ruby so
+/// players can show the pronunciation above the word. A line's romanization
+/// follows it in an `x-roman` span. Lines longer than a subtitle row are
+/// broken with `<br/>`.
+pub struct TtmlExporter;
+
+/// Subtitle rows of at most 42 characters, balanced in length.
+pub const BUDGET: Budget = Budget {
+    width: 42,
+    wrap: Wrap::Balanced,
+};
+
+fn clock(ts: Timestamp) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ts.millis / 3_600_000,
+        (ts.millis / 60_000) % 60,
+        (ts.millis / 1000) % 60,
+        ts.millis % 1000
+    )
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `base` with `text` set over it as ruby.
+fn ruby(base: &str, text: &str) -> String {
+    format!(
+        "<span tts:ruby=\"container\"><span tts:ruby=\"base\">{}</span><span tts:ruby=\"text\">{}</span></span>",
+        escape(base),
+        escape(text)
+    )
+}
+
+/// A word, wrapped in ruby when the line gives its pronunciation or
+/// furigana over its kanji.
+fn word_body(line: &Line, range: Range<usize>) -> String {
+    let word = &line.text[range.clone()];
+    match line.pronunciation(word) {
+        Some(ipa) => ruby(word, ipa),
+        None => line
+            .ruby_runs(range)
+            .into_iter()
+            .map(|(run, reading)| match reading {
+                Some(reading) => ruby(run, reading),
+                None => escape(run),
+            })
+            .collect(),
+    }
+}
+
+/// Line text with pronounced words wrapped in ruby spans, rows broken to
+/// the [`BUDGET`] and, when `timed`, every word in a span timed relative to
+/// the start of its paragraph.
+fn line_body(line: &Line, timed: Option<(Timestamp, Option<Timestamp>)>) -> String {
+    let words: Vec<(Range<usize>, Option<WordTiming>)> = match timed {
+        Some((begin, end)) => word_timings(line, begin, end)
+            .into_iter()
+            .map(|t| (t.range.clone(), Some(t)))
+            .collect(),
+        None => word_ranges(&line.text)
+            .into_iter()
+            .map(|r| (r, None))
+            .collect(),
+    };
+    let breaks = BUDGET.breaks(line);
+    let mut out = String::new();
+    let mut cursor = 0;
+    for (i, (range, timing)) in words.into_iter().enumerate() {
+        let gap = &line.text[cursor..range.start];
+        match breaks.contains(&i) {
+            true => {
+                out.push_str(&escape(gap.trim_end()));
+                out.push_str("<br/>");
+            }
+            false => out.push_str(&escape(gap)),
+        }
+        let body = word_body(line, range.clone());
+        match (timing, timed) {
+            (Some(t), Some((begin, _))) => {
+                let _ = write!(
+                    out,
+                    "<span begin=\"{}\" end=\"{}\">{}</span>",
+                    clock(Timestamp::from_millis(t.begin.millis - begin.millis)),
+                    clock(Timestamp::from_millis(t.end.millis - begin.millis)),
+                    body
+                );
+            }
+            _ => out.push_str(&body),
+        }
+        cursor = range.end;
+    }
+    out.push_str(&escape(&line.text[cursor..]));
+    out
+}
+
+impl Exporter for TtmlExporter {
+    fn name(&self) -> &'static str {
+        "ttml"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ttml"
+    }
+
+    fn budget(&self) -> Option<Budget> {
+        Some(BUDGET)
+    }
+
+    fn export(&self, song: &Song) -> Result<Vec<u8>, ExportError> {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let lang = song.meta_str("lang").unwrap_or_else(|| "en".to_string());
+        let _ = writeln!(
+            out,
+            "<tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:tts=\"http://www.w3.org/ns/ttml#styling\" xmlns:ttm=\"http://www.w3.org/ns/ttml#metadata\" xml:lang=\"{}\">",
+            escape(&lang)
+        );
+        out.push_str("  <head>\n    <metadata>\n");
+        if let Some(title) = song.title() {
+            let _ = writeln!(out, "      <ttm:title>{}</ttm:title>", escape(&title));
+        }
+        if let Some(artist) = song.artist() {
+            let _ = writeln!(
+                out,
+                "      <ttm:agent type=\"person\" xml:id=\"artist\"><ttm:name type=\"full\">{}</ttm:name></ttm:agent>",
+                escape(&artist)
+            );
+        }
+        out.push_str("    </metadata>\n  </head>\n  <body>\n");
+
+        let mut ends = line_ends(song).into_iter();
+        for section in &song.sections {
+            let _ = writeln!(
+                out,
+                "    <div ttm:role=\"x-{}\">",
+                section.kind.label().to_lowercase()
+            );
+            for line in &section.lines {
+                let end = ends.next().flatten();
+                out.push_str("      <p");
+                if let Some(begin) = line.timing {
+                    let _ = write!(out, " begin=\"{}\"", clock(begin));
+                    if let Some(end) = end {
+                        let _ = write!(out, " end=\"{}\"", clock(end));
+                    }
+                }
+                let timed = line.timing.map(|begin| (begin, end));
+                out.push('>');
+                out.push_str(&line_body(line, timed));
+                if let Some(romanized) = line.romanization() {
+                    let _ = write!(
+                        out,
+                        "<br/><span ttm:role=\"x-roman\" xml:lang=\"{}\">{}</span>",
+                        escape(&romanized.lang),
+                        escape(&romanized.text)
+                    );
+                }
+                out.push_str("</p>\n");
+            }
+            out.push_str("    </div>\n");
+        }
+        out.push_str("  </body>\n</tt>\n");
+        Ok(out.into_bytes())
+    }
+}
@@ -0,0 +1,398 @@
+//! Project grammar extensions: pest rules in an `extra.pest` file that
+//! check the annotations the base grammar leaves open.
+//!
+//! A line may carry annotations of any name, such as `{mood: wistful}`.
+//! When a project's `extra.pest` defines a rule with the annotation's name,
+//! the value must match that rule in full:
+//!
+//! ```text
+//! mood  = { "wistful" | "angry" | "tender" }
+//! scene = { "act" ~ ASCII_DIGIT ~ "." ~ number }
+//! ```
+//!
+//! Extra rules are merged with the base grammar, so they can build on its
+//! rules (`number`, `chord`, `identifier`, ...). They are run by a small
+//! interpreter over the `pest_meta` AST rather than compiled into the
+//! parser, so a project extends the language without rebuilding the crate.
+//! The stack operations (`PUSH`, `POP`, `PEEK`, ...) are not supported.
+
+use crate::ast::{Line, Song};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+use crate::grammar::{self, GrammarRule};
+use crate::parser::LINE_ATTRIBUTES;
+use crate::suggest::{self, did_you_mean};
+use pest_meta::ast::{Expr, RuleType};
+use pest_meta::parser::{self, rename_meta_rule, Rule as MetaRule};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// The file a project keeps its extra rules in, at the project root.
+pub const FILE_NAME: &str = "extra.pest";
+
+/// Built-in rules the interpreter understands.
+const BUILTINS: &[&str] = &[
+    "ANY",
+    "SOI",
+    "EOI",
+    "ASCII_DIGIT",
+    "ASCII_NONZERO_DIGIT",
+    "ASCII_BIN_DIGIT",
+    "ASCII_OCT_DIGIT",
+    "ASCII_HEX_DIGIT",
+    "ASCII_ALPHA_LOWER",
+    "ASCII_ALPHA_UPPER",
+    "ASCII_ALPHA",
+    "ASCII_ALPHANUMERIC",
+    "ASCII",
+    "LETTER",
+    "LOWERCASE_LETTER",
+    "UPPERCASE_LETTER",
+    "MARK",
+    "NUMBER",
+    "WHITE_SPACE",
+];
+
+/// Nesting depth at which matching gives up instead of overflowing the stack.
+const MAX_DEPTH: usize = 500;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExtensionError {
+    #[error("{0}")]
+    Syntax(String),
+    #[error("`{0}` is already defined by the base grammar or by pest")]
+    Redefined(String),
+    #[error("`{0}` is defined twice")]
+    Duplicate(String),
+    #[error("rule `{rule}` uses `{name}`, which is not defined")]
+    Undefined { rule: String, name: String },
+    #[error("rule `{rule}` uses `{what}`, which extensions do not support")]
+    Unsupported { rule: String, what: String },
+}
+
+/// The base grammar merged with a project's extra rules.
+#[derive(Debug, Clone)]
+pub struct Extension {
+    extra: Vec<GrammarRule>,
+    rules: HashMap<String, GrammarRule>,
+}
+
+impl Extension {
+    /// Parse and check the pest rules in `source`.
+    pub fn parse(source: &str) -> Result<Extension, ExtensionError> {
+        let syntax = |errors: Vec<pest::error::Error<MetaRule>>| {
+            let messages: Vec<String> = errors
+                .into_iter()
+                .map(|e| e.renamed_rules(rename_meta_rule).to_string())
+                .collect();
+            ExtensionError::Syntax(messages.join("\n"))
+        };
+        let pairs = parser::parse(MetaRule::grammar_rules, source).map_err(|e| syntax(vec![e]))?;
+        let extra = grammar::with_comments(source, parser::consume_rules(pairs).map_err(syntax)?);
+
+        let mut rules: HashMap<String, GrammarRule> = grammar::rules()
+            .into_iter()
+            .map(|rule| (rule.name.clone(), rule))
+            .collect();
+        for rule in &extra {
+            if is_reserved(&rule.name) || grammar::rule(&rule.name).is_some() {
+                return Err(ExtensionError::Redefined(rule.name.clone()));
+            }
+            if rules.insert(rule.name.clone(), rule.clone()).is_some() {
+                return Err(ExtensionError::Duplicate(rule.name.clone()));
+            }
+        }
+        for rule in &extra {
+            check_expr(&rule.name, &rule.expr, &rules)?;
+        }
+        Ok(Extension { extra, rules })
+    }
+
+    /// The rules that came from the extension, in definition order.
+    pub fn rules(&self) -> &[GrammarRule] {
+        &self.extra
+    }
+
+    /// Whether the extension defines the rule `name`.
+    pub fn defines(&self, name: &str) -> bool {
+        self.extra.iter().any(|r| r.name == name)
+    }
+
+    /// Whether all of `text` matches the rule `name`, which may be an
+    /// extra rule or one of the base grammar.
+    pub fn matches(&self, name: &str, text: &str) -> bool {
+        let matcher = Matcher {
+            rules: &self.rules,
+            input: text,
+        };
+        matcher.rule(name, 0, false, 0) == Some(text.len())
+    }
+}
+
+/// Check every annotation in `song` against `extension`: values that do not
+/// match their rule are errors, and annotations without a rule are warnings,
+/// which also catches misspelt attributes such as `{rhym: A}`.
+pub fn check(song: &Song, extension: Option<&Extension>) -> Vec<Diagnostic> {
+    let lines = song
+        .macros
+        .iter()
+        .flat_map(|m| &m.lines)
+        .chain(song.sections.iter().flat_map(|s| &s.lines));
+    lines.flat_map(|line| check_line(line, extension)).collect()
+}
+
+fn check_line(line: &Line, extension: Option<&Extension>) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    for annotation in &line.annotations {
+        match extension {
+            Some(extension) if extension.defines(&annotation.name) => {
+                if !extension.matches(&annotation.name, &annotation.value) {
+                    out.push(
+                        Diagnostic::error(
+                            format!(
+                                "`{}` does not match the `{}` rule of {}",
+                                annotation.value, annotation.name, FILE_NAME
+                            ),
+                            annotation.span,
+                        )
+                        .with_code(&codes::ANNOTATION_MISMATCH),
+                    );
+                }
+            }
+            _ => {
+                let mut diagnostic = Diagnostic::warning(
+                    format!(
+                        "unknown annotation `{}`; define a `{}` rule in {} to check it",
+                        annotation.name, annotation.name, FILE_NAME
+                    ),
+                    annotation.span,
+                )
+                .with_code(&codes::UNKNOWN_ANNOTATION);
+                let extra = extension.into_iter().flat_map(|e| e.rules());
+                let known = LINE_ATTRIBUTES
+                    .into_iter()
+                    .chain(extra.map(|rule| rule.name.as_str()));
+                if let Some(similar) = did_you_mean(&annotation.name, known) {
+                    diagnostic = diagnostic.with_help(suggest::help(similar));
+                }
+                out.push(diagnostic)
+            }
+        }
+    }
+    out
+}
+
+/// Names pest gives a meaning of its own; `WHITESPACE` and `COMMENT` may
+/// be defined.
+fn is_reserved(name: &str) -> bool {
+    BUILTINS.contains(&name) || is_stack_op(name) || name == "NEWLINE"
+}
+
+fn is_stack_op(name: &str) -> bool {
+    matches!(
+        name,
+        "PUSH" | "POP" | "POP_ALL" | "PEEK" | "PEEK_ALL" | "DROP"
+    )
+}
+
+fn check_expr(
+    rule: &str,
+    expr: &Expr,
+    rules: &HashMap<String, GrammarRule>,
+) -> Result<(), ExtensionError> {
+    for node in expr.iter_top_down() {
+        match node {
+            Expr::Ident(name) if rules.contains_key(&name) || BUILTINS.contains(&name.as_str()) => {
+            }
+            Expr::Ident(name) if is_stack_op(&name) => {
+                return Err(ExtensionError::Unsupported {
+                    rule: rule.to_string(),
+                    what: name,
+                })
+            }
+            Expr::Ident(name) => {
+                return Err(ExtensionError::Undefined {
+                    rule: rule.to_string(),
+                    name,
+                })
+            }
+            Expr::PeekSlice(..) | Expr::Push(_) => {
+                return Err(ExtensionError::Unsupported {
+                    rule: rule.to_string(),
+                    what: "the stack".to_string(),
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// A PEG interpreter: every expression either fails or matches a definite
+/// number of bytes, so matching needs no backtracking state.
+struct Matcher<'a> {
+    rules: &'a HashMap<String, GrammarRule>,
+    input: &'a str,
+}
+
+impl Matcher<'_> {
+    /// Where a match of the rule `name` at `pos` ends, if it matches.
+    fn rule(&self, name: &str, pos: usize, atomic: bool, depth: usize) -> Option<usize> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let Some(rule) = self.rules.get(name) else {
+            return self.builtin(name, pos);
+        };
+        let atomic = match rule.ty {
+            RuleType::Atomic | RuleType::CompoundAtomic => true,
+            RuleType::NonAtomic => false,
+            RuleType::Normal | RuleType::Silent => atomic,
+        };
+        self.eval(&rule.expr, pos, atomic, depth + 1)
+    }
+
+    fn builtin(&self, name: &str, pos: usize) -> Option<usize> {
+        if name == "SOI" {
+            return (pos == 0).then_some(pos);
+        }
+        if name == "EOI" {
+            return (pos == self.input.len()).then_some(pos);
+        }
+        let c = self.input[pos..].chars().next()?;
+        let ok = match name {
+            "ANY" => true,
+            "ASCII_DIGIT" => c.is_ascii_digit(),
+            "ASCII_NONZERO_DIGIT" => ('1'..='9').contains(&c),
+            "ASCII_BIN_DIGIT" => c == '0' || c == '1',
+            "ASCII_OCT_DIGIT" => ('0'..='7').contains(&c),
+            "ASCII_HEX_DIGIT" => c.is_ascii_hexdigit(),
+            "ASCII_ALPHA_LOWER" => c.is_ascii_lowercase(),
+            "ASCII_ALPHA_UPPER" => c.is_ascii_uppercase(),
+            "ASCII_ALPHA" => c.is_ascii_alphabetic(),
+            "ASCII_ALPHANUMERIC" => c.is_ascii_alphanumeric(),
+            "ASCII" => c.is_ascii(),
+            "LETTER" => c.is_alphabetic(),
+            "LOWERCASE_LETTER" => c.is_lowercase(),
+            "UPPERCASE_LETTER" => c.is_uppercase(),
+            "MARK" => is_mark(c),
+            "NUMBER" => c.is_numeric(),
+            "WHITE_SPACE" => c.is_whitespace(),
+            _ => false,
+        };
+        ok.then_some(pos + c.len_utf8())
+    }
+
+    /// Skip implicit `WHITESPACE` and `COMMENT` between the parts of a
+    /// non-atomic rule, as pest does.
+    fn skip(&self, mut pos: usize, atomic: bool, depth: usize) -> usize {
+        if atomic {
+            return pos;
+        }
+        loop {
+            let next = ["WHITESPACE", "COMMENT"]
+                .iter()
+                .filter(|name| self.rules.contains_key(**name))
+                .find_map(|name| self.rule(name, pos, true, depth).filter(|&end| end > pos));
+            match next {
+                Some(end) => pos = end,
+                None => return pos,
+            }
+        }
+    }
+
+    fn eval(&self, expr: &Expr, pos: usize, atomic: bool, depth: usize) -> Option<usize> {
+        let rest = &self.input[pos..];
+        match expr {
+            Expr::Str(s) => rest.starts_with(s.as_str()).then(|| pos + s.len()),
+            Expr::Insens(s) => {
+                let len: usize = rest
+                    .chars()
+                    .take(s.chars().count())
+                    .map(char::len_utf8)
+                    .sum();
+                (rest[..len].to_lowercase() == s.to_lowercase()).then_some(pos + len)
+            }
+            Expr::Range(low, high) => {
+                let c = rest.chars().next()?;
+                let low = low.chars().next()?;
+                let high = high.chars().next()?;
+                (low..=high).contains(&c).then(|| pos + c.len_utf8())
+            }
+            Expr::Ident(name) => self.rule(name, pos, atomic, depth),
+            Expr::PosPred(e) => self.eval(e, pos, atomic, depth).map(|_| pos),
+            Expr::NegPred(e) => match self.eval(e, pos, atomic, depth) {
+                Some(_) => None,
+                None => Some(pos),
+            },
+            Expr::Seq(a, b) => {
+                let mid = self.eval(a, pos, atomic, depth)?;
+                self.eval(b, self.skip(mid, atomic, depth), atomic, depth)
+            }
+            Expr::Choice(a, b) => self
+                .eval(a, pos, atomic, depth)
+                .or_else(|| self.eval(b, pos, atomic, depth)),
+            Expr::Opt(e) => self.eval(e, pos, atomic, depth).or(Some(pos)),
+            Expr::Rep(e) => self.repeat(e, pos, atomic, depth, 0, None),
+            Expr::RepOnce(e) => self.repeat(e, pos, atomic, depth, 1, None),
+            Expr::RepExact(e, n) => self.repeat(e, pos, atomic, depth, *n, Some(*n)),
+            Expr::RepMin(e, n) => self.repeat(e, pos, atomic, depth, *n, None),
+            Expr::RepMax(e, n) => self.repeat(e, pos, atomic, depth, 0, Some(*n)),
+            Expr::RepMinMax(e, min, max) => self.repeat(e, pos, atomic, depth, *min, Some(*max)),
+            Expr::Skip(ends) => {
+                let end = ends
+                    .iter()
+                    .filter_map(|s| rest.find(s.as_str()))
+                    .min()
+                    .unwrap_or(rest.len());
+                Some(pos + end)
+            }
+            // Rejected when the extension is loaded.
+            _ => None,
+        }
+    }
+
+    fn repeat(
+        &self,
+        expr: &Expr,
+        pos: usize,
+        atomic: bool,
+        depth: usize,
+        min: u32,
+        max: Option<u32>,
+    ) -> Option<usize> {
+        let mut count = 0;
+        let mut end = pos;
+        while max.is_none_or(|max| count < max) {
+            let start = if count > 0 {
+                self.skip(end, atomic, depth)
+            } else {
+                end
+            };
+            match self.eval(expr, start, atomic, depth) {
+                Some(next) => {
+                    count += 1;
+                    let stuck = next == end;
+                    end = next;
+                    // An empty match would repeat forever.
+                    if stuck {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        (count >= min).then_some(end)
+    }
+}
+
+/// Combining marks, the common blocks of Unicode's `Mark` category.
+fn is_mark(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036f | 0x0483..=0x0489 | 0x0591..=0x05bd | 0x0610..=0x061a
+            | 0x064b..=0x065f | 0x0900..=0x0903 | 0x093a..=0x094f | 0x1ab0..=0x1aff
+            | 0x1dc0..=0x1dff | 0x20d0..=0x20ff | 0x302a..=0x302f | 0x3099..=0x309a
+            | 0xfe00..=0xfe0f | 0xfe20..=0xfe2f
+    )
+}
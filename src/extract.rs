@@ -0,0 +1,117 @@
+//! A section or a run of source lines taken out of a song as a song of its
+//! own, for sharing a snippet or building a medley from it.
+//!
+//! The fragment is cut from the resolved song, so variables, macros and
+//! `REPEAT`s are written out and it reads the same without the rest of the
+//! song. It keeps the song's metadata and credits.
+
+use crate::ast::{Section, Song, Span};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExtractError {
+    #[error("no section `{name}`; the song has {labels}")]
+    NoSection { name: String, labels: String },
+    #[error("no lyric lines on lines {first} to {last}")]
+    NoLines { first: usize, last: usize },
+    #[error("invalid line range `{0}`: expected e.g. 10..20")]
+    Range(String),
+}
+
+/// What to take out of a song.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    /// The first section named so, such as `bridge` or `verse 2`.
+    Section(String),
+    /// Source lines `first..=last`.
+    Lines(usize, usize),
+}
+
+impl Selection {
+    /// A line range written `10..20`, `10..=20` or `10-20`, inclusive of
+    /// both ends.
+    pub fn lines(spec: &str) -> Result<Selection, ExtractError> {
+        let (first, last) = spec
+            .split_once("..=")
+            .or_else(|| spec.split_once(".."))
+            .or_else(|| spec.split_once('-'))
+            .unwrap_or((spec, spec));
+        match (first.trim().parse(), last.trim().parse()) {
+            (Ok(first), Ok(last)) if first >= 1 && first <= last => {
+                Ok(Selection::Lines(first, last))
+            }
+            _ => Err(ExtractError::Range(spec.to_string())),
+        }
+    }
+}
+
+/// The section of `song` as it stands on its own: written out, and with
+/// nothing pointing back into the song it came from.
+fn standalone(section: &Section) -> Section {
+    let mut section = section.clone();
+    section.repeat = None;
+    section.anchor = None;
+    section.span = Span::default();
+    for line in &mut section.lines {
+        line.anchor = None;
+    }
+    section
+}
+
+/// Take `selection` out of the resolved `song`, parsed from `source`.
+pub fn extract(song: &Song, source: &str, selection: &Selection) -> Result<Song, ExtractError> {
+    let sections = match selection {
+        Selection::Section(name) => {
+            let found = song
+                .sections
+                .iter()
+                .filter(|s| s.is_named(name))
+                .min_by_key(|s| s.repeat.is_some());
+            match found {
+                Some(section) => vec![standalone(section)],
+                None => {
+                    let mut labels: Vec<String> = Vec::new();
+                    for label in song.sections.iter().map(|s| s.label()) {
+                        if !labels.contains(&label) {
+                            labels.push(label);
+                        }
+                    }
+                    return Err(ExtractError::NoSection {
+                        name: name.clone(),
+                        labels: labels.join(", "),
+                    });
+                }
+            }
+        }
+        Selection::Lines(first, last) => {
+            let within = |span: &Span| (*first..=*last).contains(&span.line_col(source).0);
+            let mut sections = Vec::new();
+            for section in &song.sections {
+                // A `REPEAT` brings its lines along when its header is
+                // taken; the lines it copies are written elsewhere.
+                let mut taken = standalone(section);
+                match section.repeat {
+                    Some(_) if within(&section.span) => {}
+                    Some(_) => continue,
+                    None => taken.lines.retain(|l| within(&l.span)),
+                }
+                if !taken.lines.is_empty() {
+                    sections.push(taken);
+                }
+            }
+            if sections.is_empty() {
+                return Err(ExtractError::NoLines {
+                    first: *first,
+                    last: *last,
+                });
+            }
+            sections
+        }
+    };
+    Ok(Song {
+        metadata: song.metadata.clone(),
+        credits: song.credits.clone(),
+        sections,
+        ..Song::default()
+    })
+}
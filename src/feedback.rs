@@ -0,0 +1,117 @@
+//! Comments from review tools, keyed by the stable IDs of lines (see
+//! [`crate::ids`]), shown beside the lines they are about.
+//!
+//! The file is a JSON object from line ID to what was said about the line:
+//! a string, a comment, or a list of either.
+//!
+//! ```json
+//! {
+//!   "gt2naj": [{"author": "Legal", "text": "Too close to the original?", "date": "2026-10-14"}],
+//!   "k5nyrd": "Lovely"
+//! }
+//! ```
+//!
+//! Comments on IDs no longer in the song are kept, so a review can say
+//! which of them were left behind by an edit.
+
+use crate::ast::{Line, Song};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FeedbackError {
+    #[error("comments: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// One comment on a line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub author: Option<String>,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub date: Option<String>,
+}
+
+impl Comment {
+    /// The comment as a margin note, `Author: text`.
+    pub fn note(&self) -> String {
+        match &self.author {
+            Some(author) => format!("{}: {}", author, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// What the file may give for one ID.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Text(String),
+    Comment(Comment),
+    List(Vec<Entry>),
+}
+
+impl Entry {
+    fn flatten(self, out: &mut Vec<Comment>) {
+        match self {
+            Entry::Text(text) => out.push(Comment {
+                author: None,
+                text,
+                date: None,
+            }),
+            Entry::Comment(comment) => out.push(comment),
+            Entry::List(entries) => entries.into_iter().for_each(|e| e.flatten(out)),
+        }
+    }
+}
+
+/// Every comment of a review, by line ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments {
+    by_id: BTreeMap<String, Vec<Comment>>,
+}
+
+impl Comments {
+    pub fn parse(json: &str) -> Result<Comments, FeedbackError> {
+        let entries: BTreeMap<String, Entry> = serde_json::from_str(json)?;
+        let mut by_id = BTreeMap::new();
+        for (id, entry) in entries {
+            let mut comments = Vec::new();
+            entry.flatten(&mut comments);
+            if !comments.is_empty() {
+                by_id.insert(id, comments);
+            }
+        }
+        Ok(Comments { by_id })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// The comments on `line`, none when it has no ID.
+    pub fn on(&self, line: &Line) -> &[Comment] {
+        line.id
+            .as_ref()
+            .and_then(|id| self.by_id.get(id))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The IDs commented on that no line of `song` has any more, with
+    /// their comments.
+    pub fn orphans(&self, song: &Song) -> Vec<(&str, &[Comment])> {
+        let mut ids = Vec::new();
+        for (_, line) in song.lines() {
+            ids.extend(line.id.as_deref());
+            ids.extend(line.harmony.iter().filter_map(|part| part.id.as_deref()));
+        }
+        self.by_id
+            .iter()
+            .filter(|(id, _)| !ids.contains(&id.as_str()))
+            .map(|(id, comments)| (id.as_str(), comments.as_slice()))
+            .collect()
+    }
+}
@@ -0,0 +1,428 @@
+//! Canonical source formatting.
+//!
+//! [`format_song`] prints a parsed, unresolved [`Song`] back as source: one
+//! blank line between blocks, `key: value` metadata (kept in its `---`
+//! front-matter block when it came in one), single spaces around
+//! `|` bar markers and annotations, and line attributes in a fixed order.
+//! Word-level marks are written directly after their word, so `lo~~ve`
+//! becomes `love~~`. Formatting a formatted file changes nothing.
+
+use crate::ast::{
+    blank_ranges, word_ranges, Attribute, Credit, Hold, Line, Macro, Section, Song, Timestamp,
+    Value,
+};
+use std::fmt::Write;
+
+/// Render `song` as canonical source text.
+pub fn format_song(song: &Song) -> String {
+    let mut front: Vec<(usize, String)> = Vec::new();
+    let mut header: Vec<(usize, String)> = Vec::new();
+    for entry in &song.metadata {
+        let value = match (&entry.value, entry.key.as_str()) {
+            (value, _) if song.front_matter.is_some() => yaml_source(value),
+            (Value::String(s), "time" | "time_sig") if s.contains('/') => s.clone(),
+            (value, _) => value_source(value),
+        };
+        let line = match &entry.value {
+            Value::List(items) if entry.block && song.front_matter.is_some() => {
+                let mut line = format!("{}:", entry.key);
+                for item in items {
+                    let _ = write!(line, "\n  - {}", yaml_scalar(item, false));
+                }
+                line
+            }
+            _ => format!("{}: {}", entry.key, value),
+        };
+        let target = match song.front_matter {
+            Some(_) => &mut front,
+            None => &mut header,
+        };
+        target.push((entry.span.start, line));
+    }
+    if let Some(matter) = &song.front_matter {
+        for line in &matter.other {
+            front.push((line.span.start, line.text.clone()));
+        }
+    }
+    front.sort_by_key(|(start, _)| *start);
+    for change in &song.tempo_changes {
+        header.push((
+            change.span.start,
+            format!("tempo: {} @ {}", change.bpm, timestamp(change.at)),
+        ));
+    }
+    for variable in &song.variables {
+        header.push((
+            variable.span.start,
+            format!("${} = \"{}\"", variable.name, variable.value),
+        ));
+    }
+    if let Some(first) = song.credits.first() {
+        let mut block = "credits:".to_string();
+        for credit in &song.credits {
+            let _ = write!(block, "\n  {}", credit_source(credit));
+        }
+        header.push((first.span.start, block));
+    }
+    if let Some(first) = song.history.first() {
+        let mut block = "history:".to_string();
+        for entry in &song.history {
+            let _ = write!(block, "\n  {} \"{}\"", entry.when, entry.message);
+        }
+        header.push((first.span.start, block));
+    }
+    header.sort_by_key(|(start, _)| *start);
+
+    let mut blocks: Vec<(usize, String)> = Vec::new();
+    for section in &song.sections {
+        blocks.push((section.span.start, section_source(section)));
+    }
+    for m in &song.macros {
+        blocks.push((m.span.start, macro_source(m)));
+    }
+    blocks.sort_by_key(|(start, _)| *start);
+
+    // Sections follow the closing `---` directly.
+    let mut fenced = song.front_matter.is_some() && header.is_empty();
+    let mut out = String::new();
+    if song.front_matter.is_some() {
+        out.push_str("---\n");
+        for (_, line) in front {
+            let _ = writeln!(out, "{}", line);
+        }
+        out.push_str("---\n");
+    }
+    for (_, line) in header {
+        let _ = writeln!(out, "{}", line);
+    }
+    for (_, block) in blocks {
+        if !out.is_empty() && !fenced {
+            out.push('\n');
+        }
+        fenced = false;
+        out.push_str(&block);
+    }
+    out
+}
+
+/// One entry of the `credits:` block, without its indent.
+fn credit_source(credit: &Credit) -> String {
+    let mut attrs = Vec::new();
+    let fields = [
+        ("artist", &credit.artist),
+        ("writers", &credit.writers),
+        ("publishers", &credit.publishers),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            attrs.push(format!("{}: \"{}\"", key, value));
+        }
+    }
+    if let Some(year) = &credit.year {
+        match !year.is_empty() && year.bytes().all(|b| b.is_ascii_digit()) {
+            true => attrs.push(format!("year: {}", year)),
+            false => attrs.push(format!("year: \"{}\"", year)),
+        }
+    }
+    if let Some(from) = &credit.from {
+        match &credit.to {
+            Some(to) => attrs.push(format!("lines: {}..{}", from.target, to.target)),
+            None => attrs.push(format!("lines: {}", from.target)),
+        }
+    }
+    let mut out = format!("{} \"{}\"", credit.kind.keyword(), credit.work);
+    if !attrs.is_empty() {
+        let _ = write!(out, " {{{}}}", attrs.join(", "));
+    }
+    out
+}
+
+fn value_source(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        other => other.to_string(),
+    }
+}
+
+/// `value` as YAML: a scalar, or a list written `[a, b]`.
+fn yaml_source(value: &Value) -> String {
+    match value {
+        Value::List(items) => {
+            let items: Vec<String> = items.iter().map(|item| yaml_scalar(item, true)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        value => yaml_scalar(value, false),
+    }
+}
+
+/// `value` as a YAML scalar: plain where YAML reads it back as the same
+/// text, single-quoted otherwise. An item of a `[a, b]` list, `flow`, is
+/// also quoted when it holds a comma or a bracket.
+fn yaml_scalar(value: &Value, flow: bool) -> String {
+    let Value::String(s) = value else {
+        return value.to_string();
+    };
+    let reserved = ["true", "false", "yes", "no", "on", "off", "null", "~"];
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with(|c| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !s.contains('\n')
+        && !(flow && s.contains(|c| ",[]{}".contains(c)))
+        && s.parse::<f64>().is_err()
+        && !reserved.contains(&s.to_lowercase().as_str());
+    match plain {
+        true => s.clone(),
+        false => format!("'{}'", s.replace('\'', "''")),
+    }
+}
+
+fn attrs_source(attrs: &[Attribute], id: Option<&String>) -> String {
+    let mut list: Vec<String> = attrs
+        .iter()
+        .map(|a| format!("{}: {}", a.name, value_source(&a.value)))
+        .collect();
+    list.extend(id.map(|id| format!("id: {}", id)));
+    format!(" {{{}}}", list.join(", "))
+}
+
+/// `m:ss.cc`, or `m:ss.mmm` when the time is not a whole centisecond.
+fn timestamp(ts: Timestamp) -> String {
+    if ts.millis.is_multiple_of(10) {
+        ts.to_string()
+    } else {
+        format!(
+            "{}:{:06.3}",
+            ts.millis / 60_000,
+            (ts.millis % 60_000) as f64 / 1000.0
+        )
+    }
+}
+
+fn hold_time(ts: Timestamp) -> String {
+    if ts.millis.is_multiple_of(100) {
+        format!("{}s", ts.as_secs_f64())
+    } else {
+        format!("{}ms", ts.millis)
+    }
+}
+
+fn section_source(section: &Section) -> String {
+    let mut out = header_source(section);
+    out.push('\n');
+    lines_source(&mut out, &section.lines);
+    out
+}
+
+/// The canonical header line of `section`, without its newline.
+pub fn header_source(section: &Section) -> String {
+    let mut out = match &section.repeat {
+        Some(reference) => format!("REPEAT {}", reference.target),
+        None => section.header(),
+    };
+    if let Some(anchor) = &section.anchor {
+        let _ = write!(out, " &{}", anchor);
+    }
+    if !section.attrs.is_empty() || section.id.is_some() {
+        out.push_str(&attrs_source(&section.attrs, section.id.as_ref()));
+    }
+    out
+}
+
+fn macro_source(m: &Macro) -> String {
+    let mut out = format!("MACRO {}\n", m.name);
+    lines_source(&mut out, &m.lines);
+    out
+}
+
+/// Write `lines` with a blank line before each one that starts a stanza.
+fn lines_source(out: &mut String, lines: &[Line]) {
+    for (i, line) in lines.iter().enumerate() {
+        if line.stanza_break && i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&line_source(line));
+    }
+}
+
+/// Plain `text` as the text of a lyric line, with a backslash before each
+/// character that would otherwise be read as markup.
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let markup = match c {
+            '\\' | '{' | '}' | '|' | '~' | '$' | '&' => true,
+            // Only `!!` can start a note, and a `·` before a letter is text.
+            '!' => chars.peek() == Some(&'!'),
+            '·' => !chars.peek().is_some_and(|c| c.is_alphabetic()),
+            _ => false,
+        };
+        if markup {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// One source line, ending in a newline.
+pub fn line_source(line: &Line) -> String {
+    if let Some(reference) = &line.include {
+        return format!("USE {}\n", reference.target);
+    }
+
+    let text = &line.text;
+    let ranges = word_ranges(text);
+    // Marks go around whole `$name` and `${name}` references.
+    let start_of = |i: usize| {
+        let start = ranges[i].start;
+        if text[..start].ends_with("${") {
+            start - 2
+        } else if text[..start].ends_with('$') {
+            start - 1
+        } else {
+            start
+        }
+    };
+    let end_of = |i: usize| {
+        let end = ranges[i].end;
+        if text[end..].starts_with('}') && text[..ranges[i].start].ends_with("${") {
+            end + 1
+        } else {
+            end
+        }
+    };
+
+    // (position, order, markup): soft breaks sort before bars, and bars
+    // before word marks at the same spot.
+    let mut inserts: Vec<(usize, u8, String)> = Vec::new();
+    for &word in &line.breaks {
+        if word < ranges.len() {
+            inserts.push((start_of(word), 0, "\\\n  ".to_string()));
+        }
+    }
+    for &bar in &line.bars {
+        match bar {
+            i if i < ranges.len() => inserts.push((start_of(i), 1, "| ".to_string())),
+            _ => inserts.push((text.len(), 3, " |".to_string())),
+        }
+    }
+    for &beat in &line.beats {
+        match beat {
+            i if i < ranges.len() => inserts.push((start_of(i), 1, "· ".to_string())),
+            _ => inserts.push((text.len(), 3, " ·".to_string())),
+        }
+    }
+    for ruby in &line.rubies {
+        if text.get(ruby.at..ruby.end()) == Some(ruby.base.as_str()) {
+            inserts.push((ruby.end(), 2, format!("{{{}}}", ruby.reading)));
+        }
+    }
+    for p in &line.pronunciations {
+        if p.index < ranges.len() {
+            inserts.push((end_of(p.index), 2, format!("{{/{}/}}", p.ipa)));
+        }
+    }
+    for s in &line.sustains {
+        if s.word < ranges.len() {
+            let mark = match s.hold {
+                Hold::Beats(n) => "~".repeat(n as usize),
+                Hold::Fixed(ts) => format!("{{hold:{}}}", hold_time(ts)),
+            };
+            // Inside the word where it was written, as in `lo~~~ve`.
+            let at = match s.within {
+                Some(at) if text.is_char_boundary(ranges[s.word].start + at) => {
+                    (ranges[s.word].start + at).min(ranges[s.word].end)
+                }
+                _ => end_of(s.word),
+            };
+            inserts.push((at, 2, mark));
+        }
+    }
+    // A `<tbd: ...>` placeholder is written over the blank it left.
+    let mut replaced = Vec::new();
+    for (range, placeholder) in blank_ranges(text).into_iter().zip(&line.placeholders) {
+        if let Some(hint) = &placeholder.hint {
+            inserts.push((range.start, 2, format!("<tbd: {}>", hint)));
+            replaced.push(range);
+        }
+    }
+    inserts.sort_by_key(|(at, order, _)| (*at, *order));
+
+    let mut out = String::new();
+    let mut cursor = 0;
+    for (at, _, mark) in inserts {
+        if at >= cursor {
+            out.push_str(&text[cursor..at]);
+        }
+        out.push_str(&mark);
+        cursor = replaced
+            .iter()
+            .find(|r| r.start == at)
+            .map_or(at, |r| r.end)
+            .max(cursor);
+    }
+    out.push_str(&text[cursor..]);
+
+    for alternative in &line.alternatives {
+        let _ = write!(out, " / alt: \"{}\"", alternative.text);
+    }
+    if let Some(anchor) = &line.anchor {
+        let _ = write!(out, " &{}", anchor);
+    }
+    let mut attrs = Vec::new();
+    if let Some(rhyme) = line.rhyme {
+        attrs.push(format!("rhyme: {}", rhyme));
+    }
+    if let Some(stress) = &line.stress {
+        attrs.push(format!("stress: {}", stress));
+    }
+    if !line.chords.is_empty() {
+        attrs.push(format!("chord: {}", line.chords.join(", ")));
+    }
+    if !line.pitches.is_empty() {
+        attrs.push(format!("pitch: {}", line.pitches.join(", ")));
+    }
+    if let Some(timing) = line.timing {
+        attrs.push(format!("timing: {}", timestamp(timing)));
+    }
+    let provenance = &line.provenance;
+    if let Some(author) = &provenance.author {
+        attrs.push(format!("author: {}", author));
+    }
+    if let Some(source) = &provenance.source {
+        attrs.push(format!("source: {}", source));
+    }
+    if let Some(confidence) = provenance.confidence {
+        attrs.push(format!("confidence: {}", confidence));
+    }
+    if let Some(edited) = &provenance.edited {
+        attrs.push(format!("edited: {}", edited));
+    }
+    for translation in &line.translations {
+        attrs.push(format!(
+            "translation.{}: \"{}\"",
+            translation.lang, translation.text
+        ));
+    }
+    for annotation in &line.annotations {
+        attrs.push(format!("{}: {}", annotation.name, annotation.value));
+    }
+    if let Some(id) = &line.id {
+        attrs.push(format!("id: {}", id));
+    }
+    if !attrs.is_empty() {
+        let _ = write!(out, " {{{}}}", attrs.join(", "));
+    }
+    for note in &line.notes {
+        let _ = write!(out, " !! {}: {}", note.kind.keyword(), note.text);
+    }
+    out.push('\n');
+    for part in &line.harmony {
+        let _ = write!(out, "  + {}", line_source(part));
+    }
+    out
+}
@@ -0,0 +1,82 @@
+//! Freestyle capture: lines typed during a take, each stamped with the
+//! moment it was entered, turned into a draft song whose rough timings can
+//! be refined later with `sync` or `retime`.
+//!
+//! A line holding only a section header, such as `CHORUS` or `VERSE[2]`,
+//! starts a new section; lines before any header open the first verse, and
+//! each bare `VERSE` takes the next number.
+
+use crate::ast::Timestamp;
+use crate::format::escape_text;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt::Write;
+
+static HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(VERSE|CHORUS)(?:\[\d+\])?|BRIDGE|PRE-CHORUS|OUTRO|INTRO)$").unwrap()
+});
+
+/// The lines of one take, in the order they were entered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Take {
+    sections: Vec<(String, Vec<(String, Timestamp)>)>,
+    verses: u32,
+}
+
+impl Take {
+    pub fn new() -> Take {
+        Take::default()
+    }
+
+    /// Add a line entered `at` into the take, or start a section when it
+    /// is a header.
+    pub fn push(&mut self, text: &str, at: Timestamp) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if HEADER.is_match(text) {
+            let header = self.header(text);
+            self.sections.push((header, Vec::new()));
+            return;
+        }
+        if self.sections.is_empty() {
+            let header = self.header("VERSE");
+            self.sections.push((header, Vec::new()));
+        }
+        let (_, lines) = self.sections.last_mut().expect("a section was opened");
+        lines.push((text.to_string(), at));
+    }
+
+    fn header(&mut self, text: &str) -> String {
+        match text {
+            "VERSE" => {
+                self.verses += 1;
+                format!("VERSE[{}]", self.verses)
+            }
+            _ => text.to_string(),
+        }
+    }
+
+    /// Lyric lines captured so far, headers aside.
+    pub fn len(&self) -> usize {
+        self.sections.iter().map(|(_, lines)| lines.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The take as song source titled `title`, each line timed where it was
+    /// entered. Headers with no lines under them are left out.
+    pub fn to_source(&self, title: &str) -> String {
+        let mut out = format!("title: \"{}\"\n", title.replace('"', "'"));
+        for (header, lines) in self.sections.iter().filter(|(_, l)| !l.is_empty()) {
+            let _ = writeln!(out, "\n{}", header);
+            for (text, at) in lines {
+                let _ = writeln!(out, "{} {{timing: {}}}", escape_text(text), at);
+            }
+        }
+        out
+    }
+}
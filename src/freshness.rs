@@ -0,0 +1,90 @@
+//! Which exports are up to date, so that re-exporting a catalog only
+//! renders the songs that changed.
+//!
+//! Each export written to a file is stamped with a digest of its inputs
+//! (the resolved song, the format and options, and the version of
+//! lyrics-dsl) and a digest of the output. Stamps are kept one per line:
+//!
+//! ```text
+//! <inputs, 64 hex digits> <output, 64 hex digits> <file>
+//! ```
+//!
+//! An export is fresh while both digests still match, so editing the song
+//! or an included file, changing an option, upgrading, or touching the
+//! output all export it again.
+
+use crate::crypt::sha256::sha256;
+use crate::delivery::{from_hex, to_hex};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Name of the stamp file inside the project directory.
+pub const FILE_NAME: &str = "exports.txt";
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum StampError {
+    #[error("line {0} is not `<inputs> <output> <file>`")]
+    BadLine(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    inputs: [u8; 32],
+    output: [u8; 32],
+}
+
+/// The stamps of every export, by output file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stamps {
+    entries: BTreeMap<String, Stamp>,
+}
+
+/// Digest of everything an export depends on, given as `parts`.
+pub fn inputs(parts: &[&[u8]]) -> [u8; 32] {
+    let mut data = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+    for part in parts {
+        // Length prefixes keep `ab` + `c` apart from `a` + `bc`.
+        data.extend((part.len() as u64).to_be_bytes());
+        data.extend(*part);
+    }
+    sha256(&data)
+}
+
+impl Stamps {
+    pub fn parse(text: &str) -> Result<Stamps, StampError> {
+        let mut entries = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let bad = || StampError::BadLine(i + 1);
+            let mut fields = line.splitn(3, ' ');
+            let mut digest = || fields.next().and_then(from_hex).ok_or_else(bad);
+            let (inputs, output) = (digest()?, digest()?);
+            let file = fields.next().filter(|f| !f.is_empty()).ok_or_else(bad)?;
+            entries.insert(file.to_string(), Stamp { inputs, output });
+        }
+        Ok(Stamps { entries })
+    }
+
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(file, s)| format!("{} {} {}\n", to_hex(&s.inputs), to_hex(&s.output), file))
+            .collect()
+    }
+
+    /// Whether `file`, now holding `contents`, was exported from `inputs`.
+    pub fn is_fresh(&self, file: &str, inputs: &[u8; 32], contents: &[u8]) -> bool {
+        self.entries
+            .get(file)
+            .is_some_and(|s| s.inputs == *inputs && s.output == sha256(contents))
+    }
+
+    /// Note that `contents` were exported to `file` from `inputs`.
+    pub fn record(&mut self, file: &str, inputs: [u8; 32], contents: &[u8]) {
+        let output = sha256(contents);
+        self.entries
+            .insert(file.to_string(), Stamp { inputs, output });
+    }
+}
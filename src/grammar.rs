@@ -0,0 +1,491 @@
+//! Introspection of the pest grammar the parser is generated from.
+//!
+//! The grammar source is embedded into the crate so the `grammar` command can
+//! show the syntax without access to the repository. Rules are parsed with
+//! `pest_meta`, which gives the same view of the grammar that `pest_derive`
+//! compiles, and can be rendered back as pest source, as EBNF, or as an HTML
+//! page of railroad diagrams.
+
+use pest_meta::ast::{Expr, RuleType};
+use pest_meta::parser::{self, Rule as MetaRule};
+use std::fmt::Write;
+
+/// The grammar source compiled into [`crate::parser::LyricsParser`].
+pub const SOURCE: &str = include_str!("lyrics.pest");
+
+/// One rule of the grammar.
+#[derive(Debug, Clone)]
+pub struct GrammarRule {
+    pub name: String,
+    pub ty: RuleType,
+    pub expr: Expr,
+    /// `//` comment lines directly above the rule, without the markers.
+    pub comment: Option<String>,
+}
+
+impl GrammarRule {
+    /// Silent rules (`_{ }`) never appear in parse results.
+    pub fn is_silent(&self) -> bool {
+        self.ty == RuleType::Silent
+    }
+
+    /// The rule as pest source, e.g. `number = @{ ASCII_DIGIT+ }`.
+    pub fn to_pest(&self) -> String {
+        let modifier = match self.ty {
+            RuleType::Normal => "",
+            RuleType::Silent => "_",
+            RuleType::Atomic => "@",
+            RuleType::CompoundAtomic => "$",
+            RuleType::NonAtomic => "!",
+        };
+        format!(
+            "{} = {}{{ {} }}",
+            self.name,
+            modifier,
+            render(&self.expr, " ~ ")
+        )
+    }
+
+    /// The rule as an EBNF production in the style of the README.
+    pub fn to_ebnf(&self) -> String {
+        format!("{} = {} ;", self.name, render(&self.expr, " "))
+    }
+}
+
+/// All rules of the embedded grammar in definition order.
+pub fn rules() -> Vec<GrammarRule> {
+    let pairs = parser::parse(MetaRule::grammar_rules, SOURCE).expect("embedded grammar is valid");
+    let rules = parser::consume_rules(pairs).expect("embedded grammar is valid");
+    with_comments(SOURCE, rules)
+}
+
+/// Rules parsed from `source`, with the comments written above them.
+pub(crate) fn with_comments(source: &str, rules: Vec<pest_meta::ast::Rule>) -> Vec<GrammarRule> {
+    rules
+        .into_iter()
+        .map(|rule| GrammarRule {
+            comment: comment_above(source, &rule.name),
+            name: rule.name,
+            ty: rule.ty,
+            expr: rule.expr,
+        })
+        .collect()
+}
+
+/// Look up a single rule by name.
+pub fn rule(name: &str) -> Option<GrammarRule> {
+    rules().into_iter().find(|r| r.name == name)
+}
+
+fn comment_above(source: &str, name: &str) -> Option<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let index = lines.iter().position(|line| {
+        line.strip_prefix(name)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })?;
+    let comment: Vec<&str> = lines[..index]
+        .iter()
+        .rev()
+        .take_while(|line| line.trim_start().starts_with("//"))
+        .map(|line| line.trim_start().trim_start_matches('/').trim())
+        .collect();
+    if comment.is_empty() || index == comment.len() {
+        // The header comment at the top of the file is not about one rule.
+        return None;
+    }
+    Some(comment.into_iter().rev().collect::<Vec<_>>().join(" "))
+}
+
+/// Render the grammar as pest source, one rule per line.
+pub fn dump() -> String {
+    dump_rules(&rules())
+}
+
+/// Render `rules` as pest source, one rule per line.
+pub fn dump_rules(rules: &[GrammarRule]) -> String {
+    let width = rules.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for rule in rules {
+        if let Some(comment) = &rule.comment {
+            let _ = writeln!(out, "// {}", comment);
+        }
+        let pest = rule.to_pest();
+        let (_, body) = pest.split_once(" = ").expect("rule has a body");
+        let _ = writeln!(out, "{:width$} = {}", rule.name, body, width = width);
+    }
+    out
+}
+
+/// Render the grammar as EBNF productions, one rule per line.
+pub fn to_ebnf() -> String {
+    ebnf_rules(&rules())
+}
+
+/// Render `rules` as EBNF productions, one rule per line.
+pub fn ebnf_rules(rules: &[GrammarRule]) -> String {
+    let width = rules.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for rule in rules {
+        let _ = writeln!(
+            out,
+            "{:width$} = {} ;",
+            rule.name,
+            render(&rule.expr, " "),
+            width = width
+        );
+    }
+    out
+}
+
+const PREC_CHOICE: u8 = 0;
+const PREC_SEQ: u8 = 1;
+const PREC_UNARY: u8 = 2;
+const PREC_ATOM: u8 = 3;
+
+/// Render an expression, joining sequence items with `seq` (` ~ ` for pest
+/// source, a plain space for EBNF).
+fn render(expr: &Expr, seq: &str) -> String {
+    render_prec(expr, seq, PREC_CHOICE)
+}
+
+fn quote(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+#[allow(unreachable_patterns)]
+fn render_prec(expr: &Expr, seq: &str, outer: u8) -> String {
+    let (text, prec) = match expr {
+        Expr::Str(s) => (quote(s), PREC_ATOM),
+        Expr::Insens(s) => (format!("^{}", quote(s)), PREC_ATOM),
+        Expr::Range(a, b) => (format!("'{}'..'{}'", a, b), PREC_ATOM),
+        Expr::Ident(name) => (name.clone(), PREC_ATOM),
+        Expr::PosPred(e) => (format!("&{}", render_prec(e, seq, PREC_UNARY)), PREC_UNARY),
+        Expr::NegPred(e) => (format!("!{}", render_prec(e, seq, PREC_UNARY)), PREC_UNARY),
+        Expr::Seq(..) => {
+            let parts: Vec<String> = flatten_seq(expr)
+                .iter()
+                .map(|e| render_prec(e, seq, PREC_SEQ))
+                .collect();
+            (parts.join(seq), PREC_SEQ)
+        }
+        Expr::Choice(..) => {
+            let parts: Vec<String> = flatten_choice(expr)
+                .iter()
+                .map(|e| render_prec(e, seq, PREC_SEQ))
+                .collect();
+            (parts.join(" | "), PREC_CHOICE)
+        }
+        Expr::Opt(e) => (format!("{}?", render_prec(e, seq, PREC_ATOM)), PREC_UNARY),
+        Expr::Rep(e) => (format!("{}*", render_prec(e, seq, PREC_ATOM)), PREC_UNARY),
+        Expr::RepOnce(e) => (format!("{}+", render_prec(e, seq, PREC_ATOM)), PREC_UNARY),
+        Expr::RepExact(e, n) => (
+            format!("{}{{{}}}", render_prec(e, seq, PREC_ATOM), n),
+            PREC_UNARY,
+        ),
+        Expr::RepMin(e, n) => (
+            format!("{}{{{},}}", render_prec(e, seq, PREC_ATOM), n),
+            PREC_UNARY,
+        ),
+        Expr::RepMax(e, n) => (
+            format!("{}{{,{}}}", render_prec(e, seq, PREC_ATOM), n),
+            PREC_UNARY,
+        ),
+        Expr::RepMinMax(e, a, b) => (
+            format!("{}{{{}, {}}}", render_prec(e, seq, PREC_ATOM), a, b),
+            PREC_UNARY,
+        ),
+        other => (other.to_string(), PREC_ATOM),
+    };
+    // Postfix operands must be atoms, so `(!a)?` and `(a ~ b)*` keep their
+    // parentheses while `a?` does not.
+    if prec < outer {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+fn flatten_seq(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Seq(a, b) => {
+            let mut parts = flatten_seq(a);
+            parts.extend(flatten_seq(b));
+            parts
+        }
+        other => vec![other],
+    }
+}
+
+fn flatten_choice(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Choice(a, b) => {
+            let mut parts = flatten_choice(a);
+            parts.extend(flatten_choice(b));
+            parts
+        }
+        other => vec![other],
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Railroad diagrams
+// ---------------------------------------------------------------------------
+
+/// Layout tree for one railroad diagram.
+enum Node {
+    Terminal(String),
+    NonTerminal(String),
+    Predicate(String, Box<Node>),
+    Sequence(Vec<Node>),
+    Choice(Vec<Node>),
+    Loop(Box<Node>),
+    Skip,
+}
+
+const CHAR_WIDTH: f64 = 8.0;
+const BOX_HALF: f64 = 11.0;
+const GAP: f64 = 10.0;
+const ARM: f64 = 16.0;
+
+impl Node {
+    #[allow(unreachable_patterns)]
+    fn from_expr(expr: &Expr) -> Node {
+        match expr {
+            Expr::Str(s) | Expr::Insens(s) => Node::Terminal(s.clone()),
+            Expr::Range(a, b) => Node::Terminal(format!("{}..{}", a, b)),
+            Expr::Ident(name) => Node::NonTerminal(name.clone()),
+            Expr::PosPred(e) => Node::Predicate("followed by".into(), Box::new(Node::from_expr(e))),
+            Expr::NegPred(e) => Node::Predicate("not".into(), Box::new(Node::from_expr(e))),
+            Expr::Seq(..) => {
+                Node::Sequence(flatten_seq(expr).into_iter().map(Node::from_expr).collect())
+            }
+            Expr::Choice(..) => Node::Choice(
+                flatten_choice(expr)
+                    .into_iter()
+                    .map(Node::from_expr)
+                    .collect(),
+            ),
+            Expr::Opt(e) => Node::Choice(vec![Node::Skip, Node::from_expr(e)]),
+            Expr::RepOnce(e) => Node::Loop(Box::new(Node::from_expr(e))),
+            Expr::Rep(e) => {
+                Node::Choice(vec![Node::Skip, Node::Loop(Box::new(Node::from_expr(e)))])
+            }
+            other => Node::Terminal(other.to_string()),
+        }
+    }
+
+    fn width(&self) -> f64 {
+        match self {
+            Node::Terminal(t) | Node::NonTerminal(t) => {
+                t.chars().count() as f64 * CHAR_WIDTH + 20.0
+            }
+            Node::Predicate(label, inner) => {
+                inner.width().max(label.len() as f64 * 6.0) + 2.0 * GAP
+            }
+            Node::Sequence(items) => {
+                items.iter().map(Node::width).sum::<f64>()
+                    + GAP * items.len().saturating_sub(1) as f64
+            }
+            Node::Choice(items) => items.iter().map(Node::width).fold(0.0, f64::max) + 2.0 * ARM,
+            Node::Loop(inner) => inner.width() + 2.0 * ARM,
+            Node::Skip => 0.0,
+        }
+    }
+
+    /// Height above the baseline the diagram runs along.
+    fn up(&self) -> f64 {
+        match self {
+            Node::Terminal(_) | Node::NonTerminal(_) => BOX_HALF,
+            Node::Predicate(_, inner) => inner.up() + 16.0,
+            Node::Sequence(items) => items.iter().map(Node::up).fold(0.0, f64::max),
+            Node::Choice(items) => items[0].up(),
+            Node::Loop(inner) => inner.up(),
+            Node::Skip => 0.0,
+        }
+    }
+
+    /// Height below the baseline.
+    fn down(&self) -> f64 {
+        match self {
+            Node::Terminal(_) | Node::NonTerminal(_) => BOX_HALF,
+            Node::Predicate(_, inner) => inner.down() + 4.0,
+            Node::Sequence(items) => items.iter().map(Node::down).fold(0.0, f64::max),
+            Node::Choice(items) => {
+                items[0].down()
+                    + items[1..]
+                        .iter()
+                        .map(|i| GAP + i.up().max(4.0) + i.down())
+                        .sum::<f64>()
+            }
+            Node::Loop(inner) => inner.down() + GAP + 4.0,
+            Node::Skip => 0.0,
+        }
+    }
+
+    /// Append SVG for this node entering at `(x, y)` on its baseline.
+    fn render(&self, x: f64, y: f64, svg: &mut String) {
+        let w = self.width();
+        match self {
+            Node::Terminal(text) | Node::NonTerminal(text) => {
+                let radius = if matches!(self, Node::Terminal(_)) {
+                    10
+                } else {
+                    0
+                };
+                let class = if radius > 0 {
+                    "terminal"
+                } else {
+                    "nonterminal"
+                };
+                let _ = write!(
+                    svg,
+                    r#"<rect class="{}" x="{}" y="{}" width="{}" height="{}" rx="{}"/><text x="{}" y="{}">{}</text>"#,
+                    class,
+                    x,
+                    y - BOX_HALF,
+                    w,
+                    2.0 * BOX_HALF,
+                    radius,
+                    x + w / 2.0,
+                    y + 4.0,
+                    escape_html(text)
+                );
+            }
+            Node::Predicate(label, inner) => {
+                line(svg, x, y, x + GAP, y);
+                let iw = inner.width();
+                let ix = x + (w - iw) / 2.0;
+                line(svg, x + GAP, y, ix, y);
+                inner.render(ix, y, svg);
+                line(svg, ix + iw, y, x + w, y);
+                let _ = write!(
+                    svg,
+                    r#"<rect class="predicate" x="{}" y="{}" width="{}" height="{}"/><text class="label" x="{}" y="{}">{}</text>"#,
+                    x + 2.0,
+                    y - inner.up() - 14.0,
+                    w - 4.0,
+                    inner.up() + inner.down() + 18.0,
+                    x + 6.0,
+                    y - inner.up() - 4.0,
+                    label
+                );
+            }
+            Node::Sequence(items) => {
+                let mut cx = x;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        line(svg, cx, y, cx + GAP, y);
+                        cx += GAP;
+                    }
+                    item.render(cx, y, svg);
+                    cx += item.width();
+                }
+            }
+            Node::Choice(items) => {
+                let mut branch_y = y;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        branch_y += items[i - 1].down() + GAP + item.up().max(4.0);
+                        line(svg, x + ARM / 2.0, y, x + ARM / 2.0, branch_y);
+                        line(svg, x + w - ARM / 2.0, branch_y, x + w - ARM / 2.0, y);
+                    }
+                    let iw = item.width();
+                    line(svg, x, y, x + ARM, branch_y);
+                    item.render(x + ARM, branch_y, svg);
+                    line(svg, x + ARM + iw, branch_y, x + w - ARM, branch_y);
+                    line(svg, x + w - ARM, branch_y, x + w, y);
+                }
+            }
+            Node::Loop(inner) => {
+                let iw = inner.width();
+                line(svg, x, y, x + ARM, y);
+                inner.render(x + ARM, y, svg);
+                line(svg, x + ARM + iw, y, x + w, y);
+                let back = y + inner.down() + GAP;
+                line(svg, x + w - ARM / 2.0, y, x + w - ARM / 2.0, back);
+                line(svg, x + w - ARM / 2.0, back, x + ARM / 2.0, back);
+                line(svg, x + ARM / 2.0, back, x + ARM / 2.0, y);
+            }
+            Node::Skip => {}
+        }
+    }
+}
+
+fn line(svg: &mut String, x1: f64, y1: f64, x2: f64, y2: f64) {
+    if (x1 - x2).abs() < f64::EPSILON && (y1 - y2).abs() < f64::EPSILON {
+        return;
+    }
+    let _ = write!(svg, r#"<path d="M{} {}L{} {}"/>"#, x1, y1, x2, y2);
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// SVG railroad diagram for a single rule.
+pub fn railroad_svg(rule: &GrammarRule) -> String {
+    let node = Node::from_expr(&rule.expr);
+    let margin = 20.0;
+    let width = node.width() + 2.0 * margin;
+    let height = node.up() + node.down() + 2.0 * margin;
+    let y = margin + node.up();
+    let mut svg = format!(
+        r#"<svg class="railroad" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    );
+    let _ = write!(
+        svg,
+        r#"<path d="M2 {}L2 {}M2 {}L{} {}"/>"#,
+        y - 8.0,
+        y + 8.0,
+        y,
+        margin,
+        y
+    );
+    node.render(margin, y, &mut svg);
+    let end = margin + node.width();
+    let _ = write!(
+        svg,
+        r#"<path d="M{} {}L{} {}M{} {}L{} {}"/>"#,
+        end,
+        y,
+        width - 2.0,
+        y,
+        width - 2.0,
+        y - 8.0,
+        width - 2.0,
+        y + 8.0
+    );
+    svg.push_str("</svg>");
+    svg
+}
+
+/// A standalone HTML page documenting every non-silent rule with its EBNF
+/// production and railroad diagram.
+pub fn railroad_html() -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Lyrics DSL grammar</title>\n<style>\n\
+         body { font-family: sans-serif; margin: 2em; }\n\
+         pre { background: #f6f8fa; padding: 0.5em; }\n\
+         svg.railroad path { stroke: #333; stroke-width: 2; fill: none; }\n\
+         svg.railroad rect.terminal { fill: #e8f4e8; stroke: #333; stroke-width: 1.5; }\n\
+         svg.railroad rect.nonterminal { fill: #e8eef8; stroke: #333; stroke-width: 1.5; }\n\
+         svg.railroad rect.predicate { fill: none; stroke: #999; stroke-dasharray: 4 2; }\n\
+         svg.railroad text { font: 13px monospace; text-anchor: middle; }\n\
+         svg.railroad text.label { font: 10px sans-serif; text-anchor: start; fill: #666; }\n\
+         </style>\n</head>\n<body>\n<h1>Lyrics DSL grammar</h1>\n",
+    );
+    for rule in rules().iter().filter(|r| !r.is_silent()) {
+        let _ = writeln!(html, "<h2 id=\"{0}\">{0}</h2>", rule.name);
+        if let Some(comment) = &rule.comment {
+            let _ = writeln!(html, "<p>{}</p>", escape_html(comment));
+        }
+        let _ = writeln!(html, "<pre>{}</pre>", escape_html(&rule.to_ebnf()));
+        let _ = writeln!(html, "{}", railroad_svg(rule));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,240 @@
+//! Undo and redo for the commands that edit songs, and the recovery files
+//! that keep unsaved edits through a crash.
+//!
+//! A [`History`] holds whole snapshots of what is being edited rather than
+//! the edits themselves, so any surface can use it without describing its
+//! edits. Memory stays bounded by dropping the oldest snapshots once the
+//! history outgrows its budget.
+
+use crate::crypt::{self, Key};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The bytes of snapshots a [`History`] keeps by default.
+pub const DEFAULT_BUDGET: usize = 8 << 20;
+
+/// How often unsaved edits are written to the recovery file by default.
+pub const AUTOSAVE_EVERY: Duration = Duration::from_secs(5);
+
+/// Something a [`History`] can hold a copy of.
+pub trait Snapshot: Clone {
+    /// Roughly the memory a copy takes, counted against the budget.
+    fn bytes(&self) -> usize;
+}
+
+impl Snapshot for String {
+    fn bytes(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Edits that, made one after another, undo together as one step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Run {
+    Typing,
+    Deleting,
+}
+
+/// Snapshots to undo back to and to redo forward to.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    budget: usize,
+    used: usize,
+    /// The run the last step belongs to, while it can still grow.
+    run: Option<Run>,
+}
+
+impl<T: Snapshot> Default for History<T> {
+    fn default() -> History<T> {
+        History::new(DEFAULT_BUDGET)
+    }
+}
+
+impl<T: Snapshot> History<T> {
+    /// An empty history keeping about `budget` bytes of snapshots.
+    pub fn new(budget: usize) -> History<T> {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            budget,
+            used: 0,
+            run: None,
+        }
+    }
+
+    /// Note that an edit was made to `before`. An edit of the same `run`
+    /// as the one before it joins that step instead of starting another.
+    /// Anything undone can no longer be redone.
+    pub fn record(&mut self, before: T, run: Option<Run>) {
+        for snapshot in self.redo.drain(..) {
+            self.used -= snapshot.bytes();
+        }
+        if run.is_some() && run == self.run && !self.undo.is_empty() {
+            return;
+        }
+        self.run = run;
+        self.used += before.bytes();
+        self.undo.push_back(before);
+        self.trim();
+    }
+
+    /// End the current run, so the next edit is a step of its own.
+    pub fn seal(&mut self) {
+        self.run = None;
+    }
+
+    /// The state before the last step, given the `current` one to redo
+    /// back to; `None` when there is nothing to undo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let before = self.undo.pop_back()?;
+        self.run = None;
+        self.used += current.bytes();
+        self.used -= before.bytes();
+        self.redo.push(current);
+        self.trim();
+        Some(before)
+    }
+
+    /// The state the last undo left, given the `current` one to undo back
+    /// to; `None` when there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let after = self.redo.pop()?;
+        self.run = None;
+        self.used += current.bytes();
+        self.used -= after.bytes();
+        self.undo.push_back(current);
+        self.trim();
+        Some(after)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The bytes of snapshots held.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Forget the oldest steps until the history fits its budget, always
+    /// keeping the latest step.
+    fn trim(&mut self) {
+        while self.used > self.budget && self.undo.len() > 1 {
+            let oldest = self.undo.pop_front().expect("more than one step");
+            self.used -= oldest.bytes();
+        }
+    }
+}
+
+/// A file beside a song holding its unsaved edits, rewritten every so
+/// often while they last and removed once they are saved or dropped. The
+/// recovery file of a locked song is locked with its key.
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    path: PathBuf,
+    every: Duration,
+    /// What was last written and when.
+    written: Option<(String, Instant)>,
+    key: Option<Key>,
+}
+
+impl Recovery {
+    /// The recovery file of the song at `song`: `.name.lyr.recover` in the
+    /// same directory.
+    pub fn for_song(song: &Path) -> Recovery {
+        let name = song
+            .file_name()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        Recovery {
+            path: song.with_file_name(format!(".{}.recover", name)),
+            every: AUTOSAVE_EVERY,
+            written: None,
+            key: None,
+        }
+    }
+
+    /// Keep the recovery file locked under `key`, for a locked song.
+    pub fn locked(mut self, key: Key) -> Recovery {
+        self.key = Some(key);
+        self
+    }
+
+    /// Write at most once every `every`.
+    pub fn every(mut self, every: Duration) -> Recovery {
+        self.every = every;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The edits left behind by a session that did not end cleanly.
+    pub fn load(&self) -> io::Result<Option<String>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let bytes = match (crypt::is_locked(&bytes), &self.key) {
+            (false, _) => bytes,
+            (true, Some(key)) => crypt::unlock(&bytes, key).map_err(invalid)?,
+            (true, None) => return Err(invalid(crypt::CryptError::WrongKey)),
+        };
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|e| invalid(e.utf8_error()))
+    }
+
+    /// Keep `text` in the recovery file if it is `modified` and has changed
+    /// since the last write, once the interval has passed since then, or
+    /// remove the file once nothing is unsaved. Whether it wrote the file.
+    pub fn autosave(&mut self, text: &str, modified: bool, now: Instant) -> io::Result<bool> {
+        if !modified {
+            if self.written.is_some() {
+                self.discard()?;
+            }
+            return Ok(false);
+        }
+        if let Some((written, at)) = &self.written {
+            if written == text || now.duration_since(*at) < self.every {
+                return Ok(false);
+            }
+        }
+        // Written aside and renamed into place, so a crash mid-write never
+        // leaves half a recovery file.
+        let partial = self.path.with_extension("recover.partial");
+        match &self.key {
+            Some(key) => fs::write(
+                &partial,
+                crypt::lock(text.as_bytes(), key).map_err(invalid)?,
+            )?,
+            None => fs::write(&partial, text)?,
+        }
+        fs::rename(&partial, &self.path)?;
+        self.written = Some((text.to_string(), now));
+        Ok(true)
+    }
+
+    /// Remove the recovery file, once its edits are saved or given up.
+    pub fn discard(&mut self) -> io::Result<()> {
+        self.written = None;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn invalid(error: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,131 @@
+//! Commands run around pipeline stages, configured per project:
+//!
+//! ```toml
+//! hooks.pre-parse = "scripts/check-spelling.sh"
+//! hooks.post-export = "curl -sf -T - https://example.com/upload"
+//! ```
+//!
+//! Each hook is a shell command run with the song's path as its last
+//! argument and a JSON payload describing the stage on standard input. A
+//! hook that fails stops the command that triggered it, so hooks can be
+//! used as checks as well as for notifications and uploads.
+
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{self, ExitStatus, Stdio};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Before a song is read and parsed.
+    PreParse,
+    /// After a song is parsed and checked, with its diagnostic counts.
+    PostParse,
+    /// Before a song is exported, with the format and output file.
+    PreExport,
+    /// After a song is exported, with the size written.
+    PostExport,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [
+        Stage::PreParse,
+        Stage::PostParse,
+        Stage::PreExport,
+        Stage::PostExport,
+    ];
+
+    /// The stage as written in `config.toml`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::PreParse => "pre-parse",
+            Stage::PostParse => "post-parse",
+            Stage::PreExport => "pre-export",
+            Stage::PostExport => "post-export",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Stage> {
+        Stage::ALL.into_iter().find(|stage| stage.name() == name)
+    }
+}
+
+/// A shell command to run at a stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    pub stage: Stage,
+    pub command: String,
+}
+
+#[derive(Debug, Error)]
+pub enum HookError {
+    #[error("cannot run {stage} hook `{command}`: {source}")]
+    Spawn {
+        stage: &'static str,
+        command: String,
+        source: std::io::Error,
+    },
+    #[error("{stage} hook `{command}` failed ({status})")]
+    Failed {
+        stage: &'static str,
+        command: String,
+        status: ExitStatus,
+    },
+}
+
+/// The JSON a hook reads: the stage and file, plus what `details` adds for
+/// the stage.
+pub fn payload(stage: Stage, path: &str, details: Value) -> Value {
+    let mut payload = json!({ "stage": stage.name(), "file": path });
+    if let (Some(payload), Value::Object(details)) = (payload.as_object_mut(), details) {
+        payload.extend(details);
+    }
+    payload
+}
+
+/// The shell invocation running `command` with `path` appended.
+fn shell(command: &str, path: &str) -> process::Command {
+    if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.args(["/C", command, path]);
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.args(["-c", &format!("{} \"$@\"", command), "sh", path]);
+        shell
+    }
+}
+
+/// Run every hook in `hooks` for `stage`, in order, stopping at the first
+/// that fails. Their output goes to standard error, keeping standard
+/// output for the command's own, such as an export.
+pub fn run(hooks: &[Hook], stage: Stage, path: &str, details: Value) -> Result<(), HookError> {
+    let payload = payload(stage, path, details).to_string();
+    for hook in hooks.iter().filter(|hook| hook.stage == stage) {
+        let spawn_error = |source| HookError::Spawn {
+            stage: stage.name(),
+            command: hook.command.clone(),
+            source,
+        };
+        let mut child = shell(&hook.command, path)
+            .stdin(Stdio::piped())
+            .stdout(std::io::stderr())
+            .spawn()
+            .map_err(spawn_error)?;
+        // A hook that ignores the payload may exit before reading it.
+        let _ = child
+            .stdin
+            .take()
+            .expect("piped")
+            .write_all(payload.as_bytes());
+        let status = child.wait().map_err(spawn_error)?;
+        if !status.success() {
+            return Err(HookError::Failed {
+                stage: stage.name(),
+                command: hook.command.clone(),
+                status,
+            });
+        }
+    }
+    Ok(())
+}
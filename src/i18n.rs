@@ -0,0 +1,148 @@
+//! Translations of the messages commands print.
+//!
+//! Messages live in catalogs written in a subset of Fluent: `#` comments,
+//! `id = text` entries whose indented continuation lines join the text on
+//! new lines, and `{ $name }` placeholders. Selectors and terms are not
+//! supported. English and Spanish are built in; a project can add another
+//! language, or override built-in messages, with
+//! `.lyricsdsl/locales/<lang>.ftl`. Messages missing from a translation
+//! fall back to English.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// The built-in catalogs, by language code.
+pub const BUILT_IN: &[(&str, &str)] = &[
+    ("en", include_str!("locales/en.ftl")),
+    ("es", include_str!("locales/es.ftl")),
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum I18nError {
+    #[error("line {0}: expected `id = text`")]
+    Syntax(usize),
+    #[error("line {line}: `{id}` is defined twice")]
+    Duplicate { line: usize, id: String },
+}
+
+/// Messages of one language, by id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn parse(text: &str) -> Result<Catalog, I18nError> {
+        let mut messages: HashMap<String, String> = HashMap::new();
+        let mut last: Option<String> = None;
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let id = last.as_ref().ok_or(I18nError::Syntax(i + 1))?;
+                let message = messages.get_mut(id).expect("added with its id");
+                message.push('\n');
+                message.push_str(line.trim());
+                continue;
+            }
+            let (id, text) = line.split_once('=').ok_or(I18nError::Syntax(i + 1))?;
+            let id = id.trim();
+            let valid = id.starts_with(|c: char| c.is_ascii_alphabetic())
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if !valid {
+                return Err(I18nError::Syntax(i + 1));
+            }
+            if messages.contains_key(id) {
+                return Err(I18nError::Duplicate {
+                    line: i + 1,
+                    id: id.to_string(),
+                });
+            }
+            messages.insert(id.to_string(), text.trim().to_string());
+            last = Some(id.to_string());
+        }
+        Ok(Catalog { messages })
+    }
+
+    /// The built-in catalog for `lang`, e.g. `es`.
+    pub fn built_in(lang: &str) -> Option<Catalog> {
+        let (_, text) = BUILT_IN.iter().find(|(code, _)| *code == lang)?;
+        Some(Catalog::parse(text).expect("built-in catalogs parse"))
+    }
+
+    /// Add the messages of `other`, replacing any with the same id.
+    pub fn extend(&mut self, other: Catalog) {
+        self.messages.extend(other.messages);
+    }
+
+    /// Ids of every message, sorted.
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.messages.keys().map(String::as_str).collect();
+        ids.sort();
+        ids
+    }
+
+    /// The message `id` with its placeholders filled from `args`. Unknown
+    /// placeholders are left as written.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> Option<String> {
+        let mut rest = self.messages.get(id)?.as_str();
+        let mut out = String::new();
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}').map(|end| start + end + 1) else {
+                break;
+            };
+            let placeable = &rest[start..end];
+            let name = placeable[1..placeable.len() - 1].trim();
+            let value = name
+                .strip_prefix('$')
+                .and_then(|name| args.iter().find(|(arg, _)| *arg == name));
+            match value {
+                Some((_, value)) => out.push_str(&value.to_string()),
+                None => out.push_str(placeable),
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
+/// The language asked for by the environment: `LYRICS_DSL_LANG`, else the
+/// locale in `LC_ALL`, `LC_MESSAGES` or `LANG`, as a code such as `es`.
+/// `lookup` reads a variable, e.g. [`std::env::var`].
+pub fn language_from_env(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["LYRICS_DSL_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(lookup)
+        .find(|value| !value.is_empty())
+        .map(|locale| {
+            let code = locale
+                .split(['_', '.', '@', '-'])
+                .next()
+                .unwrap_or_default();
+            code.to_ascii_lowercase()
+        })
+        .filter(|code| !code.is_empty() && code != "c" && code != "posix")
+}
+
+static ACTIVE: OnceLock<Catalog> = OnceLock::new();
+static ENGLISH: OnceLock<Catalog> = OnceLock::new();
+
+/// Use `catalog` for [`tr`] from now on. Only the first call has effect.
+pub fn set(catalog: Catalog) {
+    let _ = ACTIVE.set(catalog);
+}
+
+/// The message `id` in the active language, else in English.
+pub fn tr(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let english = ENGLISH.get_or_init(|| Catalog::built_in("en").expect("English is built in"));
+    ACTIVE
+        .get()
+        .and_then(|catalog| catalog.format(id, args))
+        .or_else(|| english.format(id, args))
+        .unwrap_or_else(|| id.to_string())
+}
@@ -0,0 +1,99 @@
+//! Stable IDs for sections and lines.
+//!
+//! A section or line written with `{id: k3j9x2}` keeps that ID through
+//! every later edit, because it lives in the source with the unit it names:
+//! the formatter writes it back, and moving, rewording or renumbering the
+//! unit leaves it alone. Translation management and comment platforms can
+//! hold on to the IDs in the `json` export to find the same lyric again.
+//! [`assign`] gives an ID to every unit that has none, once.
+
+use crate::ast::{Line, Song};
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+/// How many base-36 characters a new ID has.
+const LENGTH: u32 = 6;
+
+/// Every ID `song` already uses, on sections and on lines.
+pub fn ids(song: &Song) -> HashSet<String> {
+    fn line_ids(lines: &[Line], out: &mut HashSet<String>) {
+        for line in lines {
+            out.extend(line.id.clone());
+            line_ids(&line.harmony, out);
+        }
+    }
+    let mut out = HashSet::new();
+    for section in &song.sections {
+        out.extend(section.id.clone());
+        line_ids(&section.lines, &mut out);
+    }
+    for m in &song.macros {
+        line_ids(&m.lines, &mut out);
+    }
+    out
+}
+
+/// Makes IDs no song has used: random, so two writers assigning IDs to
+/// copies of a song are unlikely to pick the same ones.
+struct Generator {
+    state: RandomState,
+    counter: u64,
+    taken: HashSet<String>,
+}
+
+impl Generator {
+    fn next(&mut self) -> String {
+        loop {
+            self.counter += 1;
+            let mut n = self.state.hash_one(self.counter) % 36u64.pow(LENGTH);
+            let mut id = String::new();
+            for _ in 0..LENGTH {
+                id.insert(0, char::from_digit((n % 36) as u32, 36).expect("digit"));
+                n /= 36;
+            }
+            if self.taken.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+
+    fn lines(&mut self, lines: &mut [Line]) -> usize {
+        let mut assigned = 0;
+        for line in lines {
+            if line.id.is_none() && line.include.is_none() {
+                line.id = Some(self.next());
+                assigned += 1;
+            }
+            assigned += self.lines(&mut line.harmony);
+        }
+        assigned
+    }
+}
+
+/// Give every section of `song` without an ID a new one, and with `lines`
+/// every lyric line, harmony part and macro line too. IDs already written
+/// are kept. Returns how many were assigned.
+pub fn assign(song: &mut Song, lines: bool) -> usize {
+    let mut generator = Generator {
+        state: RandomState::new(),
+        counter: 0,
+        taken: ids(song),
+    };
+    let mut assigned = 0;
+    for section in &mut song.sections {
+        if section.id.is_none() {
+            section.id = Some(generator.next());
+            assigned += 1;
+        }
+        if lines {
+            assigned += generator.lines(&mut section.lines);
+        }
+    }
+    if lines {
+        for m in &mut song.macros {
+            assigned += generator.lines(&mut m.lines);
+        }
+    }
+    assigned
+}
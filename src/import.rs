@@ -0,0 +1,188 @@
+//! Importers turning documents written elsewhere into song source.
+//!
+//! [`import_markdown`] reads the convention the `md` exporter writes:
+//!
+//! - an optional `---` front-matter block, kept as the song's metadata;
+//! - a `# Title` heading and, before the first section, an `*Artist*` line;
+//! - a heading of any deeper level per section, named like `Verse 2`,
+//!   `Chorus` or `Pre-Chorus`;
+//! - lyric lines in blockquotes or plain paragraphs, one per source line,
+//!   with `\` or two-space hard breaks; a blank line (or an empty `>` line)
+//!   starts a new stanza;
+//! - an optional code span before a line, as in `` `C G` Walking``, holding
+//!   its chords.
+//!
+//! Backslash escapes are undone and HTML comments are skipped.
+
+use crate::ast::SectionKind;
+use crate::format::escape_text;
+use crate::parser::parse_song;
+use std::fmt::Write;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("line {0}: text before the first section heading")]
+    OutsideSection(usize),
+    #[error("line {line}: `{heading}` does not name a section")]
+    UnknownSection { line: usize, heading: String },
+    #[error("line {0}: front matter is not closed by `---`")]
+    UnclosedFrontMatter(usize),
+    #[error("no sections found")]
+    NoSections,
+    #[error("the converted song does not parse: {0}")]
+    Invalid(String),
+}
+
+/// The section kind and number named by a heading such as `Verse 2`.
+fn section_heading(heading: &str) -> Option<(SectionKind, Option<u32>)> {
+    let (name, number) = match heading.rsplit_once(' ') {
+        Some((name, number)) if number.parse::<u32>().is_ok() => (name.trim(), number.parse().ok()),
+        _ => (heading, None),
+    };
+    let kind = SectionKind::ALL.into_iter().find(|kind| {
+        kind.label().eq_ignore_ascii_case(name) || kind.keyword().eq_ignore_ascii_case(name)
+    })?;
+    let number = number.filter(|_| matches!(kind, SectionKind::Verse | SectionKind::Chorus));
+    Some((kind, number))
+}
+
+/// Undo Markdown backslash escapes; a trailing `\` is a hard break and goes.
+fn unescape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(next)) if next.is_ascii_punctuation() => {
+                out.push(*next);
+                chars.next();
+            }
+            ('\\', None) => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `text` with its surrounding `*` or `_` emphasis removed, if it has any.
+fn emphasized(text: &str) -> Option<&str> {
+    ["*", "_"].into_iter().find_map(|mark| {
+        text.strip_prefix(mark)?
+            .strip_suffix(mark)
+            .filter(|inner| !inner.is_empty())
+    })
+}
+
+/// A lyric line as DSL source: the chords of a leading code span become a
+/// `chord` attribute.
+fn lyric_line(text: &str) -> String {
+    let (chords, text) = match text.strip_prefix('`').and_then(|rest| rest.split_once('`')) {
+        Some((chords, rest)) => (chords.split_whitespace().collect::<Vec<_>>(), rest),
+        None => (Vec::new(), text),
+    };
+    let words = unescape_markdown(text.trim());
+    let mut line = escape_text(&words.split_whitespace().collect::<Vec<_>>().join(" "));
+    if !chords.is_empty() {
+        let _ = write!(line, " {{chord: {}}}", chords.join(", "));
+    }
+    line
+}
+
+/// Convert a Markdown lyric sheet to song source.
+pub fn import_markdown(text: &str) -> Result<String, ImportError> {
+    let text = text.replace("\r\n", "\n");
+    let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l)).peekable();
+
+    let mut front_matter = None;
+    if lines.peek().is_some_and(|(_, l)| l.trim_end() == "---") {
+        let (start, _) = lines.next().expect("peeked");
+        let mut block = String::from("---\n");
+        loop {
+            let Some((_, line)) = lines.next() else {
+                return Err(ImportError::UnclosedFrontMatter(start));
+            };
+            let _ = writeln!(block, "{}", line);
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+        front_matter = Some(block);
+    }
+
+    let mut title = None;
+    let mut artist = None;
+    let mut body = String::new();
+    let mut sections = 0;
+    // Whether the current section has lines, and whether a stanza break
+    // is pending before the next one.
+    let mut in_section = false;
+    let mut has_lines = false;
+    let mut stanza_break = false;
+    let mut in_comment = false;
+
+    for (number, raw) in lines {
+        let line = raw.trim();
+        if in_comment || line.starts_with("<!--") {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix('#') {
+            let level = 1 + heading.chars().take_while(|&c| c == '#').count();
+            let heading = unescape_markdown(heading.trim_start_matches('#').trim());
+            if level == 1 && !in_section && title.is_none() {
+                title = Some(heading);
+                continue;
+            }
+            let (kind, n) = section_heading(&heading).ok_or(ImportError::UnknownSection {
+                line: number,
+                heading: heading.clone(),
+            })?;
+            body.push('\n');
+            let _ = match n {
+                Some(n) => writeln!(body, "{}[{}]", kind.keyword(), n),
+                None => writeln!(body, "{}", kind.keyword()),
+            };
+            sections += 1;
+            in_section = true;
+            has_lines = false;
+            stanza_break = false;
+            continue;
+        }
+
+        let quoted = line.strip_prefix('>').map(str::trim);
+        let content = quoted.unwrap_or(line);
+        if content.is_empty() {
+            stanza_break |= has_lines;
+            continue;
+        }
+        if !in_section {
+            match emphasized(content) {
+                Some(name) if artist.is_none() => artist = Some(unescape_markdown(name)),
+                _ => return Err(ImportError::OutsideSection(number)),
+            }
+            continue;
+        }
+        if std::mem::take(&mut stanza_break) {
+            body.push('\n');
+        }
+        let _ = writeln!(body, "{}", lyric_line(content));
+        has_lines = true;
+    }
+    if sections == 0 {
+        return Err(ImportError::NoSections);
+    }
+
+    let mut out = front_matter.unwrap_or_default();
+    let has_title = out.lines().any(|l| l.starts_with("title:"));
+    let quoted = |s: String| s.replace('"', "");
+    if !has_title {
+        let title = title.unwrap_or_else(|| "Untitled".to_string());
+        let _ = writeln!(out, "title: \"{}\"", quoted(title));
+    }
+    if let Some(artist) = artist.filter(|_| !out.lines().any(|l| l.starts_with("artist:"))) {
+        let _ = writeln!(out, "artist: \"{}\"", quoted(artist));
+    }
+    out.push_str(&body);
+    parse_song(&out).map_err(|e| ImportError::Invalid(e.to_string()))?;
+    Ok(out)
+}
@@ -1 +1,71 @@
+pub mod analysis;
+pub mod ast;
+pub mod audio;
+pub mod capitalize;
+pub mod capture;
+pub mod card;
+pub mod changelog;
+pub mod chords;
+pub mod codes;
+pub mod collab;
+pub mod collate;
+pub mod compare;
+pub mod config;
+pub mod constraints;
+pub mod cowrite;
+pub mod credits;
+pub mod crypt;
+pub mod dedupe;
+pub mod delivery;
+pub mod diagnostic;
+pub mod draft;
+pub mod edit;
+pub mod export;
+pub mod extension;
+pub mod extract;
+pub mod feedback;
+pub mod format;
+pub mod freestyle;
+pub mod freshness;
+pub mod grammar;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod ids;
+pub mod import;
+pub mod lint;
+pub mod manifest;
+pub mod medley;
+pub mod merge;
+pub mod notes;
+pub mod overlay;
 pub mod parser;
+pub mod playback;
+pub mod preset;
+pub mod profile;
+pub mod query;
+pub mod redact;
+pub mod refactor;
+pub mod rights;
+pub mod romanize;
+pub mod rubric;
+pub mod schema;
+pub mod search;
+pub mod selftest;
+pub mod semantic;
+pub mod setlist;
+pub mod songmap;
+pub mod speech;
+pub mod spelling;
+pub mod stt;
+pub mod suggest;
+pub mod sync;
+pub mod takes;
+pub mod teleprompter;
+pub mod template;
+pub mod theme;
+pub mod tui;
+pub mod typography;
+pub mod view;
+pub mod voice;
+pub mod xliff;
@@ -0,0 +1,659 @@
+//! Style checks on songs that parse and resolve: problems that do not make
+//! a song wrong but that are worth tidying.
+//!
+//! Each [`Rule`] says whether it can fix what it finds and how far its
+//! fixes can be trusted; `lint --fix` applies the machine-applicable ones
+//! and `fix` offers all of them one at a time.
+
+use crate::analysis::consistency;
+use crate::analysis::language;
+use crate::analysis::syllables::count_line_in;
+use crate::ast::{word_ranges, words, Line, RefTarget, Section, SectionKind, Song, Span};
+use crate::codes::{self, Code};
+use crate::diagnostic::{Applicability, Diagnostic, Edit, Fix};
+use crate::export::tightest_budget;
+use crate::format::header_source;
+use crate::semantic::{header_span, resolve};
+use crate::spelling::Dictionary;
+use std::ops::Range;
+
+/// What a rule looks at: the parsed song, its source text, its file name
+/// without the extension and the dictionary spelling is checked against,
+/// if the project has one.
+pub struct Context<'a> {
+    pub song: &'a Song,
+    pub source: &'a str,
+    pub name: &'a str,
+    pub dictionary: Option<&'a Dictionary>,
+}
+
+/// A lint rule.
+pub struct Rule {
+    pub name: &'static str,
+    /// The code every finding of the rule carries.
+    pub code: &'static Code,
+    pub summary: &'static str,
+    /// How far the rule's fixes can be trusted, or `None` when it has no
+    /// fixer and only reports.
+    pub fixer: Option<Applicability>,
+    check: fn(&Context) -> Vec<Diagnostic>,
+}
+
+/// Every rule, in the order their findings are reported.
+pub const RULES: &[Rule] = &[
+    Rule {
+        name: "missing-title",
+        code: &codes::MISSING_TITLE,
+        summary: "the song has no `title`; the fix takes it from the file name",
+        fixer: Some(Applicability::MaybeIncorrect),
+        check: |cx| {
+            missing_title(cx.song, cx.source, cx.name)
+                .into_iter()
+                .collect()
+        },
+    },
+    Rule {
+        name: "section-numbering",
+        code: &codes::SECTION_NUMBERING,
+        summary: "numbered sections run from 1 in order, renumbered with their `REPEAT`s",
+        fixer: Some(Applicability::MachineApplicable),
+        check: |cx| numberings(cx.song, cx.source),
+    },
+    Rule {
+        name: "header-format",
+        code: &codes::HEADER_FORMAT,
+        summary: "section headers are written as `fmt` writes them",
+        fixer: Some(Applicability::MachineApplicable),
+        check: |cx| headers(cx.song, cx.source),
+    },
+    Rule {
+        name: "line-length",
+        code: &codes::LINE_LENGTH,
+        summary: "lines fit a row of the tightest export format named in `targets`",
+        fixer: None,
+        check: |cx| line_length(cx.song),
+    },
+    Rule {
+        name: "recurring-lines",
+        code: &codes::RECURRING_LINE,
+        summary: "a line that recurs is written the same way each time",
+        fixer: Some(Applicability::MaybeIncorrect),
+        check: |cx| recurring_lines(cx.song, cx.source),
+    },
+    Rule {
+        name: "spelling",
+        code: &codes::SPELLING,
+        summary: "lyrics are spelt as in the project's dictionary and word list",
+        fixer: Some(Applicability::MachineApplicable),
+        check: |cx| match cx.dictionary {
+            Some(dictionary) => spelling(cx.song, cx.source, dictionary),
+            None => Vec::new(),
+        },
+    },
+    Rule {
+        name: "translation-alignment",
+        code: &codes::TRANSLATION_ALIGNMENT,
+        summary: "lines have every language in `translations`, within `syllable_delta` syllables",
+        fixer: None,
+        check: |cx| translation_alignment(cx.song),
+    },
+];
+
+impl Rule {
+    /// Run the rule. Findings take the rule's code, and fixes its
+    /// applicability or are dropped if it has no fixer.
+    pub fn check(&self, cx: &Context) -> Vec<Diagnostic> {
+        let mut diagnostics = (self.check)(cx);
+        for diagnostic in &mut diagnostics {
+            diagnostic.code = Some(self.code);
+            match (&mut diagnostic.fix, self.fixer) {
+                (Some(fix), Some(applicability)) => fix.applicability = applicability,
+                _ => diagnostic.fix = None,
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Run every rule on `song`, parsed from `source`; `name` is the song's
+/// file name without its extension, suggested as the title. Spelling is
+/// only checked with a `dictionary`.
+pub fn check(
+    song: &Song,
+    source: &str,
+    name: &str,
+    dictionary: Option<&Dictionary>,
+) -> Vec<Diagnostic> {
+    let cx = Context {
+        song,
+        source,
+        name,
+        dictionary,
+    };
+    RULES.iter().flat_map(|rule| rule.check(&cx)).collect()
+}
+
+/// `source` with every machine-applicable fix among `diagnostics` made,
+/// leaving out any that overlaps one already taken, and the descriptions of
+/// the fixes made.
+pub fn apply_safe_fixes(source: &str, diagnostics: &[Diagnostic]) -> (String, Vec<String>) {
+    let mut taken: Vec<&Fix> = Vec::new();
+    for fix in diagnostics.iter().filter_map(|d| d.fix.as_ref()) {
+        if fix.is_machine_applicable() && !taken.iter().any(|t| t.overlaps(fix)) {
+            taken.push(fix);
+        }
+    }
+    let edits = taken.iter().flat_map(|fix| fix.edits.clone()).collect();
+    let descriptions = taken.iter().map(|fix| fix.description.clone()).collect();
+    (Fix::new("", edits).apply(source), descriptions)
+}
+
+fn missing_title(song: &Song, source: &str, name: &str) -> Option<Diagnostic> {
+    if song.title().is_some() {
+        return None;
+    }
+    let title = name.replace('"', "");
+    let (at, entry) = match &song.front_matter {
+        Some(front) => {
+            let fence = source[front.span.start..].find('\n').map_or(0, |nl| nl + 1);
+            let at = front.span.start + fence;
+            (at, format!("title: '{}'\n", title.replace('\'', "''")))
+        }
+        // Every song without front matter starts with metadata.
+        None => {
+            let at = source.len() - source.trim_start().len();
+            (at, format!("title: \"{}\"\n", title))
+        }
+    };
+    let span = Span::new(at, at);
+    Some(
+        Diagnostic::warning("the song has no title", span).with_fix(Fix::replace(
+            format!("add the title `{}`", title),
+            span,
+            &entry,
+        )),
+    )
+}
+
+/// Span of the `[n]` directly after a section keyword starting at `start`.
+fn number_span(source: &str, start: usize, kind: SectionKind) -> Option<Span> {
+    let open = start + kind.keyword().len();
+    let rest = source.get(open..)?;
+    let close = rest.strip_prefix('[')?.find(']')?;
+    Some(Span::new(open, open + close + 2))
+}
+
+/// Span of the `[n]` in the header of `section`, which for a `SECTION`
+/// follows its name.
+fn header_number_span(source: &str, section: &Section) -> Option<Span> {
+    if section.kind != SectionKind::Custom {
+        return number_span(source, section.span.start, section.kind);
+    }
+    let header = source.get(section.span.start..)?;
+    let header = &header[..header.find('\n').unwrap_or(header.len())];
+    let open = section.span.start + header.find('[')?;
+    let close = source[open..].find(']')?;
+    Some(Span::new(open, open + close + 1))
+}
+
+/// The numbering findings of every kind of section, and of every name of
+/// `SECTION`, that is numbered.
+fn numberings(song: &Song, source: &str) -> Vec<Diagnostic> {
+    let mut names: Vec<&str> = Vec::new();
+    for section in &song.sections {
+        if let (SectionKind::Custom, Some(name), Some(_)) =
+            (section.kind, &section.name, section.number)
+        {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+    }
+    let kinds = [SectionKind::Verse, SectionKind::Chorus]
+        .into_iter()
+        .map(|kind| (kind, None));
+    let custom = names
+        .into_iter()
+        .map(|name| (SectionKind::Custom, Some(name)));
+    kinds
+        .chain(custom)
+        .filter_map(|(kind, name)| numbering(song, source, kind, name))
+        .collect()
+}
+
+/// `source` with its numbered sections renumbered 1, 2, 3... in the order
+/// they are written, together with the `REPEAT`s that name them, and a
+/// description of each kind renumbered.
+pub fn renumber(song: &Song, source: &str) -> (String, Vec<String>) {
+    let cx = Context {
+        song,
+        source,
+        name: "",
+        dictionary: None,
+    };
+    let rule = RULES
+        .iter()
+        .find(|r| r.name == "section-numbering")
+        .expect("the numbering rule");
+    apply_safe_fixes(source, &rule.check(&cx))
+}
+
+/// Numbered sections of `kind`, and for a `SECTION` of the name `name`,
+/// must run 1, 2, 3... in order.
+fn numbering(
+    song: &Song,
+    source: &str,
+    kind: SectionKind,
+    name: Option<&str>,
+) -> Option<Diagnostic> {
+    let numbered: Vec<_> = song
+        .sections
+        .iter()
+        .filter(|s| s.kind == kind && s.name.as_deref() == name && s.repeat.is_none())
+        .filter_map(|s| s.number.map(|n| (s, n)))
+        .collect();
+    let first_wrong = numbered
+        .iter()
+        .enumerate()
+        .find(|(i, (_, n))| *n as usize != i + 1)?
+        .0;
+
+    let mut edits = Vec::new();
+    // Old number to new, as of each point in the song: a `REPEAT` names the
+    // latest section with that number before it.
+    let mut renumbered: Vec<(u32, u32)> = Vec::new();
+    let mut next = 1;
+    for section in &song.sections {
+        match &section.repeat {
+            None if section.kind == kind && section.name.as_deref() == name => {
+                let Some(old) = section.number else { continue };
+                let span = header_number_span(source, section)?;
+                edits.push(Edit {
+                    span,
+                    replacement: format!("[{}]", next),
+                });
+                renumbered.retain(|(from, _)| *from != old);
+                renumbered.push((old, next));
+                next += 1;
+            }
+            Some(reference) => {
+                let RefTarget::Section {
+                    kind: target,
+                    number: Some(old),
+                } = reference.target
+                else {
+                    continue;
+                };
+                let new = renumbered.iter().find(|(from, _)| *from == old);
+                if let (true, Some((_, new))) = (target == kind, new) {
+                    let span = number_span(source, reference.span.start, kind)?;
+                    edits.push(Edit {
+                        span,
+                        replacement: format!("[{}]", new),
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+
+    let list = |numbers: Vec<String>| numbers.join(", ");
+    let found = list(numbered.iter().map(|(_, n)| n.to_string()).collect());
+    let expected = list((1..=numbered.len()).map(|n| n.to_string()).collect());
+    let (section, _) = numbered[first_wrong];
+    let span = Span::new(
+        section.span.start,
+        section.span.start + section.kind.keyword().len(),
+    );
+    let label = match name {
+        Some(name) => format!("{} {}", kind.keyword(), name),
+        None => kind.keyword().to_string(),
+    };
+    Some(
+        Diagnostic::warning(
+            format!(
+                "{} sections are numbered {}; expected {}",
+                label, found, expected
+            ),
+            span,
+        )
+        .with_fix(Fix::new(
+            format!("renumber {} sections {}", label, expected),
+            edits,
+        )),
+    )
+}
+
+/// Headers should read as `fmt` writes them.
+fn headers(song: &Song, source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for section in &song.sections {
+        let start = section.span.start;
+        let end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |nl| start + nl);
+        let written = source[start..end].trim_end_matches('\r');
+        let canonical = header_source(section);
+        if written != canonical {
+            let span = Span::new(start, start + written.len());
+            diagnostics.push(
+                Diagnostic::warning(
+                    format!("header `{}` is not in canonical form", written),
+                    span,
+                )
+                .with_fix(Fix::replace(
+                    format!("rewrite as `{}`", canonical),
+                    span,
+                    &canonical,
+                )),
+            );
+        }
+    }
+    diagnostics
+}
+
+/// Lines should fit a row of every format the song is exported to, so none
+/// of them has to wrap where the writer did not choose. Lines are measured
+/// with their variables expanded, and reported once however often they are
+/// sung.
+fn line_length(song: &Song) -> Vec<Diagnostic> {
+    let targets = song.targets();
+    let Some((format, budget)) = tightest_budget(targets.iter().map(String::as_str)) else {
+        return Vec::new();
+    };
+    let mut seen = Vec::new();
+    let mut diagnostics = Vec::new();
+    let resolved = resolve(song).song;
+    for (_, line) in resolved.lines() {
+        if !budget.wraps(line) || seen.contains(&line.span) {
+            continue;
+        }
+        seen.push(line.span);
+        let longest = line
+            .segments()
+            .iter()
+            .map(|s| s.chars().count())
+            .max()
+            .unwrap_or(0);
+        diagnostics.push(
+            Diagnostic::warning(
+                format!(
+                    "line is {} characters, more than the {} of a `{}` row",
+                    longest, budget.width, format
+                ),
+                line.span,
+            )
+            .with_note(format!(
+                "`{}` shows it on {} rows",
+                format,
+                budget.rows(line).len()
+            ))
+            .with_help("end the line early with a `\\` where its next row should start"),
+        );
+    }
+    diagnostics
+}
+
+/// Translated songs should be translated throughout: every lyric line in
+/// each language the song asks for, or without `translations` each
+/// language any line has. A section missing a language altogether is
+/// reported at its header, and a line missing one the rest of its section
+/// has at the line. Harmony parts and `USE`s are not required to have
+/// translations. With `syllable_delta`, translations are also counted
+/// against their lines, romanizations aside.
+fn translation_alignment(song: &Song) -> Vec<Diagnostic> {
+    let mut required = song.translation_languages();
+    if required.is_empty() {
+        for (_, line) in song.lines() {
+            for translation in &line.translations {
+                if !required
+                    .iter()
+                    .any(|l| l.eq_ignore_ascii_case(&translation.lang))
+                {
+                    required.push(translation.lang.clone());
+                }
+            }
+        }
+    }
+    let mut diagnostics = Vec::new();
+    for section in &song.sections {
+        let lines: Vec<&Line> = section
+            .lines
+            .iter()
+            .filter(|l| l.include.is_none() && !l.text.trim().is_empty())
+            .collect();
+        for lang in &required {
+            let missing: Vec<&&Line> = lines
+                .iter()
+                .filter(|l| l.translation(lang).is_none())
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            if missing.len() == lines.len() {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!("{} has no `{}` translation", section.label(), lang),
+                        header_span(section),
+                    )
+                    .with_help(format!(
+                        "add `translation.{}: \"...\"` to each of its lines",
+                        lang
+                    )),
+                );
+                continue;
+            }
+            let have = lines.len() - missing.len();
+            for line in missing {
+                diagnostics.push(
+                    Diagnostic::warning(format!("line has no `{}` translation", lang), line.span)
+                        .with_note(format!(
+                            "{} of the {} lines of {} have one",
+                            have,
+                            lines.len(),
+                            section.label()
+                        )),
+                );
+            }
+        }
+    }
+
+    let Some(delta) = song.syllable_delta() else {
+        return diagnostics;
+    };
+    let language = language::of(song);
+    for (_, line) in song.lines() {
+        let original = count_line_in(line, language);
+        for translation in line.translations.iter().filter(|t| !t.is_romanization()) {
+            let target = language::find(&translation.lang).unwrap_or(language);
+            let count: usize = words(&translation.text).map(|w| target.syllables(w)).sum();
+            if count.abs_diff(original) > delta {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!(
+                            "`{}` translation has {} syllables to the line's {}",
+                            translation.lang, count, original
+                        ),
+                        line.span,
+                    )
+                    .with_note(format!(
+                        "`syllable_delta` allows {} either way, so it can be sung to the same melody",
+                        delta
+                    )),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// A line sung more than once should be written the same way each time,
+/// so a chorus typed out twice does not drift. Each variant is reported
+/// with a fix to write it as the most common one, for a person to confirm.
+fn recurring_lines(song: &Song, source: &str) -> Vec<Diagnostic> {
+    // Where a line's words are written plainly, without markup.
+    let plain = |line: &Line| {
+        let span = Span::new(line.span.start, line.span.start + line.text.len());
+        source[span.start..].starts_with(&line.text).then_some(span)
+    };
+    let mut diagnostics = Vec::new();
+    for phrase in consistency::inconsistencies(song) {
+        let (common, others) = phrase.variants.split_first().expect("two or more");
+        let first = common.lines[0];
+        for line in others.iter().flat_map(|v| &v.lines) {
+            let span = plain(line).unwrap_or(line.span);
+            let mut diagnostic = Diagnostic::warning(
+                format!("`{}` recurs elsewhere as `{}`", line.text, common.text),
+                span,
+            )
+            .with_label(
+                first.span,
+                format!("written this way {} time(s)", common.lines.len()),
+            );
+            if let (Some(span), Some(_)) = (plain(line), plain(first)) {
+                diagnostic = diagnostic.with_fix(Fix::replace(
+                    format!("write it as `{}`", common.text),
+                    span,
+                    common.text,
+                ));
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+/// Lyrics should be spelt as the dictionary has them. A misspelling with a
+/// single suggestion is fixed; with several, they are listed for a person
+/// to choose from. Variables, anchors, attributes and notes are not
+/// checked, nor is `runnin'` when `running` is a word.
+fn spelling(song: &Song, source: &str, dictionary: &Dictionary) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (_, lead) in song.lines() {
+        for line in std::iter::once(lead).chain(&lead.harmony) {
+            diagnostics.extend(misspellings(line, source, dictionary));
+        }
+    }
+    diagnostics
+}
+
+fn misspellings(line: &Line, source: &str, dictionary: &Dictionary) -> Vec<Diagnostic> {
+    // The lead's span runs over its harmony lines.
+    let end = line.harmony.first().map_or(line.span.end, |h| h.span.start);
+    let region = &source[line.span.start..end];
+    let written = checked_words(region);
+    let text = masked(&line.text);
+    let words = checked_words(&line.text);
+    let mut diagnostics = Vec::new();
+    for (i, range) in words.iter().enumerate() {
+        let word = &line.text[range.clone()];
+        let dropped_g = text[range.end..].starts_with('\'')
+            && word.ends_with("in")
+            && dictionary.check(&format!("{}g", word));
+        if dictionary.check(word) || dropped_g {
+            continue;
+        }
+        // The same occurrence of the word in the source, if it is written
+        // plainly there.
+        let nth = words[..i]
+            .iter()
+            .filter(|r| &line.text[(*r).clone()] == word)
+            .count();
+        let at = written
+            .iter()
+            .filter(|r| &region[(*r).clone()] == word)
+            .nth(nth)
+            .map(|r| Span::new(line.span.start + r.start, line.span.start + r.end));
+        let suggestions = dictionary.suggest(word);
+        let message = format!("`{}` is not in the dictionary", word);
+        let mut diagnostic = Diagnostic::warning(message, at.unwrap_or(line.span));
+        diagnostic = match (suggestions.as_slice(), at) {
+            ([], _) => diagnostic.with_help("if it is spelt as meant, add it to the word list"),
+            ([only], Some(span)) => diagnostic.with_fix(Fix::replace(
+                format!("replace `{}` with `{}`", word, only),
+                span,
+                only,
+            )),
+            (many, _) => {
+                let quoted: Vec<String> = many.iter().map(|s| format!("`{}`", s)).collect();
+                diagnostic.with_help(format!("did you mean {}?", or_list(&quoted)))
+            }
+        };
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+/// `a`, `a or b`, `a, b or c`.
+fn or_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} or {}", rest.join(", "), last),
+    }
+}
+
+/// Ranges of the words of lyric `text`, or of its source, worth spell
+/// checking: hyphenated words are checked a part at a time, and words with
+/// digits are skipped.
+fn checked_words(text: &str) -> Vec<Range<usize>> {
+    let text = masked(text);
+    let mut ranges = Vec::new();
+    for word in word_ranges(&text) {
+        let mut start = word.start;
+        for part in text[word.clone()].split('-') {
+            let range = start..start + part.len();
+            start = range.end + 1;
+            if !part.is_empty() && !part.chars().any(|c| c.is_numeric()) {
+                ranges.push(range);
+            }
+        }
+    }
+    ranges
+}
+
+/// `text` with everything but its words blanked out, byte for byte:
+/// escapes, `$` variables, `&` anchors, `{…}` attributes and `!!` notes.
+fn masked(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    let mut depth = 0;
+    let mut skip_word = false;
+    while let Some((at, c)) = chars.next() {
+        let blank = match c {
+            _ if text[at..].starts_with("!!") => {
+                out.extend(std::iter::repeat_n(' ', text.len() - at));
+                break;
+            }
+            '\\' => {
+                let next = chars.next().map_or(0, |(_, n)| n.len_utf8());
+                out.extend(std::iter::repeat_n(' ', 1 + next));
+                continue;
+            }
+            '{' => {
+                depth += 1;
+                true
+            }
+            '}' => {
+                depth -= 1;
+                true
+            }
+            '$' | '&' => {
+                skip_word = true;
+                true
+            }
+            _ if depth > 0 => true,
+            c if skip_word && (c.is_alphanumeric() || c == '_' || c == '-') => true,
+            _ => {
+                skip_word = false;
+                false
+            }
+        };
+        match blank {
+            true => out.extend(std::iter::repeat_n(' ', c.len_utf8())),
+            false => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,19 @@
+# Messages of the lyrics-dsl commands, in Fluent syntax.
+# To translate them, copy this file to .lyricsdsl/locales/<lang>.ftl in
+# your project and replace the text after each `=`, keeping the `{ $name }`
+# placeholders.
+
+output-written = 💾 Output written to: { $path }
+cannot-read = cannot read '{ $path }': { $error }
+has-errors = '{ $path }' has errors
+nothing-changed = Nothing changed
+up-to-date = { $path } is up to date
+validation-failed = { $count } file(s) failed validation
+files-changed = { $changed } of { $total } file(s) changed
+files-would-change = { $changed } of { $total } file(s) would change
+fix-prompt = Apply fix: { $fix }? [y/n/u/q]
+fix-answer = Answer y, n, u to undo the last fix, or q.
+fix-undone = Undid the last fix.
+fix-nothing-to-undo = No fix to undo.
+fix-none = No automatic fix.
+fixes-applied = { $count } fix(es) applied to { $path }
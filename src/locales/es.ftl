@@ -0,0 +1,16 @@
+# Mensajes de las órdenes de lyrics-dsl, en español.
+
+output-written = 💾 Salida escrita en: { $path }
+cannot-read = no se puede leer '{ $path }': { $error }
+has-errors = '{ $path }' tiene errores
+nothing-changed = No ha cambiado nada
+up-to-date = { $path } está al día
+validation-failed = { $count } archivo(s) no superaron la validación
+files-changed = { $changed } de { $total } archivo(s) cambiados
+files-would-change = { $changed } de { $total } archivo(s) cambiarían
+fix-prompt = ¿Aplicar la corrección: { $fix }? [y/n/u/q]
+fix-answer = Responde y (sí), n (no), u (deshacer la última) o q (salir).
+fix-undone = Se deshizo la última corrección.
+fix-nothing-to-undo = No hay ninguna corrección que deshacer.
+fix-none = No hay corrección automática.
+fixes-applied = { $count } corrección(es) aplicada(s) a { $path }
@@ -1,8 +1,11 @@
 use clap::{Arg, Command};
-use colored::*;
 use std::io::{self, Write};
 
-mod parser;
+use lyrics_dsl::parser;
+use lyrics_dsl::theme::Role;
+
+mod commands;
+use commands::theme;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize CLI with clap
@@ -31,16 +34,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .action(clap::ArgAction::SetTrue)
                 .help("Enable verbose output")
         )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("PROFILE")
+                .value_parser(["strict", "standard", "permissive"])
+                .global(true)
+                .help("Parser profile songs are checked against (default: from .lyricsdsl/config.toml, else standard)")
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .value_name("LANG")
+                .global(true)
+                .help("Language of messages, e.g. es (default: from LYRICS_DSL_LANG or LANG)")
+        )
+        .subcommands(commands::all())
         .get_matches();
 
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        // Errors can be rendered diagnostics spanning several lines, so they
+        // are printed as written rather than debug-quoted.
+        if let Err(error) = commands::run(name, sub_matches) {
+            eprintln!("{} {}", theme().paint(Role::Error, "Error:"), error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Print welcome message
-    println!("{}", "🎵 Lyrics DSL Processor v0.1.0".bright_cyan().bold());
-    println!("{}", "================================".bright_cyan());
+    println!("{}", theme().paint(Role::Heading, "🎵 Lyrics DSL Processor v0.1.0"));
+    println!("{}", theme().paint(Role::Heading, "================================"));
 
     // Handle verbose flag
     let verbose = matches.get_flag("verbose");
     if verbose {
-        println!("{}", "Verbose mode enabled".yellow());
+        println!("{}", theme().paint(Role::Warning, "Verbose mode enabled"));
     }
 
     // Test basic functionality
@@ -52,18 +81,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             process_lyrics_file(input_file, output_file.map(|s| s.as_str()), verbose)?;
         }
         (None, _) => {
-            println!("{}", "No input file specified. Running in interactive mode...".green());
+            println!("{}", theme().paint(Role::Success, "No input file specified. Running in interactive mode..."));
             interactive_mode(verbose)?;
         }
     }
 
-    println!("{}", "\n✅ Lyrics DSL execution completed successfully!".bright_green().bold());
+    println!("{}", theme().paint(Role::Success, "\n✅ Lyrics DSL execution completed successfully!"));
     Ok(())
 }
 
 fn test_dependencies(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
     if verbose {
-        println!("{}", "\n🔧 Testing dependencies...".blue());
+        println!("{}", theme().paint(Role::Info, "\n🔧 Testing dependencies..."));
     }
 
     // Test pest parsing capabilities
@@ -76,7 +105,7 @@ fn test_dependencies(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
     test_regex_integration(verbose)?;
     
     if verbose {
-        println!("{}", "✅ All dependencies working correctly".green());
+        println!("{}", theme().paint(Role::Success, "✅ All dependencies working correctly"));
     }
     
     Ok(())
@@ -137,10 +166,8 @@ fn test_regex_integration(verbose: bool) -> Result<(), Box<dyn std::error::Error
     let verse_pattern = Regex::new(r"^VERSE\[\d+\]")?;
     let test_line = "VERSE[1]";
     
-    if verse_pattern.is_match(test_line) {
-        if verbose {
-            println!("    ✓ Regex pattern matching working");
-        }
+    if verse_pattern.is_match(test_line) && verbose {
+        println!("    ✓ Regex pattern matching working");
     }
     
     Ok(())
@@ -151,7 +178,7 @@ fn process_lyrics_file(
     output_file: Option<&str>, 
     verbose: bool
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", format!("📄 Processing lyrics file: {}", input_file).cyan());
+    println!("{}", theme().paint(Role::Heading, &format!("📄 Processing lyrics file: {}", input_file)));
     
     // Check if input file exists
     if !std::path::Path::new(input_file).exists() {
@@ -171,10 +198,10 @@ fn process_lyrics_file(
     match output_file {
         Some(output_path) => {
             std::fs::write(output_path, &processed)?;
-            println!("{}", format!("💾 Output written to: {}", output_path).green());
+            println!("{}", theme().paint(Role::Success, &format!("💾 Output written to: {}", output_path)));
         }
         None => {
-            println!("{}", "📺 Processed output:".yellow());
+            println!("{}", theme().paint(Role::Heading, "📺 Processed output:"));
             println!("{}", processed);
         }
     }
@@ -183,11 +210,11 @@ fn process_lyrics_file(
 }
 
 fn interactive_mode(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "🎤 Interactive Lyrics DSL Mode".magenta().bold());
-    println!("{}", "Type lyrics or DSL commands (type 'quit' to exit):".dimmed());
+    println!("{}", theme().paint(Role::Heading, "🎤 Interactive Lyrics DSL Mode"));
+    println!("{}", theme().paint(Role::Hint, "Type lyrics or DSL commands (type 'quit' to exit, ':record [TRACK]' to capture a freestyle):"));
     
     loop {
-        print!("{}", "lyrics> ".bright_blue());
+        print!("{}", theme().paint(Role::Prompt, "lyrics> "));
         io::stdout().flush()?;
         
         let mut input = String::new();
@@ -200,9 +227,18 @@ fn interactive_mode(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
         }
         
         if input == "quit" || input == "exit" {
-            println!("{}", "👋 Goodbye!".bright_yellow());
+            println!("{}", theme().paint(Role::Hint, "👋 Goodbye!"));
             break;
         }
+
+        if input == ":record" || input.starts_with(":record ") {
+            let track = input[":record".len()..].trim();
+            let track = (!track.is_empty()).then_some(track);
+            if let Err(e) = commands::freestyle::record(track) {
+                eprintln!("{}", theme().paint(Role::Error, &e.to_string()));
+            }
+            continue;
+        }
         
         // Process the input (placeholder)
         process_interactive_input(input, verbose)?;
@@ -224,19 +260,19 @@ fn process_interactive_input(input: &str, verbose: bool) -> Result<(), Box<dyn s
     let bridge_regex = Regex::new(r"^BRIDGE$")?;
     
     if verse_regex.is_match(input) {
-        println!("{}", "🎼 Detected verse marker".blue());
+        println!("{}", theme().paint(Role::Verse, "🎼 Detected verse marker"));
     } else if chorus_regex.is_match(input) {
-        println!("{}", "🎵 Detected chorus marker".green());
+        println!("{}", theme().paint(Role::Chorus, "🎵 Detected chorus marker"));
     } else if bridge_regex.is_match(input) {
-        println!("{}", "🌉 Detected bridge marker".purple());
+        println!("{}", theme().paint(Role::Bridge, "🌉 Detected bridge marker"));
     } else if input.starts_with('[') && input.ends_with(']') {
-        println!("{}", "🏷️  Detected custom section marker".yellow());
+        println!("{}", theme().paint(Role::Section, "🏷️  Detected custom section marker"));
     } else {
-        println!("{}", "📝 Processed lyric line".dimmed());
+        println!("{}", theme().paint(Role::Hint, "📝 Processed lyric line"));
     }
     
     // Echo the processed result
-    println!("   → {}", input.bright_white());
+    println!("   → {}", theme().paint(Role::Lyric, input));
     
     Ok(())
 }
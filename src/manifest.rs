@@ -0,0 +1,125 @@
+//! Catalog manifests: the metadata of every song in a catalog as one flat
+//! CSV or JSON file, for rights-management spreadsheets.
+//!
+//! Each song is one record with the same columns, left empty when the song
+//! does not say. Durations are written as `m:ss` whatever form the song
+//! used, the explicit flag as `true` or `false`, and ISRC and ISWC codes in
+//! their usual hyphenated form.
+
+use crate::analysis::alignment::parse_duration;
+use crate::analysis::vocabulary::csv_row;
+use crate::ast::Song;
+use crate::rights::{Isrc, Iswc};
+use serde::Serialize;
+
+/// The columns of a manifest, in order.
+pub const COLUMNS: [&str; 11] = [
+    "path",
+    "title",
+    "artist",
+    "writers",
+    "year",
+    "duration",
+    "language",
+    "explicit",
+    "isrc",
+    "iswc",
+    "publishers",
+];
+
+/// One song's row in a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Record {
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub writers: Option<String>,
+    pub year: Option<String>,
+    pub duration: Option<String>,
+    pub language: Option<String>,
+    pub explicit: Option<bool>,
+    pub isrc: Option<String>,
+    pub iswc: Option<String>,
+    /// Publishers and their shares, as written.
+    pub publishers: Option<String>,
+}
+
+impl Record {
+    /// The record of `song`, found at `path`.
+    pub fn new(path: &str, song: &Song) -> Record {
+        let meta = |key: &str| {
+            song.meta_str(key)
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Record {
+            path: path.to_string(),
+            title: meta("title"),
+            artist: meta("artist"),
+            writers: meta("writers"),
+            year: meta("year"),
+            duration: meta("duration").map(|d| match parse_duration(&d) {
+                Some(seconds) => clock(seconds),
+                None => d,
+            }),
+            language: meta("lang"),
+            explicit: meta("explicit").and_then(|e| flag(&e)),
+            isrc: meta("isrc").map(|c| Isrc::parse(&c).map_or(c, |isrc| isrc.to_string())),
+            iswc: meta("iswc").map(|c| Iswc::parse(&c).map_or(c, |iswc| iswc.to_string())),
+            publishers: meta("publishers"),
+        }
+    }
+
+    /// The record's fields, in [`COLUMNS`] order.
+    pub fn fields(&self) -> Vec<String> {
+        let text = |v: &Option<String>| v.clone().unwrap_or_default();
+        vec![
+            self.path.clone(),
+            text(&self.title),
+            text(&self.artist),
+            text(&self.writers),
+            text(&self.year),
+            text(&self.duration),
+            text(&self.language),
+            self.explicit.map(|e| e.to_string()).unwrap_or_default(),
+            text(&self.isrc),
+            text(&self.iswc),
+            text(&self.publishers),
+        ]
+    }
+}
+
+/// `records` as CSV, with a header row.
+pub fn to_csv(records: &[Record]) -> String {
+    let header: Vec<String> = COLUMNS.iter().map(|c| c.to_string()).collect();
+    let mut out = csv_row(&header);
+    for record in records {
+        out.push_str(&csv_row(&record.fields()));
+    }
+    out
+}
+
+/// `records` as a JSON array of objects, with `null` where a song does not
+/// say.
+pub fn to_json(records: &[Record]) -> String {
+    serde_json::to_string_pretty(records).expect("records serialize") + "\n"
+}
+
+/// `seconds` as `m:ss`, or `h:mm:ss` from an hour on.
+fn clock(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    match total / 3600 {
+        0 => format!("{}:{:02}", total / 60, total % 60),
+        hours => format!("{}:{:02}:{:02}", hours, total / 60 % 60, total % 60),
+    }
+}
+
+/// The explicit flag as written: `true`, `yes` or `explicit`, or `false`,
+/// `no` or `clean`.
+fn flag(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "true" | "yes" | "explicit" => Some(true),
+        "false" | "no" | "clean" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,221 @@
+//! Medleys: one performance stitched together from sections of other songs,
+//! read from a composition file.
+//!
+//! ```text
+//! title: "Eighties Medley"
+//! artist: "The Covers"
+//!
+//! use "take-on-me.lyr"::CHORUS
+//! use "africa.lyr"::VERSE[1] transposed +2
+//! use "africa.lyr"::*hook       # the section anchored `&hook`
+//! use "closer.lyr"              # the whole song
+//! ```
+//!
+//! Paths are relative to the composition file. A reference without a number
+//! takes the first section of that kind, and `transposed` moves the chords
+//! of what is taken by a number of semitones. Numbered sections are
+//! numbered again in medley order, so two songs' `VERSE[1]` become
+//! `VERSE[1]` and `VERSE[2]`. `key: "value"` lines give the medley's own
+//! metadata, and `#` starts a comment.
+
+use crate::ast::{MetaEntry, Section, SectionKind, Song, Span, Value};
+use crate::chords::nashville::Key;
+use crate::chords::transpose_song;
+use crate::setlist::{parse_value, strip_comment};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MedleyError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("the medley uses no songs")]
+    Empty,
+    #[error("line {line}: `{file}` has no section {section}")]
+    NoSection {
+        line: usize,
+        file: String,
+        section: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Medley {
+    pub metadata: Vec<(String, String)>,
+    pub parts: Vec<MedleyPart>,
+}
+
+/// A `use` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MedleyPart {
+    /// Path of the song, relative to the composition file.
+    pub file: String,
+    /// The section taken, as written after `::`, e.g. `CHORUS[2]` or
+    /// `*hook`; `None` takes the whole song.
+    pub section: Option<String>,
+    /// Semitones to move the chords by.
+    pub transpose: i32,
+    /// Line of the composition file, for messages.
+    pub line: usize,
+}
+
+impl Medley {
+    pub fn parse(text: &str) -> Result<Medley, MedleyError> {
+        let mut medley = Medley::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let syntax = |message: &str| MedleyError::Syntax {
+                line,
+                message: message.to_string(),
+            };
+            let content = strip_comment(raw).trim();
+            if content.is_empty() {
+                continue;
+            }
+            if let Some(rest) = content.strip_prefix("use ") {
+                medley
+                    .parts
+                    .push(parse_use(rest.trim(), line).map_err(syntax)?);
+                continue;
+            }
+            let (key, value) = content
+                .split_once(':')
+                .ok_or_else(|| syntax("expected `use \"song.lyr\"` or `key: value`"))?;
+            let value = value.trim();
+            let value = match value.starts_with('"') {
+                true => parse_value(value).ok_or_else(|| syntax("invalid string"))?,
+                false => value.to_string(),
+            };
+            medley.metadata.push((key.trim().to_string(), value));
+        }
+        match medley.parts.is_empty() {
+            true => Err(MedleyError::Empty),
+            false => Ok(medley),
+        }
+    }
+
+    /// The medley as one song: its metadata, then the sections taken from
+    /// `songs`, the resolved song of each part in order.
+    pub fn compose(&self, songs: &[Song]) -> Result<Song, MedleyError> {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(key, value)| MetaEntry {
+                key: key.clone(),
+                value: Value::String(value.clone()),
+                block: false,
+                span: Default::default(),
+            })
+            .collect();
+        let mut medley = Song {
+            metadata,
+            ..Song::default()
+        };
+        for (part, song) in self.parts.iter().zip(songs) {
+            let mut taken = Song {
+                sections: match &part.section {
+                    Some(reference) => vec![find(song, reference)
+                        .ok_or_else(|| MedleyError::NoSection {
+                            line: part.line,
+                            file: part.file.clone(),
+                            section: reference.clone(),
+                        })?
+                        .clone()],
+                    None => song.sections.clone(),
+                },
+                ..Song::default()
+            };
+            if part.transpose != 0 {
+                transpose_song(&mut taken, part.transpose, flats(song, part.transpose));
+            }
+            // Lines are already resolved, and references, anchors and
+            // spans would point across songs.
+            for section in &mut taken.sections {
+                section.repeat = None;
+                section.anchor = None;
+                section.span = Span::default();
+                for line in &mut section.lines {
+                    line.anchor = None;
+                }
+            }
+            medley.sections.extend(taken.sections);
+        }
+        // Songs number their sections from 1; number them again in medley
+        // order so headers stay unique.
+        let mut counts = HashMap::new();
+        for section in &mut medley.sections {
+            if section.number.is_some() {
+                let count = counts
+                    .entry((section.kind, section.name.clone()))
+                    .or_insert(0);
+                *count += 1;
+                section.number = Some(*count);
+            }
+        }
+        Ok(medley)
+    }
+}
+
+/// The part of a `use` line after `use`.
+fn parse_use(text: &str, line: usize) -> Result<MedleyPart, &'static str> {
+    let rest = text
+        .strip_prefix('"')
+        .ok_or("expected a quoted path after `use`")?;
+    let (file, rest) = rest.split_once('"').ok_or("unterminated path")?;
+    let (target, transpose) = match rest.rfind("transposed") {
+        Some(at) => {
+            let shift = rest[at + "transposed".len()..].trim();
+            let shift = shift.strip_prefix('+').unwrap_or(shift);
+            let shift = shift
+                .parse::<i32>()
+                .map_err(|_| "expected semitones after `transposed`, e.g. +2")?;
+            (rest[..at].trim(), shift)
+        }
+        None => (rest.trim(), 0),
+    };
+    let section = match target {
+        "" => None,
+        target => {
+            let reference = target
+                .strip_prefix("::")
+                .ok_or("expected `::` before the section")?
+                .trim();
+            let keyword = reference.split(['[', ' ']).next().unwrap_or("");
+            let known = (reference.len() > 1 && reference.starts_with('*'))
+                || SectionKind::from_keyword(keyword).is_some()
+                || keyword == SectionKind::Custom.keyword();
+            if !known {
+                return Err("expected a section such as CHORUS, VERSE[2] or *anchor");
+            }
+            Some(reference.to_string())
+        }
+    };
+    Ok(MedleyPart {
+        file: file.to_string(),
+        section,
+        transpose,
+        line,
+    })
+}
+
+/// The section of `song` that `reference` names.
+fn find<'a>(song: &'a Song, reference: &str) -> Option<&'a Section> {
+    song.sections
+        .iter()
+        .find(|s| match reference.strip_prefix('*') {
+            Some(anchor) => s.anchor.as_ref().is_some_and(|a| a.name == anchor),
+            None if reference.contains('[') => s.header() == reference,
+            None => s.header().split('[').next() == Some(reference),
+        })
+}
+
+/// Whether chords moved `semitones` from the song's key are spelled with
+/// flats; sharps when the song has no key.
+fn flats(song: &Song, semitones: i32) -> bool {
+    let Some(key) = song.meta_str("key").and_then(|k| Key::parse(&k)) else {
+        return false;
+    };
+    let tonic = key.tonic.transpose(semitones);
+    let name = format!("{}{}", tonic.name(false), if key.minor { "m" } else { "" });
+    Key::parse(&name).is_some_and(|k| k.flats)
+}
@@ -0,0 +1,316 @@
+//! Structure-aware three-way merge and diff of song files.
+//!
+//! Each version is cut into blocks: the metadata header, then one block per
+//! section, `REPEAT` or `MACRO`, each running up to the next one so blank
+//! lines travel with the block above. Blocks are matched across versions by
+//! their header (plus anchor, and occurrence for repeated headers), so two
+//! writers editing different verses never conflict. A block changed on both
+//! sides is merged line by line, and only lines changed differently on both
+//! sides become conflicts. Files that do not parse are merged line by line.
+//!
+//! [`diff`] uses the same blocks to report what changed between two
+//! versions section by section.
+
+use crate::ast::Song;
+use crate::parser::parse_song;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::collections::HashMap;
+
+/// Result of [`merge`]: the merged text, possibly with conflict markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged {
+    pub text: String,
+    pub conflicts: usize,
+}
+
+const OURS_MARKER: &str = "<<<<<<< ours\n";
+const SEPARATOR: &str = "=======\n";
+const THEIRS_MARKER: &str = ">>>>>>> theirs\n";
+
+/// Merge the changes from `base` to `ours` and from `base` to `theirs`.
+pub fn merge(base: &str, ours: &str, theirs: &str) -> Merged {
+    match (parse_song(base), parse_song(ours), parse_song(theirs)) {
+        (Ok(b), Ok(o), Ok(t)) => {
+            merge_blocks(&blocks(&b, base), &blocks(&o, ours), &blocks(&t, theirs))
+        }
+        _ => merge_lines(base, ours, theirs),
+    }
+}
+
+/// How a block differs between two versions, in [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChange {
+    /// `metadata`, a header such as `VERSE[2]`, `REPEAT CHORUS` or
+    /// `MACRO hook`, with `#2` appended for a second block of that name.
+    pub block: String,
+    pub kind: ChangeKind,
+    /// Non-blank lines removed and added within the block.
+    pub lines: Vec<LineChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineChange {
+    Removed(String),
+    Added(String),
+}
+
+/// The blocks that differ from `old` to `new`, in the order of `new` with
+/// removed blocks last. Files that do not parse are compared as one block.
+pub fn diff(old: &str, new: &str) -> Vec<BlockChange> {
+    let whole = |text: &str| {
+        vec![Block {
+            key: "song#1".to_string(),
+            text: text.to_string(),
+        }]
+    };
+    let (old, new) = match (parse_song(old), parse_song(new)) {
+        (Ok(o), Ok(n)) => (blocks(&o, old), blocks(&n, new)),
+        _ => (whole(old), whole(new)),
+    };
+
+    let mut changes = Vec::new();
+    for block in &new {
+        let before = find(&old, &block.key);
+        if before == Some(block.text.as_str()) {
+            continue;
+        }
+        let lines = line_changes(before.unwrap_or(""), &block.text);
+        if before.is_some() && lines.is_empty() {
+            continue;
+        }
+        changes.push(BlockChange {
+            block: display_key(&block.key),
+            kind: match before {
+                Some(_) => ChangeKind::Modified,
+                None => ChangeKind::Added,
+            },
+            lines,
+        });
+    }
+    for block in old.iter().filter(|b| find(&new, &b.key).is_none()) {
+        changes.push(BlockChange {
+            block: display_key(&block.key),
+            kind: ChangeKind::Removed,
+            lines: line_changes(&block.text, ""),
+        });
+    }
+    changes
+}
+
+fn display_key(key: &str) -> String {
+    key.strip_suffix("#1").unwrap_or(key).to_string()
+}
+
+fn line_changes(old: &str, new: &str) -> Vec<LineChange> {
+    let old: Vec<&str> = old.lines().map(str::trim_end).collect();
+    let new: Vec<&str> = new.lines().map(str::trim_end).collect();
+    let mut changes = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        if matches!(op, DiffOp::Equal { .. }) {
+            continue;
+        }
+        changes.extend(
+            old[op.old_range()]
+                .iter()
+                .filter(|l| !l.is_empty())
+                .map(|l| LineChange::Removed(l.to_string())),
+        );
+        changes.extend(
+            new[op.new_range()]
+                .iter()
+                .filter(|l| !l.is_empty())
+                .map(|l| LineChange::Added(l.to_string())),
+        );
+    }
+    changes
+}
+
+/// A keyed slice of a song's source.
+#[derive(Debug, Clone)]
+struct Block {
+    key: String,
+    text: String,
+}
+
+fn blocks(song: &Song, source: &str) -> Vec<Block> {
+    let mut starts: Vec<(usize, String)> = song
+        .sections
+        .iter()
+        .map(|section| {
+            let mut key = match &section.repeat {
+                Some(reference) => format!("REPEAT {}", reference.target),
+                None => section.header(),
+            };
+            if let Some(anchor) = &section.anchor {
+                key.push_str(&format!(" &{}", anchor));
+            }
+            (section.span.start, key)
+        })
+        .chain(
+            song.macros
+                .iter()
+                .map(|m| (m.span.start, format!("MACRO {}", m.name))),
+        )
+        .collect();
+    starts.sort_by_key(|(start, _)| *start);
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut result = vec![Block {
+        key: "metadata".to_string(),
+        text: source[..starts.first().map_or(source.len(), |(s, _)| *s)].to_string(),
+    }];
+    for (i, (start, key)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(source.len(), |(s, _)| *s);
+        let n = seen.entry(key.clone()).or_default();
+        *n += 1;
+        result.push(Block {
+            key: format!("{}#{}", key, n),
+            text: source[*start..end].to_string(),
+        });
+    }
+    result
+}
+
+fn find<'a>(blocks: &'a [Block], key: &str) -> Option<&'a str> {
+    blocks
+        .iter()
+        .find(|b| b.key == key)
+        .map(|b| b.text.as_str())
+}
+
+fn merge_blocks(base: &[Block], ours: &[Block], theirs: &[Block]) -> Merged {
+    // Our order, with blocks only they have slotted in after the block that
+    // precedes them on their side.
+    let mut order: Vec<&str> = ours.iter().map(|b| b.key.as_str()).collect();
+    for (i, block) in theirs.iter().enumerate() {
+        if order.contains(&block.key.as_str()) {
+            continue;
+        }
+        let at = theirs[..i]
+            .iter()
+            .rev()
+            .find_map(|prev| order.iter().position(|k| *k == prev.key))
+            .map_or(0, |p| p + 1);
+        order.insert(at, &block.key);
+    }
+    for block in base {
+        if !order.contains(&block.key.as_str()) {
+            order.push(&block.key);
+        }
+    }
+
+    let mut merged = Merged {
+        text: String::new(),
+        conflicts: 0,
+    };
+    for key in order {
+        let (b, o, t) = (find(base, key), find(ours, key), find(theirs, key));
+        let text = if o == t || t == b {
+            o.map(str::to_string)
+        } else if o == b {
+            t.map(str::to_string)
+        } else {
+            match (b, o, t) {
+                (Some(b), Some(o), Some(t)) => {
+                    let lines = merge_lines(b, o, t);
+                    merged.conflicts += lines.conflicts;
+                    Some(lines.text)
+                }
+                // Deleted on one side and edited on the other.
+                _ => {
+                    merged.conflicts += 1;
+                    Some(conflict(o.unwrap_or(""), t.unwrap_or("")))
+                }
+            }
+        };
+        if let Some(text) = text {
+            if !merged.text.is_empty() && !merged.text.ends_with('\n') {
+                merged.text.push('\n');
+            }
+            merged.text.push_str(&text);
+        }
+    }
+    merged
+}
+
+fn conflict(ours: &str, theirs: &str) -> String {
+    let mut out = String::from(OURS_MARKER);
+    push_lines(&mut out, ours);
+    out.push_str(SEPARATOR);
+    push_lines(&mut out, theirs);
+    out.push_str(THEIRS_MARKER);
+    out
+}
+
+fn push_lines(out: &mut String, text: &str) {
+    out.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// For each base line that is unchanged on a side, its index on that side.
+fn matches(base: &[&str], side: &[&str]) -> HashMap<usize, usize> {
+    let mut map = HashMap::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, side) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for n in 0..len {
+                map.insert(old_index + n, new_index + n);
+            }
+        }
+    }
+    map
+}
+
+/// Classic diff3 over lines.
+pub fn merge_lines(base: &str, ours: &str, theirs: &str) -> Merged {
+    let b: Vec<&str> = base.split_inclusive('\n').collect();
+    let o: Vec<&str> = ours.split_inclusive('\n').collect();
+    let t: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let (in_ours, in_theirs) = (matches(&b, &o), matches(&b, &t));
+
+    let mut merged = Merged {
+        text: String::new(),
+        conflicts: 0,
+    };
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    loop {
+        // The next base line kept by both sides ends the unstable chunk.
+        let stable = (i..b.len()).find(|n| {
+            matches!((in_ours.get(n), in_theirs.get(n)), (Some(&x), Some(&y)) if x >= j && y >= k)
+        });
+        let (bi, oj, tk) = match stable {
+            Some(n) => (n, in_ours[&n], in_theirs[&n]),
+            None => (b.len(), o.len(), t.len()),
+        };
+        let (bc, oc, tc) = (&b[i..bi], &o[j..oj], &t[k..tk]);
+        if oc == tc || tc == bc {
+            merged.text.push_str(&oc.concat());
+        } else if oc == bc {
+            merged.text.push_str(&tc.concat());
+        } else {
+            merged.conflicts += 1;
+            merged.text.push_str(&conflict(&oc.concat(), &tc.concat()));
+        }
+        match stable {
+            Some(n) => {
+                merged.text.push_str(b[n]);
+                (i, j, k) = (n + 1, oj + 1, tk + 1);
+            }
+            None => break,
+        }
+    }
+    merged
+}
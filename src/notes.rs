@@ -0,0 +1,88 @@
+//! `!! todo:` and `!! idea:` notes left on lines while writing, and the
+//! `____` and `<tbd: ...>` placeholders still to be filled in.
+//!
+//! Notes are collected from the song as written, before `REPEAT` and `USE`
+//! are resolved, so a note on a chorus is reported once rather than at
+//! every repeat.
+
+use crate::ast::{Line, Note, NoteKind, Song};
+
+/// A note together with where it was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Located {
+    /// `Verse 1`, or `macro name` for notes inside a macro.
+    pub section: String,
+    /// 1-based source line.
+    pub line: usize,
+    /// The lyric the note is attached to.
+    pub text: String,
+    pub kind: NoteKind,
+    pub note: String,
+}
+
+/// Every note in `song`, in source order.
+pub fn collect(song: &Song, source: &str) -> Vec<Located> {
+    let mut notes = Vec::new();
+    for r#macro in &song.macros {
+        let label = format!("macro {}", r#macro.name.name);
+        notes.extend(located(&label, &r#macro.lines, source));
+    }
+    for section in &song.sections {
+        notes.extend(located(&section.label(), &section.lines, source));
+    }
+    notes.sort_by_key(|n| n.line);
+    notes
+}
+
+/// Every placeholder in `song` as a `todo`, in source order, with its
+/// hint or a reminder to fill in the blank as the note.
+pub fn placeholders(song: &Song, source: &str) -> Vec<Located> {
+    fn walk(section: &str, lines: &[Line], source: &str, out: &mut Vec<Located>) {
+        for line in lines {
+            out.extend(line.placeholders.iter().map(|p| {
+                Located {
+                    section: section.to_string(),
+                    line: p.span.line_col(source).0,
+                    text: line.text.clone(),
+                    kind: NoteKind::Todo,
+                    note: p
+                        .hint
+                        .clone()
+                        .unwrap_or_else(|| "fill in the blank".to_string()),
+                }
+            }));
+            walk(section, &line.harmony, source, out);
+        }
+    }
+    let mut found = Vec::new();
+    for r#macro in &song.macros {
+        let label = format!("macro {}", r#macro.name.name);
+        walk(&label, &r#macro.lines, source, &mut found);
+    }
+    for section in &song.sections {
+        walk(&section.label(), &section.lines, source, &mut found);
+    }
+    found.sort_by_key(|n| n.line);
+    found
+}
+
+/// Only the notes still asking for work.
+pub fn open(notes: Vec<Located>) -> Vec<Located> {
+    notes.into_iter().filter(|n| n.kind.is_open()).collect()
+}
+
+fn located<'a>(
+    section: &'a str,
+    lines: &'a [Line],
+    source: &'a str,
+) -> impl Iterator<Item = Located> + 'a {
+    lines.iter().flat_map(move |line| {
+        line.notes.iter().map(move |note: &Note| Located {
+            section: section.to_string(),
+            line: note.span.line_col(source).0,
+            text: line.text.clone(),
+            kind: note.kind,
+            note: note.text.clone(),
+        })
+    })
+}
@@ -1,11 +1,906 @@
+use pest::error::{ErrorVariant, InputLocation};
+use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
 
+use crate::ast::{
+    word_ranges, Alternative, Annotation, Attribute, Credit, CreditKind, FrontMatter, HistoryEntry,
+    Hold, Line, Macro, MetaEntry, Name, Note, NoteKind, Placeholder, Pronunciation, RawLine,
+    RefTarget, Reference, Ruby, Section, SectionKind, Song, Span, Sustain, TempoChange, Timestamp,
+    Translation, Value, Variable, BLANK,
+};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+use crate::suggest::{self, did_you_mean};
+
 #[derive(Parser)]
 #[grammar = "lyrics.pest"]
 pub struct LyricsParser;
 
+/// Metadata keys, as listed by the `meta_key` rule.
+pub const META_KEYS: [&str; 21] = [
+    "title",
+    "artist",
+    "tempo",
+    "key",
+    "time_sig",
+    "time",
+    "genre",
+    "lang",
+    "writers",
+    "duration",
+    "meter",
+    "capo",
+    "targets",
+    "range",
+    "year",
+    "explicit",
+    "isrc",
+    "iswc",
+    "publishers",
+    "translations",
+    "syllable_delta",
+];
+
+/// Line attributes the grammar checks itself; any other is an annotation.
+pub const LINE_ATTRIBUTES: [&str; 10] = [
+    "rhyme",
+    "stress",
+    "chord",
+    "pitch",
+    "timing",
+    "author",
+    "source",
+    "confidence",
+    "edited",
+    "id",
+];
+
+/// Keywords that can start a section, besides the section kinds.
+const SECTION_DIRECTIVES: [&str; 3] = ["SECTION", "REPEAT", "MACRO"];
+
 pub fn parse_lyrics(input: &str) -> Result<(), pest::error::Error<Rule>> {
-    LyricsParser::parse(Rule::song, input).map(|_| ())
+    parse_tree(input).map(|_| ())
+}
+
+/// The `song` pair of `input`, with every time in it checked to fit a
+/// [`Timestamp`].
+fn parse_tree(input: &str) -> Result<Pair<'_, Rule>, pest::error::Error<Rule>> {
+    let song = LyricsParser::parse(Rule::song, input)?
+        .next()
+        .expect("song rule always yields one pair");
+    for pair in song.clone().into_inner().flatten() {
+        let message = match pair.as_rule() {
+            Rule::timing_info if timestamp(pair.clone()).is_none() => format!(
+                "`{}` is not a time: expected whole minutes and seconds, like `1:02.50`",
+                pair.as_str()
+            ),
+            Rule::hold_time if hold_time(pair.as_str()).is_none() => format!(
+                "`{}` is too long a hold: expected seconds or milliseconds, like `1.2s`",
+                pair.as_str()
+            ),
+            _ => continue,
+        };
+        return Err(pest::error::Error::new_from_span(
+            ErrorVariant::CustomError { message },
+            pair.as_span(),
+        ));
+    }
+    Ok(song)
+}
+
+/// Parse `input` into a typed [`Song`].
+///
+/// The result is the tree as written: variables are not interpolated and
+/// `REPEAT`/`USE` directives are not expanded until
+/// [`crate::semantic::resolve`] runs.
+pub fn parse_song(input: &str) -> Result<Song, pest::error::Error<Rule>> {
+    let song = parse_tree(input)?;
+
+    let mut result = Song::default();
+    for pair in song.into_inner() {
+        match pair.as_rule() {
+            Rule::front_matter => {
+                let mut front = FrontMatter {
+                    span: span_of(&pair),
+                    ..FrontMatter::default()
+                };
+                for entry in pair.into_inner() {
+                    match entry.as_rule() {
+                        Rule::yaml_entry => result.metadata.push(build_yaml_entry(entry)),
+                        _ => front.other.push(RawLine {
+                            text: entry.as_str().trim_end_matches('\n').to_string(),
+                            span: span_of(&entry),
+                        }),
+                    }
+                }
+                result.front_matter = Some(front);
+            }
+            Rule::metadata => {
+                for entry in pair.into_inner() {
+                    match entry.as_rule() {
+                        Rule::variable_def => result.variables.push(build_variable(entry)),
+                        Rule::tempo_change => result.tempo_changes.push(build_tempo_change(entry)),
+                        Rule::credits => {
+                            result.credits.extend(entry.into_inner().map(build_credit))
+                        }
+                        Rule::history => result
+                            .history
+                            .extend(entry.into_inner().map(build_history_entry)),
+                        _ => result.metadata.push(build_meta_entry(entry)),
+                    }
+                }
+            }
+            Rule::sections => {
+                for section in pair.into_inner() {
+                    let item = section.into_inner().next().expect("section alternative");
+                    match item.as_rule() {
+                        Rule::macro_def => result.macros.push(build_macro(item)),
+                        Rule::repeat => result.sections.push(build_repeat(item)),
+                        _ => result.sections.push(build_section(item)),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+/// Turn a syntax error from [`parse_song`] into a [`Diagnostic`] spanning
+/// the text the parser stopped at, with what it expected there in words.
+pub fn error_diagnostic(error: &pest::error::Error<Rule>, source: &str) -> Diagnostic {
+    let start = match error.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    }
+    .min(source.len());
+    let found = source[start..]
+        .split(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+    let span = Span::new(start, start + found.len());
+    let positives = match &error.variant {
+        ErrorVariant::ParsingError { positives, .. } => positives.as_slice(),
+        ErrorVariant::CustomError { message } => {
+            return Diagnostic::error(message.clone(), span).with_code(&codes::SYNTAX)
+        }
+    };
+    let mut expected: Vec<String> = Vec::new();
+    for rule in positives {
+        let description = describe(*rule);
+        if !expected.contains(&description) {
+            expected.push(description);
+        }
+    }
+    let message = match expected.split_last() {
+        None => format!("unexpected `{}`", found),
+        Some((last, [])) => format!("expected {}", last),
+        Some((last, rest)) => format!("expected {} or {}", rest.join(", "), last),
+    };
+    let wants_section = positives
+        .iter()
+        .any(|rule| matches!(rule, Rule::song | Rule::sections | Rule::section));
+    if !wants_section {
+        return Diagnostic::error(message, span).with_code(&codes::SYNTAX);
+    }
+    let word: String = found
+        .chars()
+        .take_while(|c| c.is_alphabetic() || *c == '_' || *c == '-')
+        .collect();
+    let word_span = Span::new(start, start + word.len());
+    if positives.contains(&Rule::song) && found[word.len()..].starts_with(':') {
+        let diagnostic = Diagnostic::error(format!("unknown metadata key `{}`", word), word_span)
+            .with_code(&codes::UNKNOWN_METADATA_KEY)
+            .with_note(format!("metadata keys are {}", META_KEYS.join(", ")));
+        return match did_you_mean(&word, META_KEYS) {
+            Some(key) => diagnostic
+                .with_help(suggest::help(key))
+                .with_fix(suggest::fix(&word, word_span, key)),
+            None => diagnostic,
+        };
+    }
+    let mut keywords = SectionKind::ALL
+        .iter()
+        .map(SectionKind::keyword)
+        .chain(SECTION_DIRECTIVES);
+    if keywords.any(|keyword| keyword == word) {
+        if !positives.contains(&Rule::song) {
+            return Diagnostic::error(message, span).with_code(&codes::SYNTAX);
+        }
+        return Diagnostic::error("expected metadata before the first section", word_span)
+            .with_code(&codes::MISSING_METADATA)
+            .with_help("start the song with its title, e.g. `title: \"Untitled\"`");
+    }
+    let keywords = SectionKind::ALL
+        .iter()
+        .map(SectionKind::keyword)
+        .chain(SECTION_DIRECTIVES);
+    let suggestion = did_you_mean(&word, keywords);
+    let looks_like_header = word.chars().count() > 1 && !word.chars().any(char::is_lowercase);
+    let mut diagnostic = if suggestion.is_some() || looks_like_header {
+        Diagnostic::error(format!("unknown section kind `{}`", word), word_span)
+            .with_code(&codes::UNKNOWN_SECTION_KIND)
+    } else {
+        Diagnostic::error(message, span).with_code(&codes::SYNTAX)
+    };
+    diagnostic = diagnostic.with_note(
+        "a section starts with VERSE, CHORUS, BRIDGE, PRE-CHORUS, OUTRO, INTRO, \
+         SECTION, REPEAT or MACRO",
+    );
+    match suggestion {
+        Some(keyword) => diagnostic
+            .with_help(suggest::help(keyword))
+            .with_fix(suggest::fix(&word, word_span, keyword)),
+        None => diagnostic,
+    }
+}
+
+/// What a grammar rule matches, in words for error messages.
+fn describe(rule: Rule) -> String {
+    let words = match rule {
+        Rule::EOI => "the end of the file",
+        Rule::song => "metadata or a section header",
+        Rule::sections | Rule::section | Rule::section_keyword => "a section header",
+        Rule::metadata | Rule::meta_entry | Rule::meta_key => "a metadata entry",
+        Rule::credit | Rule::credit_kind => "an indented `interpolation` or `sample` credit",
+        Rule::credit_attr | Rule::credit_key => {
+            "`lines`, `artist`, `writers`, `publishers` or `year`"
+        }
+        Rule::history_entry | Rule::utc_time => {
+            "an indented history entry like `2026-10-15 09:30 UTC \"message\"`"
+        }
+        Rule::meta_value | Rule::quoted_string => "a quoted string or a number",
+        Rule::section_number => "a section number like `[1]`",
+        Rule::section_attrs | Rule::line_attrs => "`{...}` attributes",
+        Rule::section_anchor | Rule::anchor | Rule::line_anchor_end => "an anchor like `&name`",
+        Rule::lines | Rule::line | Rule::line_content | Rule::use_line => "a lyric line",
+        Rule::harmony_line => "a `+` harmony line",
+        Rule::word
+        | Rule::escaped
+        | Rule::var_ref
+        | Rule::pronounced
+        | Rule::placeholder
+        | Rule::blank
+        | Rule::tbd_hint
+        | Rule::ruby
+        | Rule::ruby_base
+        | Rule::melisma
+        | Rule::held
+        | Rule::bar
+        | Rule::beat
+        | Rule::soft_break => "lyrics",
+        Rule::note | Rule::note_start => "a `!!` note",
+        Rule::alternative | Rule::alternative_start => "an alternative like `/ alt: \"...\"`",
+        Rule::attribute | Rule::line_attribute | Rule::attr_name | Rule::line_attr_key => {
+            "an attribute name"
+        }
+        Rule::timing_info => "a time like `1:02.50`",
+        Rule::confidence => "a confidence from 0 to 1, such as `0.8`",
+        Rule::date => "a date like `2024-05-01`",
+        Rule::id_attr | Rule::unit_id => "an ID of letters, digits, `-` and `_`",
+        Rule::lang_tag => "a language tag like `ja-Latn`",
+        Rule::ruby_text => "furigana in kana",
+        Rule::line_author => "an author",
+        Rule::line_source => "a source",
+        Rule::rhyme_scheme => "a rhyme letter",
+        Rule::stress_pattern => "a stress pattern of `x` and `/`",
+        Rule::chord | Rule::chord_sequence | Rule::chord_root => "a chord",
+        Rule::pitch | Rule::pitch_sequence => "a note such as `E4` or `F#3`",
+        Rule::number => "a number",
+        Rule::identifier | Rule::section_name => "a name",
+        Rule::NEWLINE | Rule::line_end | Rule::blank_line => "the end of the line",
+        _ => return format!("{:?}", rule).replace('_', " "),
+    };
+    words.to_string()
+}
+
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let span = pair.as_span();
+    Span::new(span.start(), span.end())
+}
+
+fn name_of(pair: Pair<Rule>) -> Name {
+    Name::new(pair.as_str(), span_of(&pair))
+}
+
+/// The identifier inside an `anchor`, `anchor_ref` or `var_ref` pair.
+fn inner_name(pair: Pair<Rule>) -> Name {
+    name_of(pair.into_inner().next().expect("identifier"))
+}
+
+fn build_variable(pair: Pair<Rule>) -> Variable {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let name = name_of(inner.next().expect("identifier"));
+    let value = string_contents(inner.next().expect("quoted_string"));
+    Variable { name, value, span }
+}
+
+fn build_tempo_change(pair: Pair<Rule>) -> TempoChange {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let bpm = inner
+        .next()
+        .expect("number")
+        .as_str()
+        .parse()
+        .expect("grammar guarantees digits");
+    let at = build_timestamp(inner.next().expect("timing_info"));
+    TempoChange { bpm, at, span }
+}
+
+fn build_credit(pair: Pair<Rule>) -> Credit {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let kind = match inner.next().expect("credit_kind").as_str() {
+        "sample" => CreditKind::Sample,
+        _ => CreditKind::Interpolation,
+    };
+    let mut credit = Credit {
+        kind,
+        work: string_contents(inner.next().expect("quoted_string")),
+        artist: None,
+        writers: None,
+        publishers: None,
+        year: None,
+        from: None,
+        to: None,
+        span,
+    };
+    let attrs = inner.flat_map(|attrs| attrs.into_inner());
+    for attr in attrs.map(|attr| attr.into_inner().next().expect("credit attribute")) {
+        match attr.as_rule() {
+            Rule::credit_lines => {
+                let mut anchors = attr.into_inner().map(|anchor| Reference {
+                    span: span_of(&anchor),
+                    target: RefTarget::Anchor(inner_name(anchor).name),
+                });
+                credit.from = anchors.next();
+                credit.to = anchors.next();
+            }
+            _ => {
+                let mut field = attr.into_inner();
+                let key = field.next().expect("credit_key").as_str();
+                let value = field.next().expect("credit value");
+                let value = match value.as_rule() {
+                    Rule::quoted_string => string_contents(value),
+                    _ => value.as_str().to_string(),
+                };
+                let slot = match key {
+                    "artist" => &mut credit.artist,
+                    "writers" => &mut credit.writers,
+                    "publishers" => &mut credit.publishers,
+                    _ => &mut credit.year,
+                };
+                *slot = Some(value);
+            }
+        }
+    }
+    credit
+}
+
+fn build_history_entry(pair: Pair<Rule>) -> HistoryEntry {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    HistoryEntry {
+        when: inner.next().expect("utc_time").as_str().to_string(),
+        message: string_contents(inner.next().expect("quoted_string")),
+        span,
+    }
+}
+
+fn build_macro(pair: Pair<Rule>) -> Macro {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let name = name_of(inner.next().expect("identifier"));
+    let lines = inner.next().map(build_lines).unwrap_or_default();
+    Macro { name, lines, span }
+}
+
+fn build_repeat(pair: Pair<Rule>) -> Section {
+    let span = span_of(&pair);
+    let mut section_ref = None;
+    let (mut id, mut attrs) = (None, Vec::new());
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::section_ref => section_ref = Some(part),
+            Rule::section_attrs => (id, attrs) = build_attrs(part),
+            _ => {}
+        }
+    }
+
+    let section_ref = section_ref.expect("section_ref");
+    let ref_span = span_of(&section_ref);
+    let mut inner = section_ref.into_inner();
+    let kind = SectionKind::from_keyword(inner.next().expect("section_kind").as_str())
+        .expect("grammar only accepts known section kinds");
+    let number = inner.next().and_then(section_number);
+
+    Section {
+        kind,
+        name: None,
+        number,
+        anchor: None,
+        id,
+        attrs,
+        repeat: Some(Reference {
+            target: RefTarget::Section { kind, number },
+            span: ref_span,
+        }),
+        lines: Vec::new(),
+        uses: Vec::new(),
+        span,
+    }
+}
+
+fn section_number(pair: Pair<Rule>) -> Option<u32> {
+    pair.into_inner()
+        .next()
+        .expect("number")
+        .as_str()
+        .parse()
+        .ok()
 }
 
+/// The attributes of a section header, with its `id` apart from the rest.
+fn build_attrs(pair: Pair<Rule>) -> (Option<String>, Vec<Attribute>) {
+    let list = pair.into_inner().next().expect("attr_list");
+    let mut id = None;
+    let mut attrs = Vec::new();
+    for attribute in list.into_inner() {
+        match attribute.clone().into_inner().next() {
+            Some(part) if part.as_rule() == Rule::id_attr => id = Some(unit_id(part)),
+            _ => attrs.push(build_attribute(attribute)),
+        }
+    }
+    (id, attrs)
+}
+
+/// The ID of an `id_attr`.
+fn unit_id(pair: Pair<Rule>) -> String {
+    pair.into_inner()
+        .next()
+        .expect("unit_id")
+        .as_str()
+        .to_string()
+}
+
+fn build_lines(pair: Pair<Rule>) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut stanza_break = false;
+    for part in pair.into_inner() {
+        let mut line = match part.as_rule() {
+            Rule::stanza_break => {
+                stanza_break = true;
+                continue;
+            }
+            Rule::use_line => build_use_line(part),
+            _ => build_line(part),
+        };
+        line.stanza_break = std::mem::take(&mut stanza_break);
+        lines.push(line);
+    }
+    lines
+}
+
+fn build_use_line(pair: Pair<Rule>) -> Line {
+    let span = span_of(&pair);
+    let target = pair.into_inner().next().expect("use target");
+    let ref_span = span_of(&target);
+    let target = match target.as_rule() {
+        Rule::anchor_ref => RefTarget::Anchor(inner_name(target).name),
+        _ => RefTarget::Macro(target.as_str().to_string()),
+    };
+    Line {
+        include: Some(Reference {
+            target,
+            span: ref_span,
+        }),
+        span,
+        ..Line::default()
+    }
+}
+
+fn build_meta_entry(pair: Pair<Rule>) -> MetaEntry {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let key = inner.next().expect("meta_key").as_str().to_string();
+    let value = build_value(inner.next().expect("meta_value"));
+    MetaEntry {
+        key,
+        value,
+        block: false,
+        span,
+    }
+}
+
+/// A front-matter entry. Plain numbers read as numbers, like unquoted ones in
+/// the native syntax, and a `[a, b]` list or `- a` items read as a list.
+fn build_yaml_entry(pair: Pair<Rule>) -> MetaEntry {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let key = inner.next().expect("meta_key").as_str().to_string();
+    let mut items = Vec::new();
+    let mut single = None;
+    let (mut flow, mut block) = (false, false);
+    for part in inner {
+        match part.as_rule() {
+            Rule::yaml_scalar => {
+                single = Some(yaml_scalar(part));
+                continue;
+            }
+            Rule::yaml_item => block = true,
+            _ => flow = true,
+        }
+        items.extend(part.into_inner().map(yaml_scalar));
+    }
+    let value = match (single, flow || block) {
+        (Some(value), false) => value,
+        (None, false) => Value::String(String::new()),
+        (single, true) => Value::List(single.into_iter().chain(items).collect()),
+    };
+    MetaEntry {
+        key,
+        value,
+        block,
+        span,
+    }
+}
+
+/// A `yaml_scalar` or `yaml_flow_item`.
+fn yaml_scalar(pair: Pair<Rule>) -> Value {
+    let inner = pair.into_inner().next().expect("scalar alternative");
+    match inner.as_rule() {
+        Rule::yaml_quoted => {
+            let quoted = inner.into_inner().next().expect("quoted scalar");
+            match quoted.as_rule() {
+                Rule::quoted_string => Value::String(string_contents(quoted)),
+                _ => Value::String(quoted.as_str().replace("''", "'")),
+            }
+        }
+        _ => {
+            let text = inner.as_str().trim();
+            let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+            let numeric = match text.split_once('.') {
+                Some((whole, fraction)) => digits(whole) && digits(fraction),
+                None => digits(text),
+            };
+            match numeric {
+                true => Value::Number(text.parse().expect("checked digits")),
+                false => Value::String(text.to_string()),
+            }
+        }
+    }
+}
+
+/// Convert a `meta_value` or `attr_value` pair into a [`Value`].
+fn build_value(pair: Pair<Rule>) -> Value {
+    let inner = pair.into_inner().next().expect("value alternative");
+    match inner.as_rule() {
+        Rule::quoted_string => Value::String(string_contents(inner)),
+        Rule::number => Value::Number(inner.as_str().parse().expect("grammar guarantees digits")),
+        Rule::boolean => Value::Boolean(inner.as_str() == "true"),
+        _ => Value::String(inner.as_str().to_string()),
+    }
+}
+
+fn string_contents(pair: Pair<Rule>) -> String {
+    pair.into_inner()
+        .next()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default()
+}
+
+fn build_section(pair: Pair<Rule>) -> Section {
+    let kind = match pair.as_rule() {
+        Rule::verse => SectionKind::Verse,
+        Rule::chorus => SectionKind::Chorus,
+        Rule::bridge => SectionKind::Bridge,
+        Rule::pre_chorus => SectionKind::PreChorus,
+        Rule::outro => SectionKind::Outro,
+        Rule::intro => SectionKind::Intro,
+        _ => SectionKind::Custom,
+    };
+    let mut section = Section {
+        kind,
+        name: None,
+        number: None,
+        anchor: None,
+        id: None,
+        attrs: Vec::new(),
+        repeat: None,
+        lines: Vec::new(),
+        uses: Vec::new(),
+        span: span_of(&pair),
+    };
+
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::section_name => section.name = Some(part.as_str().to_string()),
+            Rule::section_number => section.number = section_number(part),
+            Rule::section_anchor => {
+                section.anchor = part.into_inner().next().map(inner_name);
+            }
+            Rule::section_attrs => (section.id, section.attrs) = build_attrs(part),
+            Rule::lines => section.lines = build_lines(part),
+            _ => {}
+        }
+    }
+    section
+}
+
+fn build_attribute(pair: Pair<Rule>) -> Attribute {
+    let mut inner = pair.into_inner();
+    let name = inner.next().expect("attr_name").as_str().to_string();
+    let value = build_value(inner.next().expect("attr_value"));
+    Attribute { name, value }
+}
+
+fn build_line(pair: Pair<Rule>) -> Line {
+    let mut line = Line {
+        span: span_of(&pair),
+        ..Line::default()
+    };
+
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::line_content => build_content(&mut line, part),
+            Rule::anchor => line.anchor = Some(inner_name(part)),
+            Rule::alternative => line.alternatives.push(build_alternative(part)),
+            Rule::note => line.notes.push(build_note(part)),
+            Rule::harmony_line => line.harmony.push(build_line(part)),
+            Rule::line_attrs => {
+                let list = part.into_inner().next().expect("line_attr_list");
+                for attribute in list.into_inner() {
+                    apply_line_attribute(&mut line, attribute);
+                }
+            }
+            _ => {}
+        }
+    }
+    line
+}
+
+/// An `/ alt: "..."` take, with the `$name` references in its words at
+/// their place in the source. Words that are not lyrics have none; `choose`
+/// reports them.
+fn build_alternative(pair: Pair<Rule>) -> Alternative {
+    let span = span_of(&pair);
+    let quoted = pair.into_inner().next().expect("quoted_string");
+    let start = quoted.as_span().start() + 1;
+    let text = string_contents(quoted);
+    let vars = parse_line_content(&text)
+        .map(|words| words.vars)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|var| {
+            let at = Span::new(start + var.span.start, start + var.span.end);
+            Name::new(var.name, at)
+        })
+        .collect();
+    Alternative { text, vars, span }
+}
+
+fn build_note(pair: Pair<Rule>) -> Note {
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let kind = NoteKind::from_keyword(inner.next().expect("note_kind").as_str())
+        .expect("grammar only accepts known note kinds");
+    let text = inner
+        .next()
+        .map_or("", |t| t.as_str())
+        .trim_end()
+        .to_string();
+    Note { kind, text, span }
+}
+
+/// Parse the words of a single lyric line, with their markup but without
+/// attributes or notes, e.g. the text of an `/ alt: "..."` take.
+pub fn parse_line_content(input: &str) -> Result<Line, pest::error::Error<Rule>> {
+    let pair = LyricsParser::parse(Rule::line_content, input)?
+        .next()
+        .expect("line_content rule yields one pair");
+    let end = pair.as_span().end();
+    if end < input.len() {
+        let position = pest::Position::new(input, end).expect("end is within the input");
+        return Err(pest::error::Error::new_from_pos(
+            ErrorVariant::CustomError {
+                message: "expected only lyrics".to_string(),
+            },
+            position,
+        ));
+    }
+    let mut line = Line::default();
+    build_content(&mut line, pair);
+    Ok(line)
+}
+
+/// Fill in a line's text from its `line_content`, keeping `$name`
+/// references verbatim for resolution, reducing `word{/ipa/}`, `lo~~ve`,
+/// `word{hold:1s}` and `漢字{かんじ}` to the bare word and dropping `|` bar
+/// and `·` beat markers, and writing each `<tbd: ...>` placeholder as a
+/// blank.
+fn build_content(line: &mut Line, pair: Pair<Rule>) {
+    let start = pair.as_span().start();
+    let source = pair.as_str();
+    let mut text = String::with_capacity(source.len());
+    let mut cursor = 0;
+
+    for part in pair.into_inner() {
+        let (from, to) = (part.as_span().start() - start, part.as_span().end() - start);
+        text.push_str(&source[cursor..from]);
+        cursor = to;
+        match part.as_rule() {
+            Rule::var_ref => {
+                text.push_str(part.as_str());
+                line.vars.push(inner_name(part));
+            }
+            Rule::pronounced => {
+                let mut inner = part.into_inner();
+                let word = inner.next().expect("word").as_str().to_string();
+                let ipa = inner.next().expect("ipa").as_str().to_string();
+                text.push_str(&word);
+                let index = word_ranges(&text).len().saturating_sub(1);
+                line.pronunciations.push(Pronunciation { word, ipa, index });
+            }
+            Rule::placeholder => {
+                let span = span_of(&part);
+                let hint = match part.into_inner().next() {
+                    Some(blank) if blank.as_rule() == Rule::blank => {
+                        text.push_str(blank.as_str());
+                        None
+                    }
+                    hint => {
+                        text.push_str(BLANK);
+                        hint.map(|h| h.as_str().trim_end().to_string())
+                            .filter(|h| !h.is_empty())
+                    }
+                };
+                line.placeholders.push(Placeholder { hint, span });
+            }
+            Rule::ruby => {
+                let mut inner = part.into_inner();
+                let base = inner.next().expect("ruby_base").as_str().to_string();
+                let reading = inner.next().expect("ruby_text").as_str().to_string();
+                let at = text.len();
+                text.push_str(&base);
+                line.rubies.push(Ruby { base, reading, at });
+            }
+            Rule::melisma => {
+                // Beats belong to the word being written, or the one before.
+                let ranges = word_ranges(&text);
+                let word = ranges.len().saturating_sub(1);
+                let within = ranges.last().map(|r| text.len() - r.start);
+                let beats = part.as_str().len() as u32;
+                match line.sustains.iter_mut().find(|s| s.word == word) {
+                    Some(Sustain {
+                        hold: Hold::Beats(n),
+                        ..
+                    }) => *n += beats,
+                    Some(_) => {}
+                    None => line.sustains.push(Sustain {
+                        word,
+                        hold: Hold::Beats(beats),
+                        within,
+                    }),
+                }
+            }
+            Rule::soft_break => {
+                text.truncate(text.trim_end().len());
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                line.breaks.push(word_ranges(&text).len());
+            }
+            Rule::bar | Rule::beat => {
+                // The marker and the space around it are not part of the text.
+                text.truncate(text.trim_end().len());
+                let word = word_ranges(&text).len();
+                match part.as_rule() {
+                    Rule::bar => line.bars.push(word),
+                    _ => line.beats.push(word),
+                }
+                cursor += source[cursor..].len() - source[cursor..].trim_start().len();
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+            }
+            Rule::held => {
+                let mut inner = part.into_inner();
+                text.push_str(inner.next().expect("word").as_str());
+                let word = word_ranges(&text).len().saturating_sub(1);
+                let time = inner.next().expect("hold_time").as_str();
+                let time = hold_time(time).expect("checked by parse_tree");
+                line.sustains.retain(|s| s.word != word);
+                line.sustains.push(Sustain {
+                    word,
+                    hold: Hold::Fixed(time),
+                    within: None,
+                });
+            }
+            _ => text.push_str(part.as_str()),
+        }
+    }
+    text.push_str(&source[cursor..]);
+    line.text = text.trim_end().to_string();
+    // A break before the first word or after the last one breaks nothing.
+    let words = word_ranges(&line.text).len();
+    line.breaks.retain(|&word| word > 0 && word < words);
+    line.breaks.dedup();
+    // A melisma that ended its word is a mark after it.
+    let ranges = word_ranges(&line.text);
+    for sustain in &mut line.sustains {
+        let inside = |at: usize| ranges.get(sustain.word).is_some_and(|r| at < r.len());
+        sustain.within = sustain.within.filter(|&at| at > 0 && inside(at));
+    }
+}
+
+fn apply_line_attribute(line: &mut Line, pair: Pair<Rule>) {
+    let value = pair.into_inner().next().expect("line attribute value");
+    match value.as_rule() {
+        Rule::annotation => line.annotations.push(build_annotation(value)),
+        Rule::rhyme_scheme => line.rhyme = value.as_str().chars().next(),
+        Rule::stress_pattern => line.stress = Some(value.as_str().to_string()),
+        Rule::chord_sequence => {
+            line.chords = value.into_inner().map(|c| c.as_str().to_string()).collect();
+        }
+        Rule::pitch_sequence => {
+            line.pitches = value.into_inner().map(|p| p.as_str().to_string()).collect();
+        }
+        Rule::timing_info => line.timing = Some(build_timestamp(value)),
+        Rule::line_author => line.provenance.author = Some(value.as_str().trim_end().to_string()),
+        Rule::line_source => line.provenance.source = Some(value.as_str().trim_end().to_string()),
+        Rule::confidence => line.provenance.confidence = value.as_str().parse().ok(),
+        Rule::date => line.provenance.edited = Some(value.as_str().to_string()),
+        Rule::id_attr => line.id = Some(unit_id(value)),
+        Rule::translation => {
+            let mut inner = value.into_inner();
+            let lang = inner.next().expect("lang_tag").as_str().to_string();
+            let text = inner.next().expect("quoted_string").into_inner().as_str();
+            line.translations.retain(|t| t.lang != lang);
+            line.translations.push(Translation {
+                lang,
+                text: text.to_string(),
+            });
+        }
+        _ => {}
+    }
+}
+
+fn build_annotation(pair: Pair<Rule>) -> Annotation {
+    let mut inner = pair.into_inner();
+    let name = inner.next().expect("identifier").as_str().to_string();
+    let value = inner.next().expect("annotation_value");
+    let text = value.as_str().trim_end();
+    let start = value.as_span().start();
+    Annotation {
+        name,
+        value: text.to_string(),
+        span: Span::new(start, start + text.len()),
+    }
+}
+
+/// Convert a `timing_info` pair (`m:ss.cc`) into a [`Timestamp`].
+fn build_timestamp(pair: Pair<Rule>) -> Timestamp {
+    timestamp(pair).expect("parse_tree checks every time")
+}
+
+/// The [`Timestamp`] of a `timing_info` pair, unless its minutes are not
+/// whole or it is too late to count in milliseconds.
+/// The length of a `1.2s` or `300ms` hold, or `None` when it is too long to
+/// count in milliseconds.
+fn hold_time(time: &str) -> Option<Timestamp> {
+    let seconds = match time.strip_suffix("ms") {
+        Some(ms) => ms.parse::<f64>().ok()? / 1000.0,
+        None => time.trim_end_matches('s').parse().ok()?,
+    };
+    Timestamp::from_parts(0, seconds)
+}
+
+fn timestamp(pair: Pair<Rule>) -> Option<Timestamp> {
+    let mut parts = pair.into_inner();
+    let minutes = parts.next().expect("minutes").as_str().parse().ok()?;
+    let seconds = parts.next().expect("seconds").as_str().parse().ok()?;
+    Timestamp::from_parts(minutes, seconds)
+}
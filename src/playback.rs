@@ -0,0 +1,41 @@
+//! Following a song as it is performed: its lines in order as cues, and
+//! which of them is being sung at a given moment of a timed recording.
+
+use crate::ast::{Song, Timestamp};
+
+/// One sung line of a resolved song.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// Label of the section the line belongs to, e.g. `Chorus`.
+    pub section: String,
+    pub text: String,
+    pub chords: Vec<String>,
+    pub start: Option<Timestamp>,
+}
+
+/// Every line of `song` in performance order.
+pub fn cues(song: &Song) -> Vec<Cue> {
+    song.lines()
+        .map(|(section, line)| Cue {
+            section: section.label(),
+            text: line.text.clone(),
+            chords: line.chords.clone(),
+            start: line.timing,
+        })
+        .collect()
+}
+
+/// Whether any cue has a timing to follow.
+pub fn is_timed(cues: &[Cue]) -> bool {
+    cues.iter().any(|c| c.start.is_some())
+}
+
+/// The cue being sung `secs` into the recording: the last one that has
+/// started. Untimed cues are skipped, and `None` means nothing has started.
+pub fn cue_at(cues: &[Cue], secs: f64) -> Option<usize> {
+    cues.iter()
+        .enumerate()
+        .filter(|(_, c)| c.start.is_some_and(|t| t.as_secs_f64() <= secs))
+        .map(|(i, _)| i)
+        .next_back()
+}
@@ -0,0 +1,96 @@
+//! Export presets: named bundles of `export` options, so a team can write
+//! `export --preset web` instead of the same dozen flags on every run.
+//!
+//! A preset sets options by their long names, without the dashes, in the
+//! project's `config.toml`; switches take `"true"` or `"false"`:
+//!
+//! ```toml
+//! preset.web.format = "html"
+//! preset.web.smart-typography = "true"
+//! preset.web.case = "sentence"
+//! ```
+//!
+//! Options given on the command line win over the preset's. A project
+//! preset replaces a built-in one of the same name.
+
+/// A named set of export options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    /// Option names and their values, in the order they were set.
+    pub settings: Vec<(String, String)>,
+}
+
+/// Presets every project has.
+const BUILT_IN: &[(&str, &[(&str, &str)])] = &[
+    ("karaoke-bar", &[("format", "elrc"), ("harmony", "exclude")]),
+    (
+        "print-songbook",
+        &[
+            ("format", "pdf"),
+            ("chord-diagrams", "guitar"),
+            ("smart-typography", "true"),
+        ],
+    ),
+    (
+        "web",
+        &[
+            ("format", "html"),
+            ("smart-typography", "true"),
+            ("harmony", "separate"),
+        ],
+    ),
+];
+
+impl Preset {
+    /// The built-in presets.
+    pub fn built_in() -> Vec<Preset> {
+        BUILT_IN
+            .iter()
+            .map(|(name, settings)| Preset {
+                name: name.to_string(),
+                settings: settings
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The value the preset gives `option`; the last one when it is set
+    /// twice.
+    pub fn get(&self, option: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .rev()
+            .find(|(name, _)| name == option)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set `option`, keeping any earlier value for [`Preset::get`] to pass
+    /// over.
+    pub fn set(&mut self, option: &str, value: &str) {
+        self.settings.push((option.to_string(), value.to_string()));
+    }
+}
+
+/// The preset `name` among the project's `presets`, else the built-in one.
+pub fn find(presets: &[Preset], name: &str) -> Option<Preset> {
+    presets
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .or_else(|| Preset::built_in().into_iter().find(|p| p.name == name))
+}
+
+/// The names of the project's `presets` and the built-in ones, sorted.
+pub fn names(presets: &[Preset]) -> Vec<String> {
+    let mut names: Vec<String> = presets
+        .iter()
+        .map(|p| p.name.clone())
+        .chain(BUILT_IN.iter().map(|(name, _)| name.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
@@ -0,0 +1,153 @@
+//! Parser profiles: how much freedom a song's structure may take.
+//!
+//! Every profile parses with the same grammar. A profile is a pass over the
+//! parsed song that reports what it does not allow:
+//!
+//! - `strict` numbers every verse and chorus, from 1 in order, names the
+//!   number in every `REPEAT`, and accepts only the built-in section kinds;
+//! - `standard`, the default, accepts only the built-in section kinds;
+//! - `permissive` also accepts free-form `SECTION name` headers.
+//!
+//! Placeholders for unwritten words are warnings (W017) in every profile
+//! but `strict`, where they are errors (E025).
+
+use crate::ast::{Line, RefTarget, SectionKind, Song, Span};
+use crate::codes::{self, Code};
+use crate::diagnostic::Diagnostic;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    Strict,
+    #[default]
+    Standard,
+    Permissive,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [Profile::Strict, Profile::Standard, Profile::Permissive];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Strict => "strict",
+            Profile::Standard => "standard",
+            Profile::Permissive => "permissive",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Profile> {
+        Profile::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Sections a strict song numbers.
+fn numbered(kind: SectionKind) -> bool {
+    matches!(kind, SectionKind::Verse | SectionKind::Chorus)
+}
+
+/// A finding for each `____` or `<tbd>` in `lines` and their harmony parts,
+/// under `code`.
+fn placeholders(lines: &[Line], code: &'static Code, out: &mut Vec<Diagnostic>) {
+    for line in lines {
+        for placeholder in &line.placeholders {
+            let message = match &placeholder.hint {
+                Some(hint) => format!("unfinished line: {}", hint),
+                None => "unfinished line".to_string(),
+            };
+            out.push(Diagnostic::new(code.severity, message, placeholder.span).with_code(code));
+        }
+        placeholders(&line.harmony, code, out);
+    }
+}
+
+/// Check the unresolved `song` against `profile`.
+pub fn check(song: &Song, profile: Profile) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let code = match profile {
+        Profile::Strict => &codes::STRICT_PLACEHOLDER,
+        _ => &codes::PLACEHOLDER,
+    };
+    for lines in song
+        .sections
+        .iter()
+        .map(|s| &s.lines)
+        .chain(song.macros.iter().map(|m| &m.lines))
+    {
+        placeholders(lines, code, &mut diagnostics);
+    }
+    let mut last_number: HashMap<SectionKind, u32> = HashMap::new();
+    for section in &song.sections {
+        let keyword = Span {
+            start: section.span.start,
+            end: section.span.start + section.kind.keyword().len(),
+        };
+        if section.kind == SectionKind::Custom && profile != Profile::Permissive {
+            diagnostics.push(
+                Diagnostic::error(
+                    format!(
+                        "free-form section `{}` needs the permissive profile",
+                        section.header()
+                    ),
+                    keyword,
+                )
+                .with_code(&codes::FREE_FORM_SECTION),
+            );
+        }
+        if profile != Profile::Strict || !numbered(section.kind) {
+            continue;
+        }
+        match (&section.repeat, section.number) {
+            (Some(reference), None) => {
+                if let RefTarget::Section { kind, .. } = &reference.target {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!(
+                                "`REPEAT {}` needs a section number in the strict profile",
+                                kind.keyword()
+                            ),
+                            reference.span,
+                        )
+                        .with_code(&codes::UNNUMBERED_REPEAT),
+                    );
+                }
+            }
+            (Some(_), Some(_)) => {}
+            (None, None) => diagnostics.push(
+                Diagnostic::error(
+                    format!(
+                        "`{}` has no number; the strict profile numbers every verse and chorus",
+                        section.kind.keyword()
+                    ),
+                    keyword,
+                )
+                .with_code(&codes::UNNUMBERED_SECTION),
+            ),
+            (None, Some(n)) => {
+                let expected = last_number.get(&section.kind).map_or(1, |last| last + 1);
+                if n != expected {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!(
+                                "`{}` is out of order; expected `{}[{}]`",
+                                section.header(),
+                                section.kind.keyword(),
+                                expected
+                            ),
+                            keyword,
+                        )
+                        .with_code(&codes::SECTION_ORDER),
+                    );
+                }
+                last_number.insert(section.kind, n);
+            }
+        }
+    }
+    diagnostics
+}
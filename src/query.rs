@@ -0,0 +1,474 @@
+//! A small path language for pulling data out of a song, as serialized by
+//! the `json` exporter.
+//!
+//! A query is a path of fields separated by dots, each optionally followed
+//! by `[n]` to pick one item or `[condition]` to keep the items matching,
+//! then any number of `| function` steps:
+//!
+//! ```text
+//! sections[kind=chorus].lines | count
+//! sections.lines[provenance.author=Sam].text
+//! sections.lines.chords | unique | sort
+//! sections.lines[timing>1:00.00][0].text
+//! ```
+//!
+//! Arrays are spread as they are reached, so `sections.lines` is every line
+//! of every section and an index picks from everything reached so far.
+//! Missing fields are skipped rather than giving `null`. A condition is a
+//! field path, alone to test that it is set, or compared with `=`, `!=`,
+//! `<`, `<=`, `>`, `>=` or `~` (contains) against a number, a word, a
+//! quoted string or a time such as `1:00.00`; several conditions separated
+//! by commas must all hold. Strings compare ignoring case.
+
+use crate::schema::song_schema;
+use crate::suggest::{self, did_you_mean};
+use serde_json::Value;
+use std::cmp::Ordering;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("query syntax error at column {column}: {message}")]
+    Syntax { column: usize, message: String },
+    #[error("no field `{name}` in a song{}", help(.suggestion))]
+    UnknownField {
+        name: String,
+        suggestion: Option<String>,
+    },
+    #[error("unknown function `{name}`{}", help(.suggestion))]
+    UnknownFunction {
+        name: String,
+        suggestion: Option<String>,
+    },
+    #[error("`{function}` needs numbers, found {found}")]
+    NotNumbers {
+        function: &'static str,
+        found: String,
+    },
+}
+
+fn help(suggestion: &Option<String>) -> String {
+    suggestion
+        .as_ref()
+        .map_or(String::new(), |s| format!("; {}", suggest::help(s)))
+}
+
+/// Functions that can follow a path after `|`.
+pub const FUNCTIONS: [&str; 10] = [
+    "count", "first", "last", "unique", "sort", "sum", "min", "max", "length", "keys",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    path: Vec<String>,
+    test: Option<(Op, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Field(String),
+    Index(i64),
+    Filter(Vec<Condition>),
+}
+
+/// A parsed query, ready to run on any number of songs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+    functions: Vec<&'static str>,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError::Syntax {
+            column: self.text[..self.at].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.at..].chars().next()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.at += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        let found = self.peek() == Some(c);
+        if found {
+            self.at += c.len_utf8();
+        }
+        found
+    }
+
+    fn identifier(&mut self) -> Result<String, QueryError> {
+        self.skip_space();
+        let rest = &self.text[self.at..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error("expected a field name"));
+        }
+        self.at += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        self.skip_space();
+        let rest = &self.text[self.at..];
+        let (op, len) = [
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("=", Op::Eq),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+            ("~", Op::Contains),
+        ]
+        .into_iter()
+        .find(|(symbol, _)| rest.starts_with(symbol))
+        .map(|(symbol, op)| (op, symbol.len()))?;
+        self.at += len;
+        Some(op)
+    }
+
+    /// A quoted string, or a bare value running to the next `,` or `]`.
+    fn literal(&mut self) -> Result<String, QueryError> {
+        self.skip_space();
+        let rest = &self.text[self.at..];
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.at += end + 2;
+            return Ok(quoted[..end].to_string());
+        }
+        let len = rest.find([',', ']']).unwrap_or(rest.len());
+        let value = rest[..len].trim_end();
+        if value.is_empty() {
+            return Err(self.error("expected a value to compare with"));
+        }
+        self.at += len;
+        Ok(value.to_string())
+    }
+
+    fn condition(&mut self) -> Result<Condition, QueryError> {
+        let mut path = vec![self.identifier()?];
+        while self.eat('.') {
+            path.push(self.identifier()?);
+        }
+        let test = match self.op() {
+            Some(op) => Some((op, self.literal()?)),
+            None => None,
+        };
+        Ok(Condition { path, test })
+    }
+
+    /// What follows a `[`: an index or conditions.
+    fn bracket(&mut self) -> Result<Step, QueryError> {
+        self.skip_space();
+        let rest = &self.text[self.at..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '-'))
+            .unwrap_or(rest.len());
+        let step = match rest[..len].parse::<i64>() {
+            Ok(index) => {
+                self.at += len;
+                Step::Index(index)
+            }
+            Err(_) => {
+                let mut conditions = vec![self.condition()?];
+                while self.eat(',') {
+                    conditions.push(self.condition()?);
+                }
+                Step::Filter(conditions)
+            }
+        };
+        if !self.eat(']') {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(step)
+    }
+}
+
+/// Every field name the serialized song can have, from its schema.
+fn field_names() -> Vec<String> {
+    let schema = song_schema();
+    let mut names: Vec<String> = schema["$defs"]
+        .as_object()
+        .into_iter()
+        .flat_map(|defs| defs.values())
+        .flat_map(|def| {
+            let objects = std::iter::once(def).chain(def["oneOf"].as_array().into_iter().flatten());
+            objects
+                .filter_map(|object| object["properties"].as_object())
+                .flat_map(|properties| properties.keys().cloned())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn check_field(name: &str, known: &[String]) -> Result<(), QueryError> {
+    if known.iter().any(|k| k == name) {
+        return Ok(());
+    }
+    Err(QueryError::UnknownField {
+        name: name.to_string(),
+        suggestion: did_you_mean(name, known.iter().map(String::as_str)).map(str::to_string),
+    })
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser { text, at: 0 };
+        let known = field_names();
+        let mut steps = Vec::new();
+        parser.eat('.');
+        parser.skip_space();
+        while !matches!(parser.peek(), None | Some('|')) {
+            let name = parser.identifier()?;
+            check_field(&name, &known)?;
+            steps.push(Step::Field(name));
+            while parser.eat('[') {
+                let step = parser.bracket()?;
+                if let Step::Filter(conditions) = &step {
+                    for name in conditions.iter().flat_map(|c| &c.path) {
+                        check_field(name, &known)?;
+                    }
+                }
+                steps.push(step);
+            }
+            parser.skip_space();
+            if !matches!(parser.peek(), None | Some('|')) && !parser.eat('.') {
+                return Err(parser.error("expected `.`, `[` or `|`"));
+            }
+        }
+        let mut functions = Vec::new();
+        while parser.eat('|') {
+            let name = parser.identifier()?;
+            let function = FUNCTIONS.iter().find(|f| **f == name).ok_or_else(|| {
+                QueryError::UnknownFunction {
+                    suggestion: did_you_mean(&name, FUNCTIONS).map(str::to_string),
+                    name,
+                }
+            })?;
+            functions.push(*function);
+            parser.skip_space();
+        }
+        if parser.peek().is_some() {
+            return Err(parser.error("expected `|` or the end of the query"));
+        }
+        Ok(Query { steps, functions })
+    }
+
+    /// Run the query on `song`, the song as serialized.
+    pub fn run(&self, song: &Value) -> Result<Vec<Value>, QueryError> {
+        let mut stream = vec![song.clone()];
+        for step in &self.steps {
+            stream = match step {
+                Step::Field(name) => stream
+                    .iter()
+                    .filter_map(|value| value.get(name))
+                    .flat_map(spread)
+                    .collect(),
+                Step::Index(index) => {
+                    let at = match *index {
+                        i if i < 0 => stream.len() as i64 + i,
+                        i => i,
+                    };
+                    usize::try_from(at)
+                        .ok()
+                        .and_then(|at| stream.get(at).cloned())
+                        .into_iter()
+                        .collect()
+                }
+                Step::Filter(conditions) => stream
+                    .into_iter()
+                    .filter(|value| conditions.iter().all(|c| c.holds(value)))
+                    .collect(),
+            };
+        }
+        for function in &self.functions {
+            stream = apply(function, stream)?;
+        }
+        Ok(stream)
+    }
+}
+
+/// The items of an array, or the value itself.
+fn spread(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items.clone(),
+        other => vec![other.clone()],
+    }
+}
+
+/// `1:02.50` as milliseconds, the serialized form of a timestamp.
+fn millis(text: &str) -> Option<f64> {
+    let (minutes, seconds) = text.split_once(':')?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    Some(minutes as f64 * 60_000.0 + (seconds * 1000.0).round())
+}
+
+fn compare(value: &Value, op: Op, literal: &str) -> bool {
+    if let Value::Array(items) = value {
+        return match op {
+            Op::Ne => !items.iter().any(|item| compare(item, Op::Eq, literal)),
+            _ => items.iter().any(|item| compare(item, op, literal)),
+        };
+    }
+    let ordering = match value {
+        Value::Number(n) => {
+            let (Some(n), Some(wanted)) = (
+                n.as_f64(),
+                literal.parse::<f64>().ok().or_else(|| millis(literal)),
+            ) else {
+                return op == Op::Ne;
+            };
+            n.partial_cmp(&wanted)
+        }
+        Value::String(s) => {
+            let (s, wanted) = (s.to_lowercase(), literal.to_lowercase());
+            if op == Op::Contains {
+                return s.contains(&wanted);
+            }
+            Some(s.cmp(&wanted))
+        }
+        Value::Bool(b) => Some(b.to_string().cmp(&literal.to_lowercase())),
+        _ => None,
+    };
+    match (op, ordering) {
+        (Op::Eq | Op::Contains, Some(o)) => o == Ordering::Equal,
+        (Op::Ne, o) => o != Some(Ordering::Equal),
+        (Op::Lt, Some(o)) => o == Ordering::Less,
+        (Op::Le, Some(o)) => o != Ordering::Greater,
+        (Op::Gt, Some(o)) => o == Ordering::Greater,
+        (Op::Ge, Some(o)) => o != Ordering::Less,
+        (_, None) => false,
+    }
+}
+
+impl Condition {
+    fn holds(&self, value: &Value) -> bool {
+        let found = self
+            .path
+            .iter()
+            .try_fold(value, |value, name| value.get(name));
+        match (found, &self.test) {
+            (None | Some(Value::Null), Some((Op::Ne, _))) => true,
+            (None | Some(Value::Null), _) => false,
+            (Some(value), None) => {
+                !matches!(value, Value::Bool(false))
+                    && value != &Value::String(String::new())
+                    && value != &Value::Array(Vec::new())
+            }
+            (Some(value), Some((op, literal))) => compare(value, *op, literal),
+        }
+    }
+}
+
+/// Order for `sort`, `min` and `max`: numbers, then strings, then the rest
+/// by their JSON text.
+fn order(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or_default()
+            .total_cmp(&b.as_f64().unwrap_or_default()),
+        (Value::Number(_), _) => Ordering::Less,
+        (_, Value::Number(_)) => Ordering::Greater,
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+fn numbers(function: &'static str, stream: &[Value]) -> Result<Vec<f64>, QueryError> {
+    stream
+        .iter()
+        .map(|value| {
+            value.as_f64().ok_or_else(|| QueryError::NotNumbers {
+                function,
+                found: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn number(n: f64) -> Value {
+    match n.fract() == 0.0 && n.abs() < 1e15 {
+        true => Value::from(n as i64),
+        false => Value::from(n),
+    }
+}
+
+fn apply(function: &'static str, mut stream: Vec<Value>) -> Result<Vec<Value>, QueryError> {
+    Ok(match function {
+        "count" => vec![Value::from(stream.len())],
+        "first" => stream.into_iter().take(1).collect(),
+        "last" => stream.pop().into_iter().collect(),
+        "unique" => {
+            let mut seen = Vec::new();
+            stream.retain(|value| {
+                let new = !seen.contains(value);
+                if new {
+                    seen.push(value.clone());
+                }
+                new
+            });
+            stream
+        }
+        "sort" => {
+            stream.sort_by(order);
+            stream
+        }
+        "sum" => vec![number(numbers(function, &stream)?.iter().sum())],
+        "min" | "max" => {
+            numbers(function, &stream)?;
+            let pick = match function {
+                "min" => stream.into_iter().min_by(order),
+                _ => stream.into_iter().max_by(order),
+            };
+            pick.into_iter().collect()
+        }
+        "length" => stream
+            .iter()
+            .map(|value| match value {
+                Value::String(s) => Value::from(s.chars().count()),
+                Value::Array(items) => Value::from(items.len()),
+                Value::Object(fields) => Value::from(fields.len()),
+                _ => Value::from(0),
+            })
+            .collect(),
+        "keys" => stream
+            .iter()
+            .filter_map(Value::as_object)
+            .flat_map(|fields| fields.keys().map(|k| Value::from(k.as_str())))
+            .collect(),
+        _ => unreachable!("functions are checked when parsing"),
+    })
+}
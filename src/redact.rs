@@ -0,0 +1,405 @@
+//! Redaction of names and sensitive terms for sharing drafts.
+//!
+//! Every redacted word is replaced by a placeholder such as `Na-na-na` with
+//! the same number of syllables, so the rhythm of each line survives and a
+//! reviewer can still judge scansion. Each distinct term gets its own
+//! placeholder syllable, so repeated names stay recognisable as the same name.
+
+use crate::analysis::language::{self, Language};
+use crate::analysis::syllables::word_syllables_in;
+use crate::ast::{word_ranges, Line, Song};
+use std::collections::HashMap;
+
+/// What [`redact`] should replace.
+#[derive(Debug, Clone, Default)]
+pub struct RedactOptions {
+    /// Replace capitalized words that do not start a line or sentence, and
+    /// every other occurrence of them.
+    pub proper_nouns: bool,
+    /// Words or phrases to replace wherever they appear, ignoring case.
+    pub terms: Vec<String>,
+}
+
+/// One distinct redacted term and the placeholder syllable it was given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub term: String,
+    pub syllable: String,
+    /// How many words were replaced for this term.
+    pub count: usize,
+}
+
+/// The redacted song together with what was replaced.
+#[derive(Debug, Clone)]
+pub struct Redacted {
+    pub song: Song,
+    pub replacements: Vec<Replacement>,
+}
+
+const CONSONANTS: [&str; 12] = ["N", "M", "L", "D", "B", "T", "K", "P", "R", "S", "V", "Z"];
+const VOWELS: [&str; 3] = ["a", "o", "u"];
+
+/// Metadata that names the song or the people behind it, redacted word for
+/// word along with proper nouns.
+const NAMING: [&str; 4] = ["title", "artist", "writers", "publishers"];
+
+/// Replace proper nouns and configured terms wherever the song has text:
+/// its lines with their harmony parts, alternatives, translations, notes
+/// and annotations, variable values, metadata, credits and history.
+/// Pronunciation overrides and furigana of redacted words are dropped.
+///
+/// With proper nouns, the words of the title, artist, writers and
+/// publishers, the credited works and their people, and the authors of
+/// lines are names themselves and are replaced whole.
+pub fn redact(song: &Song, options: &RedactOptions) -> Redacted {
+    let mut redactor = Redactor::new(options, language::of(song));
+    if options.proper_nouns {
+        for text in naming_texts(song) {
+            redactor.collect_names(text);
+        }
+        let mut texts = Vec::new();
+        for line in all_lines(song) {
+            texts.push(line.text.as_str());
+            texts.extend(line.alternatives.iter().map(|a| a.text.as_str()));
+            texts.extend(line.translations.iter().map(|t| t.text.as_str()));
+            texts.extend(line.notes.iter().map(|n| n.text.as_str()));
+        }
+        texts.extend(song.variables.iter().map(|v| v.value.as_str()));
+        texts.extend(song.history.iter().map(|h| h.message.as_str()));
+        for text in texts {
+            redactor.collect_proper_nouns(text);
+        }
+    }
+
+    let mut song = song.clone();
+    let every_word = options.proper_nouns;
+    for line in song
+        .sections
+        .iter_mut()
+        .flat_map(|s| &mut s.lines)
+        .chain(song.macros.iter_mut().flat_map(|m| &mut m.lines))
+    {
+        redactor.redact_line(line, every_word);
+    }
+    for variable in &mut song.variables {
+        redactor.redact_in(&mut variable.value);
+    }
+    for entry in &mut song.metadata {
+        let naming = every_word && NAMING.contains(&entry.key.as_str());
+        for text in entry.value.texts_mut() {
+            match naming {
+                true => redactor.redact_all(text),
+                false => redactor.redact_in(text),
+            }
+        }
+    }
+    if let Some(matter) = &mut song.front_matter {
+        for line in &mut matter.other {
+            redactor.redact_in(&mut line.text);
+        }
+    }
+    for credit in &mut song.credits {
+        let people = [
+            &mut credit.artist,
+            &mut credit.writers,
+            &mut credit.publishers,
+        ];
+        for text in std::iter::once(&mut credit.work).chain(people.into_iter().flatten()) {
+            match every_word {
+                true => redactor.redact_all(text),
+                false => redactor.redact_in(text),
+            }
+        }
+    }
+    for entry in &mut song.history {
+        redactor.redact_in(&mut entry.message);
+    }
+
+    Redacted {
+        song,
+        replacements: redactor.replacements,
+    }
+}
+
+/// Every line of `song`, harmony parts included.
+fn all_lines(song: &Song) -> Vec<&Line> {
+    fn with_harmony<'a>(line: &'a Line, out: &mut Vec<&'a Line>) {
+        out.push(line);
+        for part in &line.harmony {
+            with_harmony(part, out);
+        }
+    }
+    let mut out = Vec::new();
+    let sections = song.sections.iter().flat_map(|s| &s.lines);
+    for line in sections.chain(song.macros.iter().flat_map(|m| &m.lines)) {
+        with_harmony(line, &mut out);
+    }
+    out
+}
+
+/// The texts that name the song and the people behind it.
+fn naming_texts(song: &Song) -> Vec<&str> {
+    let mut texts: Vec<&str> = song
+        .metadata
+        .iter()
+        .filter(|m| NAMING.contains(&m.key.as_str()))
+        .flat_map(|m| m.value.texts())
+        .collect();
+    for credit in &song.credits {
+        texts.push(&credit.work);
+        let people = [&credit.artist, &credit.writers, &credit.publishers];
+        texts.extend(people.into_iter().flatten().map(String::as_str));
+    }
+    let authors = all_lines(song)
+        .into_iter()
+        .filter_map(|l| l.provenance.author.as_deref());
+    texts.extend(authors);
+    texts
+}
+
+struct Redactor {
+    /// Lowercased words of each configured term.
+    phrases: Vec<Vec<String>>,
+    /// Proper nouns, matched exactly.
+    names: Vec<String>,
+    /// Index into `replacements` by lowercased term.
+    assigned: HashMap<String, usize>,
+    replacements: Vec<Replacement>,
+    /// The song's language, for syllable counts.
+    language: &'static dyn Language,
+}
+
+impl Redactor {
+    fn new(options: &RedactOptions, language: &'static dyn Language) -> Self {
+        let phrases = options
+            .terms
+            .iter()
+            .map(|term| {
+                word_ranges(term)
+                    .into_iter()
+                    .map(|r| term[r].to_lowercase())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|words| !words.is_empty())
+            .collect();
+        Redactor {
+            phrases,
+            names: Vec::new(),
+            assigned: HashMap::new(),
+            replacements: Vec::new(),
+            language,
+        }
+    }
+
+    fn words_syllables(&self, text: &str) -> Vec<usize> {
+        word_ranges(text)
+            .into_iter()
+            .map(|r| self.language.syllables(&text[r]))
+            .collect()
+    }
+
+    /// Take every capitalized word of `text`, which names something, as a
+    /// proper noun.
+    fn collect_names(&mut self, text: &str) {
+        for range in word_ranges(text) {
+            let word = &text[range.clone()];
+            if !is_variable(text, range.start)
+                && is_capitalized(word)
+                && !is_pronoun_i(word)
+                && !self.names.iter().any(|n| n == word)
+            {
+                self.names.push(word.to_string());
+            }
+        }
+    }
+
+    fn collect_proper_nouns(&mut self, text: &str) {
+        for range in word_ranges(text).into_iter().filter(|r| r.start > 0) {
+            let word = &text[range.clone()];
+            let before = text[..range.start].trim_end_matches([' ', '\t', '"', '“', '(']);
+            let sentence_start = before.is_empty() || before.ends_with(['.', '!', '?', ':']);
+            if !sentence_start
+                && !is_variable(text, range.start)
+                && is_capitalized(word)
+                && !is_pronoun_i(word)
+                && !self.names.iter().any(|n| n == word)
+            {
+                self.names.push(word.to_string());
+            }
+        }
+    }
+
+    /// Redact the names and terms in `text`.
+    fn redact_in(&mut self, text: &mut String) {
+        let syllables = self.words_syllables(text);
+        *text = self.redact_text(text, &syllables, true).0;
+    }
+
+    /// Replace every word of `text`, each as a term of its own.
+    fn redact_all(&mut self, text: &mut String) {
+        let syllables = self.words_syllables(text);
+        let ranges = word_ranges(text);
+        let mut out = text.clone();
+        for (range, syllables) in ranges.into_iter().zip(syllables).rev() {
+            if is_variable(text, range.start) {
+                continue;
+            }
+            let word = &text[range.clone()];
+            let syllable = self.syllable_for(&word.to_lowercase());
+            out.replace_range(
+                range.clone(),
+                &placeholder(word, &syllable, syllables.max(1)),
+            );
+        }
+        *text = out;
+    }
+
+    /// Redact the words of `line` and everything said along with them; with
+    /// `every_word`, its author is replaced whole.
+    fn redact_line(&mut self, line: &mut Line, every_word: bool) {
+        for part in &mut line.harmony {
+            self.redact_line(part, every_word);
+        }
+        for alternative in &mut line.alternatives {
+            self.redact_in(&mut alternative.text);
+        }
+        for translation in &mut line.translations {
+            self.redact_in(&mut translation.text);
+        }
+        for note in &mut line.notes {
+            self.redact_in(&mut note.text);
+        }
+        for annotation in &mut line.annotations {
+            self.redact_in(&mut annotation.value);
+        }
+        if let Some(author) = &mut line.provenance.author {
+            match every_word {
+                true => self.redact_all(author),
+                false => self.redact_in(author),
+            }
+        }
+        if let Some(source) = &mut line.provenance.source {
+            self.redact_in(source);
+        }
+        let syllables = word_syllables_in(line, self.language);
+        let (text, redacted) = self.redact_text(&line.text, &syllables, true);
+        // Furigana move with their word, or go with it when it is redacted.
+        let (before, after) = (word_ranges(&line.text), word_ranges(&text));
+        line.rubies.retain_mut(
+            |ruby| match before.iter().position(|r| r.contains(&ruby.at)) {
+                Some(i) if !redacted.contains(&i) && before.len() == after.len() => {
+                    ruby.at = ruby.at - before[i].start + after[i].start;
+                    true
+                }
+                _ => false,
+            },
+        );
+        line.text = text;
+        line.pronunciations.retain(|p| !redacted.contains(&p.index));
+    }
+
+    /// Redact `text`, whose words have the given syllable counts. Returns the
+    /// new text and the indices of the words that were replaced.
+    fn redact_text(
+        &mut self,
+        text: &str,
+        syllables: &[usize],
+        names: bool,
+    ) -> (String, Vec<usize>) {
+        let ranges = word_ranges(text);
+        let lower: Vec<String> = ranges
+            .iter()
+            .map(|r| text[r.clone()].to_lowercase())
+            .collect();
+        // The term each word belongs to, if it is redacted.
+        let mut terms: Vec<Option<String>> = vec![None; ranges.len()];
+        for phrase in &self.phrases {
+            for start in 0..ranges.len() {
+                let end = start + phrase.len();
+                if end <= ranges.len()
+                    && lower[start..end] == phrase[..]
+                    && terms[start..end].iter().all(Option::is_none)
+                    && !is_variable(text, ranges[start].start)
+                {
+                    let key = phrase.join(" ");
+                    terms[start..end].fill(Some(key));
+                }
+            }
+        }
+        if names {
+            for (i, range) in ranges.iter().enumerate() {
+                let word = &text[range.clone()];
+                if terms[i].is_none()
+                    && !is_variable(text, range.start)
+                    && self.names.iter().any(|n| n == word)
+                {
+                    terms[i] = Some(word.to_lowercase());
+                }
+            }
+        }
+
+        let replaced: Vec<(usize, String)> = terms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, term)| Some((i, self.syllable_for(term.as_ref()?))))
+            .collect();
+        let mut out = text.to_string();
+        for (i, syllable) in replaced.iter().rev() {
+            let range = ranges[*i].clone();
+            let placeholder = placeholder(&text[range.clone()], syllable, syllables[*i].max(1));
+            out.replace_range(range, &placeholder);
+        }
+        (out, replaced.into_iter().map(|(i, _)| i).collect())
+    }
+
+    fn syllable_for(&mut self, term: &str) -> String {
+        let next = self.replacements.len();
+        let index = *self.assigned.entry(term.to_string()).or_insert(next);
+        if index == next {
+            let consonant = CONSONANTS[index % CONSONANTS.len()];
+            let vowel = VOWELS[index / CONSONANTS.len() % VOWELS.len()];
+            self.replacements.push(Replacement {
+                term: term.to_string(),
+                syllable: format!("{}{}", consonant, vowel),
+                count: 0,
+            });
+        }
+        self.replacements[index].count += 1;
+        self.replacements[index].syllable.clone()
+    }
+}
+
+/// `syllables` copies of `syllable` joined by hyphens, in the case of `word`.
+fn placeholder(word: &str, syllable: &str, syllables: usize) -> String {
+    let joined = vec![syllable.to_lowercase(); syllables].join("-");
+    let upper = word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && word
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .all(char::is_uppercase);
+    if upper {
+        joined.to_uppercase()
+    } else if word.starts_with(char::is_uppercase) {
+        let mut chars = joined.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        joined
+    }
+}
+
+fn is_variable(text: &str, start: usize) -> bool {
+    let before = &text[..start];
+    before.ends_with('$') || before.ends_with("${")
+}
+
+fn is_capitalized(word: &str) -> bool {
+    word.starts_with(char::is_uppercase)
+}
+
+/// "I", "I'm", "I'll" and friends are capitalized without being names.
+fn is_pronoun_i(word: &str) -> bool {
+    word == "I" || word.starts_with("I'") || word.starts_with("I’")
+}
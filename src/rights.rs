@@ -0,0 +1,293 @@
+//! Rights metadata: the `isrc` of a recording, the `iswc` of the work and
+//! how the work is split between its `writers` and its `publishers`.
+//!
+//! ```text
+//! writers: "Ann Lee (lyrics) 50%, Bo Chen (music) 50%"
+//! isrc: "US-S1Z-99-00001"
+//! iswc: "T-034.524.680-1"
+//! publishers: "Harbour Songs 50%, Kobalt 30%, Self 20%"
+//! ```
+//!
+//! Codes are accepted with or without their separators and in either case,
+//! and written back in their usual form. The semantic pass reports codes
+//! that cannot be right and shares that do not add up to 100%. A
+//! [`SplitSheet`] gathers it all for the paperwork at the end of a session.
+
+use crate::ast::Song;
+use std::fmt;
+use thiserror::Error;
+
+/// How far from 100 the shares may add up to, for splits such as thirds
+/// written as `33.33%`.
+const TOLERANCE: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RightsError {
+    #[error("invalid ISRC `{0}`, expected a country, registrant, year and number such as `US-S1Z-99-00001`")]
+    Isrc(String),
+    #[error(
+        "invalid ISWC `{0}`, expected `T`, nine digits and a check digit such as `T-034.524.680-1`"
+    )]
+    Iswc(String),
+    #[error("ISWC `{code}` ends in check digit {found}, but its digits give {expected}")]
+    IswcCheckDigit {
+        code: String,
+        found: u32,
+        expected: u32,
+    },
+    #[error("publisher share `{0}` needs a name and a percentage, such as `Kobalt 50%`")]
+    Share(String),
+    #[error("writer `{0}` has no share while the others do")]
+    Unshared(String),
+    #[error("{whose} shares add up to {total}%, not 100%")]
+    Total { whose: &'static str, total: f64 },
+    #[error("the song names no `writers` to put on a split sheet")]
+    NoWriters,
+}
+
+/// An International Standard Recording Code, identifying one recording.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Isrc(String);
+
+impl Isrc {
+    /// `text` as an ISRC: a two-letter country code, a three-character
+    /// registrant, two digits of year and five of designation.
+    pub fn parse(text: &str) -> Result<Isrc, RightsError> {
+        let code: String = text
+            .trim()
+            .chars()
+            .filter(|&c| c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let valid = code.len() == 12
+            && code.is_ascii()
+            && code[..2].bytes().all(|b| b.is_ascii_uppercase())
+            && code[2..5].bytes().all(|b| b.is_ascii_alphanumeric())
+            && code[5..].bytes().all(|b| b.is_ascii_digit());
+        match valid {
+            true => Ok(Isrc(code)),
+            false => Err(RightsError::Isrc(text.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Isrc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = &self.0;
+        write!(f, "{}-{}-{}-{}", &c[..2], &c[2..5], &c[5..7], &c[7..])
+    }
+}
+
+/// An International Standard Musical Work Code, identifying a composition.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Iswc([u32; 10]);
+
+impl Iswc {
+    /// `text` as an ISWC: `T`, nine digits and a check digit, which must
+    /// agree with the nine.
+    pub fn parse(text: &str) -> Result<Iswc, RightsError> {
+        let trimmed = text.trim();
+        let rest = trimmed
+            .strip_prefix(['T', 't'])
+            .ok_or_else(|| RightsError::Iswc(text.to_string()))?;
+        let digits: Vec<u32> = rest
+            .chars()
+            .filter(|c| !matches!(c, '-' | '.' | ' '))
+            .map(|c| c.to_digit(10))
+            .collect::<Option<_>>()
+            .ok_or_else(|| RightsError::Iswc(text.to_string()))?;
+        let digits: [u32; 10] = digits
+            .try_into()
+            .map_err(|_| RightsError::Iswc(text.to_string()))?;
+        let expected = check_digit(&digits[..9]);
+        match digits[9] == expected {
+            true => Ok(Iswc(digits)),
+            false => Err(RightsError::IswcCheckDigit {
+                code: trimmed.to_string(),
+                found: digits[9],
+                expected,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Iswc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let d: String = self.0.iter().map(|d| d.to_string()).collect();
+        write!(f, "T-{}.{}.{}-{}", &d[..3], &d[3..6], &d[6..9], &d[9..])
+    }
+}
+
+/// The ISWC check digit: one plus each digit weighted by its position,
+/// taken from the next multiple of ten.
+fn check_digit(digits: &[u32]) -> u32 {
+    let sum: u32 = 1 + digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (i as u32 + 1) * d)
+        .sum::<u32>();
+    (10 - sum % 10) % 10
+}
+
+/// The comma-separated items of `text`, trimmed, leaving commas inside
+/// parentheses alone: `Bo Chen (music, topline) 50%` is one item.
+fn items(text: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    text.split(move |c| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = (depth - 1).max(0),
+            _ => {}
+        }
+        c == ',' && depth == 0
+    })
+    .map(str::trim)
+    .filter(|item| !item.is_empty())
+}
+
+/// One publisher's share of a work.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub publisher: String,
+    /// Percent, from 0 to 100.
+    pub percent: f64,
+}
+
+/// The shares in `text`, comma separated, each a name followed by a
+/// percentage: `Harbour Songs 50%, Kobalt 50%`.
+pub fn shares(text: &str) -> Result<Vec<Share>, RightsError> {
+    items(text)
+        .map(|item| {
+            let invalid = || RightsError::Share(item.to_string());
+            let (name, percent) = item.rsplit_once(char::is_whitespace).ok_or_else(invalid)?;
+            let percent: f64 = percent
+                .strip_suffix('%')
+                .and_then(|p| p.parse().ok())
+                .filter(|p: &f64| (0.0..=100.0).contains(p))
+                .ok_or_else(invalid)?;
+            let publisher = name.trim().trim_end_matches(':').trim_end();
+            match publisher.is_empty() {
+                true => Err(invalid()),
+                false => Ok(Share {
+                    publisher: publisher.to_string(),
+                    percent,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// `shares`, checked to add up to 100%.
+pub fn splits(text: &str) -> Result<Vec<Share>, RightsError> {
+    let shares = shares(text)?;
+    total("publisher", shares.iter().map(|s| s.percent))?;
+    Ok(shares)
+}
+
+fn total(whose: &'static str, percents: impl Iterator<Item = f64>) -> Result<(), RightsError> {
+    let total: f64 = percents.sum();
+    match (total - 100.0).abs() <= TOLERANCE {
+        true => Ok(()),
+        false => Err(RightsError::Total {
+            whose,
+            total: (total * 100.0).round() / 100.0,
+        }),
+    }
+}
+
+/// A songwriter's credit on a work.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Writer {
+    pub name: String,
+    /// What they wrote, such as `lyrics` or `music`.
+    pub role: Option<String>,
+    /// Percent of the writers' share, from 0 to 100.
+    pub percent: Option<f64>,
+}
+
+/// The writers in `text`, comma separated, each a name that may be
+/// followed by a role in parentheses and a percentage: `Ann Lee (lyrics)
+/// 50%, Bo Chen (music) 50%`.
+pub fn writers(text: &str) -> Result<Vec<Writer>, RightsError> {
+    items(text)
+        .map(|item| {
+            let invalid = || RightsError::Share(item.to_string());
+            let (mut name, mut percent) = (item, None);
+            if let Some((rest, last)) = item.rsplit_once(char::is_whitespace) {
+                if let Some(number) = last.strip_suffix('%') {
+                    let number: f64 = number
+                        .parse()
+                        .ok()
+                        .filter(|p: &f64| (0.0..=100.0).contains(p))
+                        .ok_or_else(invalid)?;
+                    (name, percent) = (rest.trim_end(), Some(number));
+                }
+            }
+            let mut role = None;
+            if let Some((rest, inner)) = name.strip_suffix(')').and_then(|n| n.rsplit_once('(')) {
+                (name, role) = (rest.trim_end(), Some(inner.trim().to_string()));
+            }
+            match name.is_empty() {
+                true => Err(invalid()),
+                false => Ok(Writer {
+                    name: name.to_string(),
+                    role: role.filter(|r| !r.is_empty()),
+                    percent,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// `writers`, checked to add up to 100% when any of them has a share.
+pub fn writer_splits(text: &str) -> Result<Vec<Writer>, RightsError> {
+    let writers = writers(text)?;
+    if writers.iter().any(|w| w.percent.is_some()) {
+        if let Some(unshared) = writers.iter().find(|w| w.percent.is_none()) {
+            return Err(RightsError::Unshared(unshared.name.clone()));
+        }
+        total("writer", writers.iter().filter_map(|w| w.percent))?;
+    }
+    Ok(writers)
+}
+
+/// What a split sheet records of a work: who wrote it, in what shares, and
+/// who publishes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitSheet {
+    pub title: String,
+    pub artist: Option<String>,
+    pub iswc: Option<String>,
+    pub isrc: Option<String>,
+    pub writers: Vec<Writer>,
+    pub publishers: Vec<Share>,
+}
+
+impl SplitSheet {
+    /// The split sheet of `song`, which must name its writers. Writers
+    /// without shares are listed with the share left to fill in by hand.
+    pub fn from_song(song: &Song) -> Result<SplitSheet, RightsError> {
+        let writers = match song.meta_str("writers") {
+            Some(text) => writer_splits(&text)?,
+            None => Vec::new(),
+        };
+        if writers.is_empty() {
+            return Err(RightsError::NoWriters);
+        }
+        let publishers = match song.meta_str("publishers") {
+            Some(text) => splits(&text)?,
+            None => Vec::new(),
+        };
+        let code = |key: &str, parse: fn(&str) -> Option<String>| {
+            song.meta_str(key).map(|c| parse(&c).unwrap_or(c))
+        };
+        Ok(SplitSheet {
+            title: song.title().unwrap_or_else(|| "Untitled".to_string()),
+            artist: song.artist(),
+            iswc: code("iswc", |c| Iswc::parse(c).ok().map(|i| i.to_string())),
+            isrc: code("isrc", |c| Isrc::parse(c).ok().map(|i| i.to_string())),
+            writers,
+            publishers,
+        })
+    }
+}
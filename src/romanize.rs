@@ -0,0 +1,404 @@
+//! Romanized versions of lines written in other scripts, so karaoke
+//! screens can show the original and a reading side by side.
+//!
+//! Kana are written in Hepburn romaji, Hangul in the Revised Romanization
+//! of Korean and Cyrillic in a plain English transliteration (Ukrainian
+//! spelling when the song's `lang` is `uk`). Chinese characters take their
+//! pinyin from a built-in table of common lyric characters; Japanese kanji,
+//! whose readings depend on the word, are read from their `漢字{かんじ}`
+//! furigana and otherwise not romanized. Latin text, digits and spacing are
+//! kept as written.
+
+use crate::ast::Line;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use thiserror::Error;
+
+static PINYIN: Lazy<HashMap<char, &'static str>> = Lazy::new(|| {
+    include_str!("pinyin.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (character, reading) = line.split_once(' ')?;
+            Some((character.chars().next()?, reading))
+        })
+        .collect()
+});
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum RomanizeError {
+    #[error("no {lang} reading of `{character}`")]
+    NoReading { character: char, lang: String },
+}
+
+/// A romanized line and the language tag to store it under, e.g. `ja-Latn`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Romanized {
+    pub lang: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Kana,
+    Hangul,
+    Han,
+    Cyrillic,
+}
+
+fn script(c: char) -> Option<Script> {
+    match c {
+        '\u{3041}'..='\u{309F}' | '\u{30A0}'..='\u{30FF}' => Some(Script::Kana),
+        '\u{AC00}'..='\u{D7A3}' => Some(Script::Hangul),
+        '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{3005}' => Some(Script::Han),
+        '\u{0400}'..='\u{04FF}' => Some(Script::Cyrillic),
+        _ => None,
+    }
+}
+
+/// The language a line is in: the song's `lang` when given, else a guess
+/// from its scripts. Kana make it Japanese even beside kanji.
+fn language(text: &str, song_lang: Option<&str>) -> Option<String> {
+    let scripts: Vec<Script> = text.chars().filter_map(script).collect();
+    if scripts.is_empty() {
+        return None;
+    }
+    if let Some(lang) = song_lang {
+        let code = lang.split(['-', '_']).next().unwrap_or(lang);
+        return Some(code.to_ascii_lowercase());
+    }
+    let has = |s: Script| scripts.contains(&s);
+    let code = if has(Script::Kana) {
+        "ja"
+    } else if has(Script::Hangul) {
+        "ko"
+    } else if has(Script::Han) {
+        "zh"
+    } else {
+        "ru"
+    };
+    Some(code.to_string())
+}
+
+/// Romanize `text`, written in a song whose `lang` is `song_lang`. Lines
+/// with nothing to romanize give `None`.
+pub fn romanize(text: &str, song_lang: Option<&str>) -> Result<Option<Romanized>, RomanizeError> {
+    let Some(lang) = language(text, song_lang) else {
+        return Ok(None);
+    };
+    let mut out = Output::default();
+    let mut kana: Vec<char> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if script(c) == Some(Script::Kana) && c != '・' {
+            kana.push(c);
+            continue;
+        }
+        if !kana.is_empty() {
+            out.word(&romaji(&kana));
+            kana.clear();
+        }
+        match script(c) {
+            Some(Script::Hangul) => {
+                let next = chars.peek().copied();
+                out.push(&hangul(c, next), false);
+            }
+            Some(Script::Han) => match (lang.as_str(), PINYIN.get(&c)) {
+                ("zh", Some(reading)) => out.word(reading),
+                _ => {
+                    return Err(RomanizeError::NoReading {
+                        character: c,
+                        lang: lang.clone(),
+                    })
+                }
+            },
+            Some(Script::Cyrillic) => out.push(&cyrillic(c, &lang), false),
+            _ => match punctuation(c) {
+                Some(mark) => out.push(mark, true),
+                None => out.push(&c.to_string(), false),
+            },
+        }
+    }
+    if !kana.is_empty() {
+        out.word(&romaji(&kana));
+    }
+    Ok(Some(Romanized {
+        lang: format!("{}-Latn", lang),
+        text: out.finish(),
+    }))
+}
+
+/// Romanize a lyric line, reading its kanji from their furigana.
+pub fn romanize_line(
+    line: &Line,
+    song_lang: Option<&str>,
+) -> Result<Option<Romanized>, RomanizeError> {
+    let text: String = line
+        .ruby_runs(0..line.text.len())
+        .into_iter()
+        .map(|(run, reading)| reading.unwrap_or(run))
+        .collect();
+    romanize(&text, song_lang)
+}
+
+/// Romanized text being built. Words from scripts written without spaces
+/// are kept apart from what follows them.
+#[derive(Default)]
+struct Output {
+    text: String,
+    apart: bool,
+}
+
+impl Output {
+    /// Add a word that stands on its own, like a pinyin syllable.
+    fn word(&mut self, word: &str) {
+        if self.text.ends_with(|c: char| c.is_alphanumeric()) {
+            self.text.push(' ');
+        }
+        self.text.push_str(word);
+        self.apart = true;
+    }
+
+    fn push(&mut self, text: &str, punctuation: bool) {
+        if self.apart && !punctuation && text.starts_with(|c: char| c.is_alphanumeric()) {
+            self.text.push(' ');
+        }
+        self.text.push_str(text);
+        self.apart = false;
+    }
+
+    fn finish(self) -> String {
+        let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        text.replace(" ,", ",")
+            .replace(" .", ".")
+            .replace(" !", "!")
+            .replace(" ?", "?")
+    }
+}
+
+/// Latin equivalents of CJK punctuation. Quotation marks are dropped, so
+/// the romanization can be written as a quoted attribute.
+fn punctuation(c: char) -> Option<&'static str> {
+    Some(match c {
+        '，' | '、' => ", ",
+        '。' => ". ",
+        '！' => "! ",
+        '？' => "? ",
+        '：' => ": ",
+        '；' => "; ",
+        '…' => "...",
+        '　' | '・' => " ",
+        '「' | '」' | '『' | '』' | '“' | '”' | '"' => "",
+        _ => return None,
+    })
+}
+
+#[rustfmt::skip]
+const KANA: &[(char, &str)] = &[
+    ('あ', "a"), ('い', "i"), ('う', "u"), ('え', "e"), ('お', "o"),
+    ('か', "ka"), ('き', "ki"), ('く', "ku"), ('け', "ke"), ('こ', "ko"),
+    ('が', "ga"), ('ぎ', "gi"), ('ぐ', "gu"), ('げ', "ge"), ('ご', "go"),
+    ('さ', "sa"), ('し', "shi"), ('す', "su"), ('せ', "se"), ('そ', "so"),
+    ('ざ', "za"), ('じ', "ji"), ('ず', "zu"), ('ぜ', "ze"), ('ぞ', "zo"),
+    ('た', "ta"), ('ち', "chi"), ('つ', "tsu"), ('て', "te"), ('と', "to"),
+    ('だ', "da"), ('ぢ', "ji"), ('づ', "zu"), ('で', "de"), ('ど', "do"),
+    ('な', "na"), ('に', "ni"), ('ぬ', "nu"), ('ね', "ne"), ('の', "no"),
+    ('は', "ha"), ('ひ', "hi"), ('ふ', "fu"), ('へ', "he"), ('ほ', "ho"),
+    ('ば', "ba"), ('び', "bi"), ('ぶ', "bu"), ('べ', "be"), ('ぼ', "bo"),
+    ('ぱ', "pa"), ('ぴ', "pi"), ('ぷ', "pu"), ('ぺ', "pe"), ('ぽ', "po"),
+    ('ま', "ma"), ('み', "mi"), ('む', "mu"), ('め', "me"), ('も', "mo"),
+    ('や', "ya"), ('ゆ', "yu"), ('よ', "yo"),
+    ('ら', "ra"), ('り', "ri"), ('る', "ru"), ('れ', "re"), ('ろ', "ro"),
+    ('わ', "wa"), ('ゐ', "i"), ('ゑ', "e"), ('を', "o"), ('ん', "n"),
+    ('ゔ', "vu"), ('ゕ', "ka"), ('ゖ', "ke"),
+];
+
+/// Small kana that change the syllable before them.
+#[rustfmt::skip]
+const SMALL: &[(char, &str)] = &[
+    ('ゃ', "ya"), ('ゅ', "yu"), ('ょ', "yo"), ('ゎ', "wa"),
+    ('ぁ', "a"), ('ぃ', "i"), ('ぅ', "u"), ('ぇ', "e"), ('ぉ', "o"),
+];
+
+/// Hepburn romaji of a run of kana.
+fn romaji(kana: &[char]) -> String {
+    // Katakana as the matching hiragana.
+    let hiragana = kana.iter().map(|&c| match c {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        c => c,
+    });
+    let mut syllables: Vec<String> = Vec::new();
+    for c in hiragana {
+        if let Some((_, small)) = SMALL.iter().find(|(s, _)| *s == c) {
+            if let Some(previous) = syllables.last_mut() {
+                *previous = combine(previous, small);
+                continue;
+            }
+            syllables.push(small.to_string());
+        } else if let Some((_, romaji)) = KANA.iter().find(|(k, _)| *k == c) {
+            syllables.push(romaji.to_string());
+        } else {
+            // `っ` and `ー`, settled once the neighbours are known.
+            syllables.push(c.to_string());
+        }
+    }
+
+    let mut out = String::new();
+    for (i, syllable) in syllables.iter().enumerate() {
+        let next = syllables.get(i + 1).map(String::as_str).unwrap_or("");
+        match syllable.as_str() {
+            "っ" => match next.chars().next() {
+                Some(_) if next.starts_with("ch") => out.push('t'),
+                Some(c) if c.is_ascii_alphabetic() && !"aeiou".contains(c) => out.push(c),
+                _ => {}
+            },
+            "ー" => {
+                if let Some(vowel) = out.chars().last().filter(|c| "aeiou".contains(*c)) {
+                    out.push(vowel);
+                }
+            }
+            "n" if next.starts_with(['a', 'e', 'i', 'o', 'u', 'y']) => out.push_str("n'"),
+            syllable => out.push_str(syllable),
+        }
+    }
+    out
+}
+
+/// `previous` followed by a small kana: `ki` + `ya` is `kya`, `shi` + `ya`
+/// is `sha`, `fu` + `a` is `fa` and `te` + `i` is `ti`.
+fn combine(previous: &str, small: &str) -> String {
+    if let Some(stem) = previous
+        .strip_suffix('i')
+        .filter(|_| small.starts_with('y'))
+    {
+        return match stem.ends_with("sh") || stem.ends_with("ch") || stem == "j" {
+            true => format!("{}{}", stem, &small[1..]),
+            false => format!("{}{}", stem, small),
+        };
+    }
+    if previous == "u" {
+        return format!("w{}", small);
+    }
+    match previous.strip_suffix(['a', 'e', 'i', 'o', 'u']) {
+        Some(stem) if small.len() == 1 => format!("{}{}", stem, small),
+        _ => format!("{}{}", previous, small),
+    }
+}
+
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+const VOWELS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+/// Final consonants as they sound at the end of a syllable, and as they
+/// carry over to a following syllable that starts with a vowel, where a
+/// final `ㅎ` falls silent.
+const FINALS: [(&str, &str); 28] = [
+    ("", ""),
+    ("k", "g"),
+    ("k", "kk"),
+    ("k", "ks"),
+    ("n", "n"),
+    ("n", "nj"),
+    ("n", "n"),
+    ("t", "d"),
+    ("l", "r"),
+    ("k", "lg"),
+    ("m", "lm"),
+    ("l", "lb"),
+    ("l", "ls"),
+    ("l", "lt"),
+    ("p", "lp"),
+    ("l", "r"),
+    ("m", "m"),
+    ("p", "b"),
+    ("p", "ps"),
+    ("t", "s"),
+    ("t", "ss"),
+    ("ng", "ng"),
+    ("t", "j"),
+    ("t", "ch"),
+    ("k", "k"),
+    ("t", "t"),
+    ("p", "p"),
+    ("t", ""),
+];
+
+/// Revised Romanization of one Hangul syllable. A final consonant moves to
+/// the next syllable when that one starts with a vowel, as in `사랑이`
+/// (`sarangi`).
+fn hangul(c: char, next: Option<char>) -> String {
+    let index = c as u32 - 0xAC00;
+    let (initial, vowel, last) = (index / 588, (index % 588) / 28, index % 28);
+    let mut out = String::new();
+    out.push_str(INITIALS[initial as usize]);
+    out.push_str(VOWELS[vowel as usize]);
+    let next_starts_with_vowel = next
+        .filter(|n| ('\u{AC00}'..='\u{D7A3}').contains(n))
+        .is_some_and(|n| (n as u32 - 0xAC00) / 588 == 11);
+    let (coda, carried) = FINALS[last as usize];
+    out.push_str(match next_starts_with_vowel && last != 21 {
+        true => carried,
+        false => coda,
+    });
+    out
+}
+
+/// Transliteration of one Cyrillic letter, keeping its case.
+fn cyrillic(c: char, lang: &str) -> String {
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let latin = match (lang, lower) {
+        ("uk", 'г') => "h",
+        ("uk", 'ґ') => "g",
+        ("uk", 'и') => "y",
+        ("uk", 'і') => "i",
+        ("uk", 'ї') => "yi",
+        ("uk", 'є') => "ye",
+        (_, 'а') => "a",
+        (_, 'б') => "b",
+        (_, 'в') => "v",
+        (_, 'г' | 'ґ') => "g",
+        (_, 'д') => "d",
+        (_, 'е' | 'є') => "e",
+        (_, 'ё') => "yo",
+        (_, 'ж') => "zh",
+        (_, 'з') => "z",
+        (_, 'и' | 'і') => "i",
+        (_, 'ї') => "yi",
+        (_, 'й') => "y",
+        (_, 'к') => "k",
+        (_, 'л') => "l",
+        (_, 'м') => "m",
+        (_, 'н') => "n",
+        (_, 'о') => "o",
+        (_, 'п') => "p",
+        (_, 'р') => "r",
+        (_, 'с') => "s",
+        (_, 'т') => "t",
+        (_, 'у') => "u",
+        (_, 'ф') => "f",
+        (_, 'х') => "kh",
+        (_, 'ц') => "ts",
+        (_, 'ч') => "ch",
+        (_, 'ш') => "sh",
+        (_, 'щ') => "shch",
+        (_, 'ъ' | 'ь') => "",
+        (_, 'ы') => "y",
+        (_, 'э') => "e",
+        (_, 'ю') => "yu",
+        (_, 'я') => "ya",
+        _ => return c.to_string(),
+    };
+    match c == lower {
+        true => latin.to_string(),
+        false => {
+            let mut letters = latin.chars();
+            letters.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(letters).collect()
+            })
+        }
+    }
+}
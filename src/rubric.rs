@@ -0,0 +1,185 @@
+//! A weighted score for a song, from a rubric of metrics set in the
+//! project config:
+//!
+//! ```toml
+//! rubric.singability = 3
+//! rubric.rhyme-density = 2
+//! rubric.freshness = "0.5"
+//! rubric.repetition = -1   # less repetition scores higher
+//! ```
+//!
+//! Every metric is measured from 0 to 100. The score is the mean of the
+//! metrics weighted by the size of their weights; a negative weight counts
+//! the metric the other way round, as 100 less its value. Scores depend
+//! only on the song and the rubric, so drafts scored against the same
+//! rubric can be compared.
+
+use crate::analysis::vocabulary::normalize;
+use crate::ast::{words, Song};
+use crate::compare::scores;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Something about a song measured from 0 to 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    /// Mean singability of the lines; see [`crate::analysis::singability`].
+    Singability,
+    /// Share of lines whose last word rhymes with another line's in its
+    /// section.
+    RhymeDensity,
+    /// Share of lines whose stress fits the declared meter; not measured
+    /// without one.
+    MeterFit,
+    /// Share of different words among the words written, counting each
+    /// written-out section once.
+    Freshness,
+    /// Share of the lines sung that repeat an earlier line.
+    Repetition,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::Singability,
+        Metric::RhymeDensity,
+        Metric::MeterFit,
+        Metric::Freshness,
+        Metric::Repetition,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Singability => "singability",
+            Metric::RhymeDensity => "rhyme-density",
+            Metric::MeterFit => "meter-fit",
+            Metric::Freshness => "freshness",
+            Metric::Repetition => "repetition",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Metric> {
+        Metric::ALL.into_iter().find(|m| m.name() == name)
+    }
+}
+
+/// Metrics and their weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rubric {
+    pub weights: Vec<(Metric, f64)>,
+}
+
+impl Default for Rubric {
+    /// Singability, rhyme density, freshness and repetition, weighted
+    /// alike.
+    fn default() -> Rubric {
+        Rubric {
+            weights: vec![
+                (Metric::Singability, 1.0),
+                (Metric::RhymeDensity, 1.0),
+                (Metric::Freshness, 1.0),
+                (Metric::Repetition, 1.0),
+            ],
+        }
+    }
+}
+
+impl Rubric {
+    /// Set the weight of `metric`, adding it if the rubric lacks it.
+    pub fn set(&mut self, metric: Metric, weight: f64) {
+        match self.weights.iter_mut().find(|(m, _)| *m == metric) {
+            Some((_, w)) => *w = weight,
+            None => self.weights.push((metric, weight)),
+        }
+    }
+}
+
+/// One metric's part in a score.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Part {
+    pub metric: Metric,
+    /// The measured value, 0 to 100, or none when the metric does not
+    /// apply to the song.
+    pub value: Option<u8>,
+    pub weight: f64,
+}
+
+/// A song's score and what it is made of.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Score {
+    /// 0 to 100.
+    pub total: u8,
+    pub parts: Vec<Part>,
+}
+
+/// Measure `metric` of the resolved `song`.
+pub fn measure(song: &Song, metric: Metric) -> Option<u8> {
+    let percent = |part: usize, whole: usize| match whole {
+        0 => 100,
+        _ => ((part * 100 + whole / 2) / whole) as u8,
+    };
+    let sung = || {
+        song.sections
+            .iter()
+            .flat_map(|s| &s.lines)
+            .filter(|l| !l.text.trim().is_empty())
+    };
+    match metric {
+        Metric::Singability => Some(scores(song).singability),
+        Metric::RhymeDensity => Some(scores(song).rhyme_density),
+        Metric::MeterFit => scores(song).meter_fit,
+        Metric::Freshness => {
+            let written: Vec<String> = song
+                .sections
+                .iter()
+                .filter(|s| s.repeat.is_none())
+                .flat_map(|s| &s.lines)
+                .flat_map(|l| words(&l.text))
+                .map(normalize)
+                .collect();
+            let distinct: HashSet<&String> = written.iter().collect();
+            Some(percent(distinct.len(), written.len()))
+        }
+        Metric::Repetition => {
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            let mut repeats = 0;
+            for line in sung() {
+                let key = words(&line.text)
+                    .map(normalize)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let count = seen.entry(key).or_default();
+                repeats += (*count > 0) as usize;
+                *count += 1;
+            }
+            Some(percent(repeats, sung().count()))
+        }
+    }
+}
+
+/// Score the resolved `song` against `rubric`. Metrics that do not apply
+/// to the song, and those weighted 0, are left out of the total.
+pub fn score(song: &Song, rubric: &Rubric) -> Score {
+    let parts: Vec<Part> = rubric
+        .weights
+        .iter()
+        .map(|&(metric, weight)| Part {
+            metric,
+            value: measure(song, metric),
+            weight,
+        })
+        .collect();
+    let (sum, weights) = parts
+        .iter()
+        .filter_map(|p| Some((p.value? as f64, p.weight)))
+        .map(|(value, weight)| match weight < 0.0 {
+            true => ((100.0 - value) * -weight, -weight),
+            false => (value * weight, weight),
+        })
+        .fold((0.0, 0.0), |(s, w), (v, x)| (s + v, w + x));
+    let total = match weights > 0.0 {
+        true => (sum / weights).round() as u8,
+        false => 0,
+    };
+    Score { total, parts }
+}
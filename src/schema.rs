@@ -0,0 +1,456 @@
+//! JSON Schema for the `json` export.
+//!
+//! Every AST type that appears in the export describes its serialized form
+//! through [`JsonSchema`], mirroring its serde attributes: fields skipped by
+//! serde are left out and optional fields are not `required`.
+//! [`song_schema`] collects the descriptions into one draft 2020-12 schema
+//! whose `$id` carries [`SCHEMA_VERSION`], raised whenever the serialized
+//! form changes incompatibly.
+
+use crate::ast::{
+    Alternative, Annotation, Attribute, Credit, HistoryEntry, Hold, Line, Macro, MetaEntry, Note,
+    Placeholder, Pronunciation, Provenance, RefTarget, Reference, Ruby, Section, SectionKind, Song,
+    Sustain, TempoChange, Timestamp, Translation, Value as MetaValue, Variable,
+};
+use serde_json::{json, Map, Value};
+
+/// Version of the serialized song format described by [`song_schema`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A type with a JSON Schema for its serialized form.
+pub trait JsonSchema {
+    /// Name of the definition under `$defs`.
+    const NAME: &'static str;
+
+    fn schema() -> Value;
+}
+
+/// A `$ref` to the definition of `T`.
+fn reference<T: JsonSchema>() -> Value {
+    json!({ "$ref": format!("#/$defs/{}", T::NAME) })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+/// A closed object with the given properties, of which `required` must be
+/// present.
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn word_index() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+/// A section or line `id`, as assigned by `ids`.
+fn unit_id() -> Value {
+    json!({ "type": "string", "pattern": "^[A-Za-z0-9][A-Za-z0-9_-]*$" })
+}
+
+impl JsonSchema for Song {
+    const NAME: &'static str = "Song";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "metadata": array_of(reference::<MetaEntry>()),
+                "variables": array_of(reference::<Variable>()),
+                "macros": array_of(reference::<Macro>()),
+                "tempo_changes": array_of(reference::<TempoChange>()),
+                "credits": array_of(reference::<Credit>()),
+                "history": array_of(reference::<HistoryEntry>()),
+                "sections": array_of(reference::<Section>()),
+            }),
+            &["metadata", "sections"],
+        )
+    }
+}
+
+impl JsonSchema for MetaEntry {
+    const NAME: &'static str = "MetaEntry";
+
+    fn schema() -> Value {
+        object(
+            json!({ "key": { "type": "string" }, "value": reference::<MetaValue>() }),
+            &["key", "value"],
+        )
+    }
+}
+
+impl JsonSchema for MetaValue {
+    const NAME: &'static str = "Value";
+
+    fn schema() -> Value {
+        // Lists come from front matter, and hold scalars.
+        json!({
+            "type": ["boolean", "number", "string", "array"],
+            "items": { "type": ["boolean", "number", "string"] }
+        })
+    }
+}
+
+impl JsonSchema for Variable {
+    const NAME: &'static str = "Variable";
+
+    fn schema() -> Value {
+        object(
+            json!({ "name": { "type": "string" }, "value": { "type": "string" } }),
+            &["name", "value"],
+        )
+    }
+}
+
+impl JsonSchema for Macro {
+    const NAME: &'static str = "Macro";
+
+    fn schema() -> Value {
+        object(
+            json!({ "name": { "type": "string" }, "lines": array_of(reference::<Line>()) }),
+            &["name", "lines"],
+        )
+    }
+}
+
+impl JsonSchema for TempoChange {
+    const NAME: &'static str = "TempoChange";
+
+    fn schema() -> Value {
+        object(
+            json!({ "bpm": { "type": "number" }, "at": reference::<Timestamp>() }),
+            &["bpm", "at"],
+        )
+    }
+}
+
+impl JsonSchema for Credit {
+    const NAME: &'static str = "Credit";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "kind": { "enum": ["interpolation", "sample"] },
+                "work": { "type": "string" },
+                "artist": { "type": "string" },
+                "writers": { "type": "string" },
+                "publishers": { "type": "string" },
+                "year": { "type": "string" },
+                "from": reference::<Reference>(),
+                "to": reference::<Reference>(),
+            }),
+            &["kind", "work"],
+        )
+    }
+}
+
+impl JsonSchema for HistoryEntry {
+    const NAME: &'static str = "HistoryEntry";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "when": { "type": "string", "pattern": "^\\d{4}-\\d{2}-\\d{2} \\d{2}:\\d{2} UTC$" },
+                "message": { "type": "string" },
+            }),
+            &["when", "message"],
+        )
+    }
+}
+
+impl JsonSchema for Timestamp {
+    const NAME: &'static str = "Timestamp";
+
+    fn schema() -> Value {
+        json!({
+            "description": "Milliseconds from the start of the recording",
+            "type": "integer",
+            "minimum": 0,
+        })
+    }
+}
+
+impl JsonSchema for SectionKind {
+    const NAME: &'static str = "SectionKind";
+
+    fn schema() -> Value {
+        let names: Vec<Value> = SectionKind::ALL
+            .iter()
+            .chain([&SectionKind::Custom])
+            .map(|kind| serde_json::to_value(kind).expect("unit variants serialize"))
+            .collect();
+        json!({ "enum": names })
+    }
+}
+
+impl JsonSchema for Attribute {
+    const NAME: &'static str = "Attribute";
+
+    fn schema() -> Value {
+        object(
+            json!({ "name": { "type": "string" }, "value": reference::<MetaValue>() }),
+            &["name", "value"],
+        )
+    }
+}
+
+impl JsonSchema for Section {
+    const NAME: &'static str = "Section";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "kind": reference::<SectionKind>(),
+                "name": { "type": "string" },
+                "number": { "type": "integer", "minimum": 0 },
+                "anchor": { "type": "string" },
+                "id": unit_id(),
+                "attrs": array_of(reference::<Attribute>()),
+                "repeat": reference::<Reference>(),
+                "lines": array_of(reference::<Line>()),
+            }),
+            &["kind", "lines"],
+        )
+    }
+}
+
+impl JsonSchema for Reference {
+    const NAME: &'static str = "Reference";
+
+    fn schema() -> Value {
+        object(json!({ "target": reference::<RefTarget>() }), &["target"])
+    }
+}
+
+impl JsonSchema for RefTarget {
+    const NAME: &'static str = "RefTarget";
+
+    fn schema() -> Value {
+        let section = object(
+            json!({
+                "kind": reference::<SectionKind>(),
+                "number": { "type": ["integer", "null"], "minimum": 0 },
+            }),
+            &["kind", "number"],
+        );
+        json!({
+            "oneOf": [
+                object(json!({ "section": section }), &["section"]),
+                object(json!({ "macro": { "type": "string" } }), &["macro"]),
+                object(json!({ "anchor": { "type": "string" } }), &["anchor"]),
+            ]
+        })
+    }
+}
+
+impl JsonSchema for Line {
+    const NAME: &'static str = "Line";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "text": { "type": "string" },
+                "rhyme": { "type": "string", "minLength": 1, "maxLength": 1 },
+                "stress": { "type": "string", "pattern": "^[x/]+$" },
+                "chords": array_of(json!({ "type": "string" })),
+                "pitches": array_of(json!({ "type": "string", "pattern": "^[A-G][#b]?[0-9]$" })),
+                "timing": reference::<Timestamp>(),
+                "anchor": { "type": "string" },
+                "id": unit_id(),
+                "pronunciations": array_of(reference::<Pronunciation>()),
+                "rubies": array_of(reference::<Ruby>()),
+                "placeholders": array_of(reference::<Placeholder>()),
+                "sustains": array_of(reference::<Sustain>()),
+                "bars": array_of(word_index()),
+                "beats": array_of(word_index()),
+                "breaks": array_of(word_index()),
+                "alternatives": array_of(reference::<Alternative>()),
+                "notes": array_of(reference::<Note>()),
+                "provenance": reference::<Provenance>(),
+                "translations": array_of(reference::<Translation>()),
+                "annotations": array_of(reference::<Annotation>()),
+                "include": reference::<Reference>(),
+                "stanza_break": { "type": "boolean" },
+                "harmony": array_of(reference::<Line>()),
+            }),
+            &["text"],
+        )
+    }
+}
+
+impl JsonSchema for Pronunciation {
+    const NAME: &'static str = "Pronunciation";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "word": { "type": "string" },
+                "ipa": { "type": "string" },
+                "index": word_index(),
+            }),
+            &["word", "ipa"],
+        )
+    }
+}
+
+impl JsonSchema for Ruby {
+    const NAME: &'static str = "Ruby";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "base": { "type": "string" },
+                "reading": { "type": "string" },
+                "at": { "type": "integer", "minimum": 0 },
+            }),
+            &["base", "reading"],
+        )
+    }
+}
+
+impl JsonSchema for Placeholder {
+    const NAME: &'static str = "Placeholder";
+
+    fn schema() -> Value {
+        object(json!({ "hint": { "type": "string" } }), &[])
+    }
+}
+
+impl JsonSchema for Sustain {
+    const NAME: &'static str = "Sustain";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "word": word_index(),
+                "hold": reference::<Hold>(),
+                "within": { "type": "integer", "minimum": 1 },
+            }),
+            &["word", "hold"],
+        )
+    }
+}
+
+impl JsonSchema for Hold {
+    const NAME: &'static str = "Hold";
+
+    fn schema() -> Value {
+        json!({
+            "oneOf": [
+                object(json!({ "beats": { "type": "integer", "minimum": 0 } }), &["beats"]),
+                object(json!({ "fixed": reference::<Timestamp>() }), &["fixed"]),
+            ]
+        })
+    }
+}
+
+impl JsonSchema for Alternative {
+    const NAME: &'static str = "Alternative";
+
+    fn schema() -> Value {
+        object(json!({ "text": { "type": "string" } }), &["text"])
+    }
+}
+
+impl JsonSchema for Note {
+    const NAME: &'static str = "Note";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "kind": { "enum": ["todo", "idea", "done"] },
+                "text": { "type": "string" },
+            }),
+            &["kind", "text"],
+        )
+    }
+}
+
+impl JsonSchema for Translation {
+    const NAME: &'static str = "Translation";
+
+    fn schema() -> Value {
+        object(
+            json!({ "lang": { "type": "string" }, "text": { "type": "string" } }),
+            &["lang", "text"],
+        )
+    }
+}
+
+impl JsonSchema for Annotation {
+    const NAME: &'static str = "Annotation";
+
+    fn schema() -> Value {
+        object(
+            json!({ "name": { "type": "string" }, "value": { "type": "string" } }),
+            &["name", "value"],
+        )
+    }
+}
+
+impl JsonSchema for Provenance {
+    const NAME: &'static str = "Provenance";
+
+    fn schema() -> Value {
+        object(
+            json!({
+                "author": { "type": "string" },
+                "source": { "type": "string" },
+                "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                "edited": { "type": "string", "format": "date" },
+            }),
+            &[],
+        )
+    }
+}
+
+fn define<T: JsonSchema>(defs: &mut Map<String, Value>) {
+    defs.insert(T::NAME.to_string(), T::schema());
+}
+
+/// The schema of a song as written by the `json` exporter.
+pub fn song_schema() -> Value {
+    let mut defs = Map::new();
+    define::<Song>(&mut defs);
+    define::<MetaEntry>(&mut defs);
+    define::<MetaValue>(&mut defs);
+    define::<Variable>(&mut defs);
+    define::<Macro>(&mut defs);
+    define::<TempoChange>(&mut defs);
+    define::<Credit>(&mut defs);
+    define::<HistoryEntry>(&mut defs);
+    define::<Timestamp>(&mut defs);
+    define::<SectionKind>(&mut defs);
+    define::<Attribute>(&mut defs);
+    define::<Section>(&mut defs);
+    define::<Reference>(&mut defs);
+    define::<RefTarget>(&mut defs);
+    define::<Line>(&mut defs);
+    define::<Pronunciation>(&mut defs);
+    define::<Ruby>(&mut defs);
+    define::<Placeholder>(&mut defs);
+    define::<Sustain>(&mut defs);
+    define::<Hold>(&mut defs);
+    define::<Alternative>(&mut defs);
+    define::<Note>(&mut defs);
+    define::<Provenance>(&mut defs);
+    define::<Translation>(&mut defs);
+    define::<Annotation>(&mut defs);
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:lyrics-dsl:song:v{}", SCHEMA_VERSION),
+        "title": "lyrics-dsl song",
+        "description": format!(
+            "A song as exported by `lyrics-dsl export --format json`, format version {}",
+            SCHEMA_VERSION
+        ),
+        "$ref": "#/$defs/Song",
+        "$defs": defs,
+    })
+}
@@ -0,0 +1,517 @@
+//! A full-text index of a catalog of songs, kept on disk so that a search
+//! reads only the postings of the words it asks for instead of every song.
+//!
+//! Each lyric line is a document. The index is a directory of files:
+//!
+//! ```text
+//! meta.txt        format version, line count and total words
+//! songs.tsv       <path> <title>, one row per song
+//! lines.tsv       <song row> <source line> <section> <text>
+//! lines.offsets   where each row of lines.tsv starts, 8 bytes each
+//! terms.tsv       <term> <offset> <length> <lines holding it>, sorted
+//! postings.txt    per term: <line row>:<words in it>:<positions> …
+//! ```
+//!
+//! A search looks its words up in the term list, reads their postings at
+//! the offsets given, and ranks the lines holding all of them with BM25,
+//! favoring lines where they appear in the order asked. Only the lines
+//! shown are read back from `lines.tsv`.
+
+use crate::ast::{words, Line, Song};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Changes whenever the files' layout does, so an old index is rebuilt
+/// rather than misread.
+pub const VERSION: u32 = 1;
+
+// BM25's usual constants: how fast repeats of a word stop counting, and how
+// much a long line is discounted.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// How much more a line scores when the words asked for appear in it
+/// together, in order.
+const IN_ORDER: f64 = 1.5;
+
+#[derive(Debug, Error)]
+pub enum IndexError {
+    #[error("no search index in '{0}'; build one with `lyrics-dsl index build`")]
+    Missing(String),
+    #[error("the search index in '{0}' was built by another version of lyrics-dsl; rebuild it")]
+    Version(String),
+    #[error("'{0}' is malformed; rebuild the search index")]
+    Corrupt(String),
+    #[error("search index '{path}': {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> IndexError + '_ {
+    move |source| IndexError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+/// A lyric line as the index keeps it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedLine {
+    /// One-based line in the song's source.
+    pub line: usize,
+    /// The label of the section it is sung in, e.g. `Chorus 1`.
+    pub section: String,
+    pub text: String,
+}
+
+/// The lines of the resolved `song` to index, harmony lines included, with
+/// their line numbers in `source`. A line sung several times, through a
+/// `REPEAT` or a `USE`, is indexed where it is written.
+pub fn lines(song: &Song, source: &str) -> Vec<IndexedLine> {
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for section in &song.sections {
+        let label = section.label();
+        let mut add = |line: &Line| {
+            if line.text.trim().is_empty() || !seen.insert(line.span.start) {
+                return;
+            }
+            out.push(IndexedLine {
+                line: starts.partition_point(|&start| start <= line.span.start),
+                section: label.clone(),
+                text: line.text.clone(),
+            });
+        };
+        for line in &section.lines {
+            add(line);
+            line.harmony.iter().for_each(&mut add);
+        }
+    }
+    out.sort_by_key(|l| l.line);
+    out
+}
+
+/// The searchable terms of `text`, in order: its words, lowercased and
+/// with curly apostrophes made straight.
+pub fn terms(text: &str) -> Vec<String> {
+    words(text)
+        .map(|word| word.to_lowercase().replace('’', "'"))
+        .collect()
+}
+
+/// Collects songs in memory, then writes them out as an index.
+#[derive(Debug, Default)]
+pub struct Builder {
+    songs: Vec<(String, String)>,
+    lines: Vec<Row>,
+    postings: BTreeMap<String, Vec<(u32, Posting)>>,
+    words: u64,
+}
+
+/// Where a term appears in one line, and how many words the line has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Posting {
+    words: u32,
+    positions: Vec<u32>,
+}
+
+#[derive(Debug)]
+struct Row {
+    song: u32,
+    line: usize,
+    section: String,
+    text: String,
+}
+
+impl Builder {
+    /// Add the song at `path`, titled `title`, with its `lines`.
+    pub fn add(&mut self, path: &str, title: &str, lines: Vec<IndexedLine>) {
+        let song = self.songs.len() as u32;
+        self.songs.push((one_field(path), one_field(title)));
+        for line in lines {
+            let id = self.lines.len() as u32;
+            let terms = terms(&line.text);
+            let mut positions: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+            for (i, term) in terms.iter().enumerate() {
+                positions.entry(term).or_default().push(i as u32);
+            }
+            for (term, positions) in positions {
+                let postings = self.postings.entry(term.to_string()).or_default();
+                let words = terms.len() as u32;
+                postings.push((id, Posting { words, positions }));
+            }
+            self.words += terms.len() as u64;
+            self.lines.push(Row {
+                song,
+                line: line.line,
+                section: one_field(&line.section),
+                text: one_field(&line.text),
+            });
+        }
+    }
+
+    pub fn songs(&self) -> usize {
+        self.songs.len()
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Write the index to the directory `dir`, replacing any index there
+    /// only once the new one is complete.
+    pub fn write(&self, dir: &Path) -> Result<(), IndexError> {
+        let partial = dir.with_extension("partial");
+        if partial.exists() {
+            fs::remove_dir_all(&partial).map_err(io_error(&partial))?;
+        }
+        fs::create_dir_all(&partial).map_err(io_error(&partial))?;
+        self.write_files(&partial)?;
+        if dir.exists() {
+            fs::remove_dir_all(dir).map_err(io_error(dir))?;
+        }
+        fs::rename(&partial, dir).map_err(io_error(dir))
+    }
+
+    fn write_files(&self, dir: &Path) -> Result<(), IndexError> {
+        let meta = format!(
+            "lyrics-dsl search index {}\nlines {}\nwords {}\n",
+            VERSION,
+            self.lines.len(),
+            self.words
+        );
+        write(&dir.join("meta.txt"), meta.as_bytes())?;
+
+        let songs: String = self
+            .songs
+            .iter()
+            .map(|(path, title)| format!("{}\t{}\n", path, title))
+            .collect();
+        write(&dir.join("songs.tsv"), songs.as_bytes())?;
+
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        for row in &self.lines {
+            offsets.extend((lines.len() as u64).to_le_bytes());
+            let record = format!(
+                "{}\t{}\t{}\t{}\n",
+                row.song, row.line, row.section, row.text
+            );
+            lines.extend(record.as_bytes());
+        }
+        write(&dir.join("lines.tsv"), &lines)?;
+        write(&dir.join("lines.offsets"), &offsets)?;
+
+        let mut terms = String::new();
+        let mut postings = String::new();
+        for (term, entries) in &self.postings {
+            let record: Vec<String> = entries
+                .iter()
+                .map(|(line, posting)| {
+                    let at: Vec<String> = posting.positions.iter().map(u32::to_string).collect();
+                    format!("{}:{}:{}", line, posting.words, at.join(","))
+                })
+                .collect();
+            let record = record.join(" ");
+            terms.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                term,
+                postings.len(),
+                record.len(),
+                entries.len()
+            ));
+            postings.push_str(&record);
+            postings.push('\n');
+        }
+        write(&dir.join("terms.tsv"), terms.as_bytes())?;
+        write(&dir.join("postings.txt"), postings.as_bytes())
+    }
+}
+
+/// `text` with the tabs and line breaks that would split its record made
+/// spaces.
+fn one_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+fn write(path: &Path, data: &[u8]) -> Result<(), IndexError> {
+    let mut file = File::create(path).map_err(io_error(path))?;
+    file.write_all(data).map_err(io_error(path))
+}
+
+/// What to search for: words that must all appear in a line, and quoted
+/// phrases whose words must appear together in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub words: Vec<String>,
+    pub phrases: Vec<Vec<String>>,
+}
+
+impl Query {
+    /// Read `text`, where `"…"` marks a phrase.
+    pub fn parse(text: &str) -> Query {
+        let mut query = Query::default();
+        for (i, part) in text.split('"').enumerate() {
+            // Parts at odd indices were between quotes.
+            match i % 2 {
+                1 if !terms(part).is_empty() => query.phrases.push(terms(part)),
+                _ => query.words.extend(terms(part)),
+            }
+        }
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.phrases.is_empty()
+    }
+
+    /// Every term, once each.
+    fn terms(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        let all = self.words.iter().chain(self.phrases.iter().flatten());
+        all.map(String::as_str)
+            .filter(|t| seen.insert(*t))
+            .collect()
+    }
+}
+
+/// A line that matched a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub path: String,
+    pub title: String,
+    pub section: String,
+    pub line: usize,
+    pub text: String,
+    pub score: f64,
+}
+
+/// An index on disk, opened for searching.
+#[derive(Debug)]
+pub struct Index {
+    dir: PathBuf,
+    songs: Vec<(String, String)>,
+    /// Each term's postings: where they start, how long they are and how
+    /// many lines hold the term.
+    terms: HashMap<String, (u64, usize, usize)>,
+    lines: usize,
+    /// The average number of words in a line.
+    average: f64,
+}
+
+impl Index {
+    pub fn open(dir: &Path) -> Result<Index, IndexError> {
+        let meta_path = dir.join("meta.txt");
+        let meta = match fs::read_to_string(&meta_path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(IndexError::Missing(dir.display().to_string()))
+            }
+            Err(e) => return Err(io_error(&meta_path)(e)),
+        };
+        let corrupt = |file: &str| IndexError::Corrupt(dir.join(file).display().to_string());
+        let mut meta_lines = meta.lines();
+        if meta_lines.next() != Some(&format!("lyrics-dsl search index {}", VERSION)) {
+            return Err(IndexError::Version(dir.display().to_string()));
+        }
+        let mut count = |key: &str| {
+            meta_lines
+                .next()
+                .and_then(|l| l.strip_prefix(key))
+                .and_then(|n| n.trim().parse::<u64>().ok())
+                .ok_or_else(|| corrupt("meta.txt"))
+        };
+        let lines = count("lines")? as usize;
+        let words = count("words")?;
+
+        let songs_path = dir.join("songs.tsv");
+        let songs = fs::read_to_string(&songs_path).map_err(io_error(&songs_path))?;
+        let songs = songs
+            .lines()
+            .map(|row| {
+                let (path, title) = row.split_once('\t').ok_or_else(|| corrupt("songs.tsv"))?;
+                Ok((path.to_string(), title.to_string()))
+            })
+            .collect::<Result<Vec<_>, IndexError>>()?;
+
+        let terms_path = dir.join("terms.tsv");
+        let file = File::open(&terms_path).map_err(io_error(&terms_path))?;
+        let mut terms = HashMap::new();
+        for row in BufReader::new(file).lines() {
+            let row = row.map_err(io_error(&terms_path))?;
+            let fields: Vec<&str> = row.split('\t').collect();
+            let parsed = match fields[..] {
+                [term, offset, length, count] => offset
+                    .parse()
+                    .ok()
+                    .zip(length.parse().ok())
+                    .zip(count.parse().ok())
+                    .map(|((offset, length), count)| (term.to_string(), (offset, length, count))),
+                _ => None,
+            };
+            let (term, entry) = parsed.ok_or_else(|| corrupt("terms.tsv"))?;
+            terms.insert(term, entry);
+        }
+
+        Ok(Index {
+            dir: dir.to_path_buf(),
+            songs,
+            terms,
+            lines,
+            average: match lines {
+                0 => 1.0,
+                _ => words as f64 / lines as f64,
+            },
+        })
+    }
+
+    pub fn songs(&self) -> usize {
+        self.songs.len()
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// The `limit` best lines for `query`, best first.
+    pub fn search(&self, query: &Query, limit: usize) -> Result<Vec<Hit>, IndexError> {
+        let terms = query.terms();
+        if terms.is_empty() || terms.iter().any(|t| !self.terms.contains_key(*t)) {
+            return Ok(Vec::new());
+        }
+        let mut postings = HashMap::new();
+        for term in &terms {
+            postings.insert(*term, self.postings(term)?);
+        }
+        // The rarest term's lines are the fewest to check the others in.
+        let rarest = terms
+            .iter()
+            .min_by_key(|t| postings[**t].len())
+            .expect("a term");
+
+        let mut scored = Vec::new();
+        for &line in postings[rarest].keys() {
+            if !postings.values().all(|p| p.contains_key(&line)) {
+                continue;
+            }
+            let at = |term: &str| &postings[term][&line].positions;
+            if !query.phrases.iter().all(|phrase| in_order(phrase, &at)) {
+                continue;
+            }
+            let mut score = 0.0;
+            for lines in postings.values() {
+                let posting = &lines[&line];
+                let tf = posting.positions.len() as f64;
+                let n = lines.len() as f64;
+                let idf = (1.0 + (self.lines as f64 - n + 0.5) / (n + 0.5)).ln();
+                let length = 1.0 - B + B * posting.words as f64 / self.average;
+                score += idf * tf * (K1 + 1.0) / (tf + K1 * length);
+            }
+            if query.words.len() > 1 && in_order(&query.words, &at) {
+                score *= IN_ORDER;
+            }
+            scored.push((line, score));
+        }
+        // Rows are numbered in the order songs were added, so ties go to
+        // the first song and its earliest line.
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored.truncate(limit);
+
+        let mut hits = Vec::new();
+        for (line, score) in scored {
+            let row = self.row(line)?;
+            let (path, title) = self.songs[row.song as usize].clone();
+            hits.push(Hit {
+                path,
+                title,
+                section: row.section,
+                line: row.line,
+                text: row.text,
+                score,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Where `term` appears in every line holding it.
+    fn postings(&self, term: &str) -> Result<HashMap<u32, Posting>, IndexError> {
+        let (offset, length, _) = self.terms[term];
+        let path = self.dir.join("postings.txt");
+        let text = read_at(&path, offset, length)?;
+        let corrupt = || IndexError::Corrupt(path.display().to_string());
+        let mut out = HashMap::new();
+        for entry in text.split(' ') {
+            let mut fields = entry.split(':');
+            let mut number = || fields.next().and_then(|f| f.parse::<u32>().ok());
+            let (line, words) = number().zip(number()).ok_or_else(corrupt)?;
+            let positions = fields
+                .next()
+                .ok_or_else(corrupt)?
+                .split(',')
+                .map(|p| p.parse().map_err(|_| corrupt()))
+                .collect::<Result<Vec<u32>, _>>()?;
+            out.insert(line, Posting { words, positions });
+        }
+        Ok(out)
+    }
+
+    /// Row `line` of `lines.tsv`, found through `lines.offsets`.
+    fn row(&self, line: u32) -> Result<Row, IndexError> {
+        let offsets = self.dir.join("lines.offsets");
+        let mut bytes = [0u8; 8];
+        let mut file = File::open(&offsets).map_err(io_error(&offsets))?;
+        file.seek(SeekFrom::Start(line as u64 * 8))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .map_err(io_error(&offsets))?;
+        let path = self.dir.join("lines.tsv");
+        let mut file = File::open(&path).map_err(io_error(&path))?;
+        file.seek(SeekFrom::Start(u64::from_le_bytes(bytes)))
+            .map_err(io_error(&path))?;
+        let mut record = String::new();
+        BufReader::new(file)
+            .read_line(&mut record)
+            .map_err(io_error(&path))?;
+        let corrupt = || IndexError::Corrupt(path.display().to_string());
+        let fields: Vec<&str> = record.trim_end_matches('\n').splitn(4, '\t').collect();
+        let [song, source_line, section, text] = fields[..] else {
+            return Err(corrupt());
+        };
+        Ok(Row {
+            song: song.parse().map_err(|_| corrupt())?,
+            line: source_line.parse().map_err(|_| corrupt())?,
+            section: section.to_string(),
+            text: text.to_string(),
+        })
+    }
+}
+
+/// Whether `phrase` appears in a line, given where each of its terms is.
+fn in_order<'a>(phrase: &[String], at: &impl Fn(&str) -> &'a Vec<u32>) -> bool {
+    let Some((first, rest)) = phrase.split_first() else {
+        return true;
+    };
+    at(first).iter().any(|&start| {
+        rest.iter()
+            .enumerate()
+            .all(|(i, term)| at(term).contains(&(start + i as u32 + 1)))
+    })
+}
+
+/// `length` bytes of `path` from `offset`, as text.
+fn read_at(path: &Path, offset: u64, length: usize) -> Result<String, IndexError> {
+    let mut file = File::open(path).map_err(io_error(path))?;
+    let mut bytes = vec![0; length];
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(io_error(path))?;
+    String::from_utf8(bytes).map_err(|_| IndexError::Corrupt(path.display().to_string()))
+}
@@ -0,0 +1,253 @@
+//! Golden-file checks for the exporters.
+//!
+//! Bundled sample songs are rendered through every registered exporter and
+//! compared byte-for-byte with the expected output stored under
+//! `tests/golden/`. The same data backs the `tests/golden.rs` integration test
+//! and the `lyrics-dsl self-test` command, so an installed binary can verify
+//! itself without access to the repository.
+
+use crate::export::{exporters, Exporter};
+use crate::parser::parse_song;
+use crate::semantic::resolve;
+
+/// A song shipped with the crate for self-testing.
+pub struct Sample {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+pub const SAMPLES: &[Sample] = &[
+    Sample {
+        name: "validation_blues",
+        source: include_str!("../tests/golden/validation_blues.lyr"),
+    },
+    Sample {
+        name: "glitch_song",
+        source: include_str!("../tests/glitch_song.txt"),
+    },
+];
+
+/// Expected exporter output, keyed by sample name and exporter format.
+const GOLDEN: &[(&str, &str, &[u8])] = &[
+    (
+        "validation_blues",
+        "json",
+        include_bytes!("../tests/golden/validation_blues.json"),
+    ),
+    (
+        "validation_blues",
+        "lrc",
+        include_bytes!("../tests/golden/validation_blues.lrc"),
+    ),
+    (
+        "validation_blues",
+        "elrc",
+        include_bytes!("../tests/golden/validation_blues.elrc"),
+    ),
+    (
+        "validation_blues",
+        "html",
+        include_bytes!("../tests/golden/validation_blues.html"),
+    ),
+    (
+        "validation_blues",
+        "md",
+        include_bytes!("../tests/golden/validation_blues.md"),
+    ),
+    (
+        "validation_blues",
+        "midi",
+        include_bytes!("../tests/golden/validation_blues.midi"),
+    ),
+    (
+        "validation_blues",
+        "musicxml",
+        include_bytes!("../tests/golden/validation_blues.musicxml"),
+    ),
+    (
+        "validation_blues",
+        "pdf",
+        include_bytes!("../tests/golden/validation_blues.pdf"),
+    ),
+    (
+        "validation_blues",
+        "pptx",
+        include_bytes!("../tests/golden/validation_blues.pptx"),
+    ),
+    (
+        "validation_blues",
+        "slides",
+        include_bytes!("../tests/golden/validation_blues.slides"),
+    ),
+    (
+        "validation_blues",
+        "txt",
+        include_bytes!("../tests/golden/validation_blues.txt"),
+    ),
+    (
+        "validation_blues",
+        "ttml",
+        include_bytes!("../tests/golden/validation_blues.ttml"),
+    ),
+    (
+        "validation_blues",
+        "dot",
+        include_bytes!("../tests/golden/validation_blues.dot"),
+    ),
+    (
+        "validation_blues",
+        "d2",
+        include_bytes!("../tests/golden/validation_blues.d2"),
+    ),
+    (
+        "validation_blues",
+        "review-pdf",
+        include_bytes!("../tests/golden/validation_blues.review-pdf"),
+    ),
+    (
+        "validation_blues",
+        "review-docx",
+        include_bytes!("../tests/golden/validation_blues.review-docx"),
+    ),
+    (
+        "glitch_song",
+        "json",
+        include_bytes!("../tests/golden/glitch_song.json"),
+    ),
+    (
+        "glitch_song",
+        "lrc",
+        include_bytes!("../tests/golden/glitch_song.lrc"),
+    ),
+    (
+        "glitch_song",
+        "elrc",
+        include_bytes!("../tests/golden/glitch_song.elrc"),
+    ),
+    (
+        "glitch_song",
+        "html",
+        include_bytes!("../tests/golden/glitch_song.html"),
+    ),
+    (
+        "glitch_song",
+        "md",
+        include_bytes!("../tests/golden/glitch_song.md"),
+    ),
+    (
+        "glitch_song",
+        "midi",
+        include_bytes!("../tests/golden/glitch_song.midi"),
+    ),
+    (
+        "glitch_song",
+        "musicxml",
+        include_bytes!("../tests/golden/glitch_song.musicxml"),
+    ),
+    (
+        "glitch_song",
+        "pdf",
+        include_bytes!("../tests/golden/glitch_song.pdf"),
+    ),
+    (
+        "glitch_song",
+        "pptx",
+        include_bytes!("../tests/golden/glitch_song.pptx"),
+    ),
+    (
+        "glitch_song",
+        "slides",
+        include_bytes!("../tests/golden/glitch_song.slides"),
+    ),
+    (
+        "glitch_song",
+        "txt",
+        include_bytes!("../tests/golden/glitch_song.txt"),
+    ),
+    (
+        "glitch_song",
+        "ttml",
+        include_bytes!("../tests/golden/glitch_song.ttml"),
+    ),
+    (
+        "glitch_song",
+        "dot",
+        include_bytes!("../tests/golden/glitch_song.dot"),
+    ),
+    (
+        "glitch_song",
+        "d2",
+        include_bytes!("../tests/golden/glitch_song.d2"),
+    ),
+    (
+        "glitch_song",
+        "review-pdf",
+        include_bytes!("../tests/golden/glitch_song.review-pdf"),
+    ),
+    (
+        "glitch_song",
+        "review-docx",
+        include_bytes!("../tests/golden/glitch_song.review-docx"),
+    ),
+];
+
+/// Outcome of checking one sample against one exporter.
+#[derive(Debug)]
+pub enum CaseResult {
+    Pass,
+    /// The exporter has no golden file for this sample yet.
+    Missing,
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct Case {
+    pub sample: &'static str,
+    pub format: &'static str,
+    pub result: CaseResult,
+}
+
+/// Path of the golden file for `sample` rendered as `format`, relative to the
+/// crate root.
+pub fn golden_path(sample: &str, exporter: &dyn Exporter) -> String {
+    format!("tests/golden/{}.{}", sample, exporter.name())
+}
+
+/// Render `sample` with `exporter`.
+pub fn render(sample: &Sample, exporter: &dyn Exporter) -> Result<Vec<u8>, String> {
+    let song = parse_song(sample.source).map_err(|e| e.to_string())?;
+    let song = resolve(&song).song;
+    exporter.export(&song).map_err(|e| e.to_string())
+}
+
+/// Check every sample against every exporter using the embedded golden data.
+pub fn run() -> Vec<Case> {
+    let mut cases = Vec::new();
+    for sample in SAMPLES {
+        for exporter in exporters() {
+            let expected = GOLDEN
+                .iter()
+                .find(|(s, f, _)| *s == sample.name && *f == exporter.name())
+                .map(|(_, _, bytes)| *bytes);
+            let result = match (render(sample, exporter.as_ref()), expected) {
+                (Err(e), _) => CaseResult::Failed(e),
+                (Ok(_), None) => CaseResult::Missing,
+                (Ok(actual), Some(expected)) if actual == expected => CaseResult::Pass,
+                (Ok(actual), Some(expected)) => CaseResult::Mismatch {
+                    expected: String::from_utf8_lossy(expected).into_owned(),
+                    actual: String::from_utf8_lossy(&actual).into_owned(),
+                },
+            };
+            cases.push(Case {
+                sample: sample.name,
+                format: exporter.name(),
+                result,
+            });
+        }
+    }
+    cases
+}
@@ -0,0 +1,720 @@
+//! Name resolution between parsing and the later passes.
+//!
+//! [`resolve`] collects every definition in a song (variables, macros,
+//! anchors and numbered sections) into a [`SymbolTable`], checks each
+//! reference against it, and produces an expanded copy of the song in which
+//! `$variables` are interpolated and `USE`/`REPEAT` directives are replaced by
+//! the lines they point at. Exporters and analyses work on that expanded song;
+//! tools that edit the source work on the symbol table's spans.
+
+use crate::analysis::BPM_RANGE;
+use crate::ast::{
+    word_ranges, Line, RefTarget, Reference, Section, Song, Span, TempoChange, TimeSignature, Use,
+    ESCAPABLE,
+};
+use crate::codes;
+use crate::diagnostic::Diagnostic;
+use crate::rights::{self, Isrc, Iswc};
+use crate::suggest::{self, did_you_mean};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A `\{`-style escape or a `$name` reference in lyric text.
+static INTERPOLATED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\\(.)|\$\{([A-Za-z_][A-Za-z0-9_]*)\}|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+
+/// How the lines git and `merge` write around a conflict start.
+const CONFLICT_MARKERS: [&str; 4] = ["<<<<<<<", "|||||||", "=======", ">>>>>>>"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Variable,
+    Macro,
+    Anchor,
+    Section,
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SymbolKind::Variable => "variable",
+            SymbolKind::Macro => "macro",
+            SymbolKind::Anchor => "anchor",
+            SymbolKind::Section => "section",
+        })
+    }
+}
+
+/// A named definition and every place it is referenced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    /// Span of the name at the definition site.
+    pub definition: Span,
+    pub references: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+
+    pub fn get(&self, kind: SymbolKind, name: &str) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|s| s.kind == kind && s.name == name)
+    }
+
+    /// The symbol defined or referenced at byte `offset`, for go-to-definition.
+    pub fn symbol_at(&self, offset: usize) -> Option<&Symbol> {
+        let contains = |span: &Span| span.start <= offset && offset < span.end;
+        self.symbols
+            .iter()
+            .find(|s| contains(&s.definition) || s.references.iter().any(contains))
+    }
+
+    fn index_of(&self, kind: SymbolKind, name: &str) -> Option<usize> {
+        self.symbols
+            .iter()
+            .position(|s| s.kind == kind && s.name == name)
+    }
+
+    /// Add a definition, or report a duplicate against the existing one.
+    fn define(
+        &mut self,
+        kind: SymbolKind,
+        name: &str,
+        span: Span,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        match self.get(kind, name) {
+            Some(existing) => diagnostics.push(
+                Diagnostic::error(
+                    format!("{} `{}` is defined more than once", kind, name),
+                    span,
+                )
+                .with_code(&codes::DUPLICATE_DEFINITION)
+                .with_label(existing.definition, "first defined here"),
+            ),
+            None => self.symbols.push(Symbol {
+                kind,
+                name: name.to_string(),
+                definition: span,
+                references: Vec::new(),
+            }),
+        }
+    }
+
+    /// Record a use of `name`, or report it as undefined.
+    fn reference(
+        &mut self,
+        kind: SymbolKind,
+        name: &str,
+        span: Span,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> bool {
+        match self.index_of(kind, name) {
+            Some(i) => {
+                self.symbols[i].references.push(span);
+                true
+            }
+            None => {
+                let mut diagnostic =
+                    Diagnostic::error(format!("undefined {} `{}`", kind, name), span)
+                        .with_code(&codes::UNDEFINED_REFERENCE);
+                let known = self
+                    .symbols
+                    .iter()
+                    .filter(|s| s.kind == kind)
+                    .map(|s| s.name.as_str());
+                if let Some(similar) = did_you_mean(name, known) {
+                    // `USE *name` references span the `*` as well.
+                    let at = Span::new(span.end - name.len(), span.end);
+                    diagnostic = diagnostic
+                        .with_help(suggest::help(similar))
+                        .with_fix(suggest::fix(name, at, similar));
+                }
+                diagnostics.push(diagnostic);
+                false
+            }
+        }
+    }
+}
+
+/// Result of [`resolve`].
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    /// The song with variables interpolated and directives expanded.
+    pub song: Song,
+    pub symbols: SymbolTable,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Where an anchor points.
+#[derive(Debug, Clone, Copy)]
+enum AnchorTarget {
+    Section(usize),
+    SectionLine(usize, usize),
+    MacroLine(usize, usize),
+}
+
+/// Build the symbol table for `song`, check all references and expand it.
+pub fn resolve(song: &Song) -> Resolved {
+    let mut resolver = Resolver {
+        song,
+        symbols: SymbolTable::default(),
+        diagnostics: Vec::new(),
+        anchors: HashMap::new(),
+        repeat_targets: HashMap::new(),
+        uses: Vec::new(),
+    };
+    resolver.collect_definitions();
+    resolver.check_references();
+    resolver.check_conflicts();
+    resolver.check_tempo_map();
+    resolver.check_time_signature();
+    resolver.check_rights();
+    resolver.check_credits();
+    resolver.check_ids();
+    let expanded = resolver.expand();
+
+    let mut diagnostics = resolver.diagnostics;
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics.dedup();
+    Resolved {
+        song: expanded,
+        symbols: resolver.symbols,
+        diagnostics,
+    }
+}
+
+/// Span of a section's header keyword and number, e.g. `VERSE[1]`.
+pub fn header_span(section: &Section) -> Span {
+    let start = section.span.start;
+    let len = if section.repeat.is_some() {
+        "REPEAT".len()
+    } else {
+        section.header().len()
+    };
+    Span::new(start, start + len)
+}
+
+struct Resolver<'a> {
+    song: &'a Song,
+    symbols: SymbolTable,
+    diagnostics: Vec<Diagnostic>,
+    anchors: HashMap<String, AnchorTarget>,
+    /// Section index of each `REPEAT` mapped to the section it replays.
+    repeat_targets: HashMap<usize, usize>,
+    /// `USE`s expanded in the section being expanded.
+    uses: Vec<Use>,
+}
+
+impl<'a> Resolver<'a> {
+    fn collect_definitions(&mut self) {
+        let song = self.song;
+        for variable in &song.variables {
+            self.symbols.define(
+                SymbolKind::Variable,
+                &variable.name.name,
+                variable.name.span,
+                &mut self.diagnostics,
+            );
+        }
+        for (m, mac) in song.macros.iter().enumerate() {
+            self.symbols.define(
+                SymbolKind::Macro,
+                &mac.name.name,
+                mac.name.span,
+                &mut self.diagnostics,
+            );
+            for (l, line) in mac.lines.iter().enumerate() {
+                if let Some(anchor) = &line.anchor {
+                    self.define_anchor(&anchor.name, anchor.span, AnchorTarget::MacroLine(m, l));
+                }
+            }
+        }
+        for (s, section) in song.sections.iter().enumerate() {
+            if section.repeat.is_none() && section.number.is_some() {
+                self.symbols.define(
+                    SymbolKind::Section,
+                    &section.header(),
+                    header_span(section),
+                    &mut self.diagnostics,
+                );
+            }
+            if let Some(anchor) = &section.anchor {
+                self.define_anchor(&anchor.name, anchor.span, AnchorTarget::Section(s));
+            }
+            for (l, line) in section.lines.iter().enumerate() {
+                if let Some(anchor) = &line.anchor {
+                    self.define_anchor(&anchor.name, anchor.span, AnchorTarget::SectionLine(s, l));
+                }
+            }
+        }
+    }
+
+    fn define_anchor(&mut self, name: &str, span: Span, target: AnchorTarget) {
+        let known = self.symbols.get(SymbolKind::Anchor, name).is_some();
+        self.symbols
+            .define(SymbolKind::Anchor, name, span, &mut self.diagnostics);
+        if !known {
+            self.anchors.insert(name.to_string(), target);
+        }
+    }
+
+    /// The `tempo` and every tempo change must be within [`BPM_RANGE`], and
+    /// the changes must move forward in time.
+    fn check_tempo_map(&mut self) {
+        let out_of_range = |bpm: f64, span: Span| {
+            Diagnostic::error(
+                format!(
+                    "tempo must be between {} and {} BPM, found {}",
+                    BPM_RANGE.start(),
+                    BPM_RANGE.end(),
+                    bpm
+                ),
+                span,
+            )
+            .with_code(&codes::TEMPO_RANGE)
+        };
+        for entry in self.song.metadata.iter().filter(|e| e.key == "tempo") {
+            match entry.value.as_number() {
+                Some(bpm) if !BPM_RANGE.contains(&bpm) => {
+                    self.diagnostics.push(out_of_range(bpm, entry.span))
+                }
+                _ => {}
+            }
+        }
+        let mut previous: Option<&TempoChange> = None;
+        for change in &self.song.tempo_changes {
+            if !BPM_RANGE.contains(&change.bpm) {
+                self.diagnostics.push(out_of_range(change.bpm, change.span));
+            }
+            if let Some(previous) = previous {
+                if change.at <= previous.at {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!(
+                                "tempo change at {} is not later than the one at {}",
+                                change.at, previous.at
+                            ),
+                            change.span,
+                        )
+                        .with_code(&codes::TEMPO_ORDER)
+                        .with_label(previous.span, "previous change here"),
+                    );
+                }
+            }
+            previous = Some(change);
+        }
+    }
+
+    fn check_time_signature(&mut self) {
+        for entry in &self.song.metadata {
+            if (entry.key == "time" || entry.key == "time_sig")
+                && TimeSignature::parse(&entry.value.to_string()).is_none()
+            {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "invalid time signature `{}`, expected e.g. `4/4` or `6/8`",
+                            entry.value
+                        ),
+                        entry.span,
+                    )
+                    .with_code(&codes::INVALID_TIME_SIGNATURE),
+                );
+            }
+        }
+    }
+
+    /// `isrc` and `iswc` codes must be well formed and writer and publisher
+    /// shares must add up to 100%; see [`crate::rights`].
+    fn check_rights(&mut self) {
+        for entry in &self.song.metadata {
+            let text = entry.value.to_string();
+            let (checked, code) = match entry.key.as_str() {
+                "isrc" => (Isrc::parse(&text).map(drop), &codes::INVALID_ISRC),
+                "iswc" => (Iswc::parse(&text).map(drop), &codes::INVALID_ISWC),
+                "publishers" => (rights::splits(&text).map(drop), &codes::PUBLISHER_SHARES),
+                "writers" => (
+                    rights::writer_splits(&text).map(drop),
+                    &codes::WRITER_SHARES,
+                ),
+                _ => continue,
+            };
+            if let Err(e) = checked {
+                self.diagnostics
+                    .push(Diagnostic::error(e.to_string(), entry.span).with_code(code));
+            }
+        }
+    }
+
+    /// Section and line `id`s must each be used once, macro lines included.
+    fn check_ids(&mut self) {
+        fn line_ids<'s>(lines: &'s [Line], out: &mut Vec<(&'s str, Span)>) {
+            for line in lines {
+                out.extend(line.id.as_deref().map(|id| (id, line.span)));
+                line_ids(&line.harmony, out);
+            }
+        }
+        let song = self.song;
+        let mut ids = Vec::new();
+        for section in &song.sections {
+            ids.extend(section.id.as_deref().map(|id| (id, header_span(section))));
+            line_ids(&section.lines, &mut ids);
+        }
+        for m in &song.macros {
+            line_ids(&m.lines, &mut ids);
+        }
+        ids.sort_by_key(|(_, span)| span.start);
+        let mut seen: HashMap<&str, Span> = HashMap::new();
+        for (id, span) in ids {
+            match seen.get(id) {
+                Some(&first) => self.diagnostics.push(
+                    Diagnostic::error(format!("id `{}` is used more than once", id), span)
+                        .with_code(&codes::DUPLICATE_DEFINITION)
+                        .with_label(first, "first used here"),
+                ),
+                None => {
+                    seen.insert(id, span);
+                }
+            }
+        }
+    }
+
+    /// The anchors of each credit's `lines` must be defined, on lines that
+    /// are sung, and a range must run forward.
+    fn check_credits(&mut self) {
+        for credit in &self.song.credits {
+            let mut bounds = Vec::new();
+            for (reference, last) in [(&credit.from, false), (&credit.to, true)] {
+                let Some(Reference {
+                    target: RefTarget::Anchor(name),
+                    span,
+                }) = reference
+                else {
+                    continue;
+                };
+                if !self
+                    .symbols
+                    .reference(SymbolKind::Anchor, name, *span, &mut self.diagnostics)
+                {
+                    continue;
+                }
+                let position = match self.anchors[name] {
+                    AnchorTarget::SectionLine(s, l) => (s, l),
+                    AnchorTarget::Section(s) if last => {
+                        (s, self.song.sections[s].lines.len().saturating_sub(1))
+                    }
+                    AnchorTarget::Section(s) => (s, 0),
+                    AnchorTarget::MacroLine(..) => {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                format!(
+                                    "credit lines `*{}` are in a MACRO, which is not sung",
+                                    name
+                                ),
+                                *span,
+                            )
+                            .with_code(&codes::CREDIT_LINES)
+                            .with_help("anchor the lines where the macro is used instead"),
+                        );
+                        continue;
+                    }
+                };
+                bounds.push((position, *span));
+            }
+            if let [(from, _), (to, span)] = bounds[..] {
+                if to < from {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!("credit lines for \"{}\" end before they start", credit.work),
+                            span,
+                        )
+                        .with_code(&codes::CREDIT_LINES),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_references(&mut self) {
+        let song = self.song;
+        for mac in &song.macros {
+            for line in &mac.lines {
+                self.check_line(line);
+            }
+        }
+        for (s, section) in song.sections.iter().enumerate() {
+            if let Some(reference) = &section.repeat {
+                self.check_repeat(s, reference);
+            }
+            for line in &section.lines {
+                self.check_line(line);
+            }
+        }
+    }
+
+    fn check_line(&mut self, line: &Line) {
+        let alternatives = line.alternatives.iter().flat_map(|a| &a.vars);
+        for var in line.vars.iter().chain(alternatives) {
+            self.symbols.reference(
+                SymbolKind::Variable,
+                &var.name,
+                var.span,
+                &mut self.diagnostics,
+            );
+        }
+        if let Some(reference) = &line.include {
+            let (kind, name) = match &reference.target {
+                RefTarget::Macro(name) => (SymbolKind::Macro, name),
+                RefTarget::Anchor(name) => (SymbolKind::Anchor, name),
+                RefTarget::Section { .. } => return,
+            };
+            self.symbols
+                .reference(kind, name, reference.span, &mut self.diagnostics);
+        }
+        for part in &line.harmony {
+            self.check_line(part);
+        }
+    }
+
+    /// Report the conflict markers a merge left in the lines, which parse
+    /// as lyrics.
+    fn check_conflicts(&mut self) {
+        fn markers(line: &Line, out: &mut Vec<Span>) {
+            if CONFLICT_MARKERS.iter().any(|m| line.text.starts_with(m)) {
+                out.push(Span::new(line.span.start, line.span.start + 7));
+            }
+            for part in &line.harmony {
+                markers(part, out);
+            }
+        }
+        let mut found = Vec::new();
+        let macros = self.song.macros.iter().flat_map(|m| &m.lines);
+        for line in macros.chain(self.song.sections.iter().flat_map(|s| &s.lines)) {
+            markers(line, &mut found);
+        }
+        for span in found {
+            self.diagnostics.push(
+                Diagnostic::error("unresolved merge conflict", span)
+                    .with_code(&codes::CONFLICT_MARKER)
+                    .with_help("keep the lines that should stay and delete the markers"),
+            );
+        }
+    }
+
+    fn check_repeat(&mut self, index: usize, reference: &Reference) {
+        let RefTarget::Section { kind, number } = &reference.target else {
+            return;
+        };
+        let sections = &self.song.sections;
+        match number {
+            Some(_) => {
+                let name = reference.target.to_string();
+                if !self.symbols.reference(
+                    SymbolKind::Section,
+                    &name,
+                    reference.span,
+                    &mut self.diagnostics,
+                ) {
+                    return;
+                }
+                let target = sections
+                    .iter()
+                    .position(|s| s.repeat.is_none() && s.header() == name)
+                    .expect("section symbols come from sections");
+                if target > index {
+                    let definition = self.symbols.get(SymbolKind::Section, &name).unwrap();
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!("REPEAT {} refers to a section that comes later", name),
+                            reference.span,
+                        )
+                        .with_code(&codes::FORWARD_REPEAT)
+                        .with_label(definition.definition, "defined here"),
+                    );
+                } else {
+                    self.repeat_targets.insert(index, target);
+                }
+            }
+            None => match sections[..index].iter().rposition(|s| s.kind == *kind) {
+                Some(target) => {
+                    self.repeat_targets.insert(index, target);
+                }
+                None => self.diagnostics.push(
+                    Diagnostic::error(
+                        format!("no {} before this REPEAT", kind.keyword()),
+                        reference.span,
+                    )
+                    .with_code(&codes::NOTHING_TO_REPEAT),
+                ),
+            },
+        }
+    }
+
+    fn expand(&mut self) -> Song {
+        let song = self.song;
+        let mut sections: Vec<Section> = Vec::with_capacity(song.sections.len());
+        for (s, section) in song.sections.iter().enumerate() {
+            let lines = match self.repeat_targets.get(&s) {
+                Some(&target) => sections[target].lines.clone(),
+                None if section.repeat.is_some() => Vec::new(),
+                None => self.expand_lines(&section.lines, &mut Vec::new()),
+            };
+            sections.push(Section {
+                lines,
+                uses: std::mem::take(&mut self.uses),
+                ..section.clone()
+            });
+        }
+
+        Song {
+            metadata: song.metadata.clone(),
+            front_matter: song.front_matter.clone(),
+            variables: Vec::new(),
+            macros: Vec::new(),
+            tempo_changes: song.tempo_changes.clone(),
+            credits: song.credits.clone(),
+            history: song.history.clone(),
+            sections,
+        }
+    }
+
+    /// Expand `USE` lines and interpolate variables. `stack` holds the macros
+    /// and anchors currently being expanded so cycles can be reported.
+    fn expand_lines(&mut self, lines: &[Line], stack: &mut Vec<String>) -> Vec<Line> {
+        let mut out = Vec::with_capacity(lines.len());
+        for line in lines {
+            let Some(reference) = &line.include else {
+                out.push(self.interpolate(line));
+                continue;
+            };
+            let key = reference.target.to_string();
+            if stack.contains(&key) {
+                self.diagnostics.push(
+                    Diagnostic::error(format!("`USE {}` includes itself", key), reference.span)
+                        .with_code(&codes::INCLUDE_CYCLE),
+                );
+                continue;
+            }
+            self.uses.push(Use {
+                target: key.clone(),
+                within: stack.last().cloned(),
+            });
+            stack.push(key);
+            // Included lines take the place of the `USE` line, stanza break
+            // and all.
+            let start = out.len();
+            match &reference.target {
+                RefTarget::Macro(name) => {
+                    if let Some(mac) = self.song.macros.iter().find(|m| m.name.name == *name) {
+                        out.extend(self.expand_lines(&mac.lines, stack));
+                    }
+                }
+                RefTarget::Anchor(name) => {
+                    let included = match self.anchors.get(name).copied() {
+                        Some(AnchorTarget::Section(s)) => {
+                            let lines = &self.song.sections[s].lines;
+                            self.expand_lines(lines, stack)
+                        }
+                        Some(AnchorTarget::SectionLine(s, l)) => {
+                            let line = &self.song.sections[s].lines[l..=l];
+                            self.expand_lines(line, stack)
+                        }
+                        Some(AnchorTarget::MacroLine(m, l)) => {
+                            let line = &self.song.macros[m].lines[l..=l];
+                            self.expand_lines(line, stack)
+                        }
+                        None => Vec::new(),
+                    };
+                    // Copies must not redefine the anchor they came from.
+                    out.extend(included.into_iter().map(|mut l| {
+                        l.anchor = None;
+                        l
+                    }));
+                }
+                RefTarget::Section { .. } => {}
+            }
+            if let Some(first) = out.get_mut(start) {
+                first.stanza_break = line.stanza_break;
+            }
+            stack.pop();
+        }
+        out
+    }
+
+    fn interpolate(&self, line: &Line) -> Line {
+        let mut line = line.clone();
+        if !line.vars.is_empty() || line.text.contains('\\') {
+            let raw = std::mem::take(&mut line.text);
+            line.text = self.substitute(&raw);
+            // Variables may expand to several words, so word-level marks
+            // are re-pointed at the word they were written on.
+            let ranges = word_ranges(&raw);
+            let remap = |word: usize| match ranges.get(word) {
+                Some(range) => word_ranges(&self.substitute(&raw[..range.start])).len(),
+                None => word_ranges(&line.text).len(),
+            };
+            for sustain in &mut line.sustains {
+                sustain.word = remap(sustain.word);
+            }
+            for bar in line.bars.iter_mut().chain(&mut line.beats) {
+                *bar = remap(*bar);
+            }
+            for word in &mut line.breaks {
+                *word = remap(*word);
+            }
+            for pronunciation in &mut line.pronunciations {
+                pronunciation.index = remap(pronunciation.index);
+            }
+            for ruby in &mut line.rubies {
+                ruby.at = self.substitute(&raw[..ruby.at]).len();
+            }
+            line.vars.clear();
+        }
+        for alternative in &mut line.alternatives {
+            if !alternative.vars.is_empty() {
+                alternative.text = self.substitute(&alternative.text);
+                alternative.vars.clear();
+            }
+        }
+        line.harmony = line.harmony.iter().map(|h| self.interpolate(h)).collect();
+        line
+    }
+
+    /// Expand the variable references in `text` and drop the backslash of
+    /// each escape.
+    fn substitute(&self, text: &str) -> String {
+        INTERPOLATED
+            .replace_all(text, |caps: &regex::Captures| {
+                if let Some(escaped) = caps.get(1) {
+                    return match escaped.as_str().starts_with(ESCAPABLE) {
+                        true => escaped.as_str().to_string(),
+                        false => caps[0].to_string(),
+                    };
+                }
+                let name = caps.get(2).or_else(|| caps.get(3)).unwrap().as_str();
+                self.song
+                    .variables
+                    .iter()
+                    .find(|v| v.name.name == name)
+                    .map(|v| v.value.clone())
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+}
@@ -0,0 +1,201 @@
+//! Setlists: an ordered list of songs for a performance, read from a small
+//! TOML file.
+//!
+//! ```toml
+//! title = "Friday at the Blue Room"
+//!
+//! [[song]]
+//! file = "opener.lyr"
+//!
+//! [[song]]
+//! file = "ballad.lyr"
+//! key = "Eb"          # transpose from the song's own key
+//! notes = "slow intro, no drums"
+//! ```
+//!
+//! Only the parts of TOML a setlist needs are understood: comments, string
+//! and integer values, and the `[[song]]` array of tables.
+
+use crate::ast::{MetaEntry, Song, Value};
+use crate::chords::nashville::Key;
+use crate::chords::transpose_song;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SetlistError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("song {0} has no `file`")]
+    MissingFile(usize),
+    #[error("the setlist has no songs")]
+    Empty,
+    #[error("`{0}` is not a key")]
+    InvalidKey(String),
+    #[error("cannot move to {0}: the song has no `key` to transpose from")]
+    NoKey(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Setlist {
+    pub title: Option<String>,
+    pub songs: Vec<SetlistEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetlistEntry {
+    /// Path of the song, relative to the setlist file.
+    pub file: String,
+    /// Key to perform the song in, when it differs from the song's own.
+    pub key: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl Setlist {
+    pub fn parse(text: &str) -> Result<Setlist, SetlistError> {
+        let mut setlist = Setlist::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = i + 1;
+            let syntax = |message: &str| SetlistError::Syntax {
+                line,
+                message: message.to_string(),
+            };
+            let content = strip_comment(raw).trim();
+            if content.is_empty() {
+                continue;
+            }
+            if content == "[[song]]" {
+                setlist.songs.push(SetlistEntry::default());
+                continue;
+            }
+            if content.starts_with('[') {
+                return Err(syntax(&format!("unknown table `{}`", content)));
+            }
+            let (key, value) = content
+                .split_once('=')
+                .ok_or_else(|| syntax("expected `key = value`"))?;
+            let value = parse_value(value.trim()).ok_or_else(|| syntax("invalid value"))?;
+            let field = match (setlist.songs.last_mut(), key.trim()) {
+                (None, "title") => &mut setlist.title,
+                (Some(song), "file") => {
+                    song.file = value;
+                    continue;
+                }
+                (Some(song), "key") => &mut song.key,
+                (Some(song), "notes") => &mut song.notes,
+                (_, key) => return Err(syntax(&format!("unknown key `{}`", key))),
+            };
+            *field = Some(value);
+        }
+        if setlist.songs.is_empty() {
+            return Err(SetlistError::Empty);
+        }
+        match setlist.songs.iter().position(|s| s.file.is_empty()) {
+            Some(i) => Err(SetlistError::MissingFile(i + 1)),
+            None => Ok(setlist),
+        }
+    }
+}
+
+/// The line without a trailing `#` comment, ignoring `#` inside strings.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// A basic string, an integer or a float, as text.
+pub(crate) fn parse_value(value: &str) -> Option<String> {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => out.push(match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    c @ ('"' | '\\') => c,
+                    _ => return None,
+                }),
+                '"' => return None,
+                c => out.push(c),
+            }
+        }
+        return Some(out);
+    }
+    if let Ok(n) = value.parse::<i64>() {
+        return Some(n.to_string());
+    }
+    // `f64` also reads `inf`, `NaN` and `.5`, which TOML does not.
+    let float = value.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+        && value.ends_with(|c: char| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+    let n = value
+        .parse::<f64>()
+        .ok()
+        .filter(|n| float && n.is_finite())?;
+    Some(n.to_string())
+}
+
+/// Move `song` into the entry's key, transposing its chords and updating
+/// its `key` metadata. Songs without an override are left alone.
+pub fn apply_key(song: &mut Song, entry: &SetlistEntry) -> Result<(), SetlistError> {
+    let Some(target) = &entry.key else {
+        return Ok(());
+    };
+    let to = Key::parse(target).ok_or_else(|| SetlistError::InvalidKey(target.clone()))?;
+    let from = song
+        .meta_str("key")
+        .ok_or_else(|| SetlistError::NoKey(target.clone()))?;
+    let from = Key::parse(&from).ok_or(SetlistError::InvalidKey(from))?;
+    transpose_song(song, to.tonic.0 as i32 - from.tonic.0 as i32, to.flats);
+    let value = Value::String(to.to_string());
+    match song.metadata.iter_mut().find(|m| m.key == "key") {
+        Some(entry) => entry.value = value,
+        None => song.metadata.push(MetaEntry {
+            key: "key".to_string(),
+            value,
+            block: false,
+            span: Default::default(),
+        }),
+    }
+    Ok(())
+}
+
+/// One line per song for the stage floor: position, title, then whichever
+/// of key, capo, tempo, duration and notes are known.
+pub fn cheat_sheet(songs: &[(&SetlistEntry, &Song)]) -> Vec<String> {
+    songs
+        .iter()
+        .enumerate()
+        .map(|(i, (entry, song))| {
+            let mut parts = vec![song.title().unwrap_or_else(|| entry.file.clone())];
+            if let Some(key) = song.meta_str("key") {
+                parts.push(key);
+            }
+            if let Some(capo) = song.meta_str("capo") {
+                parts.push(format!("capo {}", capo));
+            }
+            if let Some(tempo) = song.meta_str("tempo") {
+                parts.push(format!("{} bpm", tempo));
+            }
+            if let Some(duration) = song.meta_str("duration") {
+                parts.push(duration);
+            }
+            if let Some(notes) = &entry.notes {
+                parts.push(notes.clone());
+            }
+            format!("{}. {}", i + 1, parts.join(" · "))
+        })
+        .collect()
+}
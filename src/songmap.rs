@@ -0,0 +1,240 @@
+//! A song map: the sections in the order they are sung, as blocks sized by
+//! how long each lasts or how many lines it has, colored by section kind.
+//!
+//! A section lasts from its first timed line to the next section's; the
+//! last one runs to the song's `duration`. Durations are only used when
+//! every section has a timed line and the song has a `duration`, and line
+//! counts otherwise.
+
+use crate::analysis::alignment::parse_duration;
+use crate::ast::{SectionKind, Song};
+use crate::export::ttml::escape;
+use std::fmt::Write;
+use thiserror::Error;
+
+/// SVG width in pixels and height of the row of blocks.
+const SVG_WIDTH: usize = 640;
+const SVG_ROW: usize = 36;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum MapError {
+    #[error("sizing sections by duration needs a timed line in every section and a `duration`")]
+    NoDurations,
+}
+
+/// What a block's size stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    Duration,
+    Lines,
+}
+
+impl Measure {
+    pub const NAMES: [&'static str; 2] = ["duration", "lines"];
+
+    pub fn parse(name: &str) -> Option<Measure> {
+        match name {
+            "duration" => Some(Measure::Duration),
+            "lines" => Some(Measure::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// One section as it appears on the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub kind: SectionKind,
+    pub label: String,
+    pub lines: usize,
+    /// How long the section lasts, when the song's timings tell.
+    pub seconds: Option<f64>,
+}
+
+impl Block {
+    /// A label of a letter or two and the section number, e.g. `V1`, `PC`.
+    pub fn short_label(&self) -> String {
+        let letters = match self.kind {
+            SectionKind::Verse => "V".to_string(),
+            SectionKind::Chorus => "C".to_string(),
+            SectionKind::Bridge => "B".to_string(),
+            SectionKind::PreChorus => "PC".to_string(),
+            SectionKind::Outro => "O".to_string(),
+            SectionKind::Intro => "I".to_string(),
+            SectionKind::Custom => self
+                .label
+                .chars()
+                .take(1)
+                .collect::<String>()
+                .to_uppercase(),
+        };
+        let number = self
+            .label
+            .rsplit(' ')
+            .next()
+            .filter(|n| n.parse::<u32>().is_ok());
+        format!("{}{}", letters, number.unwrap_or(""))
+    }
+}
+
+/// The color of a section kind, as RGB.
+pub fn color(kind: SectionKind) -> (u8, u8, u8) {
+    match kind {
+        SectionKind::Intro => (120, 144, 156),
+        SectionKind::Verse => (66, 133, 244),
+        SectionKind::PreChorus => (171, 71, 188),
+        SectionKind::Chorus => (234, 67, 53),
+        SectionKind::Bridge => (251, 188, 4),
+        SectionKind::Outro => (96, 125, 139),
+        SectionKind::Custom => (52, 168, 83),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SongMap {
+    pub title: Option<String>,
+    pub blocks: Vec<Block>,
+    pub measure: Measure,
+}
+
+impl SongMap {
+    /// The map of the resolved `song`, sized by `measure`, or by duration
+    /// when the timings allow it and by lines otherwise.
+    pub fn new(song: &Song, measure: Option<Measure>) -> Result<SongMap, MapError> {
+        let starts: Vec<Option<f64>> = song
+            .sections
+            .iter()
+            .map(|s| {
+                s.lines
+                    .iter()
+                    .find_map(|l| l.timing)
+                    .map(|t| t.as_secs_f64())
+            })
+            .collect();
+        let end = song.meta_str("duration").and_then(|d| parse_duration(&d));
+        let known = end.is_some() && !starts.is_empty() && starts.iter().all(Option::is_some);
+        let mut blocks: Vec<Block> = song
+            .sections
+            .iter()
+            .map(|section| Block {
+                kind: section.kind,
+                label: section.label(),
+                lines: section.lines.len(),
+                seconds: None,
+            })
+            .collect();
+        if known {
+            for (i, block) in blocks.iter_mut().enumerate() {
+                let next = starts.get(i + 1).copied().flatten().or(end);
+                block.seconds = Some((next.unwrap_or(0.0) - starts[i].unwrap_or(0.0)).max(0.0));
+            }
+        }
+        let measure = match measure {
+            Some(Measure::Duration) if !known => return Err(MapError::NoDurations),
+            Some(measure) => measure,
+            None if known => Measure::Duration,
+            None => Measure::Lines,
+        };
+        Ok(SongMap {
+            title: song.title(),
+            blocks,
+            measure,
+        })
+    }
+
+    fn size(&self, block: &Block) -> f64 {
+        match self.measure {
+            Measure::Duration => block.seconds.unwrap_or(0.0),
+            Measure::Lines => block.lines as f64,
+        }
+    }
+
+    /// The total the blocks add up to, as `3:45` or `24 lines`.
+    pub fn total(&self) -> String {
+        let total: f64 = self.blocks.iter().map(|b| self.size(b)).sum();
+        match self.measure {
+            Measure::Duration => {
+                let seconds = total.round() as u64;
+                format!("{}:{:02}", seconds / 60, seconds % 60)
+            }
+            Measure::Lines => format!("{} lines", total),
+        }
+    }
+
+    /// The width of each block in `columns` cells, in proportion to its
+    /// size but never under one cell.
+    pub fn widths(&self, columns: usize) -> Vec<usize> {
+        let count = self.blocks.len();
+        let total: f64 = self.blocks.iter().map(|b| self.size(b)).sum();
+        let shares: Vec<f64> = self
+            .blocks
+            .iter()
+            .map(|b| match total > 0.0 {
+                true => self.size(b) / total * columns as f64,
+                false => columns as f64 / count as f64,
+            })
+            .collect();
+        let mut widths: Vec<usize> = shares.iter().map(|s| (s.floor() as usize).max(1)).collect();
+        // Hand out the cells lost to rounding down, largest remainder first.
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by(|&a, &b| (shares[b].fract()).total_cmp(&shares[a].fract()));
+        let used: usize = widths.iter().sum();
+        for &i in order.iter().take(columns.saturating_sub(used)) {
+            widths[i] += 1;
+        }
+        widths
+    }
+
+    /// The map as a standalone SVG image.
+    pub fn to_svg(&self) -> String {
+        let top = if self.title.is_some() { 28 } else { 0 };
+        let height = top + SVG_ROW + 24;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\">\n",
+            SVG_WIDTH, height, SVG_WIDTH, height
+        );
+        if let Some(title) = &self.title {
+            let _ = writeln!(
+                svg,
+                "<text x=\"0\" y=\"18\" font-size=\"16\" font-weight=\"bold\">{}</text>",
+                escape(title)
+            );
+        }
+        let mut x = 0;
+        for (block, width) in self.blocks.iter().zip(self.widths(SVG_WIDTH)) {
+            let (r, g, b) = color(block.kind);
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"rgb({},{},{})\" stroke=\"white\"><title>{}</title></rect>",
+                x,
+                top,
+                width,
+                SVG_ROW,
+                r,
+                g,
+                b,
+                escape(&block.label)
+            );
+            let label = block.short_label();
+            if width >= 8 * label.len() + 4 {
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"12\" fill=\"white\" text-anchor=\"middle\">{}</text>",
+                    x + width / 2,
+                    top + SVG_ROW / 2 + 4,
+                    escape(&label)
+                );
+            }
+            x += width;
+        }
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"#666\" text-anchor=\"end\">{}</text>",
+            SVG_WIDTH,
+            top + SVG_ROW + 16,
+            escape(&self.total())
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
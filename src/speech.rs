@@ -0,0 +1,148 @@
+//! Text-to-speech previews: a song's lines read aloud with the pauses a
+//! singer would leave, to hear the phrasing and catch tongue-twisters
+//! without singing.
+//!
+//! [`script`] works in every build. Speaking it needs the `tts` feature
+//! and a system speech program: `say` on macOS, `espeak-ng` or `espeak`,
+//! or `spd-say`. Each line is spoken on its own, so the pauses between
+//! lines are the same whatever the program.
+
+use crate::ast::Song;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Silence after a line, before the next one in its stanza.
+pub const LINE_PAUSE: Duration = Duration::from_millis(300);
+/// Silence at a stanza break.
+pub const STANZA_PAUSE: Duration = Duration::from_millis(900);
+/// Silence between sections.
+pub const SECTION_PAUSE: Duration = Duration::from_millis(1600);
+
+#[derive(Debug, Error)]
+pub enum SpeechError {
+    #[error("no section `{name}`; the song has {labels}")]
+    NoSection { name: String, labels: String },
+    #[error("no speech program found: install espeak-ng, or use `say` on macOS")]
+    NoBackend,
+    #[error("{program}: {message}")]
+    Backend { program: String, message: String },
+}
+
+/// A line to speak and the silence to leave after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Utterance {
+    pub text: String,
+    pub pause: Duration,
+}
+
+/// The lines of the resolved `song` to speak, or only those of the
+/// sections named `section`, such as `chorus` or `verse 2`, compared
+/// without regard to case. A name without a number takes every section of
+/// that name; `REPEAT`s are not spoken again.
+pub fn script(song: &Song, section: Option<&str>) -> Result<Vec<Utterance>, SpeechError> {
+    let sections: Vec<_> = song
+        .sections
+        .iter()
+        .filter(|s| s.repeat.is_none() || section.is_none())
+        .filter(|s| section.is_none_or(|name| s.is_named(name)))
+        .collect();
+    if sections.is_empty() {
+        if let Some(name) = section {
+            let mut labels: Vec<String> = Vec::new();
+            for label in song.sections.iter().map(|s| s.label()) {
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+            return Err(SpeechError::NoSection {
+                name: name.to_string(),
+                labels: labels.join(", "),
+            });
+        }
+    }
+
+    let mut utterances: Vec<Utterance> = Vec::new();
+    for section in sections {
+        if let Some(last) = utterances.last_mut() {
+            last.pause = SECTION_PAUSE;
+        }
+        for line in section.lines.iter().filter(|l| !l.text.trim().is_empty()) {
+            if let Some(last) = utterances.last_mut().filter(|_| line.stanza_break) {
+                last.pause = last.pause.max(STANZA_PAUSE);
+            }
+            utterances.push(Utterance {
+                text: line.text.clone(),
+                pause: LINE_PAUSE,
+            });
+        }
+    }
+    if let Some(last) = utterances.last_mut() {
+        last.pause = Duration::ZERO;
+    }
+    Ok(utterances)
+}
+
+/// Speech programs tried in turn, with the flags for a voice and a rate in
+/// words a minute.
+#[cfg(feature = "tts")]
+const BACKENDS: &[(&str, &str, &str)] = &[
+    ("say", "-v", "-r"),
+    ("espeak-ng", "-v", "-s"),
+    ("espeak", "-v", "-s"),
+    ("spd-say", "-t", "-r"),
+];
+
+/// Speak `utterances` in order with the first speech program found,
+/// waiting out each pause. `spd-say` takes a rate from -100 to 100 rather
+/// than words a minute.
+#[cfg(feature = "tts")]
+pub fn speak(
+    utterances: &[Utterance],
+    voice: Option<&str>,
+    rate: Option<u32>,
+) -> Result<(), SpeechError> {
+    use std::process::Command;
+
+    let mut backend = None;
+    for utterance in utterances {
+        let candidates = match backend {
+            Some(found) => std::slice::from_ref(found),
+            None => BACKENDS,
+        };
+        let mut spoken = false;
+        for entry in candidates {
+            let (program, voice_flag, rate_flag) = *entry;
+            let mut command = Command::new(program);
+            if program == "spd-say" {
+                command.arg("-w");
+            }
+            if let Some(voice) = voice {
+                command.args([voice_flag, voice]);
+            }
+            if let Some(rate) = rate {
+                command.args([rate_flag, &rate.to_string()]);
+            }
+            let output = match command.arg(&utterance.text).output() {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                output => output,
+            };
+            let fail = |message: String| SpeechError::Backend {
+                program: program.to_string(),
+                message,
+            };
+            let output = output.map_err(|e| fail(e.to_string()))?;
+            if !output.status.success() {
+                let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(fail(message));
+            }
+            backend = Some(entry);
+            spoken = true;
+            break;
+        }
+        if !spoken {
+            return Err(SpeechError::NoBackend);
+        }
+        std::thread::sleep(utterance.pause);
+    }
+    Ok(())
+}
@@ -0,0 +1,442 @@
+//! Spell checking against Hunspell dictionaries, the `.dic` and `.aff`
+//! pairs shipped with LibreOffice, Firefox and most Linux distributions,
+//! plus a project's own word list for invented words and names.
+//!
+//! Only what a lyric needs is read from the `.aff` file: `SET`, `FLAG`,
+//! `TRY`, `REP` and one level of `PFX` and `SFX` rules, combined when both
+//! allow it. Compounding and continuation classes are not supported, so a
+//! word formed only that way is reported as misspelt; add it to the word
+//! list.
+//!
+//! A word in lower case is also accepted capitalized or in capitals, and a
+//! capitalized one in capitals, so a line may start with `Paris` or shout
+//! `PARIS`, but `paris` is a misspelling.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the project's word list inside the project directory: one word
+/// a line, with `#` comments.
+pub const WORDS_FILE: &str = "words.txt";
+/// Most suggestions offered for one misspelling.
+const MAX_SUGGESTIONS: usize = 5;
+/// Letters tried for suggestions when the `.aff` file has no `TRY`.
+const DEFAULT_TRY: &str = "esianrtolcdugmphbyfvkwzxjq'";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SpellingError {
+    #[error(
+        "no dictionary `{0}`; install it (e.g. hunspell-en-us) or give the path to its .dic file"
+    )]
+    NotFound(String),
+    #[error("{path}: {message}")]
+    Read { path: String, message: String },
+    #[error("line {line} of the .aff file: {message}")]
+    Affix { line: usize, message: String },
+}
+
+/// How the `.aff` file writes flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagKind {
+    /// One character each, the default.
+    Char,
+    /// Two characters each: `FLAG long`.
+    Long,
+    /// Comma-separated numbers: `FLAG num`.
+    Num,
+}
+
+impl FlagKind {
+    fn split(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagKind::Char => flags.chars().map(String::from).collect(),
+            FlagKind::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars.chunks(2).map(|c| c.iter().collect()).collect()
+            }
+            FlagKind::Num => flags.split(',').map(|f| f.trim().to_string()).collect(),
+        }
+    }
+}
+
+/// One character of an affix condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl Condition {
+    fn parse(text: &str) -> Vec<Condition> {
+        let mut conditions = Vec::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            conditions.push(match c {
+                '.' => Condition::Any,
+                '[' => {
+                    let set: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                    match set.strip_prefix('^') {
+                        Some(set) => Condition::NoneOf(set.chars().collect()),
+                        None => Condition::OneOf(set.chars().collect()),
+                    }
+                }
+                c => Condition::OneOf(vec![c]),
+            });
+        }
+        conditions
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::OneOf(set) => set.contains(&c),
+            Condition::NoneOf(set) => !set.contains(&c),
+        }
+    }
+}
+
+/// A `PFX` or `SFX` rule: take `strip` off the stem and put `add` on, if
+/// the stem's start (for a prefix) or end (for a suffix) meets `condition`.
+#[derive(Debug, Clone)]
+struct Affix {
+    flag: String,
+    suffix: bool,
+    cross: bool,
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    fn apply(&self, stem: &str) -> Option<String> {
+        let chars: Vec<char> = stem.chars().collect();
+        let n = self.condition.len();
+        if chars.len() < n || chars.len() <= self.strip.chars().count() {
+            return None;
+        }
+        let tested = match self.suffix {
+            true => &chars[chars.len() - n..],
+            false => &chars[..n],
+        };
+        if !self
+            .condition
+            .iter()
+            .zip(tested)
+            .all(|(c, &ch)| c.matches(ch))
+        {
+            return None;
+        }
+        match self.suffix {
+            true => stem
+                .strip_suffix(self.strip.as_str())
+                .map(|base| format!("{}{}", base, self.add)),
+            false => stem
+                .strip_prefix(self.strip.as_str())
+                .map(|base| format!("{}{}", self.add, base)),
+        }
+    }
+}
+
+/// The words a song may use.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// Letters tried in suggestions, most common first.
+    try_chars: Vec<char>,
+    /// `REP` pairs: common misspellings and their corrections.
+    replacements: Vec<(String, String)>,
+}
+
+impl Dictionary {
+    /// A dictionary from the text of a `.aff` and a `.dic` file, with every
+    /// affixed form of every word.
+    pub fn parse(aff: &str, dic: &str) -> Result<Dictionary, SpellingError> {
+        let mut flags = FlagKind::Char;
+        let mut try_chars = DEFAULT_TRY.chars().collect();
+        let mut replacements = Vec::new();
+        let mut affixes: Vec<Affix> = Vec::new();
+        // Flag to whether its rules combine with the other kind of affix.
+        let mut cross: Vec<(String, bool)> = Vec::new();
+        for (i, raw) in aff.lines().enumerate() {
+            let fields: Vec<&str> = raw.split_whitespace().collect();
+            let malformed = |message: &str| SpellingError::Affix {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            match fields.as_slice() {
+                ["FLAG", "long"] => flags = FlagKind::Long,
+                ["FLAG", "num"] => flags = FlagKind::Num,
+                ["TRY", letters] => try_chars = letters.chars().collect(),
+                ["REP", from, to] => {
+                    replacements.push((from.replace('_', " "), to.replace('_', " ")))
+                }
+                ["PFX" | "SFX", flag, cross_product, count]
+                    if count.parse::<usize>().is_ok() && !cross.iter().any(|(f, _)| f == flag) =>
+                {
+                    cross.push((flag.to_string(), *cross_product == "Y"));
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let combines = cross
+                        .iter()
+                        .find(|(f, _)| f == flag)
+                        .ok_or_else(|| malformed("affix rule before its header"))?
+                        .1;
+                    // Continuation classes after the `/` are not followed.
+                    let add = add.split('/').next().unwrap_or_default();
+                    let zero = |s: &str| match s {
+                        "0" => String::new(),
+                        s => s.to_string(),
+                    };
+                    affixes.push(Affix {
+                        flag: flag.to_string(),
+                        suffix: *kind == "SFX",
+                        cross: combines,
+                        strip: zero(strip),
+                        add: zero(add),
+                        condition: Condition::parse(rest.first().copied().unwrap_or(".")),
+                    });
+                }
+                [kind @ ("PFX" | "SFX"), ..] => {
+                    return Err(malformed(&format!("incomplete {} rule", kind)))
+                }
+                _ => {}
+            }
+        }
+
+        let mut words = HashSet::new();
+        let mut entries = dic.lines();
+        // The first line is the number of entries, a hint only.
+        if let Some(first) = entries.clone().next() {
+            if first.trim().parse::<usize>().is_ok() {
+                entries.next();
+            }
+        }
+        for entry in entries {
+            // Morphological fields follow a tab or space.
+            let Some(entry) = entry.split(['\t', ' ']).next().filter(|e| !e.is_empty()) else {
+                continue;
+            };
+            let (word, word_flags) = match entry.split_once('/') {
+                Some((word, word_flags)) => (word, flags.split(word_flags)),
+                None => (entry, Vec::new()),
+            };
+            let rules: Vec<&Affix> = affixes
+                .iter()
+                .filter(|a| word_flags.contains(&a.flag))
+                .collect();
+            for suffix in rules.iter().filter(|a| a.suffix) {
+                let Some(suffixed) = suffix.apply(word) else {
+                    continue;
+                };
+                for prefix in rules
+                    .iter()
+                    .filter(|a| !a.suffix && a.cross && suffix.cross)
+                {
+                    words.extend(prefix.apply(&suffixed));
+                }
+                words.insert(suffixed);
+            }
+            for prefix in rules.iter().filter(|a| !a.suffix) {
+                words.extend(prefix.apply(word));
+            }
+            words.insert(word.to_string());
+        }
+        Ok(Dictionary {
+            words,
+            try_chars,
+            replacements,
+        })
+    }
+
+    /// A dictionary of just `words`, written as in a word list.
+    pub fn from_words(words: &str) -> Dictionary {
+        let mut dictionary = Dictionary {
+            try_chars: DEFAULT_TRY.chars().collect(),
+            ..Dictionary::default()
+        };
+        dictionary.add_words(words);
+        dictionary
+    }
+
+    /// Read the Hunspell dictionary at `dic`, with the `.aff` file beside
+    /// it. Dictionaries declaring `SET ISO8859-1` are decoded from it;
+    /// anything else is read as UTF-8.
+    pub fn load(dic: &Path) -> Result<Dictionary, SpellingError> {
+        let read = |path: &Path| {
+            std::fs::read(path).map_err(|e| SpellingError::Read {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })
+        };
+        let aff = read(&dic.with_extension("aff"))?;
+        let dic = read(dic)?;
+        let latin1 = String::from_utf8_lossy(&aff)
+            .lines()
+            .any(|l| l.split_whitespace().eq(["SET", "ISO8859-1"]));
+        let decode = |bytes: &[u8]| match latin1 {
+            true => bytes.iter().map(|&b| b as char).collect(),
+            false => String::from_utf8_lossy(bytes).into_owned(),
+        };
+        Dictionary::parse(&decode(&aff), &decode(&dic))
+    }
+
+    /// Accept the words of a word list: one a line, with `#` comments.
+    pub fn add_words(&mut self, list: &str) {
+        let words = list
+            .lines()
+            .map(|l| l.split('#').next().unwrap_or_default().trim())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.replace('’', "'"));
+        self.words.extend(words);
+    }
+
+    /// Whether `word` is spelt right.
+    pub fn check(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        if self.words.contains(&word) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        match case_of(&word) {
+            Case::Title => self.words.contains(&lower),
+            Case::Upper => self.words.contains(&lower) || self.words.contains(&title(&lower)),
+            _ => false,
+        }
+    }
+
+    /// Words one edit away from `word` that are spelt right, in the case
+    /// `word` was written in: first `word` itself cased as the dictionary
+    /// has it, then the `REP` corrections, then the rest.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let chars: Vec<char> = lower.chars().collect();
+        // The word itself, for one written in the wrong case.
+        let mut candidates: Vec<String> = vec![lower.clone()];
+        for (from, to) in &self.replacements {
+            for (at, _) in lower.match_indices(from.as_str()) {
+                candidates.push(format!(
+                    "{}{}{}",
+                    &lower[..at],
+                    to,
+                    &lower[at + from.len()..]
+                ));
+            }
+        }
+        let edit = |f: &mut dyn FnMut(&mut Vec<char>)| {
+            let mut chars = chars.clone();
+            f(&mut chars);
+            chars.into_iter().collect::<String>()
+        };
+        for i in 1..chars.len() {
+            candidates.push(edit(&mut |c| c.swap(i - 1, i)));
+        }
+        for i in 0..chars.len() {
+            for &t in &self.try_chars {
+                candidates.push(edit(&mut |c| c[i] = t));
+            }
+        }
+        for i in 0..chars.len() {
+            candidates.push(edit(&mut |c| {
+                c.remove(i);
+            }));
+        }
+        for i in 0..=chars.len() {
+            for &t in &self.try_chars {
+                candidates.push(edit(&mut |c| c.insert(i, t)));
+            }
+        }
+        for i in 1..chars.len() {
+            candidates.push(edit(&mut |c| c.insert(i, ' ')));
+        }
+
+        let mut suggestions: Vec<String> = Vec::new();
+        for candidate in candidates {
+            // A name keeps its capital wherever it stands.
+            let spelt = [candidate.clone(), title(&candidate)]
+                .into_iter()
+                .find(|c| c.split(' ').all(|w| self.words.contains(w)));
+            let Some(spelt) = spelt else { continue };
+            let cased = match case_of(word) {
+                Case::Title => title(&spelt),
+                Case::Upper => spelt.to_uppercase(),
+                _ => spelt,
+            };
+            if cased != word && !suggestions.contains(&cased) {
+                suggestions.push(cased);
+            }
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        suggestions
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+fn case_of(word: &str) -> Case {
+    let mut letters = word.chars().filter(|c| c.is_alphabetic());
+    let Some(first) = letters.next() else {
+        return Case::Lower;
+    };
+    let rest: Vec<char> = letters.collect();
+    match (first.is_uppercase(), rest.iter().all(|c| c.is_lowercase())) {
+        (false, true) => Case::Lower,
+        (true, true) => Case::Title,
+        (true, false) if rest.iter().all(|c| c.is_uppercase()) => Case::Upper,
+        _ => Case::Mixed,
+    }
+}
+
+fn title(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Where dictionaries are looked for: the project's `dictionaries`
+/// directory, then `$DICPATH`, then where Linux distributions and macOS
+/// install them.
+pub fn search_path(project_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![project_dir.join("dictionaries")];
+    if let Some(paths) = std::env::var_os("DICPATH") {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+    dirs.extend(
+        [
+            "/usr/share/hunspell",
+            "/usr/share/myspell",
+            "/usr/share/myspell/dicts",
+            "/usr/local/share/hunspell",
+            "/Library/Spelling",
+        ]
+        .map(PathBuf::from),
+    );
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(Path::new(&home).join("Library/Spelling"));
+    }
+    dirs
+}
+
+/// The `.dic` file of the dictionary `name`, such as `en_US`, in the first
+/// of `dirs` that has it, or `name` itself when it is a path to one.
+pub fn locate(name: &str, dirs: &[PathBuf]) -> Result<PathBuf, SpellingError> {
+    let given = Path::new(name);
+    if given.extension().is_some_and(|e| e == "dic") && given.exists() {
+        return Ok(given.to_path_buf());
+    }
+    dirs.iter()
+        .map(|dir| dir.join(format!("{}.dic", name)))
+        .find(|path| path.exists())
+        .ok_or_else(|| SpellingError::NotFound(name.to_string()))
+}
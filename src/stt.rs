@@ -0,0 +1,186 @@
+//! Speech-to-text drafts: a sung or spoken recording transcribed into a
+//! song for a person to clean up.
+//!
+//! Transcription runs whisper.cpp and needs the `stt` feature; reading its
+//! output and writing the draft work in every build. Each line of a draft
+//! carries `{source: speech-to-text, confidence: …}` provenance, with the
+//! recognizer's confidence capped at [`DRAFT_CONFIDENCE`], and the first
+//! line a `!! todo` asking for the draft to be checked.
+
+use crate::ast::Timestamp;
+use crate::format::escape_text;
+use serde_json::Value;
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Highest confidence given to a transcribed line, however sure the
+/// recognizer was: every line still wants a human ear.
+pub const DRAFT_CONFIDENCE: f64 = 0.5;
+/// A pause of at least this many seconds between segments starts a new
+/// verse.
+const STANZA_GAP: f64 = 4.0;
+
+#[derive(Debug, Error)]
+pub enum SttError {
+    #[error("cannot read the transcript: {0}")]
+    Transcript(String),
+    #[error("no speech found in the recording")]
+    NoSpeech,
+    #[error("{program}: {message}")]
+    Tool { program: String, message: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A phrase of the transcript, with times in seconds from the start of the
+/// recording and the recognizer's confidence from 0 to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    pub confidence: f64,
+}
+
+/// The segments of whisper.cpp's full JSON output (`-ojf`). A segment's
+/// confidence is the mean probability of its text tokens.
+pub fn parse_whisper_json(json: &str) -> Result<Vec<Segment>, SttError> {
+    let invalid = |what: &str| SttError::Transcript(what.to_string());
+    let value: Value = serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?;
+    let segments = value["transcription"]
+        .as_array()
+        .ok_or_else(|| invalid("no `transcription` list"))?;
+    let mut out = Vec::new();
+    for segment in segments {
+        let millis = |end: &str| segment["offsets"][end].as_f64();
+        let (Some(start), Some(end)) = (millis("from"), millis("to")) else {
+            return Err(invalid("a segment has no `offsets`"));
+        };
+        let text = segment["text"].as_str().unwrap_or_default().trim();
+        // Tokens such as `[_BEG_]` and `[_TT_150]` are timing, not text.
+        let probabilities: Vec<f64> = segment["tokens"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|t| !t["text"].as_str().is_some_and(|t| t.starts_with("[_")))
+            .filter_map(|t| t["p"].as_f64())
+            .collect();
+        let confidence = match probabilities.len() {
+            0 => 0.0,
+            n => probabilities.iter().sum::<f64>() / n as f64,
+        };
+        out.push(Segment {
+            start: start / 1000.0,
+            end: end / 1000.0,
+            text: text.to_string(),
+            confidence,
+        });
+    }
+    Ok(out)
+}
+
+/// Whether `text` only notes a sound, like `[Music]`, `(instrumental)` or
+/// `♪`, rather than words.
+fn is_sound(text: &str) -> bool {
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '♪' || c == '♫');
+    text.is_empty()
+        || (text.starts_with('[') && text.ends_with(']'))
+        || (text.starts_with('(') && text.ends_with(')'))
+}
+
+/// Song source titled `title` holding the words of `segments`, one line
+/// each, timed at their start.
+pub fn draft(title: &str, segments: &[Segment]) -> Result<String, SttError> {
+    let sung: Vec<&Segment> = segments.iter().filter(|s| !is_sound(&s.text)).collect();
+    if sung.is_empty() {
+        return Err(SttError::NoSpeech);
+    }
+    let mut out = format!("title: \"{}\"\n", title.replace('"', "'"));
+    let mut verse = 0;
+    let mut last_end = None;
+    for segment in sung {
+        if last_end.is_none_or(|end| segment.start - end >= STANZA_GAP) {
+            verse += 1;
+            let _ = writeln!(out, "\nVERSE[{}]", verse);
+        }
+        let confidence = (segment.confidence.min(DRAFT_CONFIDENCE) * 100.0).round() / 100.0;
+        let at = Timestamp::from_millis((segment.start * 1000.0).round() as u64);
+        let _ = write!(
+            out,
+            "{} {{timing: {}, source: speech-to-text, confidence: {}}}",
+            escape_text(&segment.text),
+            at,
+            confidence
+        );
+        if last_end.is_none() {
+            out.push_str(" !! todo: transcribed by speech-to-text; check every line");
+        }
+        out.push('\n');
+        last_end = Some(segment.end);
+    }
+    Ok(out)
+}
+
+/// whisper.cpp command-line programs, newest name first.
+#[cfg(feature = "stt")]
+const WHISPER: &[&str] = &["whisper-cli", "whisper-cpp"];
+
+/// Transcribe the recording at `audio` with the whisper.cpp `model`. The
+/// audio is converted to 16 kHz mono WAV with `ffmpeg` first, so any format
+/// it reads will do.
+#[cfg(feature = "stt")]
+pub fn transcribe(
+    audio: &std::path::Path,
+    model: &std::path::Path,
+) -> Result<Vec<Segment>, SttError> {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir()?;
+    let wav = dir.path().join("audio.wav");
+    let output = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-y", "-i"])
+        .arg(audio)
+        .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+        .arg(&wav)
+        .output();
+    finished("ffmpeg", output)?;
+
+    let prefix = dir.path().join("transcript");
+    let (program, output) = WHISPER
+        .iter()
+        .map(|program| {
+            let output = Command::new(program)
+                .arg("-m")
+                .arg(model)
+                .arg("-f")
+                .arg(&wav)
+                .args(["-ojf", "-np", "-of"])
+                .arg(&prefix)
+                .output();
+            (program, output)
+        })
+        .find(|(_, output)| !matches!(output, Err(e) if e.kind() == std::io::ErrorKind::NotFound))
+        .ok_or_else(|| SttError::Tool {
+            program: WHISPER.join(" or "),
+            message: "not found; install whisper.cpp".to_string(),
+        })?;
+    finished(program, output)?;
+    let json = std::fs::read_to_string(prefix.with_extension("json"))?;
+    parse_whisper_json(&json)
+}
+
+/// An error unless `program` ran and succeeded.
+#[cfg(feature = "stt")]
+fn finished(program: &str, output: std::io::Result<std::process::Output>) -> Result<(), SttError> {
+    let fail = |message: String| SttError::Tool {
+        program: program.to_string(),
+        message,
+    };
+    let output = output.map_err(|e| fail(e.to_string()))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(fail(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
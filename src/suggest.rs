@@ -0,0 +1,63 @@
+//! "Did you mean" suggestions for misspelt names.
+//!
+//! A name is compared with the names that would have been valid in its
+//! place by Levenshtein distance, ignoring case, so `VESE` suggests `VERSE`
+//! and `chorus` suggests `CHORUS`.
+
+use crate::ast::Span;
+use crate::diagnostic::Fix;
+
+/// Number of single-character insertions, deletions and substitutions
+/// turning `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// The candidate closest to `name`, if any is close enough to be a likely
+/// typo: at most one edit per three characters, fewer edits than `name`
+/// has characters, and never `name` itself.
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let folded = name.to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (levenshtein(&folded, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, candidate)| {
+            let length = name.chars().count().max(candidate.chars().count());
+            *distance <= length.div_ceil(3) && *distance < name.chars().count()
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The help text for a suggestion.
+pub fn help(suggestion: &str) -> String {
+    format!("did you mean `{}`?", suggestion)
+}
+
+/// A fix replacing `name`, written at `span`, with `suggestion`.
+pub fn fix(name: &str, span: Span, suggestion: &str) -> Fix {
+    Fix::replace(
+        format!("replace `{}` with `{}`", name, suggestion),
+        span,
+        suggestion,
+    )
+}
@@ -0,0 +1,171 @@
+//! Timing a song's lines against its recording.
+//!
+//! [`stub`] spreads a song's lines over the phrases found in its recording,
+//! each line taking a share of the sung time in proportion to its
+//! syllables. Silence between phrases is skipped, so a line never starts in
+//! a gap. The result is a first draft for a person to correct by ear, not an
+//! alignment.
+//!
+//! [`Taps`] records timings by hand instead: the recording plays and each
+//! tap stamps the start of the next line.
+
+use crate::analysis::language;
+use crate::analysis::syllables::count_line_in;
+use crate::ast::{Song, Timestamp};
+use crate::audio::Phrase;
+use crate::semantic::resolve;
+
+/// Something sung, with its share of the time: a line of the song, or lines
+/// a `REPEAT` or `USE` brings in, which take time but get no timing.
+struct Slot {
+    weight: f64,
+    line: Option<(usize, usize)>,
+}
+
+fn weight(syllables: usize) -> f64 {
+    syllables.max(1) as f64
+}
+
+/// What is sung, in order.
+fn slots(song: &Song) -> Vec<Slot> {
+    let resolved = resolve(song).song;
+    let language = language::of(song);
+    let count_line = |line| count_line_in(line, language);
+    let mut slots = Vec::new();
+    for (s, (section, expanded)) in song.sections.iter().zip(&resolved.sections).enumerate() {
+        let total: f64 = expanded.lines.iter().map(|l| weight(count_line(l))).sum();
+        if section.repeat.is_some() {
+            slots.push(Slot {
+                weight: total,
+                line: None,
+            });
+            continue;
+        }
+        let (included, own): (Vec<_>, Vec<_>) = section
+            .lines
+            .iter()
+            .enumerate()
+            .partition(|(_, line)| line.include.is_some());
+        let own_weight: f64 = own.iter().map(|(_, l)| weight(count_line(l))).sum();
+        let per_include = (total - own_weight).max(0.0) / included.len().max(1) as f64;
+        for (l, line) in section.lines.iter().enumerate() {
+            slots.push(match line.include {
+                Some(_) => Slot {
+                    weight: per_include,
+                    line: None,
+                },
+                None => Slot {
+                    weight: weight(count_line(line)),
+                    line: Some((s, l)),
+                },
+            });
+        }
+    }
+    slots
+}
+
+/// Where `offset` seconds into the sung time falls in the recording. An
+/// offset at the end of one phrase falls at the start of the next.
+fn place(phrases: &[Phrase], mut offset: f64) -> f64 {
+    for phrase in phrases {
+        if offset < phrase.duration() {
+            return phrase.start + offset;
+        }
+        offset -= phrase.duration();
+    }
+    phrases.last().map_or(0.0, |p| p.end)
+}
+
+/// `song` with a timing for every line, replacing any it had, spread over
+/// `phrases`. With no phrases the song is returned unchanged.
+pub fn stub(song: &Song, phrases: &[Phrase]) -> Song {
+    let mut out = song.clone();
+    let slots = slots(song);
+    let total: f64 = slots.iter().map(|slot| slot.weight).sum();
+    let sung: f64 = phrases.iter().map(Phrase::duration).sum();
+    if phrases.is_empty() || total == 0.0 {
+        return out;
+    }
+    let mut before = 0.0;
+    for slot in &slots {
+        if let Some((s, l)) = slot.line {
+            let at = place(phrases, before / total * sung);
+            out.sections[s].lines[l].timing =
+                Some(Timestamp::from_millis((at * 1000.0).round() as u64));
+        }
+        before += slot.weight;
+    }
+    out
+}
+
+/// The lines that take a timing, as section and line indices, in the order
+/// they are sung: every line of the song's sections except `USE` lines.
+/// `REPEAT` sections have no lines of their own.
+pub fn timed_lines(song: &Song) -> Vec<(usize, usize)> {
+    song.sections
+        .iter()
+        .enumerate()
+        .flat_map(|(s, section)| {
+            section
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.include.is_none())
+                .map(move |(l, _)| (s, l))
+        })
+        .collect()
+}
+
+/// A tap-along session: one timestamp per line of [`timed_lines`], in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Taps {
+    lines: Vec<(usize, usize)>,
+    times: Vec<Timestamp>,
+}
+
+impl Taps {
+    pub fn new(song: &Song) -> Taps {
+        Taps {
+            lines: timed_lines(song),
+            times: Vec::new(),
+        }
+    }
+
+    /// The line the next tap stamps, or `None` once every line has one.
+    pub fn current(&self) -> Option<(usize, usize)> {
+        self.lines.get(self.times.len()).copied()
+    }
+
+    /// Lines stamped so far, and lines in all.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.times.len(), self.lines.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Stamp the current line as starting `at`. A tap after the last line,
+    /// or one no later than the tap before it, is ignored.
+    pub fn tap(&mut self, at: Timestamp) {
+        let later = self.times.last().is_none_or(|last| at > *last);
+        if !self.is_finished() && later {
+            self.times.push(at);
+        }
+    }
+
+    /// Take back the latest tap, so its line is stamped again.
+    pub fn undo(&mut self) {
+        self.times.pop();
+    }
+
+    /// `song` with the stamped lines timed. Lines not reached keep the
+    /// timing they had.
+    pub fn apply(&self, song: &Song) -> Song {
+        let mut out = song.clone();
+        for (&(s, l), &at) in self.lines.iter().zip(&self.times) {
+            out.sections[s].lines[l].timing = Some(at);
+        }
+        out
+    }
+}
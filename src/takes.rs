@@ -0,0 +1,135 @@
+//! Alternative takes of a line, written after it as `/ alt: "..."`.
+//!
+//! The line's own words are the take that is sung; its alternatives wait
+//! beside it, numbered from 1, until [`choose`] swaps one in. The take
+//! swapped out becomes the alternative in its place, so a choice can always
+//! be undone. Exports leave the alternatives out; see
+//! [`crate::export::without_alternatives`].
+
+use crate::ast::{Line, Song};
+use crate::format::line_source;
+use crate::parser::parse_line_content;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TakesError {
+    #[error("line {0} is not a lyric line")]
+    NotALine(usize),
+    #[error("line {0} has no alternatives")]
+    NoAlternatives(usize),
+    #[error("line {line} has {count} alternative(s), not {alt}")]
+    NoSuchAlternative {
+        line: usize,
+        alt: usize,
+        count: usize,
+    },
+    #[error("alternative {alt} of line {line} is not a lyric: {message}")]
+    Unparsable {
+        line: usize,
+        alt: usize,
+        message: String,
+    },
+}
+
+/// A line with alternatives, as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    /// `Verse 1`, or `macro name` for lines inside a macro.
+    pub section: String,
+    /// 1-based source line.
+    pub line: usize,
+    /// The take that is sung.
+    pub text: String,
+    pub alternatives: Vec<String>,
+}
+
+/// Every line of `song`, parsed from `source`, that has alternatives, in
+/// source order.
+pub fn stacks(song: &Song, source: &str) -> Vec<Stack> {
+    let macros = song
+        .macros
+        .iter()
+        .map(|m| (format!("macro {}", m.name.name), &m.lines));
+    let sections = song.sections.iter().map(|s| (s.label(), &s.lines));
+    let mut found: Vec<Stack> = macros
+        .chain(sections)
+        .flat_map(|(label, lines)| {
+            lines
+                .iter()
+                .filter(|l| !l.alternatives.is_empty())
+                .map(move |l| Stack {
+                    section: label.clone(),
+                    line: l.span.line_col(source).0,
+                    text: l.text.clone(),
+                    alternatives: l.alternatives.iter().map(|a| a.text.clone()).collect(),
+                })
+        })
+        .collect();
+    found.sort_by_key(|s| s.line);
+    found
+}
+
+/// The words of `line` as they are written, markup and all but without
+/// its attributes and notes, ready to be kept as an alternative.
+fn words_source(line: &Line) -> String {
+    let words = Line {
+        text: line.text.clone(),
+        pronunciations: line.pronunciations.clone(),
+        rubies: line.rubies.clone(),
+        placeholders: line.placeholders.clone(),
+        sustains: line.sustains.clone(),
+        bars: line.bars.clone(),
+        beats: line.beats.clone(),
+        ..Line::default()
+    };
+    // A quoted alternative cannot hold a double quote.
+    line_source(&words).trim_end().replace('"', "'")
+}
+
+/// Sing alternative `alt` (from 1) of the lyric line written at source
+/// line `number` of `song`, parsed from `source`, keeping the words it
+/// replaces as that alternative. Returns the words now sung.
+pub fn choose(
+    song: &mut Song,
+    source: &str,
+    number: usize,
+    alt: usize,
+) -> Result<String, TakesError> {
+    let macros = song.macros.iter_mut().flat_map(|m| m.lines.iter_mut());
+    let sections = song.sections.iter_mut().flat_map(|s| s.lines.iter_mut());
+    let line = macros
+        .chain(sections)
+        .find(|l| l.include.is_none() && l.span.line_col(source).0 == number)
+        .ok_or(TakesError::NotALine(number))?;
+    let count = line.alternatives.len();
+    if count == 0 {
+        return Err(TakesError::NoAlternatives(number));
+    }
+    if alt == 0 || alt > count {
+        return Err(TakesError::NoSuchAlternative {
+            line: number,
+            alt,
+            count,
+        });
+    }
+
+    let chosen = parse_line_content(&line.alternatives[alt - 1].text).map_err(|e| {
+        TakesError::Unparsable {
+            line: number,
+            alt,
+            message: e.variant.message().into_owned(),
+        }
+    })?;
+    line.alternatives[alt - 1].text = words_source(line);
+    line.alternatives[alt - 1].vars = std::mem::take(&mut line.vars);
+    line.text = chosen.text;
+    line.pronunciations = chosen.pronunciations;
+    line.rubies = chosen.rubies;
+    line.placeholders = chosen.placeholders;
+    line.sustains = chosen.sustains;
+    line.bars = chosen.bars;
+    line.beats = chosen.beats;
+    line.breaks = chosen.breaks;
+    line.vars = chosen.vars;
+    Ok(line.text.clone())
+}
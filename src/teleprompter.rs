@@ -0,0 +1,228 @@
+//! A scrolling lyric view for reading from a screen on stage.
+//!
+//! The song is laid out as rows (a heading per section, then its lines) and
+//! the view keeps a reading position within them. Without timings the
+//! position moves at a steady pace; with `timed` it jumps to the line being
+//! sung at the elapsed time, so the view follows a recording.
+
+use crate::ast::Song;
+use crate::playback::{cue_at, cues, Cue};
+
+/// Rows scrolled per second at speed 1.0 when not following timings.
+pub const ROWS_PER_SECOND: f64 = 0.5;
+
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub text: String,
+    pub heading: bool,
+    /// Index into the cues for a lyric row.
+    pub cue: Option<usize>,
+}
+
+/// How a row of a [`Frame`] should be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowStyle {
+    Heading,
+    Current,
+    Normal,
+}
+
+/// One screenful: `height` lines of at most `width` characters.
+pub type Frame = Vec<(String, RowStyle)>;
+
+#[derive(Debug, Clone)]
+pub struct Teleprompter {
+    cues: Vec<Cue>,
+    rows: Vec<Row>,
+    /// Reading position in rows; the row under the reading line.
+    position: f64,
+    /// Seconds of the song played so far, scaled by `speed`.
+    elapsed: f64,
+    pub speed: f64,
+    pub paused: bool,
+    pub timed: bool,
+}
+
+impl Teleprompter {
+    pub fn new(song: &Song, speed: f64, timed: bool) -> Teleprompter {
+        let cues = cues(song);
+        let mut rows: Vec<Row> = Vec::new();
+        for (i, cue) in cues.iter().enumerate() {
+            if i == 0 || cues[i - 1].section != cue.section {
+                if i > 0 {
+                    rows.push(Row {
+                        text: String::new(),
+                        heading: false,
+                        cue: None,
+                    });
+                }
+                rows.push(Row {
+                    text: cue.section.to_uppercase(),
+                    heading: true,
+                    cue: None,
+                });
+            }
+            rows.push(Row {
+                text: cue.text.clone(),
+                heading: false,
+                cue: Some(i),
+            });
+        }
+        Teleprompter {
+            cues,
+            rows,
+            position: 0.0,
+            elapsed: 0.0,
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+            paused: false,
+            timed,
+        }
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Index of the row under the reading line.
+    pub fn current_row(&self) -> usize {
+        (self.position.floor() as usize).min(self.rows.len().saturating_sub(1))
+    }
+
+    /// Seconds into the song, as followed in timed mode.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Whether the last row has been reached.
+    pub fn finished(&self) -> bool {
+        self.current_row() + 1 >= self.rows.len()
+    }
+
+    /// Advance by `dt` seconds of wall-clock time.
+    pub fn tick(&mut self, dt: f64) {
+        if self.paused {
+            return;
+        }
+        self.elapsed += dt * self.speed;
+        if self.timed {
+            if let Some(cue) = cue_at(&self.cues, self.elapsed) {
+                let row = self.rows.iter().position(|r| r.cue == Some(cue));
+                self.position = row.unwrap_or(0) as f64;
+            }
+        } else {
+            let last = self.rows.len().saturating_sub(1) as f64;
+            self.position = (self.position + dt * self.speed * ROWS_PER_SECOND).min(last);
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Multiply the speed by `factor`, within 0.25× to 4×.
+    pub fn change_speed(&mut self, factor: f64) {
+        self.speed = (self.speed * factor).clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    /// Move the reading position by whole rows. In timed mode this also
+    /// moves the clock to the start of the new line, when it has one.
+    pub fn scroll(&mut self, rows: i32) {
+        let last = self.rows.len().saturating_sub(1) as f64;
+        self.position = (self.current_row() as f64 + rows as f64).clamp(0.0, last);
+        if self.timed {
+            let start = self.rows[self.current_row()..]
+                .iter()
+                .find_map(|r| self.cues[r.cue?].start);
+            if let Some(start) = start {
+                self.elapsed = start.as_secs_f64();
+            }
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.position = 0.0;
+        self.elapsed = 0.0;
+    }
+
+    /// The view for a `width` × `height` screen, with the reading line a
+    /// third of the way down and rows centered.
+    pub fn frame(&self, width: usize, height: usize) -> Frame {
+        let current = self.current_row();
+        let anchor = height / 3;
+        let mut frame: Frame = Vec::with_capacity(height);
+        // Rows above the reading line, wrapped, nearest first.
+        let mut above: Frame = Vec::new();
+        for (i, row) in self.rows[..current].iter().enumerate().rev() {
+            for line in self.render(i, row, current, width).into_iter().rev() {
+                above.push(line);
+            }
+            if above.len() >= anchor {
+                break;
+            }
+        }
+        above.truncate(anchor);
+        frame.extend(std::iter::repeat_n(
+            (String::new(), RowStyle::Normal),
+            anchor - above.len(),
+        ));
+        frame.extend(above.into_iter().rev());
+        for (i, row) in self.rows.iter().enumerate().skip(current) {
+            if frame.len() >= height {
+                break;
+            }
+            frame.extend(self.render(i, row, current, width));
+        }
+        frame.truncate(height);
+        frame.resize(height, (String::new(), RowStyle::Normal));
+        frame
+    }
+
+    fn render(&self, index: usize, row: &Row, current: usize, width: usize) -> Frame {
+        let style = match (row.heading, index == current) {
+            (true, _) => RowStyle::Heading,
+            (false, true) => RowStyle::Current,
+            (false, false) => RowStyle::Normal,
+        };
+        wrap(&row.text, width)
+            .into_iter()
+            .map(|line| (center(&line, width), style))
+            .collect()
+    }
+}
+
+/// Break `text` at spaces into lines of at most `width` characters; longer
+/// words are cut.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines: Vec<String> = vec![String::new()];
+    let chars: Vec<Vec<char>> = text
+        .split_whitespace()
+        .flat_map(|w| {
+            let chars: Vec<char> = w.chars().collect();
+            chars
+                .chunks(width)
+                .map(<[char]>::to_vec)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for word in chars {
+        let len = lines.last().map_or(0, |l| l.chars().count());
+        if len > 0 && len + 1 + word.len() > width {
+            lines.push(String::new());
+        }
+        let line = lines.last_mut().expect("never empty");
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    lines
+}
+
+fn center(line: &str, width: usize) -> String {
+    let pad = width.saturating_sub(line.chars().count()) / 2;
+    format!("{}{}", " ".repeat(pad), line)
+}
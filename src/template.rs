@@ -0,0 +1,412 @@
+//! Templates for the `html` lyric sheet, so a designer can lay a song out
+//! without forking the exporter.
+//!
+//! The syntax is a small Handlebars:
+//!
+//! - `{{title}}` writes a value, HTML-escaped, and `{{{style}}}` writes it
+//!   as it is; `a.b` reaches into objects, `this` is the current item and
+//!   `@index`, `@first` and `@last` describe its place in an `each`.
+//! - `{{#if chords}}…{{else}}…{{/if}}` and `{{#unless …}}` test a value:
+//!   `false`, `null`, `0`, `""`, `[]` and `{}` count as false.
+//! - `{{#each sections}}…{{else}}…{{/each}}` repeats for every item, with
+//!   the item's fields in scope; names it lacks are looked up outside.
+//! - `{{> header}}` includes the partial `header`, the file `header.html`
+//!   beside the template, with the same scope.
+//! - `{{! a comment }}` writes nothing.
+//!
+//! What the sheet exposes to templates is described by
+//! [`crate::export::HtmlExporter`].
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use thiserror::Error;
+
+/// Partials may include partials, but no deeper than this, which also
+/// stops a partial that includes itself.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("no partial `{0}`")]
+    MissingPartial(String),
+    #[error("partials nest more than {MAX_DEPTH} deep")]
+    TooDeep,
+    #[error("{path}: {message}")]
+    Read { path: String, message: String },
+    #[error("partial `{name}`: {error}")]
+    Partial {
+        name: String,
+        error: Box<TemplateError>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value {
+        path: String,
+        escape: bool,
+    },
+    If {
+        path: String,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Partial(String),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// A block being parsed: its tag, the path it tests and its nodes so far,
+/// with those before an `{{else}}` set aside.
+struct Open {
+    tag: String,
+    path: String,
+    line: usize,
+    nodes: Vec<Node>,
+    before_else: Option<Vec<Node>>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut stack = vec![Open {
+            tag: String::new(),
+            path: String::new(),
+            line: 1,
+            nodes: Vec::new(),
+            before_else: None,
+        }];
+        let mut rest = source;
+        let line_of = |rest: &str| source[..source.len() - rest.len()].matches('\n').count() + 1;
+        while let Some(start) = rest.find("{{") {
+            let line = line_of(&rest[start..]);
+            let syntax = |message: String| TemplateError::Syntax { line, message };
+            if start > 0 {
+                let text = rest[..start].to_string();
+                stack.last_mut().expect("root").nodes.push(Node::Text(text));
+            }
+            let raw = rest[start..].starts_with("{{{");
+            let (open, close) = match raw {
+                true => ("{{{", "}}}"),
+                false => ("{{", "}}"),
+            };
+            let inner = &rest[start + open.len()..];
+            let end = inner
+                .find(close)
+                .ok_or_else(|| syntax(format!("`{}` is never closed", open)))?;
+            let tag = inner[..end].trim();
+            rest = &inner[end + close.len()..];
+
+            let nested = stack.len() > 1;
+            let top = stack.last_mut().expect("root");
+            if raw {
+                top.nodes.push(Node::Value {
+                    path: tag.to_string(),
+                    escape: false,
+                });
+                continue;
+            }
+            match tag.chars().next() {
+                Some('!') => {}
+                Some('>') => top.nodes.push(Node::Partial(tag[1..].trim().to_string())),
+                Some('#') => {
+                    let (name, path) = tag[1..].split_once(' ').unwrap_or((&tag[1..], ""));
+                    if !matches!(name, "if" | "unless" | "each") {
+                        return Err(syntax(format!("unknown block `#{}`", name)));
+                    }
+                    if path.trim().is_empty() {
+                        return Err(syntax(format!("`#{}` needs a value to test", name)));
+                    }
+                    stack.push(Open {
+                        tag: name.to_string(),
+                        path: path.trim().to_string(),
+                        line,
+                        nodes: Vec::new(),
+                        before_else: None,
+                    });
+                }
+                Some('/') => {
+                    let name = tag[1..].trim();
+                    if !nested {
+                        return Err(syntax(format!("`/{}` closes nothing", name)));
+                    }
+                    let block = stack.pop().expect("a block is open");
+                    if block.tag != name {
+                        return Err(syntax(format!(
+                            "`/{}` closes the `#{}` opened on line {}",
+                            name, block.tag, block.line
+                        )));
+                    }
+                    let (body, otherwise) = match block.before_else {
+                        Some(before) => (before, block.nodes),
+                        None => (block.nodes, Vec::new()),
+                    };
+                    let node = match name {
+                        "each" => Node::Each {
+                            path: block.path,
+                            body,
+                            otherwise,
+                        },
+                        _ => Node::If {
+                            path: block.path,
+                            negate: name == "unless",
+                            then: body,
+                            otherwise,
+                        },
+                    };
+                    stack.last_mut().expect("root").nodes.push(node);
+                }
+                _ if tag == "else" => {
+                    if !nested || top.before_else.is_some() {
+                        return Err(syntax("`else` outside a block".to_string()));
+                    }
+                    top.before_else = Some(std::mem::take(&mut top.nodes));
+                }
+                _ if tag.is_empty() => return Err(syntax("empty `{{}}`".to_string())),
+                _ => top.nodes.push(Node::Value {
+                    path: tag.to_string(),
+                    escape: true,
+                }),
+            }
+        }
+        if !rest.is_empty() {
+            stack
+                .last_mut()
+                .expect("root")
+                .nodes
+                .push(Node::Text(rest.to_string()));
+        }
+        let root = stack.remove(0);
+        if let Some(open) = stack.first() {
+            return Err(TemplateError::Syntax {
+                line: open.line,
+                message: format!("`#{}` is never closed", open.tag),
+            });
+        }
+        Ok(Template { nodes: root.nodes })
+    }
+
+    /// The names of the partials the template includes.
+    pub fn partials(&self) -> Vec<&str> {
+        fn walk<'a>(nodes: &'a [Node], names: &mut Vec<&'a str>) {
+            for node in nodes {
+                match node {
+                    Node::Partial(name) if !names.contains(&name.as_str()) => names.push(name),
+                    Node::If {
+                        then: a,
+                        otherwise: b,
+                        ..
+                    }
+                    | Node::Each {
+                        body: a,
+                        otherwise: b,
+                        ..
+                    } => {
+                        walk(a, names);
+                        walk(b, names);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut names = Vec::new();
+        walk(&self.nodes, &mut names);
+        names
+    }
+
+    /// The template filled in from `data`, with `partials` by name.
+    pub fn render(
+        &self,
+        data: &Value,
+        partials: &BTreeMap<String, Template>,
+    ) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        let mut scopes = vec![Scope::item(data)];
+        render(&self.nodes, &mut scopes, partials, 0, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// A template with the partials it includes, read from files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub template: Template,
+    pub partials: BTreeMap<String, Template>,
+}
+
+impl Layout {
+    /// Read the template at `path` and every partial it includes, in turn,
+    /// from files named after them beside it with the same extension.
+    pub fn load(path: &Path) -> Result<Layout, TemplateError> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path).map_err(|e| TemplateError::Read {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })
+        };
+        let in_file = |path: &Path, e: TemplateError| match e {
+            TemplateError::Syntax { .. } => TemplateError::Read {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            },
+            e => e,
+        };
+        let template = Template::parse(&read(path)?).map_err(|e| in_file(path, e))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("html");
+        let mut partials = BTreeMap::new();
+        let mut wanted: Vec<String> = template.partials().iter().map(|s| s.to_string()).collect();
+        while let Some(name) = wanted.pop() {
+            if partials.contains_key(&name) {
+                continue;
+            }
+            let file = dir.join(format!("{}.{}", name, extension));
+            let partial = Template::parse(&read(&file)?).map_err(|e| in_file(&file, e))?;
+            wanted.extend(partial.partials().iter().map(|s| s.to_string()));
+            partials.insert(name, partial);
+        }
+        Ok(Layout { template, partials })
+    }
+
+    pub fn render(&self, data: &Value) -> Result<String, TemplateError> {
+        self.template.render(data, &self.partials)
+    }
+}
+
+/// The value names are looked up in, and the item's place in its `each`.
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<(usize, usize)>,
+}
+
+impl<'a> Scope<'a> {
+    fn item(value: &'a Value) -> Scope<'a> {
+        Scope { value, index: None }
+    }
+}
+
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> Option<Value> {
+    if let Some(name) = path.strip_prefix('@') {
+        let (index, count) = scopes.last()?.index?;
+        return match name {
+            "index" => Some(Value::from(index)),
+            "first" => Some(Value::from(index == 0)),
+            "last" => Some(Value::from(index + 1 == count)),
+            _ => None,
+        };
+    }
+    if path == "this" || path == "." {
+        return scopes.last().map(|s| s.value.clone());
+    }
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = scopes.iter().rev().find_map(|s| s.value.get(first))?;
+    for part in parts {
+        value = match value {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            value => value.get(part)?,
+        };
+    }
+    Some(value.clone())
+}
+
+fn truthy(value: &Option<Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(Value::Object(o)) => !o.is_empty(),
+    }
+}
+
+fn render<'a>(
+    nodes: &[Node],
+    scopes: &mut Vec<Scope<'a>>,
+    partials: &BTreeMap<String, Template>,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value { path, escape } => {
+                let text = match lookup(scopes, path) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s,
+                    Some(value) => value.to_string(),
+                };
+                match escape {
+                    true => out.push_str(&crate::export::ttml::escape(&text)),
+                    false => out.push_str(&text),
+                }
+            }
+            Node::If {
+                path,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let chosen = match truthy(&lookup(scopes, path)) != *negate {
+                    true => then,
+                    false => otherwise,
+                };
+                render(chosen, scopes, partials, depth, out)?;
+            }
+            Node::Each {
+                path,
+                body,
+                otherwise,
+            } => {
+                let items: Vec<Value> = match lookup(scopes, path) {
+                    Some(Value::Array(items)) => items,
+                    Some(Value::Object(fields)) => fields.into_iter().map(|(_, v)| v).collect(),
+                    _ => Vec::new(),
+                };
+                if items.is_empty() {
+                    render(otherwise, scopes, partials, depth, out)?;
+                }
+                for (i, item) in items.iter().enumerate() {
+                    // The item lives only for this pass, so it is rendered
+                    // with scopes of its own borrowing it.
+                    let mut inner: Vec<Scope> = scopes
+                        .iter()
+                        .map(|s| Scope {
+                            value: s.value,
+                            index: s.index,
+                        })
+                        .collect();
+                    inner.push(Scope {
+                        value: item,
+                        index: Some((i, items.len())),
+                    });
+                    render(body, &mut inner, partials, depth, out)?;
+                }
+            }
+            Node::Partial(name) => {
+                if depth == MAX_DEPTH {
+                    return Err(TemplateError::TooDeep);
+                }
+                let partial = partials
+                    .get(name)
+                    .ok_or_else(|| TemplateError::MissingPartial(name.clone()))?;
+                render(&partial.nodes, scopes, partials, depth + 1, out)?;
+            }
+        }
+    }
+    Ok(())
+}
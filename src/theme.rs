@@ -0,0 +1,302 @@
+//! Colors of terminal output, by what the text is rather than by color, so
+//! a project can pick a palette that reads well on its terminals.
+//!
+//! A project picks a theme in `.lyricsdsl/config.toml` and may restyle
+//! single roles on top of it:
+//!
+//! ```toml
+//! theme.name = "colorblind"
+//! theme.warning = "bright yellow bold"
+//! theme.chorus = "#009e73"
+//! ```
+//!
+//! A style is a color name as `colored` spells it, such as `red` or
+//! `bright blue`, or `#rrggbb`, with any of `bold`, `dimmed`, `italic` and
+//! `underline`; `plain` leaves the text as it is. `NO_COLOR` still turns
+//! every color off.
+
+use crate::ast::SectionKind;
+use crate::diagnostic::Severity;
+use colored::{Color, ColoredString, Colorize};
+
+/// What a piece of terminal output is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Error,
+    Warning,
+    Info,
+    Success,
+    Heading,
+    Prompt,
+    Hint,
+    Verse,
+    Chorus,
+    Bridge,
+    /// Sections other than verses, choruses and bridges.
+    Section,
+    Lyric,
+}
+
+impl Role {
+    pub const ALL: [Role; 12] = [
+        Role::Error,
+        Role::Warning,
+        Role::Info,
+        Role::Success,
+        Role::Heading,
+        Role::Prompt,
+        Role::Hint,
+        Role::Verse,
+        Role::Chorus,
+        Role::Bridge,
+        Role::Section,
+        Role::Lyric,
+    ];
+
+    /// The role's name in `theme.<role>` settings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Error => "error",
+            Role::Warning => "warning",
+            Role::Info => "info",
+            Role::Success => "success",
+            Role::Heading => "heading",
+            Role::Prompt => "prompt",
+            Role::Hint => "hint",
+            Role::Verse => "verse",
+            Role::Chorus => "chorus",
+            Role::Bridge => "bridge",
+            Role::Section => "section",
+            Role::Lyric => "lyric",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|r| r.name() == name)
+    }
+
+    /// The role of a header for a section of `kind`.
+    pub fn of_section(kind: SectionKind) -> Role {
+        match kind {
+            SectionKind::Verse => Role::Verse,
+            SectionKind::Chorus => Role::Chorus,
+            SectionKind::Bridge => Role::Bridge,
+            _ => Role::Section,
+        }
+    }
+}
+
+impl From<Severity> for Role {
+    fn from(severity: Severity) -> Role {
+        match severity {
+            Severity::Error => Role::Error,
+            Severity::Warning => Role::Warning,
+            Severity::Info => Role::Info,
+        }
+    }
+}
+
+/// How text in one role is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    /// Text as it is.
+    pub const PLAIN: Style = Style {
+        color: None,
+        bold: false,
+        dimmed: false,
+        italic: false,
+        underline: false,
+    };
+
+    const fn color(color: Color) -> Style {
+        Style {
+            color: Some(color),
+            ..Style::PLAIN
+        }
+    }
+
+    const fn hex(rgb: u32) -> Style {
+        Style::color(Color::TrueColor {
+            r: (rgb >> 16) as u8,
+            g: (rgb >> 8) as u8,
+            b: rgb as u8,
+        })
+    }
+
+    const fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    const fn dimmed() -> Style {
+        Style {
+            dimmed: true,
+            ..Style::PLAIN
+        }
+    }
+
+    /// A style as written in `config.toml`, e.g. `bright red bold`.
+    pub fn parse(text: &str) -> Option<Style> {
+        let mut style = Style::default();
+        let mut color: Vec<&str> = Vec::new();
+        for word in text.split([' ', ',']).filter(|w| !w.is_empty()) {
+            match word {
+                "bold" => style.bold = true,
+                "dimmed" => style.dimmed = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "plain" => {}
+                word => color.push(word),
+            }
+        }
+        if !color.is_empty() {
+            let color = color.join(" ");
+            style.color = Some(match color.strip_prefix('#') {
+                Some(hex) if hex.len() == 6 => {
+                    let rgb = u32::from_str_radix(hex, 16).ok()?;
+                    Style::hex(rgb).color?
+                }
+                Some(_) => return None,
+                None => color.parse().ok()?,
+            });
+        }
+        Some(style)
+    }
+
+    pub fn paint(&self, text: &str) -> ColoredString {
+        let mut out = match self.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if self.bold {
+            out = out.bold();
+        }
+        if self.dimmed {
+            out = out.dimmed();
+        }
+        if self.italic {
+            out = out.italic();
+        }
+        if self.underline {
+            out = out.underline();
+        }
+        out
+    }
+}
+
+/// The built-in themes, each giving a style to every role in
+/// [`Role::ALL`] order. `colorblind` uses the Okabe-Ito palette, whose
+/// colors stay apart under the common kinds of color blindness, and
+/// `high-contrast` only bright colors in bold, with nothing dimmed.
+const THEMES: &[(&str, [Style; 12])] = &[
+    (
+        "default",
+        [
+            Style::color(Color::Red),
+            Style::color(Color::Yellow),
+            Style::dimmed(),
+            Style::color(Color::Green),
+            Style::color(Color::BrightCyan).bold(),
+            Style::color(Color::BrightBlue),
+            Style::dimmed(),
+            Style::color(Color::Blue),
+            Style::color(Color::Green),
+            Style::color(Color::Magenta),
+            Style::color(Color::Yellow),
+            Style::color(Color::BrightWhite),
+        ],
+    ),
+    (
+        "high-contrast",
+        [
+            Style::color(Color::BrightRed).bold(),
+            Style::color(Color::BrightYellow).bold(),
+            Style::color(Color::BrightWhite),
+            Style::color(Color::BrightGreen).bold(),
+            Style::color(Color::BrightWhite).bold(),
+            Style::color(Color::BrightWhite).bold(),
+            Style::color(Color::White),
+            Style::color(Color::BrightCyan).bold(),
+            Style::color(Color::BrightGreen).bold(),
+            Style::color(Color::BrightMagenta).bold(),
+            Style::color(Color::BrightYellow).bold(),
+            Style::color(Color::BrightWhite),
+        ],
+    ),
+    (
+        "colorblind",
+        [
+            Style::hex(0xd55e00).bold(),
+            Style::hex(0xe69f00),
+            Style::hex(0x56b4e9),
+            Style::hex(0x009e73),
+            Style::hex(0x0072b2).bold(),
+            Style::hex(0x0072b2),
+            Style::dimmed(),
+            Style::hex(0x0072b2),
+            Style::hex(0xe69f00),
+            Style::hex(0xcc79a7),
+            Style::hex(0xf0e442),
+            Style::PLAIN,
+        ],
+    ),
+];
+
+/// A style for every [`Role`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    styles: [Style; 12],
+}
+
+impl Theme {
+    /// The names of the built-in themes.
+    pub fn names() -> Vec<&'static str> {
+        THEMES.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// The built-in theme `name`.
+    pub fn named(name: &str) -> Option<Theme> {
+        THEMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(name, styles)| Theme {
+                name: name.to_string(),
+                styles: *styles,
+            })
+    }
+
+    fn index(role: Role) -> usize {
+        Role::ALL
+            .iter()
+            .position(|r| *r == role)
+            .expect("every role")
+    }
+
+    pub fn style(&self, role: Role) -> Style {
+        self.styles[Theme::index(role)]
+    }
+
+    /// Restyle `role`, leaving the rest of the theme as it is.
+    pub fn set(&mut self, role: Role, style: Style) {
+        self.styles[Theme::index(role)] = style;
+    }
+
+    /// `text` in the style of `role`.
+    pub fn paint(&self, role: impl Into<Role>, text: &str) -> ColoredString {
+        self.style(role.into()).paint(text)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::named("default").expect("built in")
+    }
+}
@@ -0,0 +1,170 @@
+//! The text being edited, as lines with a cursor.
+
+use crate::history::Snapshot;
+
+/// Lines of text and a cursor at a line and a character within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffer {
+    lines: Vec<String>,
+    /// The cursor's line.
+    pub row: usize,
+    /// The cursor's character within its line.
+    pub col: usize,
+    /// Set by every edit, cleared when the text is saved.
+    pub modified: bool,
+}
+
+impl Default for Buffer {
+    fn default() -> Buffer {
+        Buffer::new("")
+    }
+}
+
+impl Snapshot for Buffer {
+    fn bytes(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| line.capacity() + std::mem::size_of::<String>())
+            .sum()
+    }
+}
+
+impl Buffer {
+    pub fn new(text: &str) -> Buffer {
+        Buffer {
+            lines: text.split('\n').map(str::to_string).collect(),
+            row: 0,
+            col: 0,
+            modified: false,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    /// The byte offset of the cursor in its line.
+    fn byte(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(i, _)| i)
+    }
+
+    /// The byte offset of the cursor in [`Buffer::text`].
+    pub fn offset(&self) -> usize {
+        let before: usize = self.lines[..self.row].iter().map(|l| l.len() + 1).sum();
+        before + self.byte()
+    }
+
+    /// Put the cursor at byte `offset` of [`Buffer::text`], or as near as
+    /// the text allows.
+    pub fn go_to(&mut self, offset: usize) {
+        let mut start = 0;
+        for (row, line) in self.lines.iter().enumerate() {
+            if offset <= start + line.len() || row + 1 == self.lines.len() {
+                self.row = row;
+                let within = offset.saturating_sub(start).min(line.len());
+                self.col = line.char_indices().take_while(|(i, _)| *i < within).count();
+                return;
+            }
+            start += line.len() + 1;
+        }
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte();
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+        self.modified = true;
+    }
+
+    /// Split the line at the cursor, keeping its indentation on the new
+    /// line so harmony lines can follow one another.
+    pub fn newline(&mut self) {
+        let at = self.byte();
+        let rest = self.lines[self.row].split_off(at);
+        let indent: String = self.lines[self.row]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        self.col = indent.chars().count();
+        self.lines.insert(self.row + 1, indent + &rest);
+        self.row += 1;
+        self.modified = true;
+    }
+
+    /// Delete the character before the cursor, joining lines at the start
+    /// of one.
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        } else {
+            return;
+        }
+        self.modified = true;
+    }
+
+    /// Delete the character under the cursor, joining the next line at the
+    /// end of one.
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let at = self.byte();
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        } else {
+            return;
+        }
+        self.modified = true;
+    }
+
+    pub fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Move the cursor `rows` lines down, or up when negative.
+    pub fn vertical(&mut self, rows: isize) {
+        let last = self.lines.len() - 1;
+        self.row = self.row.saturating_add_signed(rows).min(last);
+        self.col = self.col.min(self.line_len(self.row));
+    }
+
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+}
@@ -0,0 +1,173 @@
+//! Syntax highlighting for song source, a line at a time so the editor can
+//! color text that does not parse yet.
+//!
+//! It follows the grammar's surface rather than running it: metadata and
+//! variable lines before the first section, section headers, and in lyric
+//! lines the `{…}` attributes, `$` references, `&` anchors, `!!` notes and
+//! the `|`, `·`, `~` and `\` markup.
+
+use std::ops::Range;
+
+/// What a stretch of source is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    /// A section header keyword, such as `CHORUS[2]` or `REPEAT`.
+    Keyword,
+    /// A metadata key or a `---` fence.
+    Meta,
+    /// A quoted or metadata value.
+    Value,
+    Variable,
+    /// A `{…}` attribute list or pronunciation.
+    Attribute,
+    Anchor,
+    Note,
+    /// The `+` that starts a harmony line.
+    Harmony,
+    Markup,
+}
+
+const KEYWORDS: &[&str] = &[
+    "PRE-CHORUS",
+    "VERSE",
+    "CHORUS",
+    "BRIDGE",
+    "OUTRO",
+    "INTRO",
+    "SECTION",
+    "REPEAT",
+    "MACRO",
+    "USE",
+];
+
+/// The highlighted runs of every line of `source`, by byte range within
+/// the line. Runs cover the line in order; text between them is
+/// [`Kind::Text`].
+pub fn highlight(source: &str) -> Vec<Vec<(Range<usize>, Kind)>> {
+    let mut in_front_matter = false;
+    let mut in_sections = false;
+    let mut out = Vec::new();
+    for (i, line) in source.split('\n').enumerate() {
+        let runs = if line.trim_end() == "---" && (i == 0 || in_front_matter) {
+            in_front_matter = i == 0;
+            vec![(0..line.len(), Kind::Meta)]
+        } else if in_front_matter {
+            key_value(line, ':')
+        } else if let Some(runs) = header(line) {
+            in_sections = true;
+            runs
+        } else if !in_sections && line.starts_with('$') {
+            key_value(line, '=')
+        } else if !in_sections && is_meta(line) {
+            key_value(line, ':')
+        } else {
+            lyric(line)
+        };
+        out.push(runs);
+    }
+    out
+}
+
+fn is_meta(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim_end();
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+    })
+}
+
+/// `key: value` or `$name = "value"`.
+fn key_value(line: &str, separator: char) -> Vec<(Range<usize>, Kind)> {
+    let Some(at) = line.find(separator) else {
+        return vec![(0..line.len(), Kind::Meta)];
+    };
+    let key = match line.starts_with('$') {
+        true => Kind::Variable,
+        false => Kind::Meta,
+    };
+    vec![(0..at, key), (at + 1..line.len(), Kind::Value)]
+}
+
+fn header(line: &str) -> Option<Vec<(Range<usize>, Kind)>> {
+    let keyword = KEYWORDS.iter().find(|k| {
+        line.strip_prefix(**k)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '[', '{', '&']))
+    })?;
+    // `USE name` and `SECTION name` headers run to the attributes or anchor.
+    let end = line
+        .find(['{', '&'])
+        .unwrap_or(line.len())
+        .max(keyword.len());
+    let mut runs = vec![(0..end, Kind::Keyword)];
+    runs.extend(
+        lyric(&line[end..])
+            .into_iter()
+            .filter(|(_, kind)| *kind != Kind::Text)
+            .map(|(range, kind)| (range.start + end..range.end + end, kind)),
+    );
+    Some(runs)
+}
+
+fn lyric(line: &str) -> Vec<(Range<usize>, Kind)> {
+    let mut runs: Vec<(Range<usize>, Kind)> = Vec::new();
+    let mut start = 0;
+    if let Some(plus) = line.trim_start().strip_prefix('+') {
+        if line.starts_with([' ', '\t']) && plus.starts_with([' ', '\t']) {
+            let at = line.len() - plus.len();
+            runs.push((at - 1..at, Kind::Harmony));
+            start = at;
+        }
+    }
+    let bytes = line.as_bytes();
+    let mut i = start;
+    let mut text_from = start;
+    let push = |runs: &mut Vec<(Range<usize>, Kind)>, from: usize, range: Range<usize>, kind| {
+        if from < range.start {
+            runs.push((from..range.start, Kind::Text));
+        }
+        runs.push((range, kind));
+    };
+    while i < line.len() {
+        let rest = &line[i..];
+        let (len, kind) = if rest.starts_with('\\') {
+            (
+                rest.chars().nth(1).map_or(1, |c| 1 + c.len_utf8()),
+                Kind::Markup,
+            )
+        } else if rest.starts_with('{') {
+            (
+                rest.find('}').map_or(rest.len(), |end| end + 1),
+                Kind::Attribute,
+            )
+        } else if let Some(reference) = rest.strip_prefix('$') {
+            let name = reference.trim_start_matches('{');
+            let word = name
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(name.len());
+            let closing = reference.starts_with('{') && name[word..].starts_with('}');
+            (
+                rest.len() - name.len() + word + closing as usize,
+                Kind::Variable,
+            )
+        } else if rest.starts_with('&') && (i == 0 || bytes[i - 1] == b' ') {
+            let word = rest[1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len() - 1);
+            (1 + word, Kind::Anchor)
+        } else if rest.starts_with("!!") && (i == 0 || bytes[i - 1] == b' ') {
+            (rest.len(), Kind::Note)
+        } else if rest.starts_with(['|', '~', '·']) {
+            (rest.chars().next().map_or(1, char::len_utf8), Kind::Markup)
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        push(&mut runs, text_from, i..i + len, kind);
+        i += len;
+        text_from = i;
+    }
+    if text_from < line.len() {
+        runs.push((text_from..line.len(), Kind::Text));
+    }
+    runs
+}
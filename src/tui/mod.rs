@@ -0,0 +1,709 @@
+//! The state behind `lyrics-dsl tui`: the project's songs in a tree, the
+//! song being edited, its diagnostics and a preview of it, laid out as one
+//! screen of styled rows.
+//!
+//! Nothing here touches the terminal or checks songs itself; the command
+//! feeds in key presses and the results of checking, and draws the
+//! [`Frame`]s it gets back.
+
+pub mod buffer;
+pub mod highlight;
+
+pub use buffer::Buffer;
+pub use highlight::{highlight, Kind};
+
+use crate::analysis::analyze;
+use crate::ast::Song;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::export::exporter;
+use crate::history::{History, Recovery, Run};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The formats the export preview cycles through, all of them text.
+pub const PREVIEW_FORMATS: &[&str] = &["txt", "md", "lrc", "elrc", "html", "ttml", "json"];
+
+/// Columns below which the preview takes the editor's place while it has
+/// the focus rather than standing beside it.
+const WIDE: usize = 100;
+/// Columns below which the file tree is left out.
+const NARROW: usize = 60;
+
+/// How a stretch of a [`Frame`] should be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Plain,
+    Border,
+    /// A pane's title row, brighter for the pane with the focus.
+    Title {
+        focused: bool,
+    },
+    Selected,
+    Cursor,
+    Gutter,
+    Dim,
+    Code(Kind),
+    Severity(Severity),
+    Status,
+}
+
+/// One row of the screen: runs of text and how to show each.
+pub type Row = Vec<(String, Paint)>;
+/// A screenful of rows, each exactly as wide as the screen.
+pub type Frame = Vec<Row>;
+
+/// The pane key presses go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Files,
+    Editor,
+    Diagnostics,
+    Preview,
+}
+
+/// What the preview pane shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Analysis,
+    /// The song exported as the [`PREVIEW_FORMATS`] entry at this index.
+    Export(usize),
+}
+
+impl Tab {
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Analysis => "analysis",
+            Tab::Export(i) => PREVIEW_FORMATS[i],
+        }
+    }
+
+    fn all() -> impl Iterator<Item = Tab> {
+        std::iter::once(Tab::Analysis).chain((0..PREVIEW_FORMATS.len()).map(Tab::Export))
+    }
+}
+
+/// A directory or song in the file tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub depth: usize,
+    pub dir: bool,
+}
+
+/// The directories and `.lyr` files under `root`, depth first and sorted,
+/// skipping hidden directories, build output and directories without
+/// songs.
+pub fn tree(root: &Path) -> io::Result<Vec<Entry>> {
+    fn walk(dir: &Path, depth: usize, out: &mut Vec<Entry>) -> io::Result<()> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            if path.is_dir() {
+                if name.starts_with('.') || name == "target" {
+                    continue;
+                }
+                let at = out.len();
+                walk(&path, depth + 1, out)?;
+                if out.len() > at {
+                    let entry = Entry {
+                        path,
+                        depth,
+                        dir: true,
+                    };
+                    out.insert(at, entry);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "lyr") {
+                out.push(Entry {
+                    path,
+                    depth,
+                    dir: false,
+                });
+            }
+        }
+        Ok(())
+    }
+    let mut entries = Vec::new();
+    walk(root, 0, &mut entries)?;
+    Ok(entries)
+}
+
+/// Everything on screen and what the keys act on.
+#[derive(Debug, Clone)]
+pub struct Workbench {
+    pub root: PathBuf,
+    pub files: Vec<Entry>,
+    /// The highlighted entry of `files`.
+    pub selected: usize,
+    /// The song being edited, if one is open.
+    pub path: Option<PathBuf>,
+    pub buffer: Buffer,
+    /// Earlier and undone states of `buffer`.
+    pub history: History<Buffer>,
+    /// Where unsaved edits to the open song are kept through a crash.
+    pub recovery: Option<Recovery>,
+    /// The text of the song as last read or saved.
+    saved: String,
+    pub focus: Focus,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The highlighted entry of `diagnostics`.
+    pub diagnostic: usize,
+    pub tab: Tab,
+    /// A message for the status bar, until the next key press.
+    pub message: Option<String>,
+    /// The text `diagnostics` and the preview were made from.
+    checked: Option<String>,
+    song: Option<Song>,
+    preview: String,
+    preview_scroll: usize,
+    /// The first line and character of the buffer on screen.
+    scroll: (usize, usize),
+}
+
+impl Workbench {
+    pub fn new(root: &Path) -> io::Result<Workbench> {
+        Ok(Workbench {
+            root: root.to_path_buf(),
+            files: tree(root)?,
+            selected: 0,
+            path: None,
+            buffer: Buffer::default(),
+            history: History::default(),
+            recovery: None,
+            saved: String::new(),
+            focus: Focus::Files,
+            diagnostics: Vec::new(),
+            diagnostic: 0,
+            tab: Tab::Analysis,
+            message: None,
+            checked: None,
+            song: None,
+            preview: String::new(),
+            preview_scroll: 0,
+            scroll: (0, 0),
+        })
+    }
+
+    /// Start editing `text`, the contents of `path`.
+    pub fn open(&mut self, path: &Path, text: &str) {
+        self.path = Some(path.to_path_buf());
+        self.buffer = Buffer::new(text);
+        self.history = History::default();
+        self.recovery = Some(Recovery::for_song(path));
+        self.saved = text.to_string();
+        self.diagnostics.clear();
+        self.diagnostic = 0;
+        self.checked = None;
+        self.song = None;
+        self.preview.clear();
+        self.scroll = (0, 0);
+        self.focus = Focus::Editor;
+        if let Some(i) = self.files.iter().position(|e| e.path == path) {
+            self.selected = i;
+        }
+    }
+
+    /// Make a change to the buffer that can be undone, as part of `run`
+    /// when it continues one.
+    pub fn edit(&mut self, run: Option<Run>, change: impl FnOnce(&mut Buffer)) {
+        let before = self.buffer.clone();
+        change(&mut self.buffer);
+        if before.lines() != self.buffer.lines() {
+            self.history.record(before, run);
+            self.buffer.modified = self.buffer.text() != self.saved;
+        }
+    }
+
+    /// Replace the text with `text`, such as edits recovered after a
+    /// crash, as a step that can be undone.
+    pub fn replace(&mut self, text: &str) {
+        self.edit(None, |buffer| *buffer = Buffer::new(text));
+    }
+
+    /// Go back a step; whether there was one.
+    pub fn undo(&mut self) -> bool {
+        match self.history.undo(self.buffer.clone()) {
+            Some(before) => self.restore(before),
+            None => false,
+        }
+    }
+
+    /// Go forward again a step that was undone; whether there was one.
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo(self.buffer.clone()) {
+            Some(after) => self.restore(after),
+            None => false,
+        }
+    }
+
+    fn restore(&mut self, buffer: Buffer) -> bool {
+        self.buffer = buffer;
+        self.buffer.modified = self.buffer.text() != self.saved;
+        true
+    }
+
+    /// Note that the buffer's text is now what the song holds.
+    pub fn saved(&mut self) {
+        self.saved = self.buffer.text();
+        self.buffer.modified = false;
+        self.history.seal();
+    }
+
+    /// The entry of the file tree under the highlight.
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        self.files.get(self.selected)
+    }
+
+    /// Whether the buffer has changed since it was last checked.
+    pub fn stale(&self) -> bool {
+        self.path.is_some() && self.checked.as_deref() != Some(&self.buffer.text())
+    }
+
+    /// Take in the results of checking `text`: its diagnostics, and the
+    /// resolved song when it parsed, which the preview is made from. A
+    /// song that does not parse keeps the last preview.
+    pub fn checked(&mut self, text: String, diagnostics: Vec<Diagnostic>, song: Option<Song>) {
+        self.checked = Some(text);
+        self.diagnostics = diagnostics;
+        self.diagnostic = self
+            .diagnostic
+            .min(self.diagnostics.len().saturating_sub(1));
+        if song.is_some() {
+            self.song = song;
+            self.refresh_preview();
+        }
+    }
+
+    fn refresh_preview(&mut self) {
+        self.preview = match &self.song {
+            Some(song) => preview(song, self.tab),
+            None => String::new(),
+        };
+    }
+
+    pub fn next_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Files => Focus::Editor,
+            Focus::Editor => Focus::Diagnostics,
+            Focus::Diagnostics => Focus::Preview,
+            Focus::Preview => Focus::Files,
+        };
+    }
+
+    /// Show the next preview tab, or the previous one when `step` is
+    /// negative.
+    pub fn switch_tab(&mut self, step: isize) {
+        let tabs: Vec<Tab> = Tab::all().collect();
+        let at = tabs.iter().position(|t| *t == self.tab).unwrap_or(0);
+        let count = tabs.len() as isize;
+        self.tab = tabs[(at as isize + step).rem_euclid(count) as usize];
+        self.preview_scroll = 0;
+        self.refresh_preview();
+    }
+
+    /// Move the highlight in the file tree or the diagnostics, or scroll
+    /// the preview, whichever has the focus, by `step` rows.
+    pub fn move_by(&mut self, step: isize) {
+        let moved =
+            |at: usize, len: usize| at.saturating_add_signed(step).min(len.saturating_sub(1));
+        match self.focus {
+            Focus::Files => self.selected = moved(self.selected, self.files.len()),
+            Focus::Diagnostics => self.diagnostic = moved(self.diagnostic, self.diagnostics.len()),
+            Focus::Preview => {
+                let lines = self.preview.lines().count();
+                self.preview_scroll = moved(self.preview_scroll, lines);
+            }
+            Focus::Editor => self.buffer.vertical(step),
+        }
+    }
+
+    /// Put the cursor where the highlighted diagnostic points.
+    pub fn jump_to_diagnostic(&mut self) {
+        if let Some(diagnostic) = self.diagnostics.get(self.diagnostic) {
+            self.buffer.go_to(diagnostic.span.start);
+            self.focus = Focus::Editor;
+        }
+    }
+
+    /// The number of diagnostics of `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+
+    /// The screen, `width` columns by `height` rows.
+    pub fn frame(&mut self, width: usize, height: usize) -> Frame {
+        let body = height.saturating_sub(1);
+        let files_width = match width >= NARROW {
+            true => (width / 5).clamp(16, 28),
+            false => 0,
+        };
+        let rest = width.saturating_sub(files_width + (files_width > 0) as usize);
+        let (middle_width, preview_width) = match width >= WIDE {
+            true => {
+                let preview = rest * 2 / 5;
+                (rest - preview - 1, preview)
+            }
+            false => (rest, 0),
+        };
+
+        let mut columns: Vec<Vec<Row>> = Vec::new();
+        if files_width > 0 {
+            columns.push(self.files_pane(files_width, body));
+        }
+        if preview_width == 0 && self.focus == Focus::Preview {
+            columns.push(self.preview_pane(middle_width, body));
+        } else {
+            columns.push(self.middle_pane(middle_width, body));
+        }
+        if preview_width > 0 {
+            columns.push(self.preview_pane(preview_width, body));
+        }
+
+        let mut frame: Frame = (0..body)
+            .map(|r| {
+                let mut row = Row::new();
+                for (i, column) in columns.iter().enumerate() {
+                    if i > 0 {
+                        row.push(("│".to_string(), Paint::Border));
+                    }
+                    row.extend(column[r].iter().cloned());
+                }
+                row
+            })
+            .collect();
+        frame.push(fit(
+            vec![(self.status(), Paint::Status)],
+            width,
+            Paint::Status,
+        ));
+        frame
+    }
+
+    fn status(&self) -> String {
+        let name = match &self.path {
+            Some(path) => path
+                .strip_prefix(&self.root)
+                .unwrap_or(path)
+                .display()
+                .to_string(),
+            None => "no song open".to_string(),
+        };
+        let modified = match self.buffer.modified {
+            true => " ●",
+            false => "",
+        };
+        let mut status = format!(
+            " {}{} · {} error(s), {} warning(s)",
+            name,
+            modified,
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        );
+        match &self.message {
+            Some(message) => status.push_str(&format!(" · {}", message)),
+            None => status.push_str(" · Tab next pane · ^S save · ^Z undo · ^Q quit"),
+        }
+        status
+    }
+
+    fn files_pane(&self, width: usize, height: usize) -> Vec<Row> {
+        let focused = self.focus == Focus::Files;
+        let top = scroll_to(self.selected, 0, height.saturating_sub(1));
+        let mut rows = vec![title(" songs", focused, width)];
+        for (i, entry) in self.files.iter().enumerate().skip(top) {
+            let name = entry
+                .path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            let text = format!(
+                "{}{}{}",
+                "  ".repeat(entry.depth),
+                name,
+                if entry.dir { "/" } else { "" }
+            );
+            let paint = match (i == self.selected && focused, entry.dir) {
+                (true, _) => Paint::Selected,
+                (false, true) => Paint::Dim,
+                (false, false) if self.path.as_ref() == Some(&entry.path) => {
+                    Paint::Title { focused: false }
+                }
+                (false, false) => Paint::Plain,
+            };
+            rows.push(fit(vec![(text, paint)], width, paint));
+        }
+        pad(rows, width, height)
+    }
+
+    /// The editor above the diagnostics.
+    fn middle_pane(&mut self, width: usize, height: usize) -> Vec<Row> {
+        let listed = self.diagnostics.len().max(1) + 1;
+        let diagnostics_height = listed.clamp(2, (height / 3).max(2));
+        let editor_height = height.saturating_sub(diagnostics_height);
+        let mut rows = self.editor_pane(width, editor_height);
+        rows.extend(self.diagnostics_pane(width, diagnostics_height));
+        rows
+    }
+
+    fn editor_pane(&mut self, width: usize, height: usize) -> Vec<Row> {
+        let focused = self.focus == Focus::Editor;
+        let name = match &self.path {
+            Some(path) => path
+                .file_name()
+                .map_or_else(String::new, |n| format!(" {}", n.to_string_lossy())),
+            None => " no song open — pick one from the tree".to_string(),
+        };
+        let mut rows = vec![title(&name, focused, width)];
+        if self.path.is_none() || height < 2 {
+            return pad(rows, width, height);
+        }
+
+        const GUTTER: usize = 6;
+        let text_width = width.saturating_sub(GUTTER).max(1);
+        let visible = height - 1;
+        let (row, col) = (self.buffer.row, self.buffer.col);
+        self.scroll.0 = scroll_to(row, self.scroll.0, visible);
+        self.scroll.1 = scroll_to(col, self.scroll.1, text_width);
+        let marks = self.line_marks();
+        let text = self.buffer.text();
+        let highlighted = highlight(&text);
+        for (i, line) in self
+            .buffer
+            .lines()
+            .iter()
+            .enumerate()
+            .skip(self.scroll.0)
+            .take(visible)
+        {
+            let mark = match marks.get(&(i + 1)) {
+                Some(severity) => ("● ".to_string(), Paint::Severity(*severity)),
+                None => ("  ".to_string(), Paint::Gutter),
+            };
+            let mut paints: Vec<(char, Paint)> = line
+                .char_indices()
+                .map(|(at, c)| {
+                    let kind = highlighted[i]
+                        .iter()
+                        .find(|(range, _)| range.contains(&at))
+                        .map_or(Kind::Text, |(_, kind)| *kind);
+                    (c, Paint::Code(kind))
+                })
+                .collect();
+            if focused && i == row {
+                if col == paints.len() {
+                    paints.push((' ', Paint::Cursor));
+                } else {
+                    paints[col].1 = Paint::Cursor;
+                }
+            }
+            let mut out = vec![(format!("{:>4}", i + 1), Paint::Gutter), mark];
+            out.extend(runs(
+                paints.into_iter().skip(self.scroll.1).take(text_width),
+            ));
+            rows.push(fit(out, width, Paint::Plain));
+        }
+        pad(rows, width, height)
+    }
+
+    /// The worst severity diagnosed on each one-based line.
+    fn line_marks(&self) -> std::collections::HashMap<usize, Severity> {
+        let mut marks = std::collections::HashMap::new();
+        let Some(text) = &self.checked else {
+            return marks;
+        };
+        let rank = |s: Severity| match s {
+            Severity::Error => 2,
+            Severity::Warning => 1,
+            Severity::Info => 0,
+        };
+        for diagnostic in &self.diagnostics {
+            let (line, _) = diagnostic.span.line_col(text);
+            let mark = marks.entry(line).or_insert(diagnostic.severity);
+            if rank(diagnostic.severity) > rank(*mark) {
+                *mark = diagnostic.severity;
+            }
+        }
+        marks
+    }
+
+    fn diagnostics_pane(&self, width: usize, height: usize) -> Vec<Row> {
+        let focused = self.focus == Focus::Diagnostics;
+        let heading = format!(" diagnostics ({})", self.diagnostics.len());
+        let mut rows = vec![title(&heading, focused, width)];
+        let text = self.checked.as_deref().unwrap_or_default();
+        let top = scroll_to(self.diagnostic, 0, height.saturating_sub(1));
+        if self.diagnostics.is_empty() && self.checked.is_some() {
+            rows.push(fit(
+                vec![(" no problems".to_string(), Paint::Dim)],
+                width,
+                Paint::Plain,
+            ));
+        }
+        for (i, diagnostic) in self.diagnostics.iter().enumerate().skip(top) {
+            let (line, col) = diagnostic.span.line_col(text);
+            let code = diagnostic
+                .code
+                .map_or(String::new(), |c| format!("[{}] ", c.id));
+            let place = format!(" {:>4}:{:<3} ", line, col);
+            let message = format!("{}{}", code, diagnostic.message);
+            let row = match i == self.diagnostic && focused {
+                true => vec![(format!("{}{}", place, message), Paint::Selected)],
+                false => vec![
+                    (place, Paint::Gutter),
+                    (message, Paint::Severity(diagnostic.severity)),
+                ],
+            };
+            let fill = match i == self.diagnostic && focused {
+                true => Paint::Selected,
+                false => Paint::Plain,
+            };
+            rows.push(fit(row, width, fill));
+        }
+        pad(rows, width, height)
+    }
+
+    fn preview_pane(&self, width: usize, height: usize) -> Vec<Row> {
+        let focused = self.focus == Focus::Preview;
+        // Leading tabs give way when the row is too narrow to reach the
+        // selected one.
+        let all: Vec<Tab> = Tab::all().collect();
+        let at = all.iter().position(|t| *t == self.tab).unwrap_or(0);
+        let span =
+            |from: usize| -> usize { all[from..=at].iter().map(|t| t.title().len() + 3).sum() };
+        let first = (0..at).find(|&from| span(from) < width).unwrap_or(at);
+        let mut tabs = Row::new();
+        let lead = match first {
+            0 => " ",
+            _ => "…",
+        };
+        tabs.push((lead.to_string(), Paint::Title { focused }));
+        for (i, tab) in all.into_iter().enumerate().skip(first) {
+            if i > first {
+                tabs.push((" · ".to_string(), Paint::Title { focused }));
+            }
+            let paint = match tab == self.tab {
+                true => Paint::Selected,
+                false => Paint::Title { focused },
+            };
+            tabs.push((tab.title().to_string(), paint));
+        }
+        let mut rows = vec![fit(tabs, width, Paint::Title { focused })];
+        if self.song.is_none() {
+            let text = match self.path {
+                Some(_) => " the song has not parsed yet",
+                None => "",
+            };
+            rows.push(fit(
+                vec![(text.to_string(), Paint::Dim)],
+                width,
+                Paint::Plain,
+            ));
+        }
+        for line in self.preview.lines().skip(self.preview_scroll) {
+            let line: String = line
+                .chars()
+                .map(|c| if c == '\t' { ' ' } else { c })
+                .collect();
+            rows.push(fit(
+                vec![(format!(" {}", line), Paint::Plain)],
+                width,
+                Paint::Plain,
+            ));
+        }
+        pad(rows, width, height)
+    }
+}
+
+/// What the preview pane shows of `song` on `tab`.
+pub fn preview(song: &Song, tab: Tab) -> String {
+    match tab {
+        Tab::Analysis => {
+            let report = analyze(song);
+            let mut out = String::new();
+            for section in &report.sections {
+                out.push_str(&format!(
+                    "[{}] singability {}\n",
+                    section.label, section.singability
+                ));
+                for line in &section.lines {
+                    out.push_str(&format!(
+                        "  {:>2} {:>3}  {}\n",
+                        line.score.syllables, line.score.score, line.text
+                    ));
+                }
+            }
+            out.push_str(&format!("Singability: {}\n", report.singability));
+            out
+        }
+        Tab::Export(i) => {
+            let rendered = exporter(PREVIEW_FORMATS[i]).and_then(|e| e.export(song));
+            match rendered {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => format!("cannot export: {}", e),
+            }
+        }
+    }
+}
+
+/// The first row to show so that `at` stays within `visible` rows of a
+/// view now starting at `top`.
+fn scroll_to(at: usize, top: usize, visible: usize) -> usize {
+    let visible = visible.max(1);
+    if at < top {
+        at
+    } else if at >= top + visible {
+        at + 1 - visible
+    } else {
+        top
+    }
+}
+
+fn title(text: &str, focused: bool, width: usize) -> Row {
+    let paint = Paint::Title { focused };
+    fit(vec![(text.to_string(), paint)], width, paint)
+}
+
+/// Consecutive characters with the same paint as runs.
+fn runs(chars: impl Iterator<Item = (char, Paint)>) -> Row {
+    let mut row: Row = Vec::new();
+    for (c, paint) in chars {
+        match row.last_mut() {
+            Some((text, last)) if *last == paint => text.push(c),
+            _ => row.push((c.to_string(), paint)),
+        }
+    }
+    row
+}
+
+/// `row` cut or padded with `fill` to exactly `width` characters.
+fn fit(row: Row, width: usize, fill: Paint) -> Row {
+    let mut out = Row::new();
+    let mut left = width;
+    for (text, paint) in row {
+        if left == 0 {
+            break;
+        }
+        let text: String = text.chars().take(left).collect();
+        left -= text.chars().count();
+        out.push((text, paint));
+    }
+    if left > 0 {
+        out.push((" ".repeat(left), fill));
+    }
+    out
+}
+
+/// `rows` cut or padded with blank rows to exactly `height`.
+fn pad(mut rows: Vec<Row>, width: usize, height: usize) -> Vec<Row> {
+    rows.truncate(height);
+    while rows.len() < height {
+        rows.push(fit(Row::new(), width, Paint::Plain));
+    }
+    rows
+}
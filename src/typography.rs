@@ -0,0 +1,100 @@
+//! Typographic punctuation for display: curly quotes and apostrophes,
+//! dashes and ellipses in place of the straight characters typed in a song.
+//!
+//! `---` becomes an em dash, `--` an en dash and `...` an ellipsis. A quote
+//! mark opens at the start of the text or after a space, bracket or dash,
+//! and closes anywhere else; a `'` inside or at the end of a word is an
+//! apostrophe. A `'` before a word opens a quote unless it elides the start
+//! of a common word, as in `'cause`, `'til` or `'90s`.
+//!
+//! Only the `html` and `pdf` sheets use it, and only when asked; songs are
+//! never rewritten.
+
+use crate::ast::{Line, Song};
+
+/// Metadata shown on a lyric sheet.
+const SHOWN: &[&str] = &["title", "artist", "writers"];
+
+/// Words often written with their first letters left out.
+const ELISIONS: &[&str] = &[
+    "bout", "cause", "cept", "cos", "coz", "cuz", "em", "fore", "kay", "n", "neath", "nother",
+    "nuff", "round", "s", "sup", "til", "tis", "twas", "ya", "yall",
+];
+
+/// `text` with typographic quotes, dashes and ellipses.
+pub fn smarten(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let before = out.chars().last();
+        let opens = before.is_none_or(|b| b.is_whitespace() || "([{–—".contains(b));
+        match c {
+            '-' if chars[i..].starts_with(&['-', '-', '-']) => {
+                out.push('—');
+                i += 3;
+                continue;
+            }
+            '-' if chars[i..].starts_with(&['-', '-']) => {
+                out.push('–');
+                i += 2;
+                continue;
+            }
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                out.push('…');
+                i += 3;
+                continue;
+            }
+            '"' => out.push(if opens { '“' } else { '”' }),
+            '\'' if opens && !elides(&chars[i + 1..]) => out.push('‘'),
+            '\'' => out.push('’'),
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Whether a `'` followed by `rest` stands for missing letters.
+fn elides(rest: &[char]) -> bool {
+    let word: String = rest
+        .iter()
+        .take_while(|c| c.is_alphanumeric() || **c == '\'')
+        .collect();
+    let word = word.to_lowercase();
+    word.starts_with(|c: char| c.is_ascii_digit())
+        || ELISIONS.contains(&word.trim_end_matches('\''))
+}
+
+/// A line with typographic punctuation, its furigana moved to match.
+pub fn smarten_line(line: &Line) -> Line {
+    let mut line = line.clone();
+    for ruby in &mut line.rubies {
+        ruby.at = smarten(&line.text[..ruby.at]).len();
+    }
+    line.text = smarten(&line.text);
+    for translation in &mut line.translations {
+        translation.text = smarten(&translation.text);
+    }
+    line
+}
+
+/// `song` with typographic punctuation in its lines and the metadata that
+/// is shown, such as the title and artist.
+pub fn smarten_song(song: &Song) -> Song {
+    let mut song = song.clone();
+    let shown = song
+        .metadata
+        .iter_mut()
+        .filter(|m| SHOWN.contains(&m.key.as_str()));
+    for entry in shown {
+        for text in entry.value.texts_mut() {
+            *text = smarten(text);
+        }
+    }
+    for section in &mut song.sections {
+        section.lines = section.lines.iter().map(smarten_line).collect();
+    }
+    song
+}
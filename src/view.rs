@@ -0,0 +1,171 @@
+//! A song laid out for reading in a terminal: the title, a heading per
+//! section, and each line with its chords set above the words they fall on.
+//!
+//! A line's chords have no positions of their own. When the line has as
+//! many `|` bar markers as chords, each chord sits over the first word of
+//! its bar; otherwise the chords are spread evenly over the words.
+
+use crate::ast::{word_ranges, Line, SectionKind, Song};
+
+/// What a row of the view is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    Title,
+    /// A section heading, by the kind of section.
+    Heading(SectionKind),
+    Chords,
+    Lyric,
+    /// A harmony part under its lead line.
+    Harmony,
+    Blank,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub kind: RowKind,
+    /// 1-based source line of a lyric or harmony row.
+    pub line: Option<usize>,
+    pub text: String,
+}
+
+impl Row {
+    fn new(kind: RowKind, text: impl Into<String>) -> Row {
+        Row {
+            kind,
+            line: None,
+            text: text.into(),
+        }
+    }
+}
+
+/// The word each of `line`'s chords falls on; the word count for a chord
+/// after the last word.
+pub fn chord_words(line: &Line) -> Vec<usize> {
+    let count = word_ranges(&line.text).len();
+    if line.bars.len() == line.chords.len() {
+        return line.bars.iter().map(|&b| b.min(count)).collect();
+    }
+    let chords = line.chords.len();
+    (0..chords).map(|i| i * count / chords).collect()
+}
+
+/// The chord row over the words of `line` from `words`, laid out over the
+/// text from byte `start`; each chord is kept a space clear of the last.
+fn chord_row(
+    line: &Line,
+    anchors: &[usize],
+    words: std::ops::Range<usize>,
+    start: usize,
+) -> String {
+    let ranges = word_ranges(&line.text);
+    let mut row = String::new();
+    let mut width = 0;
+    for (chord, &word) in line.chords.iter().zip(anchors) {
+        let last = words.end == ranges.len();
+        if !(words.contains(&word) || (last && word == ranges.len())) {
+            continue;
+        }
+        let column = match ranges.get(word) {
+            Some(range) => line.text[start..range.start].chars().count(),
+            None => line.text[start..].trim_end().chars().count() + 1,
+        };
+        let column = match width {
+            0 => column,
+            _ => column.max(width + 1),
+        };
+        row.push_str(&" ".repeat(column - width));
+        row.push_str(chord);
+        width = column + chord.chars().count();
+    }
+    row
+}
+
+/// The rows of `line` and its harmony parts, parsed from `source`, cut at
+/// its soft breaks.
+fn line_rows(line: &Line, source: &str, rows: &mut Vec<Row>) {
+    let number = line.span.line_col(source).0;
+    let ranges = word_ranges(&line.text);
+    let anchors = chord_words(line);
+    let mut cuts: Vec<usize> = line
+        .breaks
+        .iter()
+        .copied()
+        .filter(|&w| w > 0 && w < ranges.len())
+        .collect();
+    cuts.dedup();
+    let mut bounds = vec![0];
+    bounds.extend(cuts);
+    bounds.push(ranges.len());
+    for (k, words) in bounds.windows(2).enumerate() {
+        let start = match words[0] {
+            0 => 0,
+            w => ranges[w].start,
+        };
+        let end = match ranges.get(words[1]) {
+            Some(range) if words[1] > words[0] => range.start,
+            _ => line.text.len(),
+        };
+        let chords = chord_row(line, &anchors, words[0]..words[1], start);
+        if !chords.is_empty() {
+            rows.push(Row::new(RowKind::Chords, chords));
+        }
+        rows.push(Row {
+            kind: RowKind::Lyric,
+            line: Some(number + k),
+            text: line.text[start..end].trim_end().to_string(),
+        });
+    }
+    for part in &line.harmony {
+        rows.push(Row {
+            kind: RowKind::Harmony,
+            line: Some(part.span.line_col(source).0),
+            text: format!("  {}", part.text),
+        });
+    }
+}
+
+/// The rows of the resolved `song`, parsed from `source`.
+pub fn rows(song: &Song, source: &str) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let title = match (song.title(), song.artist()) {
+        (Some(title), Some(artist)) => Some(format!("{} - {}", title, artist)),
+        (title, _) => title,
+    };
+    if let Some(title) = title {
+        rows.push(Row::new(RowKind::Title, title));
+        rows.push(Row::new(RowKind::Blank, ""));
+    }
+    for (i, section) in song.sections.iter().enumerate() {
+        if i > 0 {
+            rows.push(Row::new(RowKind::Blank, ""));
+        }
+        rows.push(Row::new(
+            RowKind::Heading(section.kind),
+            format!("[{}]", section.label()),
+        ));
+        for line in &section.lines {
+            if line.stanza_break {
+                rows.push(Row::new(RowKind::Blank, ""));
+            }
+            line_rows(line, source, &mut rows);
+        }
+    }
+    rows
+}
+
+/// `rows` as text, each painted by `paint`, with source line numbers in a
+/// gutter when `numbers` is set.
+pub fn render(rows: &[Row], numbers: bool, paint: impl Fn(RowKind, &str) -> String) -> String {
+    let mut out = String::new();
+    for row in rows {
+        match (numbers, row.line) {
+            (false, _) => {}
+            (true, Some(line)) => out.push_str(&format!("{:>4}  ", line)),
+            (true, None) if row.kind == RowKind::Blank => {}
+            (true, None) => out.push_str("      "),
+        }
+        out.push_str(&paint(row.kind, &row.text));
+        out.push('\n');
+    }
+    out
+}
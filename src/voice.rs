@@ -0,0 +1,173 @@
+//! Vocal range: the lowest and highest notes of a song's melody, and whether
+//! a singer's range holds them.
+//!
+//! Notes come from `{pitch: E4, B4}` annotations, which only need to mark
+//! the lines that reach the extremes, and from a `range: "A2-E4"` entry
+//! declaring the melody's range. Both use scientific pitch notation, where
+//! `C4` is middle C.
+
+use crate::ast::Song;
+use crate::chords::nashville::Key;
+use crate::chords::Note;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum VoiceError {
+    #[error("`{0}` is not a range of notes such as `A2-E4`")]
+    InvalidRange(String),
+    #[error("the song has no `pitch` annotations or `range` to take its notes from")]
+    NoNotes,
+}
+
+/// A sung note as a MIDI note number: 60 is `C4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pitch(pub i32);
+
+impl Pitch {
+    /// Parse a note such as `C4`, `F#3` or `Bb2`.
+    pub fn parse(text: &str) -> Option<Pitch> {
+        let text = text.trim();
+        let (note, len) = Note::parse_prefix(text)?;
+        let octave: i32 = text[len..].parse().ok()?;
+        // `B#3` is `C4` and `Cb4` is `B3`: the octave goes with the letter.
+        let natural = Note::parse(&text[..1])?.0 as i32;
+        let accidental = match (note.0 as i32 - natural).rem_euclid(12) {
+            11 => -1,
+            n => n,
+        };
+        Some(Pitch((octave + 1) * 12 + natural + accidental))
+    }
+
+    pub fn transpose(self, semitones: i32) -> Pitch {
+        Pitch(self.0 + semitones)
+    }
+
+    /// The note's name, spelled with flats or sharps, e.g. `Bb3`.
+    pub fn name(self, flats: bool) -> String {
+        let note = Note(self.0.rem_euclid(12) as u8);
+        format!("{}{}", note.name(flats), self.0.div_euclid(12) - 1)
+    }
+}
+
+/// The notes from `low` to `high`, such as a singer's range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VocalRange {
+    pub low: Pitch,
+    pub high: Pitch,
+}
+
+impl VocalRange {
+    /// Parse `A2-E4`, with the notes in either order.
+    pub fn parse(text: &str) -> Result<VocalRange, VoiceError> {
+        let invalid = || VoiceError::InvalidRange(text.to_string());
+        let (a, b) = text.split_once(['-', '–']).ok_or_else(invalid)?;
+        let (a, b) = (
+            Pitch::parse(a).ok_or_else(invalid)?,
+            Pitch::parse(b).ok_or_else(invalid)?,
+        );
+        Ok(VocalRange {
+            low: a.min(b),
+            high: a.max(b),
+        })
+    }
+
+    pub fn semitones(&self) -> i32 {
+        self.high.0 - self.low.0
+    }
+
+    pub fn transpose(self, semitones: i32) -> VocalRange {
+        VocalRange {
+            low: self.low.transpose(semitones),
+            high: self.high.transpose(semitones),
+        }
+    }
+
+    pub fn contains(&self, other: &VocalRange) -> bool {
+        self.low <= other.low && other.high <= self.high
+    }
+
+    /// The smallest and largest shift in semitones that puts `self`
+    /// inside `singer`, if any does.
+    pub fn shifts_within(&self, singer: &VocalRange) -> Option<(i32, i32)> {
+        let (down, up) = (singer.low.0 - self.low.0, singer.high.0 - self.high.0);
+        (down <= up).then_some((down, up))
+    }
+
+    pub fn name(&self, flats: bool) -> String {
+        format!("{}-{}", self.low.name(flats), self.high.name(flats))
+    }
+}
+
+impl fmt::Display for VocalRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name(false))
+    }
+}
+
+/// Where a song's lowest or highest note is sung: a section label, or
+/// `None` when it comes from the `range` metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reach {
+    pub pitch: Pitch,
+    pub section: Option<String>,
+}
+
+/// The notes a resolved song's melody reaches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SongRange {
+    pub lowest: Reach,
+    pub highest: Reach,
+    /// Whether notes are named with flats, by the song's key.
+    pub flats: bool,
+}
+
+impl SongRange {
+    /// The lowest and highest of the song's `pitch` annotations and its
+    /// `range` metadata.
+    pub fn of(song: &Song) -> Result<SongRange, VoiceError> {
+        let mut reaches: Vec<Reach> = Vec::new();
+        if let Some(range) = song.meta_str("range") {
+            let range = VocalRange::parse(&range)?;
+            for pitch in [range.low, range.high] {
+                reaches.push(Reach {
+                    pitch,
+                    section: None,
+                });
+            }
+        }
+        for (section, line) in song.lines() {
+            for pitch in line.pitches.iter().filter_map(|p| Pitch::parse(p)) {
+                reaches.push(Reach {
+                    pitch,
+                    section: Some(section.label()),
+                });
+            }
+        }
+        // The first line reaching a note is named, over the metadata.
+        let lowest = reaches
+            .iter()
+            .min_by_key(|r| (r.pitch, r.section.is_none()))
+            .ok_or(VoiceError::NoNotes)?;
+        let highest = reaches
+            .iter()
+            .rev()
+            .max_by_key(|r| (r.pitch, r.section.is_some()))
+            .ok_or(VoiceError::NoNotes)?;
+        Ok(SongRange {
+            lowest: lowest.clone(),
+            highest: highest.clone(),
+            flats: song
+                .meta_str("key")
+                .and_then(|k| Key::parse(&k))
+                .is_some_and(|k| k.flats),
+        })
+    }
+
+    pub fn range(&self) -> VocalRange {
+        VocalRange {
+            low: self.lowest.pitch,
+            high: self.highest.pitch,
+        }
+    }
+}
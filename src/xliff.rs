@@ -0,0 +1,267 @@
+//! XLIFF for localization vendors: [`export`] writes a song's lines as
+//! translation units, and [`import`] reads the translated file back into
+//! the lines' `{translation.<tag>: "..."}` variants.
+//!
+//! Each unit is named after the line's stable ID (see [`crate::ids`]), so
+//! the translations find their lines again however the song is edited in
+//! the meantime. Lines without an ID are numbered `line-1`, `line-2`, ...
+//! in song order, which only holds while no line is added or removed.
+//!
+//! Export writes XLIFF 1.2, which every CAT tool reads; import takes the
+//! `<trans-unit>`s of 1.2 and the `<unit>`s of 2.0. Sources are the lines
+//! as they read, without the backslashes of `\&`-style escapes; targets are
+//! filed as written, as translations are quoted strings and have none.
+
+use crate::ast::{Line, Song, Translation, ESCAPABLE};
+use crate::export::ttml::escape;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum XliffError {
+    #[error("no target language; pass one or set target-language in the file")]
+    NoTargetLanguage,
+    #[error("`{0}` is not a language tag such as `fr` or `pt-BR`")]
+    BadLanguage(String),
+    #[error("no translation units in the file")]
+    NoUnits,
+}
+
+/// What [`import`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Imported {
+    /// The language the translations were filed under.
+    pub lang: String,
+    /// How many lines got a translation.
+    pub translated: usize,
+    /// Units naming no line of the song.
+    pub unknown: Vec<String>,
+}
+
+static LANG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z]+(-[A-Za-z0-9]+)*$").expect("valid regex"));
+static UNIT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?s)<(trans-unit|unit)\b[^>]*?\bid="([^"]*)"[^>]*>(.*?)</(?:trans-unit|unit)>"#)
+        .expect("valid regex")
+});
+static TARGET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<target\b([^>]*)>(.*?)</target>").expect("valid regex"));
+static TARGET_LANGUAGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(?:file|xliff)\b[^>]*?\b(?:target-language|trgLang)="([^"]*)""#)
+        .expect("valid regex")
+});
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").expect("valid regex"));
+static ENTITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#x[0-9A-Fa-f]+|#[0-9]+|[a-z]+);").expect("valid regex"));
+
+/// Whether `line` is translated: `USE`s and empty lines are not.
+fn translatable(line: &Line) -> bool {
+    line.include.is_none() && !line.text.trim().is_empty()
+}
+
+/// The translated lines of `lines` and their harmony parts, in order.
+fn collect<'a>(lines: &'a [Line], out: &mut Vec<&'a Line>) {
+    for line in lines {
+        if translatable(line) {
+            out.push(line);
+        }
+        collect(&line.harmony, out);
+    }
+}
+
+/// Call `f` on every translated line of `lines`, in [`collect`]'s order.
+fn visit(lines: &mut [Line], f: &mut impl FnMut(&mut Line)) {
+    for line in lines {
+        if translatable(line) {
+            f(line);
+        }
+        visit(&mut line.harmony, f);
+    }
+}
+
+/// The lines that are translated, in song order: every lyric line and
+/// harmony part of the sections, then the macros'.
+fn lines(song: &Song) -> Vec<&Line> {
+    let mut out = Vec::new();
+    song.sections
+        .iter()
+        .for_each(|s| collect(&s.lines, &mut out));
+    song.macros.iter().for_each(|m| collect(&m.lines, &mut out));
+    out
+}
+
+/// `text` as it reads, with the backslash of each markup escape dropped.
+fn unescaped(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && ESCAPABLE.contains(&next) => {
+                out.push(next);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The unit ID of the `index`th translated line.
+fn unit_id(line: &Line, index: usize) -> String {
+    line.id
+        .clone()
+        .unwrap_or_else(|| format!("line-{}", index + 1))
+}
+
+/// How many translated lines have no stable ID, and so get a numbered unit.
+pub fn unnamed(song: &Song) -> usize {
+    lines(song).iter().filter(|l| l.id.is_none()).count()
+}
+
+/// `song`, read from `original`, as an XLIFF 1.2 file of translation units
+/// from `source_lang` into `target_lang`, grouped by section. Lines that
+/// already have a `target_lang` translation carry it as their target.
+pub fn export(
+    song: &Song,
+    original: &str,
+    source_lang: &str,
+    target_lang: &str,
+) -> Result<String, XliffError> {
+    for lang in [source_lang, target_lang] {
+        if !LANG.is_match(lang) {
+            return Err(XliffError::BadLanguage(lang.to_string()));
+        }
+    }
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n");
+    out.push_str(&format!(
+        "  <file original=\"{}\" source-language=\"{}\" target-language=\"{}\" datatype=\"plaintext\">\n",
+        escape(original),
+        source_lang,
+        target_lang
+    ));
+    out.push_str("    <body>\n");
+
+    let mut index = 0;
+    let mut group = |name: String, lines: &[Line], out: &mut String| {
+        let mut units = Vec::new();
+        collect(lines, &mut units);
+        if units.is_empty() {
+            return;
+        }
+        out.push_str(&format!("      <group resname=\"{}\">\n", escape(&name)));
+        for line in units {
+            out.push_str(&format!(
+                "        <trans-unit id=\"{}\">\n",
+                escape(&unit_id(line, index))
+            ));
+            out.push_str(&format!(
+                "          <source>{}</source>\n",
+                escape(&unescaped(&line.text))
+            ));
+            if let Some(text) = line.translation(target_lang) {
+                out.push_str(&format!(
+                    "          <target state=\"translated\">{}</target>\n",
+                    escape(text)
+                ));
+            }
+            out.push_str("        </trans-unit>\n");
+            index += 1;
+        }
+        out.push_str("      </group>\n");
+    };
+    for section in &song.sections {
+        group(section.label(), &section.lines, &mut out);
+    }
+    for m in &song.macros {
+        group(format!("MACRO {}", m.name.name), &m.lines, &mut out);
+    }
+
+    out.push_str("    </body>\n  </file>\n</xliff>\n");
+    Ok(out)
+}
+
+/// XML text with its tags dropped and its entities replaced.
+fn text_of(xml: &str) -> String {
+    let text = TAG.replace_all(xml, "");
+    let text = ENTITY.replace_all(&text, |caps: &regex::Captures| {
+        let entity = &caps[1];
+        let code = match entity.strip_prefix("#x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+        };
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => code.and_then(char::from_u32),
+        };
+        c.map_or_else(|| caps[0].to_string(), String::from)
+    });
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// File the targets of `xliff` under `lang`, or the file's own target
+/// language, as translations of the lines their units name, replacing
+/// any translation the lines had in that language. Empty targets and ones
+/// still marked `new` or `needs-translation` are left out, and `"` becomes
+/// `'`, which a quoted string can hold.
+pub fn import(song: &mut Song, xliff: &str, lang: Option<&str>) -> Result<Imported, XliffError> {
+    let lang = match lang {
+        Some(lang) => lang.to_string(),
+        None => TARGET_LANGUAGE
+            .captures(xliff)
+            .map(|caps| caps[1].to_string())
+            .ok_or(XliffError::NoTargetLanguage)?,
+    };
+    if !LANG.is_match(&lang) {
+        return Err(XliffError::BadLanguage(lang));
+    }
+
+    let mut targets = HashMap::new();
+    let mut units = 0;
+    for unit in UNIT.captures_iter(xliff) {
+        units += 1;
+        let target = TARGET.captures(&unit[3]).filter(|target| {
+            !target[1].contains("state=\"new\"") && !target[1].contains("needs-translation")
+        });
+        let text = target.map_or_else(String::new, |target| text_of(&target[2]));
+        if !text.is_empty() {
+            targets.insert(text_of(&unit[2]), text.replace('"', "'"));
+        }
+    }
+    if units == 0 {
+        return Err(XliffError::NoUnits);
+    }
+
+    let mut imported = Imported {
+        lang: lang.clone(),
+        ..Imported::default()
+    };
+    let mut index = 0;
+    let mut file = |line: &mut Line| {
+        if let Some(text) = targets.remove(&unit_id(line, index)) {
+            line.translations
+                .retain(|t| !t.lang.eq_ignore_ascii_case(&lang));
+            line.translations.push(Translation {
+                lang: lang.clone(),
+                text,
+            });
+            imported.translated += 1;
+        }
+        index += 1;
+    };
+    for section in &mut song.sections {
+        visit(&mut section.lines, &mut file);
+    }
+    for m in &mut song.macros {
+        visit(&mut m.lines, &mut file);
+    }
+    imported.unknown = targets.into_keys().collect();
+    imported.unknown.sort();
+    Ok(imported)
+}
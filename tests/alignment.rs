@@ -0,0 +1,76 @@
+use lyrics_dsl::analysis::alignment::{check, check_length, parse_duration};
+use lyrics_dsl::parser::parse_song;
+
+fn findings(source: &str) -> Vec<(String, Option<String>)> {
+    check(&parse_song(source).unwrap())
+        .into_iter()
+        .map(|d| (format!("{}: {}", d.code.unwrap().id, d.message), d.help))
+        .collect()
+}
+
+#[test]
+fn durations_are_read_as_clock_times_or_seconds() {
+    assert_eq!(parse_duration("3:45"), Some(225.0));
+    assert_eq!(parse_duration("1:02:30"), Some(3750.0));
+    assert_eq!(parse_duration("225s"), Some(225.0));
+    assert_eq!(parse_duration("3:45.5"), Some(225.5));
+    assert_eq!(parse_duration("3:75"), None);
+    assert_eq!(parse_duration("three"), None);
+}
+
+#[test]
+fn timings_past_the_declared_duration_are_errors() {
+    let found = findings(
+        "title: \"T\"\nduration: \"1:00\"\n\nVERSE\nOne {timing: 0:50.00}\nTwo {timing: 0:55.00}\nThree {timing: 1:05.00}\n",
+    );
+    assert_eq!(
+        found,
+        [(
+            "E018: timing 1:05.00 is past the end of the track at 1:00.00, from the `duration`"
+                .to_string(),
+            None
+        )]
+    );
+
+    let song = parse_song("title: \"T\"\n\nVERSE\nOne {timing: 0:50.00}\n").unwrap();
+    assert!(check_length(&song, 50.0, "track.wav").is_empty());
+    assert_eq!(check_length(&song, 49.9, "track.wav").len(), 1);
+}
+
+#[test]
+fn a_minute_slip_is_caught_and_the_intended_timing_suggested() {
+    let found = findings(
+        "title: \"T\"\n\nVERSE\nOne {timing: 0:05.00}\nTwo {timing: 0:09.00}\nThree {timing: 1:13.00}\nFour {timing: 0:17.00}\n",
+    );
+    assert_eq!(
+        found,
+        [
+            (
+                "W011: timing 1:13.00 comes 64s after the line before, where lines are usually 4.0s apart"
+                    .to_string(),
+                Some("is it 0:13.00, a minute off?".to_string())
+            ),
+            (
+                "W010: timing 0:17.00 is earlier than the line before, at 1:13.00".to_string(),
+                Some("is the line before at 0:13.00, a minute off?".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn lines_need_time_to_be_sung() {
+    let found = findings(
+        "title: \"T\"\n\nVERSE\nEverybody wants to rule the world {timing: 0:05.00}\nTwo {timing: 0:05.30}\n",
+    );
+    assert_eq!(found.len(), 1);
+    assert!(found[0]
+        .0
+        .starts_with("W011: timing 0:05.30 leaves 0.30s to sing the 9 syllable(s)"));
+
+    // REPEATs replay earlier timings and are not checked again.
+    assert!(findings(
+        "title: \"T\"\n\nCHORUS\nLa {timing: 0:05.00}\nLa {timing: 0:08.00}\n\nREPEAT CHORUS\n",
+    )
+    .is_empty());
+}
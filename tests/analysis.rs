@@ -0,0 +1,109 @@
+use lyrics_dsl::analysis::{analyze, count_ipa, count_line, count_word, score_line};
+use lyrics_dsl::ast::SectionKind;
+use lyrics_dsl::parser::parse_song;
+
+#[test]
+fn counts_syllables_of_written_words() {
+    let cases = [
+        ("tree", 1),
+        ("walking", 2),
+        ("syntax", 2),
+        ("make", 1),
+        ("table", 2),
+        ("walked", 1),
+        ("wanted", 2),
+        ("glasses", 2),
+        ("carefully", 3),
+        ("validate", 3),
+        ("you", 1),
+        ("twenty-one", 3),
+    ];
+    for (word, expected) in cases {
+        assert_eq!(count_word(word), expected, "{}", word);
+    }
+}
+
+#[test]
+fn counts_ipa_nuclei() {
+    assert_eq!(count_ipa("prəˌnʌnsiˈeɪʃən"), 5);
+    assert_eq!(count_ipa("ˈsɪləbl̩z"), 3);
+    assert_eq!(count_ipa("faɪə"), 1);
+    assert_eq!(count_ipa("ˈfaɪ.ə"), 2);
+}
+
+#[test]
+fn pronunciation_override_feeds_counts_and_scores() {
+    let song =
+        parse_song("title: \"Fire\"\n\nCHORUS\nFire{/ˈfaɪ.ə/} in the hall\nFire in the hall\n")
+            .unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines[0].text, "Fire in the hall");
+    assert_eq!(lines[0].pronunciation("fire"), Some("ˈfaɪ.ə"));
+    assert_eq!(count_line(&lines[0]), 5);
+    assert_eq!(count_line(&lines[1]), 4);
+
+    let score = score_line(&lines[0], SectionKind::Chorus);
+    assert_eq!(score.score, 100);
+}
+
+#[test]
+fn long_and_crowded_lines_score_lower() {
+    let song = parse_song(
+        "title: \"Strength\"\n\nCHORUS\nStrengths of twelfths and extraordinary constitutional amendments\n",
+    )
+    .unwrap();
+    let report = analyze(&song);
+    let line = &report.sections[0].lines[0];
+    assert!(line.score.syllables > 8);
+    assert!(line.score.score < 50, "{:?}", line.score);
+    assert!(!line.score.issues.is_empty());
+    assert_eq!(report.singability, line.score.score);
+}
+
+#[test]
+fn sections_sung_far_faster_than_the_rest_are_flagged() {
+    let mut source = String::from("title: \"Pace\"\nduration: \"0:50\"\n");
+    for (i, header) in ["VERSE[1]", "CHORUS", "VERSE[2]"].iter().enumerate() {
+        let at = i * 10;
+        source.push_str(&format!(
+            "\n{}\nOne two three four five six {{timing: 0:{:02}.00}}\nSeven eight nine ten eleven twelve {{timing: 0:{:02}.00}}\n",
+            header,
+            at,
+            at + 5
+        ));
+    }
+    source.push_str("\nBRIDGE\nOne two three four five six seven eight {timing: 0:30.00}\nNine ten eleven twelve {timing: 0:32.00}\n\nREPEAT CHORUS\n");
+    let report = analyze(&parse_song(&source).unwrap());
+
+    let verse = &report.sections[0];
+    let pace = verse.pace.as_ref().unwrap();
+    assert_eq!(
+        (pace.seconds, pace.words, pace.words_per_minute),
+        (10.0, 12, 72.0)
+    );
+    assert_eq!(verse.pace_warning, None);
+
+    // The bridge runs into a REPEAT, so only its own timed lines count.
+    let bridge = &report.sections[3];
+    assert_eq!(bridge.pace.as_ref().unwrap().words_per_minute, 240.0);
+    assert_eq!(
+        bridge.pace_warning.as_deref(),
+        Some("rushed: 240 words a minute against 72 in the rest of the song")
+    );
+    assert_eq!(report.sections[4].pace, None);
+}
+
+#[test]
+fn far_off_timings_and_tempos_still_analyze() {
+    let song =
+        parse_song("title: \"T\"\ntempo: 1000\n\nVERSE\nLate {timing: 307445734561825:00}\n")
+            .unwrap();
+    let report = analyze(&song);
+    assert_eq!(report.sections[0].lines[0].bar, Some(u32::MAX));
+
+    let song =
+        parse_song("title: \"T\"\ntempo: 99999999999999999999\n\nVERSE\nLate {timing: 1:00.00}\n")
+            .unwrap();
+    let report = analyze(&song);
+    assert_eq!(report.sections[0].lines[0].bar, Some(31));
+}
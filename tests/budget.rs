@@ -0,0 +1,44 @@
+use lyrics_dsl::export::{tightest_budget, Budget, Wrap};
+use lyrics_dsl::parser::parse_song;
+
+fn rows(text: &str, width: usize, wrap: Wrap) -> Vec<String> {
+    let song = parse_song(&format!("title: \"T\"\n\nVERSE\n{}\n", text)).unwrap();
+    Budget { width, wrap }.rows(&song.sections[0].lines[0])
+}
+
+#[test]
+fn greedy_rows_fill_up_and_balanced_rows_even_out() {
+    let line = "Sometimes I forget which voice is mine";
+    assert_eq!(
+        rows(line, 36, Wrap::Greedy),
+        ["Sometimes I forget which voice is", "mine"]
+    );
+    assert_eq!(
+        rows(line, 36, Wrap::Balanced),
+        ["Sometimes I forget", "which voice is mine"]
+    );
+    assert_eq!(rows(line, 40, Wrap::Balanced), [line]);
+}
+
+#[test]
+fn soft_breaks_start_rows_and_long_words_stand_alone() {
+    assert_eq!(
+        rows(
+            "Over the hills \\\n  and far away, far away",
+            12,
+            Wrap::Balanced
+        ),
+        ["Over the", "hills", "and far", "away,", "far away"]
+    );
+    assert_eq!(
+        rows("a supercalifragilistic word", 8, Wrap::Greedy),
+        ["a", "supercalifragilistic", "word"]
+    );
+}
+
+#[test]
+fn the_tightest_named_target_wins() {
+    let (format, budget) = tightest_budget(["lrc", "ttml", "slides", "nope"]).unwrap();
+    assert_eq!((format, budget.width), ("slides", 36));
+    assert_eq!(tightest_budget(["lrc", "html"]), None);
+}
@@ -0,0 +1,81 @@
+use lyrics_dsl::export::bundle::{bundle, Archive, MANIFEST};
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::parser::parse_song;
+use serde_json::Value;
+
+const SONG: &str =
+    "title: \"Harbour\"\nartist: \"The Tides\"\n\nVERSE\nOut on the water {timing: 0:05.00}\n";
+
+/// The name and contents of each file in a tar archive.
+fn untar(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut files = Vec::new();
+    let mut at = 0;
+    while archive[at] != 0 {
+        let header = &archive[at..at + 512];
+        let field = |range: std::ops::Range<usize>| {
+            let text = String::from_utf8_lossy(&header[range]);
+            text.trim_end_matches(['\0', ' ']).to_string()
+        };
+        let checksum: u32 = header
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    *b as u32
+                }
+            })
+            .sum();
+        assert_eq!(u32::from_str_radix(&field(148..155), 8), Ok(checksum));
+        assert_eq!(field(257..262), "ustar");
+        let size = usize::from_str_radix(&field(124..136), 8).unwrap();
+        files.push((field(0..100), archive[at + 512..at + 512 + size].to_vec()));
+        at += 512 + size.div_ceil(512) * 512;
+    }
+    assert_eq!(archive.len(), at + 1024);
+    files
+}
+
+#[test]
+fn bundles_each_format_with_a_manifest() {
+    let song = parse_song(SONG).unwrap();
+    let exporters = ["lrc", "elrc", "txt"].map(|f| exporter(f).unwrap());
+    let archive = bundle(&song, "harbour", &exporters, Archive::Tar).unwrap();
+    let files = untar(&archive);
+    let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "harbour.lrc.lrc",
+            "harbour.elrc.lrc",
+            "harbour.txt",
+            MANIFEST
+        ]
+    );
+    assert_eq!(files[2].1, exporters[2].export(&song).unwrap());
+
+    let manifest: Value = serde_json::from_slice(&files[3].1).unwrap();
+    assert_eq!(manifest["title"], "Harbour");
+    assert_eq!(manifest["artist"], "The Tides");
+    assert_eq!(manifest["files"][2]["name"], "harbour.txt");
+    assert_eq!(manifest["files"][2]["format"], "txt");
+    assert_eq!(manifest["files"][2]["bytes"], files[2].1.len());
+    assert_eq!(manifest["files"][2]["sha256"].as_str().unwrap().len(), 64);
+
+    let zip = bundle(&song, "harbour", &exporters[2..], Archive::Zip).unwrap();
+    assert!(zip.starts_with(b"PK\x03\x04"));
+    assert!(String::from_utf8_lossy(&zip).contains("manifest.json"));
+    assert_eq!(
+        zip,
+        bundle(&song, "harbour", &exporters[2..], Archive::Zip).unwrap()
+    );
+}
+
+#[test]
+fn the_archive_follows_the_extension() {
+    assert_eq!(Archive::from_path("out/song.zip"), Some(Archive::Zip));
+    assert_eq!(Archive::from_path("song.TAR"), Some(Archive::Tar));
+    assert_eq!(Archive::from_path("song.tar.gz"), None);
+    assert_eq!(Archive::from_path("song"), None);
+}
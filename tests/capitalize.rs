@@ -0,0 +1,45 @@
+use lyrics_dsl::capitalize::{Capitalizer, CaseStyle};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+
+fn styled(style: CaseStyle, text: &str) -> String {
+    Capitalizer::new(style, &["Maria".to_string(), "NASA".to_string()]).apply(text)
+}
+
+#[test]
+fn each_style_keeps_protected_words() {
+    let text = "WHEN i met maria at nasa. she said i'm in the mood for a song";
+    assert_eq!(
+        styled(CaseStyle::Sentence, text),
+        "When I met Maria at NASA. She said I'm in the mood for a song"
+    );
+    assert_eq!(
+        styled(CaseStyle::Title, text),
+        "When I Met Maria at NASA. She Said I'm in the Mood for a Song"
+    );
+    assert_eq!(
+        styled(CaseStyle::Lower, "Dancing With MARIA's Ghost"),
+        "dancing with Maria's ghost"
+    );
+    assert_eq!(
+        styled(CaseStyle::Title, "a place to go on"),
+        "A Place to Go On"
+    );
+}
+
+#[test]
+fn variables_and_markup_survive_fmt() {
+    let source = "title: \"T\"\n$Name = \"x\"\n\nVERSE\nHELLO $Name, lo~~ve | AGAIN {chord: C}\n";
+    let song = parse_song(source).unwrap();
+    let lower = Capitalizer::new(CaseStyle::Lower, &[]).apply_song(&song);
+    assert_eq!(
+        format_song(&lower),
+        "title: \"T\"\n$Name = \"x\"\n\nVERSE\nhello $Name, lo~~ve | again {chord: C}\n"
+    );
+}
+
+#[test]
+fn protected_words_can_come_from_the_project_config() {
+    let config = lyrics_dsl::config::Config::parse("protect = \"Maria, NASA\"\n").unwrap();
+    assert_eq!(config.protect, ["Maria", "NASA"]);
+}
@@ -0,0 +1,57 @@
+use lyrics_dsl::capture::{capture, format_utc, import_plain, inbox_entry, CaptureError, Detected};
+
+#[test]
+fn keeps_dsl_songs_as_written() {
+    let captured = capture("title: \"X\"\n\nVERSE\nHi\n").unwrap();
+    assert_eq!(
+        captured.detected,
+        Detected::Song {
+            title: Some("X".into()),
+            sections: 1,
+            lines: 1
+        }
+    );
+    assert_eq!(captured.text, "title: \"X\"\n\nVERSE\nHi\n");
+    assert_eq!(capture(" \n\n"), Err(CaptureError::Empty));
+}
+
+#[test]
+fn converts_plain_lyrics_into_sections() {
+    let plain = "Rain on the roof\nSound of you\n\nHold me | now\n\nRain on the roof\nSound of you\n\n[Bridge]\nFade away\n";
+    assert_eq!(
+        import_plain(plain),
+        "title: \"Rain on the roof\"\n\nCHORUS\nRain on the roof\nSound of you\n\n\
+         VERSE[1]\nHold me \\| now\n\nCHORUS\nRain on the roof\nSound of you\n\nBRIDGE\nFade away\n"
+    );
+    let captured = capture(plain).unwrap();
+    assert_eq!(
+        captured.detected,
+        Detected::Plain {
+            stanzas: 4,
+            lines: 6
+        }
+    );
+}
+
+#[test]
+fn titles_keep_the_first_line_as_written() {
+    let imported = import_plain("Rock & \"roll\" $5\nAll night\n");
+    assert_eq!(
+        imported,
+        "title: \"Rock & 'roll' $5\"\n\nVERSE[1]\nRock \\& \"roll\" \\$5\nAll night\n"
+    );
+    let song = lyrics_dsl::parser::parse_song(&imported).unwrap();
+    assert_eq!(song.title(), Some("Rock & 'roll' $5".to_string()));
+}
+
+#[test]
+fn inbox_entries_are_stamped() {
+    assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+    assert_eq!(format_utc(1_709_210_096), "2024-02-29 12:34 UTC");
+    let captured = capture("CHORUS\n").unwrap();
+    assert_eq!(captured.detected, Detected::Text { lines: 1 });
+    assert_eq!(
+        inbox_entry(&captured, "now"),
+        "## now · plain text, 1 line(s), kept as is\nCHORUS\n\n"
+    );
+}
@@ -0,0 +1,47 @@
+use lyrics_dsl::card::{attribution, excerpt, parse_line_range, render, CardOptions, CardStyle};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"T\"
+artist: \"A\"
+
+CHORUS
+Hold on
+Let go
+
+VERSE[1]
+Walking home
+
+REPEAT CHORUS
+";
+
+#[test]
+fn parses_line_ranges() {
+    assert_eq!(parse_line_range("12-15"), Some((12, 15)));
+    assert_eq!(parse_line_range("7"), Some((7, 7)));
+    assert_eq!(parse_line_range("15-12"), None);
+    assert_eq!(parse_line_range("0-2"), None);
+}
+
+#[test]
+fn quotes_source_lines_once() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    assert_eq!(excerpt(&song, SONG, 5, 6), ["Hold on", "Let go"]);
+    assert_eq!(excerpt(&song, SONG, 1, 4), Vec::<String>::new());
+    assert_eq!(attribution(&song), "T — A");
+}
+
+#[test]
+fn renders_a_png_of_the_requested_size() {
+    let options = CardOptions {
+        style: CardStyle::Light,
+        width: 320,
+        height: 200,
+    };
+    let png = render(&["Hold on".to_string()], "T — A", &options);
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 320);
+    assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 200);
+    assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+}
@@ -0,0 +1,56 @@
+use lyrics_dsl::changelog::{entry_message, log_change};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+
+const SONG: &str = "title: \"Changes\"
+$name = \"Jo\"
+
+VERSE
+Hello $name
+";
+
+#[test]
+fn the_first_change_starts_a_history_block_after_the_header() {
+    let logged = log_change(SONG, "2026-10-01 08:00 UTC", "first draft").unwrap();
+    assert_eq!(
+        logged,
+        "title: \"Changes\"
+$name = \"Jo\"
+history:
+  2026-10-01 08:00 UTC \"first draft\"
+
+VERSE
+Hello $name
+"
+    );
+    let song = parse_song(&logged).unwrap();
+    assert_eq!(song.history.len(), 1);
+    assert_eq!(song.history[0].when, "2026-10-01 08:00 UTC");
+    assert_eq!(song.history[0].message, "first draft");
+    assert_eq!(format_song(&song), logged);
+}
+
+#[test]
+fn later_changes_are_appended_and_the_rest_is_left_as_written() {
+    let first = log_change(SONG, "2026-10-01 08:00 UTC", "first draft").unwrap();
+    let untidy = first.replace("Hello $name", "Hello   $name");
+    let second = log_change(&untidy, "2026-10-15 09:30 UTC", "new bridge").unwrap();
+    assert!(second.contains(
+        "  2026-10-01 08:00 UTC \"first draft\"\n  2026-10-15 09:30 UTC \"new bridge\"\n\n"
+    ));
+    assert!(second.contains("Hello   $name"));
+    let front = "---\ntitle: Changes\n---\n\nVERSE\nHello\n";
+    assert_eq!(
+        log_change(front, "2026-10-01 08:00 UTC", "yaml").unwrap(),
+        "---\ntitle: Changes\n---\nhistory:\n  2026-10-01 08:00 UTC \"yaml\"\n\nVERSE\nHello\n"
+    );
+}
+
+#[test]
+fn messages_are_kept_to_one_line_without_double_quotes() {
+    assert_eq!(
+        entry_message("swapped \"the\n  end\" lines"),
+        "swapped 'the end' lines"
+    );
+    assert!(log_change("VERSE\nno header\n", "2026-10-01 08:00 UTC", "x").is_err());
+}
@@ -0,0 +1,134 @@
+use lyrics_dsl::chords::capo::{shape, song_capo, suggest, ShapeSet};
+use lyrics_dsl::chords::diagram::{diagrams, fingering, Fretboard, Instrument};
+use lyrics_dsl::chords::{transpose_symbol, Chord, Note};
+use lyrics_dsl::export::{with_chord_diagrams, ExportError};
+use lyrics_dsl::parser::parse_song;
+
+#[test]
+fn parses_chord_symbols() {
+    let chord = Chord::parse("Bbm7").unwrap();
+    assert_eq!(chord.root, Note(10));
+    assert_eq!(chord.quality, "m7");
+    assert_eq!(chord.to_string(), "Bbm7");
+    assert_eq!(chord.notes(), vec![Note(10), Note(1), Note(5), Note(8)]);
+
+    let slash = Chord::parse("G/B").unwrap();
+    assert_eq!(slash.bass, Some(Note(11)));
+    assert_eq!(slash.transpose(2).to_string(), "A/C#");
+
+    assert_eq!(transpose_symbol("Cmaj9/E", 3, true), "Ebmaj9/G");
+    assert_eq!(transpose_symbol("N.C.", 3, true), "N.C.");
+
+    assert!(Chord::parse("H7").is_none());
+    assert!(Chord::parse("Cfoo").is_none());
+}
+
+#[test]
+fn standard_tunings_use_familiar_shapes() {
+    let guitar = Fretboard::standard(Instrument::Guitar);
+    assert_eq!(guitar.diagram("Amin").unwrap().shape(), "x02210");
+    assert_eq!(guitar.diagram("C").unwrap().shape(), "x32010");
+
+    let f = guitar.diagram("F").unwrap();
+    assert_eq!(f.fingers, fingering(&f.frets));
+    assert_eq!(
+        f.fingers,
+        vec![Some(1), Some(3), Some(4), Some(2), Some(1), Some(1)]
+    );
+
+    let cm = guitar.diagram("Cm").unwrap();
+    assert_eq!(cm.base_fret, 3);
+
+    let ukulele = Fretboard::standard(Instrument::Ukulele);
+    assert_eq!(ukulele.diagram("G7").unwrap().shape(), "0212");
+}
+
+#[test]
+fn other_chords_and_tunings_are_searched() {
+    let dadgad = Fretboard::new(Instrument::Guitar, "DADGAD").unwrap();
+    let d = dadgad.diagram("D").unwrap();
+    let notes: Vec<Note> = d
+        .frets
+        .iter()
+        .zip(&dadgad.tuning)
+        .filter_map(|(f, open)| f.map(|f| open.transpose(f as i32)))
+        .collect();
+    assert_eq!(notes[0], Note(2));
+    for tone in Chord::parse("D").unwrap().notes() {
+        assert!(notes.contains(&tone), "{:?} misses {:?}", d.frets, tone);
+    }
+
+    let guitar = Fretboard::standard(Instrument::Guitar);
+    assert!(guitar.diagram("F#m").is_some());
+    assert!(Fretboard::new(Instrument::Guitar, "XYZ").is_none());
+}
+
+#[test]
+fn diagrams_are_listed_once_in_first_use_order() {
+    let guitar = Fretboard::standard(Instrument::Guitar);
+    let (found, unknown) = diagrams(&guitar, ["G", "C", "G", "Q"]);
+    let names: Vec<&str> = found.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["G", "C"]);
+    assert_eq!(unknown, ["Q"]);
+}
+
+#[test]
+fn html_and_pdf_exports_append_diagrams() {
+    let song = parse_song("title: \"T\"\n\nVERSE[1]\nHello {chord: C, G}\n").unwrap();
+    let guitar = Fretboard::standard(Instrument::Guitar);
+
+    let html = with_chord_diagrams("html", guitar.clone())
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert_eq!(html.matches("<svg class=\"chord-diagram\"").count(), 2);
+    assert!(html.contains("Chords (guitar, EADGBE)"));
+
+    let pdf = with_chord_diagrams("pdf", guitar.clone())
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+    assert!(pdf.windows(18).any(|w| w == b"(CHORDS \\(guitar, "));
+
+    assert!(matches!(
+        with_chord_diagrams("lrc", guitar),
+        Err(ExportError::Unsupported { .. })
+    ));
+}
+
+#[test]
+fn capo_suggestions_prefer_open_shapes() {
+    let open = ShapeSet::open(Instrument::Guitar);
+    let chords = ["A", "E", "F#m", "D", "Bm"];
+    let suggestions = suggest(&chords, &open, 7);
+    let best = &suggestions[0];
+    assert_eq!(best.capo, 2);
+    assert!(best.barre.is_empty());
+    assert_eq!(best.shapes[2], ("F#m".to_string(), "Em".to_string()));
+
+    let uncapoed = suggestions.iter().find(|s| s.capo == 0).unwrap();
+    assert_eq!(uncapoed.barre, ["F#m", "Bm"]);
+
+    let custom = ShapeSet::parse(&["C", "G", "Am", "F"]).unwrap();
+    assert_eq!(suggest(&["D", "A", "Bm", "G"], &custom, 7)[0].capo, 2);
+    assert_eq!(ShapeSet::parse(&["C", "Xq"]), Err("Xq".to_string()));
+}
+
+#[test]
+fn capo_metadata_shows_shapes_in_exports() {
+    assert_eq!(shape("Bb/D", 3), "G/B");
+    assert_eq!(shape("4", 3), "4");
+
+    let song = parse_song("title: \"T\"\ncapo: 2\n\nVERSE[1]\nHello {chord: A, F#m}\n").unwrap();
+    assert_eq!(song_capo(&song), Some(2));
+    let html = with_chord_diagrams("html", Fretboard::standard(Instrument::Guitar))
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<p class=\"capo\">Capo 2</p>"));
+    assert!(html.contains("<span class=\"chords\">G Em</span>"));
+    assert!(html.contains("font-weight=\"bold\">Em</text>"));
+}
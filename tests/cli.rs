@@ -0,0 +1,1239 @@
+//! End-to-end checks of the `lyrics-dsl` binary.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn lyrics_dsl(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("run lyrics-dsl")
+}
+
+/// A fresh copy of `source` in the temp directory.
+fn scratch(name: &str, source: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lyrics-dsl-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+const UNTIDY: &str = "title:\"T\"\nVERSE\nHello   {timing: 0:02.00}\n";
+
+#[test]
+fn dry_run_prints_a_diff_and_leaves_the_file_alone() {
+    let path = scratch("dry-run.lyr", UNTIDY);
+    let out = lyrics_dsl(&["fmt", "--dry-run", path.to_str().unwrap()]);
+    assert!(out.status.success());
+
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        stdout.contains("-title:\"T\"\n+title: \"T\"\n"),
+        "{}",
+        stdout
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), UNTIDY);
+}
+
+#[test]
+fn retime_shifts_timings_in_place() {
+    let path = scratch("retime.lyr", UNTIDY);
+    let out = lyrics_dsl(&["retime", "--shift", "-1.5", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "title: \"T\"\n\nVERSE\nHello {timing: 0:00.50}\n"
+    );
+
+    let out = lyrics_dsl(&["retime", "--shift", "-1", path.to_str().unwrap()]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn syntax_errors_print_as_rendered() {
+    let path = scratch("syntax.lyr", "title: \"T\"\nartist: \"a\\\" b\n");
+    for command in ["fmt", "show"] {
+        let out = lyrics_dsl(&[command, path.to_str().unwrap()]);
+        assert!(!out.status.success());
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(
+            stderr.contains(
+                "error[E001]: expected the end of the file\n  |\n2 | artist: \"a\\\" b\n"
+            ),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn in_place_writes_keep_a_backup() {
+    let path = scratch("backup.lyr", UNTIDY);
+    let path_str = path.to_str().unwrap();
+    let out = lyrics_dsl(&["fmt", "--backup", path_str]);
+    assert!(out.status.success());
+    assert_eq!(
+        std::fs::read_to_string(format!("{}.bak", path_str)).unwrap(),
+        UNTIDY
+    );
+
+    let formatted = std::fs::read_to_string(&path).unwrap();
+    let out = lyrics_dsl(&["export", "-f", "txt", "--in-place", "--backup", path_str]);
+    assert!(out.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "T\n\n[Verse]\nHello\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(format!("{}.bak", path_str)).unwrap(),
+        formatted
+    );
+}
+
+#[test]
+fn fmt_check_fails_on_untidy_files_without_writing() {
+    let path = scratch("check.lyr", UNTIDY);
+    let out = lyrics_dsl(&["fmt", "--check", path.to_str().unwrap()]);
+    assert!(!out.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), UNTIDY);
+
+    assert!(lyrics_dsl(&["fmt", path.to_str().unwrap()])
+        .status
+        .success());
+    let out = lyrics_dsl(&["fmt", "--check", path.to_str().unwrap()]);
+    assert!(out.status.success());
+}
+
+#[test]
+fn hook_checks_the_staged_version_of_songs() {
+    let repo = std::env::temp_dir().join(format!("lyrics-dsl-hook-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&repo);
+    std::fs::create_dir_all(&repo).unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .expect("run git")
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    let hook = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .arg("hook")
+            .args(args)
+            .current_dir(&repo)
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+    git(&["init", "-q"]);
+
+    assert!(hook(&["install"]).status.success());
+    assert!(repo.join(".git/hooks/pre-commit").exists());
+    assert!(!hook(&["install"]).status.success());
+    assert!(hook(&["install", "--force"]).status.success());
+
+    std::fs::write(repo.join("song.lyr"), UNTIDY).unwrap();
+    git(&["add", "song.lyr"]);
+    assert!(!hook(&["run"]).status.success());
+
+    // Only the staged content counts: tidying the working copy is not enough.
+    std::fs::write(
+        repo.join("song.lyr"),
+        "title: \"T\"\n\nVERSE\nHello {timing: 0:02.00}\n",
+    )
+    .unwrap();
+    assert!(!hook(&["run"]).status.success());
+    git(&["add", "song.lyr"]);
+    let out = hook(&["run"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    // Tidy and valid is not enough either: lint findings block the commit.
+    std::fs::write(
+        repo.join("song.lyr"),
+        "title: \"T\"\n\nVERSE\nHello there\n\nCHORUS\nhello, there\n",
+    )
+    .unwrap();
+    git(&["add", "song.lyr"]);
+    let out = hook(&["run"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("W015"));
+
+    // A merge left unresolved is tidy, but not clean.
+    std::fs::write(repo.join("song.lyr"), CONFLICTED).unwrap();
+    git(&["add", "song.lyr"]);
+    let out = hook(&["run"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("E024"));
+}
+
+const CONFLICTED: &str =
+    "title: \"T\"\n\nVERSE\n<<<<<<< ours\nHello\n=======\nHi there\n>>>>>>> theirs\n";
+
+#[test]
+fn validate_rejects_conflict_markers() {
+    let path = scratch("conflicted.lyr", CONFLICTED);
+    let out = lyrics_dsl(&["validate", path.to_str().unwrap()]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert_eq!(
+        stderr.matches("unresolved merge conflict").count(),
+        3,
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn chords_round_trip_through_nashville_numbers() {
+    const SONG: &str = "title: \"T\"\nkey: \"G\"\n\nVERSE[1]\nHello {chord: G, D/F#, Em7}\n";
+    let path = scratch("nashville.lyr", SONG);
+    let path_str = path.to_str().unwrap();
+
+    let out = lyrics_dsl(&["chords", "--nashville", path_str]);
+    assert!(out.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "title: \"T\"\nkey: \"G\"\n\nVERSE[1]\nHello {chord: 1, 5/7, 6m7}\n"
+    );
+
+    let out = lyrics_dsl(&["chords", "--letters", path_str]);
+    assert!(out.status.success());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), SONG);
+
+    let out = lyrics_dsl(&["chords", "--nashville", "--key", "X", path_str]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn capo_suggests_positions_and_sets_metadata() {
+    const SONG: &str = "title: \"T\"\nkey: \"A\"\n\nVERSE[1]\nHello {chord: A, E, F#m, D}\n";
+    let path = scratch("capo.lyr", SONG);
+    let path_str = path.to_str().unwrap();
+
+    let out = lyrics_dsl(&["capo", path_str, "--top", "1"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        stdout.starts_with("capo 2   A→G E→D F#m→Em D→C\n"),
+        "{}",
+        stdout
+    );
+
+    let out = lyrics_dsl(&["capo", path_str, "--set", "2"]);
+    assert!(out.status.success());
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("capo: 2\n"));
+}
+
+#[test]
+fn setlist_prints_a_cheat_sheet_in_the_overridden_keys() {
+    scratch(
+        "opener.lyr",
+        "title: \"Opener\"\nkey: \"G\"\ntempo: 120\n\nVERSE[1]\nHello {chord: G}\n",
+    );
+    let set = scratch(
+        "set.toml",
+        "title = \"Friday\"\n\n[[song]]\nfile = \"opener.lyr\"\nkey = \"A\"\n",
+    );
+    let set = set.to_str().unwrap();
+    let out = lyrics_dsl(&["setlist", set, "--cheat-sheet", "-f", "txt"]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "Friday\n\n1. Opener · A · 120 bpm\n"
+    );
+}
+
+#[test]
+fn prompt_needs_a_terminal_and_timings_for_timed_mode() {
+    let path = scratch("prompt.lyr", "title: \"T\"\n\nVERSE\nHello\n");
+    let path = path.to_str().unwrap();
+    let out = lyrics_dsl(&["prompt", "--timed", path]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--timed needs `timing`"));
+
+    let out = lyrics_dsl(&["prompt", path]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("interactive terminal"));
+}
+
+#[test]
+fn sync_needs_lines_and_a_terminal() {
+    let path = scratch("sync.lyr", "title: \"T\"\n\nVERSE\nHello\n");
+    let out = lyrics_dsl(&["sync", path.to_str().unwrap()]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("interactive terminal"));
+
+    let path = scratch(
+        "sync-macro.lyr",
+        "title: \"T\"\n\nMACRO hook\nOh\n\nVERSE\nUSE hook\n",
+    );
+    let out = lyrics_dsl(&["sync", path.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no lines to time"));
+}
+
+#[test]
+fn query_prints_results_per_file() {
+    let a = scratch(
+        "query-a.lyr",
+        "title: \"A\"\n\nVERSE\nHello {author: Sam}\nThere\n",
+    );
+    let b = scratch("query-b.lyr", "title: \"B\"\n\nCHORUS\nOh\n");
+    let out = lyrics_dsl(&[
+        "query",
+        "sections.lines[provenance.author=sam].text",
+        a.to_str().unwrap(),
+    ]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "Hello\n");
+
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    let out = lyrics_dsl(&["query", "sections.lines | count", a, b]);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        format!("{}: 2\n{}: 1\n", a, b)
+    );
+
+    let out = lyrics_dsl(&["query", "sections.kind", a, "--json"]);
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "[\n  \"verse\"\n]\n");
+
+    let out = lyrics_dsl(&["query", "sectons", a]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("did you mean `sections`?"));
+}
+
+#[test]
+fn run_scripts_transform_or_report_on_a_song() {
+    let path = scratch("run.lyr", "title: \"T\"\n\nVERSE\nHello there\n");
+    let song = path.to_str().unwrap();
+    let upper = scratch("upper.sh", "sed 's/\"Hello there\"/\"HELLO THERE\"/'\n");
+    let out = lyrics_dsl(&["run", upper.to_str().unwrap(), song]);
+    assert!(out.status.success());
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "title: \"T\"\n\nVERSE\nHELLO THERE\n"
+    );
+
+    let report = scratch(
+        "report.sh",
+        "grep -c '\"text\"'\necho \"$LYRICS_DSL_FILE\"\n",
+    );
+    let out = lyrics_dsl(&["run", report.to_str().unwrap(), song]);
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        format!("1\n{}\n", song)
+    );
+
+    let failing = scratch("failing.sh", "exit 3\n");
+    let out = lyrics_dsl(&["run", failing.to_str().unwrap(), song]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("exit status: 3"));
+}
+
+#[test]
+fn overlay_writes_the_current_line_and_clears_it_at_the_end() {
+    let song = scratch(
+        "overlay.lyr",
+        "title: \"T\"\n\nVERSE\nHello {timing: 0:00.50}\n",
+    );
+    let text = song.with_extension("txt");
+    let out = lyrics_dsl(&[
+        "overlay",
+        song.to_str().unwrap(),
+        "--text",
+        text.to_str().unwrap(),
+        "--start",
+        "5.3",
+    ]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Hello"));
+    assert_eq!(std::fs::read_to_string(&text).unwrap(), "");
+
+    let out = lyrics_dsl(&["overlay", song.to_str().unwrap()]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn clip_appends_stdin_to_the_inbox() {
+    let inbox = scratch("inbox.txt", "");
+    let clip = |text: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(["clip", "--stdin", "--inbox", inbox.to_str().unwrap()])
+            .env("NO_COLOR", "1")
+            .stdin(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("run lyrics-dsl");
+        use std::io::Write;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+    let out = clip("First idea\nsecond line\n");
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("plain lyrics, 1 stanza(s)"));
+    assert!(clip("title: \"X\"\n\nVERSE\nHi\n").status.success());
+
+    let inbox = std::fs::read_to_string(&inbox).unwrap();
+    assert_eq!(inbox.matches("## ").count(), 2);
+    assert!(inbox.contains("VERSE[1]\nFirst idea\nsecond line\n"));
+    assert!(inbox.contains("DSL song \"X\""));
+}
+
+#[test]
+fn todos_lists_open_notes_by_song_and_section() {
+    let song = scratch(
+        "todos.lyr",
+        "title: \"Draft\"\n\nVERSE[1]\nI walk the line !! todo: find better rhyme\nHome !! done: fixed\n",
+    );
+    let out = lyrics_dsl(&["todos", song.to_str().unwrap()]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Draft"));
+    assert!(stdout.contains("Verse 1"));
+    assert!(stdout.contains("4  todo: find better rhyme"));
+    assert!(!stdout.contains("fixed"));
+}
+
+#[test]
+fn index_builds_once_and_searches_by_phrase() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::create_dir_all(project.path().join("album")).unwrap();
+    std::fs::write(
+        project.path().join("album/night.lyr"),
+        "title: \"Night\"\n\nCHORUS\nHold on to the night\nOn we hold\n",
+    )
+    .unwrap();
+    std::fs::write(project.path().join("broken.lyr"), "VERSE[\n").unwrap();
+    let index = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .arg("index")
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = index(&["search", "night"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("index build"));
+
+    let out = index(&["build"]);
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("skipping broken.lyr"));
+    assert!(stderr.contains("Indexed 1 song(s), 2 line(s)"));
+    assert!(project.path().join(".lyricsdsl/index/terms.tsv").exists());
+
+    let out = index(&["search", "\"hold on\""]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "album/night.lyr:4  Night · Chorus\n    Hold on to the night\n"
+    );
+}
+
+#[test]
+fn catalog_dedupe_clusters_copies_and_suggests_one_to_keep() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::create_dir_all(project.path().join("archive")).unwrap();
+    let night = "title: \"Night\"\n\nVERSE\nHold on to the night we never knew\nWe hold the line until the morning comes\n";
+    std::fs::write(project.path().join("night.lyr"), night).unwrap();
+    std::fs::write(project.path().join("archive/night copy.lyr"), night).unwrap();
+    std::fs::write(
+        project.path().join("day.lyr"),
+        "title: \"Day\"\n\nVERSE\nSomething else entirely under the sun\n",
+    )
+    .unwrap();
+    let dedupe = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(["catalog", "dedupe"])
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = dedupe(&[]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "Cluster 1 · 2 songs\n  ★ night.lyr               keep\n    archive/night copy.lyr  100% alike\n\n"
+    );
+    assert!(String::from_utf8_lossy(&out.stderr).contains("1 file(s) look like copies"));
+
+    let out = dedupe(&["archive", "day.lyr"]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("No near-identical songs among 2"));
+}
+
+#[test]
+fn catalog_manifest_lists_metadata_as_csv_or_json() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        project.path().join("night.lyr"),
+        "title: \"Night\"\nyear: 2021\nexplicit: true\n\nVERSE\nHold on\n",
+    )
+    .unwrap();
+    std::fs::write(project.path().join("broken.lyr"), "VERSE[\n").unwrap();
+    let manifest = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(["catalog", "manifest"])
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = manifest(&[]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("skipping broken.lyr"));
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "path,title,artist,writers,year,duration,language,explicit,isrc,iswc,publishers\r\n\
+         night.lyr,Night,,,2021,,,true,,,\r\n"
+    );
+
+    let out = manifest(&["-o", "catalog.json"]);
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(project.path().join("catalog.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(json[0]["title"], "Night");
+    assert_eq!(json[0]["explicit"], true);
+}
+
+#[test]
+fn split_sheet_writes_pdf_or_docx_by_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let song = dir.path().join("song.lyr");
+    std::fs::write(
+        &song,
+        "title: \"T\"\nwriters: \"Ann Lee 50%, Bo Chen 50%\"\n\nVERSE\nLa\n",
+    )
+    .unwrap();
+    for (name, magic) in [("splits.pdf", &b"%PDF"[..]), ("splits.docx", b"PK\x03\x04")] {
+        let out = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args([
+                "split-sheet",
+                "song.lyr",
+                "-o",
+                name,
+                "--date",
+                "2026-10-15",
+            ])
+            .current_dir(dir.path())
+            .output()
+            .expect("run lyrics-dsl");
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert!(std::fs::read(dir.path().join(name))
+            .unwrap()
+            .starts_with(magic));
+    }
+
+    std::fs::write(&song, "title: \"T\"\n\nVERSE\nLa\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(["split-sheet", "song.lyr", "-o", "none.pdf"])
+        .current_dir(dir.path())
+        .output()
+        .expect("run lyrics-dsl");
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no `writers`"));
+}
+
+#[test]
+fn draft_saves_lists_and_diffs_snapshots() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    let song = project.path().join("song.lyr");
+    let draft = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .arg("draft")
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    std::fs::write(&song, "title: \"D\"\n\nVERSE\nOld line\n").unwrap();
+    assert!(draft(&["save", "song.lyr", "first try"]).status.success());
+    std::fs::write(&song, "title: \"D\"\n\nVERSE\nNew line\n\nBRIDGE\nUp\n").unwrap();
+    assert!(draft(&["save", "song.lyr", "tried new bridge"])
+        .status
+        .success());
+    assert!(project
+        .path()
+        .join(".lyricsdsl/drafts/song.lyr/2.lyr")
+        .exists());
+
+    let list = String::from_utf8_lossy(&draft(&["list", "song.lyr"]).stdout).into_owned();
+    assert!(list.contains("1  ") && list.contains("first try"));
+    assert!(list.contains("tried new bridge"));
+
+    let diff = String::from_utf8_lossy(&draft(&["diff", "song.lyr", "1"]).stdout).into_owned();
+    assert!(diff.contains("~ VERSE\n    -Old line\n    +New line\n"));
+    assert!(diff.contains("+ BRIDGE"));
+    assert!(!draft(&["diff", "song.lyr", "7"]).status.success());
+}
+
+#[test]
+fn collab_host_edits_and_saves_the_shared_song() {
+    let song = scratch("collab.lyr", "title: \"C\"\n\nVERSE\nOld words\n");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(["collab", "host", song.to_str().unwrap()])
+        .args(["--bind", "127.0.0.1:0", "--name", "host"])
+        .env("NO_COLOR", "1")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("run lyrics-dsl");
+    use std::io::Write;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"set 4 New words\nlock 4\nset 4 New words\nshow\nsave\nquit\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("lock line 4 before changing it"));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("4  New words  [host]"));
+    assert_eq!(
+        std::fs::read_to_string(&song).unwrap(),
+        "title: \"C\"\n\nVERSE\nNew words\n"
+    );
+}
+
+#[test]
+fn locked_songs_are_read_and_rewritten_transparently() {
+    let song = scratch("locked.lyr", UNTIDY);
+    let key = scratch("locked.key", "0123456789abcdef0123456789abcdef");
+    let with_key = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(args)
+            .env("NO_COLOR", "1")
+            .env("LYRICS_DSL_KEYFILE", &key)
+            .output()
+            .expect("run lyrics-dsl")
+    };
+    let song_path = song.to_str().unwrap();
+
+    assert!(with_key(&["lock", song_path]).status.success());
+    let locked = std::fs::read(&song).unwrap();
+    assert!(locked.starts_with(b"lyrics-dsl locked v1\n"));
+    assert!(!locked.windows(5).any(|w| w == b"Hello"));
+
+    let out = lyrics_dsl(&["validate", song_path]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("LYRICS_DSL_KEYFILE"));
+    assert!(with_key(&["validate", song_path]).status.success());
+
+    assert!(with_key(&["fmt", song_path]).status.success());
+    assert!(std::fs::read(&song)
+        .unwrap()
+        .starts_with(b"lyrics-dsl locked v1\n"));
+
+    assert!(with_key(&["unlock", song_path]).status.success());
+    let unlocked = std::fs::read_to_string(&song).unwrap();
+    assert!(unlocked.starts_with("title: \"T\"\n"));
+}
+
+#[test]
+fn signed_exports_verify_until_the_file_changes() {
+    let song = scratch("signed.lyr", UNTIDY);
+    let dir = song.parent().unwrap();
+    let key = dir.join("label.key");
+    let public = dir.join("label.key.pub");
+    let out = dir.join("signed.txt");
+    let manifest = dir.join("signed.txt.sha256");
+    let (key, public, out, manifest) = (
+        key.to_str().unwrap(),
+        public.to_str().unwrap(),
+        out.to_str().unwrap(),
+        manifest.to_str().unwrap(),
+    );
+
+    assert!(lyrics_dsl(&["keygen", key]).status.success());
+    assert!(!lyrics_dsl(&["keygen", key]).status.success());
+    let export = lyrics_dsl(&[
+        "export",
+        song.to_str().unwrap(),
+        "-f",
+        "txt",
+        "-o",
+        out,
+        "--sign",
+        key,
+    ]);
+    assert!(export.status.success());
+    assert!(std::fs::read_to_string(manifest)
+        .unwrap()
+        .ends_with("  signed.txt\n"));
+
+    let verify = lyrics_dsl(&["verify", manifest, "--key", public]);
+    assert!(verify.status.success());
+    assert_eq!(String::from_utf8_lossy(&verify.stdout), "signed.txt: OK\n");
+
+    std::fs::write(out, "Tampered\n").unwrap();
+    let verify = lyrics_dsl(&["verify", manifest, "--key", public]);
+    assert!(!verify.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&verify.stdout),
+        "signed.txt: FAILED\n"
+    );
+}
+
+#[test]
+fn extra_pest_rules_check_annotations_in_the_project() {
+    let dir = scratch("annotated.lyr", "").with_extension("d");
+    std::fs::create_dir_all(dir.join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        dir.join("song.lyr"),
+        "title: \"T\"\n\nVERSE\nOne {mood: wistful}\nTwo {mood: happy}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("extra.pest"),
+        "mood = { \"wistful\" | \"angry\" }\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(args)
+            .current_dir(&dir)
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = run(&["validate", "song.lyr"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains(
+        "song.lyr:5:12: error[E013]: `happy` does not match the `mood` rule of extra.pest"
+    ));
+
+    let grammar = run(&["grammar"]);
+    assert!(String::from_utf8_lossy(&grammar.stdout)
+        .ends_with("// extra.pest\nmood = { \"wistful\" | \"angry\" }\n"));
+}
+
+#[test]
+fn profile_comes_from_the_flag_or_the_project_config() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        project.path().join("song.lyr"),
+        "title: \"T\"\n\nVERSE\nOne\n\nSECTION Tag\nTwo\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = run(&["validate", "song.lyr"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains(
+        "song.lyr:6:1: error[E014]: free-form section `SECTION Tag` needs the permissive profile"
+    ));
+    assert!(run(&["validate", "--profile", "permissive", "song.lyr"])
+        .status
+        .success());
+
+    std::fs::write(
+        project.path().join(".lyricsdsl/config.toml"),
+        "profile = \"permissive\"\n",
+    )
+    .unwrap();
+    assert!(run(&["validate", "song.lyr"]).status.success());
+    let strict = run(&["validate", "--profile", "strict", "song.lyr"]);
+    assert!(String::from_utf8_lossy(&strict.stderr)
+        .contains("song.lyr:3:1: error[E015]: `VERSE` has no number"));
+}
+
+#[test]
+fn hooks_run_around_parsing_and_export() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        project.path().join("song.lyr"),
+        "title: \"T\"\n\nVERSE\nOne\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.path().join(".lyricsdsl/config.toml"),
+        "hooks.post-export = \"cat > exported.json; echo >> exported.json; echo >> exported.json\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+
+    let out = run(&["export", "song.lyr", "-f", "txt"]);
+    assert!(out.status.success());
+    let payload = std::fs::read_to_string(project.path().join("exported.json")).unwrap();
+    assert!(payload.starts_with("{\"bytes\":"));
+    assert!(payload.ends_with(
+        "\"file\":\"song.lyr\",\"format\":\"txt\",\"output\":null,\"stage\":\"post-export\"}\nsong.lyr\n"
+    ));
+
+    std::fs::write(
+        project.path().join(".lyricsdsl/config.toml"),
+        "hooks.pre-parse = \"test -f approved.txt\"\n",
+    )
+    .unwrap();
+    let out = run(&["validate", "song.lyr"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr)
+        .contains("pre-parse hook `test -f approved.txt` failed"));
+}
+
+#[test]
+fn exports_are_skipped_while_up_to_date() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    let song = project.path().join("song.lyr");
+    std::fs::write(&song, "title: \"T\"\n\nVERSE\nOne\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+    let export = ["export", "song.lyr", "-f", "txt", "-o", "song.txt"];
+    let up_to_date = |out: &Output| String::from_utf8_lossy(&out.stderr).contains("up to date");
+
+    assert!(!up_to_date(&run(&export)));
+    assert!(up_to_date(&run(&export)));
+    assert!(!up_to_date(&run(&[&export[..], &["--force"]].concat())));
+    assert!(!up_to_date(&run(&[
+        "export", "song.lyr", "-f", "md", "-o", "song.txt"
+    ])));
+    assert!(!up_to_date(&run(&export)));
+
+    std::fs::write(&song, "title: \"T\"\n\nVERSE\nTwo\n").unwrap();
+    assert!(!up_to_date(&run(&export)));
+    assert_eq!(
+        std::fs::read_to_string(project.path().join("song.txt")).unwrap(),
+        "T\n\n[Verse]\nTwo\n"
+    );
+}
+
+#[test]
+fn messages_follow_the_language() {
+    let song = scratch("lang.lyr", "title: \"T\"\n\nVERSE\nOne\n");
+    let out = song.with_extension("txt");
+    let (song, out) = (song.to_str().unwrap(), out.to_str().unwrap());
+    let export = lyrics_dsl(&["export", song, "-f", "txt", "-o", out, "--lang", "es"]);
+    assert!(String::from_utf8_lossy(&export.stderr).contains("Salida escrita en:"));
+
+    let export = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(["export", song, "-f", "txt", "-o", out])
+        .env("NO_COLOR", "1")
+        .env("LANG", "es_MX.UTF-8")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&export.stderr).contains("Salida escrita en:"));
+
+    let unknown = lyrics_dsl(&["validate", song, "--lang", "xx"]);
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("no messages in `xx`"));
+}
+
+#[test]
+fn fix_applies_the_fixes_the_user_accepts() {
+    let song = scratch(
+        "fixable.lyr",
+        "artist: \"A\"\n\nVESE\nOne\n\nCHORUS[2]  \nLa\n",
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(["fix", song.to_str().unwrap()])
+        .env("NO_COLOR", "1")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("run lyrics-dsl");
+    use std::io::Write;
+    // Take the keyword, decline the title, renumber, then quit.
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"y\nn\ny\nq\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Apply fix: replace `VESE` with `VERSE`?"));
+    assert!(stdout.contains("Apply fix: renumber CHORUS sections 1?"));
+    assert_eq!(
+        std::fs::read_to_string(&song).unwrap(),
+        "artist: \"A\"\n\nVERSE\nOne\n\nCHORUS[1]  \nLa\n"
+    );
+}
+
+#[test]
+fn fix_can_take_back_the_last_fix() {
+    let song = scratch(
+        "undoable.lyr",
+        "artist: \"A\"\n\nVESE\nOne\n\nCHORUS[2]  \nLa\n",
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+        .args(["fix", song.to_str().unwrap()])
+        .env("NO_COLOR", "1")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("run lyrics-dsl");
+    use std::io::Write;
+    // Take the keyword, decline the title, renumber, undo that, then quit.
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"y\nn\ny\nu\nq\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Undid the last fix."));
+    assert_eq!(
+        std::fs::read_to_string(&song).unwrap(),
+        "artist: \"A\"\n\nVERSE\nOne\n\nCHORUS[2]  \nLa\n"
+    );
+}
+
+#[test]
+fn lint_fix_rewrites_only_what_is_safe() {
+    let song = scratch(
+        "lint.lyr",
+        "artist: \"A\"\n\nVERSE[2]   &start\nOne\n\nREPEAT VERSE[2]\n",
+    );
+    let out = lyrics_dsl(&["lint", song.to_str().unwrap()]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("2 can be fixed with `lint --fix`"));
+
+    let out = lyrics_dsl(&["lint", "--fix", song.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("lint.lyr: 2 fix(es)"), "{}", stdout);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("warning[W007]: the song has no title"));
+    assert_eq!(
+        std::fs::read_to_string(&song).unwrap(),
+        "artist: \"A\"\n\nVERSE[1] &start\nOne\n\nREPEAT VERSE[1]\n"
+    );
+}
+
+#[test]
+fn sync_stub_drafts_timings_from_the_sung_phrases() {
+    // 8-bit mono at 1kHz: a second of silence, two seconds of square wave.
+    let rate = 1000u32;
+    let data: Vec<u8> = (0..3000)
+        .map(|i| {
+            if i < 1000 {
+                128
+            } else {
+                128 + (i % 2) as u8 * 100
+            }
+        })
+        .collect();
+    let mut wav = b"RIFF".to_vec();
+    wav.extend((36 + data.len() as u32).to_le_bytes());
+    wav.extend(b"WAVEfmt ");
+    for field in [16u32, 1 | 1 << 16, rate, rate, 1 | 8 << 16] {
+        wav.extend(field.to_le_bytes());
+    }
+    wav.extend(b"data");
+    wav.extend((data.len() as u32).to_le_bytes());
+    wav.extend(data);
+    let track = scratch("track.wav", "");
+    std::fs::write(&track, wav).unwrap();
+
+    let song = scratch(
+        "sync-stub.lyr",
+        "title: \"T\"\n\nVERSE\nOne two\nThree four\n",
+    );
+    let out = lyrics_dsl(&["sync-stub", track.to_str().unwrap(), song.to_str().unwrap()]);
+    assert!(out.status.success(), "{:?}", out);
+    assert_eq!(
+        std::fs::read_to_string(&song).unwrap(),
+        "title: \"T\"\n\nVERSE\nOne two {timing: 0:01.00}\nThree four {timing: 0:02.00}\n"
+    );
+
+    let out = lyrics_dsl(&["sync-stub", song.to_str().unwrap(), song.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("not a WAV file"));
+}
+
+#[test]
+fn romanize_adds_romanized_variants() {
+    let path = scratch(
+        "romanize.lyr",
+        "title: \"T\"\nlang: \"ja\"\n\nVERSE[1]\nきみがすき {timing: 0:01.00}\n東京\nHello\n",
+    );
+    let out = lyrics_dsl(&["romanize", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains(":6: no ja reading of `東`"));
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "title: \"T\"\nlang: \"ja\"\n\nVERSE[1]\nきみがすき {timing: 0:01.00, translation.ja-Latn: \"kimigasuki\"}\n東京\nHello\n"
+    );
+}
+
+#[test]
+fn map_draws_blocks_and_writes_svg() {
+    let path = scratch(
+        "map.lyr",
+        "title: \"T\"\n\nVERSE[1]\nOne\nTwo\nThree\n\nCHORUS\nLa\n",
+    );
+    let svg = path.with_extension("svg");
+    let out = lyrics_dsl(&[
+        "map",
+        path.to_str().unwrap(),
+        "--width",
+        "20",
+        "--svg",
+        svg.to_str().unwrap(),
+    ]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("V1             C"));
+    assert!(stdout.contains("4 lines"));
+    assert_eq!(
+        std::fs::read_to_string(&svg)
+            .unwrap()
+            .matches("<rect ")
+            .count(),
+        2
+    );
+
+    let out = lyrics_dsl(&["map", path.to_str().unwrap(), "--by", "duration"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn medley_combines_sections_of_several_songs() {
+    let a = scratch(
+        "medley_a.lyr",
+        "title: \"A\"\nkey: \"G\"\n\nCHORUS[1]\nSing {chord: D, G}\n",
+    );
+    scratch(
+        "medley_b.lyr",
+        "title: \"B\"\n\nVERSE[1]\nOne\n\nBRIDGE\nFalling {chord: Dm}\n",
+    );
+    let medley = a.with_file_name("friday.medley");
+    std::fs::write(
+        &medley,
+        "title: \"Friday\"\n\nuse \"medley_b.lyr\"::BRIDGE transposed +2\nuse \"medley_a.lyr\"::CHORUS\n",
+    )
+    .unwrap();
+    let out = lyrics_dsl(&["medley", medley.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "title: \"Friday\"\n\nBRIDGE\nFalling {chord: Em}\n\nCHORUS[1]\nSing {chord: D, G}\n"
+    );
+
+    let out = lyrics_dsl(&["medley", medley.to_str().unwrap(), "-f", "txt"]);
+    assert!(String::from_utf8_lossy(&out.stdout).starts_with("Friday\n\n[Bridge]\nFalling\n"));
+
+    std::fs::write(&medley, "use \"medley_a.lyr\"::OUTRO\n").unwrap();
+    let out = lyrics_dsl(&["medley", medley.to_str().unwrap()]);
+    assert!(String::from_utf8_lossy(&out.stderr)
+        .contains("line 1: `medley_a.lyr` has no section OUTRO"));
+}
+
+#[test]
+fn range_checks_a_transposition_against_the_singer() {
+    let path = scratch(
+        "range.lyr",
+        "title: \"T\"\nkey: \"Bb\"\n\nVERSE[1]\nLow {pitch: F2}\n\nCHORUS\nUp {pitch: D4}\n",
+    );
+    let path = path.to_str().unwrap();
+    let out = lyrics_dsl(&["range", path, "--singer", "G2-E4"]);
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("F2 is 2 semitone(s) below the singer's G2"));
+    assert!(stdout.contains("Fits transposed by +2\n"));
+
+    let out = lyrics_dsl(&["range", path, "--singer", "G2-E4", "--key", "C"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.starts_with("Transposed +2\nLowest   G2"));
+    assert!(stdout.contains("Fits G2-E4"));
+}
+
+#[test]
+fn flow_prints_the_syllables_on_each_beat() {
+    let path = scratch(
+        "flow.lyr",
+        "title: \"T\"\ntempo: 90\n\nVERSE[1]\nYo | I came to the mic · with a rhyme · and a reason · to stay |\n",
+    );
+    let out = lyrics_dsl(&["flow", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout
+        .contains("    (1) | 5 · 3 · 4 · 2  3.5 a beat, 5.25 a second at 90 BPM (sixteenths)\n"));
+}
+
+#[test]
+fn speak_prints_the_section_with_its_pauses() {
+    let path = scratch(
+        "speak.lyr",
+        "title: \"T\"\n\nVERSE[1]\nFirst\n\nCHORUS\nSing it\n\nout loud\n\nREPEAT CHORUS\n",
+    );
+    let out = lyrics_dsl(&[
+        "speak",
+        path.to_str().unwrap(),
+        "--section",
+        "chorus",
+        "--print",
+    ]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(stdout, "Sing it [900 ms]\nout loud [0 ms]\n");
+}
+
+#[test]
+fn export_presets_fill_in_options_the_command_line_leaves_out() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        project.path().join(".lyricsdsl/config.toml"),
+        "preset.plain.format = \"txt\"\npreset.plain.harmony = \"exclude\"\npreset.plain.case = \"lower\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.path().join("song.lyr"),
+        "title: \"P\"\n\nVERSE\nHello World\n  + Ooh\n",
+    )
+    .unwrap();
+    let export = |args: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(["export", "song.lyr"])
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl");
+        (
+            out.status.success(),
+            String::from_utf8_lossy(&out.stdout).into_owned(),
+            String::from_utf8_lossy(&out.stderr).into_owned(),
+        )
+    };
+
+    let (ok, stdout, _) = export(&["--preset", "plain"]);
+    assert!(ok);
+    assert!(stdout.contains("hello world\n") && !stdout.contains("ooh"));
+    let (ok, stdout, _) = export(&["--preset", "plain", "--harmony", "include"]);
+    assert!(ok);
+    assert!(stdout.contains("hello world\n  Ooh\n"));
+    let (ok, stdout, _) = export(&["--preset", "web"]);
+    assert!(ok);
+    assert!(stdout.contains("<p class=\"line harmony part-1\">Ooh</p>"));
+    let (ok, _, stderr) = export(&["--preset", "stage"]);
+    assert!(!ok);
+    assert!(stderr.contains("no preset `stage`; there are karaoke-bar, plain, print-songbook, web"));
+}
+
+#[test]
+fn export_templates_rerender_when_a_partial_changes() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::create_dir(project.path().join(".lyricsdsl")).unwrap();
+    std::fs::write(
+        project.path().join("song.lyr"),
+        "title: \"P\"\n\nVERSE\nHello\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project.path().join("sheet.html"),
+        "{{> head}}{{#each sections}}{{#each lines}}<p>{{text}}</p>{{/each}}{{/each}}",
+    )
+    .unwrap();
+    std::fs::write(project.path().join("head.html"), "<h1>{{title}}</h1>").unwrap();
+    let export = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_lyrics-dsl"))
+            .args(["export", "song.lyr", "--template", "sheet.html"])
+            .args(args)
+            .current_dir(project.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run lyrics-dsl")
+    };
+    let read = || std::fs::read_to_string(project.path().join("song.html")).unwrap();
+
+    assert!(export(&["-f", "html", "-o", "song.html"]).status.success());
+    assert_eq!(read(), "<h1>P</h1><p>Hello</p>");
+    std::fs::write(project.path().join("head.html"), "<h2>{{title}}</h2>").unwrap();
+    assert!(export(&["-f", "html", "-o", "song.html"]).status.success());
+    assert_eq!(read(), "<h2>P</h2><p>Hello</p>");
+
+    let out = export(&["-f", "pdf", "-o", "song.pdf"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("templates"), "{}", stderr);
+}
+
+#[test]
+fn log_change_records_the_edit_in_the_song_history() {
+    let path = scratch("history.lyr", UNTIDY);
+    let file = path.to_str().unwrap();
+    let out = lyrics_dsl(&["fmt", file, "--log-change", "tidied up"]);
+    assert!(out.status.success());
+    let source = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        source.starts_with("title: \"T\"\nhistory:\n  20"),
+        "{}",
+        source
+    );
+    assert!(
+        source.contains(" UTC \"tidied up\"\n\nVERSE\n"),
+        "{}",
+        source
+    );
+
+    let history = String::from_utf8(lyrics_dsl(&["history", file]).stdout).unwrap();
+    assert!(history.ends_with(" UTC  tidied up\n"), "{}", history);
+    let later = lyrics_dsl(&["history", file, "--since", "9999-01-01"]).stdout;
+    assert!(later.is_empty());
+}
@@ -0,0 +1,109 @@
+use lyrics_dsl::analysis::{alignment, meter, scansion};
+use lyrics_dsl::ast::words;
+use lyrics_dsl::codes::{self, ALL};
+use lyrics_dsl::diagnostic::Diagnostic;
+use lyrics_dsl::extension::{self, Extension};
+use lyrics_dsl::lint;
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::profile::{self, Profile};
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::spelling::Dictionary;
+use std::collections::HashSet;
+
+/// Every check's findings on `source`, under the standard profile and the
+/// strict one.
+fn diagnostics(source: &str, extra_pest: Option<&str>) -> Vec<Diagnostic> {
+    let song = match parse_song(source) {
+        Ok(song) => song,
+        Err(e) => return vec![error_diagnostic(&e, source)],
+    };
+    let extension = extra_pest.map(|extra| Extension::parse(extra).unwrap());
+    let mut found = resolve(&song).diagnostics;
+    found.extend(meter::check(&song));
+    found.extend(scansion::check(&song));
+    found.extend(alignment::check(&song));
+    found.extend(extension::check(&song, extension.as_ref()));
+    found.extend(profile::check(&song, Profile::Standard));
+    found.extend(profile::check(&song, Profile::Strict));
+    found.extend(lint::check(&song, source, "song", Some(&dictionary())));
+    found
+}
+
+/// A dictionary of every word the corrected examples use.
+fn dictionary() -> Dictionary {
+    let words: Vec<&str> = ALL.iter().flat_map(|code| words(code.right)).collect();
+    Dictionary::from_words(&words.join("\n"))
+}
+
+fn ids(diagnostics: &[Diagnostic]) -> Vec<&'static str> {
+    diagnostics
+        .iter()
+        .filter_map(|d| d.code)
+        .map(|c| c.id)
+        .collect()
+}
+
+#[test]
+fn every_example_shows_its_code() {
+    for code in ALL {
+        let wrong = ids(&diagnostics(code.wrong, code.extra_pest));
+        assert!(
+            wrong.contains(&code.id),
+            "{}: wrong gave {:?}",
+            code.id,
+            wrong
+        );
+        let found = diagnostics(code.wrong, code.extra_pest);
+        for d in found.iter().filter(|d| d.code == Some(*code)) {
+            assert_eq!(d.severity, code.severity, "{}: {}", code.id, d.message);
+        }
+        let right = ids(&diagnostics(code.right, code.extra_pest));
+        assert!(
+            !right.contains(&code.id),
+            "{}: right gave {:?}",
+            code.id,
+            right
+        );
+    }
+}
+
+#[test]
+fn codes_are_unique_and_match_their_severity() {
+    let mut seen = HashSet::new();
+    for code in ALL {
+        assert!(seen.insert(code.id), "{} is used twice", code.id);
+        let prefix = if code.severity.to_string() == "error" {
+            'E'
+        } else {
+            'W'
+        };
+        assert!(code.id.starts_with(prefix), "{}", code.id);
+    }
+}
+
+#[test]
+fn codes_are_found_ignoring_case() {
+    assert_eq!(codes::find("e006"), Some(&codes::UNDEFINED_REFERENCE));
+    assert_eq!(codes::find("E999"), None);
+}
+
+#[test]
+fn rendered_diagnostics_show_their_code() {
+    let source = "title: \"T\"\n\nVERSE\nHello ${who}\n";
+    let song = parse_song(source).unwrap();
+    let rendered = resolve(&song).diagnostics[0].render("song.lyr", source);
+    assert!(
+        rendered.starts_with("song.lyr:4:9: error[E006]: undefined variable `who`"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn strict_placeholders_render_as_errors_with_an_error_code() {
+    let source = codes::PLACEHOLDER.wrong;
+    let song = parse_song(source).unwrap();
+    let render = |profile| profile::check(&song, profile)[0].render("song.lyr", source);
+    assert!(render(Profile::Standard).starts_with("song.lyr:4:14: warning[W017]"));
+    assert!(render(Profile::Strict).starts_with("song.lyr:4:14: error[E025]"));
+}
@@ -0,0 +1,90 @@
+use lyrics_dsl::collab::{clean_name, CollabError, Document, Edit, Message, Request};
+
+const SONG: &str = "title: \"Together\"\n\nVERSE\nFirst line\nSecond line\n";
+
+#[test]
+fn editing_a_line_needs_its_lock() {
+    let mut doc = Document::from_source(SONG);
+    let set = Edit::Set(4, "First line, better".into());
+    assert_eq!(doc.apply("ana", &set), Err(CollabError::NotLocked(4)));
+    doc.apply("ana", &Edit::Lock(4)).unwrap();
+    assert_eq!(
+        doc.apply("ben", &Edit::Lock(4)),
+        Err(CollabError::Locked {
+            line: 4,
+            by: "ana".into()
+        })
+    );
+    assert!(doc.apply("ben", &set).is_err());
+    doc.apply("ana", &set).unwrap();
+    assert_eq!(doc.lines()[3], "First line, better");
+    assert_eq!(
+        doc.apply("ana", &Edit::Lock(9)),
+        Err(CollabError::NoSuchLine(9))
+    );
+}
+
+#[test]
+fn locks_move_with_their_line() {
+    let mut doc = Document::from_source(SONG);
+    doc.apply("ana", &Edit::Lock(5)).unwrap();
+    doc.apply("ben", &Edit::Insert(4, "New first line".into()))
+        .unwrap();
+    assert_eq!(doc.lock(5), None);
+    assert_eq!(doc.lock(6), Some("ana"));
+    // Ana's stale line number now points at someone else's line.
+    assert_eq!(
+        doc.apply("ana", &Edit::Set(5, "x".into())),
+        Err(CollabError::NotLocked(5))
+    );
+    doc.apply("ana", &Edit::Delete(6)).unwrap();
+    assert_eq!(
+        doc.source(),
+        "title: \"Together\"\n\nVERSE\nNew first line\nFirst line\n"
+    );
+}
+
+#[test]
+fn replaying_the_host_document_rebuilds_it() {
+    let mut host = Document::from_source(SONG);
+    host.apply("ana", &Edit::Lock(3)).unwrap();
+    assert_eq!(host.release("ana"), [Edit::Unlock(3)]);
+
+    let mut copy = Document::default();
+    for message in host.replay("host") {
+        let line = message.to_string();
+        match Message::parse(&line).unwrap() {
+            Message::Edit { by, edit } => copy.apply(&by, &edit).unwrap(),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(copy, host);
+}
+
+#[test]
+fn messages_round_trip_through_text() {
+    for request in [
+        Request::Hello("ana".into()),
+        Request::Edit(Edit::Set(2, "  indented text".into())),
+        Request::Edit(Edit::Insert(1, String::new())),
+        Request::Edit(Edit::Delete(3)),
+        Request::Save,
+    ] {
+        assert_eq!(Request::parse(&request.to_string()), Ok(request));
+    }
+    for message in [
+        Message::Welcome("ana-2".into()),
+        Message::Edit {
+            by: "ben".into(),
+            edit: Edit::Unlock(4),
+        },
+        Message::Error("line 4 is locked by ana".into()),
+        Message::Ok,
+    ] {
+        assert_eq!(Message::parse(&message.to_string()), Ok(message));
+    }
+    assert!(Request::parse("LOCK x").is_err());
+    assert!(Request::parse("LOCK 2 extra").is_err());
+    assert_eq!(clean_name("Ana Lee"), "Ana_Lee");
+    assert_eq!(clean_name(" "), "writer");
+}
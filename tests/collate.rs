@@ -0,0 +1,66 @@
+use lyrics_dsl::collate::Collator;
+use lyrics_dsl::config::Config;
+use std::cmp::Ordering;
+
+fn sorted(locale: &str, words: &[&str]) -> Vec<String> {
+    let mut words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+    Collator::new(locale).sort_by_key(&mut words, |w| w);
+    words
+}
+
+#[test]
+fn accents_and_case_come_after_letters() {
+    assert_eq!(
+        sorted(
+            "en",
+            &["Zebra", "éclair", "Eclair", "eclair", "apple", "Ápple"]
+        ),
+        ["apple", "Ápple", "eclair", "Eclair", "éclair", "Zebra"]
+    );
+    assert_eq!(
+        sorted("en", &["strasse", "straße", "strass"]),
+        ["strass", "strasse", "straße"]
+    );
+    assert_eq!(sorted("en", &["b", "2", "-", "a"]), ["-", "2", "a", "b"]);
+}
+
+#[test]
+fn locales_move_letters_of_their_alphabet() {
+    let words = ["ñu", "nube", "oso"];
+    assert_eq!(sorted("en", &words), ["ñu", "nube", "oso"]);
+    assert_eq!(sorted("es-ES", &words), ["nube", "ñu", "oso"]);
+    assert_eq!(sorted("es", &["ñu", "nz", "o"]), ["nz", "ñu", "o"]);
+    assert_eq!(sorted("en", &["ñu", "nz", "o"]), ["ñu", "nz", "o"]);
+
+    let words = ["öl", "zebra", "åska", "äpple", "apa"];
+    assert_eq!(
+        sorted("sv_SE.UTF-8", &words),
+        ["apa", "zebra", "åska", "äpple", "öl"]
+    );
+    assert_eq!(
+        sorted("en", &words),
+        ["apa", "äpple", "åska", "öl", "zebra"]
+    );
+    assert_eq!(
+        sorted("nb", &["år", "øl", "ærlig", "zoo"]),
+        ["zoo", "ærlig", "øl", "år"]
+    );
+}
+
+#[test]
+fn other_scripts_follow_latin_and_kana_share_letters() {
+    assert_eq!(
+        sorted("ja", &["さくら", "東京", "あめ", "river"]),
+        ["river", "あめ", "さくら", "東京"]
+    );
+    let collator = Collator::default();
+    assert_eq!(collator.compare("カ", "か"), Ordering::Greater);
+    assert_eq!(collator.compare("カ", "き"), Ordering::Less);
+}
+
+#[test]
+fn the_locale_is_read_from_the_project_config() {
+    let config = Config::parse("locale = \"sv\"\n").unwrap();
+    assert_eq!(config.locale.as_deref(), Some("sv"));
+    assert_eq!(Collator::new("sv_SE.UTF-8").language(), "sv");
+}
@@ -0,0 +1,58 @@
+use lyrics_dsl::compare::{compare, CompareError};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const A: &str = "title: \"A\"
+meter: iambic
+
+CHORUS
+Hold on to the night {rhyme: A}
+Hold on to the light {rhyme: A}
+We're never letting go {rhyme: B}
+";
+
+const B: &str = "title: \"B\"
+meter: iambic
+
+VERSE
+Something else entirely here
+
+CHORUS
+Hold me through the burning night {rhyme: A}
+Keep me in the fading light {rhyme: A}
+And never let me go {rhyme: B}
+Never let me go {rhyme: B}
+";
+
+#[test]
+fn chorus_takes_are_compared_line_by_line_and_overall() {
+    let a = resolve(&parse_song(A).unwrap()).song;
+    let b = resolve(&parse_song(B).unwrap()).song;
+    let comparison = compare(&a, &b, Some("chorus")).unwrap();
+    assert_eq!(comparison.rows.len(), 4);
+    let deltas: Vec<_> = comparison.rows.iter().map(|r| r.syllable_delta()).collect();
+    assert_eq!(deltas, [Some(2), Some(2), Some(0), None]);
+    assert!(comparison.rows[0].same_ending());
+    assert_eq!(
+        comparison.rows[3].b.as_ref().unwrap().text,
+        "Never let me go"
+    );
+
+    assert_eq!((comparison.a.lines, comparison.b.lines), (3, 4));
+    assert_eq!(comparison.a.rhyme_density, 67);
+    assert_eq!(comparison.b.rhyme_density, 100);
+    assert_eq!(comparison.a.meter_fit, Some(100));
+    assert_eq!(comparison.b.meter_fit, Some(25));
+    assert!(comparison.a.overall > comparison.b.overall);
+
+    // Without a section the whole songs are compared.
+    assert_eq!(compare(&a, &b, None).unwrap().b.lines, 5);
+    assert_eq!(
+        compare(&a, &b, Some("verse")),
+        Err(CompareError::NoSection {
+            take: 'A',
+            name: "verse".to_string(),
+            labels: "Chorus".to_string(),
+        })
+    );
+}
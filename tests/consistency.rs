@@ -0,0 +1,57 @@
+use lyrics_dsl::analysis::consistency::{inconsistencies, normalized};
+use lyrics_dsl::parser::parse_song;
+
+#[test]
+fn contractions_case_and_punctuation_are_set_aside() {
+    assert_eq!(
+        normalized("We can't stop, we're GONNA run!"),
+        "we cannot stop we are going to run"
+    );
+    assert_eq!(normalized("we can not stop"), "we cannot stop");
+    assert_eq!(normalized("'Cause I don’t"), "because i do not");
+}
+
+#[test]
+fn variants_are_ordered_by_how_often_they_are_written() {
+    let source = "title: \"T\"
+
+CHORUS[1]
+Hold on, hold on
+I won't let the river take you
+
+VERSE[1]
+Hold on to nothing
+I will not let the river take you
+
+CHORUS[2]
+Hold on, hold on
+I won't let the river take you
+
+CHORUS[3]
+hold on hold on
+I won't let the rivers take you
+";
+    let song = parse_song(source).unwrap();
+    let phrases = inconsistencies(&song);
+    let texts: Vec<Vec<(&str, usize)>> = phrases
+        .iter()
+        .map(|p| p.variants.iter().map(|v| (v.text, v.lines.len())).collect())
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            vec![("Hold on, hold on", 2), ("hold on hold on", 1)],
+            vec![
+                ("I won't let the river take you", 2),
+                ("I will not let the river take you", 1),
+                ("I won't let the rivers take you", 1),
+            ],
+        ]
+    );
+}
+
+#[test]
+fn short_lines_must_match_word_for_word() {
+    let source = "title: \"T\"\n\nVERSE[1]\nOh my love\n\nVERSE[2]\nOh my lord\n";
+    assert!(inconsistencies(&parse_song(source).unwrap()).is_empty());
+}
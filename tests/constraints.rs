@@ -0,0 +1,71 @@
+use lyrics_dsl::constraints::{constraints, ConstraintsError};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Gaps\"
+meter: iambic
+
+VERSE[1]
+I walked along the river {rhyme: A}
+The water cold and grey {rhyme: B}
+I heard the current shiver {rhyme: A}
+It carried me away {rhyme: B}
+
+VERSE[2]
+The night came down in silver {rhyme: A}
+The stars began to play {rhyme: B}
+<tbd: rhyme with \"silver\">
+And ______ the day {rhyme: B}
+";
+
+#[test]
+fn a_missing_line_takes_its_constraints_from_the_verse_it_parallels() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let banned = vec!["cold".to_string()];
+    let found = constraints(&song, SONG, 13, &banned).unwrap();
+    assert_eq!(found.section, "Verse 2");
+    assert_eq!(found.hints, ["rhyme with \"silver\""]);
+
+    let syllables = found.syllables.as_ref().unwrap();
+    assert_eq!((syllables.target, syllables.min, syllables.max), (7, 6, 8));
+    assert_eq!(syllables.basis, "line 3 of Verse 1");
+    let rhyme = found.rhyme.as_ref().unwrap();
+    assert_eq!(
+        (rhyme.scheme, rhyme.with.as_slice()),
+        ('A', &["silver".to_string()][..])
+    );
+    assert_eq!(found.stress.as_ref().unwrap().pattern, "x/x/x/x");
+
+    assert_eq!(
+        constraints(&song, SONG, 4, &banned),
+        Err(ConstraintsError::NotALine(4))
+    );
+}
+
+#[test]
+fn candidates_are_checked_against_every_constraint() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let banned = vec!["cold".to_string(), "in a".to_string()];
+    let found = constraints(&song, SONG, 13, &banned).unwrap();
+
+    let verdict = found.check("The moon came up to quiver");
+    assert!(verdict.ok, "{:?}", verdict);
+
+    let failed = |candidate: &str| -> Vec<&'static str> {
+        found
+            .check(candidate)
+            .checks
+            .into_iter()
+            .filter(|c| !c.ok)
+            .map(|c| c.constraint)
+            .collect()
+    };
+    assert_eq!(failed("I felt the cold wind quiver"), ["banned"]);
+    assert_eq!(failed("The moon was In A quiver"), ["banned"]);
+    assert_eq!(failed("The moon came up to silver"), ["rhyme"]);
+    assert_eq!(
+        failed("The moon came up to ____"),
+        ["complete", "syllables", "rhyme"]
+    );
+    assert_eq!(failed("Quiver"), ["syllables", "stress"]);
+}
@@ -0,0 +1,83 @@
+use lyrics_dsl::ast::{Note, NoteKind};
+use lyrics_dsl::cowrite::{merge, pack, suggestions, CowriteError};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Gaps\"
+meter: iambic
+
+VERSE[1]
+I walked along the river {rhyme: A}
+The water cold and grey {rhyme: B}
+I heard the current shiver {rhyme: A}
+It carried me away {rhyme: B}
+
+VERSE[2]
+The night came down in silver {rhyme: A}
+The stars began to play {rhyme: B}
+<tbd: rhyme with \"silver\">
+And ______ the day {rhyme: B}
+
+REPEAT VERSE[1]
+";
+
+#[test]
+fn the_pack_holds_the_structure_style_and_open_lines() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let pack = pack(&song, SONG, &[]);
+    let labels: Vec<_> = pack.structure.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, ["Verse 1", "Verse 2", "Verse 1"]);
+    assert_eq!(pack.structure[2].repeats.as_deref(), Some("VERSE[1]"));
+    assert!(pack.structure[2].lines.is_empty());
+    assert_eq!(pack.style.lines, 8);
+    assert_eq!(pack.style.meter.as_deref(), Some("iambic"));
+    assert_eq!(
+        pack.style.rhyme_schemes,
+        [
+            ("Verse 1".to_string(), "ABAB".to_string()),
+            ("Verse 2".to_string(), "AB-B".to_string()),
+        ]
+    );
+    let open: Vec<_> = pack.open.iter().map(|c| c.line).collect();
+    assert_eq!(open, [13, 14]);
+
+    let markdown = pack.to_markdown();
+    assert!(markdown.contains("  13  ____\n"), "{}", markdown);
+    assert!(markdown.contains("[Verse 1: repeats VERSE[1]]\n```"));
+    assert!(markdown.contains("- rhymes with silver"));
+    assert!(markdown.ends_with("as many alternatives per line as you like.\n"));
+}
+
+#[test]
+fn suggestions_are_merged_as_idea_notes_on_their_lines() {
+    let reply = "Sure!\n```json\n{\"suggestions\": [\
+        {\"line\": 13, \"text\": \"The moon came up to quiver\"},\
+        {\"line\": 13, \"text\": \"I felt the cold\"},\
+        {\"line\": 99, \"text\": \"Nowhere\"}]}\n```\n";
+    let found = suggestions(reply).unwrap();
+    assert_eq!(found.len(), 3);
+    assert!(matches!(
+        suggestions("{\"suggestions\": []}"),
+        Err(CowriteError::NoSuggestions)
+    ));
+
+    let mut song = parse_song(SONG).unwrap();
+    let merged = merge(&mut song, SONG, &found, &[]);
+    assert_eq!((merged.added, merged.missing), (2, 1));
+    assert_eq!(merged.unknown, ["line 99"]);
+    let notes = &song.sections[1].lines[2].notes;
+    assert!(notes.iter().all(|n| n.kind == NoteKind::Idea));
+    assert_eq!(notes[0].text, "suggested: The moon came up to quiver");
+    assert_eq!(
+        notes[1].text,
+        "suggested (misses syllables, rhyme): I felt the cold"
+    );
+
+    // The notes survive a round trip, and merging again adds nothing.
+    let source = format_song(&song);
+    let mut again = parse_song(&source).unwrap();
+    let texts = |notes: &[Note]| -> Vec<String> { notes.iter().map(|n| n.text.clone()).collect() };
+    assert_eq!(texts(&again.sections[1].lines[2].notes), texts(notes));
+    assert_eq!(merge(&mut again, &source, &found, &[]).added, 0);
+}
@@ -0,0 +1,89 @@
+use lyrics_dsl::ast::CreditKind;
+use lyrics_dsl::codes;
+use lyrics_dsl::credits::{citations, credited_lines, ranges};
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Borrowed\"
+credits:
+  interpolation \"Stand By Me\" {writers: \"Ben E. King, Jerry Leiber, Mike Stoller\", year: 1961, lines: *hook}
+  sample \"Amen, Brother\" {artist: \"The Winstons\", lines: *drop..*last}
+
+VERSE[1]
+First line &drop
+Second line
+Third line &last
+
+CHORUS &hook
+Hold on
+Stand by
+
+REPEAT CHORUS
+";
+
+#[test]
+fn credits_are_parsed_and_formatted_back_as_written() {
+    let song = parse_song(SONG).unwrap();
+    assert_eq!(song.credits.len(), 2);
+    let sample = &song.credits[1];
+    assert_eq!(sample.kind, CreditKind::Sample);
+    assert_eq!(sample.work, "Amen, Brother");
+    assert_eq!(sample.artist.as_deref(), Some("The Winstons"));
+    assert_eq!(song.credits[0].year.as_deref(), Some("1961"));
+
+    let formatted = format_song(&song);
+    assert_eq!(formatted, SONG);
+    assert!(resolve(&song).diagnostics.is_empty());
+}
+
+#[test]
+fn credited_lines_follow_the_lines_wherever_they_are_sung() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    assert_eq!(credited_lines(&song, &song.credits[0]), [4, 5, 6, 7]);
+    assert_eq!(credited_lines(&song, &song.credits[1]), [1, 2, 3]);
+    assert_eq!(ranges(&[1, 2, 3, 7, 9, 10]), "1–3, 7, 9–10");
+    assert_eq!(
+        citations(&song),
+        [
+            "Contains an interpolation of \"Stand By Me\", written by Ben E. King, \
+             Jerry Leiber, Mike Stoller (1961) (lines 4–7)",
+            "Contains a sample of \"Amen, Brother\" as performed by The Winstons (lines 1–3)",
+        ]
+    );
+}
+
+#[test]
+fn credit_anchors_must_be_defined_and_sung() {
+    let diagnostics = |source: &str| resolve(&parse_song(source).unwrap()).diagnostics;
+    let undefined = diagnostics(
+        "title: \"T\"\ncredits:\n  sample \"Old\" {lines: *nowhere}\n\nVERSE\nOne &there\n",
+    );
+    assert_eq!(undefined.len(), 1);
+    assert_eq!(
+        undefined[0].code.map(|c| c.id),
+        Some(codes::UNDEFINED_REFERENCE.id)
+    );
+    let in_macro = diagnostics(
+        "title: \"T\"\ncredits:\n  sample \"Old\" {lines: *oh}\n\nMACRO tag\nOh &oh\n\nVERSE\nUSE tag\n",
+    );
+    assert_eq!(in_macro.len(), 1);
+    assert_eq!(in_macro[0].code.map(|c| c.id), Some(codes::CREDIT_LINES.id));
+}
+
+#[test]
+fn sheets_close_with_the_credits_and_the_review_copy_notes_each_line() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let text = String::from_utf8(exporter("txt").unwrap().export(&song).unwrap()).unwrap();
+    assert!(text.ends_with(
+        "Stand by\n\nContains an interpolation of \"Stand By Me\", written by Ben E. King, \
+         Jerry Leiber, Mike Stoller (1961) (lines 4–7)\nContains a sample of \"Amen, Brother\" \
+         as performed by The Winstons (lines 1–3)\n"
+    ));
+    let docx = exporter("review-docx").unwrap().export(&song).unwrap();
+    let docx = String::from_utf8_lossy(&docx);
+    let note =
+        "<w:i/></w:rPr><w:t xml:space=\"preserve\">Interpolates &quot;Stand By Me&quot;</w:t>";
+    assert_eq!(docx.matches(note).count(), 4);
+}
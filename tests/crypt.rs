@@ -0,0 +1,136 @@
+use lyrics_dsl::crypt::ed25519;
+use lyrics_dsl::crypt::sha256::{hmac, pbkdf2, sha256};
+use lyrics_dsl::crypt::{
+    is_locked, lock_with_iterations, locked_with, open, seal, unlock, CryptError, Key, KeyKind,
+    MAX_ITERATIONS,
+};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn sha256_hmac_and_pbkdf2_match_published_vectors() {
+    assert_eq!(
+        hex(&sha256(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // RFC 4231, test case 2.
+    assert_eq!(
+        hex(&hmac(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    // RFC 7914, section 11.
+    let mut out = [0u8; 64];
+    pbkdf2(b"passwd", b"salt", 1, &mut out);
+    assert_eq!(
+        hex(&out),
+        "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+         49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+    );
+}
+
+#[test]
+fn chacha20_poly1305_matches_rfc_8439() {
+    let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+only one tip for the future, sunscreen would be it.";
+    let aad = [
+        0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7,
+    ];
+    let key: [u8; 32] = std::array::from_fn(|i| 0x80 + i as u8);
+    let nonce = [7, 0, 0, 0, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+
+    let sealed = seal(&key, &nonce, &aad, plaintext);
+    assert_eq!(hex(&sealed[..16]), "d31a8d34648e60db7b86afbc53ef7ec2");
+    assert_eq!(
+        hex(&sealed[sealed.len() - 16..]),
+        "1ae10b594f09e26a7e902ecbd0600691"
+    );
+    assert_eq!(open(&key, &nonce, &aad, &sealed).unwrap(), plaintext);
+
+    let mut altered = sealed.clone();
+    altered[3] ^= 1;
+    assert_eq!(open(&key, &nonce, &aad, &altered), None);
+    assert_eq!(open(&key, &nonce, b"other", &sealed), None);
+}
+
+fn unhex<const N: usize>(hex: &str) -> [u8; N] {
+    std::array::from_fn(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+}
+
+#[test]
+fn ed25519_matches_rfc_8032() {
+    // Section 7.1, tests 1 and 2.
+    let cases: [(&str, &str, &[u8], &str); 2] = [
+        (
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            b"",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            &[0x72],
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+    ];
+    for (secret, public, message, signature) in cases {
+        let secret = unhex(secret);
+        let public = unhex(public);
+        assert_eq!(ed25519::public_key(&secret), public);
+        let signed = ed25519::sign(&secret, message);
+        assert_eq!(hex(&signed), signature);
+        assert!(ed25519::verify(&public, message, &signed));
+
+        let mut altered = signed;
+        altered[5] ^= 1;
+        assert!(!ed25519::verify(&public, message, &altered));
+        assert!(!ed25519::verify(&public, b"other", &signed));
+    }
+}
+
+#[test]
+fn locked_files_unlock_only_with_their_key() {
+    let song = b"title: \"Unreleased\"\n\nVERSE\nSecret line\n";
+    let key = Key::Passphrase("correct horse".into());
+    let locked = lock_with_iterations(song, &key, 1000).unwrap();
+    assert!(is_locked(&locked));
+    assert!(!is_locked(song));
+    assert_eq!(locked_with(&locked), Some(KeyKind::Passphrase));
+    assert!(!locked.windows(6).any(|w| w == b"Secret"));
+    assert_eq!(unlock(&locked, &key).unwrap(), song);
+
+    // Salt and nonce are fresh each time.
+    assert_ne!(lock_with_iterations(song, &key, 1000).unwrap(), locked);
+
+    let wrong = Key::Passphrase("battery staple".into());
+    assert_eq!(unlock(&locked, &wrong), Err(CryptError::WrongKey));
+    let keyfile = Key::Keyfile(vec![7; 32]);
+    assert_eq!(
+        unlock(&locked, &keyfile),
+        Err(CryptError::WrongKind(KeyKind::Passphrase))
+    );
+    assert_eq!(unlock(song, &key), Err(CryptError::NotLocked));
+    assert_eq!(
+        unlock(&locked[..locked.len() - 20], &key),
+        Err(CryptError::WrongKey)
+    );
+
+    let with_file = lock_with_iterations(song, &keyfile, 1).unwrap();
+    assert_eq!(unlock(&with_file, &keyfile).unwrap(), song);
+}
+
+#[test]
+fn round_counts_out_of_range_are_refused_before_deriving() {
+    let key = Key::Passphrase("correct horse".into());
+    let locked = lock_with_iterations(b"title: \"T\"\n", &key, 1000).unwrap();
+    // The round count follows the magic line and the key kind.
+    let at = locked.iter().position(|&b| b == b'\n').unwrap() + 2;
+    assert_eq!(&locked[at..at + 4], &1000u32.to_be_bytes());
+    for rounds in [0, MAX_ITERATIONS + 1, u32::MAX] {
+        let mut tampered = locked.clone();
+        tampered[at..at + 4].copy_from_slice(&rounds.to_be_bytes());
+        assert_eq!(unlock(&tampered, &key), Err(CryptError::Damaged));
+    }
+}
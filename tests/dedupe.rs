@@ -0,0 +1,81 @@
+use lyrics_dsl::dedupe::{clusters, jaccard, looks_like_copy, Entry};
+
+const NIGHT: &[&str] = &[
+    "Hold on to the night we never knew",
+    "We hold the line until the morning comes",
+    "And every word we said is coming true",
+];
+
+fn entry(path: &str, lines: &[&str], metadata: usize, modified: u64) -> Entry {
+    Entry::new(path, lines.iter().copied(), 4, metadata, modified)
+}
+
+#[test]
+fn copies_cluster_and_the_original_is_suggested() {
+    let mut draft = NIGHT.to_vec();
+    draft[2] = "And every word we said is coming home";
+    let entries = vec![
+        entry("night (1).lyr", NIGHT, 2, 300),
+        entry("day.lyr", &["Something else entirely here today"], 1, 0),
+        entry("old/night-draft.lyr", &draft, 2, 200),
+        entry("night.lyr", NIGHT, 2, 100),
+        entry("empty.lyr", &[], 0, 0),
+        entry("empty copy.lyr", &[], 0, 0),
+    ];
+    let found = clusters(&entries, 0.8);
+    assert_eq!(found.len(), 1);
+    let members: Vec<(&str, u32)> = found[0]
+        .members
+        .iter()
+        .map(|m| (m.path.as_str(), (m.similarity * 100.0).round() as u32))
+        .collect();
+    assert_eq!(
+        members,
+        [
+            ("night.lyr", 100),
+            ("night (1).lyr", 100),
+            ("old/night-draft.lyr", 91)
+        ]
+    );
+    // A stricter threshold leaves the draft out.
+    assert_eq!(clusters(&entries, 0.95)[0].members.len(), 2);
+}
+
+#[test]
+fn the_more_complete_draft_is_kept() {
+    let mut longer = NIGHT.to_vec();
+    longer.push("Hold on to the night we never knew");
+    longer.push("Until the light is breaking through");
+    let entries = vec![entry("a.lyr", NIGHT, 3, 500), entry("b.lyr", &longer, 1, 0)];
+    let found = clusters(&entries, 0.7);
+    assert_eq!(found[0].canonical().path, "b.lyr");
+}
+
+#[test]
+fn names_that_read_like_copies() {
+    for copy in [
+        "night copy.lyr",
+        "dir/night (2).lyr",
+        "night-old.lyr",
+        "night.bak.lyr",
+        "Night_Backup.lyr",
+    ] {
+        assert!(looks_like_copy(copy), "{}", copy);
+    }
+    for original in [
+        "night.lyr",
+        "copyright.lyr",
+        "old/night.lyr",
+        "night (live).lyr",
+    ] {
+        assert!(!looks_like_copy(original), "{}", original);
+    }
+}
+
+#[test]
+fn jaccard_is_the_shared_share() {
+    let a = entry("a", &["one two three four five"], 0, 0).shingles;
+    let b = entry("b", &["one two three four six"], 0, 0).shingles;
+    assert_eq!(jaccard(&a, &b), 1.0 / 3.0);
+    assert_eq!(jaccard(&a, &a), 1.0);
+}
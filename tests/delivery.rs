@@ -0,0 +1,50 @@
+use lyrics_dsl::delivery::{matches, DeliveryError, Manifest, PublicKey, SigningKey};
+
+#[test]
+fn manifests_read_back_and_accept_sha256sum_output() {
+    let mut manifest = Manifest::new();
+    manifest.add("song.pdf", b"%PDF");
+    manifest.add("song lyrics.txt", b"");
+    let text = manifest.to_text();
+    assert_eq!(
+        text.lines().nth(1),
+        Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  song lyrics.txt")
+    );
+    assert_eq!(Manifest::parse(&text), Ok(manifest.clone()));
+    assert!(matches(&manifest.entries[0], b"%PDF"));
+    assert!(!matches(&manifest.entries[0], b"%PDF-1.4"));
+
+    let binary = text.replacen("  song.pdf", " *song.pdf", 1);
+    assert_eq!(Manifest::parse(&binary), Ok(manifest));
+    assert_eq!(
+        Manifest::parse("abc  song.pdf\n"),
+        Err(DeliveryError::BadLine(1))
+    );
+}
+
+#[test]
+fn signatures_verify_only_for_the_signed_manifest_and_key() {
+    let key = SigningKey::from_seed([7; 32]);
+    assert_eq!(SigningKey::parse(&key.to_text()), Ok(key.clone()));
+    let public = PublicKey::parse(&key.public().to_text()).unwrap();
+    assert_eq!(public, key.public());
+
+    let manifest = b"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  a.txt\n";
+    let signature = key.sign(manifest);
+    assert_eq!(public.verify(manifest, &signature), Ok(()));
+    assert_eq!(
+        public.verify(b"something else", &signature),
+        Err(DeliveryError::BadSignature)
+    );
+
+    let other = SigningKey::from_seed([8; 32]).public();
+    assert_eq!(
+        other.verify(manifest, &signature),
+        Err(DeliveryError::OtherKey)
+    );
+    assert_eq!(
+        public.verify(manifest, "not a signature"),
+        Err(DeliveryError::Malformed("signature"))
+    );
+    assert!(PublicKey::parse(&key.to_text()).is_err());
+}
@@ -0,0 +1,48 @@
+use lyrics_dsl::crypt::{self, Key};
+use lyrics_dsl::draft::{DraftError, DraftStore};
+
+#[test]
+fn drafts_are_numbered_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = DraftStore::new(dir.path().join("song.lyr"));
+    assert!(store.list().unwrap().is_empty());
+
+    let first = store
+        .save("VERSE\nOne\n", "first try", "2026-01-01 10:00 UTC")
+        .unwrap();
+    let second = store
+        .save(
+            "VERSE\nTwo\n",
+            "tried new\tbridge\n",
+            "2026-01-02 10:00 UTC",
+        )
+        .unwrap();
+    assert_eq!((first.number, second.number), (1, 2));
+    assert_eq!(second.message, "tried new bridge");
+
+    let drafts = store.list().unwrap();
+    assert_eq!(drafts, [first, second]);
+    assert_eq!(store.load(2).unwrap(), "VERSE\nTwo\n");
+    assert!(matches!(store.load(3), Err(DraftError::NotFound(3))));
+}
+
+#[test]
+fn drafts_of_a_locked_song_are_locked() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = Key::Keyfile(b"a key file".to_vec());
+    let store = DraftStore::new(dir.path().join("song.lyr")).locked(key);
+    store
+        .save("VERSE\nSecret words\n", "first try", "2026-01-01 10:00 UTC")
+        .unwrap();
+
+    let saved = std::fs::read(dir.path().join("song.lyr").join("1.lyr")).unwrap();
+    assert!(crypt::is_locked(&saved));
+    assert!(!String::from_utf8_lossy(&saved).contains("Secret"));
+    assert_eq!(store.load(1).unwrap(), "VERSE\nSecret words\n");
+
+    let unkeyed = DraftStore::new(dir.path().join("song.lyr"));
+    assert!(matches!(
+        unkeyed.load(1),
+        Err(DraftError::Crypt { number: 1, .. })
+    ));
+}
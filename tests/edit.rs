@@ -0,0 +1,89 @@
+use lyrics_dsl::edit::{
+    delete_line, delete_section, insert_line, insert_section, move_section, EditError, Place,
+};
+
+const SONG: &str = "title: \"Edit\"
+
+VERSE[1]
+Walking down the river {chord: C, G}
+Under the silver moon
+  + (moon)
+
+CHORUS
+Oh my love
+Hold on
+
+REPEAT CHORUS
+";
+
+#[test]
+fn sections_move_and_go_without_touching_the_rest() {
+    let moved = move_section(SONG, 2, Place::Before(1)).unwrap();
+    assert_eq!(
+        moved,
+        "title: \"Edit\"
+
+CHORUS
+Oh my love
+Hold on
+
+VERSE[1]
+Walking down the river {chord: C, G}
+Under the silver moon
+  + (moon)
+
+REPEAT CHORUS
+"
+    );
+    assert_eq!(move_section(&moved, 1, Place::Before(2)).unwrap(), moved);
+    assert_eq!(move_section(SONG, 1, Place::After(2)).unwrap(), moved);
+
+    assert_eq!(
+        move_section(SONG, 3, Place::Before(1)),
+        Err(EditError::Breaks("no CHORUS before this REPEAT".into()))
+    );
+    assert_eq!(
+        delete_section(SONG, 4),
+        Err(EditError::NoSection { index: 4, count: 3 })
+    );
+    assert!(delete_section(SONG, 3)
+        .unwrap()
+        .ends_with("Oh my love\nHold on\n"));
+}
+
+#[test]
+fn sections_are_inserted_with_their_lines() {
+    let lines = vec!["Far away".to_string(), "Still here".to_string()];
+    let inserted = insert_section(SONG, "BRIDGE", &lines, Some(Place::After(2))).unwrap();
+    assert!(inserted.contains("Hold on\n\nBRIDGE\nFar away\nStill here\n\nREPEAT CHORUS\n"));
+    let appended = insert_section(SONG, "OUTRO", &lines[..1], None).unwrap();
+    assert!(appended.ends_with("REPEAT CHORUS\n\nOUTRO\nFar away\n"));
+    assert_eq!(
+        insert_section(SONG, "Bridge", &lines, None),
+        Err(EditError::NotAHeader("Bridge".into()))
+    );
+}
+
+#[test]
+fn lines_are_inserted_and_deleted_with_their_harmony() {
+    let inserted = insert_line(SONG, "Hold on {chord: G}", Place::After(5)).unwrap();
+    assert!(inserted.contains("  + (moon)\nHold on {chord: G}\n\nCHORUS"));
+    let inserted = insert_line(SONG, "First", Place::Before(4)).unwrap();
+    assert!(inserted.contains("VERSE[1]\nFirst\nWalking"));
+    assert_eq!(
+        insert_line(SONG, "CHORUS", Place::Before(4)),
+        Err(EditError::NotOneLine("CHORUS".into()))
+    );
+
+    let deleted = delete_line(SONG, 5).unwrap();
+    assert!(deleted.contains("{chord: C, G}\n\nCHORUS"));
+    assert_eq!(delete_line(SONG, 3), Err(EditError::NotALine(3)));
+    let single = delete_line(SONG, 9).unwrap();
+    assert_eq!(delete_line(&single, 9), Err(EditError::LastLine(9)));
+}
+
+#[test]
+fn a_repeat_is_inserted_without_lines() {
+    let inserted = insert_section(SONG, "REPEAT VERSE[1]", &[], Some(Place::Before(3))).unwrap();
+    assert!(inserted.ends_with("Hold on\n\nREPEAT VERSE[1]\n\nREPEAT CHORUS\n"));
+}
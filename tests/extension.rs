@@ -0,0 +1,94 @@
+use lyrics_dsl::diagnostic::Severity;
+use lyrics_dsl::extension::{check, Extension, ExtensionError};
+use lyrics_dsl::parser::parse_song;
+
+const EXTRA: &str = "\
+mood    = { \"wistful\" | \"angry\" | ^\"tender\" }
+// Built on the base grammar's `number`
+scene   = @{ \"act\" ~ ASCII_DIGIT ~ \".\" ~ number }
+cues    = { cue ~ (\",\" ~ cue)* }
+cue     = @{ ASCII_ALPHA+ }
+WHITESPACE = _{ \" \" }
+";
+
+#[test]
+fn extra_rules_match_whole_values_and_reuse_base_rules() {
+    let extension = Extension::parse(EXTRA).unwrap();
+    assert!(extension.defines("scene"));
+    assert!(!extension.defines("number"));
+    assert_eq!(
+        extension.rules()[1].comment.as_deref(),
+        Some("Built on the base grammar's `number`")
+    );
+
+    assert!(extension.matches("mood", "wistful"));
+    assert!(extension.matches("mood", "TENDER"));
+    assert!(!extension.matches("mood", "wistful!"));
+    assert!(extension.matches("scene", "act2.15"));
+    assert!(!extension.matches("scene", "act 2.15"));
+    // Implicit whitespace applies in non-atomic rules only.
+    assert!(extension.matches("cues", "lights , smoke,drums"));
+    assert!(!extension.matches("cue", "lig hts"));
+    assert!(extension.matches("chord", "Am7"));
+}
+
+#[test]
+fn bad_extensions_are_rejected() {
+    assert_eq!(
+        Extension::parse("number = { \"1\" }").unwrap_err(),
+        ExtensionError::Redefined("number".into())
+    );
+    assert_eq!(
+        Extension::parse("a = { \"x\" }\na = { \"y\" }").unwrap_err(),
+        ExtensionError::Duplicate("a".into())
+    );
+    assert_eq!(
+        Extension::parse("mood = { feeling }").unwrap_err(),
+        ExtensionError::Undefined {
+            rule: "mood".into(),
+            name: "feeling".into()
+        }
+    );
+    assert!(matches!(
+        Extension::parse("echo = { PUSH(\"a\") ~ POP }"),
+        Err(ExtensionError::Unsupported { .. })
+    ));
+    assert!(matches!(
+        Extension::parse("mood = { \"a\" "),
+        Err(ExtensionError::Syntax(_))
+    ));
+}
+
+#[test]
+fn annotations_are_checked_against_their_rules() {
+    let song = parse_song(
+        "title: \"T\"\n\nVERSE\nOne {rhyme: A, mood: wistful}\nTwo {mood: happy}\nThree {rhym: B}\n",
+    )
+    .unwrap();
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.rhyme, Some('A'));
+    assert_eq!(line.annotations[0].name, "mood");
+    assert_eq!(line.annotations[0].value, "wistful");
+
+    let extension = Extension::parse(EXTRA).unwrap();
+    let found: Vec<(Severity, String)> = check(&song, Some(&extension))
+        .into_iter()
+        .map(|d| (d.severity, d.message))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                Severity::Error,
+                "`happy` does not match the `mood` rule of extra.pest".to_string()
+            ),
+            (
+                Severity::Warning,
+                "unknown annotation `rhym`; define a `rhym` rule in extra.pest to check it"
+                    .to_string()
+            ),
+        ]
+    );
+    // Without an extension every annotation is unknown.
+    assert_eq!(check(&song, None).len(), 3);
+}
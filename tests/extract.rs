@@ -0,0 +1,68 @@
+use lyrics_dsl::extract::{extract, ExtractError, Selection};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Extract\"
+artist: \"The Cuts\"
+$place = \"river\"
+
+VERSE[1]
+Walking down the $place {chord: C, G}
+Under the silver moon
+
+CHORUS &hook
+Oh my love
+Hold on
+
+BRIDGE
+Far away
+Still here
+
+REPEAT CHORUS
+";
+
+fn fragment(selection: &Selection) -> Result<String, ExtractError> {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    extract(&song, SONG, selection).map(|s| format_song(&s))
+}
+
+#[test]
+fn a_section_stands_on_its_own_with_the_song_metadata() {
+    let text = fragment(&Selection::Section("verse".into())).unwrap();
+    assert_eq!(
+        text,
+        "title: \"Extract\"\nartist: \"The Cuts\"\n\nVERSE[1]\nWalking down the river {chord: C, G}\nUnder the silver moon\n"
+    );
+    let song = parse_song(&text).unwrap();
+    assert!(resolve(&song).diagnostics.is_empty());
+
+    let text = fragment(&Selection::Section("chorus".into())).unwrap();
+    assert!(text.ends_with("\nCHORUS\nOh my love\nHold on\n"));
+    assert_eq!(
+        fragment(&Selection::Section("outro".into())),
+        Err(ExtractError::NoSection {
+            name: "outro".into(),
+            labels: "Verse 1, Chorus, Bridge".into(),
+        })
+    );
+}
+
+#[test]
+fn a_line_range_keeps_the_sections_it_crosses() {
+    assert_eq!(Selection::lines("7..14"), Ok(Selection::Lines(7, 14)));
+    assert_eq!(Selection::lines("7-14"), Ok(Selection::Lines(7, 14)));
+    assert_eq!(Selection::lines("7..=14"), Ok(Selection::Lines(7, 14)));
+    assert!(Selection::lines("14..7").is_err());
+
+    let text = fragment(&Selection::Lines(7, 14)).unwrap();
+    assert!(text.ends_with(
+        "\nVERSE[1]\nUnder the silver moon\n\nCHORUS\nOh my love\nHold on\n\nBRIDGE\nFar away\n"
+    ));
+    let text = fragment(&Selection::Lines(17, 17)).unwrap();
+    assert!(text.ends_with("\nCHORUS\nOh my love\nHold on\n"));
+    assert_eq!(
+        fragment(&Selection::Lines(4, 4)),
+        Err(ExtractError::NoLines { first: 4, last: 4 })
+    );
+}
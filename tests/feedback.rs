@@ -0,0 +1,75 @@
+use lyrics_dsl::export::{with_sheet_options, SheetOptions};
+use lyrics_dsl::feedback::{Comment, Comments};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Notes\"
+
+VERSE {id: v1}
+Borrowed from the radio {id: gt2naj}
+  + radio {id: h4rm0n}
+Mine alone {id: k5nyrd}
+";
+
+const COMMENTS: &str = r#"{
+  "gt2naj": [{"author": "Legal", "text": "Too close to the original?", "date": "2026-10-14"}],
+  "h4rm0n": ["Drop this", {"text": "Keep it"}],
+  "k5nyrd": "Lovely",
+  "gone42": {"author": "Ana", "text": "Was this cut?"}
+}"#;
+
+#[test]
+fn comments_may_be_a_string_a_comment_or_a_list_of_either() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let comments = Comments::parse(COMMENTS).unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(
+        comments.on(&lines[0]),
+        [Comment {
+            author: Some("Legal".into()),
+            text: "Too close to the original?".into(),
+            date: Some("2026-10-14".into()),
+        }]
+    );
+    assert_eq!(
+        comments.on(&lines[0])[0].note(),
+        "Legal: Too close to the original?"
+    );
+    let notes: Vec<_> = comments
+        .on(&lines[0].harmony[0])
+        .iter()
+        .map(Comment::note)
+        .collect();
+    assert_eq!(notes, ["Drop this", "Keep it"]);
+    assert_eq!(comments.on(&lines[1])[0].note(), "Lovely");
+
+    let orphans = comments.orphans(&song);
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].0, "gone42");
+    assert!(Comments::parse("[1, 2]").is_err());
+}
+
+#[test]
+fn html_and_pdf_set_comments_in_the_margin() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let options = SheetOptions {
+        comments: Comments::parse(COMMENTS).unwrap(),
+        ..SheetOptions::default()
+    };
+    let html = with_sheet_options("html", options.clone())
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<span class=\"comment\">Legal: Too close to the original?</span>"));
+    assert!(html.contains("<span class=\"comment\">Lovely</span>"));
+    assert!(html.contains("span.comment {"));
+    assert!(!html.contains("Was this cut?"));
+
+    let pdf = with_sheet_options("pdf", options)
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    assert!(pdf.windows(6).any(|w| w == b"Lovely"));
+    assert!(pdf.windows(13).any(|w| w == b"(Legal: Too c"));
+}
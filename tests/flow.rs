@@ -0,0 +1,46 @@
+use lyrics_dsl::analysis::flow::flow;
+use lyrics_dsl::analysis::meter;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SOURCE: &str = "title: \"Flow\"
+tempo: 90
+tempo: 120 @ 0:30.00
+time: 4/4
+
+VERSE[1]
+Yo | I came to the mic · with a rhyme · and a reason · to stay | {timing: 0:01.00}
+Just a line with no beats
+Col·lecció | of words · · in a row {timing: 0:31.00}
+";
+
+#[test]
+fn beats_count_the_syllables_up_to_the_next_marker() {
+    let song = resolve(&parse_song(SOURCE).unwrap()).song;
+    let flows = flow(&song);
+    assert_eq!(flows.len(), 2);
+
+    let first = &flows[0];
+    let counts: Vec<usize> = first.beats.iter().map(|b| b.syllables).collect();
+    assert_eq!(counts, [1, 5, 3, 4, 2]);
+    assert!(first.beats[0].pickup && first.beats[1].downbeat);
+    assert!(!first.beats[2].downbeat);
+    assert_eq!(first.beats[1].words, "I came to the mic");
+    assert_eq!(first.syllables_per_beat, 3.5);
+    assert_eq!(first.syllables_per_second, 5.25);
+    assert_eq!(first.subdivision(), Some("sixteenths"));
+
+    // The empty beat is a rest, and the later tempo applies.
+    let second = &flows[1];
+    assert_eq!(second.text, "Col·lecció of words in a row");
+    let counts: Vec<usize> = second.beats.iter().map(|b| b.syllables).collect();
+    assert_eq!(counts, [3, 2, 0, 3]);
+    assert_eq!(second.bpm, 120.0);
+    assert_eq!(second.syllables_per_beat, 1.67);
+}
+
+#[test]
+fn flow_lines_are_not_held_to_the_bar_capacity() {
+    let song = resolve(&parse_song(SOURCE).unwrap()).song;
+    assert!(meter::check(&song).is_empty());
+}
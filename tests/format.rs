@@ -0,0 +1,197 @@
+use lyrics_dsl::ast::Value;
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::format::{escape_text, format_song};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::selftest::SAMPLES;
+use lyrics_dsl::semantic::resolve;
+
+const RICH: &str = "title: \"Marks\"
+time: 6/8
+tempo: 96
+tempo: 120 @ 1:02.005
+$who = \"my dear\"
+
+MACRO tag
+Oh oh &oh
+
+VERSE[1] &first {mood: \"calm\", energy: 2}
+| ${who}~ lo~~ve | you{hold:1.5s} | {rhyme: A, chord: C, Amin, timing: 0:01.00}
+Fire{/ˈfaɪ.ə/} in the hall &hall {stress: /x/, mood: hushed and low, author: Sam}
+  + in the ha~ll {chord: F}
+  + (hall) !! idea: an octave up
+USE tag
+
+Price is \\$5 for \\{this\\} \\| that \\&more
+
+CHORUS
+USE *hall
+
+REPEAT VERSE[1] {energy: 3}
+";
+
+fn json(source: &str) -> String {
+    let song = resolve(&parse_song(source).unwrap()).song;
+    String::from_utf8(exporter("json").unwrap().export(&song).unwrap()).unwrap()
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let mut sources: Vec<&str> = SAMPLES.iter().map(|s| s.source).collect();
+    sources.push(RICH);
+    for source in sources {
+        let once = format_song(&parse_song(source).unwrap());
+        let twice = format_song(&parse_song(&once).unwrap());
+        assert_eq!(once, twice);
+    }
+}
+
+#[test]
+fn sustains_stay_inside_their_word() {
+    let source = "title: \"T\"\n\nVERSE\nO lo~~~ve me~ ten~~der\n";
+    let formatted = format_song(&parse_song(source).unwrap());
+    assert!(
+        formatted.contains("O lo~~~ve me~ ten~~der\n"),
+        "{}",
+        formatted
+    );
+    assert_eq!(json(source), json(&formatted));
+}
+
+#[test]
+fn formatting_preserves_meaning() {
+    let formatted = format_song(&parse_song(RICH).unwrap());
+    assert_eq!(json(RICH), json(&formatted));
+    assert!(
+        formatted.contains(
+            "| ${who}~ lo~~ve | you{hold:1.5s} | {rhyme: A, chord: C, Amin, timing: 0:01.00}\n"
+        ),
+        "{}",
+        formatted
+    );
+    assert!(
+        formatted.contains("tempo: 120 @ 1:02.005\n"),
+        "{}",
+        formatted
+    );
+}
+
+#[test]
+fn escaped_markup_reads_as_plain_text() {
+    let plain = [
+        "Price is $5 for {this} | that",
+        "lo~ve & &me!! todo: not a note",
+        r"back\slash [laughs] and: colons",
+        "a dot · here, col·lecció and one at the end ·",
+    ];
+    for text in plain {
+        let source = format!("title: \"T\"\n\nVERSE\n{}\n", escape_text(text));
+        let song = parse_song(&source).unwrap();
+        let line = &song.sections[0].lines[0];
+        assert!(line.notes.is_empty() && line.anchor.is_none(), "{}", source);
+        assert_eq!(resolve(&song).song.sections[0].lines[0].text, text);
+        assert_eq!(format_song(&song), source);
+    }
+}
+
+#[test]
+fn continued_lines_stay_one_line() {
+    let source = "title: \"T\"\n\nVERSE\nI walked along the river \\\n    where we used to go {rhyme: A}\nShort\n";
+    let song = parse_song(source).unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0].text,
+        "I walked along the river where we used to go"
+    );
+    assert_eq!(lines[0].breaks, vec![5]);
+    assert_eq!(lines[0].rhyme, Some('A'));
+    assert_eq!(
+        lines[0].segments(),
+        vec!["I walked along the river", "where we used to go"]
+    );
+
+    let formatted = format_song(&song);
+    assert!(
+        formatted.contains("the river \\\n  where we"),
+        "{}",
+        formatted
+    );
+    assert_eq!(format_song(&parse_song(&formatted).unwrap()), formatted);
+
+    let song = resolve(&song).song;
+    let lrc = exporter("lrc").unwrap().export(&song).unwrap();
+    assert!(String::from_utf8(lrc)
+        .unwrap()
+        .contains("I walked along the river where we used to go\n"));
+    let txt = exporter("txt").unwrap().export(&song).unwrap();
+    assert!(String::from_utf8(txt)
+        .unwrap()
+        .contains("the river\nwhere we used to go\n"));
+}
+
+#[test]
+fn blank_lines_inside_a_section_break_stanzas() {
+    let source = "title: \"T\"\n\nMACRO tag\nOh oh\n\nVERSE\nOne {timing: 0:01.00}\nTwo {timing: 0:03.00}\n\nUSE tag\nThree {timing: 0:10.00}\n\nCHORUS\nFour\n";
+    let song = parse_song(source).unwrap();
+    let breaks: Vec<bool> = song.sections[0]
+        .lines
+        .iter()
+        .map(|l| l.stanza_break)
+        .collect();
+    assert_eq!(breaks, vec![false, false, true, false]);
+    assert_eq!(format_song(&song), source);
+
+    let song = resolve(&song).song;
+    assert!(song.sections[0].lines[2].stanza_break);
+    assert_eq!(song.sections[0].lines[2].text, "Oh oh");
+    let export =
+        |name: &str| String::from_utf8(exporter(name).unwrap().export(&song).unwrap()).unwrap();
+    assert!(export("txt").contains("Two\n\nOh oh\nThree\n"));
+    assert!(export("html").contains("<p class=\"line stanza\">Oh oh</p>"));
+    assert!(export("lrc").contains("[00:03.00]Two\n[00:09.00]\nOh oh\n"));
+    assert!(export("ttml").contains("<p begin=\"00:00:03.000\" end=\"00:00:09.000\">"));
+}
+
+#[test]
+fn front_matter_carries_metadata_and_keeps_other_keys() {
+    let source = "---\nlayout: song   # site layout\ntitle: Harbour Lights\nartist: \"The Parsers\"\ntempo: 96\nkey: '7'\nwriters: [Ann Lee, \"Bo Chen\"]\ntags:\n  - sea\n---\n$who = \"you\"\n\nVERSE\nLights for ${who}\n";
+    let song = parse_song(source).unwrap();
+    assert_eq!(song.title().as_deref(), Some("Harbour Lights"));
+    assert_eq!(song.meta_str("artist").as_deref(), Some("The Parsers"));
+    assert_eq!(song.meta("tempo").and_then(|v| v.as_number()), Some(96.0));
+    assert_eq!(
+        song.meta_str("writers").as_deref(),
+        Some("Ann Lee, Bo Chen")
+    );
+    assert_eq!(song.variables.len(), 1);
+
+    let formatted = format_song(&song);
+    assert_eq!(
+        formatted,
+        "---\nlayout: song   # site layout\ntitle: Harbour Lights\nartist: The Parsers\ntempo: 96\nkey: '7'\nwriters: [Ann Lee, Bo Chen]\ntags:\n  - sea\n---\n$who = \"you\"\n\nVERSE\nLights for ${who}\n"
+    );
+    assert_eq!(json(source), json(&formatted));
+    assert!(parse_song("---\ntitle: T\n\nVERSE\nHi\n").is_err());
+}
+
+#[test]
+fn front_matter_lists_stay_lists() {
+    let source = "---\ntitle: T\nwriters: [Ann, \"Bob, Jr.\"]\ntargets:\n- ttml\n-   slides\n---\n\nVERSE\nHi\n";
+    let song = parse_song(source).unwrap();
+    assert_eq!(
+        song.meta("writers"),
+        Some(&Value::List(vec![
+            Value::String("Ann".into()),
+            Value::String("Bob, Jr.".into())
+        ]))
+    );
+    assert_eq!(song.targets(), ["ttml", "slides"]);
+
+    let formatted = format_song(&song);
+    assert_eq!(
+        formatted,
+        "---\ntitle: T\nwriters: [Ann, 'Bob, Jr.']\ntargets:\n  - ttml\n  - slides\n---\nVERSE\nHi\n"
+    );
+    assert_eq!(format_song(&parse_song(&formatted).unwrap()), formatted);
+    assert_eq!(json(source), json(&formatted));
+}
@@ -0,0 +1,43 @@
+use lyrics_dsl::ast::Timestamp;
+use lyrics_dsl::freestyle::Take;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+#[test]
+fn a_take_becomes_a_timed_draft() {
+    let mut take = Take::new();
+    take.push("Mic check {one}", Timestamp::from_millis(1_500));
+    take.push("CHORUS", Timestamp::from_millis(4_000));
+    take.push("  hook on the beat  ", Timestamp::from_millis(5_250));
+    take.push("", Timestamp::from_millis(6_000));
+    take.push("BRIDGE", Timestamp::from_millis(7_000));
+    take.push("VERSE", Timestamp::from_millis(8_000));
+    take.push("back again", Timestamp::from_millis(61_000));
+    assert_eq!(take.len(), 3);
+
+    let source = take.to_source("Take \"1\"");
+    assert_eq!(
+        source,
+        "title: \"Take '1'\"
+
+VERSE[1]
+Mic check \\{one\\} {timing: 0:01.50}
+
+CHORUS
+hook on the beat {timing: 0:05.25}
+
+VERSE[2]
+back again {timing: 1:01.00}
+"
+    );
+    let song = resolve(&parse_song(&source).unwrap()).song;
+    assert_eq!(song.sections[0].lines[0].text, "Mic check {one}");
+}
+
+#[test]
+fn an_empty_take_has_no_lines() {
+    let mut take = Take::new();
+    take.push("CHORUS", Timestamp::from_millis(0));
+    assert!(take.is_empty());
+    assert_eq!(take.to_source("T"), "title: \"T\"\n");
+}
@@ -0,0 +1,29 @@
+use lyrics_dsl::freshness::{inputs, StampError, Stamps};
+
+#[test]
+fn exports_are_fresh_until_an_input_or_the_output_changes() {
+    let song = inputs(&[b"song", b"pdf"]);
+    let mut stamps = Stamps::default();
+    assert!(!stamps.is_fresh("/out/song.pdf", &song, b"%PDF"));
+
+    stamps.record("/out/song.pdf", song, b"%PDF");
+    assert!(stamps.is_fresh("/out/song.pdf", &song, b"%PDF"));
+    assert!(!stamps.is_fresh("/out/song.pdf", &song, b"%PDF edited"));
+    assert!(!stamps.is_fresh("/out/song.pdf", &inputs(&[b"song", b"txt"]), b"%PDF"));
+    assert!(!stamps.is_fresh("/out/other.pdf", &song, b"%PDF"));
+    assert_ne!(inputs(&[b"ab", b"c"]), inputs(&[b"a", b"bc"]));
+
+    let reread = Stamps::parse(&stamps.to_text()).unwrap();
+    assert_eq!(reread, stamps);
+    assert!(stamps.to_text().ends_with(" /out/song.pdf\n"));
+}
+
+#[test]
+fn malformed_stamps_are_rejected() {
+    let line = format!("{} {} out/song.pdf\n", "ab".repeat(32), "cd".repeat(32));
+    assert!(Stamps::parse(&format!("\n{}", line)).is_ok());
+    assert_eq!(
+        Stamps::parse(&format!("{}{} song.pdf\n", line, "ab".repeat(32))),
+        Err(StampError::BadLine(2))
+    );
+}
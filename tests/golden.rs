@@ -0,0 +1,43 @@
+//! Golden-file tests for every exporter.
+//!
+//! Run with `UPDATE_GOLDEN=1 cargo test --test golden` to rewrite the expected
+//! files after an intentional output change, then review the diff.
+
+use lyrics_dsl::export::exporters;
+use lyrics_dsl::selftest::{self, golden_path, render, CaseResult, SAMPLES};
+
+#[test]
+fn exporters_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    for sample in SAMPLES {
+        for exporter in exporters() {
+            let path = golden_path(sample.name, exporter.as_ref());
+            let actual = render(sample, exporter.as_ref()).expect("sample renders");
+            if update {
+                std::fs::write(&path, &actual).expect("write golden file");
+                continue;
+            }
+            let expected = std::fs::read(&path).unwrap_or_default();
+            if expected != actual {
+                failures.push(path);
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "golden mismatch: {:?}", failures);
+}
+
+#[test]
+fn embedded_golden_data_is_complete() {
+    for case in selftest::run() {
+        assert!(
+            matches!(case.result, CaseResult::Pass),
+            "{} as {}: {:?}",
+            case.sample,
+            case.format,
+            case.result
+        );
+    }
+}
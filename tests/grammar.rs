@@ -0,0 +1,35 @@
+use lyrics_dsl::grammar;
+
+#[test]
+fn embedded_grammar_lists_every_rule() {
+    let names: Vec<String> = grammar::rules().into_iter().map(|r| r.name).collect();
+    assert_eq!(names.first().map(String::as_str), Some("song"));
+    for expected in ["metadata", "section", "line", "chord", "timing_info"] {
+        assert!(
+            names.iter().any(|n| n == expected),
+            "missing rule {}",
+            expected
+        );
+    }
+}
+
+#[test]
+fn rules_render_as_pest_and_ebnf() {
+    let rule = grammar::rule("section_number").unwrap();
+    assert_eq!(
+        rule.to_pest(),
+        "section_number = { \"[\" ~ number ~ \"]\" }"
+    );
+    assert_eq!(rule.to_ebnf(), "section_number = \"[\" number \"]\" ;");
+
+    let rule = grammar::rule("sections").unwrap();
+    assert_eq!(rule.to_ebnf(), "sections = (section blank_line*)+ ;");
+}
+
+#[test]
+fn railroad_page_documents_public_rules() {
+    let html = grammar::railroad_html();
+    assert!(html.contains("<h2 id=\"line\">line</h2>"));
+    assert!(!html.contains("id=\"sp\""), "silent rules are omitted");
+    assert_eq!(html.matches("<svg").count(), html.matches("</svg>").count());
+}
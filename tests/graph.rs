@@ -0,0 +1,80 @@
+use lyrics_dsl::ast::Use;
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Reuse\"
+
+MACRO hook
+Hold on
+USE tag
+
+MACRO tag
+Oh oh
+
+VERSE[1]
+One line
+USE hook
+
+CHORUS[1] &main
+La la
+
+VERSE[2]
+Two line
+USE *main
+
+REPEAT CHORUS[1]
+";
+
+fn export(format: &str) -> String {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    String::from_utf8(exporter(format).unwrap().export(&song).unwrap()).unwrap()
+}
+
+#[test]
+fn resolving_records_what_each_section_uses() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    assert_eq!(
+        song.sections[0].uses,
+        [
+            Use {
+                target: "hook".into(),
+                within: None
+            },
+            Use {
+                target: "tag".into(),
+                within: Some("hook".into())
+            },
+        ]
+    );
+    assert_eq!(song.sections[2].uses[0].target, "*main");
+    assert!(song.sections[3].uses.is_empty());
+}
+
+#[test]
+fn dot_shows_flow_repeats_and_uses() {
+    let dot = export("dot");
+    assert!(dot.starts_with("digraph song {\n  label=\"Reuse\";"));
+    assert!(dot.contains(
+        "  s4 [label=\"Chorus 1\", fillcolor=\"#ea4335\", style=\"rounded,filled,dashed\"];"
+    ));
+    assert!(
+        dot.contains("  u1 [label=\"hook\", fillcolor=\"#ffffff\", shape=note, fontcolor=black];")
+    );
+    assert!(dot.contains("  s3 -> s4;\n"));
+    assert!(dot.contains("  s4 -> s2 [label=\"REPEAT\", style=dashed, constraint=false];"));
+    assert!(dot.contains("  s1 -> u1 [label=\"USE\", style=dotted];"));
+    assert!(dot.contains("  u1 -> u2 [label=\"USE\", style=dotted];"));
+    // An anchored section is used in place, not drawn twice.
+    assert!(dot.contains("  s3 -> s2 [label=\"USE\", style=dotted];"));
+    assert!(!dot.contains("*main"));
+}
+
+#[test]
+fn d2_shows_the_same_graph() {
+    let d2 = export("d2");
+    assert!(d2.starts_with("direction: right\ntitle: \"Reuse\" {"));
+    assert!(d2.contains("u2: \"tag\" {\n  shape: page\n}"));
+    assert!(d2.contains("s4 -> s2: REPEAT {\n  style.stroke-dash: 3\n}"));
+    assert!(d2.contains("u1 -> u2: USE {"));
+}
@@ -0,0 +1,76 @@
+use lyrics_dsl::export::{exporter, with_sheet_options, without_harmony, Harmony, SheetOptions};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SOURCE: &str = "title: \"Stacked\"
+$who = \"you\"
+
+VERSE[1]
+I will follow {timing: 0:01.00}
+  + follow, follow {timing: 0:02.00}
+  + (ooh) {chord: G}
+Wherever $who go
+  + $who go
+";
+
+fn html(harmony: Harmony) -> String {
+    let song = resolve(&parse_song(SOURCE).unwrap()).song;
+    let options = SheetOptions {
+        harmony,
+        ..SheetOptions::default()
+    };
+    let html = with_sheet_options("html", options)
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    String::from_utf8(html).unwrap()
+}
+
+#[test]
+fn plus_lines_stack_under_their_lead_line() {
+    let song = resolve(&parse_song(SOURCE).unwrap()).song;
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].text, "I will follow");
+    let harmony: Vec<&str> = lines[0].harmony.iter().map(|h| h.text.as_str()).collect();
+    assert_eq!(harmony, ["follow, follow", "(ooh)"]);
+    assert_eq!(lines[0].harmony[1].chords, ["G"]);
+    assert_eq!(lines[1].harmony[0].text, "you go");
+}
+
+#[test]
+fn a_harmony_line_needs_a_lead_line() {
+    assert!(parse_song("VERSE\n  + ooh\nHello\n").is_err());
+}
+
+#[test]
+fn sheets_include_exclude_or_color_the_parts() {
+    let included = html(Harmony::Include);
+    assert!(included.contains("<p class=\"line harmony\">follow, follow</p>"));
+    assert!(!included.contains("part-1"));
+
+    let separate = html(Harmony::Separate);
+    assert!(separate.contains("<p class=\"line harmony part-1\">follow, follow</p>"));
+    assert!(separate.contains("<p class=\"line harmony part-2\">(ooh)</p>"));
+    assert!(separate.contains("p.part-2 { color:"));
+
+    assert!(!html(Harmony::Exclude).contains("harmony\">"));
+
+    let song = resolve(&parse_song(SOURCE).unwrap()).song;
+    let text = |song| String::from_utf8(exporter("txt").unwrap().export(song).unwrap()).unwrap();
+    assert!(text(&song).contains("I will follow\n  follow, follow\n  (ooh)\n"));
+    assert!(!text(&without_harmony(&song)).contains("ooh"));
+}
+
+#[test]
+fn harmony_colors_are_for_sheets_only() {
+    let options = SheetOptions {
+        harmony: Harmony::Separate,
+        ..SheetOptions::default()
+    };
+    let err = with_sheet_options("lrc", options).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "`lrc` export does not support harmony colors"
+    );
+}
@@ -0,0 +1,118 @@
+use lyrics_dsl::crypt::{self, Key};
+use lyrics_dsl::history::{History, Recovery, Run};
+use std::time::{Duration, Instant};
+
+#[test]
+fn undo_and_redo_walk_the_steps_and_an_edit_drops_the_redos() {
+    let mut history = History::default();
+    let mut text = "a".to_string();
+    for next in ["ab", "abc"] {
+        history.record(std::mem::replace(&mut text, next.to_string()), None);
+    }
+    text = history.undo(text).unwrap();
+    assert_eq!(text, "ab");
+    text = history.undo(text).unwrap();
+    assert_eq!(text, "a");
+    assert_eq!(history.undo(text.clone()), None);
+    text = history.redo(text).unwrap();
+    assert_eq!(text, "ab");
+
+    history.record(std::mem::replace(&mut text, "abd".to_string()), None);
+    assert!(!history.can_redo());
+    assert_eq!(history.undo(text).unwrap(), "ab");
+}
+
+#[test]
+fn a_run_of_typing_undoes_as_one_step_until_sealed() {
+    let mut history = History::default();
+    let mut text = String::new();
+    for c in "hi".chars() {
+        let before = text.clone();
+        text.push(c);
+        history.record(before, Some(Run::Typing));
+    }
+    history.seal();
+    history.record(text.clone(), Some(Run::Typing));
+    text.push('!');
+    let before = text.clone();
+    text.pop();
+    history.record(before, Some(Run::Deleting));
+
+    text = history.undo(text).unwrap();
+    assert_eq!(text, "hi!");
+    text = history.undo(text).unwrap();
+    assert_eq!(text, "hi");
+    assert_eq!(history.undo(text).unwrap(), "");
+}
+
+#[test]
+fn the_oldest_steps_go_once_the_budget_is_spent() {
+    let mut history = History::new(100);
+    let mut text = String::new();
+    for _ in 0..10 {
+        let before = text.clone();
+        text.push_str("12345");
+        history.record(before, None);
+    }
+    assert_eq!(history.used(), 45 + 40);
+    let mut steps = 0;
+    while let Some(before) = history.undo(text.clone()) {
+        text = before;
+        steps += 1;
+    }
+    assert_eq!(
+        (steps, text.as_str()),
+        (2, "1234512345123451234512345123451234512345")
+    );
+}
+
+#[test]
+fn recovery_keeps_unsaved_edits_until_they_are_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let song = dir.path().join("night.lyr");
+    let mut recovery = Recovery::for_song(&song).every(Duration::from_secs(5));
+    assert_eq!(recovery.path(), dir.path().join(".night.lyr.recover"));
+    assert_eq!(recovery.load().unwrap(), None);
+
+    let start = Instant::now();
+    assert!(recovery.autosave("one", true, start).unwrap());
+    // Not again until the interval has passed, nor when nothing changed.
+    assert!(!recovery
+        .autosave("two", true, start + Duration::from_secs(1))
+        .unwrap());
+    assert!(!recovery
+        .autosave("one", true, start + Duration::from_secs(9))
+        .unwrap());
+    assert!(recovery
+        .autosave("two", true, start + Duration::from_secs(9))
+        .unwrap());
+    // A session that crashes now leaves its edits for the next one.
+    let next = Recovery::for_song(&song);
+    assert_eq!(next.load().unwrap().as_deref(), Some("two"));
+
+    assert!(!recovery
+        .autosave("two", false, start + Duration::from_secs(20))
+        .unwrap());
+    assert_eq!(next.load().unwrap(), None);
+}
+
+#[test]
+fn recovery_of_a_locked_song_is_locked() {
+    let dir = tempfile::tempdir().unwrap();
+    let song = dir.path().join("night.lyr");
+    let key = Key::Keyfile(b"a key file".to_vec());
+    let mut recovery = Recovery::for_song(&song).locked(key.clone());
+    assert!(recovery
+        .autosave("VERSE\nSecret words\n", true, Instant::now())
+        .unwrap());
+
+    let saved = std::fs::read(recovery.path()).unwrap();
+    assert!(crypt::is_locked(&saved));
+    assert!(!String::from_utf8_lossy(&saved).contains("Secret"));
+    let next = Recovery::for_song(&song).locked(key);
+    assert_eq!(
+        next.load().unwrap().as_deref(),
+        Some("VERSE\nSecret words\n")
+    );
+    assert!(Recovery::for_song(&song).load().is_err());
+}
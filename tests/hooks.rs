@@ -0,0 +1,69 @@
+use lyrics_dsl::config::{Config, ConfigError};
+use lyrics_dsl::hooks::{payload, run, Hook, HookError, Stage};
+use serde_json::json;
+
+#[test]
+fn hooks_are_read_from_the_project_config() {
+    let config = Config::parse(
+        "profile = \"strict\"\nhooks.pre-parse = \"check.sh\"\nhooks.post-export = \"upload.sh --quiet\"\nhooks.pre-parse = \"notify.sh\"\n",
+    )
+    .unwrap();
+    let hook = |stage, command: &str| Hook {
+        stage,
+        command: command.to_string(),
+    };
+    assert_eq!(
+        config.hooks,
+        [
+            hook(Stage::PreParse, "check.sh"),
+            hook(Stage::PostExport, "upload.sh --quiet"),
+            hook(Stage::PreParse, "notify.sh"),
+        ]
+    );
+    assert_eq!(
+        Config::parse("hooks.post-parser = \"x\"\n"),
+        Err(ConfigError::UnknownHook {
+            line: 1,
+            name: "post-parser".into(),
+        })
+    );
+}
+
+#[test]
+fn the_payload_names_the_stage_and_file() {
+    assert_eq!(
+        payload(Stage::PreExport, "song.lyr", json!({ "format": "pdf" })),
+        json!({ "stage": "pre-export", "file": "song.lyr", "format": "pdf" })
+    );
+    for stage in Stage::ALL {
+        assert_eq!(Stage::parse(stage.name()), Some(stage));
+    }
+}
+
+#[test]
+fn hooks_get_the_path_and_payload_and_can_fail() {
+    let dir = tempfile::tempdir().unwrap();
+    let seen = dir.path().join("seen.txt");
+    let hooks = [
+        Hook {
+            stage: Stage::PostParse,
+            command: format!("(cat; echo) > '{0}'; echo >> '{0}'", seen.display()),
+        },
+        Hook {
+            stage: Stage::PreParse,
+            command: "exit 4;".into(),
+        },
+    ];
+    run(&hooks, Stage::PostParse, "song.lyr", json!({ "errors": 0 })).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&seen).unwrap(),
+        "{\"errors\":0,\"file\":\"song.lyr\",\"stage\":\"post-parse\"}\nsong.lyr\n"
+    );
+    run(&hooks, Stage::PreExport, "song.lyr", json!({})).unwrap();
+
+    let failed = run(&hooks, Stage::PreParse, "song.lyr", json!({})).unwrap_err();
+    assert!(matches!(failed, HookError::Failed { .. }));
+    assert!(failed
+        .to_string()
+        .starts_with("pre-parse hook `exit 4;` failed"));
+}
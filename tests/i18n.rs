@@ -0,0 +1,80 @@
+use lyrics_dsl::i18n::{language_from_env, Catalog, I18nError, BUILT_IN};
+
+#[test]
+fn messages_fill_their_placeholders() {
+    let catalog = Catalog::parse(
+        "# greetings\nhello = Hello, { $name }!\nbye = Bye { $name }\n  see you { $when }\n",
+    )
+    .unwrap();
+    assert_eq!(
+        catalog.format("hello", &[("name", &"Sam")]).as_deref(),
+        Some("Hello, Sam!")
+    );
+    assert_eq!(
+        catalog
+            .format("bye", &[("name", &"Sam"), ("when", &3)])
+            .as_deref(),
+        Some("Bye Sam\nsee you 3")
+    );
+    assert_eq!(
+        catalog.format("hello", &[]).as_deref(),
+        Some("Hello, { $name }!")
+    );
+    assert_eq!(catalog.format("missing", &[]), None);
+}
+
+#[test]
+fn catalogs_reject_what_they_cannot_read() {
+    assert_eq!(Catalog::parse("hello Sam\n"), Err(I18nError::Syntax(1)));
+    assert_eq!(Catalog::parse("  Sam\n"), Err(I18nError::Syntax(1)));
+    assert_eq!(
+        Catalog::parse("a = 1\na = 2\n"),
+        Err(I18nError::Duplicate {
+            line: 2,
+            id: "a".into()
+        })
+    );
+
+    let mut spanish = Catalog::built_in("es").unwrap();
+    spanish.extend(Catalog::parse("nothing-changed = Nada cambió\n").unwrap());
+    assert_eq!(
+        spanish.format("nothing-changed", &[]).as_deref(),
+        Some("Nada cambió")
+    );
+}
+
+#[test]
+fn built_in_translations_have_every_message() {
+    let english = Catalog::built_in("en").unwrap();
+    for (lang, _) in BUILT_IN {
+        assert_eq!(
+            Catalog::built_in(lang).unwrap().ids(),
+            english.ids(),
+            "{}",
+            lang
+        );
+    }
+    assert_eq!(Catalog::built_in("xx"), None);
+}
+
+#[test]
+fn the_language_comes_from_the_environment() {
+    let env = |vars: &'static [(&str, &str)]| {
+        language_from_env(move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    };
+    assert_eq!(env(&[("LANG", "es_ES.UTF-8")]).as_deref(), Some("es"));
+    assert_eq!(
+        env(&[("LANG", "es_ES.UTF-8"), ("LYRICS_DSL_LANG", "pt")]).as_deref(),
+        Some("pt")
+    );
+    assert_eq!(
+        env(&[("LC_ALL", ""), ("LANG", "de_DE")]).as_deref(),
+        Some("de")
+    );
+    assert_eq!(env(&[("LANG", "C.UTF-8")]), None);
+    assert_eq!(env(&[]), None);
+}
@@ -0,0 +1,63 @@
+use lyrics_dsl::codes;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::ids::{assign, ids};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Units\"
+
+VERSE[1] {energy: 2}
+First line {rhyme: A}
+  + ooh
+Second line {id: kept-1}
+
+CHORUS {id: chorus}
+Hold on
+USE tag
+
+MACRO tag
+Oh oh
+";
+
+#[test]
+fn ids_are_parsed_and_formatted_back_last_among_the_attributes() {
+    let song = parse_song(SONG).unwrap();
+    assert_eq!(song.sections[1].id.as_deref(), Some("chorus"));
+    assert_eq!(song.sections[0].lines[1].id.as_deref(), Some("kept-1"));
+    assert!(song.sections[0].attrs.iter().all(|a| a.name != "id"));
+    assert_eq!(format_song(&song), SONG);
+    assert!(resolve(&song).diagnostics.is_empty());
+}
+
+#[test]
+fn assign_fills_in_missing_ids_once_and_keeps_the_rest() {
+    let mut song = parse_song(SONG).unwrap();
+    assert_eq!(assign(&mut song, false), 1);
+    assert_eq!(song.sections[1].id.as_deref(), Some("chorus"));
+    assert!(song.sections[0].lines[0].id.is_none());
+
+    // Sections have theirs; the lead line, harmony part, chorus line and
+    // macro line get one each, but not the `USE`.
+    assert_eq!(assign(&mut song, true), 4);
+    assert_eq!(ids(&song).len(), 7);
+    assert_eq!(song.sections[0].lines[1].id.as_deref(), Some("kept-1"));
+    assert!(song.sections[1].lines[1].id.is_none());
+
+    let formatted = format_song(&song);
+    let mut again = parse_song(&formatted).unwrap();
+    assert_eq!(assign(&mut again, true), 0);
+    assert_eq!(format_song(&again), formatted);
+    assert!(resolve(&again).diagnostics.is_empty());
+}
+
+#[test]
+fn an_id_may_only_be_used_once() {
+    let source =
+        "title: \"T\"\n\nVERSE {id: a1}\nOne {id: a1}\n\nMACRO m\nTwo {id: b2}\nThree {id: b2}\n";
+    let diagnostics = resolve(&parse_song(source).unwrap()).diagnostics;
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.code.map(|c| c.id) == Some(codes::DUPLICATE_DEFINITION.id)));
+    assert!(parse_song("title: \"T\"\n\nVERSE\nOne {id: -x}\n").is_err());
+}
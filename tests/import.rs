@@ -0,0 +1,73 @@
+use lyrics_dsl::export::{exporter, with_markdown_options, MarkdownOptions};
+use lyrics_dsl::import::{import_markdown, ImportError};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Harbour\"
+artist: \"The Parsers\"
+
+VERSE[1]
+Out on the water {chord: C, G}
+Price is \\$5 for *stars* \\| more
+
+Nobody sees us
+";
+
+fn markdown(options: MarkdownOptions) -> String {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let md = with_markdown_options("md", options).unwrap();
+    String::from_utf8(md.export(&song).unwrap()).unwrap()
+}
+
+#[test]
+fn markdown_export_reads_back_as_the_same_song() {
+    let options = MarkdownOptions {
+        chords: true,
+        ..MarkdownOptions::default()
+    };
+    let md = markdown(options);
+    assert_eq!(
+        md,
+        "# Harbour\n\n*The Parsers*\n\n## Verse 1\n\n> `C G` Out on the water\\\n> Price is $5 for \\*stars\\* \\| more\n\n> Nobody sees us\n"
+    );
+    assert_eq!(import_markdown(&md).unwrap(), SONG);
+
+    let plain = markdown(MarkdownOptions {
+        heading_level: 3,
+        blockquotes: false,
+        chords: false,
+    });
+    assert!(
+        plain.contains("### Verse 1\n\nOut on the water\\\nPrice"),
+        "{}",
+        plain
+    );
+    let imported = import_markdown(&plain).unwrap();
+    assert!(
+        imported.contains("VERSE[1]\nOut on the water\n"),
+        "{}",
+        imported
+    );
+}
+
+#[test]
+fn markdown_import_follows_the_documented_convention() {
+    let md = "---\nlayout: song\ntitle: Harbour\n---\n# Ignored for the front matter title\n\n<!-- draft -->\n### Pre-Chorus\n\nHold on  \nHold on\n\n## chorus 2\n> Light\n>\n> Dark\n";
+    assert_eq!(
+        import_markdown(md).unwrap(),
+        "---\nlayout: song\ntitle: Harbour\n---\n\nPRE-CHORUS\nHold on\nHold on\n\nCHORUS[2]\nLight\n\nDark\n"
+    );
+    assert_eq!(
+        import_markdown("# T\n\nStray text\n\n## Verse\n> x\n"),
+        Err(ImportError::OutsideSection(3))
+    );
+    assert_eq!(
+        import_markdown("# T\n\n## Notes\n> x\n"),
+        Err(ImportError::UnknownSection {
+            line: 3,
+            heading: "Notes".to_string()
+        })
+    );
+    assert_eq!(import_markdown("# T\n"), Err(ImportError::NoSections));
+    assert!(exporter("md").is_ok());
+}
@@ -0,0 +1,96 @@
+use lyrics_dsl::ast::{Hold, Timestamp};
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::export::karaoke::word_timings;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+fn first_line(source: &str) -> lyrics_dsl::ast::Line {
+    let song = resolve(&parse_song(source).unwrap()).song;
+    song.sections[0].lines[0].clone()
+}
+
+fn spans(line: &lyrics_dsl::ast::Line, end: u64) -> Vec<(u64, u64)> {
+    word_timings(
+        line,
+        Timestamp::from_millis(0),
+        Some(Timestamp::from_millis(end)),
+    )
+    .iter()
+    .map(|t| (t.begin.millis, t.end.millis))
+    .collect()
+}
+
+#[test]
+fn melisma_adds_beats_to_its_word() {
+    let line = first_line("title: \"T\"\n\nVERSE\nI lo~~ve you\n");
+    assert_eq!(line.text, "I love you");
+    assert_eq!(line.sustain(1), Some(Hold::Beats(2)));
+    assert_eq!(
+        spans(&line, 5000),
+        vec![(0, 1000), (1000, 4000), (4000, 5000)]
+    );
+}
+
+#[test]
+fn fixed_hold_is_taken_before_sharing_the_rest() {
+    let line = first_line("title: \"T\"\n\nVERSE\nI love{hold:1.5s} you\n");
+    assert_eq!(line.text, "I love you");
+    assert_eq!(
+        line.sustain(1),
+        Some(Hold::Fixed(Timestamp::from_millis(1500)))
+    );
+    assert_eq!(
+        spans(&line, 2500),
+        vec![(0, 500), (500, 2000), (2000, 2500)]
+    );
+}
+
+#[test]
+fn sustains_follow_their_word_through_variables() {
+    let line = first_line("title: \"T\"\n$who = \"my dear friend\"\n\nVERSE\n$who, I lo~ve you\n");
+    assert_eq!(line.text, "my dear friend, I love you");
+    assert_eq!(line.sustain(4), Some(Hold::Beats(1)));
+}
+
+#[test]
+fn enhanced_lrc_tags_every_word() {
+    let song =
+        parse_song("title: \"T\"\n\nVERSE\nHold on~ {timing: 0:01.00}\nLet go {timing: 0:04.00}\n")
+            .unwrap();
+    let out = exporter("elrc").unwrap().export(&song).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+        out.contains("[00:01.00]<00:01.00>Hold <00:02.00>on<00:04.00>\n"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn holds_past_the_end_of_time_stop_there() {
+    let source = "title: \"T\"\n\nVERSE\nOne{hold:10000000000000000s} two{hold:10000000000000000s} three {timing: 0:01.00}\n";
+    let song = resolve(&parse_song(source).unwrap()).song;
+    let line = &song.sections[0].lines[0];
+    let timings = word_timings(line, Timestamp::from_millis(1000), None);
+    assert_eq!(timings[2].begin.millis, u64::MAX);
+    for format in ["elrc", "ttml", "midi"] {
+        assert!(
+            exporter(format).unwrap().export(&song).is_ok(),
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn lines_before_an_earlier_timing_keep_their_beats() {
+    let source = "title: \"T\"\n\nCHORUS\nOne two {timing: 0:10.00}\n\nVERSE\nThree {timing: 0:30.00}\n\nREPEAT CHORUS\n";
+    let song = resolve(&parse_song(source).unwrap()).song;
+    let out = exporter("elrc").unwrap().export(&song).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+        out.contains("[00:30.00]<00:30.00>Three<00:30.40>\n"),
+        "{}",
+        out
+    );
+}
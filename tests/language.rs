@@ -0,0 +1,122 @@
+use lyrics_dsl::analysis::language::{self, find, register, English, German, Portuguese, Spanish};
+use lyrics_dsl::analysis::{analyze, Language};
+use lyrics_dsl::parser::parse_song;
+
+fn syllables(language: &dyn Language, cases: &[(&str, usize)]) {
+    for (word, expected) in cases {
+        assert_eq!(language.syllables(word), *expected, "{}", word);
+    }
+}
+
+#[test]
+fn spanish_counts_diphthongs_and_hiatus() {
+    syllables(
+        &Spanish,
+        &[
+            ("canción", 2),
+            ("corazón", 3),
+            ("poeta", 3),
+            ("día", 2),
+            ("ciudad", 2),
+            ("quiero", 2),
+            ("guitarra", 3),
+            ("pingüino", 3),
+            ("hoy", 1),
+            ("ayer", 2),
+            ("y", 1),
+        ],
+    );
+    assert_eq!(Spanish.rhyme("canción"), "on");
+    assert_eq!(Spanish.rhyme("historia"), "oria");
+    assert!(Spanish.rhymes("quiero", "primero"));
+    assert!(Spanish.rhymes("amor", "dolor"));
+    assert!(!Spanish.rhymes("amor", "amo"));
+}
+
+#[test]
+fn portuguese_joins_nasal_diphthongs() {
+    syllables(
+        &Portuguese,
+        &[
+            ("coração", 3),
+            ("canções", 2),
+            ("saudade", 3),
+            ("mãe", 1),
+            ("aqui", 2),
+        ],
+    );
+    assert!(Portuguese.rhymes("coração", "canção"));
+    assert_eq!(Portuguese.rhyme("saudade"), "ade");
+    assert_eq!(Portuguese.rhyme("café"), "e");
+}
+
+#[test]
+fn german_reads_digraphs_and_unstressed_endings() {
+    syllables(
+        &German,
+        &[
+            ("Liebe", 2),
+            ("Feuer", 2),
+            ("Zeit", 1),
+            ("Sonnenschein", 3),
+            ("Träume", 2),
+            ("Schnee", 1),
+        ],
+    );
+    assert!(German.rhymes("Liebe", "Triebe"));
+    assert!(German.rhymes("Herzen", "Schmerzen"));
+    assert!(German.rhymes("Sonnenschein", "allein"));
+}
+
+#[test]
+fn english_rhymes_from_the_last_vowel() {
+    assert!(English.rhymes("tonight", "light"));
+    assert!(English.rhymes("love", "above"));
+    assert!(English.rhymes("day", "away"));
+    assert_eq!(English.rhyme("tonight"), "ight");
+}
+
+#[test]
+fn songs_pick_their_language_by_lang() {
+    let song = |lang: &str| {
+        parse_song(&format!(
+            "title: \"x\"\nlang: {}\n\nVERSE[1]\npoeta\n",
+            lang
+        ))
+        .unwrap()
+    };
+    assert_eq!(language::of(&song("es")).code(), "es");
+    assert_eq!(language::of(&song("\"pt-BR\"")).code(), "pt");
+    assert_eq!(language::of(&song("German")).code(), "de");
+    assert_eq!(language::of(&song("fr")).code(), "en");
+    assert_eq!(analyze(&song("es")).sections[0].lines[0].score.syllables, 3);
+    assert_eq!(analyze(&song("en")).sections[0].lines[0].score.syllables, 2);
+}
+
+struct Italian;
+
+impl Language for Italian {
+    fn code(&self) -> &str {
+        "it"
+    }
+
+    fn name(&self) -> &str {
+        "Italian"
+    }
+
+    fn syllables(&self, word: &str) -> usize {
+        Spanish.syllables(word)
+    }
+
+    fn rhyme(&self, word: &str) -> String {
+        Spanish.rhyme(word)
+    }
+}
+
+#[test]
+fn registered_languages_are_found_by_code_and_name() {
+    assert!(find("it").is_none());
+    register(Box::new(Italian));
+    assert_eq!(find("it-IT").map(|l| l.name()), Some("Italian"));
+    assert_eq!(find("italian").map(|l| l.code()), Some("it"));
+}
@@ -0,0 +1,187 @@
+use lyrics_dsl::diagnostic::Applicability;
+use lyrics_dsl::lint::{apply_safe_fixes, check, renumber, RULES};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::spelling::Dictionary;
+
+/// Apply every fix `check` offers, one at a time as `fix` would.
+fn fix_all(source: &str, name: &str) -> String {
+    let mut source = source.to_string();
+    for _ in 0..10 {
+        let song = parse_song(&source).unwrap();
+        let Some(fix) = check(&song, &source, name, None)
+            .into_iter()
+            .find_map(|d| d.fix)
+        else {
+            return source;
+        };
+        source = fix.apply(&source);
+    }
+    panic!("fixes do not settle:\n{}", source);
+}
+
+#[test]
+fn renumbers_sections_and_the_repeats_that_name_them() {
+    let source = "title: \"T\"\n\nVERSE[2]\nA\n\nVERSE[2]\nB\n\nREPEAT VERSE[2]\n\nVERSE[5] {energy: 2}\nC\n";
+    let song = parse_song(source).unwrap();
+    let diagnostics = check(&song, source, "t", None);
+    assert_eq!(
+        diagnostics[0].message,
+        "VERSE sections are numbered 2, 2, 5; expected 1, 2, 3"
+    );
+    assert_eq!(
+        diagnostics[0].fix.as_ref().unwrap().apply(source),
+        "title: \"T\"\n\nVERSE[1]\nA\n\nVERSE[2]\nB\n\nREPEAT VERSE[2]\n\nVERSE[3] {energy: 2}\nC\n"
+    );
+}
+
+#[test]
+fn renumber_fixes_only_the_numbering_of_every_kind() {
+    let source = "title: \"T\"\n\nVERSE[1]\nOne\n\nVERSE[3]\nInserted\n\nVERSE[2]\nTwo\n\nSECTION Tag [2]  {energy: 1}\nA\n\nREPEAT VERSE[2]\n\nSECTION Tag[2]\nB\n";
+    let song = parse_song(source).unwrap();
+    let (fixed, made) = renumber(&song, source);
+    assert_eq!(
+        made,
+        [
+            "renumber VERSE sections 1, 2, 3",
+            "renumber SECTION Tag sections 1, 2"
+        ]
+    );
+    assert_eq!(
+        fixed,
+        "title: \"T\"\n\nVERSE[1]\nOne\n\nVERSE[2]\nInserted\n\nVERSE[3]\nTwo\n\nSECTION Tag [1]  {energy: 1}\nA\n\nREPEAT VERSE[3]\n\nSECTION Tag[2]\nB\n"
+    );
+    let song = parse_song(&fixed).unwrap();
+    assert_eq!(renumber(&song, &fixed), (fixed.clone(), Vec::new()));
+}
+
+#[test]
+fn fixes_keep_everything_they_do_not_touch() {
+    assert_eq!(
+        fix_all(
+            "$x = \"y\"\n\nCHORUS   &hook\nLa   la {chord: C}\n",
+            "My Song"
+        ),
+        "title: \"My Song\"\n$x = \"y\"\n\nCHORUS &hook\nLa   la {chord: C}\n"
+    );
+    assert_eq!(
+        fix_all("---\nlayout: song\n---\nVERSE\nOne\n", "demo"),
+        "---\ntitle: 'demo'\nlayout: song\n---\nVERSE\nOne\n"
+    );
+}
+
+#[test]
+fn only_machine_applicable_fixes_apply_unasked() {
+    let source = "artist: \"A\"\n\nCHORUS[3]  {energy: 1}\nLa\n";
+    let song = parse_song(source).unwrap();
+    let diagnostics = check(&song, source, "untitled", None);
+    assert_eq!(diagnostics.len(), 3);
+    let (fixed, made) = apply_safe_fixes(source, &diagnostics);
+    // The header rewrite overlaps the renumbering and waits for a second pass.
+    assert_eq!(made, ["renumber CHORUS sections 1"]);
+    assert_eq!(fixed, "artist: \"A\"\n\nCHORUS[1]  {energy: 1}\nLa\n");
+
+    let song = parse_song(&fixed).unwrap();
+    let (fixed, made) = apply_safe_fixes(&fixed, &check(&song, &fixed, "untitled", None));
+    assert_eq!(made, ["rewrite as `CHORUS[1] {energy: 1}`"]);
+    assert_eq!(fixed, "artist: \"A\"\n\nCHORUS[1] {energy: 1}\nLa\n");
+
+    let title = RULES.iter().find(|r| r.name == "missing-title").unwrap();
+    assert_eq!(title.fixer, Some(Applicability::MaybeIncorrect));
+}
+
+#[test]
+fn long_lines_are_measured_against_the_tightest_target() {
+    let source = "title: \"T\"\ntargets: \"lrc, ttml, slides\"\n$tail = \"all the way down to the sea\"\n\nVERSE\nAnd the river runs $tail\nShort line\n\nREPEAT VERSE\n";
+    let song = parse_song(source).unwrap();
+    let diagnostics = check(&song, source, "t", None);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "line is 46 characters, more than the 36 of a `slides` row"
+    );
+
+    let untargeted = source.replace("targets: \"lrc, ttml, slides\"\n", "");
+    let song = parse_song(&untargeted).unwrap();
+    assert!(check(&song, &untargeted, "t", None).is_empty());
+}
+
+#[test]
+fn misspellings_are_fixed_when_the_correction_is_clear() {
+    let dictionary = Dictionary::from_words(
+        "we\ndance\nuntil\nthe\nmorning\nin\nrunning\nlate\nlight\nnight\nsight\nhigh\nhi",
+    );
+    let source = "title: \"T\"\n$x = \"zzz\"\n\nVERSE\nWe danse until the mornin' $x {timing: 0:01.00}\n  + runnin' hight !! todo: check the nigt\n";
+    let song = parse_song(source).unwrap();
+    let diagnostics = check(&song, source, "t", Some(&dictionary));
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "`danse` is not in the dictionary",
+            "`hight` is not in the dictionary"
+        ]
+    );
+    assert_eq!(
+        diagnostics[1].help.as_deref(),
+        Some("did you mean `sight`, `night`, `light` or `high`?")
+    );
+    assert!(diagnostics[1].fix.is_none());
+
+    let (fixed, made) = apply_safe_fixes(source, &diagnostics);
+    assert_eq!(made, ["replace `danse` with `dance`"]);
+    assert!(fixed.contains("\nWe dance until"));
+}
+
+#[test]
+fn recurring_lines_are_unified_to_the_most_common_variant() {
+    let source = "title: \"T\"\n\nCHORUS[1]\nWe can't stop the rain\n\nCHORUS[2]\nWe cannot stop the rain\n\nCHORUS[3]\nWe can't stop the rain\n";
+    let song = parse_song(source).unwrap();
+    let diagnostics = check(&song, source, "t", None);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "`We cannot stop the rain` recurs elsewhere as `We can't stop the rain`"
+    );
+    let fix = diagnostics[0].fix.as_ref().unwrap();
+    assert_eq!(fix.applicability, Applicability::MaybeIncorrect);
+    assert_eq!(fix.apply(source), source.replace("cannot", "can't"));
+}
+
+#[test]
+fn translations_cover_every_line_and_keep_close_in_syllables() {
+    let source = "title: \"T\"\nlang: en\n\nVERSE\nHold on to me {translation.es: \"Agárrate a mí\", translation.ja-Latn: \"shikkari\"}\nLet go\n\nCHORUS\nWe run {translation.es: \"Corremos juntos hasta el final\"}\n\nBRIDGE\nAll alone\n";
+    let song = parse_song(source).unwrap();
+    let messages: Vec<String> = check(&song, source, "t", None)
+        .into_iter()
+        .map(|d| d.message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "line has no `es` translation",
+            "line has no `ja-Latn` translation",
+            "Chorus has no `ja-Latn` translation",
+            "Bridge has no `es` translation",
+            "Bridge has no `ja-Latn` translation",
+        ]
+    );
+
+    // Asked for explicitly, and counted: `Corremos ...` is far longer.
+    let strict = source.replace(
+        "lang: en\n",
+        "lang: en\ntranslations: \"es\"\nsyllable_delta: 2\n",
+    );
+    let song = parse_song(&strict).unwrap();
+    let messages: Vec<String> = check(&song, &strict, "t", None)
+        .into_iter()
+        .map(|d| d.message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "line has no `es` translation",
+            "Bridge has no `es` translation",
+            "`es` translation has 10 syllables to the line's 2",
+        ]
+    );
+}
@@ -0,0 +1,52 @@
+use lyrics_dsl::manifest::{to_csv, to_json, Record};
+use lyrics_dsl::parser::parse_song;
+
+fn record(path: &str, source: &str) -> Record {
+    Record::new(path, &parse_song(source).unwrap())
+}
+
+#[test]
+fn metadata_becomes_one_row_per_song() {
+    let night = record(
+        "album/night.lyr",
+        "title: \"Night, Again\"\nartist: \"Ann\"\nwriters: \"Ann Lee\"\nyear: 2021\nduration: 225\nlang: \"es\"\nexplicit: yes\nisrc: \"uss1z9900001\"\niswc: \"T0345246801\"\npublishers: \"Harbour Songs 50%, Kobalt 50%\"\n\nVERSE\nHold on\n",
+    );
+    let day = record(
+        "day.lyr",
+        "---\ntitle: Day\nwriters: [Ann Lee, Bo Chen]\nduration: \"1:02:03\"\nexplicit: clean\n---\n\nVERSE\nLa\n",
+    );
+    assert_eq!(night.duration.as_deref(), Some("3:45"));
+    assert_eq!(night.explicit, Some(true));
+    assert_eq!(day.writers.as_deref(), Some("Ann Lee, Bo Chen"));
+    assert_eq!(
+        to_csv(&[night, day]),
+        "path,title,artist,writers,year,duration,language,explicit,isrc,iswc,publishers\r\n\
+         album/night.lyr,\"Night, Again\",Ann,Ann Lee,2021,3:45,es,true,US-S1Z-99-00001,T-034.524.680-1,\"Harbour Songs 50%, Kobalt 50%\"\r\n\
+         day.lyr,Day,,\"Ann Lee, Bo Chen\",,1:02:03,,false,,,\r\n"
+    );
+}
+
+#[test]
+fn missing_metadata_is_null_in_json() {
+    let bare = record(
+        "bare.lyr",
+        "title: \"Bare\"\nexplicit: maybe\n\nVERSE\nLa\n",
+    );
+    let json: serde_json::Value = serde_json::from_str(&to_json(&[bare])).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{
+            "path": "bare.lyr",
+            "title": "Bare",
+            "artist": null,
+            "writers": null,
+            "year": null,
+            "duration": null,
+            "language": null,
+            "explicit": null,
+            "isrc": null,
+            "iswc": null,
+            "publishers": null
+        }])
+    );
+}
@@ -0,0 +1,92 @@
+use lyrics_dsl::medley::{Medley, MedleyError, MedleyPart};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG_A: &str = "title: \"A\"\nkey: \"G\"\n\nVERSE[1]\nWalking {chord: G, C}\n\nCHORUS[1] &hook\nSing {chord: D}\n";
+const SONG_B: &str =
+    "title: \"B\"\nkey: \"F\"\n\nVERSE[1]\nAnother day {chord: F, Bb}\n\nREPEAT VERSE[1]\n";
+
+fn compose(text: &str, sources: &[&str]) -> Result<lyrics_dsl::ast::Song, MedleyError> {
+    let songs: Vec<_> = sources
+        .iter()
+        .map(|source| resolve(&parse_song(source).unwrap()).song)
+        .collect();
+    Medley::parse(text)?.compose(&songs)
+}
+
+#[test]
+fn parses_metadata_and_use_lines() {
+    let medley = Medley::parse(
+        "title: \"Friday\"  # opener\ntempo: 120\n\nuse \"a.lyr\"::CHORUS[2] transposed -3\nuse \"b.lyr\"\nuse \"c.lyr\"::SECTION Post-Chorus\n",
+    )
+    .unwrap();
+    assert_eq!(
+        medley.metadata,
+        [
+            ("title".to_string(), "Friday".to_string()),
+            ("tempo".to_string(), "120".to_string())
+        ]
+    );
+    assert_eq!(
+        medley.parts[0],
+        MedleyPart {
+            file: "a.lyr".into(),
+            section: Some("CHORUS[2]".into()),
+            transpose: -3,
+            line: 4,
+        }
+    );
+    assert_eq!(medley.parts[1].section, None);
+    assert_eq!(
+        medley.parts[2].section.as_deref(),
+        Some("SECTION Post-Chorus")
+    );
+}
+
+#[test]
+fn rejects_malformed_lines() {
+    let error = |text: &str| match Medley::parse(text) {
+        Err(MedleyError::Syntax { message, .. }) => message,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(error("use a.lyr\n"), "expected a quoted path after `use`");
+    assert_eq!(
+        error("use \"a.lyr\"::CHRUS\n"),
+        "expected a section such as CHORUS, VERSE[2] or *anchor"
+    );
+    assert_eq!(
+        error("use \"a.lyr\" transposed up\n"),
+        "expected semitones after `transposed`, e.g. +2"
+    );
+    assert_eq!(Medley::parse("title: \"T\"\n"), Err(MedleyError::Empty));
+}
+
+#[test]
+fn composes_sections_in_order_transposed_and_renumbered() {
+    let song = compose(
+        "title: \"Medley\"\n\nuse \"a.lyr\"::VERSE\nuse \"b.lyr\" transposed +2\nuse \"a.lyr\"::*hook\n",
+        &[SONG_A, SONG_B, SONG_A],
+    )
+    .unwrap();
+    assert_eq!(song.title().as_deref(), Some("Medley"));
+    let headers: Vec<String> = song.sections.iter().map(|s| s.header()).collect();
+    assert_eq!(headers, ["VERSE[1]", "VERSE[2]", "VERSE[3]", "CHORUS[1]"]);
+    assert_eq!(song.sections[1].lines[0].chords, ["G", "C"]);
+    // The REPEAT came resolved and no longer refers back.
+    assert!(song.sections[2].repeat.is_none());
+    assert_eq!(song.sections[2].lines[0].text, "Another day");
+    assert!(song.sections[3].anchor.is_none());
+    assert!(resolve(&song).diagnostics.is_empty());
+}
+
+#[test]
+fn missing_sections_name_the_line() {
+    assert_eq!(
+        compose("\n\nuse \"a.lyr\"::BRIDGE\n", &[SONG_A]),
+        Err(MedleyError::NoSection {
+            line: 3,
+            file: "a.lyr".into(),
+            section: "BRIDGE".into(),
+        })
+    );
+}
@@ -0,0 +1,84 @@
+use lyrics_dsl::merge::{diff, merge, merge_lines, ChangeKind, LineChange};
+
+const BASE: &str = "title: \"Two Writers\"
+
+VERSE[1]
+First line
+Second line
+
+CHORUS
+Sing it
+";
+
+#[test]
+fn edits_to_different_sections_merge_cleanly() {
+    let ours = BASE.replace("Second line", "Second line, reworked");
+    let theirs = BASE.replace("Sing it", "Sing it louder");
+    let merged = merge(BASE, &ours, &theirs);
+    assert_eq!(merged.conflicts, 0);
+    assert_eq!(
+        merged.text,
+        BASE.replace("Second line", "Second line, reworked")
+            .replace("Sing it", "Sing it louder")
+    );
+}
+
+#[test]
+fn sections_added_on_both_sides_are_kept() {
+    let ours = format!("{}\nVERSE[2]\nOurs\n", BASE);
+    let theirs = BASE.replace("\nCHORUS", "\nBRIDGE\nTheirs\n\nCHORUS");
+    let merged = merge(BASE, &ours, &theirs);
+    assert_eq!(merged.conflicts, 0);
+    assert!(
+        merged
+            .text
+            .contains("BRIDGE\nTheirs\n\nCHORUS\nSing it\n\nVERSE[2]\nOurs\n"),
+        "{}",
+        merged.text
+    );
+}
+
+#[test]
+fn same_line_edited_twice_conflicts() {
+    let ours = BASE.replace("First line", "Our first line");
+    let theirs = BASE.replace("First line", "Their first line");
+    let merged = merge(BASE, &ours, &theirs);
+    assert_eq!(merged.conflicts, 1);
+    assert!(merged.text.contains(
+        "<<<<<<< ours\nOur first line\n=======\nTheir first line\n>>>>>>> theirs\nSecond line\n"
+    ));
+}
+
+#[test]
+fn unparsable_input_falls_back_to_lines() {
+    let merged = merge_lines("a\nb\nc\nd\n", "a\nB\nc\nd\n", "a\nb\nc\nD\n");
+    assert_eq!(merged.conflicts, 0);
+    assert_eq!(merged.text, "a\nB\nc\nD\n");
+    assert_eq!(merge("{", "{\n", "{").text, "{\n");
+}
+
+#[test]
+fn diff_reports_changes_by_section() {
+    let new = BASE
+        .replace("Second line", "Second line, reworked")
+        .replace("\nCHORUS\nSing it\n", "\nBRIDGE\nNew bridge\n");
+    let changes = diff(BASE, &new);
+    let summary: Vec<(&str, ChangeKind)> =
+        changes.iter().map(|c| (c.block.as_str(), c.kind)).collect();
+    assert_eq!(
+        summary,
+        [
+            ("VERSE[1]", ChangeKind::Modified),
+            ("BRIDGE", ChangeKind::Added),
+            ("CHORUS", ChangeKind::Removed),
+        ]
+    );
+    assert_eq!(
+        changes[0].lines,
+        [
+            LineChange::Removed("Second line".into()),
+            LineChange::Added("Second line, reworked".into()),
+        ]
+    );
+    assert!(diff(BASE, BASE).is_empty());
+}
@@ -0,0 +1,50 @@
+use lyrics_dsl::analysis::meter::{check, line_bars};
+use lyrics_dsl::ast::TimeSignature;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+#[test]
+fn bar_markers_split_lines_without_touching_text() {
+    let song =
+        parse_song("title: \"B\"\ntime: 6/8\n\nVERSE\nUp | on the roof || tonight |\n").unwrap();
+    assert_eq!(
+        song.time_signature(),
+        Some(TimeSignature { beats: 6, unit: 8 })
+    );
+
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.text, "Up on the roof tonight");
+    assert_eq!(line.bars, vec![1, 4, 4, 5]);
+    assert_eq!(line_bars(line, 5), vec![0..1, 1..4, 4..4, 4..5]);
+}
+
+#[test]
+fn overfull_bars_are_reported() {
+    let song =
+        parse_song("title: \"B\"\ntime: 2/4\n\nVERSE\n| Celebrating tonight | now |\n").unwrap();
+    let diagnostics = check(&song);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "bar 1 of this line has 6 syllables, more than the 4 that fit in 2/4"
+    );
+}
+
+#[test]
+fn bars_without_a_time_signature_assume_common_time() {
+    let song = parse_song("title: \"B\"\n\nVERSE\nOne | two\nThree | four\n").unwrap();
+    let diagnostics = check(&song);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "bar markers without a `time` signature; assuming 4/4"
+    );
+}
+
+#[test]
+fn invalid_time_signatures_are_errors() {
+    let song = parse_song("title: \"B\"\ntime: 6/7\n\nVERSE\nHi\n").unwrap();
+    let resolved = resolve(&song);
+    assert_eq!(resolved.diagnostics.len(), 1);
+    assert!(resolved.diagnostics[0].is_error());
+}
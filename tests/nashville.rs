@@ -0,0 +1,55 @@
+use lyrics_dsl::chords::nashville::{is_number, to_letter, to_number, Key};
+use lyrics_dsl::chords::Note;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+
+#[test]
+fn parses_keys() {
+    let g = Key::parse("G").unwrap();
+    assert_eq!((g.tonic, g.minor, g.flats), (Note(7), false, false));
+    assert_eq!(Key::parse("F#m").unwrap().to_string(), "F#m");
+    assert_eq!(Key::parse("Bb major").unwrap().to_string(), "Bb");
+    assert!(Key::parse("D minor").unwrap().flats);
+    assert!(Key::parse("A minor").unwrap().minor);
+    assert!(Key::parse("H").is_none());
+    assert!(Key::parse("C lydian").is_none());
+}
+
+#[test]
+fn converts_chords_to_numbers() {
+    let g = Key::parse("G").unwrap();
+    let numbers: Vec<String> = ["G", "D/F#", "Em7", "Cadd9", "F", "Bb"]
+        .iter()
+        .map(|c| to_number(c, &g))
+        .collect();
+    assert_eq!(numbers, ["1", "5/7", "6m7", "4add9", "b7", "b3"]);
+
+    // The tonic of a minor key is 1.
+    let a_minor = Key::parse("Am").unwrap();
+    assert_eq!(to_number("Am", &a_minor), "1m");
+    assert_eq!(to_number("F", &a_minor), "b6");
+    assert_eq!(to_number("N.C.", &a_minor), "N.C.");
+}
+
+#[test]
+fn converts_numbers_back_in_any_key() {
+    let f = Key::parse("F").unwrap();
+    let letters: Vec<String> = ["1", "5/7", "6m7", "b7", "#4dim"]
+        .iter()
+        .map(|n| to_letter(n, &f))
+        .collect();
+    assert_eq!(letters, ["F", "C/E", "Dm7", "Eb", "Bdim"]);
+    assert_eq!(to_letter("b3", &Key::parse("E").unwrap()), "G");
+
+    // Letters pass through unchanged.
+    assert_eq!(to_letter("Bb", &f), "Bb");
+    assert!(is_number("b7") && !is_number("Bb"));
+}
+
+#[test]
+fn number_charts_parse_and_format() {
+    let source = "title: \"T\"\nkey: \"G\"\n\nVERSE[1]\nHello {chord: 1, 5/7, 6m7, b7}\n";
+    let song = parse_song(source).unwrap();
+    assert_eq!(song.sections[0].lines[0].chords, ["1", "5/7", "6m7", "b7"]);
+    assert_eq!(format_song(&song), source);
+}
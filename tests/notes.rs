@@ -0,0 +1,53 @@
+use lyrics_dsl::ast::NoteKind;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::notes::{collect, open};
+use lyrics_dsl::parser::parse_song;
+
+const SONG: &str = "title: \"Notes\"
+
+VERSE[1]
+I walk the line !! todo: find better rhyme
+Wow!! so good
+Then home again {chord: G} !! done: fixed !! idea: slower here
+
+CHORUS
+Sing it loud
+
+REPEAT CHORUS
+";
+
+#[test]
+fn notes_attach_to_lines_without_changing_the_text() {
+    let song = parse_song(SONG).unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines[0].text, "I walk the line");
+    assert_eq!(lines[0].notes[0].kind, NoteKind::Todo);
+    assert_eq!(lines[0].notes[0].text, "find better rhyme");
+    assert_eq!(lines[1].text, "Wow!! so good");
+    assert!(lines[1].notes.is_empty());
+    let kinds: Vec<NoteKind> = lines[2].notes.iter().map(|n| n.kind).collect();
+    assert_eq!(kinds, [NoteKind::Done, NoteKind::Idea]);
+    assert_eq!(lines[2].chords, ["G"]);
+}
+
+#[test]
+fn formatting_keeps_notes() {
+    let song = parse_song("title: \"T\"\n\nVERSE\nHi   !!todo:louder\n").unwrap();
+    let formatted = format_song(&song);
+    assert!(formatted.contains("Hi !! todo: louder\n"));
+    assert_eq!(format_song(&parse_song(&formatted).unwrap()), formatted);
+}
+
+#[test]
+fn collects_notes_with_section_and_line() {
+    let song = parse_song(SONG).unwrap();
+    let all = collect(&song, SONG);
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].section, "Verse 1");
+    assert_eq!(all[0].line, 4);
+    assert_eq!(all[0].text, "I walk the line");
+
+    let open = open(all);
+    let notes: Vec<&str> = open.iter().map(|n| n.note.as_str()).collect();
+    assert_eq!(notes, ["find better rhyme", "slower here"]);
+}
@@ -0,0 +1,52 @@
+use lyrics_dsl::overlay::{accept_key, handshake_key, text_frame, Snapshot};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::playback::cues;
+
+const SONG: &str = "title: \"T\"
+
+VERSE[1]
+Hello there {timing: 0:01.00}
+Goodbye now {timing: 0:03.00}
+
+CHORUS
+La la la {timing: 0:05.00}
+";
+
+#[test]
+fn snapshots_show_the_current_and_next_line() {
+    let cues = cues(&parse_song(SONG).unwrap());
+    let before = Snapshot::at(&cues, 0.0);
+    assert_eq!(before.current, "");
+    assert_eq!(before.next, "Hello there");
+
+    let snapshot = Snapshot::at(&cues, 3.2);
+    assert_eq!(snapshot.section, "Verse 1");
+    assert_eq!(snapshot.current, "Goodbye now");
+    assert_eq!(snapshot.next, "La la la");
+    assert_eq!(
+        snapshot.to_json(),
+        r#"{"section":"Verse 1","current":"Goodbye now","next":"La la la"}"#
+    );
+
+    assert_eq!(Snapshot::at(&cues, 9.0).next, "");
+}
+
+#[test]
+fn answers_the_websocket_handshake() {
+    // The example exchange from RFC 6455.
+    let request = "GET /chat HTTP/1.1\r\nHost: server.example.com\r\n\
+                   Upgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+    let key = handshake_key(request).unwrap();
+    assert_eq!(key, "dGhlIHNhbXBsZSBub25jZQ==");
+    assert_eq!(accept_key(key), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    assert_eq!(handshake_key("GET / HTTP/1.1\r\n\r\n"), None);
+}
+
+#[test]
+fn frames_text_messages() {
+    assert_eq!(text_frame("Hi"), [0x81, 2, b'H', b'i']);
+    let long = "x".repeat(300);
+    let frame = text_frame(&long);
+    assert_eq!(&frame[..4], [0x81, 126, 1, 44]);
+    assert_eq!(frame.len(), 304);
+}
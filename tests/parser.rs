@@ -1,4 +1,6 @@
-use lyrics_dsl::parser::parse_lyrics;
+use lyrics_dsl::ast::Span;
+use lyrics_dsl::diagnostic::Diagnostic;
+use lyrics_dsl::parser::{error_diagnostic, parse_lyrics, parse_song};
 
 #[test]
 fn parse_basic_song() {
@@ -19,3 +21,68 @@ fn parse_glitch_song() {
     assert!(parse_lyrics(&song).is_ok());
 }
 
+#[test]
+fn syntax_errors_render_with_the_source_line() {
+    let source = "title: \"T\"\n\nchorus\nOne\n";
+    let error = parse_song(source).unwrap_err();
+    assert_eq!(
+        error_diagnostic(&error, source).render("song.lyr", source),
+        "song.lyr:3:1: error[E002]: unknown section kind `chorus`
+  |
+3 | chorus
+  | ^^^^^^
+  = note: a section starts with VERSE, CHORUS, BRIDGE, PRE-CHORUS, OUTRO, INTRO, SECTION, REPEAT or MACRO
+  = help: did you mean `CHORUS`?"
+    );
+
+    let source = "title: \"T\"\n\nVERSE[1\nOne\n";
+    let error = parse_song(source).unwrap_err();
+    let diagnostic = error_diagnostic(&error, source);
+    assert_eq!(diagnostic.span, Span::new(17, 19));
+    assert_eq!(diagnostic.help, None);
+}
+
+#[test]
+fn times_out_of_range_are_syntax_errors() {
+    for time in ["99999999999999999999:00", "1.5:00", "307445734561826:00"] {
+        let source = format!("title: \"T\"\n\nVERSE\nOne {{timing: {}}}\n", time);
+        let error = parse_song(&source).unwrap_err();
+        let diagnostic = error_diagnostic(&error, &source);
+        assert_eq!(diagnostic.code.map(|c| c.id), Some("E001"), "{}", time);
+        assert!(diagnostic.message.contains(time), "{}", diagnostic.message);
+    }
+    assert!(
+        parse_lyrics("title: \"T\"\ntempo: 90 @ 99999999999999999999:00\n\nVERSE\nOne\n").is_err()
+    );
+    assert!(parse_song("title: \"T\"\n\nVERSE\nOne {timing: 1:02.50}\n").is_ok());
+}
+
+#[test]
+fn holds_out_of_range_are_syntax_errors() {
+    for hold in ["18446744073709552s", "99999999999999999999999ms"] {
+        let source = format!("title: \"T\"\n\nVERSE\nOne{{hold:{}}}\n", hold);
+        let error = parse_song(&source).unwrap_err();
+        let diagnostic = error_diagnostic(&error, &source);
+        assert_eq!(diagnostic.code.map(|c| c.id), Some("E001"), "{}", hold);
+        assert!(diagnostic.message.contains(hold), "{}", diagnostic.message);
+    }
+    assert!(parse_song("title: \"T\"\n\nVERSE\nOne{hold:1.5s}\n").is_ok());
+}
+
+#[test]
+fn labels_underline_their_own_line() {
+    let source = "$a = \"x\"\n\tUSE  *tag\n";
+    let diagnostic = Diagnostic::error("unknown anchor `tag`", Span::new(16, 19))
+        .with_label(Span::new(1, 2), "declared here")
+        .with_help("define it with `&tag`");
+    assert_eq!(
+        diagnostic.render("s.lyr", source),
+        "s.lyr:2:8: error: unknown anchor `tag`
+  |
+2 | \tUSE  *tag
+  | \t      ^^^
+1 | $a = \"x\"
+  |  - declared here
+  = help: define it with `&tag`"
+    );
+}
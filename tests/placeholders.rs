@@ -0,0 +1,85 @@
+use lyrics_dsl::codes;
+use lyrics_dsl::diagnostic::Severity;
+use lyrics_dsl::export::{exporter, without_placeholders};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::notes::placeholders;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::profile::{check, Profile};
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Gaps\"
+
+VERSE[1]
+The ______ runs <tbd: rhyme with \"river\">
+<tbd>
+
+Cold as ice
+  + ooh <tbd: backing>
+";
+
+#[test]
+fn placeholders_leave_blanks_and_are_written_back_as_they_were() {
+    let song = parse_song(SONG).unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines[0].text, "The ______ runs ____");
+    let hints: Vec<_> = lines[0]
+        .placeholders
+        .iter()
+        .map(|p| p.hint.as_deref())
+        .collect();
+    assert_eq!(hints, [None, Some("rhyme with \"river\"")]);
+    assert_eq!(lines[1].text, "____");
+    assert_eq!(
+        lines[2].harmony[0].placeholders[0].hint.as_deref(),
+        Some("backing")
+    );
+
+    // A bare `<tbd>` says no more than `____`, so it is written as one.
+    assert_eq!(format_song(&song), SONG.replace("<tbd>\n", "____\n"));
+}
+
+#[test]
+fn placeholders_are_warnings_but_errors_in_the_strict_profile() {
+    let song = parse_song(SONG).unwrap();
+    let standard = check(&song, Profile::Standard);
+    assert_eq!(standard.len(), 4);
+    assert!(standard
+        .iter()
+        .all(|d| d.code == Some(&codes::PLACEHOLDER) && d.severity == Severity::Warning));
+    assert_eq!(standard[1].message, "unfinished line: rhyme with \"river\"");
+    assert!(check(&song, Profile::Strict)
+        .iter()
+        .all(|d| d.code == Some(&codes::STRICT_PLACEHOLDER) && d.severity == Severity::Error));
+
+    let todos = placeholders(&song, SONG);
+    let found: Vec<_> = todos.iter().map(|t| (t.line, t.note.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (4, "fill in the blank"),
+            (4, "rhyme with \"river\""),
+            (5, "fill in the blank"),
+            (8, "backing"),
+        ]
+    );
+}
+
+#[test]
+fn exports_show_blanks_or_leave_placeholders_out() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let txt = exporter("txt").unwrap();
+    let blank = String::from_utf8(txt.export(&song).unwrap()).unwrap();
+    assert!(blank.contains("The ______ runs ____\n____\n\nCold as ice\n  ooh ____\n"));
+
+    let hidden = without_placeholders(&song);
+    assert!(hidden.sections[0]
+        .lines
+        .iter()
+        .all(|l| l.placeholders.is_empty()));
+    let hidden = String::from_utf8(txt.export(&hidden).unwrap()).unwrap();
+    assert!(
+        hidden.contains("The runs\n\nCold as ice\n  ooh\n"),
+        "{}",
+        hidden
+    );
+}
@@ -0,0 +1,44 @@
+use lyrics_dsl::config::{Config, ConfigError};
+use lyrics_dsl::preset::{self, Preset};
+
+#[test]
+fn presets_are_read_from_dotted_keys() {
+    let config = Config::parse(
+        "preset.web.format = \"html\"\npreset.songbook.format = \"pdf\"\npreset.web.case = \"title\"\npreset.web.format = \"md\"\n",
+    )
+    .unwrap();
+    let names: Vec<&str> = config.presets.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["web", "songbook"]);
+    let web = &config.presets[0];
+    assert_eq!(web.get("format"), Some("md"));
+    assert_eq!(web.get("case"), Some("title"));
+    assert_eq!(web.get("harmony"), None);
+}
+
+#[test]
+fn a_preset_needs_a_name_and_an_option() {
+    let err = Config::parse("preset.web = \"html\"\n").unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::Syntax {
+            line: 1,
+            message: "expected `preset.<name>.<option> = value`".to_string()
+        }
+    );
+}
+
+#[test]
+fn project_presets_replace_built_in_ones() {
+    let built_in = preset::find(&[], "web").unwrap();
+    assert_eq!(built_in.get("format"), Some("html"));
+    assert_eq!(built_in.get("smart-typography"), Some("true"));
+
+    let ours = Preset {
+        name: "web".to_string(),
+        settings: vec![("format".to_string(), "md".to_string())],
+    };
+    let web = preset::find(std::slice::from_ref(&ours), "web").unwrap();
+    assert_eq!(web, ours);
+    assert_eq!(web.get("smart-typography"), None);
+    assert!(preset::find(&[ours], "stage").is_none());
+}
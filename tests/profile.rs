@@ -0,0 +1,73 @@
+use lyrics_dsl::config::{Config, ConfigError};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::profile::{check, Profile};
+
+const LOOSE: &str = "title: \"T\"
+
+VERSE[1]
+One
+
+CHORUS
+Two
+
+VERSE[3]
+Three
+
+SECTION Post-Chorus &tag
+Four
+
+REPEAT CHORUS
+";
+
+fn messages(profile: Profile) -> Vec<String> {
+    let song = parse_song(LOOSE).unwrap();
+    check(&song, profile)
+        .into_iter()
+        .map(|d| d.message)
+        .collect()
+}
+
+#[test]
+fn profiles_check_one_grammar_with_different_rules() {
+    assert_eq!(messages(Profile::Permissive), Vec::<String>::new());
+    assert_eq!(
+        messages(Profile::Standard),
+        ["free-form section `SECTION Post-Chorus` needs the permissive profile"]
+    );
+    assert_eq!(
+        messages(Profile::Strict),
+        [
+            "`CHORUS` has no number; the strict profile numbers every verse and chorus",
+            "`VERSE[3]` is out of order; expected `VERSE[2]`",
+            "free-form section `SECTION Post-Chorus` needs the permissive profile",
+            "`REPEAT CHORUS` needs a section number in the strict profile",
+        ]
+    );
+
+    let song = parse_song(LOOSE).unwrap();
+    let custom = &song.sections[3];
+    assert_eq!(custom.name.as_deref(), Some("Post-Chorus"));
+    assert_eq!(custom.anchor.as_ref().map(|a| a.name.as_str()), Some("tag"));
+    assert_eq!(custom.label(), "Post-Chorus");
+}
+
+#[test]
+fn config_selects_a_profile() {
+    let config = Config::parse("# project settings\nprofile = \"strict\"\n").unwrap();
+    assert_eq!(config.profile, Some(Profile::Strict));
+    assert_eq!(Config::parse("").unwrap().profile, None);
+    assert_eq!(
+        Config::parse("\nprofile = \"loose\"\n"),
+        Err(ConfigError::UnknownProfile {
+            line: 2,
+            name: "loose".to_string()
+        })
+    );
+    assert_eq!(
+        Config::parse("font = \"serif\"\n"),
+        Err(ConfigError::UnknownKey {
+            line: 1,
+            key: "font".to_string()
+        })
+    );
+}
@@ -0,0 +1,60 @@
+use lyrics_dsl::ast::Provenance;
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::semantic::resolve;
+
+const CO_WRITE: &str = "title: \"T\"
+
+VERSE
+Out on the water {author: Sam, source: voice memo 3, confidence: 0.4, edited: 2024-05-01}
+Under the stars {author: Alex, mood: calm}
+";
+
+#[test]
+fn provenance_is_read_from_line_attributes() {
+    let song = parse_song(CO_WRITE).unwrap();
+    let lines = &song.sections[0].lines;
+    assert_eq!(
+        lines[0].provenance,
+        Provenance {
+            author: Some("Sam".into()),
+            source: Some("voice memo 3".into()),
+            confidence: Some(0.4),
+            edited: Some("2024-05-01".into()),
+        }
+    );
+    assert_eq!(lines[1].provenance.author.as_deref(), Some("Alex"));
+    assert_eq!(lines[1].annotations.len(), 1);
+
+    let formatted = format_song(&song);
+    assert!(formatted.contains("Under the stars {author: Alex, mood: calm}\n"));
+    assert_eq!(parse_song(&formatted).unwrap(), song);
+}
+
+#[test]
+fn provenance_is_exported_and_can_be_set_in_code() {
+    let mut song = resolve(&parse_song(CO_WRITE).unwrap()).song;
+    song.sections[0].lines[1].provenance.confidence = Some(1.0);
+    let json = exporter("json").unwrap().export(&song).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let lines = &json["sections"][0]["lines"];
+    assert_eq!(lines[0]["provenance"]["source"], "voice memo 3");
+    assert_eq!(lines[1]["provenance"]["confidence"], 1.0);
+    assert_eq!(
+        format_song(&song).lines().nth(4),
+        Some("Under the stars {author: Alex, confidence: 1, mood: calm}")
+    );
+}
+
+#[test]
+fn confidence_runs_from_zero_to_one() {
+    let source = "title: \"T\"\n\nVERSE\nOne {confidence: 80}\n";
+    let error = parse_song(source).unwrap_err();
+    let diagnostic = error_diagnostic(&error, source);
+    assert!(
+        diagnostic.message.contains("a confidence from 0 to 1"),
+        "{}",
+        diagnostic.message
+    );
+}
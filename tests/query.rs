@@ -0,0 +1,101 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::query::{Query, QueryError};
+use lyrics_dsl::semantic::resolve;
+use serde_json::{json, Value};
+
+const SONG: &str = "title: \"Harbour\"
+
+VERSE[1]
+Out on the water {chord: G, timing: 0:05.00, author: Sam}
+Under the stars {chord: C, G, timing: 0:09.50, author: Alex}
+
+CHORUS
+Bring me home {chord: D, timing: 0:20.00, author: Sam}
+Bring me home again {chord: D, G, timing: 0:24.00}
+
+REPEAT CHORUS
+";
+
+fn query(text: &str) -> Vec<Value> {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    Query::parse(text)
+        .unwrap()
+        .run(&serde_json::to_value(song).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn paths_spread_arrays_and_filters_keep_matching_items() {
+    assert_eq!(query("sections[kind=chorus].lines | count"), [json!(4)]);
+    assert_eq!(
+        query("sections.lines[provenance.author=sam].text | unique"),
+        [json!("Out on the water"), json!("Bring me home")]
+    );
+    assert_eq!(query("metadata[key=title].value"), [json!("Harbour")]);
+    assert_eq!(
+        query(".sections.lines.chords | unique | sort"),
+        [json!("C"), json!("D"), json!("G")]
+    );
+    assert_eq!(
+        query("sections.lines[chords=C].text"),
+        [json!("Under the stars")]
+    );
+    assert_eq!(query("sections.lines[text~AGAIN] | count"), [json!(2)]);
+    assert_eq!(
+        query("sections.lines[provenance.author!=sam, timing>=0:09.50].text | first"),
+        [json!("Under the stars")]
+    );
+    assert_eq!(query("sections[number].kind"), [json!("verse")]);
+}
+
+#[test]
+fn indexes_pick_from_everything_reached() {
+    assert_eq!(query("sections.lines[1].text"), [json!("Under the stars")]);
+    assert_eq!(
+        query("sections.lines[-1].text"),
+        [json!("Bring me home again")]
+    );
+    assert_eq!(query("sections[9]"), Vec::<Value>::new());
+}
+
+#[test]
+fn functions_reduce_the_results() {
+    assert_eq!(query("sections.lines.timing | max"), [json!(24000)]);
+    assert_eq!(query("sections[0].lines.timing | sum"), [json!(14500)]);
+    assert_eq!(query("sections.lines.text | last | length"), [json!(19)]);
+    assert_eq!(
+        query("sections | first | keys"),
+        [json!("kind"), json!("lines"), json!("number")]
+    );
+}
+
+#[test]
+fn mistakes_are_reported_with_suggestions() {
+    let error = |text| Query::parse(text).unwrap_err();
+    assert_eq!(
+        error("sectons.lines"),
+        QueryError::UnknownField {
+            name: "sectons".into(),
+            suggestion: Some("sections".into()),
+        }
+    );
+    assert_eq!(
+        error("sections | cuont").to_string(),
+        "unknown function `cuont`; did you mean `count`?"
+    );
+    assert_eq!(
+        error("sections[kind=").to_string(),
+        "query syntax error at column 15: expected a value to compare with"
+    );
+    assert!(matches!(
+        error("sections[kind=verse"),
+        QueryError::Syntax { .. }
+    ));
+
+    let song = serde_json::to_value(resolve(&parse_song(SONG).unwrap()).song).unwrap();
+    let sum = Query::parse("sections.kind | sum").unwrap().run(&song);
+    assert_eq!(
+        sum.unwrap_err().to_string(),
+        "`sum` needs numbers, found \"verse\""
+    );
+}
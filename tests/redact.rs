@@ -0,0 +1,109 @@
+use lyrics_dsl::analysis::count_line;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::redact::{redact, RedactOptions};
+
+const SONG: &str = "title: \"Postcards\"
+$home = \"back home in Tulsa\"
+
+VERSE[1]
+Jennifer, the night is young {timing: 0:01.00}
+I wrote to Jennifer from Tulsa
+Acme{/ˈækmi/} Records never called
+Sing it $home tonight
+";
+
+fn options(proper_nouns: bool, terms: &[&str]) -> RedactOptions {
+    RedactOptions {
+        proper_nouns,
+        terms: terms.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+fn lines(song: &lyrics_dsl::ast::Song) -> Vec<String> {
+    song.sections[0]
+        .lines
+        .iter()
+        .map(|l| l.text.clone())
+        .collect()
+}
+
+#[test]
+fn names_are_replaced_everywhere_with_the_same_syllable_count() {
+    let song = parse_song(SONG).unwrap();
+    let redacted = redact(&song, &options(true, &[]));
+    let texts = lines(&redacted.song);
+
+    // "Jennifer" starts the first line but is known from the second.
+    assert_eq!(texts[0], "Na-na-na, the night is young");
+    assert_eq!(texts[1], "I wrote to Na-na-na from Ma-ma");
+    assert_eq!(redacted.song.variables[0].value, "back home in Ma-ma");
+    for (before, after) in song.sections[0]
+        .lines
+        .iter()
+        .zip(&redacted.song.sections[0].lines)
+    {
+        assert_eq!(count_line(before), count_line(after), "{}", after.text);
+    }
+}
+
+#[test]
+fn terms_match_phrases_ignoring_case_and_drop_their_pronunciations() {
+    let song = parse_song(SONG).unwrap();
+    let redacted = redact(&song, &options(false, &["acme records", "postcards"]));
+    let line = &redacted.song.sections[0].lines[2];
+
+    assert_eq!(line.text, "Na-na Na-na never called");
+    assert!(line.pronunciations.is_empty());
+    assert_eq!(redacted.song.title().as_deref(), Some("Ma-ma"));
+    assert_eq!(redacted.replacements.len(), 2);
+    assert_eq!(redacted.replacements[0].count, 2);
+}
+
+#[test]
+fn variable_references_are_left_alone() {
+    let song = parse_song(SONG).unwrap();
+    let redacted = redact(&song, &options(true, &["home"]));
+    assert_eq!(
+        redacted.song.sections[0].lines[3].text,
+        "Sing it $home tonight"
+    );
+    assert_eq!(redacted.song.variables[0].value, "back da in Ma-ma");
+}
+
+#[test]
+fn no_name_survives_anywhere_in_the_song() {
+    let source = "title: \"Letters to Marisol\"
+artist: \"Dana Quill\"
+writers: \"Dana Quill, Omar Vance\"
+credits:
+  sample \"Amen, Brother\" {artist: \"The Winstons\", lines: *hook}
+history:
+  2026-10-01 08:00 UTC \"moved Marisol to the bridge\"
+
+VERSE[1]
+I wrote to Marisol from Tulsa / alt: \"I called Marisol in Tulsa\" &hook {author: Omar, translation.es: \"Le escribí a Marisol\"} !! todo: ask Marisol
+  + oh Marisol
+";
+    let song = parse_song(source).unwrap();
+    let redacted = format_song(&redact(&song, &options(true, &[])).song);
+    for name in [
+        "Letters", "Marisol", "Dana", "Quill", "Omar", "Vance", "Amen", "Brother", "Winstons",
+        "Tulsa",
+    ] {
+        assert!(!redacted.contains(name), "{} in:\n{}", name, redacted);
+    }
+    parse_song(&redacted).unwrap();
+}
+
+#[test]
+fn front_matter_lists_are_redacted_item_by_item() {
+    let source =
+        "---\ntitle: Marisol\nwriters: [Dana Quill, \"Omar Vance\"]\n---\nVERSE\nHello Marisol\n";
+    let song = parse_song(source).unwrap();
+    let redacted = format_song(&redact(&song, &options(true, &[])).song);
+    for name in ["Marisol", "Dana", "Quill", "Omar", "Vance"] {
+        assert!(!redacted.contains(name), "{} in:\n{}", name, redacted);
+    }
+    assert!(redacted.contains("writers: ["), "{}", redacted);
+}
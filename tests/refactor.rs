@@ -0,0 +1,67 @@
+use lyrics_dsl::refactor::{rename, RenameError};
+use lyrics_dsl::semantic::SymbolKind;
+
+const SONG: &str = "title: \"Hooks\"
+$hook = \"hook, line and sinker\"
+
+MACRO hook
+Hook me in  &hook
+
+VERSE[1]
+There's a $hook and a ${hook}
+USE hook
+USE *hook
+";
+
+#[test]
+fn renames_only_the_chosen_symbol() {
+    let renamed = rename(SONG, Some(SymbolKind::Variable), "hook", "bait").unwrap();
+    assert_eq!(
+        renamed,
+        SONG.replace("$hook =", "$bait =")
+            .replace("a $hook and a ${hook}", "a $bait and a ${bait}")
+    );
+
+    let renamed = rename(SONG, Some(SymbolKind::Anchor), "hook", "catch").unwrap();
+    assert!(renamed.contains("Hook me in  &catch\n"));
+    assert!(renamed.contains("USE hook\nUSE *catch\n"));
+}
+
+#[test]
+fn refuses_ambiguous_conflicting_and_invalid_renames() {
+    assert_eq!(
+        rename(SONG, None, "hook", "bait"),
+        Err(RenameError::Ambiguous("hook".into()))
+    );
+    assert_eq!(
+        rename(SONG, None, "line", "bait"),
+        Err(RenameError::NotFound("line".into()))
+    );
+    assert_eq!(
+        rename(SONG, Some(SymbolKind::Macro), "hook", "9lives"),
+        Err(RenameError::InvalidName("9lives".into()))
+    );
+
+    let two = SONG.replace("$hook =", "$bait = \"b\"\n$hook =");
+    assert_eq!(
+        rename(&two, Some(SymbolKind::Variable), "hook", "bait"),
+        Err(RenameError::Conflict {
+            kind: SymbolKind::Variable,
+            name: "bait".into()
+        })
+    );
+}
+
+#[test]
+fn renames_variables_in_harmony_lines() {
+    let source = "title: x\n$who = \"you\"\nVERSE[1]\nWherever $who go\n  + $who go\n";
+    let renamed = rename(source, Some(SymbolKind::Variable), "who", "whom").unwrap();
+    assert_eq!(renamed, source.replace("$who", "$whom"));
+}
+
+#[test]
+fn renames_variables_in_alternatives() {
+    let source = "title: x\n$who = \"you\"\nVERSE[1]\nWherever $who go / alt: \"Where $who go\"\n";
+    let renamed = rename(source, Some(SymbolKind::Variable), "who", "whom").unwrap();
+    assert_eq!(renamed, source.replace("$who", "$whom"));
+}
@@ -0,0 +1,64 @@
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Borrowed\"\nwriters: \"Ann Lee, Bo Chen\"\n\n\
+VERSE[1]\nFirst line\nSecond line\n\nCHORUS\nHold on\n  + hold on (hold on)\nLet go\n";
+
+fn review(format: &str) -> String {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let bytes = exporter(format).unwrap().export(&song).unwrap();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The text drawn on the page, one string per `Tj`.
+fn drawn(pdf: &str) -> Vec<&str> {
+    pdf.lines()
+        .filter_map(|l| l.strip_suffix(") Tj ET"))
+        .filter_map(|l| l.split_once(" Td (").map(|(_, text)| text))
+        .collect()
+}
+
+#[test]
+fn pdf_numbers_lines_through_the_song_and_within_each_section() {
+    let pdf = review("review-pdf");
+    let text = drawn(&pdf);
+    let from = text.iter().position(|t| *t == "VERSE 1").unwrap();
+    assert_eq!(
+        text[from..],
+        [
+            "VERSE 1",
+            "1",
+            "1",
+            "First line",
+            "2",
+            "2",
+            "Second line",
+            "CHORUS",
+            "3",
+            "1",
+            "Hold on",
+            "3a",
+            "1a",
+            "hold on \\(hold on\\)",
+            "4",
+            "2",
+            "Let go",
+        ]
+    );
+    assert!(text.contains(&"Comments"));
+    assert!(text.contains(&"Written by Ann Lee, Bo Chen"));
+}
+
+#[test]
+fn docx_is_a_table_with_an_empty_comments_column() {
+    let docx = review("review-docx");
+    assert!(docx.starts_with("PK\u{3}\u{4}"));
+    assert!(docx.contains("<w:tblHeader/>"));
+    let cell = |text: &str| format!("<w:t xml:space=\"preserve\">{}</w:t>", text);
+    for text in ["Comments", "CHORUS", "3a", "hold on (hold on)", "Let go"] {
+        assert!(docx.contains(&cell(text)), "{}", text);
+    }
+    // The heading row, two sections and five lines.
+    assert_eq!(docx.matches("<w:tr><w:trPr>").count(), 1 + 2 + 5);
+}
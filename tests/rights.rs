@@ -0,0 +1,163 @@
+use lyrics_dsl::export::{exporter, split_sheet_docx, split_sheet_pdf};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::rights::{
+    shares, splits, writer_splits, Isrc, Iswc, RightsError, Share, SplitSheet, Writer,
+};
+use lyrics_dsl::semantic::resolve;
+
+#[test]
+fn codes_are_read_in_any_form_and_written_in_the_usual_one() {
+    assert_eq!(
+        Isrc::parse("uss1z9900001").unwrap().to_string(),
+        "US-S1Z-99-00001"
+    );
+    assert!(Isrc::parse("US-S1Z-1999-0001").is_err());
+    assert!(Isrc::parse("1S-S1Z-99-00001").is_err());
+    assert_eq!(
+        Iswc::parse("T0345246801").unwrap().to_string(),
+        "T-034.524.680-1"
+    );
+    assert_eq!(
+        Iswc::parse("T-034.524.680-7"),
+        Err(RightsError::IswcCheckDigit {
+            code: "T-034.524.680-7".to_string(),
+            found: 7,
+            expected: 1
+        })
+    );
+    assert!(matches!(
+        Iswc::parse("034.524.680-1"),
+        Err(RightsError::Iswc(_))
+    ));
+}
+
+#[test]
+fn publisher_shares_must_add_up_to_a_hundred() {
+    assert_eq!(
+        shares("Harbour Songs: 50%, Kobalt 50%").unwrap(),
+        [
+            Share {
+                publisher: "Harbour Songs".to_string(),
+                percent: 50.0
+            },
+            Share {
+                publisher: "Kobalt".to_string(),
+                percent: 50.0
+            }
+        ]
+    );
+    assert!(splits("A 33.33%, B 33.33%, C 33.34%").is_ok());
+    assert_eq!(
+        splits("A 50%, B 40%").unwrap_err().to_string(),
+        "publisher shares add up to 90%, not 100%"
+    );
+    assert_eq!(
+        splits("A 50%, B half"),
+        Err(RightsError::Share("B half".to_string()))
+    );
+}
+
+#[test]
+fn the_semantic_pass_reports_each_bad_field() {
+    let song = parse_song(
+        "title: \"T\"\nisrc: \"US-S1Z-99-0001\"\niswc: \"T-034.524.680-1\"\npublishers: \"A 70%, B 20%\"\n\nVERSE\nOne\n",
+    )
+    .unwrap();
+    let found: Vec<(&str, String)> = resolve(&song)
+        .diagnostics
+        .iter()
+        .map(|d| (d.code.unwrap().id, d.message.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("E019", "invalid ISRC `US-S1Z-99-0001`, expected a country, registrant, year and number such as `US-S1Z-99-00001`".to_string()),
+            ("E021", "publisher shares add up to 90%, not 100%".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn musicxml_carries_the_codes_and_shares() {
+    let song = parse_song(
+        "title: \"T\"\nisrc: \"USS1Z9900001\"\niswc: \"T0345246801\"\npublishers: \"Harbour & Co 60%, Kobalt 40%\"\n\nVERSE\nOne\n",
+    )
+    .unwrap();
+    let xml = String::from_utf8(exporter("musicxml").unwrap().export(&song).unwrap()).unwrap();
+    assert!(xml.contains(
+        "<work><work-number>T-034.524.680-1</work-number><work-title>T</work-title></work>"
+    ));
+    assert!(xml.contains(
+        "<identification><rights type=\"publisher\">Harbour &amp; Co (60%)</rights>\
+         <rights type=\"publisher\">Kobalt (40%)</rights><miscellaneous>\
+         <miscellaneous-field name=\"isrc\">US-S1Z-99-00001</miscellaneous-field>\
+         </miscellaneous></identification>"
+    ));
+}
+
+#[test]
+fn writers_take_a_role_and_a_share() {
+    assert_eq!(
+        writer_splits("Ann Lee (lyrics) 50%, Bo Chen (music, topline) 50%").unwrap(),
+        [
+            Writer {
+                name: "Ann Lee".to_string(),
+                role: Some("lyrics".to_string()),
+                percent: Some(50.0)
+            },
+            Writer {
+                name: "Bo Chen".to_string(),
+                role: Some("music, topline".to_string()),
+                percent: Some(50.0)
+            }
+        ]
+    );
+    // Plain names are still writers, with nothing to add up.
+    assert_eq!(writer_splits("Ann Lee, Bo Chen").unwrap().len(), 2);
+    assert_eq!(
+        writer_splits("Ann Lee 50%, Bo Chen"),
+        Err(RightsError::Unshared("Bo Chen".to_string()))
+    );
+    assert_eq!(
+        writer_splits("Ann Lee 50%, Bo Chen 30%")
+            .unwrap_err()
+            .to_string(),
+        "writer shares add up to 80%, not 100%"
+    );
+}
+
+#[test]
+fn a_split_sheet_lists_writers_publishers_and_signature_lines() {
+    let song = parse_song(
+        "title: \"Harbour Lights\"\nwriters: \"Ann Lee (lyrics) 60%, Bo Chen 40%\"\niswc: \"T0345246801\"\npublishers: \"Harbour Songs 100%\"\n\nVERSE\nLights\n",
+    )
+    .unwrap();
+    let sheet = SplitSheet::from_song(&song).unwrap();
+    assert_eq!(sheet.iswc.as_deref(), Some("T-034.524.680-1"));
+
+    let pdf = String::from_utf8_lossy(&split_sheet_pdf(&sheet, "2026-10-15")).into_owned();
+    assert!(pdf.starts_with("%PDF-1.4"));
+    for text in [
+        "(Harbour Lights)",
+        "(lyrics)",
+        "(60%)",
+        "(Harbour Songs)",
+        "(2026-10-15)",
+    ] {
+        assert!(pdf.contains(text), "{}", text);
+    }
+    // The date of the split, then a signature line for each writer.
+    assert_eq!(pdf.matches("(Date) Tj").count(), 3);
+
+    let docx = split_sheet_docx(&sheet, "2026-10-15");
+    assert!(docx.starts_with(b"PK\x03\x04"));
+    let text = String::from_utf8_lossy(&docx);
+    assert!(text.contains("word/document.xml"));
+    assert!(text.contains(">Bo Chen</w:t><w:tab/>"));
+
+    let unsigned = parse_song("title: \"T\"\n\nVERSE\nLa\n").unwrap();
+    assert_eq!(
+        SplitSheet::from_song(&unsigned),
+        Err(RightsError::NoWriters)
+    );
+}
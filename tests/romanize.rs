@@ -0,0 +1,74 @@
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::romanize::{romanize, RomanizeError};
+
+fn roman(text: &str, lang: Option<&str>) -> (String, String) {
+    let romanized = romanize(text, lang).unwrap().unwrap();
+    (romanized.lang, romanized.text)
+}
+
+#[test]
+fn kana_become_hepburn_romaji() {
+    assert_eq!(
+        roman("きみがすき", None),
+        ("ja-Latn".into(), "kimigasuki".into())
+    );
+    assert_eq!(roman("ちょっとまって", None).1, "chottomatte");
+    assert_eq!(roman("ラーメン、パーティー", None).1, "raamen, paatii");
+    assert_eq!(roman("きんようび", None).1, "kin'youbi");
+    assert_eq!(roman("まっちゃ", None).1, "matcha");
+}
+
+#[test]
+fn hangul_cyrillic_and_han_follow_their_schemes() {
+    assert_eq!(
+        roman("사랑이 좋아", None),
+        ("ko-Latn".into(), "sarangi joa".into())
+    );
+    assert_eq!(
+        roman("Я люблю тебя", None),
+        ("ru-Latn".into(), "Ya lyublyu tebya".into())
+    );
+    assert_eq!(roman("Привіт, гарна", Some("uk")).1, "Pryvit, harna");
+    assert_eq!(
+        roman("我爱你，月亮", None),
+        ("zh-Latn".into(), "wǒ ài nǐ, yuè liàng".into())
+    );
+    assert_eq!(roman("爱你 baby", Some("zh-TW")).1, "ài nǐ baby");
+}
+
+#[test]
+fn latin_lines_are_left_alone_and_unknown_readings_reported() {
+    assert_eq!(romanize("Hello, world", Some("ja")), Ok(None));
+    assert_eq!(
+        romanize("東京の夜", None),
+        Err(RomanizeError::NoReading {
+            character: '東',
+            lang: "ja".into(),
+        })
+    );
+}
+
+#[test]
+fn translations_round_trip_and_show_in_karaoke_exports() {
+    let source = "title: \"T\"\n\nVERSE[1]\nきみ {timing: 0:01.00, translation.ja-Latn: \"kimi, ne\", translation.en: \"you\"}\n";
+    let song = parse_song(source).unwrap();
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.translation("EN"), Some("you"));
+    assert_eq!(
+        line.romanization().map(|t| t.text.as_str()),
+        Some("kimi, ne")
+    );
+    assert_eq!(format_song(&song), source);
+
+    let lrc = exporter("lrc").unwrap().export(&song).unwrap();
+    assert_eq!(
+        String::from_utf8(lrc).unwrap(),
+        "[ti:T]\n[00:01.00]きみ\n[00:01.00]kimi, ne\n"
+    );
+    let ttml = String::from_utf8(exporter("ttml").unwrap().export(&song).unwrap()).unwrap();
+    assert!(
+        ttml.contains("<br/><span ttm:role=\"x-roman\" xml:lang=\"ja-Latn\">kimi, ne</span></p>")
+    );
+}
@@ -0,0 +1,70 @@
+use lyrics_dsl::config::{Config, ConfigError};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::rubric::{measure, score, Metric, Rubric};
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Night\"
+
+VERSE
+Hold on to the night
+Hold on to the light
+We're never letting go
+
+CHORUS
+Stay with me
+Stay with me
+
+REPEAT CHORUS
+";
+
+#[test]
+fn metrics_are_measured_from_0_to_100() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    assert_eq!(measure(&song, Metric::RhymeDensity), Some(80));
+    // 3 of the 7 lines sung repeat one before them.
+    assert_eq!(measure(&song, Metric::Repetition), Some(43));
+    // 13 different words among the 20 written.
+    assert_eq!(measure(&song, Metric::Freshness), Some(65));
+    assert_eq!(measure(&song, Metric::MeterFit), None);
+}
+
+#[test]
+fn the_score_weighs_metrics_by_the_project_rubric() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let config =
+        Config::parse("rubric.rhyme-density = 3\nrubric.repetition = -1\nrubric.meter-fit = 0.5\n")
+            .unwrap();
+    let rubric = config.rubric.unwrap();
+    assert_eq!(
+        rubric.weights,
+        [
+            (Metric::RhymeDensity, 3.0),
+            (Metric::Repetition, -1.0),
+            (Metric::MeterFit, 0.5),
+        ]
+    );
+    // (80 × 3 + (100 - 43) × 1) / 4, leaving out the meter the song lacks.
+    let scored = score(&song, &rubric);
+    assert_eq!(scored.total, 74);
+    assert_eq!(scored.parts[2].value, None);
+
+    assert_eq!(Rubric::default().weights.len(), 4);
+    assert_eq!(Config::parse("").unwrap().rubric, None);
+    assert!(matches!(
+        Config::parse("rubric.melody = 1\n"),
+        Err(ConfigError::Syntax { line: 1, .. })
+    ));
+    assert!(matches!(
+        Config::parse("rubric.freshness = \"lots\"\n"),
+        Err(ConfigError::Syntax { line: 1, .. })
+    ));
+    let fractional = Config::parse("rubric.freshness = 2.5e-1\nrubric.repetition = \"-0.5\"\n");
+    assert_eq!(
+        fractional.unwrap().rubric.unwrap().weights,
+        [(Metric::Freshness, 0.25), (Metric::Repetition, -0.5)]
+    );
+    for value in ["inf", "nan", ".5", "1."] {
+        let line = format!("rubric.freshness = {}\n", value);
+        assert!(Config::parse(&line).is_err(), "{}", value);
+    }
+}
@@ -0,0 +1,71 @@
+use lyrics_dsl::ast::Ruby;
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::romanize::romanize_line;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str =
+    "title: \"T\"\nlang: \"ja\"\n\nVERSE[1]\n夜空{よぞら}の星{ほし} {timing: 0:01.00}\n";
+
+#[test]
+fn furigana_leave_the_kanji_in_the_text() {
+    let song = parse_song(SONG).unwrap();
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.text, "夜空の星");
+    assert_eq!(
+        line.rubies,
+        vec![
+            Ruby {
+                base: "夜空".into(),
+                reading: "よぞら".into(),
+                at: 0,
+            },
+            Ruby {
+                base: "星".into(),
+                reading: "ほし".into(),
+                at: "夜空の".len(),
+            },
+        ]
+    );
+    assert_eq!(
+        line.ruby_runs(0..line.text.len()),
+        vec![("夜空", Some("よぞら")), ("の", None), ("星", Some("ほし"))]
+    );
+    assert_eq!(format_song(&song), SONG);
+}
+
+#[test]
+fn furigana_follow_their_kanji_past_variables() {
+    let source = "title: \"T\"\n$who = \"きみと\"\n\nVERSE[1]\n$who 花火{はなび}~~\n";
+    let song = resolve(&parse_song(source).unwrap()).song;
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.text, "きみと 花火");
+    assert_eq!(line.rubies[0].at, "きみと ".len());
+    assert_eq!(
+        line.ruby_runs(0..line.text.len())[1],
+        ("花火", Some("はなび"))
+    );
+}
+
+#[test]
+fn furigana_render_as_ruby() {
+    let song = parse_song(SONG).unwrap();
+    let export =
+        |format: &str| String::from_utf8(exporter(format).unwrap().export(&song).unwrap()).unwrap();
+    assert!(export("html")
+        .contains("<ruby>夜空<rt>よぞら</rt></ruby>の<ruby>星<rt>ほし</rt></ruby></p>"));
+    assert!(export("ttml").contains(
+        "<span tts:ruby=\"container\"><span tts:ruby=\"base\">星</span><span tts:ruby=\"text\">ほし</span></span>"
+    ));
+    assert!(export("pdf").starts_with("%PDF"));
+}
+
+#[test]
+fn kanji_are_romanized_from_their_furigana() {
+    let song = parse_song(SONG).unwrap();
+    let romanized = romanize_line(&song.sections[0].lines[0], Some("ja"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(romanized.text, "yozoranohoshi");
+}
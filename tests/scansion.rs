@@ -0,0 +1,62 @@
+use lyrics_dsl::analysis::count_word;
+use lyrics_dsl::analysis::scansion::{check, scan_line, word_stress, Meter};
+use lyrics_dsl::parser::parse_song;
+
+#[test]
+fn word_stress_comes_from_ipa_dictionary_and_rules() {
+    assert_eq!(word_stress("river", Some("ˈrɪvɚ")), "/x");
+    assert_eq!(word_stress("record", Some("rɪˈkɔɹd")), "x/");
+    assert_eq!(word_stress("tonight", None), "x/");
+    assert_eq!(word_stress("the", None), "x");
+    assert_eq!(word_stress("heart", None), "/");
+    assert_eq!(word_stress("become", None), "x/");
+    assert_eq!(word_stress("nation", None), "/x");
+    assert_eq!(word_stress("electricity", None), "xx/xx");
+}
+
+#[test]
+fn dictionary_patterns_match_syllable_counts() {
+    let lines = include_str!("../src/analysis/stress.txt").lines();
+    for line in lines.filter(|l| !l.starts_with('#')) {
+        if let Some((word, pattern)) = line.split_once(' ') {
+            assert_eq!(count_word(word), pattern.len(), "{}", word);
+        }
+    }
+}
+
+#[test]
+fn only_fixed_stress_of_longer_words_clashes() {
+    let song = parse_song(
+        "title: \"S\"\nmeter: iambic\n\nVERSE[1]\nI wander down beside the water line\nHappy the river running\n",
+    )
+    .unwrap();
+    let meter = Meter::parse("iambic").unwrap();
+    let lines = &song.sections[0].lines;
+
+    let scansion = scan_line(&lines[0], Some(&meter));
+    assert_eq!(scansion.pattern(), "x/x/x/x/x/");
+    assert!(scansion.clashes.is_empty());
+
+    let scansion = scan_line(&lines[1], Some(&meter));
+    assert_eq!(scansion.expected.as_deref(), Some("x/x/x/x"));
+    assert_eq!(scansion.clashes, vec![0]);
+
+    let diagnostics = check(&song);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0]
+        .message
+        .contains("`Happy` fights the iambic meter"));
+}
+
+#[test]
+fn declared_stress_and_section_meter_take_precedence() {
+    let song = parse_song(
+        "title: \"S\"\nmeter: iambic\n\nVERSE[1] {meter: \"trochaic\"}\nHappy river running\nMorning comes {stress: x/x}\n\nCHORUS[1] {meter: \"limerick\"}\nLa la la\n",
+    )
+    .unwrap();
+    let diagnostics = check(&song);
+    let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages.len(), 2, "{:?}", messages);
+    assert!(messages[0].contains("`Morning` fights the declared stress"));
+    assert!(messages[1].starts_with("unknown meter `limerick`"));
+}
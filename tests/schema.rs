@@ -0,0 +1,167 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::schema::{song_schema, SCHEMA_VERSION};
+use lyrics_dsl::selftest::SAMPLES;
+use lyrics_dsl::semantic::resolve;
+use serde_json::Value;
+
+const EVERYTHING: &str = "title: \"Marks\"
+tempo: 96
+tempo: 120 @ 1:02.005
+credits:
+  interpolation \"Old Song\" {writers: \"Ann Lee\", year: 1961, lines: *first..*hall}
+history:
+  2026-10-15 09:30 UTC \"new bridge\"
+$who = \"my dear\"
+
+MACRO tag
+Oh oh &oh {author: Sam, source: demo 2, confidence: 0.5, edited: 2024-05-01}
+
+VERSE[1] &first {energy: 2, id: v1-open}
+| ${who}~ lo~~ve | you{hold:1.5s} | {rhyme: A, stress: x/, chord: C, Amin, timing: 0:01.00}
+Fire{/ˈfaɪ.ə/} in the \\
+  hall &hall {mood: hushed, id: hall_1} !! todo: better rhyme
+
+USE tag
+
+CHORUS
+USE *hall
+Still ____ to <tbd: rhyme with \"hall\"> / alt: \"Calling out\"
+
+REPEAT VERSE[1]
+";
+
+/// Check `value` against the subset of JSON Schema that `song_schema` uses.
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(target) = schema["$ref"].as_str() {
+        let name = target.trim_start_matches("#/$defs/");
+        return check(root, &root["$defs"][name], value, path, errors);
+    }
+    if let Some(options) = schema["oneOf"].as_array() {
+        let matching = options
+            .iter()
+            .filter(|option| {
+                let mut inner = Vec::new();
+                check(root, option, value, path, &mut inner);
+                inner.is_empty()
+            })
+            .count();
+        if matching != 1 {
+            errors.push(format!("{}: matches {} of oneOf", path, matching));
+        }
+        return;
+    }
+    if let Some(names) = schema["enum"].as_array() {
+        if !names.contains(value) {
+            errors.push(format!("{}: {} not in enum", path, value));
+        }
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let type_of = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let type_ok = types.is_empty()
+        || types.contains(&type_of)
+        || (type_of == "integer" && types.contains(&"number"));
+    if !type_ok {
+        errors.push(format!("{}: {} is not {:?}", path, type_of, types));
+        return;
+    }
+    if let (Some(min), Some(n)) = (schema["minimum"].as_f64(), value.as_f64()) {
+        if n < min {
+            errors.push(format!("{}: below {}", path, min));
+        }
+    }
+    if let Value::String(s) = value {
+        let chars = s.chars().count() as u64;
+        if schema["minLength"].as_u64().is_some_and(|min| chars < min)
+            || schema["maxLength"].as_u64().is_some_and(|max| chars > max)
+        {
+            errors.push(format!("{}: length {}", path, chars));
+        }
+        if let Some(pattern) = schema["pattern"].as_str() {
+            if !regex::Regex::new(pattern).unwrap().is_match(s) {
+                errors.push(format!("{}: `{}` does not match {}", path, s, pattern));
+            }
+        }
+    }
+    if let Value::Array(items) = value {
+        for (i, item) in items.iter().enumerate() {
+            check(
+                root,
+                &schema["items"],
+                item,
+                &format!("{}[{}]", path, i),
+                errors,
+            );
+        }
+    }
+    if let Value::Object(fields) = value {
+        for name in schema["required"].as_array().into_iter().flatten() {
+            if !fields.contains_key(name.as_str().unwrap()) {
+                errors.push(format!("{}: missing {}", path, name));
+            }
+        }
+        for (name, field) in fields {
+            match schema["properties"].get(name) {
+                Some(property) => {
+                    check(root, property, field, &format!("{}.{}", path, name), errors)
+                }
+                None if schema["additionalProperties"] == Value::Bool(false) => {
+                    errors.push(format!("{}: unexpected {}", path, name))
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+fn assert_valid(schema: &Value, value: &Value) {
+    let mut errors = Vec::new();
+    check(schema, schema, value, "$", &mut errors);
+    assert!(errors.is_empty(), "{:#?}", errors);
+}
+
+#[test]
+fn exported_songs_match_the_schema() {
+    let schema = song_schema();
+    assert_eq!(
+        schema["$id"],
+        format!("urn:lyrics-dsl:song:v{}", SCHEMA_VERSION)
+    );
+    let mut sources: Vec<&str> = SAMPLES.iter().map(|s| s.source).collect();
+    sources.push(EVERYTHING);
+    for source in sources {
+        let song = parse_song(source).unwrap();
+        assert_valid(&schema, &serde_json::to_value(&song).unwrap());
+        let resolved = resolve(&song).song;
+        assert_valid(&schema, &serde_json::to_value(&resolved).unwrap());
+    }
+}
+
+#[test]
+fn the_schema_rejects_what_the_export_never_writes() {
+    let schema = song_schema();
+    let bad = [
+        r#"{"sections": []}"#,
+        r#"{"metadata": [], "sections": [{"kind": "hook", "lines": []}]}"#,
+        r#"{"metadata": [], "sections": [{"kind": "verse", "lines": [{"text": "a", "rhyme": "AB"}]}]}"#,
+        r#"{"metadata": [], "sections": [{"kind": "verse", "lines": [{"text": "a", "span": 1}]}]}"#,
+        r#"{"metadata": [{"key": "tempo", "value": [[1]]}], "sections": []}"#,
+    ];
+    for json in bad {
+        let mut errors = Vec::new();
+        let value: Value = serde_json::from_str(json).unwrap();
+        check(&schema, &schema, &value, "$", &mut errors);
+        assert!(!errors.is_empty(), "{}", json);
+    }
+}
@@ -0,0 +1,97 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::search::{lines, terms, Builder, Index, IndexError, IndexedLine, Query};
+use lyrics_dsl::semantic::resolve;
+
+const NIGHT: &str = "title: \"Night\"
+
+VERSE
+Hold on to the night
+We hold the line
+
+CHORUS
+On and on we hold
+  + (hold on)
+REPEAT CHORUS
+";
+
+fn indexed(source: &str) -> Vec<IndexedLine> {
+    let song = resolve(&parse_song(source).unwrap()).song;
+    lines(&song, source)
+}
+
+#[test]
+fn lines_are_indexed_once_where_they_are_written() {
+    let found = indexed(NIGHT);
+    let found: Vec<(usize, &str, &str)> = found
+        .iter()
+        .map(|l| (l.line, l.section.as_str(), l.text.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (4, "Verse", "Hold on to the night"),
+            (5, "Verse", "We hold the line"),
+            (8, "Chorus", "On and on we hold"),
+            (9, "Chorus", "(hold on)"),
+        ]
+    );
+    assert_eq!(terms("Don’t STOP, don't"), ["don't", "stop", "don't"]);
+}
+
+#[test]
+fn queries_split_words_from_quoted_phrases() {
+    let query = Query::parse("night \"Hold on\" we");
+    assert_eq!(query.words, ["night", "we"]);
+    assert_eq!(query.phrases, [vec!["hold", "on"]]);
+    assert!(Query::parse(" \"\" ,").is_empty());
+}
+
+#[test]
+fn a_search_ranks_the_lines_holding_every_word() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut builder = Builder::default();
+    builder.add("night.lyr", "Night", indexed(NIGHT));
+    builder.add(
+        "day.lyr",
+        "",
+        indexed("artist: \"A\"\n\nVERSE\nHold the door\nOn the way\n"),
+    );
+    let at = dir.path().join("index");
+    builder.write(&at).unwrap();
+
+    let index = Index::open(&at).unwrap();
+    assert_eq!((index.songs(), index.lines()), (2, 6));
+    let found = |query: &str| -> Vec<(String, usize)> {
+        let hits = index.search(&Query::parse(query), 10).unwrap();
+        hits.into_iter().map(|h| (h.path, h.line)).collect()
+    };
+    let hold = found("hold");
+    assert_eq!(hold.len(), 5);
+    // Both words, and in the order asked first.
+    assert_eq!(
+        found("hold on"),
+        [
+            ("night.lyr".to_string(), 9),
+            ("night.lyr".to_string(), 4),
+            ("night.lyr".to_string(), 8),
+        ]
+    );
+    assert_eq!(found("\"on we\""), [("night.lyr".to_string(), 8)]);
+    assert_eq!(found("\"we on\""), []);
+    assert_eq!(found("hold nowhere"), []);
+
+    let hit = &index.search(&Query::parse("door"), 1).unwrap()[0];
+    assert_eq!(
+        (hit.title.as_str(), hit.section.as_str(), hit.text.as_str()),
+        ("", "Verse", "Hold the door")
+    );
+    assert_eq!(index.search(&Query::parse("hold"), 2).unwrap().len(), 2);
+}
+
+#[test]
+fn a_missing_index_says_how_to_build_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = Index::open(&dir.path().join("index")).unwrap_err();
+    assert!(matches!(err, IndexError::Missing(_)));
+    assert!(err.to_string().contains("index build"));
+}
@@ -0,0 +1,146 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::{resolve, SymbolKind};
+
+const SONG: &str = "title: \"Refs\"
+$hook = \"glitch in the mirror\"
+
+MACRO tag
+Oh oh oh &oh
+
+VERSE[1] &first
+Hello ${hook}
+USE tag
+
+CHORUS
+There's a $hook
+USE *first
+
+REPEAT VERSE[1]
+";
+
+#[test]
+fn expands_variables_macros_and_repeats() {
+    let resolved = resolve(&parse_song(SONG).unwrap());
+    assert!(
+        resolved.diagnostics.is_empty(),
+        "{:?}",
+        resolved.diagnostics
+    );
+
+    let text = |s: usize| -> Vec<&str> {
+        resolved.song.sections[s]
+            .lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect()
+    };
+    assert_eq!(text(0), ["Hello glitch in the mirror", "Oh oh oh"]);
+    assert_eq!(
+        text(1),
+        [
+            "There's a glitch in the mirror",
+            "Hello glitch in the mirror",
+            "Oh oh oh"
+        ]
+    );
+    assert_eq!(text(2), text(0));
+    assert!(resolved.song.macros.is_empty());
+}
+
+#[test]
+fn symbol_table_records_definitions_and_uses() {
+    let resolved = resolve(&parse_song(SONG).unwrap());
+    let hook = resolved.symbols.get(SymbolKind::Variable, "hook").unwrap();
+    assert_eq!(&SONG[hook.definition.start..hook.definition.end], "hook");
+    assert_eq!(hook.references.len(), 2);
+
+    let use_site = hook.references[1];
+    let found = resolved.symbols.symbol_at(use_site.start).unwrap();
+    assert_eq!(found.definition, hook.definition);
+
+    let verse = resolved
+        .symbols
+        .get(SymbolKind::Section, "VERSE[1]")
+        .unwrap();
+    assert_eq!(verse.references.len(), 1);
+}
+
+#[test]
+fn reports_duplicates_with_both_spans() {
+    let source = "title: x\n$a = \"1\"\n$a = \"2\"\nVERSE[1]\nLine $a\n";
+    let resolved = resolve(&parse_song(source).unwrap());
+    assert_eq!(resolved.diagnostics.len(), 1);
+
+    let diagnostic = &resolved.diagnostics[0];
+    assert!(diagnostic.message.contains("defined more than once"));
+    assert_eq!(diagnostic.span.line_col(source), (3, 2));
+    assert_eq!(diagnostic.labels[0].span.line_col(source), (2, 2));
+}
+
+#[test]
+fn reports_undefined_references() {
+    let source = "title: x\nVERSE[1]\nLine $nope\nUSE missing\nUSE *gone\nREPEAT CHORUS\n";
+    let resolved = resolve(&parse_song(source).unwrap());
+    let messages: Vec<&str> = resolved
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "undefined variable `nope`",
+            "undefined macro `missing`",
+            "undefined anchor `gone`",
+            "no CHORUS before this REPEAT",
+        ]
+    );
+}
+
+#[test]
+fn rejects_forward_repeats_and_include_cycles() {
+    let source = "title: x\nMACRO a\nUSE a\nVERSE[1]\nUSE a\nREPEAT VERSE[2]\nVERSE[2]\nLine\n";
+    let resolved = resolve(&parse_song(source).unwrap());
+    let messages: Vec<&str> = resolved
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "`USE a` includes itself",
+            "REPEAT VERSE[2] refers to a section that comes later",
+        ]
+    );
+}
+
+#[test]
+fn checks_variables_in_harmony_lines() {
+    let source = "title: x\nVERSE[1]\nWherever you go\n  + $who go\n";
+    let resolved = resolve(&parse_song(source).unwrap());
+    let messages: Vec<&str> = resolved
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, ["undefined variable `who`"]);
+}
+
+#[test]
+fn checks_and_expands_variables_in_alternatives() {
+    let source = "title: x\nVERSE[1]\nWherever you go / alt: \"Wherever $who goes\"\n";
+    let resolved = resolve(&parse_song(source).unwrap());
+    let messages: Vec<&str> = resolved
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, ["undefined variable `who`"]);
+
+    let source = source.replace("VERSE", "$who = \"she\"\nVERSE");
+    let resolved = resolve(&parse_song(&source).unwrap());
+    assert!(resolved.diagnostics.is_empty());
+    let alternative = &resolved.song.sections[0].lines[0].alternatives[0];
+    assert_eq!(alternative.text, "Wherever she goes");
+}
@@ -0,0 +1,82 @@
+use lyrics_dsl::export::PdfExporter;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::setlist::{apply_key, cheat_sheet, Setlist, SetlistEntry, SetlistError};
+
+const SET: &str = r#"# Friday night
+title = "Blue Room # 2"
+
+[[song]]
+file = "opener.lyr"
+
+[[song]]
+file = "ballad.lyr"
+key = "Eb"   # lower for the late set
+notes = "slow \"rubato\" intro"
+"#;
+
+#[test]
+fn parses_setlists() {
+    let setlist = Setlist::parse(SET).unwrap();
+    assert_eq!(setlist.title.as_deref(), Some("Blue Room # 2"));
+    assert_eq!(setlist.songs.len(), 2);
+    assert_eq!(setlist.songs[0].file, "opener.lyr");
+    assert_eq!(setlist.songs[1].key.as_deref(), Some("Eb"));
+    assert_eq!(
+        setlist.songs[1].notes.as_deref(),
+        Some("slow \"rubato\" intro")
+    );
+}
+
+#[test]
+fn reports_setlist_errors() {
+    assert_eq!(Setlist::parse("title = \"T\"\n"), Err(SetlistError::Empty));
+    assert_eq!(
+        Setlist::parse("[[song]]\nkey = \"G\"\n"),
+        Err(SetlistError::MissingFile(1))
+    );
+    assert!(matches!(
+        Setlist::parse("[[song]]\nfile = \"a.lyr\"\ntempo = 1\n"),
+        Err(SetlistError::Syntax { line: 3, .. })
+    ));
+    assert!(matches!(
+        Setlist::parse("[songs]\n"),
+        Err(SetlistError::Syntax { line: 1, .. })
+    ));
+}
+
+#[test]
+fn key_overrides_transpose_chords() {
+    let mut song = parse_song(
+        "title: \"Ballad\"\nkey: \"C\"\n\nVERSE[1]\nGoodbye {chord: C, Am7, G/B, Cmaj9}\n",
+    )
+    .unwrap();
+    let entry = SetlistEntry {
+        file: "ballad.lyr".to_string(),
+        key: Some("Eb".to_string()),
+        notes: Some("slow intro".to_string()),
+    };
+    apply_key(&mut song, &entry).unwrap();
+    assert_eq!(
+        song.sections[0].lines[0].chords,
+        ["Eb", "Cm7", "Bb/D", "Ebmaj9"]
+    );
+    assert_eq!(song.meta_str("key").as_deref(), Some("Eb"));
+    assert_eq!(
+        cheat_sheet(&[(&entry, &song)]),
+        ["1. Ballad · Eb · slow intro"]
+    );
+
+    let mut keyless = parse_song("title: \"T\"\n\nVERSE[1]\nHi {chord: C}\n").unwrap();
+    assert_eq!(
+        apply_key(&mut keyless, &entry),
+        Err(SetlistError::NoKey("Eb".to_string()))
+    );
+}
+
+#[test]
+fn combined_pdf_starts_each_song_on_a_new_page() {
+    let song = parse_song("title: \"T\"\n\nVERSE[1]\nHello\n").unwrap();
+    let pdf = PdfExporter::default().export_all("Set", &[song.clone(), song]);
+    let pages = pdf.windows(12).filter(|w| w == b"/Type /Page ").count();
+    assert_eq!(pages, 2);
+}
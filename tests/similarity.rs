@@ -0,0 +1,73 @@
+use lyrics_dsl::analysis::similarity::{compare, SimilarityOptions};
+use lyrics_dsl::ast::Song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+fn song(source: &str) -> Song {
+    resolve(&parse_song(source).unwrap()).song
+}
+
+const A: &str = "title: \"A\"
+
+VERSE[1]
+Walking down the empty street tonight
+Nothing here but neon light
+I remember what you said
+
+CHORUS[1]
+Hold on, hold on to me
+
+REPEAT CHORUS[1]
+";
+
+const B: &str = "title: \"B\"
+
+VERSE[1]
+Something new begins again
+Walking down an empty street tonight
+Nothing here but neon lights
+Hold on, hold on to me
+";
+
+#[test]
+fn near_identical_consecutive_lines_form_one_passage() {
+    let similarity = compare(&song(A), &song(B), &SimilarityOptions::default());
+
+    assert_eq!(similarity.passages.len(), 2);
+    let passage = &similarity.passages[0];
+    assert_eq!(passage.lines.len(), 2);
+    assert_eq!(
+        passage.lines[0].b_text,
+        "Walking down an empty street tonight"
+    );
+    assert!(passage.lines[1].score >= 0.8);
+    // The repeated chorus is matched once, not twice.
+    assert_eq!(
+        similarity.passages[1].lines[0].a_text,
+        "Hold on, hold on to me"
+    );
+    assert!(similarity.ngram > 0.0 && similarity.ngram < 1.0);
+}
+
+#[test]
+fn threshold_and_minimum_length_filter_matches() {
+    let strict = SimilarityOptions {
+        threshold: 0.95,
+        ..SimilarityOptions::default()
+    };
+    let similarity = compare(&song(A), &song(B), &strict);
+    assert_eq!(similarity.passages.len(), 1);
+
+    let long_only = SimilarityOptions {
+        min_words: 7,
+        ..SimilarityOptions::default()
+    };
+    assert!(compare(&song(A), &song(B), &long_only).passages.is_empty());
+}
+
+#[test]
+fn a_song_is_identical_to_itself() {
+    let similarity = compare(&song(B), &song(B), &SimilarityOptions::default());
+    assert_eq!(similarity.ngram, 1.0);
+    assert_eq!(similarity.passages[0].lines.len(), 4);
+}
@@ -0,0 +1,63 @@
+use lyrics_dsl::export::slides::{slides, Slide, SlideOptions};
+use lyrics_dsl::export::{exporter, with_slide_options, Exporter, SlidesExporter};
+use lyrics_dsl::parser::parse_song;
+
+const SONG: &str = "title: \"T\"
+artist: \"A\"
+
+VERSE[1]
+One
+Two
+Three
+Four
+Five
+
+CHORUS
+La
+";
+
+#[test]
+fn balances_long_sections_across_slides() {
+    let song = parse_song(SONG).unwrap();
+    let slides = slides(&song, &SlideOptions::default());
+    assert_eq!(
+        slides,
+        [
+            Slide::Title {
+                title: "T".into(),
+                artist: Some("A".into())
+            },
+            Slide::Lyrics(vec!["One".into(), "Two".into(), "Three".into()]),
+            Slide::Lyrics(vec!["Four".into(), "Five".into()]),
+            Slide::Lyrics(vec!["La".into()]),
+        ]
+    );
+}
+
+#[test]
+fn exports_slides_as_text_blocks() {
+    let song = parse_song(SONG).unwrap();
+    let exporter = SlidesExporter {
+        options: SlideOptions {
+            lines_per_slide: 5,
+            title_slide: false,
+            blank_between: true,
+        },
+    };
+    assert_eq!(
+        String::from_utf8(exporter.export(&song).unwrap()).unwrap(),
+        "One\nTwo\nThree\nFour\nFive\n---\n---\nLa\n"
+    );
+    assert!(with_slide_options("lrc", SlideOptions::default()).is_err());
+}
+
+#[test]
+fn pptx_is_a_zip_with_one_part_per_slide() {
+    let song = parse_song(SONG).unwrap();
+    let deck = exporter("pptx").unwrap().export(&song).unwrap();
+    assert!(deck.starts_with(b"PK\x03\x04"));
+    let text = String::from_utf8_lossy(&deck);
+    assert!(text.contains("ppt/slides/slide4.xml"));
+    assert!(!text.contains("ppt/slides/slide5.xml"));
+    assert!(text.contains("<a:t>Three</a:t>"));
+}
@@ -0,0 +1,58 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::songmap::{MapError, Measure, SongMap};
+
+const TIMED: &str = "title: \"Map\"\nduration: \"1:00\"\n\nINTRO\nOoh {timing: 0:00.00}\n\nVERSE[1]\nOne {timing: 0:10.00}\nTwo\nThree\n\nCHORUS\nLa {timing: 0:40.00}\n";
+
+fn map(source: &str, measure: Option<Measure>) -> Result<SongMap, MapError> {
+    SongMap::new(&resolve(&parse_song(source).unwrap()).song, measure)
+}
+
+#[test]
+fn blocks_follow_the_sections_in_order() {
+    let map = map(TIMED, None).unwrap();
+    let labels: Vec<String> = map.blocks.iter().map(|b| b.short_label()).collect();
+    assert_eq!(labels, ["I", "V1", "C"]);
+    assert_eq!(map.measure, Measure::Duration);
+    let seconds: Vec<Option<f64>> = map.blocks.iter().map(|b| b.seconds).collect();
+    assert_eq!(seconds, [Some(10.0), Some(30.0), Some(20.0)]);
+    assert_eq!(map.total(), "1:00");
+}
+
+#[test]
+fn widths_are_proportional_and_fill_the_columns() {
+    let map = map(TIMED, None).unwrap();
+    assert_eq!(map.widths(60), [10, 30, 20]);
+    assert_eq!(map.widths(13).iter().sum::<usize>(), 13);
+    // Every block keeps at least one cell.
+    assert!(map.widths(3).iter().all(|&w| w == 1));
+
+    let by_lines = map_lines();
+    assert_eq!(by_lines.measure, Measure::Lines);
+    assert_eq!(by_lines.total(), "5 lines");
+    assert_eq!(by_lines.widths(10), [2, 6, 2]);
+}
+
+fn map_lines() -> SongMap {
+    map(TIMED, Some(Measure::Lines)).unwrap()
+}
+
+#[test]
+fn duration_needs_every_section_timed() {
+    let untimed = "title: \"Map\"\n\nVERSE[1]\nOne\n\nCHORUS\nLa\n";
+    assert_eq!(map(untimed, None).unwrap().measure, Measure::Lines);
+    assert_eq!(
+        map(untimed, Some(Measure::Duration)),
+        Err(MapError::NoDurations)
+    );
+}
+
+#[test]
+fn svg_draws_a_block_per_section() {
+    let svg = map(TIMED, None).unwrap().to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(svg.matches("<rect ").count(), 3);
+    assert!(svg.contains("<title>Verse 1</title>"));
+    assert!(svg.contains(">Map</text>"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
@@ -0,0 +1,60 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::speech::{script, SpeechError, LINE_PAUSE, SECTION_PAUSE, STANZA_PAUSE};
+use std::time::Duration;
+
+const SONG: &str = "title: \"T\"
+
+VERSE[1]
+Peter picked a peck
+of pickled peppers
+
+where's the peck
+CHORUS
+Sing it out
+  + sing it
+VERSE[2]
+Second verse
+REPEAT CHORUS
+";
+
+fn song() -> lyrics_dsl::ast::Song {
+    resolve(&parse_song(SONG).unwrap()).song
+}
+
+#[test]
+fn pauses_follow_lines_stanzas_and_sections() {
+    let script = script(&song(), None).unwrap();
+    let lines: Vec<(&str, Duration)> = script.iter().map(|u| (u.text.as_str(), u.pause)).collect();
+    assert_eq!(
+        lines,
+        [
+            ("Peter picked a peck", LINE_PAUSE),
+            ("of pickled peppers", STANZA_PAUSE),
+            ("where's the peck", SECTION_PAUSE),
+            ("Sing it out", SECTION_PAUSE),
+            ("Second verse", SECTION_PAUSE),
+            ("Sing it out", Duration::ZERO),
+        ]
+    );
+}
+
+#[test]
+fn sections_are_picked_by_name_without_regard_to_case() {
+    let verses = script(&song(), Some("verse")).unwrap();
+    assert_eq!(verses.len(), 4);
+    let second = script(&song(), Some("Verse 2")).unwrap();
+    assert_eq!(second[0].text, "Second verse");
+    // The chorus is read once, not again for its REPEAT.
+    assert_eq!(script(&song(), Some("CHORUS")).unwrap().len(), 1);
+}
+
+#[test]
+fn an_unknown_section_names_the_ones_there_are() {
+    let err = script(&song(), Some("bridge")).unwrap_err();
+    assert!(matches!(err, SpeechError::NoSection { .. }));
+    assert_eq!(
+        err.to_string(),
+        "no section `bridge`; the song has Verse 1, Chorus, Verse 2"
+    );
+}
@@ -0,0 +1,91 @@
+use lyrics_dsl::spelling::{Dictionary, SpellingError};
+
+const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwzxjq
+REP 2
+REP f ph
+REP ph f
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX D Y 3
+SFX D 0 d e
+SFX D y ied [^aeiou]y
+SFX D 0 ed [^ey]
+
+SFX S Y 1
+SFX S 0 s .
+";
+
+const DIC: &str = "8
+dance/DS
+cry/D
+tie/DU
+night/S
+phone
+Paris
+river/S
+morning\tpo:noun
+";
+
+fn dictionary() -> Dictionary {
+    Dictionary::parse(AFF, DIC).unwrap()
+}
+
+#[test]
+fn affixes_make_every_form_of_a_word() {
+    let dictionary = dictionary();
+    for word in [
+        "dance", "danced", "dances", "cried", "untie", "untied", "nights", "morning",
+    ] {
+        assert!(dictionary.check(word), "{}", word);
+    }
+    // Conditions choose the rule: `cry` takes `ied`, not `d` or `ed`.
+    for word in ["cryd", "cryed", "uncry", "unnight"] {
+        assert!(!dictionary.check(word), "{}", word);
+    }
+}
+
+#[test]
+fn case_follows_the_dictionary() {
+    let dictionary = dictionary();
+    assert!(dictionary.check("Dance"));
+    assert!(dictionary.check("DANCED"));
+    assert!(dictionary.check("PARIS"));
+    assert!(!dictionary.check("paris"));
+    assert!(!dictionary.check("dAnce"));
+}
+
+#[test]
+fn suggestions_are_one_edit_away_and_keep_the_case() {
+    let dictionary = dictionary();
+    assert_eq!(dictionary.suggest("danse"), ["dance"]);
+    assert_eq!(dictionary.suggest("Nigth"), ["Night"]);
+    assert_eq!(dictionary.suggest("fone"), ["phone"]);
+    assert_eq!(dictionary.suggest("paris"), ["Paris"]);
+    assert_eq!(dictionary.suggest("rivernight"), ["river night"]);
+    assert!(dictionary.suggest("zzzz").is_empty());
+}
+
+#[test]
+fn word_lists_add_invented_words() {
+    let mut dictionary = dictionary();
+    assert!(!dictionary.check("Zephyrine"));
+    dictionary.add_words("# names\nZephyrine\n\nskadoosh  # a made-up word\n");
+    assert!(dictionary.check("Zephyrine"));
+    assert!(dictionary.check("Skadoosh"));
+    assert!(!dictionary.check("zephyrine"));
+}
+
+#[test]
+fn a_rule_before_its_header_is_an_error() {
+    let err = Dictionary::parse("SFX D 0 d e\n", "dance/D\n").unwrap_err();
+    assert_eq!(
+        err,
+        SpellingError::Affix {
+            line: 1,
+            message: "affix rule before its header".to_string()
+        }
+    );
+}
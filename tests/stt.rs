@@ -0,0 +1,67 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::stt::{draft, parse_whisper_json, Segment, SttError};
+
+const WHISPER_JSON: &str = r#"{
+  "transcription": [
+    {
+      "offsets": { "from": 1200, "to": 3400 },
+      "text": " I woke up {early}",
+      "tokens": [
+        { "text": "[_BEG_]", "p": 0.1 },
+        { "text": " I", "p": 0.9 },
+        { "text": " woke", "p": 0.8 },
+        { "text": " up early", "p": 0.7 }
+      ]
+    },
+    { "offsets": { "from": 3400, "to": 9000 }, "text": " [Music]", "tokens": [] },
+    {
+      "offsets": { "from": 9000, "to": 11000 },
+      "text": " and the sun was gone",
+      "tokens": [{ "text": " and the sun was gone", "p": 0.3 }]
+    }
+  ]
+}"#;
+
+#[test]
+fn whisper_segments_carry_the_mean_token_probability() {
+    let segments = parse_whisper_json(WHISPER_JSON).unwrap();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(segments[0].start, 1.2);
+    assert_eq!(segments[0].text, "I woke up {early}");
+    assert!((segments[0].confidence - 0.8).abs() < 1e-9);
+    assert!(parse_whisper_json("{}").is_err());
+}
+
+#[test]
+fn drafts_are_timed_and_marked_low_confidence() {
+    let segments = parse_whisper_json(WHISPER_JSON).unwrap();
+    let source = draft("demo", &segments).unwrap();
+    assert_eq!(
+        source,
+        "title: \"demo\"
+
+VERSE[1]
+I woke up \\{early\\} {timing: 0:01.20, source: speech-to-text, confidence: 0.5} !! todo: transcribed by speech-to-text; check every line
+
+VERSE[2]
+and the sun was gone {timing: 0:09.00, source: speech-to-text, confidence: 0.3}
+"
+    );
+    let song = resolve(&parse_song(&source).unwrap()).song;
+    let first = &song.sections[0].lines[0];
+    assert_eq!(first.text, "I woke up {early}");
+    assert_eq!(first.provenance.confidence, Some(0.5));
+    assert_eq!(first.notes.len(), 1);
+}
+
+#[test]
+fn a_recording_of_only_music_has_nothing_to_draft() {
+    let music = Segment {
+        start: 0.0,
+        end: 5.0,
+        text: "♪ (instrumental) ♪".to_string(),
+        confidence: 0.9,
+    };
+    assert!(matches!(draft("demo", &[music]), Err(SttError::NoSpeech)));
+}
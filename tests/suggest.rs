@@ -0,0 +1,37 @@
+use lyrics_dsl::parser::{error_diagnostic, parse_song};
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::suggest::{did_you_mean, levenshtein};
+
+#[test]
+fn suggests_the_closest_name_within_reach() {
+    assert_eq!(levenshtein("VESE", "VERSE"), 1);
+    assert_eq!(levenshtein("titel", "title"), 2);
+    assert_eq!(levenshtein("", "abc"), 3);
+
+    let kinds = ["VERSE", "CHORUS", "BRIDGE", "PRE-CHORUS"];
+    assert_eq!(did_you_mean("VESE", kinds), Some("VERSE"));
+    assert_eq!(did_you_mean("chorus", kinds), Some("CHORUS"));
+    assert_eq!(did_you_mean("PRECHORUS", kinds), Some("PRE-CHORUS"));
+    assert_eq!(did_you_mean("HOOK", kinds), None);
+    assert_eq!(did_you_mean("VERSE", kinds), None);
+    assert_eq!(did_you_mean("b", ["a"]), None);
+}
+
+#[test]
+fn errors_carry_suggestions() {
+    let source = "title: \"T\"\n\nVESE[1]\nOne\n";
+    let diagnostic = error_diagnostic(&parse_song(source).unwrap_err(), source);
+    assert_eq!(diagnostic.message, "unknown section kind `VESE`");
+    assert_eq!(&source[diagnostic.span.start..diagnostic.span.end], "VESE");
+    assert_eq!(diagnostic.help.as_deref(), Some("did you mean `VERSE`?"));
+
+    let source = "titel: \"T\"\n\nVERSE\nOne\n";
+    let diagnostic = error_diagnostic(&parse_song(source).unwrap_err(), source);
+    assert_eq!(diagnostic.message, "unknown metadata key `titel`");
+    assert_eq!(diagnostic.help.as_deref(), Some("did you mean `title`?"));
+
+    let song = parse_song("$who = \"x\"\ntitle: \"T\"\n\nVERSE\nOne ${whoo}\n").unwrap();
+    let diagnostics = resolve(&song).diagnostics;
+    assert_eq!(diagnostics[0].message, "undefined variable `whoo`");
+    assert_eq!(diagnostics[0].help.as_deref(), Some("did you mean `who`?"));
+}
@@ -0,0 +1,122 @@
+use lyrics_dsl::ast::Timestamp;
+use lyrics_dsl::audio::{wav_length, Audio, AudioError, Phrase};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::sync::{stub, Taps};
+
+const RATE: u32 = 8000;
+
+/// A 16-bit WAV of `channels` channels, silent but for a tone during each
+/// of `bursts`, given in seconds.
+fn wav(seconds: f64, bursts: &[(f64, f64)], channels: u16) -> Vec<u8> {
+    let count = (seconds * RATE as f64) as usize;
+    let mut data = Vec::new();
+    for i in 0..count {
+        let t = i as f64 / RATE as f64;
+        let loud = bursts.iter().any(|(from, to)| (*from..*to).contains(&t));
+        let value = if loud {
+            ((t * 440.0 * std::f64::consts::TAU).sin() * 12_000.0) as i16
+        } else {
+            (i % 7) as i16 - 3
+        };
+        for _ in 0..channels {
+            data.extend(value.to_le_bytes());
+        }
+    }
+    let block = 2 * channels;
+    let mut out = Vec::new();
+    out.extend(b"RIFF");
+    out.extend((36 + data.len() as u32).to_le_bytes());
+    out.extend(b"WAVEfmt ");
+    out.extend(16u32.to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(channels.to_le_bytes());
+    out.extend(RATE.to_le_bytes());
+    out.extend((RATE * block as u32).to_le_bytes());
+    out.extend(block.to_le_bytes());
+    out.extend(16u16.to_le_bytes());
+    out.extend(b"data");
+    out.extend((data.len() as u32).to_le_bytes());
+    out.extend(data);
+    out
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.05
+}
+
+#[test]
+fn wav_files_are_decoded_to_mono() {
+    let audio = Audio::read_wav(&wav(1.5, &[], 2)).unwrap();
+    assert_eq!(audio.sample_rate, RATE);
+    assert!(close(audio.duration(), 1.5));
+    assert!(close(wav_length(&wav(1.5, &[], 2)).unwrap(), 1.5));
+    assert_eq!(Audio::read_wav(b"ID3 not a wav"), Err(AudioError::NotWav));
+}
+
+#[test]
+fn phrases_start_at_onsets_and_bridge_breaths() {
+    // The 0.1s breath inside the second phrase does not split it.
+    let audio = Audio::read_wav(&wav(8.0, &[(1.0, 3.0), (4.5, 5.5), (5.6, 7.0)], 1)).unwrap();
+    let phrases = audio.phrases();
+    assert_eq!(phrases.len(), 2, "{:?}", phrases);
+    assert!(close(phrases[0].start, 1.0) && close(phrases[0].end, 3.0));
+    assert!(close(phrases[1].start, 4.5) && close(phrases[1].end, 7.0));
+
+    let silent = Audio::read_wav(&wav(2.0, &[], 1)).unwrap();
+    assert!(silent.phrases().is_empty());
+}
+
+#[test]
+fn lines_share_the_sung_time_by_syllables_and_skip_gaps() {
+    let song = parse_song(
+        "title: \"T\"\n\nVERSE\nOne two\nThree four\n\nCHORUS\nFive six\n\nREPEAT CHORUS\n",
+    )
+    .unwrap();
+    let phrases = [
+        Phrase {
+            start: 1.0,
+            end: 5.0,
+        },
+        Phrase {
+            start: 10.0,
+            end: 14.0,
+        },
+    ];
+    let timings: Vec<_> = stub(&song, &phrases)
+        .lines()
+        .map(|(_, line)| line.timing.unwrap().millis)
+        .collect();
+    // Four equal lines, the repeated chorus included, over 8s sung.
+    assert_eq!(timings, [1000, 3000, 10_000]);
+}
+
+#[test]
+fn taps_stamp_lines_in_order_and_can_be_taken_back() {
+    let song = parse_song(
+        "title: \"T\"\n\nMACRO hook\nOh\n\nVERSE\nOne {timing: 0:09.00}\nUSE hook\nTwo\n\nREPEAT VERSE\n\nCHORUS\nThree\n",
+    )
+    .unwrap();
+    let mut taps = Taps::new(&song);
+    assert_eq!(taps.current(), Some((0, 0)));
+    assert_eq!(taps.progress(), (0, 3));
+
+    taps.tap(Timestamp::from_millis(1000));
+    taps.tap(Timestamp::from_millis(900));
+    taps.tap(Timestamp::from_millis(2500));
+    taps.undo();
+    taps.tap(Timestamp::from_millis(2000));
+    assert_eq!(taps.progress(), (2, 3));
+    assert_eq!(taps.current(), Some((2, 0)));
+
+    let timed = taps.apply(&song);
+    let timings: Vec<_> = timed
+        .lines()
+        .map(|(_, line)| line.timing.map(|t| t.millis))
+        .collect();
+    assert_eq!(timings, [Some(1000), None, Some(2000), None]);
+
+    taps.tap(Timestamp::from_millis(3000));
+    assert!(taps.is_finished());
+    taps.tap(Timestamp::from_millis(4000));
+    assert_eq!(taps.progress(), (3, 3));
+}
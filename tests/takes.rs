@@ -0,0 +1,71 @@
+use lyrics_dsl::export::{exporter, without_alternatives};
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::takes::{choose, stacks, TakesError};
+
+const SONG: &str = "title: \"Takes\"
+
+VERSE[1]
+I walked along the river / alt: \"I wandered by the water\" / alt: \"lo~~ng by the <tbd: river word>\" {rhyme: A}
+The water cold and grey
+";
+
+#[test]
+fn alternatives_are_kept_beside_the_line_and_listed() {
+    let song = parse_song(SONG).unwrap();
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.text, "I walked along the river");
+    assert_eq!(line.rhyme, Some('A'));
+    let alternatives: Vec<_> = line.alternatives.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(
+        alternatives,
+        ["I wandered by the water", "lo~~ng by the <tbd: river word>"]
+    );
+    assert_eq!(format_song(&song), SONG);
+
+    let found = stacks(&song, SONG);
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].line, found[0].section.as_str()), (4, "Verse 1"));
+
+    // Only the take that is sung is exported.
+    let song = without_alternatives(&resolve(&song).song);
+    let json = exporter("json").unwrap().export(&song).unwrap();
+    assert!(!String::from_utf8(json).unwrap().contains("wandered"));
+}
+
+#[test]
+fn choosing_swaps_an_alternative_in_and_can_be_undone() {
+    let mut song = parse_song(SONG).unwrap();
+    assert_eq!(
+        choose(&mut song, SONG, 4, 2),
+        Ok("long by the ____".to_string())
+    );
+    let line = &song.sections[0].lines[0];
+    assert_eq!(line.sustains.len(), 1);
+    assert_eq!(line.placeholders[0].hint.as_deref(), Some("river word"));
+    assert_eq!(line.alternatives[1].text, "I walked along the river");
+
+    let source = format_song(&song);
+    assert!(source.contains(
+        "lo~~ng by the <tbd: river word> / alt: \"I wandered by the water\" \
+         / alt: \"I walked along the river\" {rhyme: A}\n"
+    ));
+    let mut again = parse_song(&source).unwrap();
+    choose(&mut again, &source, 4, 2).unwrap();
+    assert_eq!(again.sections[0].lines[0].text, "I walked along the river");
+
+    assert_eq!(
+        choose(&mut song, SONG, 4, 3),
+        Err(TakesError::NoSuchAlternative {
+            line: 4,
+            alt: 3,
+            count: 2
+        })
+    );
+    assert_eq!(
+        choose(&mut song, SONG, 5, 1),
+        Err(TakesError::NoAlternatives(5))
+    );
+    assert_eq!(choose(&mut song, SONG, 3, 1), Err(TakesError::NotALine(3)));
+}
@@ -0,0 +1,91 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::playback::{cue_at, cues, is_timed};
+use lyrics_dsl::teleprompter::{RowStyle, Teleprompter, ROWS_PER_SECOND};
+
+const SONG: &str = "title: \"T\"
+
+VERSE[1]
+Hello there {timing: 0:01.00}
+Goodbye now {timing: 0:03.00}
+
+CHORUS
+La la la {timing: 0:05.00}
+";
+
+#[test]
+fn cues_follow_the_timings() {
+    let song = parse_song(SONG).unwrap();
+    let cues = cues(&song);
+    assert!(is_timed(&cues));
+    assert_eq!(cues[2].section, "Chorus");
+    assert_eq!(cue_at(&cues, 0.5), None);
+    assert_eq!(cue_at(&cues, 1.0), Some(0));
+    assert_eq!(cue_at(&cues, 4.9), Some(1));
+    assert_eq!(cue_at(&cues, 60.0), Some(2));
+}
+
+#[test]
+fn lays_out_sections_as_rows() {
+    let prompter = Teleprompter::new(&parse_song(SONG).unwrap(), 1.0, false);
+    let rows: Vec<&str> = prompter.rows().iter().map(|r| r.text.as_str()).collect();
+    assert_eq!(
+        rows,
+        [
+            "VERSE 1",
+            "Hello there",
+            "Goodbye now",
+            "",
+            "CHORUS",
+            "La la la"
+        ]
+    );
+}
+
+#[test]
+fn scrolls_steadily_and_pauses() {
+    let mut prompter = Teleprompter::new(&parse_song(SONG).unwrap(), 2.0, false);
+    prompter.tick(1.0 / ROWS_PER_SECOND);
+    assert_eq!(prompter.current_row(), 2);
+
+    prompter.toggle_pause();
+    prompter.tick(10.0);
+    assert_eq!(prompter.current_row(), 2);
+
+    prompter.toggle_pause();
+    prompter.tick(100.0);
+    assert!(prompter.finished());
+
+    prompter.change_speed(100.0);
+    assert_eq!(prompter.speed, 4.0);
+    prompter.restart();
+    assert_eq!(prompter.current_row(), 0);
+}
+
+#[test]
+fn timed_mode_follows_the_song() {
+    let mut prompter = Teleprompter::new(&parse_song(SONG).unwrap(), 1.0, true);
+    prompter.tick(3.5);
+    assert_eq!(prompter.rows()[prompter.current_row()].text, "Goodbye now");
+
+    // Scrolling moves the clock to the line scrolled to.
+    prompter.scroll(1);
+    prompter.tick(0.0);
+    assert_eq!(prompter.rows()[prompter.current_row()].text, "La la la");
+    assert_eq!(prompter.elapsed(), 5.0);
+}
+
+#[test]
+fn frames_put_the_reading_line_a_third_down() {
+    let mut prompter = Teleprompter::new(&parse_song(SONG).unwrap(), 1.0, false);
+    prompter.scroll(1);
+    let frame = prompter.frame(15, 6);
+    assert_eq!(frame.len(), 6);
+    assert_eq!(frame[1], ("    VERSE 1".to_string(), RowStyle::Heading));
+    assert_eq!(frame[2], ("  Hello there".to_string(), RowStyle::Current));
+    assert_eq!(frame[3], ("  Goodbye now".to_string(), RowStyle::Normal));
+
+    // Long lines wrap to the width.
+    let narrow = prompter.frame(6, 6);
+    assert_eq!(narrow[2].0, "Hello");
+    assert_eq!(narrow[3], ("there".to_string(), RowStyle::Current));
+}
@@ -0,0 +1,153 @@
+use lyrics_dsl::export::{with_sheet_options, ExportError, SheetOptions};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::template::{Layout, Template, TemplateError};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn render(source: &str, data: serde_json::Value) -> String {
+    Template::parse(source)
+        .unwrap()
+        .render(&data, &BTreeMap::new())
+        .unwrap()
+}
+
+#[test]
+fn values_are_escaped_unless_tripled() {
+    let data = json!({ "a": "<b>&</b>", "n": { "m": 3 } });
+    assert_eq!(
+        render("{{a}} {{{a}}} {{ n.m }}{{missing}}", data),
+        "&lt;b&gt;&amp;&lt;/b&gt; <b>&</b> 3"
+    );
+}
+
+#[test]
+fn conditionals_and_loops() {
+    let data = json!({
+        "title": "T",
+        "items": ["x", "y", "z"],
+        "none": [],
+        "zero": 0,
+    });
+    assert_eq!(
+        render(
+            "{{#each items}}{{@index}}{{this}}{{title}}{{#unless @last}},{{/unless}}{{/each}}",
+            data.clone()
+        ),
+        "0xT,1yT,2zT"
+    );
+    assert_eq!(
+        render(
+            "{{#each none}}x{{else}}empty{{/each}} {{#if zero}}yes{{else}}no{{/if}}",
+            data
+        ),
+        "empty no"
+    );
+}
+
+#[test]
+fn comments_write_nothing() {
+    assert_eq!(render("a{{! not this }}b", json!({})), "ab");
+}
+
+#[test]
+fn syntax_errors_name_their_line() {
+    let error = Template::parse("a\n{{#if x}}\nb").unwrap_err();
+    assert_eq!(
+        error,
+        TemplateError::Syntax {
+            line: 2,
+            message: "`#if` is never closed".to_string()
+        }
+    );
+    let error = Template::parse("{{#each x}}{{/if}}").unwrap_err();
+    assert!(
+        error.to_string().contains("closes the `#each`"),
+        "{}",
+        error
+    );
+    assert!(Template::parse("{{/if}}").is_err());
+    assert!(Template::parse("{{#with x}}{{/with}}").is_err());
+    assert!(Template::parse("{{a").is_err());
+}
+
+#[test]
+fn partials_are_read_beside_the_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sheet.html");
+    std::fs::write(&path, "[{{> head}}]").unwrap();
+    std::fs::write(dir.path().join("head.html"), "{{title}}{{> tail}}").unwrap();
+    std::fs::write(dir.path().join("tail.html"), "!").unwrap();
+    let layout = Layout::load(&path).unwrap();
+    assert_eq!(layout.render(&json!({ "title": "T" })).unwrap(), "[T!]");
+
+    std::fs::write(dir.path().join("tail.html"), "{{> head}}").unwrap();
+    let layout = Layout::load(&path).unwrap();
+    assert_eq!(
+        layout.render(&json!({})).unwrap_err(),
+        TemplateError::TooDeep
+    );
+
+    std::fs::remove_file(dir.path().join("head.html")).unwrap();
+    assert!(matches!(
+        Layout::load(&path),
+        Err(TemplateError::Read { .. })
+    ));
+}
+
+const SONG: &str = "title: \"Night Light\"
+artist: \"Ann\"
+
+VERSE[1]
+I saw you in the light {chord: C}
+  + in the light
+We talked all through the night {chord: G}
+Then morning came {chord: C}
+";
+
+fn sheet(template: &str) -> Result<String, ExportError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sheet.html");
+    std::fs::write(&path, template).unwrap();
+    let options = SheetOptions {
+        template: Some(Layout::load(&path).unwrap()),
+        ..SheetOptions::default()
+    };
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let html = with_sheet_options("html", options)?.export(&song)?;
+    Ok(String::from_utf8(html).unwrap())
+}
+
+#[test]
+fn the_html_sheet_fills_in_a_template() {
+    let html = sheet(
+        "<h1>{{title}} by {{artist}}</h1>{{#each sections}}<h2>{{label}}</h2>\
+         {{#each lines}}{{rhyme}}{{syllables}}:{{text}}{{#each harmony}}+{{text}}{{/each}};{{/each}}\
+         {{/each}} {{#each chords}}{{this}}{{/each}} {{syllables}}",
+    )
+    .unwrap();
+    assert_eq!(
+        html,
+        "<h1>Night Light by Ann</h1><h2>Verse 1</h2>\
+         A6:I saw you in the light+in the light;A6:We talked all through the night;4:Then morning came; \
+         CG 16"
+    );
+}
+
+#[test]
+fn the_pdf_sheet_takes_no_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sheet.html");
+    std::fs::write(&path, "x").unwrap();
+    let options = SheetOptions {
+        template: Some(Layout::load(&path).unwrap()),
+        ..SheetOptions::default()
+    };
+    assert!(matches!(
+        with_sheet_options("pdf", options),
+        Err(ExportError::Unsupported {
+            option: "templates",
+            ..
+        })
+    ));
+}
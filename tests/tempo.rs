@@ -0,0 +1,81 @@
+use lyrics_dsl::analysis::TempoMap;
+use lyrics_dsl::ast::Timestamp;
+use lyrics_dsl::export::exporter;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+const SONG: &str = "title: \"Shift\"
+tempo: 120
+tempo: 60 @ 0:10.00
+tempo: 180 @ 0:20.00
+
+VERSE
+Faster now {timing: 0:25.00}
+";
+
+#[test]
+fn beats_follow_every_tempo_change() {
+    let song = parse_song(SONG).unwrap();
+    assert_eq!(song.tempo_changes.len(), 2);
+
+    let tempo = TempoMap::from_song(&song);
+    let at = |s: u64| Timestamp::from_millis(s * 1000);
+    assert_eq!(tempo.beat_at(at(10)), 20.0);
+    assert_eq!(tempo.beat_at(at(20)), 30.0);
+    assert_eq!(tempo.beat_at(at(25)), 45.0);
+    assert_eq!(tempo.bpm_at(at(15)), 60.0);
+    assert_eq!(tempo.time_at_beat(45.0), at(25));
+    assert_eq!(tempo.time_at_beat(25.0), at(15));
+}
+
+#[test]
+fn tempo_changes_must_move_forward() {
+    let song =
+        parse_song("title: \"Back\"\ntempo: 100 @ 1:00.00\ntempo: 90 @ 0:30.00\n\nVERSE\nHi\n")
+            .unwrap();
+    let resolved = resolve(&song);
+    assert_eq!(resolved.diagnostics.len(), 1);
+    assert_eq!(
+        resolved.diagnostics[0].message,
+        "tempo change at 0:30.00 is not later than the one at 1:00.00"
+    );
+    assert_eq!(resolved.diagnostics[0].labels.len(), 1);
+}
+
+#[test]
+fn the_song_tempo_must_be_in_range() {
+    let source = "title: \"Still\"\ntempo: 0\n\nVERSE\nHi\n";
+    let song = parse_song(source).unwrap();
+    let resolved = resolve(&song);
+    assert_eq!(resolved.diagnostics.len(), 1);
+    assert_eq!(
+        resolved.diagnostics[0].message,
+        "tempo must be between 1 and 1000 BPM, found 0"
+    );
+    assert_eq!(resolved.diagnostics[0].code.map(|c| c.id), Some("E007"));
+    assert_eq!(resolved.diagnostics[0].span.line_col(source).0, 2);
+}
+
+#[test]
+fn midi_places_lyrics_through_the_tempo_map() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let midi = exporter("midi").unwrap().export(&song).unwrap();
+    assert_eq!(&midi[..4], b"MThd");
+
+    // 60 bpm is 1,000,000 µs per quarter note: 0x0F4240.
+    let tempo_event = [0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40];
+    assert!(midi.windows(6).any(|w| w == tempo_event));
+    // "Faster " lands on beat 45, tick 45 * 480 = 21600 (0x81 0xA8 0x60).
+    let lyric = [0x81, 0xA8, 0x60, 0xFF, 0x05, 0x07];
+    assert!(midi.windows(6).any(|w| w == lyric));
+}
+
+#[test]
+fn tempos_outside_the_sane_range_are_reported() {
+    for tempo in ["tempo: 99999999999999999999", "tempo: 2000 @ 0:10.00"] {
+        let source = format!("title: \"T\"\n{}\n\nVERSE\nHi\n", tempo);
+        let resolved = resolve(&parse_song(&source).unwrap());
+        assert_eq!(resolved.diagnostics.len(), 1, "{}", tempo);
+        assert_eq!(resolved.diagnostics[0].code.map(|c| c.id), Some("E007"));
+    }
+}
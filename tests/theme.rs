@@ -0,0 +1,79 @@
+use colored::Color;
+use lyrics_dsl::ast::SectionKind;
+use lyrics_dsl::config::{Config, ConfigError};
+use lyrics_dsl::diagnostic::Severity;
+use lyrics_dsl::theme::{Role, Style, Theme};
+
+#[test]
+fn styles_read_color_names_hex_and_attributes() {
+    let style = Style::parse("bright red bold").unwrap();
+    assert_eq!(style.color, Some(Color::BrightRed));
+    assert!(style.bold && !style.dimmed);
+    let style = Style::parse("#0072b2 underline").unwrap();
+    assert_eq!(
+        style.color,
+        Some(Color::TrueColor {
+            r: 0x00,
+            g: 0x72,
+            b: 0xb2
+        })
+    );
+    assert!(style.underline);
+    assert_eq!(Style::parse("plain"), Some(Style::PLAIN));
+    assert_eq!(Style::parse("mauve"), None);
+    assert_eq!(Style::parse("#12345"), None);
+}
+
+#[test]
+fn every_built_in_theme_tells_severities_and_sections_apart() {
+    for name in Theme::names() {
+        let theme = Theme::named(name).unwrap();
+        let error = theme.style(Severity::Error.into());
+        let warning = theme.style(Severity::Warning.into());
+        assert_ne!(error, warning, "{}", name);
+        let verse = theme.style(Role::of_section(SectionKind::Verse));
+        let chorus = theme.style(Role::of_section(SectionKind::Chorus));
+        assert_ne!(verse, chorus, "{}", name);
+    }
+    let high_contrast = Theme::named("high-contrast").unwrap();
+    assert!(Role::ALL.iter().all(|r| !high_contrast.style(*r).dimmed));
+    assert_eq!(
+        Role::of_section(SectionKind::Outro),
+        Role::of_section(SectionKind::Custom)
+    );
+}
+
+#[test]
+fn config_picks_a_theme_and_restyles_roles_over_it() {
+    let config = Config::parse("theme.error = \"magenta\"\ntheme.name = \"colorblind\"\n").unwrap();
+    assert_eq!(config.theme.name, "colorblind");
+    assert_eq!(config.theme.style(Role::Error).color, Some(Color::Magenta));
+    let colorblind = Theme::named("colorblind").unwrap();
+    assert_eq!(
+        config.theme.style(Role::Warning),
+        colorblind.style(Role::Warning)
+    );
+    assert_eq!(Config::parse("").unwrap().theme, Theme::default());
+}
+
+#[test]
+fn config_rejects_unknown_themes_roles_and_styles() {
+    assert_eq!(
+        Config::parse("theme.name = \"neon\"\n").unwrap_err(),
+        ConfigError::UnknownTheme {
+            line: 1,
+            name: "neon".to_string()
+        }
+    );
+    let err = Config::parse("theme.footer = \"red\"\n").unwrap_err();
+    assert!(
+        err.to_string().contains("unknown theme role `footer`"),
+        "{}",
+        err
+    );
+    let err = Config::parse("theme.error = \"rouge\"\n").unwrap_err();
+    assert!(err.to_string().contains("invalid style `rouge`"), "{}", err);
+    // `theme` is a table, so it cannot also be a string.
+    let err = Config::parse("theme = \"colorblind\"\n").unwrap_err();
+    assert!(err.to_string().contains("`theme.name = "), "{}", err);
+}
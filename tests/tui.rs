@@ -0,0 +1,213 @@
+use lyrics_dsl::ast::Span;
+use lyrics_dsl::diagnostic::{Diagnostic, Severity};
+use lyrics_dsl::history::Run;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::tui::{highlight, tree, Buffer, Focus, Kind, Paint, Tab, Workbench};
+
+const SONG: &str = "title: \"Night\"
+$who = \"you\"
+
+CHORUS[1] &hook {mood: \"calm\"}
+Hold $who {chord: G} !! todo: rhyme
+  + (ooh)
+REPEAT CHORUS[1]
+";
+
+fn kinds(source: &str, line: usize) -> Vec<(&str, Kind)> {
+    let text = source.split('\n').nth(line).unwrap();
+    highlight(source)[line]
+        .iter()
+        .map(|(range, kind)| (&text[range.clone()], *kind))
+        .collect()
+}
+
+#[test]
+fn highlighting_follows_the_grammar_line_by_line() {
+    assert_eq!(
+        kinds(SONG, 0),
+        [("title", Kind::Meta), (" \"Night\"", Kind::Value)]
+    );
+    assert_eq!(kinds(SONG, 1)[0], ("$who ", Kind::Variable));
+    assert_eq!(
+        kinds(SONG, 3),
+        [
+            ("CHORUS[1] ", Kind::Keyword),
+            ("&hook", Kind::Anchor),
+            ("{mood: \"calm\"}", Kind::Attribute)
+        ]
+    );
+    assert_eq!(
+        kinds(SONG, 4),
+        [
+            ("Hold ", Kind::Text),
+            ("$who", Kind::Variable),
+            (" ", Kind::Text),
+            ("{chord: G}", Kind::Attribute),
+            (" ", Kind::Text),
+            ("!! todo: rhyme", Kind::Note)
+        ]
+    );
+    assert_eq!(
+        kinds(SONG, 5),
+        [("+", Kind::Harmony), (" (ooh)", Kind::Text)]
+    );
+    assert_eq!(kinds(SONG, 6), [("REPEAT CHORUS[1]", Kind::Keyword)]);
+    // Once sections start, a colon no longer makes metadata.
+    assert_eq!(
+        kinds("VERSE\nnote: this\n", 1),
+        [("note: this", Kind::Text)]
+    );
+}
+
+#[test]
+fn the_buffer_edits_across_lines() {
+    let mut buffer = Buffer::new("ab\n  + cd");
+    buffer.vertical(1);
+    buffer.end();
+    buffer.newline();
+    buffer.insert('é');
+    assert_eq!(buffer.text(), "ab\n  + cd\n  é");
+    assert_eq!((buffer.row, buffer.col), (2, 3));
+    buffer.home();
+    buffer.backspace();
+    assert_eq!(buffer.text(), "ab\n  + cd  é");
+    buffer.go_to(1);
+    buffer.delete();
+    buffer.end();
+    buffer.delete();
+    assert_eq!(buffer.text(), "a  + cd  é");
+    assert!(buffer.modified);
+    buffer.go_to(buffer.text().len());
+    assert_eq!(buffer.offset(), buffer.text().len());
+}
+
+#[test]
+fn the_tree_lists_songs_and_the_folders_holding_them() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("album/demos")).unwrap();
+    std::fs::create_dir_all(dir.path().join("empty")).unwrap();
+    std::fs::create_dir_all(dir.path().join(".lyricsdsl")).unwrap();
+    for file in [
+        "b.lyr",
+        "album/a.lyr",
+        "album/notes.txt",
+        ".lyricsdsl/x.lyr",
+    ] {
+        std::fs::write(dir.path().join(file), "").unwrap();
+    }
+    let entries: Vec<(String, usize, bool)> = tree(dir.path())
+        .unwrap()
+        .into_iter()
+        .map(|e| {
+            let name = e
+                .path
+                .strip_prefix(dir.path())
+                .unwrap()
+                .display()
+                .to_string();
+            (name, e.depth, e.dir)
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("album".to_string(), 0, true),
+            ("album/a.lyr".to_string(), 1, false),
+            ("b.lyr".to_string(), 0, false),
+        ]
+    );
+}
+
+fn text(row: &[(String, Paint)]) -> String {
+    row.iter().map(|(text, _)| text.as_str()).collect()
+}
+
+#[test]
+fn the_workbench_lays_out_every_pane() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("night.lyr");
+    std::fs::write(&path, SONG).unwrap();
+    let mut bench = Workbench::new(dir.path()).unwrap();
+    bench.open(&path, SONG);
+    assert!(bench.stale());
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let at = SONG.find("&hook").unwrap();
+    let warning = Diagnostic::new(Severity::Warning, "check this", Span::new(at, at + 5));
+    bench.checked(SONG.to_string(), vec![warning], Some(song));
+    assert!(!bench.stale());
+
+    let frame = bench.frame(120, 20);
+    assert_eq!(frame.len(), 20);
+    for row in &frame {
+        assert_eq!(text(row).chars().count(), 120);
+    }
+    let screen: Vec<String> = frame.iter().map(|row| text(row)).collect();
+    assert!(screen[0].contains(" songs") && screen[0].contains(" night.lyr"));
+    assert!(screen[0].contains("analysis · txt"));
+    assert!(screen[1].contains("night.lyr") && screen[1].contains("[Chorus 1] singability"));
+    assert!(screen.iter().any(|r| r.contains("diagnostics (1)")));
+    assert!(screen.iter().any(|r| r.contains("   4:11  check this")));
+    assert!(screen[19].contains("0 error(s), 1 warning(s)"));
+    // The warning is marked beside line 4 and the cursor sits on line 1.
+    assert!(frame[4]
+        .iter()
+        .any(|(t, p)| t == "● " && *p == Paint::Severity(Severity::Warning)));
+    assert!(frame[1]
+        .iter()
+        .any(|(t, p)| t == "t" && *p == Paint::Cursor));
+
+    bench.focus = Focus::Preview;
+    bench.switch_tab(2);
+    assert_eq!(bench.tab, Tab::Export(1));
+    bench.switch_tab(-3);
+    assert_eq!(bench.tab, Tab::Export(6));
+    bench.focus = Focus::Diagnostics;
+    bench.jump_to_diagnostic();
+    assert_eq!(
+        (bench.focus, bench.buffer.row, bench.buffer.col),
+        (Focus::Editor, 3, 10)
+    );
+
+    // On a narrow screen the preview takes the editor's place.
+    bench.focus = Focus::Preview;
+    let narrow: Vec<String> = bench.frame(70, 10).iter().map(|r| text(r)).collect();
+    assert!(narrow[0].contains("│…") && narrow[0].contains("ttml · json"));
+    assert!(!narrow[0].contains("night.lyr") && narrow[2].contains("\"metadata\""));
+}
+
+#[test]
+fn edits_undo_by_word_and_saving_settles_the_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("night.lyr");
+    let mut bench = Workbench::new(dir.path()).unwrap();
+    bench.open(&path, "VERSE\n");
+    bench.buffer.vertical(1);
+    for c in "la la".chars() {
+        bench.edit(Some(Run::Typing), |b| b.insert(c));
+        if c == ' ' {
+            bench.history.seal();
+        }
+    }
+    assert_eq!(bench.buffer.text(), "VERSE\nla la");
+    assert!(bench.undo());
+    assert_eq!(bench.buffer.text(), "VERSE\nla ");
+    assert!(bench.undo());
+    assert_eq!(bench.buffer.text(), "VERSE\n");
+    assert!(!bench.buffer.modified);
+    assert!(!bench.undo());
+    assert!(bench.redo());
+    assert!(bench.buffer.modified);
+
+    bench.saved();
+    bench.edit(None, Buffer::newline);
+    assert!(bench.buffer.modified);
+    assert!(bench.undo());
+    assert!(!bench.buffer.modified);
+
+    // Recovered edits replace the text as a step of their own.
+    bench.replace("VERSE\nrecovered\n");
+    assert!(bench.buffer.modified);
+    assert!(bench.undo());
+    assert_eq!(bench.buffer.text(), "VERSE\nla ");
+}
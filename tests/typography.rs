@@ -0,0 +1,56 @@
+use lyrics_dsl::export::{with_sheet_options, ExportError, SheetOptions};
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::typography::smarten;
+
+#[test]
+fn quotes_dashes_and_ellipses_become_typographic() {
+    assert_eq!(
+        smarten("She said \"don't go\"... then -- silence --- gone"),
+        "She said “don’t go”… then – silence — gone"
+    );
+    assert_eq!(
+        smarten("'Cause I'm singin' 'bout the '90s, 'hello'"),
+        "’Cause I’m singin’ ’bout the ’90s, ‘hello’"
+    );
+    assert_eq!(smarten("(\"Oh\")"), "(“Oh”)");
+}
+
+#[test]
+fn sheets_smarten_only_when_asked() {
+    let source = "title: \"Don't Stop...\"\n\nVERSE[1]\nWe're \"fine\" -- aren't we\n";
+    let song = parse_song(source).unwrap();
+    let html = |smart_typography| {
+        let options = SheetOptions {
+            smart_typography,
+            ..SheetOptions::default()
+        };
+        let html = with_sheet_options("html", options)
+            .unwrap()
+            .export(&song)
+            .unwrap();
+        String::from_utf8(html).unwrap()
+    };
+    assert!(html(true).contains("<h1>Don’t Stop…</h1>"));
+    assert!(html(true).contains("We’re “fine” – aren’t we</p>"));
+    assert!(html(false).contains("We're &quot;fine&quot; -- aren't we</p>"));
+
+    let options = SheetOptions {
+        smart_typography: true,
+        ..SheetOptions::default()
+    };
+    let pdf = with_sheet_options("pdf", options.clone())
+        .unwrap()
+        .export(&song)
+        .unwrap();
+    // WinAnsi curly apostrophe, quotes and en dash.
+    assert!(pdf
+        .windows(20)
+        .any(|w| w == b"(We\x92re \x93fine\x94 \x96 aren"));
+    assert!(matches!(
+        with_sheet_options("lrc", options),
+        Err(ExportError::Unsupported {
+            option: "smart typography",
+            ..
+        })
+    ));
+}
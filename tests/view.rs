@@ -0,0 +1,56 @@
+use lyrics_dsl::ast::SectionKind;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::view::{render, rows, RowKind};
+
+const SONG: &str = "title: \"Show\"
+time: 4/4
+
+VERSE[1]
+Walking down the river {chord: C, G}
+| Under the | silver moon {chord: Am, F}
+
+CHORUS
+Oh my love \\
+  hold on {chord: C, G, D}
+
+REPEAT VERSE[1]
+";
+
+#[test]
+fn chords_sit_above_the_words_they_fall_on() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let rows = rows(&song, SONG);
+    let text = render(&rows, false, |_, text| text.to_string());
+    assert!(text.starts_with(
+        "Show
+
+[Verse 1]
+C            G
+Walking down the river
+Am        F
+Under the silver moon
+
+[Chorus]
+C  G
+Oh my love
+D
+hold on
+"
+    ));
+    assert_eq!(text.matches("[Verse 1]").count(), 2);
+    assert_eq!(rows[2].kind, RowKind::Heading(SectionKind::Verse));
+}
+
+#[test]
+fn line_numbers_follow_the_source() {
+    let song = resolve(&parse_song(SONG).unwrap()).song;
+    let text = render(&rows(&song, SONG), true, |kind, text| match kind {
+        RowKind::Chords => format!("<{}>", text),
+        _ => text.to_string(),
+    });
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[3], "      <C            G>");
+    assert_eq!(lines[4], "   5  Walking down the river");
+    assert_eq!(lines[12], "  10  hold on");
+}
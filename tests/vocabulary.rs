@@ -0,0 +1,65 @@
+use lyrics_dsl::analysis::Vocabulary;
+use lyrics_dsl::collate::Collator;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+
+fn song(source: &str) -> lyrics_dsl::ast::Song {
+    resolve(&parse_song(source).unwrap()).song
+}
+
+#[test]
+fn counts_words_per_section_kind_including_repeats() {
+    let mut vocabulary = Vocabulary::new();
+    vocabulary.add(
+        "one",
+        &song("title: \"One\"\n\nVERSE[1]\nDon’t stop, don't\n\nCHORUS[1]\nStop now\n\nREPEAT CHORUS[1]\n"),
+    );
+
+    let words = vocabulary.words();
+    assert_eq!(words[0].word, "stop");
+    assert_eq!(words[0].total, 3);
+    assert_eq!(words[0].by_kind[..2], [1, 2]);
+    assert_eq!(words[1].word, "don't");
+    assert_eq!(words[1].total, 2);
+    assert_eq!(vocabulary.tokens(), 7);
+    assert_eq!(vocabulary.types(), 3);
+    assert_eq!(
+        vocabulary.to_csv().lines().next(),
+        Some("word,total,verse,chorus,bridge,pre-chorus,outro,intro")
+    );
+}
+
+#[test]
+fn corpus_csv_has_a_column_per_song() {
+    let mut vocabulary = Vocabulary::new();
+    vocabulary.add("first", &song("title: \"A\"\n\nVERSE[1]\nhello there\n"));
+    vocabulary.add(
+        "second, take 2",
+        &song("title: \"B\"\n\nVERSE[1]\nhello again\n"),
+    );
+
+    let csv = vocabulary.to_csv();
+    let rows: Vec<&str> = csv.lines().map(|l| l.trim_end_matches('\r')).collect();
+    assert!(rows[0].ends_with(",intro,first,\"second, take 2\""));
+    assert_eq!(rows[1], "hello,2,2,0,0,0,0,0,1,1");
+    assert_eq!(rows[2], "again,1,1,0,0,0,0,0,0,1");
+    assert_eq!(rows[3], "there,1,1,0,0,0,0,0,1,0");
+}
+
+#[test]
+fn words_are_ordered_for_the_locale() {
+    let source = "title: \"Ñ\"\n\nVERSE[1]\nnz ñu oso\n";
+    let mut vocabulary = Vocabulary::new();
+    vocabulary.add("one", &song(source));
+    let words: Vec<&str> = vocabulary.words().iter().map(|c| c.word.as_str()).collect();
+    assert_eq!(words, ["ñu", "nz", "oso"]);
+
+    let mut vocabulary = Vocabulary::with_collator(Collator::new("es"));
+    vocabulary.add("one", &song("title: \"Ñ\"\n\nVERSE[1]\nñu nz oso\n"));
+    let words: Vec<&str> = vocabulary
+        .words_alphabetically()
+        .iter()
+        .map(|c| c.word.as_str())
+        .collect();
+    assert_eq!(words, ["nz", "ñu", "oso"]);
+}
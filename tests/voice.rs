@@ -0,0 +1,66 @@
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::semantic::resolve;
+use lyrics_dsl::voice::{Pitch, SongRange, VocalRange, VoiceError};
+
+#[test]
+fn pitches_are_read_in_scientific_notation() {
+    assert_eq!(Pitch::parse("C4"), Some(Pitch(60)));
+    assert_eq!(Pitch::parse("A4"), Some(Pitch(69)));
+    assert_eq!(Pitch::parse("Bb2"), Some(Pitch(46)));
+    // The octave goes with the letter.
+    assert_eq!(Pitch::parse("B#3"), Some(Pitch(60)));
+    assert_eq!(Pitch::parse("Cb4"), Some(Pitch(59)));
+    assert_eq!(Pitch::parse("H2"), None);
+    assert_eq!(Pitch(70).name(true), "Bb4");
+    assert_eq!(Pitch(70).name(false), "A#4");
+}
+
+#[test]
+fn ranges_tell_which_shifts_fit_a_singer() {
+    let song = VocalRange::parse("F2-D4").unwrap();
+    let singer = VocalRange::parse("E4 - G2").unwrap();
+    assert_eq!(singer.name(false), "G2-E4");
+    assert_eq!(song.semitones(), 21);
+    assert!(!singer.contains(&song));
+    assert!(singer.contains(&song.transpose(2)));
+    assert_eq!(song.shifts_within(&singer), Some((2, 2)));
+    assert_eq!(
+        song.shifts_within(&VocalRange::parse("C3-C4").unwrap()),
+        None
+    );
+    assert_eq!(
+        VocalRange::parse("low-high"),
+        Err(VoiceError::InvalidRange("low-high".into()))
+    );
+}
+
+#[test]
+fn song_range_comes_from_annotations_and_metadata() {
+    let song = |source: &str| resolve(&parse_song(source).unwrap()).song;
+    let annotated = song(
+        "title: \"T\"\nkey: \"Bb\"\n\nVERSE[1]\nLow {pitch: F2}\nPlain\n\nCHORUS\nUp {pitch: Bb3, D4}\n",
+    );
+    let range = SongRange::of(&annotated).unwrap();
+    assert_eq!(range.lowest.pitch.name(range.flats), "F2");
+    assert_eq!(range.lowest.section.as_deref(), Some("Verse 1"));
+    assert_eq!(range.highest.section.as_deref(), Some("Chorus"));
+    assert_eq!(range.range().name(true), "F2-D4");
+
+    let declared = song("title: \"T\"\nrange: \"A2-E4\"\n\nVERSE[1]\nHigh {pitch: E4}\n");
+    let range = SongRange::of(&declared).unwrap();
+    assert_eq!(range.lowest.section, None);
+    assert_eq!(range.highest.section.as_deref(), Some("Verse 1"));
+
+    assert_eq!(
+        SongRange::of(&song("title: \"T\"\n\nVERSE[1]\nNothing\n")),
+        Err(VoiceError::NoNotes)
+    );
+}
+
+#[test]
+fn pitch_annotations_round_trip_through_the_formatter() {
+    let source = "title: \"T\"\n\nVERSE[1]\nHigh {chord: G, pitch: G4, B4, timing: 0:01.00}\n";
+    let song = parse_song(source).unwrap();
+    assert_eq!(song.sections[0].lines[0].pitches, ["G4", "B4"]);
+    assert_eq!(lyrics_dsl::format::format_song(&song), source);
+}
@@ -0,0 +1,102 @@
+use lyrics_dsl::format::format_song;
+use lyrics_dsl::parser::parse_song;
+use lyrics_dsl::xliff::{export, import, unnamed, XliffError};
+
+const SONG: &str = "title: \"Vendor\"
+
+VERSE {id: v1}
+Rock & roll {id: a1, translation.fr: \"Rock et roll\"}
+  + ooh
+Mine alone {id: b2}
+
+CHORUS
+USE tag
+
+MACRO tag
+Hey you {id: c3}
+";
+
+#[test]
+fn lines_become_units_named_by_id_grouped_by_section() {
+    let song = parse_song(SONG).unwrap();
+    let xliff = export(&song, "vendor.lyr", "en", "fr").unwrap();
+    assert!(xliff
+        .contains("<file original=\"vendor.lyr\" source-language=\"en\" target-language=\"fr\""));
+    assert!(xliff.contains("<group resname=\"Verse\">"));
+    assert!(xliff.contains(
+        "<trans-unit id=\"a1\">\n          <source>Rock &amp; roll</source>\n          <target state=\"translated\">Rock et roll</target>"
+    ));
+    // The harmony part has no ID, so it is numbered; the `USE` is left out.
+    assert!(xliff.contains(
+        "<trans-unit id=\"line-2\">\n          <source>ooh</source>\n        </trans-unit>"
+    ));
+    assert!(xliff.contains("<group resname=\"MACRO tag\">\n        <trans-unit id=\"c3\">"));
+    assert!(!xliff.contains("Chorus"));
+    assert_eq!(unnamed(&song), 1);
+    assert!(matches!(
+        export(&song, "vendor.lyr", "en", "not a tag"),
+        Err(XliffError::BadLanguage(_))
+    ));
+}
+
+#[test]
+fn translated_targets_come_back_as_translations_of_their_lines() {
+    let mut song = parse_song(SONG).unwrap();
+    let xliff = r#"<?xml version="1.0"?>
+<xliff version="2.0" xmlns="urn:oasis:names:tc:xliff:document:2.0" srcLang="en" trgLang="fr-CA">
+  <file id="f1">
+    <unit id="a1"><segment><source>Rock &amp; roll</source><target>Rock &amp; <pc id="1">roll</pc></target></segment></unit>
+    <unit id="line-2"><segment><source>ooh</source><target>  ouh
+      ouh </target></segment></unit>
+    <unit id="b2"><segment><source>Mine alone</source><target state="initial"></target></segment></unit>
+    <unit id="c3"><segment><source>Hey you</source><target>H&#233; "toi"</target></segment></unit>
+    <unit id="gone"><segment><source>Cut</source><target>Coupé</target></segment></unit>
+  </file>
+</xliff>"#;
+    let imported = import(&mut song, xliff, None).unwrap();
+    assert_eq!(imported.lang, "fr-CA");
+    assert_eq!(imported.translated, 3);
+    assert_eq!(imported.unknown, ["gone"]);
+
+    let lines = &song.sections[0].lines;
+    assert_eq!(lines[0].translation("fr-CA"), Some("Rock & roll"));
+    assert_eq!(lines[0].translation("fr"), Some("Rock et roll"));
+    assert_eq!(lines[0].harmony[0].translation("fr-ca"), Some("ouh ouh"));
+    assert!(lines[1].translations.is_empty());
+    assert_eq!(
+        song.macros[0].lines[0].translation("fr-CA"),
+        Some("Hé 'toi'")
+    );
+
+    // Importing under the old tag replaces that translation.
+    let imported = import(&mut song, xliff, Some("fr")).unwrap();
+    assert_eq!(imported.translated, 3);
+    let formatted = format_song(&song);
+    assert!(formatted.contains(
+        "Rock & roll {translation.fr-CA: \"Rock & roll\", translation.fr: \"Rock & roll\", id: a1}"
+    ));
+    assert_eq!(format_song(&parse_song(&formatted).unwrap()), formatted);
+    assert!(matches!(
+        import(&mut song, "<xliff/>", Some("fr")),
+        Err(XliffError::NoUnits)
+    ));
+}
+
+#[test]
+fn escaped_lines_go_out_as_they_read_and_come_back_unchanged() {
+    let source = "title: \"Escapes\"\n\nVERSE\nRock \\& roll \\$5 {id: e1, translation.fr: \"Rock & roll 5 $\"}\n";
+    let mut song = parse_song(source).unwrap();
+    let xliff = export(&song, "escapes.lyr", "en", "fr").unwrap();
+    assert!(xliff.contains("<source>Rock &amp; roll $5</source>"));
+    assert!(xliff.contains("<target state=\"translated\">Rock &amp; roll 5 $</target>"));
+
+    import(&mut song, &xliff, None).unwrap();
+    assert_eq!(
+        format_song(&song),
+        format_song(&parse_song(source).unwrap())
+    );
+    assert_eq!(
+        song.sections[0].lines[0].translation("fr"),
+        Some("Rock & roll 5 $")
+    );
+}